@@ -0,0 +1,87 @@
+use actix_web::{web, HttpResponse};
+use serde::Deserialize;
+use std::sync::Arc;
+
+use super::audit::{record_audit, AUDIT_ADDRESS_BOOK_ADD, AUDIT_ADDRESS_BOOK_DELETE};
+use crate::api::middleware::{AuthenticatedUser, ClientIp};
+use crate::db::models::CreateAddressBookEntryRequest;
+use crate::db::repositories::AuditRepository;
+use crate::error::AppResult;
+use crate::services::{AddressBook, Permission};
+
+#[derive(Debug, Deserialize)]
+pub struct AddressBookQuery {
+    pub chain: Option<String>,
+}
+
+/// List address book entries, optionally for one chain
+pub async fn list_address_book(
+    address_book: web::Data<Arc<AddressBook>>,
+    _user: AuthenticatedUser,
+    query: web::Query<AddressBookQuery>,
+) -> AppResult<HttpResponse> {
+    let entries = address_book.list(query.chain.as_deref()).await?;
+
+    Ok(HttpResponse::Ok().json(serde_json::json!({
+        "whitelist_only": address_book.whitelist_only(),
+        "entries": entries
+    })))
+}
+
+/// Add a named recipient
+pub async fn add_address_book_entry(
+    address_book: web::Data<Arc<AddressBook>>,
+    audit: web::Data<Arc<AuditRepository>>,
+    client_ip: ClientIp,
+    user: AuthenticatedUser,
+    request: web::Json<CreateAddressBookEntryRequest>,
+) -> AppResult<HttpResponse> {
+    user.require_permission(Permission::SettingsWrite)?;
+
+    let entry = address_book.add(&request, user.user_id).await?;
+
+    record_audit(
+        &audit,
+        &client_ip,
+        Some(user.user_id),
+        AUDIT_ADDRESS_BOOK_ADD,
+        Some(format!("address_book:{}", entry.id)),
+        serde_json::json!({
+            "label": entry.label,
+            "chain": entry.chain,
+            "address": entry.address
+        }),
+    )
+    .await;
+    Ok(HttpResponse::Created().json(entry))
+}
+
+/// Remove a named recipient
+pub async fn delete_address_book_entry(
+    address_book: web::Data<Arc<AddressBook>>,
+    audit: web::Data<Arc<AuditRepository>>,
+    client_ip: ClientIp,
+    user: AuthenticatedUser,
+    path: web::Path<i32>,
+) -> AppResult<HttpResponse> {
+    user.require_permission(Permission::SettingsWrite)?;
+
+    let entry = address_book.remove(path.into_inner()).await?;
+
+    record_audit(
+        &audit,
+        &client_ip,
+        Some(user.user_id),
+        AUDIT_ADDRESS_BOOK_DELETE,
+        Some(format!("address_book:{}", entry.id)),
+        serde_json::json!({
+            "label": entry.label,
+            "chain": entry.chain,
+            "address": entry.address
+        }),
+    )
+    .await;
+    Ok(HttpResponse::Ok().json(serde_json::json!({
+        "message": "Address book entry deleted"
+    })))
+}
@@ -0,0 +1,182 @@
+use actix_web::{web, HttpResponse};
+use serde::Deserialize;
+use std::sync::Arc;
+
+use super::pagination;
+use super::transfer::parse_date_range;
+use crate::api::middleware::{AuthenticatedUser, ClientIp};
+use crate::db::repositories::{AuditFilter, AuditRepository};
+use crate::error::{AppError, AppResult};
+use crate::services::Permission;
+
+/// Actions written to the audit log
+pub const AUDIT_LOGIN: &str = "login";
+pub const AUDIT_LOGIN_FAILED: &str = "login_failed";
+pub const AUDIT_WALLET_CREATE: &str = "wallet_create";
+pub const AUDIT_WALLET_IMPORT: &str = "wallet_import";
+pub const AUDIT_WALLET_DELETE: &str = "wallet_delete";
+pub const AUDIT_WALLET_ACTIVATE: &str = "wallet_activate";
+pub const AUDIT_KEY_EXPORT: &str = "key_export";
+pub const AUDIT_KEY_EXPORT_DENIED: &str = "key_export_denied";
+pub const AUDIT_KEY_ROTATE: &str = "encryption_key_rotate";
+pub const AUDIT_MESSAGE_SIGN: &str = "message_sign";
+pub const AUDIT_TRANSFER_INITIATE: &str = "transfer_initiate";
+pub const AUDIT_TRANSFER_EXECUTE: &str = "transfer_execute";
+pub const AUDIT_TRANSFER_APPROVE: &str = "transfer_approve";
+pub const AUDIT_TRANSFER_REJECT: &str = "transfer_reject";
+pub const AUDIT_TRANSFER_BATCH: &str = "transfer_batch";
+pub const AUDIT_TRANSFER_BUMP_FEE: &str = "transfer_bump_fee";
+pub const AUDIT_NOTES_CONSOLIDATE: &str = "notes_consolidate";
+pub const AUDIT_SPENDING_LIMIT_SET: &str = "spending_limit_set";
+pub const AUDIT_SPENDING_LIMIT_DELETE: &str = "spending_limit_delete";
+pub const AUDIT_SPENDING_LIMIT_EXCEEDED: &str = "spending_limit_exceeded";
+pub const AUDIT_SPENDING_LIMIT_OVERRIDE: &str = "spending_limit_override";
+pub const AUDIT_ADDRESS_BOOK_ADD: &str = "address_book_add";
+pub const AUDIT_ADDRESS_BOOK_DELETE: &str = "address_book_delete";
+pub const AUDIT_RPC_CONFIG_UPDATE: &str = "rpc_config_update";
+pub const AUDIT_TOKEN_ALLOWLIST_ADD: &str = "token_allowlist_add";
+pub const AUDIT_TOKEN_ALLOWLIST_REMOVE: &str = "token_allowlist_remove";
+pub const AUDIT_TOKEN_METADATA_REFRESH: &str = "token_metadata_refresh";
+pub const AUDIT_TRANSFER_EXPORT: &str = "transfer_export";
+pub const AUDIT_WALLET_KEYS_VERIFY: &str = "wallet_keys_verify";
+
+const MAX_AUDIT_PAGE: i32 = 100;
+
+/// Write an audit entry for a request
+///
+/// `details` must never carry secrets: no private keys, mnemonics, viewing keys,
+/// passwords or memos. A failed write is logged and does not fail the request.
+pub async fn record_audit(
+    audit: &AuditRepository,
+    client_ip: &ClientIp,
+    user_id: Option<i32>,
+    action: &str,
+    resource: Option<String>,
+    details: serde_json::Value,
+) {
+    tracing::info!(action, audit_user_id = ?user_id, resource = ?resource, "audit: {}", action);
+
+    let ip = client_ip.to_string_opt();
+    if let Err(e) = audit
+        .record(user_id, action, resource.as_deref(), &details, ip.as_deref())
+        .await
+    {
+        tracing::warn!("Failed to write audit entry '{}': {}", action, e);
+    }
+}
+
+/// Record a send blocked by its wallet's spending limit before returning the error
+pub async fn audit_limit_exceeded<T>(
+    result: AppResult<T>,
+    audit: &AuditRepository,
+    client_ip: &ClientIp,
+    user: &AuthenticatedUser,
+    resource: String,
+) -> AppResult<T> {
+    if let Err(AppError::SpendingLimitExceeded(reason)) = &result {
+        record_audit(
+            audit,
+            client_ip,
+            Some(user.user_id),
+            AUDIT_SPENDING_LIMIT_EXCEEDED,
+            Some(resource),
+            serde_json::json!({ "reason": reason }),
+        )
+        .await;
+    }
+    result
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AuditQuery {
+    pub user_id: Option<i32>,
+    pub action: Option<String>,
+    /// Earliest `created_at`, RFC 3339 or YYYY-MM-DD
+    pub from: Option<String>,
+    /// Latest `created_at`, RFC 3339 or YYYY-MM-DD (the whole day is included)
+    pub to: Option<String>,
+    pub limit: Option<i32>,
+    pub offset: Option<i32>,
+}
+
+impl AuditQuery {
+    fn page(&self) -> AppResult<(i32, i32)> {
+        pagination(self.limit, self.offset, MAX_AUDIT_PAGE)
+    }
+
+    fn filter(&self) -> AppResult<AuditFilter> {
+        let (from, to) = parse_date_range(self.from.as_deref(), self.to.as_deref())?;
+
+        Ok(AuditFilter {
+            user_id: self.user_id,
+            action: self.action.clone(),
+            from,
+            to,
+        })
+    }
+}
+
+/// List audit entries newest first (admin only)
+pub async fn list_audit_logs(
+    audit: web::Data<Arc<AuditRepository>>,
+    user: AuthenticatedUser,
+    query: web::Query<AuditQuery>,
+) -> AppResult<HttpResponse> {
+    user.require_permission(Permission::AuditRead)?;
+
+    let (limit, offset) = query.page()?;
+    let filter = query.filter()?;
+
+    let entries = audit.list(&filter, limit, offset).await?;
+    let total = audit.count(&filter).await?;
+
+    Ok(HttpResponse::Ok().json(serde_json::json!({
+        "entries": entries,
+        "total": total,
+        "limit": limit,
+        "offset": offset
+    })))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_audit_query_filter() {
+        let query = web::Query::<AuditQuery>::from_query("user_id=1&action=key_export&to=2024-05-07")
+            .unwrap()
+            .into_inner();
+        assert_eq!(query.page().unwrap(), (20, 0));
+
+        let filter = query.filter().unwrap();
+        assert_eq!(filter.user_id, Some(1));
+        assert_eq!(filter.action.as_deref(), Some("key_export"));
+        assert_eq!(filter.to.unwrap().to_rfc3339(), "2024-05-08T00:00:00+00:00");
+
+        let query = web::Query::<AuditQuery>::from_query("limit=500").unwrap().into_inner();
+        assert!(query.page().is_err());
+    }
+
+    #[test]
+    fn test_settings_write_audit_details() {
+        use super::super::settings::{zcash_rpc_audit_details, UpdateZcashRpcConfigRequest};
+
+        let request = UpdateZcashRpcConfigRequest {
+            primary_rpc: "http://zcash:8232".to_string(),
+            fallback_rpcs: None,
+            rpc_user: None,
+            rpc_password: Some("hunter2".to_string()),
+        };
+        let details = zcash_rpc_audit_details(&request);
+        assert_eq!(details["primary_rpc"], "http://zcash:8232");
+        assert_eq!(details["rpc_user_changed"], false);
+        assert_eq!(details["rpc_password_changed"], true);
+        assert!(!details.to_string().contains("hunter2"));
+
+        let query = web::Query::<AuditQuery>::from_query(&format!("action={}", AUDIT_RPC_CONFIG_UPDATE))
+            .unwrap()
+            .into_inner();
+        assert_eq!(query.filter().unwrap().action.as_deref(), Some("rpc_config_update"));
+    }
+}
@@ -1,34 +1,94 @@
-use actix_web::{web, HttpResponse};
+use actix_web::{web, HttpMessage, HttpRequest, HttpResponse};
 use std::sync::Arc;
 
-use crate::api::middleware::AuthenticatedUser;
-use crate::db::models::{ChangePasswordRequest, LoginRequest};
-use crate::error::AppResult;
+use super::audit::{record_audit, AUDIT_LOGIN, AUDIT_LOGIN_FAILED};
+use crate::api::middleware::{AuthenticatedUser, ClientIp};
+use crate::db::models::{ChangePasswordRequest, LoginRequest, RefreshTokenRequest};
+use crate::error::{AppError, AppResult};
+use crate::services::auth_service::Claims;
+use crate::db::repositories::AuditRepository;
 use crate::services::AuthService;
 
 pub async fn login(
     auth_service: web::Data<Arc<AuthService>>,
+    audit: web::Data<Arc<AuditRepository>>,
+    client_ip: ClientIp,
     request: web::Json<LoginRequest>,
 ) -> AppResult<HttpResponse> {
-    let response = auth_service.login(request.into_inner()).await?;
-    Ok(HttpResponse::Ok().json(response))
+    let request = request.into_inner();
+    let username = request.username.clone();
+
+    match auth_service.login(request).await {
+        Ok(response) => {
+            record_audit(&audit, &client_ip, Some(response.user.id), AUDIT_LOGIN, None, serde_json::json!({})).await;
+            Ok(HttpResponse::Ok().json(response))
+        }
+        Err(e) => {
+            if matches!(e, AppError::InvalidCredentials) {
+                record_audit(
+                    &audit,
+                    &client_ip,
+                    None,
+                    AUDIT_LOGIN_FAILED,
+                    None,
+                    serde_json::json!({ "username": username }),
+                )
+                .await;
+            }
+            Err(e)
+        }
+    }
+}
+
+pub async fn refresh_token(
+    auth_service: web::Data<Arc<AuthService>>,
+    request: web::Json<RefreshTokenRequest>,
+) -> AppResult<HttpResponse> {
+    let tokens = auth_service.refresh(&request.refresh_token).await?;
+    Ok(HttpResponse::Ok().json(tokens))
 }
 
-pub async fn logout() -> AppResult<HttpResponse> {
-    // For stateless JWT, logout is handled client-side
+pub async fn logout(
+    auth_service: web::Data<Arc<AuthService>>,
+    req: HttpRequest,
+    request: Option<web::Json<RefreshTokenRequest>>,
+) -> AppResult<HttpResponse> {
+    let claims = req
+        .extensions()
+        .get::<Claims>()
+        .cloned()
+        .ok_or_else(|| AppError::Unauthorized("Missing token claims".to_string()))?;
+
+    let refresh_token = request.as_ref().map(|r| r.refresh_token.as_str());
+    auth_service.logout(&claims, refresh_token).await?;
+
     Ok(HttpResponse::Ok().json(serde_json::json!({"message": "Logged out successfully"})))
 }
 
+pub async fn logout_all(
+    auth_service: web::Data<Arc<AuthService>>,
+    user: AuthenticatedUser,
+) -> AppResult<HttpResponse> {
+    auth_service.logout_all(user.user_id).await?;
+
+    Ok(HttpResponse::Ok().json(serde_json::json!({"message": "All sessions logged out"})))
+}
+
 pub async fn change_password(
     auth_service: web::Data<Arc<AuthService>>,
     user: AuthenticatedUser,
     request: web::Json<ChangePasswordRequest>,
 ) -> AppResult<HttpResponse> {
-    auth_service
+    let tokens = auth_service
         .change_password(user.user_id, &request.old_password, &request.new_password)
         .await?;
 
-    Ok(HttpResponse::Ok().json(serde_json::json!({"message": "Password changed successfully"})))
+    Ok(HttpResponse::Ok().json(serde_json::json!({
+        "message": "Password changed successfully",
+        "token": tokens.token,
+        "refresh_token": tokens.refresh_token,
+        "expires_in": tokens.expires_in
+    })))
 }
 
 pub async fn me(
@@ -1,11 +1,40 @@
+pub mod address_book;
+pub mod audit;
 pub mod auth;
+pub mod health;
 pub mod orchard;
 pub mod settings;
 pub mod transfer;
 pub mod wallet;
 
+pub use address_book::*;
+pub use audit::list_audit_logs;
 pub use auth::*;
+pub use health::{health, metrics, ready};
 pub use orchard::*;
 pub use settings::*;
 pub use transfer::*;
 pub use wallet::*;
+
+use crate::error::{AppError, AppResult};
+
+/// Page size of a list endpoint when the request gives no `limit`
+const DEFAULT_PAGE: i32 = 20;
+
+/// Validate a list request's `limit` (1 to `max`, default 20) and `offset`
+fn pagination(limit: Option<i32>, offset: Option<i32>, max: i32) -> AppResult<(i32, i32)> {
+    let limit = limit.unwrap_or(DEFAULT_PAGE);
+    if !(1..=max).contains(&limit) {
+        return Err(AppError::ValidationError(format!(
+            "limit must be between 1 and {}",
+            max
+        )));
+    }
+
+    let offset = offset.unwrap_or(0);
+    if offset < 0 {
+        return Err(AppError::ValidationError("offset must not be negative".to_string()));
+    }
+
+    Ok((limit, offset))
+}
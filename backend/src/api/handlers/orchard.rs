@@ -3,12 +3,32 @@
 //! Handles API requests for Zcash Orchard shielded transfers.
 
 use actix_web::{web, HttpResponse};
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::broadcast;
 
-use crate::api::middleware::AuthenticatedUser;
+use super::audit::{
+    audit_limit_exceeded, record_audit, AUDIT_NOTES_CONSOLIDATE, AUDIT_TRANSFER_EXECUTE,
+    AUDIT_TRANSFER_EXPORT, AUDIT_TRANSFER_INITIATE,
+};
+use super::pagination;
+use crate::api::middleware::{AuthenticatedUser, ClientIp};
+use crate::blockchain::zcash::orchard::{
+    coin_selection::SelectionStrategy,
+    constants::{MAX_CONSOLIDATION_NOTES, MAX_FEE_ZATOSHIS},
+    memo::{decode_memo, Memo},
+    offline::UnsignedTransferPackage,
+    scanner::ShieldedBalance,
+    transfer,
+    ScanProgress,
+};
+use crate::crypto::units::{format_units, ZEC_DECIMALS};
+use crate::db::repositories::AuditRepository;
 use crate::error::{AppError, AppResult};
-use crate::services::WalletService;
+use crate::services::zcash_history::ZCASH_POOLS;
+use crate::services::{AddressBook, Permission, WalletService};
 
 /// Request to enable Orchard for a wallet
 #[derive(Debug, Deserialize)]
@@ -45,6 +65,18 @@ pub struct ShieldedBalanceResponse {
     pub pool: String,
 }
 
+impl From<ShieldedBalance> for ShieldedBalanceResponse {
+    fn from(balance: ShieldedBalance) -> Self {
+        Self {
+            total_zatoshis: balance.total_zatoshis,
+            spendable_zatoshis: balance.spendable_zatoshis,
+            pending_zatoshis: balance.pending_zatoshis,
+            note_count: balance.note_count,
+            pool: balance.pool.to_string(),
+        }
+    }
+}
+
 /// Combined balance response
 #[derive(Debug, Serialize)]
 pub struct CombinedBalanceResponse {
@@ -52,7 +84,10 @@ pub struct CombinedBalanceResponse {
     pub address: String,
     pub transparent_balance: String,
     pub shielded_balance: Option<ShieldedBalanceResponse>,
-    pub total_zec: f64,
+    /// Sapling notes found by the scan (not spendable through this service)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sapling_balance: Option<ShieldedBalanceResponse>,
+    pub total_zec: String,
 }
 
 /// Scan progress response
@@ -64,10 +99,27 @@ pub struct ScanProgressResponse {
     pub chain_tip_height: u64,
     pub progress_percent: f64,
     pub estimated_seconds_remaining: Option<u64>,
+    pub blocks_per_second: Option<f64>,
     pub is_scanning: bool,
     pub notes_found: u64,
 }
 
+impl From<ScanProgress> for ScanProgressResponse {
+    fn from(progress: ScanProgress) -> Self {
+        Self {
+            chain: progress.chain,
+            scan_type: progress.scan_type,
+            last_scanned_height: progress.last_scanned_height,
+            chain_tip_height: progress.chain_tip_height,
+            progress_percent: progress.progress_percent,
+            estimated_seconds_remaining: progress.estimated_seconds_remaining,
+            blocks_per_second: progress.blocks_per_second,
+            is_scanning: progress.is_scanning,
+            notes_found: progress.notes_found,
+        }
+    }
+}
+
 /// Fund source for transfers
 #[derive(Debug, Clone, Deserialize)]
 #[serde(rename_all = "lowercase")]
@@ -86,6 +138,16 @@ impl Default for FundSource {
     }
 }
 
+impl From<&FundSource> for transfer::FundSource {
+    fn from(source: &FundSource) -> Self {
+        match source {
+            FundSource::Auto => transfer::FundSource::Auto,
+            FundSource::Shielded => transfer::FundSource::Shielded,
+            FundSource::Transparent => transfer::FundSource::Transparent,
+        }
+    }
+}
+
 /// Orchard transfer request
 #[derive(Debug, Deserialize)]
 pub struct OrchardTransferRequest {
@@ -98,6 +160,13 @@ pub struct OrchardTransferRequest {
     pub target_pool: Option<String>,
     #[serde(default)]
     pub fund_source: FundSource,
+    /// UTXO selection when spending transparent funds: largest_first (default),
+    /// smallest_first or minimize_change
+    #[serde(default)]
+    pub coin_selection: SelectionStrategy,
+    /// Explicit confirmation for amounts above the configured threshold
+    #[serde(default)]
+    pub confirm_large: bool,
 }
 
 /// Orchard transfer response
@@ -141,9 +210,7 @@ pub async fn enable_orchard(
     path: web::Path<i32>,
     request: web::Json<EnableOrchardRequest>,
 ) -> AppResult<HttpResponse> {
-    if user.role != "admin" {
-        return Err(AppError::Forbidden("Only admin can enable Orchard".to_string()));
-    }
+    user.require_permission(Permission::WalletManage)?;
 
     let wallet_id = path.into_inner();
 
@@ -192,11 +259,53 @@ pub struct NoteResponse {
     pub id: i32,
     pub nullifier: String,
     pub value_zatoshis: u64,
-    pub value_zec: f64,
+    pub value_zec: String,
     pub block_height: u64,
     pub tx_hash: String,
     pub is_spent: bool,
+    /// Text memo, if the note has one; binary memos are read through the memo endpoint
     pub memo: Option<String>,
+    /// Blocks mined on top of the note's block at the current chain height
+    pub confirmations: u64,
+    pub required_confirmations: u32,
+    pub has_witness: bool,
+    pub spendable: bool,
+    /// Why the note is not spendable yet: `confirmations`, `witness`, `spend_data`
+    /// and/or `reserved`
+    pub blocked_by: Vec<&'static str>,
+    /// End of the reservation held by a transfer being built
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reserved_until: Option<DateTime<Utc>>,
+}
+
+/// Unspent notes of a wallet with their spendability at the current chain height
+async fn unspent_note_responses(wallet_service: &WalletService, wallet_id: i32) -> AppResult<Vec<NoteResponse>> {
+    let (notes, tip) = wallet_service.get_unspent_notes_at_tip(wallet_id).await?;
+    let required_confirmations = wallet_service.min_confirmations();
+    let now = Utc::now();
+
+    Ok(notes
+        .into_iter()
+        .map(|n| {
+            let blocked_by = n.spend_blockers(tip, required_confirmations, now);
+            NoteResponse {
+                confirmations: n.confirmations(tip),
+                required_confirmations,
+                has_witness: n.has_witness(),
+                spendable: blocked_by.is_empty(),
+                blocked_by,
+                reserved_until: n.reserved_until.filter(|_| n.is_reserved(now)),
+                id: n.id,
+                value_zec: format_units(n.value_zatoshis, ZEC_DECIMALS),
+                nullifier: n.nullifier,
+                value_zatoshis: n.value_zatoshis,
+                block_height: n.block_height,
+                tx_hash: n.tx_hash,
+                is_spent: n.is_spent,
+                memo: n.memo.as_deref().and_then(text_memo),
+            }
+        })
+        .collect())
 }
 
 /// Get unspent notes for a wallet
@@ -204,22 +313,73 @@ pub async fn get_unspent_notes(
     wallet_service: web::Data<Arc<WalletService>>,
     path: web::Path<i32>,
 ) -> AppResult<HttpResponse> {
-    let wallet_id = path.into_inner();
-    let notes = wallet_service.get_unspent_notes_from_db(wallet_id).await?;
+    let notes = unspent_note_responses(&wallet_service, path.into_inner()).await?;
+    Ok(HttpResponse::Ok().json(notes))
+}
 
-    let response: Vec<NoteResponse> = notes
-        .into_iter()
-        .map(|n| NoteResponse {
-            id: n.id,
-            nullifier: n.nullifier,
-            value_zatoshis: n.value_zatoshis,
-            value_zec: n.value_zatoshis as f64 / 100_000_000.0,
-            block_height: n.block_height,
-            tx_hash: n.tx_hash,
-            is_spent: n.is_spent,
-            memo: n.memo,
-        })
-        .collect();
+#[derive(Debug, Deserialize)]
+pub struct NoteListQuery {
+    pub wallet_id: i32,
+}
+
+/// Per-note breakdown of a wallet's shielded balance, to see why funds are not spendable
+pub async fn list_orchard_notes(
+    wallet_service: web::Data<Arc<WalletService>>,
+    _user: AuthenticatedUser,
+    query: web::Query<NoteListQuery>,
+) -> AppResult<HttpResponse> {
+    let notes = unspent_note_responses(&wallet_service, query.wallet_id).await?;
+    Ok(HttpResponse::Ok().json(notes))
+}
+
+/// The text of a stored (hex-encoded) memo
+fn text_memo(memo_hex: &str) -> Option<String> {
+    let bytes = hex::decode(memo_hex).ok()?;
+    match decode_memo(&bytes).ok()? {
+        Memo::Text(text) => Some(text),
+        _ => None,
+    }
+}
+
+/// Memo of a received note
+#[derive(Debug, Serialize)]
+pub struct NoteMemoResponse {
+    pub note_id: i32,
+    pub wallet_id: i32,
+    pub tx_hash: String,
+    pub has_memo: bool,
+    pub is_binary: bool,
+    /// UTF-8 memo text with the zero padding removed
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub text: Option<String>,
+    /// Hex of a binary memo, without trailing zero padding
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub data_hex: Option<String>,
+}
+
+/// Get the decoded memo of a note
+pub async fn get_note_memo(
+    wallet_service: web::Data<Arc<WalletService>>,
+    path: web::Path<i32>,
+) -> AppResult<HttpResponse> {
+    let note = wallet_service.get_note_memo(path.into_inner()).await?;
+
+    let has_memo = note.memo != Memo::Empty;
+    let (text, data_hex) = match note.memo {
+        Memo::Empty => (None, None),
+        Memo::Text(text) => (Some(text), None),
+        Memo::Binary(data) => (None, Some(hex::encode(data))),
+    };
+
+    let response = NoteMemoResponse {
+        note_id: note.note_id,
+        wallet_id: note.wallet_id,
+        tx_hash: note.tx_hash,
+        has_memo,
+        is_binary: data_hex.is_some(),
+        text,
+        data_hex,
+    };
 
     Ok(HttpResponse::Ok().json(response))
 }
@@ -236,149 +396,447 @@ pub async fn get_combined_balance(
         wallet_id: balance.wallet_id,
         address: balance.address,
         transparent_balance: balance.transparent_balance,
-        shielded_balance: balance.shielded_balance.map(|b| ShieldedBalanceResponse {
-            total_zatoshis: b.total_zatoshis,
-            spendable_zatoshis: b.spendable_zatoshis,
-            pending_zatoshis: b.pending_zatoshis,
-            note_count: b.note_count,
-            pool: format!("{:?}", b.pool).to_lowercase(),
-        }),
+        shielded_balance: balance.shielded_balance.map(ShieldedBalanceResponse::from),
+        sapling_balance: balance.sapling_balance.map(ShieldedBalanceResponse::from),
         total_zec: balance.total_zec,
     };
 
     Ok(HttpResponse::Ok().json(response))
 }
 
+/// Most history entries returned per page
+const MAX_ACTIVITY_PAGE: i32 = 100;
+
+/// Query for a Zcash wallet's transaction history
+#[derive(Debug, Deserialize)]
+pub struct WalletActivityQuery {
+    /// One of `transparent`, `orchard`, `sapling` (default: all)
+    pub pool: Option<String>,
+    pub limit: Option<i32>,
+    pub offset: Option<i32>,
+}
+
+/// Get a Zcash wallet's transparent and shielded transactions, newest first
+pub async fn get_wallet_transactions(
+    wallet_service: web::Data<Arc<WalletService>>,
+    path: web::Path<i32>,
+    query: web::Query<WalletActivityQuery>,
+) -> AppResult<HttpResponse> {
+    let wallet_id = path.into_inner();
+
+    let pool = query.pool.as_deref().map(str::to_lowercase);
+    if let Some(pool) = &pool {
+        if !ZCASH_POOLS.contains(&pool.as_str()) {
+            return Err(AppError::ValidationError(format!(
+                "Unknown pool '{}', expected one of {}",
+                pool,
+                ZCASH_POOLS.join(", ")
+            )));
+        }
+    }
+
+    let (limit, offset) = pagination(query.limit, query.offset, MAX_ACTIVITY_PAGE)?;
+
+    let (transactions, total) = wallet_service
+        .get_zcash_activity(wallet_id, pool.as_deref(), limit, offset)
+        .await?;
+
+    Ok(HttpResponse::Ok().json(serde_json::json!({
+        "wallet_id": wallet_id,
+        "transactions": transactions,
+        "total": total,
+        "limit": limit,
+        "offset": offset
+    })))
+}
+
 /// Get scan progress
 pub async fn get_scan_progress(
     wallet_service: web::Data<Arc<WalletService>>,
 ) -> AppResult<HttpResponse> {
     let progress = wallet_service.get_scan_progress().await?;
 
-    let response = ScanProgressResponse {
-        chain: progress.chain,
-        scan_type: progress.scan_type,
-        last_scanned_height: progress.last_scanned_height,
-        chain_tip_height: progress.chain_tip_height,
-        progress_percent: progress.progress_percent,
-        estimated_seconds_remaining: progress.estimated_seconds_remaining,
-        is_scanning: progress.is_scanning,
-        notes_found: progress.notes_found,
-    };
+    let response = ScanProgressResponse::from(progress);
 
     Ok(HttpResponse::Ok().json(response))
 }
 
+/// Keep-alive interval for the progress stream; also how quickly a closed
+/// connection is noticed while no sync is running
+const SYNC_STREAM_KEEP_ALIVE: Duration = Duration::from_secs(15);
+
+/// Stream Orchard sync progress as Server-Sent Events
+///
+/// Emits a `progress` event after each scanned batch. Nothing is spawned: the
+/// response body owns the broadcast receiver, so a client disconnect drops it.
+pub async fn stream_sync_progress(
+    wallet_service: web::Data<Arc<WalletService>>,
+) -> AppResult<HttpResponse> {
+    let receiver = wallet_service.subscribe_sync_progress();
+
+    let events = futures::stream::unfold(receiver, |mut receiver| async move {
+        let event = tokio::select! {
+            received = receiver.recv() => match received {
+                Ok(progress) => {
+                    let data = serde_json::to_string(&ScanProgressResponse::from(progress)).ok()?;
+                    format!("event: progress\ndata: {}\n\n", data)
+                }
+                // Slow reader skipped some batches; the next event is current anyway
+                Err(broadcast::error::RecvError::Lagged(_)) => ": lagged\n\n".to_string(),
+                Err(broadcast::error::RecvError::Closed) => return None,
+            },
+            _ = tokio::time::sleep(SYNC_STREAM_KEEP_ALIVE) => ": keep-alive\n\n".to_string(),
+        };
+        Some((Ok::<_, actix_web::Error>(web::Bytes::from(event)), receiver))
+    });
+
+    Ok(HttpResponse::Ok()
+        .content_type("text/event-stream")
+        .insert_header(("Cache-Control", "no-cache"))
+        .streaming(events))
+}
+
 /// Trigger Orchard sync
 pub async fn sync_orchard(
     wallet_service: web::Data<Arc<WalletService>>,
     user: AuthenticatedUser,
 ) -> AppResult<HttpResponse> {
-    if user.role != "admin" {
-        return Err(AppError::Forbidden("Only admin can trigger sync".to_string()));
-    }
+    user.require_permission(Permission::WalletManage)?;
 
     let progress = wallet_service.sync_orchard().await?;
 
-    let response = ScanProgressResponse {
-        chain: progress.chain,
-        scan_type: progress.scan_type,
-        last_scanned_height: progress.last_scanned_height,
-        chain_tip_height: progress.chain_tip_height,
-        progress_percent: progress.progress_percent,
-        estimated_seconds_remaining: progress.estimated_seconds_remaining,
-        is_scanning: progress.is_scanning,
-        notes_found: progress.notes_found,
-    };
+    let response = ScanProgressResponse::from(progress);
 
     Ok(HttpResponse::Ok().json(response))
 }
 
+/// Refresh witnesses and check each note's root against the node's anchor
+pub async fn validate_witnesses(
+    wallet_service: web::Data<Arc<WalletService>>,
+    user: AuthenticatedUser,
+    path: web::Path<i32>,
+) -> AppResult<HttpResponse> {
+    user.require_permission(Permission::WalletManage)?;
+
+    let report = wallet_service.validate_witnesses(path.into_inner()).await?;
+
+    Ok(HttpResponse::Ok().json(report))
+}
+
+/// Per wallet, how many unspent notes lack the witness data needed to spend them
+pub async fn get_witness_health(
+    wallet_service: web::Data<Arc<WalletService>>,
+) -> AppResult<HttpResponse> {
+    let report = wallet_service.witness_health().await?;
+
+    Ok(HttpResponse::Ok().json(report))
+}
+
+/// Refresh a wallet's witnesses to the chain tip
+pub async fn refresh_witnesses(
+    wallet_service: web::Data<Arc<WalletService>>,
+    user: AuthenticatedUser,
+    path: web::Path<i32>,
+) -> AppResult<HttpResponse> {
+    user.require_permission(Permission::WalletManage)?;
+
+    let health = wallet_service.refresh_witnesses(path.into_inner()).await?;
+
+    Ok(HttpResponse::Ok().json(health))
+}
+
+/// Stop the background sync from scanning a wallet
+pub async fn pause_wallet_sync(
+    wallet_service: web::Data<Arc<WalletService>>,
+    user: AuthenticatedUser,
+    path: web::Path<i32>,
+) -> AppResult<HttpResponse> {
+    user.require_permission(Permission::WalletManage)?;
+
+    let wallet_id = path.into_inner();
+    wallet_service.set_wallet_sync_paused(wallet_id, true).await?;
+
+    Ok(HttpResponse::Ok().json(serde_json::json!({ "wallet_id": wallet_id, "paused": true })))
+}
+
+/// Let the background sync scan a paused wallet again
+pub async fn resume_wallet_sync(
+    wallet_service: web::Data<Arc<WalletService>>,
+    user: AuthenticatedUser,
+    path: web::Path<i32>,
+) -> AppResult<HttpResponse> {
+    user.require_permission(Permission::WalletManage)?;
+
+    let wallet_id = path.into_inner();
+    wallet_service.set_wallet_sync_paused(wallet_id, false).await?;
+
+    Ok(HttpResponse::Ok().json(serde_json::json!({ "wallet_id": wallet_id, "paused": false })))
+}
+
+/// Request to rescan a wallet
+#[derive(Debug, Deserialize)]
+pub struct RescanWalletRequest {
+    /// Must be true; the wallet's cached notes are deleted
+    #[serde(default)]
+    pub confirm: bool,
+}
+
+/// Queue deleting a wallet's cached notes and rescanning it from its birthday
+pub async fn rescan_wallet(
+    wallet_service: web::Data<Arc<WalletService>>,
+    user: AuthenticatedUser,
+    path: web::Path<i32>,
+    request: web::Json<RescanWalletRequest>,
+) -> AppResult<HttpResponse> {
+    user.require_permission(Permission::WalletManage)?;
+
+    if !request.confirm {
+        return Err(AppError::ValidationError(
+            "Rescan deletes the wallet's cached notes and rescans from its birthday; resend with confirm=true".to_string(),
+        ));
+    }
+
+    let rescan = wallet_service.rescan_wallet(path.into_inner()).await?;
+
+    Ok(HttpResponse::Accepted().json(rescan))
+}
+
+/// Status of a wallet's latest rescan
+pub async fn get_rescan_status(
+    wallet_service: web::Data<Arc<WalletService>>,
+    _user: AuthenticatedUser,
+    path: web::Path<i32>,
+) -> AppResult<HttpResponse> {
+    let rescan = wallet_service.rescan_status(path.into_inner()).await?;
+
+    Ok(HttpResponse::Ok().json(rescan))
+}
+
+/// Request to consolidate a wallet's notes
+#[derive(Debug, Deserialize)]
+pub struct ConsolidateNotesRequest {
+    /// Most notes to sweep (capped at 20)
+    pub max_notes: Option<usize>,
+}
+
+/// Sweep a wallet's smallest notes into one shielded note
+pub async fn consolidate_notes(
+    wallet_service: web::Data<Arc<WalletService>>,
+    audit: web::Data<Arc<AuditRepository>>,
+    client_ip: ClientIp,
+    user: AuthenticatedUser,
+    path: web::Path<i32>,
+    request: Option<web::Json<ConsolidateNotesRequest>>,
+) -> AppResult<HttpResponse> {
+    user.require_permission(Permission::TransferExecute)?;
+
+    let wallet_id = path.into_inner();
+    let max_notes = request
+        .and_then(|r| r.max_notes)
+        .unwrap_or(MAX_CONSOLIDATION_NOTES);
+    let result = wallet_service
+        .consolidate_notes(wallet_id, max_notes, user.user_id)
+        .await?;
+
+    record_audit(
+        &audit,
+        &client_ip,
+        Some(user.user_id),
+        AUDIT_NOTES_CONSOLIDATE,
+        Some(format!("wallet:{}", wallet_id)),
+        serde_json::json!({
+            "wallet_id": wallet_id,
+            "chain": "zcash",
+            "max_notes": max_notes,
+            "amount_zatoshis": result.amount_zatoshis,
+            "fee_zatoshis": result.fee_zatoshis,
+            "tx_id": result.tx_id
+        }),
+    )
+    .await;
+
+    Ok(HttpResponse::Ok().json(ExecuteTransferResponse {
+        tx_id: result.tx_id,
+        status: format!("{:?}", result.status).to_lowercase(),
+        raw_tx: result.raw_tx,
+        amount_zatoshis: result.amount_zatoshis,
+        fee_zatoshis: result.fee_zatoshis,
+        broadcast: true,
+    }))
+}
+
+/// Fee estimate request, the proposal fields that affect the fee
+#[derive(Debug, Deserialize)]
+pub struct OrchardEstimateRequest {
+    pub wallet_id: i32,
+    pub to_address: String,
+    pub amount: String,
+    pub amount_zatoshis: Option<u64>,
+    #[serde(default)]
+    pub fund_source: FundSource,
+}
+
+/// Fee estimate response
+#[derive(Debug, Serialize)]
+pub struct OrchardEstimateResponse {
+    pub amount_zatoshis: u64,
+    pub fee_zatoshis: u64,
+    pub fee_zec: String,
+    /// shielding, deshielding, shielded or transparent
+    pub operation: String,
+    pub fund_source: String,
+    pub available_zatoshis: u64,
+    pub sufficient: bool,
+}
+
+/// Preview the ZIP-317 fee and funding of an Orchard transfer without building proofs
+pub async fn estimate_orchard_transfer(
+    wallet_service: web::Data<Arc<WalletService>>,
+    _user: AuthenticatedUser,
+    request: web::Json<OrchardEstimateRequest>,
+) -> AppResult<HttpResponse> {
+    let estimate = wallet_service
+        .estimate_privacy_transfer(
+            request.wallet_id,
+            &request.to_address,
+            &request.amount,
+            request.amount_zatoshis,
+            (&request.fund_source).into(),
+        )
+        .await?;
+
+    Ok(HttpResponse::Ok().json(OrchardEstimateResponse {
+        amount_zatoshis: estimate.amount_zatoshis,
+        fee_zatoshis: estimate.fee_zatoshis,
+        fee_zec: format_units(estimate.fee_zatoshis, ZEC_DECIMALS),
+        operation: estimate.operation().to_string(),
+        fund_source: format!("{:?}", estimate.fund_source).to_lowercase(),
+        available_zatoshis: estimate.available_zatoshis,
+        sufficient: estimate.sufficient,
+    }))
+}
+
 /// Transfer proposal response
 #[derive(Debug, Serialize)]
 pub struct TransferProposalResponse {
     pub proposal_id: String,
     pub amount_zatoshis: u64,
-    pub amount_zec: f64,
+    pub amount_zec: String,
     pub fee_zatoshis: u64,
-    pub fee_zec: f64,
+    pub fee_zec: String,
     pub fund_source: String,
     pub is_shielding: bool,
     pub is_deshielding: bool,
     pub to_address: String,
     pub memo: Option<String>,
     pub expiry_height: u64,
+    /// Transfer a second user must approve before the proposal can be executed
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub approval_transfer_id: Option<i32>,
 }
 
 /// Initiate an Orchard transfer
 pub async fn initiate_orchard_transfer(
     wallet_service: web::Data<Arc<WalletService>>,
+    address_book: web::Data<Arc<AddressBook>>,
+    audit: web::Data<Arc<AuditRepository>>,
+    client_ip: ClientIp,
     user: AuthenticatedUser,
     request: web::Json<OrchardTransferRequest>,
 ) -> AppResult<HttpResponse> {
-    if user.role != "admin" {
-        return Err(AppError::Forbidden("Only admin can initiate transfers".to_string()));
-    }
+    user.require_permission(Permission::TransferCreate)?;
 
-    // Convert fund source
-    let fund_source = match request.fund_source {
-        FundSource::Auto => crate::blockchain::zcash::orchard::transfer::FundSource::Auto,
-        FundSource::Shielded => crate::blockchain::zcash::orchard::transfer::FundSource::Shielded,
-        FundSource::Transparent => crate::blockchain::zcash::orchard::transfer::FundSource::Transparent,
-    };
+    let to_address = address_book.resolve_label("zcash", &request.to_address).await?;
 
     // Create transfer proposal
     let proposal = wallet_service
         .create_privacy_transfer_proposal(
-            request.wallet_id,
-            &request.to_address,
-            &request.amount,
-            request.amount_zatoshis, // Pass zatoshis if provided by frontend
-            request.memo.clone(),
-            fund_source,
+            transfer::TransferRequest {
+                wallet_id: request.wallet_id,
+                to_address: to_address.clone(),
+                amount_zec: request.amount.clone(),
+                amount_zatoshis: request.amount_zatoshis, // Pass zatoshis if provided by frontend
+                memo: request.memo.clone(),
+                fund_source: (&request.fund_source).into(),
+            },
+            request.coin_selection,
+            request.confirm_large,
+            user.user_id,
         )
         .await?;
 
     tracing::info!(
         "Orchard transfer proposal created: wallet={}, to={}, amount_zec={}, amount_zatoshis={}, fee={} zatoshis",
         request.wallet_id,
-        request.to_address,
+        to_address,
         request.amount,
         proposal.amount_zatoshis,
         proposal.fee_zatoshis
     );
 
+    // The memo stays out of the audit log, only whether one was attached
+    record_audit(
+        &audit,
+        &client_ip,
+        Some(user.user_id),
+        AUDIT_TRANSFER_INITIATE,
+        Some(format!("proposal:{}", proposal.proposal_id)),
+        serde_json::json!({
+            "wallet_id": request.wallet_id,
+            "chain": "zcash",
+            "to": proposal.to_address,
+            "amount_zatoshis": proposal.amount_zatoshis,
+            "fee_zatoshis": proposal.fee_zatoshis,
+            "has_memo": proposal.memo.is_some()
+        }),
+    )
+    .await;
+
     let response = TransferProposalResponse {
         proposal_id: proposal.proposal_id.clone(),
         amount_zatoshis: proposal.amount_zatoshis,
-        amount_zec: proposal.amount_zatoshis as f64 / 100_000_000.0,
+        amount_zec: format_units(proposal.amount_zatoshis, ZEC_DECIMALS),
         fee_zatoshis: proposal.fee_zatoshis,
-        fee_zec: proposal.fee_zatoshis as f64 / 100_000_000.0,
+        fee_zec: format_units(proposal.fee_zatoshis, ZEC_DECIMALS),
         fund_source: format!("{:?}", proposal.fund_source).to_lowercase(),
         is_shielding: proposal.is_shielding,
         is_deshielding: proposal.is_deshielding,
         to_address: proposal.to_address.clone(),
         memo: proposal.memo.clone(),
         expiry_height: proposal.expiry_height,
+        approval_transfer_id: wallet_service.proposal_approval_transfer(&proposal.proposal_id),
     };
 
     Ok(HttpResponse::Ok().json(response))
 }
 
 /// Execute transfer request
-#[derive(Debug, Deserialize)]
+///
+/// The proposal itself is kept server-side, so the body is optional. Older clients
+/// that send back the whole proposal still work: fields other than these are ignored.
+#[derive(Debug, Default, Deserialize)]
 pub struct ExecuteTransferRequest {
-    pub wallet_id: i32,
-    pub proposal_id: String,
-    pub amount_zatoshis: u64,
-    pub fee_zatoshis: u64,
-    pub to_address: String,
-    pub memo: Option<String>,
-    pub fund_source: String,
-    pub is_shielding: bool,
+    /// Checked against the wallet the proposal was created for when given
+    pub wallet_id: Option<i32>,
+    /// Checked against the path when given
+    pub proposal_id: Option<String>,
+    /// Build and sign without broadcasting; the signed `raw_tx` is returned
     #[serde(default)]
-    pub is_deshielding: bool,
-    pub expiry_height: u64,
+    pub dry_run: bool,
+}
+
+impl ExecuteTransferRequest {
+    fn check_matches(&self, proposal_id: &str, wallet_id: i32) -> AppResult<()> {
+        if self.proposal_id.as_deref().is_some_and(|id| id != proposal_id) {
+            return Err(AppError::ValidationError("Proposal ID mismatch".to_string()));
+        }
+        if self.wallet_id.is_some_and(|id| id != wallet_id) {
+            return Err(AppError::ValidationError(
+                "Proposal was created for a different wallet".to_string(),
+            ));
+        }
+        Ok(())
+    }
 }
 
 /// Execute transfer response
@@ -389,49 +847,47 @@ pub struct ExecuteTransferResponse {
     pub raw_tx: Option<String>,
     pub amount_zatoshis: u64,
     pub fee_zatoshis: u64,
+    /// False when the transaction was only signed, not sent to any node
+    pub broadcast: bool,
 }
 
 /// Execute a pending Orchard transfer
 pub async fn execute_orchard_transfer(
     wallet_service: web::Data<Arc<WalletService>>,
+    address_book: web::Data<Arc<AddressBook>>,
+    audit: web::Data<Arc<AuditRepository>>,
+    client_ip: ClientIp,
     user: AuthenticatedUser,
     path: web::Path<String>,
     request: Option<web::Json<ExecuteTransferRequest>>,
 ) -> AppResult<HttpResponse> {
-    if user.role != "admin" {
-        return Err(AppError::Forbidden("Only admin can execute transfers".to_string()));
-    }
+    user.require_permission(Permission::TransferExecute)?;
 
     let proposal_id = path.into_inner();
+    let req = request.map(|r| r.into_inner()).unwrap_or_default();
 
-    // Get the request body
-    let req = request.ok_or_else(|| {
-        AppError::ValidationError("Request body required for execute".to_string())
-    })?;
-
-    // Validate proposal ID matches
-    if req.proposal_id != proposal_id {
-        return Err(AppError::ValidationError(
-            "Proposal ID mismatch".to_string(),
-        ));
-    }
+    // The proposal is the one stored at initiate, and only its creator may execute it
+    let (wallet_id, proposal) = wallet_service.find_privacy_proposal(&proposal_id, user.user_id)?;
+    req.check_matches(&proposal_id, wallet_id)?;
+    // The recipient may have been removed from the address book since the proposal
+    address_book.ensure_listed("zcash", &proposal.to_address).await?;
 
     tracing::info!(
         "Executing Orchard transfer: proposal={}, amount_zatoshis={}, fee_zatoshis={}, is_shielding={}, is_deshielding={}",
         proposal_id,
-        req.amount_zatoshis,
-        req.fee_zatoshis,
-        req.is_shielding,
-        req.is_deshielding
+        proposal.amount_zatoshis,
+        proposal.fee_zatoshis,
+        proposal.is_shielding,
+        proposal.is_deshielding
     );
 
     // CRITICAL SAFETY CHECK: Prevent zero-value transactions
     // A zero-value transaction would cause all input funds to become miner fees
-    if req.amount_zatoshis == 0 {
+    if proposal.amount_zatoshis == 0 {
         tracing::error!(
             "BLOCKED: Attempted to execute transfer with amount_zatoshis=0! proposal={}, to={}",
             proposal_id,
-            req.to_address
+            proposal.to_address
         );
         return Err(AppError::ValidationError(
             "Transfer amount cannot be zero. This would result in complete fund loss.".to_string(),
@@ -440,67 +896,48 @@ pub async fn execute_orchard_transfer(
 
     // Additional sanity check: amount should be reasonable (at least 1000 zatoshis = 0.00001 ZEC)
     const MIN_TRANSFER_ZATOSHIS: u64 = 1000;
-    if req.amount_zatoshis < MIN_TRANSFER_ZATOSHIS {
+    if proposal.amount_zatoshis < MIN_TRANSFER_ZATOSHIS {
         tracing::warn!(
             "Transfer amount very small: {} zatoshis for proposal {}",
-            req.amount_zatoshis,
+            proposal.amount_zatoshis,
             proposal_id
         );
     }
 
     // Safety check: fee should not exceed 0.001 ZEC (100,000 zatoshis)
     // ZIP-317 fees for shielding with change can be up to 15,000-20,000 zatoshis
-    const MAX_FEE_ZATOSHIS: u64 = 100_000; // 0.001 ZEC - reasonable upper limit
-    if req.fee_zatoshis > MAX_FEE_ZATOSHIS {
+    if proposal.fee_zatoshis > MAX_FEE_ZATOSHIS {
         tracing::error!(
             "BLOCKED: Fee ({} zatoshis) exceeds maximum allowed ({} zatoshis)! proposal={}",
-            req.fee_zatoshis,
+            proposal.fee_zatoshis,
             MAX_FEE_ZATOSHIS,
             proposal_id
         );
         return Err(AppError::ValidationError(
             format!(
                 "Fee ({} zatoshis = {} ZEC) exceeds maximum allowed (0.001 ZEC). This indicates a configuration error.",
-                req.fee_zatoshis,
-                req.fee_zatoshis as f64 / 100_000_000.0
+                proposal.fee_zatoshis,
+                format_units(proposal.fee_zatoshis, ZEC_DECIMALS)
             ),
         ));
     }
 
     // Warning if fee seems high but still acceptable
-    if req.fee_zatoshis > 20_000 {
+    if proposal.fee_zatoshis > 20_000 {
         tracing::warn!(
             "Fee is higher than typical: {} zatoshis ({} ZEC) for proposal {}",
-            req.fee_zatoshis,
-            req.fee_zatoshis as f64 / 100_000_000.0,
+            proposal.fee_zatoshis,
+            format_units(proposal.fee_zatoshis, ZEC_DECIMALS),
             proposal_id
         );
     }
 
-    // Convert fund source
-    let fund_source = match req.fund_source.as_str() {
-        "shielded" => crate::blockchain::zcash::orchard::transfer::FundSource::Shielded,
-        "transparent" => crate::blockchain::zcash::orchard::transfer::FundSource::Transparent,
-        _ => crate::blockchain::zcash::orchard::transfer::FundSource::Auto,
-    };
-
-    // Reconstruct proposal
-    let proposal = crate::blockchain::zcash::orchard::transfer::TransferProposal {
-        proposal_id: req.proposal_id.clone(),
-        amount_zatoshis: req.amount_zatoshis,
-        fee_zatoshis: req.fee_zatoshis,
-        fund_source,
-        is_shielding: req.is_shielding,
-        is_deshielding: req.is_deshielding,
-        to_address: req.to_address.clone(),
-        memo: req.memo.clone(),
-        expiry_height: req.expiry_height,
-    };
-
     // Execute the transfer
     let result = wallet_service
-        .execute_privacy_transfer(req.wallet_id, &proposal)
-        .await?;
+        .execute_privacy_transfer(wallet_id, &proposal, req.dry_run, user.user_id)
+        .await;
+    let resource = format!("proposal:{}", proposal_id);
+    let result = audit_limit_exceeded(result, &audit, &client_ip, &user, resource).await?;
 
     tracing::info!(
         "Orchard transfer executed: proposal={}, tx_id={}, status={:?}",
@@ -509,13 +946,165 @@ pub async fn execute_orchard_transfer(
         result.status
     );
 
+    record_audit(
+        &audit,
+        &client_ip,
+        Some(user.user_id),
+        AUDIT_TRANSFER_EXECUTE,
+        Some(format!("proposal:{}", proposal_id)),
+        serde_json::json!({
+            "wallet_id": wallet_id,
+            "chain": "zcash",
+            "to": proposal.to_address,
+            "amount_zatoshis": result.amount_zatoshis,
+            "fee_zatoshis": result.fee_zatoshis,
+            "tx_id": result.tx_id,
+            "has_memo": proposal.memo.is_some(),
+            "dry_run": req.dry_run
+        }),
+    )
+    .await;
+
     let response = ExecuteTransferResponse {
         tx_id: result.tx_id,
         status: format!("{:?}", result.status).to_lowercase(),
         raw_tx: result.raw_tx,
         amount_zatoshis: result.amount_zatoshis,
         fee_zatoshis: result.fee_zatoshis,
+        broadcast: !req.dry_run,
     };
 
     Ok(HttpResponse::Ok().json(response))
 }
+
+/// Export a proposal as an unsigned package for an air-gapped signer
+pub async fn export_orchard_transfer(
+    wallet_service: web::Data<Arc<WalletService>>,
+    audit: web::Data<Arc<AuditRepository>>,
+    client_ip: ClientIp,
+    user: AuthenticatedUser,
+    path: web::Path<String>,
+    request: Option<web::Json<ExecuteTransferRequest>>,
+) -> AppResult<HttpResponse> {
+    user.require_permission(Permission::TransferCreate)?;
+
+    let proposal_id = path.into_inner();
+    let (wallet_id, proposal) = wallet_service.find_privacy_proposal(&proposal_id, user.user_id)?;
+    if let Some(request) = request {
+        request.check_matches(&proposal_id, wallet_id)?;
+    }
+
+    let package = wallet_service
+        .export_unsigned_transfer(wallet_id, proposal)
+        .await?;
+
+    record_audit(
+        &audit,
+        &client_ip,
+        Some(user.user_id),
+        AUDIT_TRANSFER_EXPORT,
+        Some(format!("proposal:{}", proposal_id)),
+        serde_json::json!({
+            "wallet_id": wallet_id,
+            "to": package.proposal.to_address,
+            "amount_zatoshis": package.proposal.amount_zatoshis,
+            "fee_zatoshis": package.proposal.fee_zatoshis,
+            "chain": "zcash"
+        }),
+    )
+    .await;
+
+    Ok(HttpResponse::Ok().json(package))
+}
+
+/// Request to sign an exported package on the instance holding the key
+#[derive(Debug, Deserialize)]
+pub struct SignPackageRequest {
+    pub wallet_id: i32,
+    pub package: UnsignedTransferPackage,
+}
+
+/// Sign an unsigned package offline; the raw transaction is returned, not broadcast
+///
+/// Only the package of a live proposal the caller created is signed.
+pub async fn sign_orchard_package(
+    wallet_service: web::Data<Arc<WalletService>>,
+    address_book: web::Data<Arc<AddressBook>>,
+    audit: web::Data<Arc<AuditRepository>>,
+    client_ip: ClientIp,
+    user: AuthenticatedUser,
+    request: web::Json<SignPackageRequest>,
+) -> AppResult<HttpResponse> {
+    user.require_permission(Permission::TransferExecute)?;
+
+    // A signed package is broadcast as is, so the whitelist is checked before signing
+    address_book
+        .ensure_listed("zcash", &request.package.proposal.to_address)
+        .await?;
+
+    let result = wallet_service
+        .sign_offline_package(request.wallet_id, &request.package, user.user_id)
+        .await;
+    let resource = format!("proposal:{}", request.package.proposal.proposal_id);
+    let result = audit_limit_exceeded(result, &audit, &client_ip, &user, resource).await?;
+
+    Ok(HttpResponse::Ok().json(ExecuteTransferResponse {
+        tx_id: result.tx_id,
+        status: format!("{:?}", result.status).to_lowercase(),
+        raw_tx: result.raw_tx,
+        amount_zatoshis: result.amount_zatoshis,
+        fee_zatoshis: result.fee_zatoshis,
+        broadcast: false,
+    }))
+}
+
+/// Request to broadcast a transaction signed offline
+#[derive(Debug, Deserialize)]
+pub struct BroadcastSignedRequest {
+    pub wallet_id: i32,
+    /// Proposal the transaction was signed for
+    pub proposal_id: String,
+    pub raw_tx: String,
+}
+
+/// Broadcast a transaction produced by `sign_orchard_package` for the same proposal
+pub async fn broadcast_orchard_transfer(
+    wallet_service: web::Data<Arc<WalletService>>,
+    audit: web::Data<Arc<AuditRepository>>,
+    client_ip: ClientIp,
+    user: AuthenticatedUser,
+    request: web::Json<BroadcastSignedRequest>,
+) -> AppResult<HttpResponse> {
+    user.require_permission(Permission::TransferExecute)?;
+
+    let (_, proposal) = wallet_service.find_privacy_proposal(&request.proposal_id, user.user_id)?;
+    let result = wallet_service
+        .broadcast_signed_transfer(request.wallet_id, &request.proposal_id, &request.raw_tx, user.user_id)
+        .await;
+    let resource = format!("proposal:{}", request.proposal_id);
+    let result = audit_limit_exceeded(result, &audit, &client_ip, &user, resource).await?;
+
+    record_audit(
+        &audit,
+        &client_ip,
+        Some(user.user_id),
+        AUDIT_TRANSFER_EXECUTE,
+        Some(format!("proposal:{}", request.proposal_id)),
+        serde_json::json!({
+            "wallet_id": request.wallet_id,
+            "chain": "zcash",
+            "to": proposal.to_address,
+            "amount_zatoshis": result.amount_zatoshis,
+            "fee_zatoshis": result.fee_zatoshis,
+            "tx_id": result.tx_id,
+            "has_memo": proposal.memo.is_some(),
+            "offline": true
+        }),
+    )
+    .await;
+
+    Ok(HttpResponse::Ok().json(serde_json::json!({
+        "tx_id": result.tx_id,
+        "status": "submitted"
+    })))
+}
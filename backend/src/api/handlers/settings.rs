@@ -2,14 +2,32 @@ use actix_web::{web, HttpResponse};
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
 
-use crate::api::middleware::AuthenticatedUser;
-use crate::blockchain::ethereum::EthereumClient;
-use crate::db::repositories::SettingsRepository;
+use super::audit::{
+    record_audit, AUDIT_RPC_CONFIG_UPDATE, AUDIT_TOKEN_ALLOWLIST_ADD, AUDIT_TOKEN_ALLOWLIST_REMOVE,
+};
+use crate::api::middleware::{AuthenticatedUser, ClientIp};
+use crate::blockchain::ethereum::{EndpointStatus, EthereumClient};
+use crate::blockchain::traits::ChainClient;
+use crate::blockchain::zcash::ZcashClient;
+use crate::blockchain::ChainRegistry;
+use crate::config::{SecurityConfig, ZcashConfig};
+use crate::db::repositories::settings_repo::ZCASH_RPC_PASSWORD_KEY;
+use crate::db::repositories::{AuditRepository, SettingsRepository};
 use crate::error::{AppError, AppResult};
+use crate::services::Permission;
 
 // Database keys for RPC settings
 const RPC_PRIMARY_KEY: &str = "rpc_primary";
 const RPC_FALLBACKS_KEY: &str = "rpc_fallbacks";
+const ZCASH_RPC_PRIMARY_KEY: &str = "zcash_rpc_primary";
+const ZCASH_RPC_FALLBACKS_KEY: &str = "zcash_rpc_fallbacks";
+const ZCASH_RPC_USER_KEY: &str = "zcash_rpc_user";
+/// Followed by the chain key, e.g. `token_allowlist:ethereum`
+const TOKEN_ALLOWLIST_KEY_PREFIX: &str = "token_allowlist:";
+
+fn token_allowlist_key(chain: &str) -> String {
+    format!("{}{}", TOKEN_ALLOWLIST_KEY_PREFIX, chain)
+}
 
 /// Preset RPC providers
 #[derive(Debug, Clone, Serialize)]
@@ -28,6 +46,15 @@ pub struct RpcConfig {
     pub fallback_rpcs: Vec<String>,
 }
 
+/// Current RPC configuration with the endpoint actually in use and per-endpoint health
+#[derive(Debug, Clone, Serialize)]
+pub struct RpcStatusResponse {
+    #[serde(flatten)]
+    pub config: RpcConfig,
+    pub active_rpc: Option<String>,
+    pub endpoints: Vec<EndpointStatus>,
+}
+
 /// Load RPC configuration from database (for startup)
 pub async fn load_rpc_config_from_db(
     settings_repo: &SettingsRepository,
@@ -57,6 +84,52 @@ pub async fn load_rpc_config_from_db(
     }
 }
 
+/// Overlay saved Zcash RPC settings on the .env defaults (for startup)
+pub async fn load_zcash_rpc_config_from_db(
+    settings_repo: &SettingsRepository,
+    defaults: &ZcashConfig,
+    keys: &SecurityConfig,
+) -> ZcashConfig {
+    let mut config = defaults.clone();
+    let saved = |key: &'static str| async move { settings_repo.get(key).await.ok().flatten() };
+
+    if let Some(primary_rpc) = saved(ZCASH_RPC_PRIMARY_KEY).await {
+        config.rpc_url = primary_rpc;
+    }
+    if let Some(fallbacks) = saved(ZCASH_RPC_FALLBACKS_KEY).await.and_then(|v| serde_json::from_str(&v).ok()) {
+        config.fallback_rpcs = fallbacks;
+    }
+    if let Some(user) = saved(ZCASH_RPC_USER_KEY).await {
+        config.rpc_user = Some(user).filter(|u| !u.is_empty());
+    }
+    if let Some(encrypted) = saved(ZCASH_RPC_PASSWORD_KEY).await {
+        match keys.decrypt(&encrypted) {
+            Ok(password) => config.rpc_password = Some(password).filter(|p| !p.is_empty()),
+            Err(e) => tracing::warn!("Ignoring saved Zcash RPC password: {}", e),
+        }
+    }
+
+    tracing::info!("Loaded Zcash RPC config: {}", config.rpc_url);
+    config
+}
+
+/// Apply a chain's saved token allowlist (for startup)
+pub async fn load_token_allowlist_from_db(settings_repo: &SettingsRepository, client: &dyn ChainClient) {
+    let saved: Option<Vec<String>> = settings_repo
+        .get(&token_allowlist_key(client.chain_id()))
+        .await
+        .ok()
+        .flatten()
+        .and_then(|v| serde_json::from_str(&v).ok());
+
+    if let Some(contracts) = saved {
+        match client.set_token_allowlist(&contracts).await {
+            Ok(tokens) => tracing::info!("Loaded {} allowlisted tokens for {}", tokens.len(), client.chain_id()),
+            Err(e) => tracing::warn!("Ignoring saved token allowlist for {}: {}", client.chain_id(), e),
+        }
+    }
+}
+
 /// Get list of preset RPC providers
 pub async fn get_rpc_presets() -> AppResult<HttpResponse> {
     let presets = vec![
@@ -136,9 +209,13 @@ pub async fn get_rpc_config(
         .and_then(|v| serde_json::from_str(&v).ok())
         .unwrap_or_default();
 
-    Ok(HttpResponse::Ok().json(RpcConfig {
-        primary_rpc: current_rpc,
-        fallback_rpcs,
+    Ok(HttpResponse::Ok().json(RpcStatusResponse {
+        config: RpcConfig {
+            primary_rpc: current_rpc,
+            fallback_rpcs,
+        },
+        active_rpc: eth_client.get_active_rpc(),
+        endpoints: eth_client.get_rpc_health().await,
     }))
 }
 
@@ -152,13 +229,12 @@ pub struct UpdateRpcConfigRequest {
 pub async fn update_rpc_config(
     settings_repo: web::Data<Arc<SettingsRepository>>,
     eth_client: web::Data<Arc<EthereumClient>>,
+    audit: web::Data<Arc<AuditRepository>>,
+    client_ip: ClientIp,
     user: AuthenticatedUser,
     request: web::Json<UpdateRpcConfigRequest>,
 ) -> AppResult<HttpResponse> {
-    // Only admin can update RPC config
-    if user.role != "admin" {
-        return Err(AppError::Forbidden("Only admin can update RPC configuration".to_string()));
-    }
+    user.require_permission(Permission::SettingsWrite)?;
 
     // Validate URL format
     if !request.primary_rpc.starts_with("http://") && !request.primary_rpc.starts_with("https://") {
@@ -180,6 +256,15 @@ pub async fn update_rpc_config(
     }
 
     tracing::info!("RPC configuration saved to database and applied: {}", request.primary_rpc);
+    record_audit(
+        &audit,
+        &client_ip,
+        Some(user.user_id),
+        AUDIT_RPC_CONFIG_UPDATE,
+        Some("chain:ethereum".to_string()),
+        serde_json::json!({ "primary_rpc": request.primary_rpc, "fallback_rpcs": request.fallback_rpcs }),
+    )
+    .await;
 
     Ok(HttpResponse::Ok().json(serde_json::json!({
         "message": "RPC configuration updated and applied immediately.",
@@ -187,6 +272,223 @@ pub async fn update_rpc_config(
     })))
 }
 
+/// Zcash RPC configuration with credentials masked
+#[derive(Debug, Serialize)]
+pub struct ZcashRpcConfigResponse {
+    pub primary_rpc: String,
+    pub fallback_rpcs: Vec<String>,
+    pub rpc_user: Option<String>,
+    pub rpc_password: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UpdateZcashRpcConfigRequest {
+    pub primary_rpc: String,
+    pub fallback_rpcs: Option<Vec<String>>,
+    /// Omit to keep the current value, empty string to clear
+    pub rpc_user: Option<String>,
+    /// Omit to keep the current value, empty string to clear
+    pub rpc_password: Option<String>,
+}
+
+const MASKED_SECRET: &str = "********";
+
+async fn zcash_rpc_config_response(zcash_client: &ZcashClient) -> ZcashRpcConfigResponse {
+    let settings = zcash_client.rpc_settings().await;
+    ZcashRpcConfigResponse {
+        primary_rpc: settings.primary_rpc,
+        fallback_rpcs: settings.fallback_rpcs,
+        rpc_user: settings.rpc_user.map(|_| MASKED_SECRET.to_string()),
+        rpc_password: settings.rpc_password.map(|_| MASKED_SECRET.to_string()),
+    }
+}
+
+/// Get current Zcash RPC configuration
+pub async fn get_zcash_rpc_config(
+    zcash_client: web::Data<Arc<ZcashClient>>,
+) -> AppResult<HttpResponse> {
+    Ok(HttpResponse::Ok().json(zcash_rpc_config_response(&zcash_client).await))
+}
+
+/// Update Zcash RPC configuration; the Orchard sync picks it up on its next cycle
+pub async fn update_zcash_rpc_config(
+    settings_repo: web::Data<Arc<SettingsRepository>>,
+    zcash_client: web::Data<Arc<ZcashClient>>,
+    keys: web::Data<SecurityConfig>,
+    audit: web::Data<Arc<AuditRepository>>,
+    client_ip: ClientIp,
+    user: AuthenticatedUser,
+    request: web::Json<UpdateZcashRpcConfigRequest>,
+) -> AppResult<HttpResponse> {
+    user.require_permission(Permission::SettingsWrite)?;
+
+    let is_http = |url: &str| url.starts_with("http://") || url.starts_with("https://");
+    if !is_http(&request.primary_rpc) {
+        return Err(AppError::ValidationError("Invalid RPC URL format".to_string()));
+    }
+    if let Some(fallbacks) = &request.fallback_rpcs {
+        if !fallbacks.iter().all(|url| is_http(url)) {
+            return Err(AppError::ValidationError("Invalid fallback RPC URL format".to_string()));
+        }
+    }
+
+    let request = request.into_inner();
+    zcash_client
+        .update_rpc(
+            request.primary_rpc.clone(),
+            request.fallback_rpcs.clone(),
+            request.rpc_user.clone(),
+            request.rpc_password.clone(),
+        )
+        .await?;
+
+    // Save to database for persistence across restarts
+    settings_repo.set(ZCASH_RPC_PRIMARY_KEY, &request.primary_rpc).await?;
+    if let Some(fallbacks) = &request.fallback_rpcs {
+        let fallbacks_json = serde_json::to_string(fallbacks)
+            .map_err(|e| AppError::InternalError(format!("Failed to serialize fallbacks: {}", e)))?;
+        settings_repo.set(ZCASH_RPC_FALLBACKS_KEY, &fallbacks_json).await?;
+    }
+    if let Some(rpc_user) = &request.rpc_user {
+        settings_repo.set(ZCASH_RPC_USER_KEY, rpc_user).await?;
+    }
+    if let Some(rpc_password) = &request.rpc_password {
+        let encrypted = crate::crypto::encrypt(rpc_password, &keys.encryption_key)?;
+        settings_repo.set(ZCASH_RPC_PASSWORD_KEY, &encrypted).await?;
+    }
+
+    tracing::info!("Zcash RPC configuration saved to database and applied: {}", request.primary_rpc);
+    record_audit(
+        &audit,
+        &client_ip,
+        Some(user.user_id),
+        AUDIT_RPC_CONFIG_UPDATE,
+        Some("chain:zcash".to_string()),
+        zcash_rpc_audit_details(&request),
+    )
+    .await;
+
+    Ok(HttpResponse::Ok().json(zcash_rpc_config_response(&zcash_client).await))
+}
+
+/// What a Zcash RPC update changed, for the audit log; credentials are never recorded
+pub(super) fn zcash_rpc_audit_details(request: &UpdateZcashRpcConfigRequest) -> serde_json::Value {
+    serde_json::json!({
+        "primary_rpc": request.primary_rpc,
+        "fallback_rpcs": request.fallback_rpcs,
+        "rpc_user_changed": request.rpc_user.is_some(),
+        "rpc_password_changed": request.rpc_password.is_some(),
+    })
+}
+
+#[derive(Debug, Serialize)]
+pub struct TokenAllowlistResponse {
+    pub chain: String,
+    /// Empty means the built-in token list is reported
+    pub tokens: Vec<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AddAllowlistTokenRequest {
+    pub contract_address: String,
+}
+
+/// Apply the allowlist to the chain client, then persist what it accepted
+async fn save_token_allowlist(
+    settings_repo: &SettingsRepository,
+    client: &dyn ChainClient,
+    contracts: &[String],
+) -> AppResult<TokenAllowlistResponse> {
+    let tokens = client.set_token_allowlist(contracts).await?;
+    let tokens_json = serde_json::to_string(&tokens)
+        .map_err(|e| AppError::InternalError(format!("Failed to serialize token allowlist: {}", e)))?;
+    settings_repo.set(&token_allowlist_key(client.chain_id()), &tokens_json).await?;
+
+    Ok(TokenAllowlistResponse {
+        chain: client.chain_id().to_string(),
+        tokens,
+    })
+}
+
+/// Get the token contracts whose balances are reported for a chain
+pub async fn get_token_allowlist(
+    chain_registry: web::Data<Arc<ChainRegistry>>,
+    _user: AuthenticatedUser,
+    path: web::Path<String>,
+) -> AppResult<HttpResponse> {
+    let client = chain_registry.get(&path)?;
+
+    Ok(HttpResponse::Ok().json(TokenAllowlistResponse {
+        chain: client.chain_id().to_string(),
+        tokens: client.token_allowlist().await,
+    }))
+}
+
+/// Add a token contract to a chain's allowlist
+pub async fn add_allowlist_token(
+    settings_repo: web::Data<Arc<SettingsRepository>>,
+    chain_registry: web::Data<Arc<ChainRegistry>>,
+    audit: web::Data<Arc<AuditRepository>>,
+    client_ip: ClientIp,
+    user: AuthenticatedUser,
+    path: web::Path<String>,
+    request: web::Json<AddAllowlistTokenRequest>,
+) -> AppResult<HttpResponse> {
+    user.require_permission(Permission::SettingsWrite)?;
+    let client = chain_registry.get(&path)?;
+
+    let mut contracts = client.token_allowlist().await;
+    contracts.push(request.contract_address.clone());
+
+    let response = save_token_allowlist(&settings_repo, client.as_ref(), &contracts).await?;
+    record_audit(
+        &audit,
+        &client_ip,
+        Some(user.user_id),
+        AUDIT_TOKEN_ALLOWLIST_ADD,
+        Some(format!("chain:{}", response.chain)),
+        serde_json::json!({ "contract_address": request.contract_address }),
+    )
+    .await;
+    Ok(HttpResponse::Ok().json(response))
+}
+
+/// Remove a token contract from a chain's allowlist
+pub async fn remove_allowlist_token(
+    settings_repo: web::Data<Arc<SettingsRepository>>,
+    chain_registry: web::Data<Arc<ChainRegistry>>,
+    audit: web::Data<Arc<AuditRepository>>,
+    client_ip: ClientIp,
+    user: AuthenticatedUser,
+    path: web::Path<(String, String)>,
+) -> AppResult<HttpResponse> {
+    user.require_permission(Permission::SettingsWrite)?;
+    let (chain, contract_address) = path.into_inner();
+    let client = chain_registry.get(&chain)?;
+
+    let mut contracts = client.token_allowlist().await;
+    let before = contracts.len();
+    contracts.retain(|c| !c.eq_ignore_ascii_case(contract_address.trim()));
+    if contracts.len() == before {
+        return Err(AppError::NotFound(format!(
+            "Token {} is not on the {} allowlist",
+            contract_address, chain
+        )));
+    }
+
+    let response = save_token_allowlist(&settings_repo, client.as_ref(), &contracts).await?;
+    record_audit(
+        &audit,
+        &client_ip,
+        Some(user.user_id),
+        AUDIT_TOKEN_ALLOWLIST_REMOVE,
+        Some(format!("chain:{}", chain)),
+        serde_json::json!({ "contract_address": contract_address }),
+    )
+    .await;
+    Ok(HttpResponse::Ok().json(response))
+}
+
 #[derive(Debug, Deserialize)]
 pub struct TestRpcRequest {
     pub rpc_url: String,
@@ -1,43 +1,326 @@
-use actix_web::{web, HttpResponse};
+use actix_web::{web, HttpRequest, HttpResponse};
+use chrono::{DateTime, Duration, NaiveDate, NaiveTime, Utc};
 use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
 
-use crate::api::middleware::AuthenticatedUser;
+use super::audit::{
+    audit_limit_exceeded, record_audit, AUDIT_SPENDING_LIMIT_OVERRIDE, AUDIT_TOKEN_METADATA_REFRESH,
+    AUDIT_TRANSFER_APPROVE, AUDIT_TRANSFER_BATCH, AUDIT_TRANSFER_BUMP_FEE, AUDIT_TRANSFER_EXECUTE,
+    AUDIT_TRANSFER_INITIATE, AUDIT_TRANSFER_REJECT,
+};
+use super::pagination;
+use crate::api::middleware::{AuthenticatedUser, ClientIp};
 use crate::blockchain::ChainRegistry;
 use crate::blockchain::traits::TransferParams;
-use crate::db::models::TransferRequest;
+use crate::db::models::{BatchTransferRequest, TransferRequest};
+use crate::db::repositories::{AuditRepository, TransferFilter};
 use crate::error::{AppError, AppResult};
-use crate::services::{TransferService, WalletService};
+use crate::services::{Permission, TransferService, WalletService};
+
+const IDEMPOTENCY_KEY_HEADER: &str = "Idempotency-Key";
+const MAX_IDEMPOTENCY_KEY_LEN: usize = 255;
+
+/// Optional `Idempotency-Key` header value
+fn idempotency_key(req: &HttpRequest) -> AppResult<Option<String>> {
+    let Some(value) = req.headers().get(IDEMPOTENCY_KEY_HEADER) else {
+        return Ok(None);
+    };
+    let key = value
+        .to_str()
+        .map(str::trim)
+        .map_err(|_| AppError::ValidationError("Idempotency-Key must be ASCII".to_string()))?;
+    if key.is_empty() || key.len() > MAX_IDEMPOTENCY_KEY_LEN {
+        return Err(AppError::ValidationError(format!(
+            "Idempotency-Key must be 1 to {} characters",
+            MAX_IDEMPOTENCY_KEY_LEN
+        )));
+    }
+    Ok(Some(key.to_string()))
+}
 
 pub async fn initiate_transfer(
     transfer_service: web::Data<Arc<TransferService>>,
+    audit: web::Data<Arc<AuditRepository>>,
+    client_ip: ClientIp,
     user: AuthenticatedUser,
+    req: HttpRequest,
     request: web::Json<TransferRequest>,
 ) -> AppResult<HttpResponse> {
-    // Only admin can initiate transfers
-    if user.role != "admin" {
-        return Err(AppError::Forbidden("Only admin can initiate transfers".to_string()));
-    }
+    user.require_permission(Permission::TransferCreate)?;
 
-    let transfer = transfer_service
-        .initiate_transfer(request.into_inner(), user.user_id)
+    let key = idempotency_key(&req)?;
+    let (transfer, created) = transfer_service
+        .initiate_transfer(request.into_inner(), user.user_id, key.as_deref())
         .await?;
 
+    if !created {
+        return Ok(HttpResponse::Ok()
+            .insert_header(("Idempotent-Replayed", "true"))
+            .json(transfer));
+    }
+
+    record_audit(
+        &audit,
+        &client_ip,
+        Some(user.user_id),
+        AUDIT_TRANSFER_INITIATE,
+        Some(format!("transfer:{}", transfer.id)),
+        serde_json::json!({
+            "wallet_id": transfer.wallet_id,
+            "chain": transfer.chain,
+            "to": transfer.to_address,
+            "token": transfer.token,
+            "amount": transfer.amount
+        }),
+    )
+    .await;
+
     Ok(HttpResponse::Created().json(transfer))
 }
 
+/// Create and send several transfers in one request
+///
+/// Responds 200 with one result per item even when some of them failed.
+pub async fn batch_transfer(
+    transfer_service: web::Data<Arc<TransferService>>,
+    audit: web::Data<Arc<AuditRepository>>,
+    client_ip: ClientIp,
+    user: AuthenticatedUser,
+    request: web::Json<BatchTransferRequest>,
+) -> AppResult<HttpResponse> {
+    user.require_permission(Permission::TransferCreate)?;
+    user.require_permission(Permission::TransferExecute)?;
+
+    let request = request.into_inner();
+    let chain = request.chain.clone();
+    let results = transfer_service.batch_transfer(request, user.user_id).await?;
+
+    record_audit(
+        &audit,
+        &client_ip,
+        Some(user.user_id),
+        AUDIT_TRANSFER_BATCH,
+        None,
+        serde_json::json!({
+            "chain": chain,
+            "items": results.iter().map(|r| serde_json::json!({
+                "transfer_id": r.transfer_id,
+                "to": r.to_address,
+                "token": r.token,
+                "amount": r.amount,
+                "status": r.status,
+                "tx_hash": r.tx_hash,
+                "error": r.error
+            })).collect::<Vec<_>>()
+        }),
+    )
+    .await;
+
+    Ok(HttpResponse::Ok().json(serde_json::json!({ "results": results })))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ExecuteQuery {
+    /// Build and sign only; nothing is broadcast and the transfer stays pending
+    #[serde(default)]
+    pub dry_run: bool,
+}
+
+/// Signed transaction from a dry run, for inspection or broadcasting elsewhere
+#[derive(Debug, Serialize)]
+pub struct DryRunResponse {
+    pub transfer_id: i32,
+    pub raw_tx: String,
+    pub tx_hash: String,
+    /// Always false: the transaction was not sent to any node
+    pub broadcast: bool,
+}
+
 pub async fn execute_transfer(
     transfer_service: web::Data<Arc<TransferService>>,
+    audit: web::Data<Arc<AuditRepository>>,
+    client_ip: ClientIp,
     user: AuthenticatedUser,
     path: web::Path<i32>,
+    query: web::Query<ExecuteQuery>,
 ) -> AppResult<HttpResponse> {
-    // Only admin can execute transfers
-    if user.role != "admin" {
-        return Err(AppError::Forbidden("Only admin can execute transfers".to_string()));
+    user.require_permission(Permission::TransferExecute)?;
+
+    let transfer_id = path.into_inner();
+    if query.dry_run {
+        let signed = transfer_service.dry_run_transfer(transfer_id).await;
+        let resource = format!("transfer:{}", transfer_id);
+        let signed = audit_limit_exceeded(signed, &audit, &client_ip, &user, resource).await?;
+
+        record_audit(
+            &audit,
+            &client_ip,
+            Some(user.user_id),
+            AUDIT_TRANSFER_EXECUTE,
+            Some(format!("transfer:{}", transfer_id)),
+            serde_json::json!({ "dry_run": true, "tx_hash": signed.tx_hash }),
+        )
+        .await;
+        return Ok(HttpResponse::Ok().json(DryRunResponse {
+            transfer_id,
+            raw_tx: signed.raw_tx,
+            tx_hash: signed.tx_hash,
+            broadcast: false,
+        }));
     }
 
-    let transfer = transfer_service.execute_transfer(path.into_inner()).await?;
+    let transfer = transfer_service.execute_transfer(transfer_id).await;
+    let resource = format!("transfer:{}", transfer_id);
+    let transfer = audit_limit_exceeded(transfer, &audit, &client_ip, &user, resource).await?;
+
+    record_audit(
+        &audit,
+        &client_ip,
+        Some(user.user_id),
+        AUDIT_TRANSFER_EXECUTE,
+        Some(format!("transfer:{}", transfer.id)),
+        serde_json::json!({ "status": transfer.status, "tx_hash": transfer.tx_hash }),
+    )
+    .await;
+    Ok(HttpResponse::Ok().json(transfer_service.with_fiat_fee(transfer).await))
+}
+
+/// Let a pending transfer exceed its wallet's spending limit
+pub async fn override_spending_limit(
+    transfer_service: web::Data<Arc<TransferService>>,
+    audit: web::Data<Arc<AuditRepository>>,
+    client_ip: ClientIp,
+    user: AuthenticatedUser,
+    path: web::Path<i32>,
+) -> AppResult<HttpResponse> {
+    user.require_permission(Permission::TransferApprove)?;
+
+    let transfer = transfer_service
+        .override_spending_limit(path.into_inner(), user.user_id)
+        .await?;
+
+    record_audit(
+        &audit,
+        &client_ip,
+        Some(user.user_id),
+        AUDIT_SPENDING_LIMIT_OVERRIDE,
+        Some(format!("transfer:{}", transfer.id)),
+        serde_json::json!({
+            "wallet_id": transfer.wallet_id,
+            "initiated_by": transfer.initiated_by,
+            "token": transfer.token,
+            "amount": transfer.amount
+        }),
+    )
+    .await;
+    Ok(HttpResponse::Ok().json(transfer))
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct ApprovalRequest {
+    /// Why the transfer was rejected; stored as its error message
+    #[serde(default)]
+    pub reason: Option<String>,
+}
+
+/// Approve a transfer above the approval threshold so it can be executed
+pub async fn approve_transfer(
+    transfer_service: web::Data<Arc<TransferService>>,
+    audit: web::Data<Arc<AuditRepository>>,
+    client_ip: ClientIp,
+    user: AuthenticatedUser,
+    path: web::Path<i32>,
+) -> AppResult<HttpResponse> {
+    user.require_permission(Permission::TransferApprove)?;
+
+    let transfer = transfer_service
+        .decide_approval(path.into_inner(), user.user_id, true, None)
+        .await?;
+
+    record_audit(
+        &audit,
+        &client_ip,
+        Some(user.user_id),
+        AUDIT_TRANSFER_APPROVE,
+        Some(format!("transfer:{}", transfer.id)),
+        serde_json::json!({ "initiated_by": transfer.initiated_by, "amount": transfer.amount }),
+    )
+    .await;
+    Ok(HttpResponse::Ok().json(transfer))
+}
+
+/// Reject a transfer awaiting approval; it can no longer be executed
+pub async fn reject_transfer(
+    transfer_service: web::Data<Arc<TransferService>>,
+    audit: web::Data<Arc<AuditRepository>>,
+    client_ip: ClientIp,
+    user: AuthenticatedUser,
+    path: web::Path<i32>,
+    request: Option<web::Json<ApprovalRequest>>,
+) -> AppResult<HttpResponse> {
+    user.require_permission(Permission::TransferApprove)?;
+
+    let reason = request.map(|r| r.into_inner()).unwrap_or_default().reason;
+    let transfer = transfer_service
+        .decide_approval(path.into_inner(), user.user_id, false, reason.as_deref())
+        .await?;
+
+    record_audit(
+        &audit,
+        &client_ip,
+        Some(user.user_id),
+        AUDIT_TRANSFER_REJECT,
+        Some(format!("transfer:{}", transfer.id)),
+        serde_json::json!({
+            "initiated_by": transfer.initiated_by,
+            "amount": transfer.amount,
+            "reason": reason
+        }),
+    )
+    .await;
+    Ok(HttpResponse::Ok().json(transfer))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct BumpFeeRequest {
+    /// New max fee per gas (gas price for legacy transactions), in Gwei
+    pub gas_price_gwei: String,
+}
+
+/// Speed up a submitted Ethereum transfer by replacing it with a higher fee
+pub async fn bump_transfer_fee(
+    transfer_service: web::Data<Arc<TransferService>>,
+    audit: web::Data<Arc<AuditRepository>>,
+    client_ip: ClientIp,
+    user: AuthenticatedUser,
+    path: web::Path<i32>,
+    request: web::Json<BumpFeeRequest>,
+) -> AppResult<HttpResponse> {
+    user.require_permission(Permission::TransferExecute)?;
+
+    let gas_price_gwei: Decimal = request.gas_price_gwei.parse()
+        .map_err(|_| AppError::ValidationError("Invalid gas price".to_string()))?;
+
+    let (transfer, old_tx_hash) = transfer_service
+        .bump_transfer_fee(path.into_inner(), gas_price_gwei)
+        .await?;
+
+    record_audit(
+        &audit,
+        &client_ip,
+        Some(user.user_id),
+        AUDIT_TRANSFER_BUMP_FEE,
+        Some(format!("transfer:{}", transfer.id)),
+        serde_json::json!({
+            "chain": transfer.chain,
+            "old_tx_hash": old_tx_hash,
+            "new_tx_hash": transfer.tx_hash,
+            "gas_price_gwei": transfer.gas_price
+        }),
+    )
+    .await;
+
     Ok(HttpResponse::Ok().json(transfer))
 }
 
@@ -46,23 +329,22 @@ pub async fn get_transfer(
     path: web::Path<i32>,
 ) -> AppResult<HttpResponse> {
     let transfer = transfer_service.get_transfer(path.into_inner()).await?;
-    Ok(HttpResponse::Ok().json(transfer))
+    Ok(HttpResponse::Ok().json(transfer_service.with_fiat_fee(transfer).await))
 }
 
 pub async fn list_transfers(
     transfer_service: web::Data<Arc<TransferService>>,
     query: web::Query<TransferListQuery>,
 ) -> AppResult<HttpResponse> {
-    let limit = query.limit.unwrap_or(20).min(100);
-    let offset = query.offset.unwrap_or(0);
-
-    let (transfers, total) = if let Some(wallet_id) = query.wallet_id {
-        transfer_service
-            .list_wallet_transfers(wallet_id, limit, offset)
-            .await?
-    } else {
-        transfer_service.list_transfers(limit, offset).await?
-    };
+    let (limit, offset) = query.page()?;
+    let filter = query.filter()?;
+
+    let (mut transfers, total) = transfer_service.list_transfers(&filter, limit, offset).await?;
+
+    if let Some(places) = query.display_decimals {
+        transfer_service.apply_display_decimals(&mut transfers, places);
+    }
+    let transfers = transfer_service.with_fiat_fees(transfers).await;
 
     Ok(HttpResponse::Ok().json(serde_json::json!({
         "transfers": transfers,
@@ -79,11 +361,136 @@ pub async fn list_chains(
     Ok(HttpResponse::Ok().json(chains))
 }
 
+/// Re-read a token's decimals and symbol from its contract, replacing the cached entry
+pub async fn refresh_token_metadata(
+    chain_registry: web::Data<Arc<ChainRegistry>>,
+    audit: web::Data<Arc<AuditRepository>>,
+    client_ip: ClientIp,
+    user: AuthenticatedUser,
+    path: web::Path<(String, String)>,
+) -> AppResult<HttpResponse> {
+    user.require_permission(Permission::SettingsWrite)?;
+    let (chain, symbol) = path.into_inner();
+
+    let metadata = chain_registry.get(&chain)?.refresh_token_metadata(&symbol).await?;
+    record_audit(
+        &audit,
+        &client_ip,
+        Some(user.user_id),
+        AUDIT_TOKEN_METADATA_REFRESH,
+        Some(format!("chain:{}", chain)),
+        serde_json::json!({ "token": symbol, "metadata": metadata }),
+    )
+    .await;
+    Ok(HttpResponse::Ok().json(metadata))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ResolveNameQuery {
+    pub chain: Option<String>,
+    pub name: Option<String>,
+    pub address: Option<String>,
+}
+
+/// Resolve a name (e.g. ENS) to an address, or an address back to its primary name
+pub async fn resolve_name(
+    chain_registry: web::Data<Arc<ChainRegistry>>,
+    _user: AuthenticatedUser,
+    query: web::Query<ResolveNameQuery>,
+) -> AppResult<HttpResponse> {
+    let client = chain_registry.get(query.chain.as_deref().unwrap_or("ethereum"))?;
+
+    let (name, address) = match (&query.name, &query.address) {
+        (Some(name), _) => (Some(name.clone()), client.resolve_address(name).await?),
+        (None, Some(address)) => (client.lookup_name(address).await?, address.clone()),
+        (None, None) => {
+            return Err(AppError::ValidationError(
+                "Either name or address is required".to_string(),
+            ))
+        }
+    };
+
+    Ok(HttpResponse::Ok().json(serde_json::json!({
+        "name": name,
+        "address": address
+    })))
+}
+
+/// Largest page `GET /transfers` returns
+const MAX_TRANSFER_PAGE: i32 = 100;
+
+const TRANSFER_STATUSES: [&str; 5] = ["pending", "submitted", "confirmed", "failed", "rejected"];
+
 #[derive(Debug, serde::Deserialize)]
 pub struct TransferListQuery {
     pub wallet_id: Option<i32>,
+    pub status: Option<String>,
+    pub chain: Option<String>,
+    /// Earliest `created_at`, RFC 3339 or YYYY-MM-DD
+    pub from: Option<String>,
+    /// Latest `created_at`, RFC 3339 or YYYY-MM-DD (the whole day is included)
+    pub to: Option<String>,
     pub limit: Option<i32>,
     pub offset: Option<i32>,
+    /// Decimal places in display strings (default: full precision)
+    pub display_decimals: Option<u32>,
+}
+
+impl TransferListQuery {
+    fn page(&self) -> AppResult<(i32, i32)> {
+        pagination(self.limit, self.offset, MAX_TRANSFER_PAGE)
+    }
+
+    fn filter(&self) -> AppResult<TransferFilter> {
+        let status = self.status.as_deref().map(str::to_lowercase);
+        if let Some(status) = &status {
+            if !TRANSFER_STATUSES.contains(&status.as_str()) {
+                return Err(AppError::ValidationError(format!(
+                    "Unknown status '{}', expected one of {}",
+                    status,
+                    TRANSFER_STATUSES.join(", ")
+                )));
+            }
+        }
+
+        let (from, to) = parse_date_range(self.from.as_deref(), self.to.as_deref())?;
+
+        Ok(TransferFilter {
+            wallet_id: self.wallet_id,
+            status,
+            chain: self.chain.as_deref().map(str::to_lowercase),
+            from,
+            to,
+        })
+    }
+}
+
+/// Parse optional `from`/`to` bounds, rejecting an empty range
+pub(super) fn parse_date_range(
+    from: Option<&str>,
+    to: Option<&str>,
+) -> AppResult<(Option<DateTime<Utc>>, Option<DateTime<Utc>>)> {
+    let from = from.map(|v| parse_date_bound(v, false)).transpose()?;
+    let to = to.map(|v| parse_date_bound(v, true)).transpose()?;
+    if let (Some(from), Some(to)) = (from, to) {
+        if from >= to {
+            return Err(AppError::ValidationError("from must be before to".to_string()));
+        }
+    }
+    Ok((from, to))
+}
+
+/// Parse a date range bound; a bare date used as an upper bound covers that whole day
+fn parse_date_bound(value: &str, upper: bool) -> AppResult<DateTime<Utc>> {
+    if let Ok(at) = DateTime::parse_from_rfc3339(value) {
+        return Ok(at.with_timezone(&Utc));
+    }
+
+    let date = NaiveDate::parse_from_str(value, "%Y-%m-%d").map_err(|_| {
+        AppError::ValidationError(format!("Invalid date '{}', use YYYY-MM-DD or RFC 3339", value))
+    })?;
+    let date = if upper { date + Duration::days(1) } else { date };
+    Ok(date.and_time(NaiveTime::MIN).and_utc())
 }
 
 #[derive(Debug, Deserialize)]
@@ -149,3 +556,151 @@ pub async fn estimate_gas(
         max_fee_gwei: estimate.max_fee_gwei,
     }))
 }
+
+#[derive(Debug, Deserialize)]
+pub struct FeeEstimateQuery {
+    pub chain: Option<String>,
+    /// Sender; defaults to the chain's active wallet
+    pub from: Option<String>,
+    pub to: String,
+    /// Token symbol; defaults to the chain's native token
+    pub token: Option<String>,
+    pub amount: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct FeeEstimateResponse {
+    pub chain: String,
+    pub from: String,
+    pub to: String,
+    pub token: String,
+    pub amount: Decimal,
+    pub gas_limit: u64,
+    pub base_fee_gwei: Option<Decimal>,
+    pub priority_fee_gwei: Option<Decimal>,
+    pub max_fee_gwei: Option<Decimal>,
+    /// Worst-case fee (gas limit at the max fee per gas)
+    pub estimated_fee_wei: Option<String>,
+    pub estimated_fee: Decimal,
+    pub fee_symbol: String,
+}
+
+/// Preview the fee of an EVM transfer without recording or sending anything
+pub async fn preview_transfer_fee(
+    chain_registry: web::Data<Arc<ChainRegistry>>,
+    wallet_service: web::Data<Arc<WalletService>>,
+    _user: AuthenticatedUser,
+    query: web::Query<FeeEstimateQuery>,
+) -> AppResult<HttpResponse> {
+    let chain = query.chain.as_deref().unwrap_or("ethereum").to_lowercase();
+    let client = chain_registry.get(&chain)?;
+    if client.key_scheme() != "ethereum" {
+        return Err(AppError::ValidationError(format!(
+            "Gas estimates are only available for EVM chains, not {}",
+            chain
+        )));
+    }
+
+    let amount: Decimal = query.amount.parse()
+        .map_err(|_| AppError::ValidationError("Invalid amount".to_string()))?;
+    if amount <= Decimal::ZERO {
+        return Err(AppError::ValidationError("Amount must be positive".to_string()));
+    }
+
+    let from = match &query.from {
+        Some(from) => from.clone(),
+        None => wallet_service.get_active_wallet(&chain).await?.address,
+    };
+    if !client.validate_address(&from) {
+        return Err(AppError::ValidationError(format!("Invalid sender address: {}", from)));
+    }
+
+    let token = query
+        .token
+        .clone()
+        .unwrap_or_else(|| client.native_token_symbol().to_string());
+
+    let params = TransferParams {
+        from_address: from.clone(),
+        to_address: query.to.clone(),
+        token: token.clone(),
+        amount,
+        private_key: String::new(), // Not needed for estimation
+        gas_price_gwei: None,
+        gas_limit: None,
+    };
+    let estimate = client.estimate_gas(&params).await?;
+
+    Ok(HttpResponse::Ok().json(FeeEstimateResponse {
+        chain,
+        from,
+        to: query.to.clone(),
+        token,
+        amount,
+        gas_limit: estimate.gas_limit,
+        base_fee_gwei: estimate.base_fee_gwei,
+        priority_fee_gwei: estimate.priority_fee_gwei,
+        max_fee_gwei: estimate.max_fee_gwei,
+        estimated_fee_wei: estimate.estimated_fee_wei.map(|wei| wei.to_string()),
+        estimated_fee: estimate.estimated_fee_eth,
+        fee_symbol: client.native_token_symbol().to_string(),
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::test::TestRequest;
+
+    #[test]
+    fn test_idempotency_key_header() {
+        let req = TestRequest::default().to_http_request();
+        assert_eq!(idempotency_key(&req).unwrap(), None);
+
+        let req = TestRequest::default()
+            .insert_header((IDEMPOTENCY_KEY_HEADER, " order-42 "))
+            .to_http_request();
+        assert_eq!(idempotency_key(&req).unwrap().as_deref(), Some("order-42"));
+
+        let req = TestRequest::default()
+            .insert_header((IDEMPOTENCY_KEY_HEADER, "x".repeat(256)))
+            .to_http_request();
+        assert!(idempotency_key(&req).is_err());
+    }
+
+    fn query(params: &str) -> TransferListQuery {
+        web::Query::<TransferListQuery>::from_query(params).unwrap().into_inner()
+    }
+
+    #[test]
+    fn test_filters_failed_transfers_for_a_week() {
+        let query = query("wallet_id=3&status=FAILED&chain=zcash&from=2024-05-01&to=2024-05-07&limit=50&offset=100");
+        assert_eq!(query.page().unwrap(), (50, 100));
+
+        let filter = query.filter().unwrap();
+        assert_eq!(filter.wallet_id, Some(3));
+        assert_eq!(filter.status.as_deref(), Some("failed"));
+        assert_eq!(filter.from.unwrap().to_rfc3339(), "2024-05-01T00:00:00+00:00");
+        // The last day is included
+        assert_eq!(filter.to.unwrap().to_rfc3339(), "2024-05-08T00:00:00+00:00");
+    }
+
+    #[test]
+    fn test_filters_rejected_transfers() {
+        let filter = query("status=rejected").filter().unwrap();
+        assert_eq!(filter.status.as_deref(), Some("rejected"));
+    }
+
+    #[test]
+    fn test_rejects_bad_pages_and_filters() {
+        assert!(query("limit=101").page().is_err());
+        assert!(query("limit=0").page().is_err());
+        assert!(query("offset=-1").page().is_err());
+        assert_eq!(query("").page().unwrap(), (20, 0));
+
+        assert!(query("status=lost").filter().is_err());
+        assert!(query("from=last-week").filter().is_err());
+        assert!(query("from=2024-05-07&to=2024-05-01").filter().is_err());
+        assert!(query("from=2024-05-01T12:00:00Z").filter().unwrap().from.is_some());
+    }
+}
@@ -1,16 +1,36 @@
+use actix_web::http::header;
 use actix_web::{web, HttpResponse};
+use futures::StreamExt;
 use std::sync::Arc;
 
-use crate::api::middleware::AuthenticatedUser;
-use crate::db::models::{CreateWalletRequest, ExportPrivateKeyRequest, ImportWalletRequest};
+use super::audit::{
+    audit_limit_exceeded, record_audit, AUDIT_KEY_EXPORT, AUDIT_KEY_EXPORT_DENIED, AUDIT_KEY_ROTATE, AUDIT_MESSAGE_SIGN,
+    AUDIT_SPENDING_LIMIT_DELETE, AUDIT_SPENDING_LIMIT_SET, AUDIT_WALLET_ACTIVATE,
+    AUDIT_WALLET_CREATE, AUDIT_WALLET_DELETE, AUDIT_WALLET_IMPORT, AUDIT_WALLET_KEYS_VERIFY,
+};
+use crate::api::middleware::{AuthenticatedUser, ClientIp};
+use crate::db::models::{
+    CreateWalletRequest, DeriveAddressRequest, ExportKeystoreRequest, ExportPrivateKeyRequest, ImportKeystoreRequest,
+    ImportMnemonicRequest, ImportViewingKeyRequest,
+    ImportWalletRequest, RotateEncryptionKeyRequest, SetSpendingLimitRequest, SetWalletTagsRequest,
+    SignMessageRequest, SignTypedDataRequest,
+};
+use super::pagination;
+use super::transfer::parse_date_range;
+use crate::db::repositories::{AuditRepository, DepositRepository, TransferFilter};
 use crate::error::{AppError, AppResult};
-use crate::services::{AuthService, WalletService};
+use crate::services::activity_export::ExportFormat;
+use crate::services::{AuthService, Permission, TransferService, WalletService};
 
 pub async fn list_wallets(
     wallet_service: web::Data<Arc<WalletService>>,
     query: web::Query<ChainQuery>,
 ) -> AppResult<HttpResponse> {
-    let wallets = if let Some(chain) = &query.chain {
+    let wallets = if let Some(tag) = &query.tag {
+        wallet_service
+            .list_wallets_by_tag(tag, query.chain.as_deref())
+            .await?
+    } else if let Some(chain) = &query.chain {
         wallet_service.list_wallets_by_chain(chain).await?
     } else {
         wallet_service.list_wallets().await?
@@ -21,35 +41,142 @@ pub async fn list_wallets(
 
 pub async fn create_wallet(
     wallet_service: web::Data<Arc<WalletService>>,
+    audit: web::Data<Arc<AuditRepository>>,
+    client_ip: ClientIp,
     user: AuthenticatedUser,
     request: web::Json<CreateWalletRequest>,
 ) -> AppResult<HttpResponse> {
-    // Only admin can create wallets
-    if user.role != "admin" {
-        return Err(AppError::Forbidden("Only admin can create wallets".to_string()));
-    }
+    user.require_permission(Permission::WalletCreate)?;
+
+    let word_count = if request.mnemonic {
+        Some(request.word_count.unwrap_or(12))
+    } else {
+        None
+    };
 
     let wallet = wallet_service
-        .create_wallet(&request.name, &request.chain)
+        .create_wallet(&request.name, &request.chain, word_count)
         .await?;
 
+    record_audit(
+        &audit,
+        &client_ip,
+        Some(user.user_id),
+        AUDIT_WALLET_CREATE,
+        Some(format!("wallet:{}", wallet.wallet.id)),
+        serde_json::json!({
+            "chain": wallet.wallet.chain,
+            "address": wallet.wallet.address,
+            "mnemonic": wallet.mnemonic.is_some()
+        }),
+    )
+    .await;
+
     Ok(HttpResponse::Created().json(wallet))
 }
 
 pub async fn import_wallet(
     wallet_service: web::Data<Arc<WalletService>>,
+    audit: web::Data<Arc<AuditRepository>>,
+    client_ip: ClientIp,
     user: AuthenticatedUser,
     request: web::Json<ImportWalletRequest>,
 ) -> AppResult<HttpResponse> {
-    // Only admin can import wallets
-    if user.role != "admin" {
-        return Err(AppError::Forbidden("Only admin can import wallets".to_string()));
-    }
+    user.require_permission(Permission::WalletCreate)?;
+
+    let wallet = wallet_service
+        .import_wallet(&request.name, &request.private_key, &request.chain, request.birthday_height)
+        .await?;
+
+    record_audit(
+        &audit,
+        &client_ip,
+        Some(user.user_id),
+        AUDIT_WALLET_IMPORT,
+        Some(format!("wallet:{}", wallet.id)),
+        serde_json::json!({ "chain": wallet.chain, "address": wallet.address, "source": "private_key" }),
+    )
+    .await;
+
+    Ok(HttpResponse::Created().json(wallet))
+}
+
+pub async fn import_mnemonic_wallet(
+    wallet_service: web::Data<Arc<WalletService>>,
+    audit: web::Data<Arc<AuditRepository>>,
+    client_ip: ClientIp,
+    user: AuthenticatedUser,
+    request: web::Json<ImportMnemonicRequest>,
+) -> AppResult<HttpResponse> {
+    user.require_permission(Permission::WalletCreate)?;
+
+    let wallet = wallet_service
+        .import_wallet_from_mnemonic(&request.name, &request.mnemonic, &request.chain, request.account_index)
+        .await?;
+
+    record_audit(
+        &audit,
+        &client_ip,
+        Some(user.user_id),
+        AUDIT_WALLET_IMPORT,
+        Some(format!("wallet:{}", wallet.id)),
+        serde_json::json!({ "chain": wallet.chain, "address": wallet.address, "source": "mnemonic" }),
+    )
+    .await;
+
+    Ok(HttpResponse::Created().json(wallet))
+}
+
+pub async fn import_viewing_key_wallet(
+    wallet_service: web::Data<Arc<WalletService>>,
+    audit: web::Data<Arc<AuditRepository>>,
+    client_ip: ClientIp,
+    user: AuthenticatedUser,
+    request: web::Json<ImportViewingKeyRequest>,
+) -> AppResult<HttpResponse> {
+    user.require_permission(Permission::WalletCreate)?;
 
     let wallet = wallet_service
-        .import_wallet(&request.name, &request.private_key, &request.chain)
+        .import_viewing_key(&request.name, &request.viewing_key)
         .await?;
 
+    record_audit(
+        &audit,
+        &client_ip,
+        Some(user.user_id),
+        AUDIT_WALLET_IMPORT,
+        Some(format!("wallet:{}", wallet.id)),
+        serde_json::json!({ "chain": wallet.chain, "address": wallet.address, "source": "viewing_key" }),
+    )
+    .await;
+
+    Ok(HttpResponse::Created().json(wallet))
+}
+
+pub async fn import_keystore_wallet(
+    wallet_service: web::Data<Arc<WalletService>>,
+    audit: web::Data<Arc<AuditRepository>>,
+    client_ip: ClientIp,
+    user: AuthenticatedUser,
+    request: web::Json<ImportKeystoreRequest>,
+) -> AppResult<HttpResponse> {
+    user.require_permission(Permission::WalletCreate)?;
+
+    let request = request.into_inner();
+    let wallet = wallet_service
+        .import_keystore(&request.name, request.keystore, &request.passphrase, &request.chain)
+        .await?;
+
+    record_audit(
+        &audit,
+        &client_ip,
+        Some(user.user_id),
+        AUDIT_WALLET_IMPORT,
+        Some(format!("wallet:{}", wallet.id)),
+        serde_json::json!({ "chain": wallet.chain, "address": wallet.address, "source": "keystore" }),
+    )
+    .await;
+
     Ok(HttpResponse::Created().json(wallet))
 }
 
@@ -61,79 +188,503 @@ pub async fn get_wallet(
     Ok(HttpResponse::Ok().json(wallet))
 }
 
+#[derive(Debug, serde::Deserialize)]
+pub struct ActivityExportQuery {
+    /// `csv` (default) or `json`
+    pub format: Option<String>,
+    /// Earliest `created_at`, RFC 3339 or YYYY-MM-DD
+    pub from: Option<String>,
+    /// Latest `created_at`, RFC 3339 or YYYY-MM-DD (the whole day is included)
+    pub to: Option<String>,
+}
+
+/// Download a wallet's transfer history for bookkeeping, streamed as it is read
+pub async fn export_wallet_activity(
+    wallet_service: web::Data<Arc<WalletService>>,
+    transfer_service: web::Data<Arc<TransferService>>,
+    _user: AuthenticatedUser,
+    path: web::Path<i32>,
+    query: web::Query<ActivityExportQuery>,
+) -> AppResult<HttpResponse> {
+    let wallet_id = path.into_inner();
+    let format = match query.format.as_deref().map(str::to_lowercase).as_deref() {
+        None | Some("csv") => ExportFormat::Csv,
+        Some("json") => ExportFormat::Json,
+        Some(other) => {
+            return Err(AppError::ValidationError(format!(
+                "Unknown format '{}', expected csv or json",
+                other
+            )))
+        }
+    };
+    let (from, to) = parse_date_range(query.from.as_deref(), query.to.as_deref())?;
+    wallet_service.get_wallet(wallet_id).await?;
+
+    let filter = TransferFilter {
+        wallet_id: Some(wallet_id),
+        from,
+        to,
+        ..Default::default()
+    };
+    // Headers are already sent when a page fails, so the download is cut short
+    let body = transfer_service
+        .get_ref()
+        .clone()
+        .export_activity(filter, format)
+        .map(move |chunk| {
+            chunk.map(web::Bytes::from).map_err(|e| {
+                tracing::error!("Activity export of wallet {} failed: {}", wallet_id, e);
+                actix_web::Error::from(e)
+            })
+        });
+
+    Ok(HttpResponse::Ok()
+        .content_type(format.content_type())
+        .insert_header((
+            header::CONTENT_DISPOSITION,
+            format!(
+                "attachment; filename=\"wallet-{}-activity.{}\"",
+                wallet_id,
+                format.extension()
+            ),
+        ))
+        .streaming(body))
+}
+
+#[derive(Debug, serde::Deserialize)]
+pub struct WalletBalanceQuery {
+    /// Decimal places in display strings (default: full precision)
+    pub display_decimals: Option<u32>,
+    /// Query the chain even if a recently fetched balance is cached
+    #[serde(default)]
+    pub force_refresh: bool,
+}
+
+/// Balance of a wallet summed over its own and derived receive addresses
+pub async fn get_wallet_balance(
+    wallet_service: web::Data<Arc<WalletService>>,
+    _user: AuthenticatedUser,
+    path: web::Path<i32>,
+    query: web::Query<WalletBalanceQuery>,
+) -> AppResult<HttpResponse> {
+    let balance = wallet_service
+        .get_wallet_balance(path.into_inner(), query.display_decimals, query.force_refresh)
+        .await?;
+    Ok(HttpResponse::Ok().json(balance))
+}
+
+/// Derived receive addresses of a mnemonic wallet
+pub async fn list_derived_addresses(
+    wallet_service: web::Data<Arc<WalletService>>,
+    _user: AuthenticatedUser,
+    path: web::Path<i32>,
+) -> AppResult<HttpResponse> {
+    let addresses = wallet_service.list_derived_addresses(path.into_inner()).await?;
+    Ok(HttpResponse::Ok().json(addresses))
+}
+
+/// Derive the receive address at a BIP44 index of a mnemonic wallet
+pub async fn derive_address(
+    wallet_service: web::Data<Arc<WalletService>>,
+    user: AuthenticatedUser,
+    path: web::Path<i32>,
+    request: web::Json<DeriveAddressRequest>,
+) -> AppResult<HttpResponse> {
+    user.require_permission(Permission::WalletCreate)?;
+
+    let address = wallet_service
+        .derive_address(path.into_inner(), request.index)
+        .await?;
+    Ok(HttpResponse::Ok().json(address))
+}
+
+/// Most deposits returned per page
+const MAX_DEPOSIT_PAGE: i32 = 100;
+
+#[derive(Debug, serde::Deserialize)]
+pub struct DepositListQuery {
+    pub limit: Option<i32>,
+    pub offset: Option<i32>,
+}
+
+/// Transparent deposits the watcher has recorded for a Zcash wallet, newest first
+pub async fn list_wallet_deposits(
+    wallet_service: web::Data<Arc<WalletService>>,
+    deposit_repo: web::Data<Arc<DepositRepository>>,
+    _user: AuthenticatedUser,
+    path: web::Path<i32>,
+    query: web::Query<DepositListQuery>,
+) -> AppResult<HttpResponse> {
+    let wallet_id = path.into_inner();
+    let (limit, offset) = pagination(query.limit, query.offset, MAX_DEPOSIT_PAGE)?;
+
+    wallet_service.get_wallet(wallet_id).await?;
+    let deposits = deposit_repo.list_by_wallet(wallet_id, limit, offset).await?;
+
+    Ok(HttpResponse::Ok().json(serde_json::json!({
+        "deposits": deposits,
+        "limit": limit,
+        "offset": offset
+    })))
+}
+
 pub async fn get_balance(
     wallet_service: web::Data<Arc<WalletService>>,
     query: web::Query<BalanceQuery>,
 ) -> AppResult<HttpResponse> {
     let chain = query.chain.as_deref().unwrap_or("ethereum");
-    let balance = wallet_service.get_balance(&query.address, chain).await?;
+    let balance = wallet_service
+        .get_balance(&query.address, chain, query.display_decimals, query.force_refresh)
+        .await?;
     Ok(HttpResponse::Ok().json(balance))
 }
 
 pub async fn set_active_wallet(
     wallet_service: web::Data<Arc<WalletService>>,
+    audit: web::Data<Arc<AuditRepository>>,
+    client_ip: ClientIp,
     user: AuthenticatedUser,
     path: web::Path<i32>,
 ) -> AppResult<HttpResponse> {
-    // Only admin can set active wallet
-    if user.role != "admin" {
-        return Err(AppError::Forbidden("Only admin can set active wallet".to_string()));
-    }
+    user.require_permission(Permission::WalletManage)?;
+
+    let wallet_id = path.into_inner();
+    wallet_service.set_active_wallet(wallet_id).await?;
 
-    wallet_service.set_active_wallet(path.into_inner()).await?;
+    record_audit(
+        &audit,
+        &client_ip,
+        Some(user.user_id),
+        AUDIT_WALLET_ACTIVATE,
+        Some(format!("wallet:{}", wallet_id)),
+        serde_json::json!({}),
+    )
+    .await;
     Ok(HttpResponse::Ok().json(serde_json::json!({"message": "Wallet set as active"})))
 }
 
+/// Replace a wallet's tags; an empty list clears them
+pub async fn set_wallet_tags(
+    wallet_service: web::Data<Arc<WalletService>>,
+    user: AuthenticatedUser,
+    path: web::Path<i32>,
+    request: web::Json<SetWalletTagsRequest>,
+) -> AppResult<HttpResponse> {
+    user.require_permission(Permission::WalletManage)?;
+
+    let wallet_id = path.into_inner();
+    let tags = wallet_service.set_wallet_tags(wallet_id, &request.tags).await?;
+    Ok(HttpResponse::Ok().json(serde_json::json!({ "wallet_id": wallet_id, "tags": tags })))
+}
+
+pub async fn clear_wallet_tags(
+    wallet_service: web::Data<Arc<WalletService>>,
+    user: AuthenticatedUser,
+    path: web::Path<i32>,
+) -> AppResult<HttpResponse> {
+    user.require_permission(Permission::WalletManage)?;
+
+    let wallet_id = path.into_inner();
+    wallet_service.set_wallet_tags(wallet_id, &[]).await?;
+    Ok(HttpResponse::Ok().json(serde_json::json!({ "wallet_id": wallet_id, "tags": [] })))
+}
+
+pub async fn list_spending_limits(
+    wallet_service: web::Data<Arc<WalletService>>,
+    _user: AuthenticatedUser,
+    path: web::Path<i32>,
+) -> AppResult<HttpResponse> {
+    let limits = wallet_service.list_spending_limits(path.into_inner()).await?;
+    Ok(HttpResponse::Ok().json(limits))
+}
+
+/// Create or replace a wallet's spending limit on one token
+pub async fn set_spending_limit(
+    wallet_service: web::Data<Arc<WalletService>>,
+    audit: web::Data<Arc<AuditRepository>>,
+    client_ip: ClientIp,
+    user: AuthenticatedUser,
+    path: web::Path<i32>,
+    request: web::Json<SetSpendingLimitRequest>,
+) -> AppResult<HttpResponse> {
+    user.require_permission(Permission::SettingsWrite)?;
+
+    let wallet_id = path.into_inner();
+    let limit = wallet_service
+        .set_spending_limit(wallet_id, &request, user.user_id)
+        .await?;
+
+    record_audit(
+        &audit,
+        &client_ip,
+        Some(user.user_id),
+        AUDIT_SPENDING_LIMIT_SET,
+        Some(format!("wallet:{}", wallet_id)),
+        serde_json::json!({
+            "token": limit.token,
+            "max_per_transfer": limit.max_per_transfer,
+            "max_per_window": limit.max_per_window,
+            "window_secs": limit.window_secs
+        }),
+    )
+    .await;
+    Ok(HttpResponse::Ok().json(limit))
+}
+
+pub async fn delete_spending_limit(
+    wallet_service: web::Data<Arc<WalletService>>,
+    audit: web::Data<Arc<AuditRepository>>,
+    client_ip: ClientIp,
+    user: AuthenticatedUser,
+    path: web::Path<(i32, String)>,
+) -> AppResult<HttpResponse> {
+    user.require_permission(Permission::SettingsWrite)?;
+
+    let (wallet_id, token) = path.into_inner();
+    wallet_service.delete_spending_limit(wallet_id, &token).await?;
+
+    record_audit(
+        &audit,
+        &client_ip,
+        Some(user.user_id),
+        AUDIT_SPENDING_LIMIT_DELETE,
+        Some(format!("wallet:{}", wallet_id)),
+        serde_json::json!({ "token": token.to_uppercase() }),
+    )
+    .await;
+    Ok(HttpResponse::NoContent().finish())
+}
+
 pub async fn export_private_key(
     wallet_service: web::Data<Arc<WalletService>>,
     auth_service: web::Data<Arc<AuthService>>,
+    audit: web::Data<Arc<AuditRepository>>,
+    client_ip: ClientIp,
     user: AuthenticatedUser,
     path: web::Path<i32>,
     request: web::Json<ExportPrivateKeyRequest>,
 ) -> AppResult<HttpResponse> {
-    // Only admin can export private keys
-    if user.role != "admin" {
-        return Err(AppError::Forbidden("Only admin can export private keys".to_string()));
-    }
+    user.require_permission(Permission::KeyExport)?;
 
     // Verify password
     let valid = auth_service
         .verify_user_password(user.user_id, &request.password)
         .await?;
 
+    let wallet_id = path.into_inner();
+    let resource = Some(format!("wallet:{}", wallet_id));
+
     if !valid {
+        record_audit(&audit, &client_ip, Some(user.user_id), AUDIT_KEY_EXPORT_DENIED, resource, serde_json::json!({})).await;
         return Err(AppError::InvalidCredentials);
     }
 
-    let private_key = wallet_service.export_private_key(path.into_inner()).await?;
+    let private_key = wallet_service.export_private_key(wallet_id).await?;
+    let (mnemonic, account_index) = match wallet_service.export_mnemonic(wallet_id).await? {
+        Some((phrase, account_index)) => (Some(phrase), account_index),
+        None => (None, None),
+    };
+
+    record_audit(
+        &audit,
+        &client_ip,
+        Some(user.user_id),
+        AUDIT_KEY_EXPORT,
+        resource,
+        serde_json::json!({ "mnemonic": mnemonic.is_some() }),
+    )
+    .await;
 
     Ok(HttpResponse::Ok().json(crate::db::models::ExportPrivateKeyResponse {
         private_key,
+        mnemonic,
+        account_index,
         warning: "Keep this private key secure. Anyone with access to it can control your funds.".to_string(),
     }))
 }
 
-pub async fn delete_wallet(
+/// Export an Ethereum wallet's key as a v3 keystore (geth/MetaMask compatible)
+pub async fn export_keystore(
     wallet_service: web::Data<Arc<WalletService>>,
+    auth_service: web::Data<Arc<AuthService>>,
+    audit: web::Data<Arc<AuditRepository>>,
+    client_ip: ClientIp,
     user: AuthenticatedUser,
     path: web::Path<i32>,
+    request: web::Json<ExportKeystoreRequest>,
 ) -> AppResult<HttpResponse> {
-    // Only admin can delete wallets
-    if user.role != "admin" {
-        return Err(AppError::Forbidden("Only admin can delete wallets".to_string()));
+    user.require_permission(Permission::KeyExport)?;
+
+    let valid = auth_service
+        .verify_user_password(user.user_id, &request.password)
+        .await?;
+
+    let wallet_id = path.into_inner();
+    let resource = Some(format!("wallet:{}", wallet_id));
+
+    if !valid {
+        record_audit(&audit, &client_ip, Some(user.user_id), AUDIT_KEY_EXPORT_DENIED, resource, serde_json::json!({})).await;
+        return Err(AppError::InvalidCredentials);
     }
 
-    wallet_service.delete_wallet(path.into_inner()).await?;
+    let keystore = wallet_service.export_keystore(wallet_id, &request.passphrase).await?;
+
+    record_audit(
+        &audit,
+        &client_ip,
+        Some(user.user_id),
+        AUDIT_KEY_EXPORT,
+        resource,
+        serde_json::json!({ "format": "keystore" }),
+    )
+    .await;
+
+    Ok(HttpResponse::Ok().json(keystore))
+}
+
+/// Sign a message with an Ethereum wallet's key (EIP-191 `personal_sign`)
+pub async fn sign_message(
+    wallet_service: web::Data<Arc<WalletService>>,
+    audit: web::Data<Arc<AuditRepository>>,
+    client_ip: ClientIp,
+    user: AuthenticatedUser,
+    path: web::Path<i32>,
+    request: web::Json<SignMessageRequest>,
+) -> AppResult<HttpResponse> {
+    user.require_permission(Permission::TransferExecute)?;
+
+    let wallet_id = path.into_inner();
+    let signed = wallet_service.sign_message(wallet_id, &request.message).await?;
+
+    record_audit(
+        &audit,
+        &client_ip,
+        Some(user.user_id),
+        AUDIT_MESSAGE_SIGN,
+        Some(format!("wallet:{}", wallet_id)),
+        serde_json::json!({ "kind": "personal_sign", "bytes": request.message.len() }),
+    )
+    .await;
+
+    Ok(HttpResponse::Ok().json(signed))
+}
+
+/// Sign an EIP-712 typed-data payload with an Ethereum wallet's key
+pub async fn sign_typed_data(
+    wallet_service: web::Data<Arc<WalletService>>,
+    audit: web::Data<Arc<AuditRepository>>,
+    client_ip: ClientIp,
+    user: AuthenticatedUser,
+    path: web::Path<i32>,
+    request: web::Json<SignTypedDataRequest>,
+) -> AppResult<HttpResponse> {
+    user.require_permission(Permission::TransferExecute)?;
+
+    let wallet_id = path.into_inner();
+    let signed = wallet_service
+        .sign_typed_data(wallet_id, request.into_inner().typed_data)
+        .await;
+    let resource = format!("wallet:{}", wallet_id);
+    let signed = audit_limit_exceeded(signed, &audit, &client_ip, &user, resource).await?;
+
+    record_audit(
+        &audit,
+        &client_ip,
+        Some(user.user_id),
+        AUDIT_MESSAGE_SIGN,
+        Some(format!("wallet:{}", wallet_id)),
+        serde_json::json!({ "kind": "eip712", "primary_type": signed.primary_type, "digest": signed.digest }),
+    )
+    .await;
+
+    Ok(HttpResponse::Ok().json(signed))
+}
+
+/// Re-derive every Zcash wallet's keys and report wallets inconsistent with stored data
+pub async fn verify_wallet_keys(
+    wallet_service: web::Data<Arc<WalletService>>,
+    audit: web::Data<Arc<AuditRepository>>,
+    client_ip: ClientIp,
+    user: AuthenticatedUser,
+) -> AppResult<HttpResponse> {
+    user.require_permission(Permission::KeyManage)?;
+
+    let report = wallet_service.verify_wallet_keys().await?;
+    record_audit(
+        &audit,
+        &client_ip,
+        Some(user.user_id),
+        AUDIT_WALLET_KEYS_VERIFY,
+        None,
+        serde_json::json!({ "checked": report.checked, "failed": report.failed }),
+    )
+    .await;
+    Ok(HttpResponse::Ok().json(report))
+}
+
+pub async fn rotate_encryption_key(
+    wallet_service: web::Data<Arc<WalletService>>,
+    audit: web::Data<Arc<AuditRepository>>,
+    client_ip: ClientIp,
+    user: AuthenticatedUser,
+    body: web::Json<RotateEncryptionKeyRequest>,
+) -> AppResult<HttpResponse> {
+    user.require_permission(Permission::KeyManage)?;
+
+    let report = wallet_service
+        .rotate_encryption_key(&body.old_key, &body.new_key)
+        .await?;
+
+    record_audit(
+        &audit,
+        &client_ip,
+        Some(user.user_id),
+        AUDIT_KEY_ROTATE,
+        None,
+        serde_json::json!({ "wallets": report.wallets, "rotated": report.rotated }),
+    )
+    .await;
+    Ok(HttpResponse::Ok().json(report))
+}
+
+pub async fn delete_wallet(
+    wallet_service: web::Data<Arc<WalletService>>,
+    audit: web::Data<Arc<AuditRepository>>,
+    client_ip: ClientIp,
+    user: AuthenticatedUser,
+    path: web::Path<i32>,
+) -> AppResult<HttpResponse> {
+    user.require_permission(Permission::WalletManage)?;
+
+    let wallet_id = path.into_inner();
+    wallet_service.delete_wallet(wallet_id).await?;
+
+    record_audit(
+        &audit,
+        &client_ip,
+        Some(user.user_id),
+        AUDIT_WALLET_DELETE,
+        Some(format!("wallet:{}", wallet_id)),
+        serde_json::json!({}),
+    )
+    .await;
     Ok(HttpResponse::Ok().json(serde_json::json!({"message": "Wallet deleted"})))
 }
 
 #[derive(Debug, serde::Deserialize)]
 pub struct ChainQuery {
     pub chain: Option<String>,
+    /// Only wallets carrying this tag
+    pub tag: Option<String>,
 }
 
 #[derive(Debug, serde::Deserialize)]
 pub struct BalanceQuery {
     pub address: String,
     pub chain: Option<String>,
+    /// Decimal places in display strings (default: full precision)
+    pub display_decimals: Option<u32>,
+    /// Query the chain even if a recently fetched balance is cached
+    #[serde(default)]
+    pub force_refresh: bool,
 }
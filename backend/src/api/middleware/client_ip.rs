@@ -0,0 +1,104 @@
+use actix_web::{dev::ServiceRequest, Error, HttpMessage, HttpRequest};
+use futures::future::{ok, Ready};
+use std::net::IpAddr;
+
+/// Number of reverse proxies in front of the server, registered as app data
+///
+/// Each trusted proxy appends the address it received the request from to
+/// `X-Forwarded-For`, so the client is that many entries from the right. With
+/// zero the header is ignored, as any client could set it.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TrustedProxies(pub usize);
+
+/// The caller's IP address, resolved once by the request logger
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ClientIp(pub Option<IpAddr>);
+
+impl ClientIp {
+    pub fn to_string_opt(&self) -> Option<String> {
+        self.0.map(|ip| ip.to_string())
+    }
+}
+
+/// Pick the client address from the peer and the `X-Forwarded-For` chain
+pub fn resolve_client_ip(
+    peer: Option<IpAddr>,
+    forwarded_for: Option<&str>,
+    trusted_proxies: usize,
+) -> Option<IpAddr> {
+    if trusted_proxies == 0 {
+        return peer;
+    }
+
+    // Hops as seen by the server: client, intermediate proxies, then the direct peer
+    let mut hops: Vec<Option<IpAddr>> = forwarded_for
+        .unwrap_or_default()
+        .split(',')
+        .map(str::trim)
+        .filter(|hop| !hop.is_empty())
+        .map(|hop| hop.parse().ok())
+        .collect();
+    hops.push(peer);
+
+    // A shorter chain than configured means the request skipped a proxy; use the leftmost hop
+    let index = hops.len().saturating_sub(trusted_proxies + 1);
+    hops[index]
+}
+
+/// Resolve the client IP and store it in the request extensions
+pub fn attach_client_ip(req: &ServiceRequest) -> ClientIp {
+    let trusted = req
+        .app_data::<actix_web::web::Data<TrustedProxies>>()
+        .map(|t| t.0)
+        .unwrap_or_default();
+    let forwarded_for = req
+        .headers()
+        .get("X-Forwarded-For")
+        .and_then(|v| v.to_str().ok());
+
+    let ip = ClientIp(resolve_client_ip(req.peer_addr().map(|a| a.ip()), forwarded_for, trusted));
+    req.extensions_mut().insert(ip);
+    ip
+}
+
+impl actix_web::FromRequest for ClientIp {
+    type Error = Error;
+    type Future = Ready<Result<Self, Self::Error>>;
+
+    fn from_request(req: &HttpRequest, _payload: &mut actix_web::dev::Payload) -> Self::Future {
+        let ip = req
+            .extensions()
+            .get::<ClientIp>()
+            .copied()
+            // Not behind the logger (e.g. in tests): fall back to the direct peer
+            .unwrap_or_else(|| ClientIp(req.peer_addr().map(|a| a.ip())));
+        ok(ip)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ip(s: &str) -> Option<IpAddr> {
+        Some(s.parse().unwrap())
+    }
+
+    #[test]
+    fn test_forwarded_for_ignored_without_trusted_proxies() {
+        let peer = ip("10.0.0.2");
+        assert_eq!(resolve_client_ip(peer, Some("1.2.3.4"), 0), peer);
+    }
+
+    #[test]
+    fn test_client_is_counted_from_the_right() {
+        let peer = ip("10.0.0.2");
+        // A spoofed entry in front of the real client is skipped
+        let chain = Some("6.6.6.6, 203.0.113.9, 10.0.0.1");
+
+        assert_eq!(resolve_client_ip(peer, chain, 1), ip("10.0.0.1"));
+        assert_eq!(resolve_client_ip(peer, chain, 2), ip("203.0.113.9"));
+        assert_eq!(resolve_client_ip(peer, Some("203.0.113.9"), 5), ip("203.0.113.9"));
+        assert_eq!(resolve_client_ip(peer, None, 1), peer);
+    }
+}
@@ -3,10 +3,21 @@ use actix_web::{
     dev::{ServiceRequest, ServiceResponse},
     Error,
 };
+use actix_web::http::header::{HeaderName, HeaderValue};
 use actix_web::middleware::Next;
 use std::time::Instant;
+use tracing::Instrument;
+
+use super::client_ip::attach_client_ip;
+use super::request_id::{request_id_for, with_request_id};
 
 /// Logging middleware that logs request and response details
+///
+/// Everything logged while handling the request runs inside a `request` span, so logs
+/// carry its request id, method, path, client IP and (once authenticated) user id. The
+/// request id comes from `X-Request-Id` or is generated, and is echoed in the response
+/// header and in error bodies. Also resolves the client IP for everything behind it,
+/// see [`ClientIp`](super::ClientIp).
 pub async fn request_logger(
     req: ServiceRequest,
     next: Next<impl MessageBody>,
@@ -15,49 +26,68 @@ pub async fn request_logger(
     let method = req.method().to_string();
     let path = req.path().to_string();
     let query = req.query_string().to_string();
-    let remote_addr = req
-        .connection_info()
-        .realip_remote_addr()
-        .map(|s| s.to_string())
+    let remote_addr = attach_client_ip(&req)
+        .to_string_opt()
         .unwrap_or_else(|| "-".to_string());
+    let request_id = request_id_for(&req);
 
-    // Log request
-    if !query.is_empty() {
-        tracing::info!(
-            "--> {} {} ?{} (from: {})",
-            method,
-            path,
-            query,
-            remote_addr
-        );
-    } else {
-        tracing::info!("--> {} {} (from: {})", method, path, remote_addr);
-    }
+    let span = tracing::info_span!(
+        "request",
+        request_id = %request_id,
+        method = %method,
+        path = %path,
+        client_ip = %remote_addr,
+        user_id = tracing::field::Empty,
+    );
 
-    // Call the next service
-    let res = next.call(req).await?;
-
-    // Log response
-    let elapsed = start.elapsed().as_millis();
-    let status = res.status().as_u16();
-
-    if status >= 400 {
-        tracing::warn!(
-            "<-- {} {} {} ({}ms)",
-            method,
-            path,
-            status,
-            elapsed
-        );
-    } else {
-        tracing::info!(
-            "<-- {} {} {} ({}ms)",
-            method,
-            path,
-            status,
-            elapsed
-        );
-    }
+    async move {
+        // Log request
+        if !query.is_empty() {
+            tracing::info!(
+                "--> {} {} ?{} (from: {})",
+                method,
+                path,
+                query,
+                remote_addr
+            );
+        } else {
+            tracing::info!("--> {} {} (from: {})", method, path, remote_addr);
+        }
+
+        // Call the next service
+        let mut res = with_request_id(request_id.clone(), next.call(req)).await?;
+        if let Ok(value) = HeaderValue::from_str(&request_id) {
+            res.headers_mut().insert(HeaderName::from_static("x-request-id"), value);
+        }
 
-    Ok(res)
+        // Log response
+        let elapsed = start.elapsed().as_millis() as u64;
+        let status = res.status().as_u16();
+
+        if status >= 400 {
+            tracing::warn!(
+                status,
+                elapsed_ms = elapsed,
+                "<-- {} {} {} ({}ms)",
+                method,
+                path,
+                status,
+                elapsed
+            );
+        } else {
+            tracing::info!(
+                status,
+                elapsed_ms = elapsed,
+                "<-- {} {} {} ({}ms)",
+                method,
+                path,
+                status,
+                elapsed
+            );
+        }
+
+        Ok::<_, Error>(res)
+    }
+    .instrument(span)
+    .await
 }
@@ -1,5 +1,11 @@
 pub mod auth;
+pub mod client_ip;
 pub mod logging;
+pub mod rate_limit;
+pub mod request_id;
 
 pub use auth::{AuthMiddleware, AuthenticatedUser};
+pub use client_ip::{ClientIp, TrustedProxies};
 pub use logging::request_logger;
+pub use rate_limit::{RateLimit, RateLimiter};
+pub use request_id::{current_request_id, REQUEST_ID_HEADER};
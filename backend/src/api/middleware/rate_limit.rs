@@ -0,0 +1,258 @@
+use actix_web::{
+    body::EitherBody,
+    dev::{forward_ready, Service, ServiceRequest, ServiceResponse, Transform},
+    Error, HttpMessage, ResponseError,
+};
+use futures::future::{ok, LocalBoxFuture, Ready};
+use std::collections::HashMap;
+use std::rc::Rc;
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+
+use super::client_ip::ClientIp;
+use crate::config::RateLimitConfig;
+use crate::error::AppError;
+use crate::services::auth_service::Claims;
+
+/// Drop idle buckets once the table grows past this many clients
+const MAX_TRACKED_BUCKETS: usize = 10_000;
+
+struct Bucket {
+    tokens: f64,
+    last_refill: Instant,
+}
+
+/// Token buckets keyed by client (IP or user id) and endpoint class
+pub struct RateLimiter {
+    config: RateLimitConfig,
+    buckets: Mutex<HashMap<String, Bucket>>,
+}
+
+impl RateLimiter {
+    pub fn new(config: RateLimitConfig) -> Self {
+        Self {
+            config,
+            buckets: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Endpoint class and its requests per window; login gets the strict limit
+    fn limit_for(&self, path: &str) -> (&'static str, u32) {
+        if path.ends_with("/auth/login") {
+            ("login", self.config.login_requests_per_window)
+        } else {
+            ("api", self.config.requests_per_window)
+        }
+    }
+
+    /// Take one token from the bucket, or return the seconds until one is available
+    fn take(&self, key: String, capacity: u32) -> Result<(), u64> {
+        let capacity = capacity.max(1) as f64;
+        let refill_per_sec = capacity / self.config.window_secs.max(1) as f64;
+        let now = Instant::now();
+
+        let mut buckets = self.buckets.lock().unwrap_or_else(|e| e.into_inner());
+        if buckets.len() > MAX_TRACKED_BUCKETS {
+            buckets.retain(|_, b| {
+                b.tokens + now.duration_since(b.last_refill).as_secs_f64() * refill_per_sec < capacity
+            });
+        }
+
+        let bucket = buckets.entry(key).or_insert(Bucket {
+            tokens: capacity,
+            last_refill: now,
+        });
+
+        let elapsed = now.duration_since(bucket.last_refill).as_secs_f64();
+        bucket.tokens = (bucket.tokens + elapsed * refill_per_sec).min(capacity);
+        bucket.last_refill = now;
+
+        if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            Ok(())
+        } else {
+            Err(((1.0 - bucket.tokens) / refill_per_sec).ceil() as u64)
+        }
+    }
+}
+
+/// Which client identity a `RateLimit` layer counts against
+#[derive(Clone, Copy)]
+enum RateLimitKey {
+    /// Client IP resolved by `request_logger`, falling back to the peer address
+    Ip,
+    /// Authenticated user id; must be wrapped inside `AuthMiddleware`
+    User,
+}
+
+/// Rate limiting middleware. Use `per_ip` on the whole API and `per_user`
+/// inside the authenticated scope, sharing one `RateLimiter`.
+pub struct RateLimit {
+    limiter: Arc<RateLimiter>,
+    key: RateLimitKey,
+}
+
+impl RateLimit {
+    /// Limit by client IP; must be wrapped inside `request_logger`, which resolves the
+    /// IP behind trusted proxies. Outside it only the peer address is used, and requests
+    /// without one are not limited.
+    pub fn per_ip(limiter: Arc<RateLimiter>) -> Self {
+        Self { limiter, key: RateLimitKey::Ip }
+    }
+
+    pub fn per_user(limiter: Arc<RateLimiter>) -> Self {
+        Self { limiter, key: RateLimitKey::User }
+    }
+}
+
+impl<S, B> Transform<S, ServiceRequest> for RateLimit
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = Error;
+    type Transform = RateLimitService<S>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ok(RateLimitService {
+            service: Rc::new(service),
+            limiter: self.limiter.clone(),
+            key: self.key,
+        })
+    }
+}
+
+pub struct RateLimitService<S> {
+    service: Rc<S>,
+    limiter: Arc<RateLimiter>,
+    key: RateLimitKey,
+}
+
+impl<S, B> Service<ServiceRequest> for RateLimitService<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = Error;
+    type Future = LocalBoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let service = self.service.clone();
+
+        if !self.limiter.config.enabled {
+            return Box::pin(async move { Ok(service.call(req).await?.map_into_left_body()) });
+        }
+
+        let (class, capacity) = self.limiter.limit_for(req.path());
+        let client = match self.key {
+            RateLimitKey::Ip => req
+                .extensions()
+                .get::<ClientIp>()
+                .and_then(|ip| ip.0)
+                .or_else(|| req.peer_addr().map(|addr| addr.ip()))
+                .map(|ip| format!("ip:{}", ip)),
+            RateLimitKey::User => req
+                .extensions()
+                .get::<Claims>()
+                .map(|claims| format!("user:{}", claims.sub)),
+        };
+
+        let result = match client {
+            Some(client) => self.limiter.take(format!("{}:{}", class, client), capacity),
+            None => Ok(()),
+        };
+
+        Box::pin(async move {
+            match result {
+                Ok(()) => Ok(service.call(req).await?.map_into_left_body()),
+                Err(retry_after) => {
+                    tracing::warn!("Rate limit exceeded for {} {}", req.method(), req.path());
+                    let response = AppError::RateLimited(retry_after).error_response();
+                    Ok(req.into_response(response).map_into_right_body())
+                }
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::middleware::{request_logger, TrustedProxies};
+    use actix_web::{http::StatusCode, middleware::from_fn, test, web, App, HttpResponse};
+
+    fn config(login: u32) -> RateLimitConfig {
+        RateLimitConfig {
+            enabled: true,
+            window_secs: 60,
+            requests_per_window: 100,
+            login_requests_per_window: login,
+        }
+    }
+
+    #[test]
+    fn test_bucket_exhausts_and_reports_retry_after() {
+        let limiter = RateLimiter::new(config(3));
+        for _ in 0..3 {
+            assert!(limiter.take("login:ip:1.2.3.4".to_string(), 3).is_ok());
+        }
+        // 3 per 60s refills one token every 20s
+        assert_eq!(limiter.take("login:ip:1.2.3.4".to_string(), 3), Err(20));
+
+        // Other clients have their own bucket
+        assert!(limiter.take("login:ip:5.6.7.8".to_string(), 3).is_ok());
+    }
+
+    #[actix_web::test]
+    async fn test_login_is_throttled_after_limit() {
+        let limiter = Arc::new(RateLimiter::new(config(5)));
+        let app = test::init_service(
+            App::new().app_data(web::Data::new(TrustedProxies(1))).service(
+                web::scope("/api/v1")
+                    .wrap(RateLimit::per_ip(limiter))
+                    .wrap(from_fn(request_logger))
+                    .route("/auth/login", web::post().to(|| async { HttpResponse::Ok().finish() }))
+                    .route("/health", web::get().to(|| async { HttpResponse::Ok().finish() })),
+            ),
+        )
+        .await;
+
+        for _ in 0..5 {
+            let req = test::TestRequest::post()
+                .uri("/api/v1/auth/login")
+                .insert_header(("X-Forwarded-For", "10.0.0.1"))
+                .to_request();
+            assert_eq!(test::call_service(&app, req).await.status(), StatusCode::OK);
+        }
+
+        let req = test::TestRequest::post()
+            .uri("/api/v1/auth/login")
+            .insert_header(("X-Forwarded-For", "10.0.0.1"))
+            .to_request();
+        let res = test::call_service(&app, req).await;
+        assert_eq!(res.status(), StatusCode::TOO_MANY_REQUESTS);
+        assert!(res.headers().contains_key("retry-after"));
+
+        // The client behind the trusted proxy is limited, not the proxy
+        let req = test::TestRequest::post()
+            .uri("/api/v1/auth/login")
+            .insert_header(("X-Forwarded-For", "10.0.0.2"))
+            .to_request();
+        assert_eq!(test::call_service(&app, req).await.status(), StatusCode::OK);
+
+        // Read endpoints use the looser limit
+        let req = test::TestRequest::get()
+            .uri("/api/v1/health")
+            .insert_header(("X-Forwarded-For", "10.0.0.1"))
+            .to_request();
+        assert_eq!(test::call_service(&app, req).await.status(), StatusCode::OK);
+    }
+}
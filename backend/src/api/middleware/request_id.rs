@@ -0,0 +1,89 @@
+use actix_web::dev::ServiceRequest;
+use std::future::Future;
+
+/// Header carrying the correlation id, accepted from clients and echoed in responses
+pub const REQUEST_ID_HEADER: &str = "X-Request-Id";
+
+const MAX_REQUEST_ID_LEN: usize = 128;
+
+tokio::task_local! {
+    static REQUEST_ID: String;
+}
+
+/// The client's `X-Request-Id` when it is safe to log, otherwise a new random id
+pub fn request_id_for(req: &ServiceRequest) -> String {
+    req.headers()
+        .get(REQUEST_ID_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .filter(|id| is_valid_request_id(id))
+        .map(str::to_string)
+        .unwrap_or_else(|| hex::encode(rand::random::<[u8; 16]>()))
+}
+
+/// Short ids of `[A-Za-z0-9._-]`, so a client cannot inject text into log lines
+fn is_valid_request_id(id: &str) -> bool {
+    !id.is_empty()
+        && id.len() <= MAX_REQUEST_ID_LEN
+        && id.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+}
+
+/// Run `fut` with `id` as the current request id
+pub async fn with_request_id<F: Future>(id: String, fut: F) -> F::Output {
+    REQUEST_ID.scope(id, fut).await
+}
+
+/// Id of the request being handled, for error bodies
+pub fn current_request_id() -> Option<String> {
+    REQUEST_ID.try_with(|id| id.clone()).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::AppError;
+    use actix_web::{test, web, App};
+
+    #[test]
+    fn test_request_id_validation() {
+        assert!(is_valid_request_id("3f2a-support.42_x"));
+        assert!(!is_valid_request_id(""));
+        assert!(!is_valid_request_id("id with spaces"));
+        assert!(!is_valid_request_id("line\nbreak"));
+        assert!(!is_valid_request_id(&"a".repeat(MAX_REQUEST_ID_LEN + 1)));
+    }
+
+    #[actix_web::test]
+    async fn test_request_id_echoed_in_header_and_error_body() {
+        let app = test::init_service(
+            App::new()
+                .wrap(actix_web::middleware::from_fn(super::super::request_logger))
+                .route(
+                    "/",
+                    web::get().to(|| async {
+                        Err::<String, _>(AppError::NotFound("Transfer not found".to_string()))
+                    }),
+                ),
+        )
+        .await;
+
+        let req = test::TestRequest::get()
+            .uri("/")
+            .insert_header((REQUEST_ID_HEADER, "support-123"))
+            .to_request();
+        let res = test::call_service(&app, req).await;
+        assert_eq!(res.headers().get(REQUEST_ID_HEADER).unwrap(), "support-123");
+        let body: serde_json::Value = test::read_body_json(res).await;
+        assert_eq!(body["request_id"], "support-123");
+
+        // Without a usable header the server generates one
+        let req = test::TestRequest::get()
+            .uri("/")
+            .insert_header((REQUEST_ID_HEADER, "bad id"))
+            .to_request();
+        let res = test::call_service(&app, req).await;
+        let generated = res.headers().get(REQUEST_ID_HEADER).unwrap().to_str().unwrap().to_string();
+        assert_eq!(generated.len(), 32);
+        let body: serde_json::Value = test::read_body_json(res).await;
+        assert_eq!(body["request_id"], generated.as_str());
+    }
+}
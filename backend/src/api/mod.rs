@@ -3,3 +3,108 @@ pub mod middleware;
 pub mod routes;
 
 pub use routes::configure_routes;
+
+use actix_cors::Cors;
+use actix_web::error::JsonPayloadError;
+use actix_web::web;
+
+use crate::config::ServerConfig;
+use crate::error::AppError;
+
+/// CORS policy: only the configured origins, unless any origin is explicitly allowed
+///
+/// Requests from other origins are refused before they reach a handler.
+pub fn cors(config: &ServerConfig) -> Cors {
+    let mut cors = Cors::default()
+        .allow_any_method()
+        .allow_any_header()
+        .expose_headers([middleware::REQUEST_ID_HEADER])
+        .max_age(config.cors_max_age_secs);
+
+    if config.cors_allow_any_origin {
+        cors = cors.allow_any_origin();
+    } else {
+        for origin in &config.cors_allowed_origins {
+            cors = cors.allowed_origin(origin);
+        }
+    }
+    if config.cors_allow_credentials {
+        cors = cors.supports_credentials();
+    }
+    cors
+}
+
+/// JSON extractor settings: bodies over `limit_bytes` are rejected before they
+/// are buffered, and extraction failures come back as validation errors
+pub fn json_config(limit_bytes: usize) -> web::JsonConfig {
+    web::JsonConfig::default()
+        .limit(limit_bytes)
+        .error_handler(|err, _req| AppError::ValidationError(json_error_message(&err)).into())
+}
+
+fn json_error_message(err: &JsonPayloadError) -> String {
+    match err {
+        JsonPayloadError::OverflowKnownLength { length, limit } => format!(
+            "Request body is {} bytes, the limit is {}",
+            length, limit
+        ),
+        JsonPayloadError::Overflow { limit } => {
+            format!("Request body exceeds the limit of {} bytes", limit)
+        }
+        JsonPayloadError::ContentType => "Content-Type must be application/json".to_string(),
+        JsonPayloadError::Deserialize(e) => format!("Invalid JSON: {}", e),
+        other => format!("Invalid request body: {}", other),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::http::header::{ACCESS_CONTROL_ALLOW_ORIGIN, ORIGIN};
+    use actix_web::{test, App, HttpResponse};
+
+    #[actix_web::test]
+    async fn test_cors_blocks_other_origins() {
+        let mut config = crate::config::AppConfig::default().server;
+        config.cors_allowed_origins = vec!["https://wallet.example.com".to_string()];
+        let app = test::init_service(
+            App::new()
+                .wrap(cors(&config))
+                .route("/", web::get().to(HttpResponse::Ok)),
+        )
+        .await;
+
+        let req = test::TestRequest::get()
+            .uri("/")
+            .insert_header((ORIGIN, "https://wallet.example.com"))
+            .to_request();
+        let res = test::call_service(&app, req).await;
+        assert!(res.status().is_success());
+        assert_eq!(
+            res.headers().get(ACCESS_CONTROL_ALLOW_ORIGIN).unwrap(),
+            "https://wallet.example.com"
+        );
+
+        let req = test::TestRequest::get()
+            .uri("/")
+            .insert_header((ORIGIN, "https://evil.example.com"))
+            .to_request();
+        let res = test::try_call_service(&app, req).await;
+        assert!(res.map_or(true, |res| res.status().is_client_error()));
+    }
+
+    #[test]
+    fn test_json_error_message_reports_sizes() {
+        let err = JsonPayloadError::OverflowKnownLength {
+            length: 2_000_000,
+            limit: 1_048_576,
+        };
+        assert_eq!(
+            json_error_message(&err),
+            "Request body is 2000000 bytes, the limit is 1048576"
+        );
+
+        let err = JsonPayloadError::Deserialize(serde_json::from_str::<u32>("\"x\"").unwrap_err());
+        assert!(json_error_message(&err).starts_with("Invalid JSON: "));
+    }
+}
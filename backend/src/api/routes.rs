@@ -2,62 +2,121 @@ use actix_web::web;
 use std::sync::Arc;
 
 use super::handlers;
-use super::middleware::AuthMiddleware;
+use super::middleware::{AuthMiddleware, RateLimit, RateLimiter};
 use crate::services::AuthService;
 
-pub fn configure_routes(cfg: &mut web::ServiceConfig, auth_service: Arc<AuthService>) {
+pub fn configure_routes(
+    cfg: &mut web::ServiceConfig,
+    auth_service: Arc<AuthService>,
+    rate_limiter: Arc<RateLimiter>,
+) {
+    // Load balancer probes and Prometheus scraping, outside the API scope and its rate limit
+    cfg.route("/health", web::get().to(handlers::health))
+        .route("/ready", web::get().to(handlers::ready))
+        .route("/metrics", web::get().to(handlers::metrics));
+
     cfg.service(
         web::scope("/api/v1")
+            .wrap(RateLimit::per_ip(rate_limiter.clone()))
             // Public routes
             .route("/auth/login", web::post().to(handlers::login))
-            .route("/health", web::get().to(health_check))
+            .route("/auth/refresh", web::post().to(handlers::refresh_token))
+            .route("/health", web::get().to(handlers::health))
             // Protected routes
             .service(
                 web::scope("")
+                    // Registered before AuthMiddleware so it runs after it and sees the user
+                    .wrap(RateLimit::per_user(rate_limiter))
                     .wrap(AuthMiddleware { auth_service })
                     // Auth routes
                     .route("/auth/logout", web::post().to(handlers::logout))
+                    .route("/auth/logout-all", web::post().to(handlers::logout_all))
                     .route("/auth/password", web::put().to(handlers::change_password))
                     .route("/auth/me", web::get().to(handlers::me))
+                    .route("/audit", web::get().to(handlers::list_audit_logs))
                     // Wallet routes
                     .route("/wallets", web::get().to(handlers::list_wallets))
                     .route("/wallets", web::post().to(handlers::create_wallet))
                     .route("/wallets/import", web::post().to(handlers::import_wallet))
+                    .route("/wallets/import-mnemonic", web::post().to(handlers::import_mnemonic_wallet))
+                    .route("/wallets/import-viewing-key", web::post().to(handlers::import_viewing_key_wallet))
+                    .route("/wallets/import-keystore", web::post().to(handlers::import_keystore_wallet))
                     .route("/wallets/balance", web::get().to(handlers::get_balance))
+                    .route("/wallets/verify-keys", web::post().to(handlers::verify_wallet_keys))
+                    .route("/wallets/rotate-encryption-key", web::post().to(handlers::rotate_encryption_key))
                     .route("/wallets/{id}", web::get().to(handlers::get_wallet))
                     .route("/wallets/{id}", web::delete().to(handlers::delete_wallet))
                     .route("/wallets/{id}/activate", web::put().to(handlers::set_active_wallet))
+                    .route("/wallets/{id}/export", web::get().to(handlers::export_wallet_activity))
+                    .route("/wallets/{id}/deposits", web::get().to(handlers::list_wallet_deposits))
+                    .route("/wallets/{id}/balance", web::get().to(handlers::get_wallet_balance))
+                    .route("/wallets/{id}/addresses", web::get().to(handlers::list_derived_addresses))
+                    .route("/wallets/{id}/addresses", web::post().to(handlers::derive_address))
+                    .route("/wallets/{id}/tags", web::put().to(handlers::set_wallet_tags))
+                    .route("/wallets/{id}/tags", web::delete().to(handlers::clear_wallet_tags))
+                    .route("/wallets/{id}/spending-limits", web::get().to(handlers::list_spending_limits))
+                    .route("/wallets/{id}/spending-limits", web::put().to(handlers::set_spending_limit))
+                    .route("/wallets/{id}/spending-limits/{token}", web::delete().to(handlers::delete_spending_limit))
                     .route("/wallets/{id}/export-key", web::post().to(handlers::export_private_key))
+                    .route("/wallets/{id}/export-keystore", web::post().to(handlers::export_keystore))
+                    .route("/wallets/{id}/sign-message", web::post().to(handlers::sign_message))
+                    .route("/wallets/{id}/sign-typed-data", web::post().to(handlers::sign_typed_data))
                     // Transfer routes
                     .route("/transfers", web::get().to(handlers::list_transfers))
                     .route("/transfers", web::post().to(handlers::initiate_transfer))
+                    .route("/transfers/batch", web::post().to(handlers::batch_transfer))
                     .route("/transfers/estimate-gas", web::post().to(handlers::estimate_gas))
+                    .route("/transfers/estimate", web::get().to(handlers::preview_transfer_fee))
                     .route("/transfers/{id}", web::get().to(handlers::get_transfer))
                     .route("/transfers/{id}/execute", web::post().to(handlers::execute_transfer))
+                    .route("/transfers/{id}/approve", web::post().to(handlers::approve_transfer))
+                    .route("/transfers/{id}/reject", web::post().to(handlers::reject_transfer))
+                    .route("/transfers/{id}/override-limit", web::post().to(handlers::override_spending_limit))
+                    .route("/transfers/{id}/bump-fee", web::post().to(handlers::bump_transfer_fee))
+                    // Address book routes
+                    .route("/address-book", web::get().to(handlers::list_address_book))
+                    .route("/address-book", web::post().to(handlers::add_address_book_entry))
+                    .route("/address-book/{id}", web::delete().to(handlers::delete_address_book_entry))
                     // Chain routes
                     .route("/chains", web::get().to(handlers::list_chains))
+                    .route("/chains/{chain}/tokens/{symbol}/refresh-metadata", web::post().to(handlers::refresh_token_metadata))
+                    .route("/names/resolve", web::get().to(handlers::resolve_name))
                     // Settings routes
                     .route("/settings/rpc/presets", web::get().to(handlers::get_rpc_presets))
                     .route("/settings/rpc", web::get().to(handlers::get_rpc_config))
                     .route("/settings/rpc", web::put().to(handlers::update_rpc_config))
                     .route("/settings/rpc/test", web::post().to(handlers::test_rpc_endpoint))
+                    .route("/settings/zcash-rpc", web::get().to(handlers::get_zcash_rpc_config))
+                    .route("/settings/zcash-rpc", web::put().to(handlers::update_zcash_rpc_config))
+                    .route("/settings/tokens/{chain}", web::get().to(handlers::get_token_allowlist))
+                    .route("/settings/tokens/{chain}", web::post().to(handlers::add_allowlist_token))
+                    .route("/settings/tokens/{chain}/{contract}", web::delete().to(handlers::remove_allowlist_token))
                     // Orchard (Zcash privacy) routes
                     .route("/wallets/{id}/orchard/enable", web::post().to(handlers::enable_orchard))
                     .route("/wallets/{id}/orchard/addresses", web::get().to(handlers::get_unified_addresses))
                     .route("/wallets/{id}/orchard/balance", web::get().to(handlers::get_shielded_balance))
                     .route("/wallets/{id}/orchard/balance/combined", web::get().to(handlers::get_combined_balance))
+                    .route("/wallets/{id}/transactions", web::get().to(handlers::get_wallet_transactions))
                     .route("/wallets/{id}/orchard/notes", web::get().to(handlers::get_unspent_notes))
+                    .route("/wallets/{id}/orchard/consolidate", web::post().to(handlers::consolidate_notes))
+                    .route("/wallets/{id}/orchard/sync/pause", web::post().to(handlers::pause_wallet_sync))
+                    .route("/wallets/{id}/orchard/sync/resume", web::post().to(handlers::resume_wallet_sync))
+                    .route("/wallets/{id}/orchard/rescan", web::post().to(handlers::rescan_wallet))
+                    .route("/wallets/{id}/orchard/rescan", web::get().to(handlers::get_rescan_status))
+                    .route("/orchard/notes", web::get().to(handlers::list_orchard_notes))
+                    .route("/orchard/notes/{id}/memo", web::get().to(handlers::get_note_memo))
+                    .route("/orchard/transfer/estimate", web::post().to(handlers::estimate_orchard_transfer))
                     .route("/zcash/scan/status", web::get().to(handlers::get_scan_progress))
                     .route("/zcash/scan/sync", web::post().to(handlers::sync_orchard))
+                    .route("/zcash/scan/stream", web::get().to(handlers::stream_sync_progress))
+                    .route("/zcash/{id}/validate-witnesses", web::post().to(handlers::validate_witnesses))
+                    .route("/zcash/witness-health", web::get().to(handlers::get_witness_health))
+                    .route("/zcash/{id}/refresh-witnesses", web::post().to(handlers::refresh_witnesses))
                     .route("/transfers/orchard", web::post().to(handlers::initiate_orchard_transfer))
-                    .route("/transfers/orchard/{id}/execute", web::post().to(handlers::execute_orchard_transfer)),
+                    .route("/transfers/orchard/{id}/execute", web::post().to(handlers::execute_orchard_transfer))
+                    .route("/transfers/orchard/{id}/export", web::post().to(handlers::export_orchard_transfer))
+                    .route("/transfers/orchard/sign", web::post().to(handlers::sign_orchard_package))
+                    .route("/transfers/orchard/broadcast", web::post().to(handlers::broadcast_orchard_transfer)),
             ),
     );
 }
-
-async fn health_check() -> actix_web::HttpResponse {
-    actix_web::HttpResponse::Ok().json(serde_json::json!({
-        "status": "ok",
-        "version": env!("CARGO_PKG_VERSION")
-    }))
-}
@@ -1,30 +1,188 @@
 use async_trait::async_trait;
 use ethers::prelude::*;
 use ethers::providers::{Http, Provider};
-use ethers::utils::{format_units, parse_units};
+use ethers::utils::to_checksum;
 use reqwest::Proxy;
 use rust_decimal::Decimal;
-use std::str::FromStr;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
+use std::time::Duration;
 use tokio::sync::RwLock;
 use url::Url;
 
-use crate::blockchain::traits::{ChainClient, GasEstimate, TokenBalance, TransferParams, TxStatus};
-use crate::config::EthereumConfig;
+use crate::blockchain::traits::{
+    ChainClient, GasEstimate, SignedTransfer, TokenBalance, TokenMetadata, TransferParams, TxStatus,
+};
+use crate::config::{EthereumConfig, EvmChainConfig};
+use crate::crypto::units::{
+    decimal_to_units, format_units, units_to_decimal, ETH_DECIMALS, GWEI_DECIMALS,
+};
 use crate::error::{AppError, AppResult};
 
-use super::tokens::{get_token_info, SUPPORTED_TOKENS};
+use super::ens::{is_ens_name, normalize_name, EnsCache, ENS_CACHE_TTL};
+use super::multicall::{balance_calls, decode_balances, Multicall3, MULTICALL3_ADDRESS};
+use super::nonce::NonceManager;
+use super::rpc_health::{EndpointStatus, RpcHealth, TrackedHttp, RPC_DEMOTE_COOLDOWN};
+use super::token_metadata::TokenMetadataCache;
+use super::tokens::{get_token_info, parse_allowlist, TokenInfo, SUPPORTED_TOKENS};
 
-// ERC20 ABI for balanceOf and transfer
+/// Provider whose calls are counted in the endpoint's health
+type RpcProvider = Provider<TrackedHttp>;
+
+// ERC20 ABI for balanceOf, transfer and metadata
 abigen!(
     ERC20,
     r#"[
         function balanceOf(address account) external view returns (uint256)
         function transfer(address to, uint256 amount) external returns (bool)
         function decimals() external view returns (uint8)
+        function symbol() external view returns (string)
     ]"#
 );
 
+/// Chain IDs of public test networks (Goerli, Holesky, Sepolia and the Sepolia
+/// rollups, BSC testnet, Polygon Mumbai/Amoy) and of local dev nodes
+const TESTNET_CHAIN_IDS: [u64; 11] = [5, 97, 1337, 17000, 31337, 80001, 80002, 84532, 421614, 11155111, 11155420];
+
+fn is_testnet_chain_id(chain_id: u64) -> bool {
+    TESTNET_CHAIN_IDS.contains(&chain_id)
+}
+
+/// Sign a legacy (type 0) transaction with EIP-155 replay protection
+///
+/// Returns the RLP-encoded signed transaction, ready for `eth_sendRawTransaction`.
+/// Fails rather than emit a pre-EIP-155 signature whose `v` does not commit to `chain_id`.
+pub fn sign_legacy_transaction(
+    wallet: &LocalWallet,
+    tx: TransactionRequest,
+    chain_id: u64,
+) -> AppResult<Bytes> {
+    let tx: TypedTransaction = tx.chain_id(chain_id).into();
+    let signature = wallet
+        .clone()
+        .with_chain_id(chain_id)
+        .sign_transaction_sync(&tx)
+        .map_err(|e| AppError::InternalError(format!("Failed to sign transaction: {}", e)))?;
+
+    if signature.v != chain_id * 2 + 35 && signature.v != chain_id * 2 + 36 {
+        return Err(AppError::InternalError(format!(
+            "Signature v={} is not EIP-155 protected for chain {}",
+            signature.v, chain_id
+        )));
+    }
+
+    Ok(tx.rlp_signed(&signature))
+}
+
+/// EIP-1559 native transfer paying `fees` (max fee, priority fee), without a nonce
+fn native_transfer_request(
+    to: Address,
+    value: U256,
+    chain_id: u64,
+    (max_fee, priority_fee): (U256, U256),
+    params: &TransferParams,
+) -> AppResult<TypedTransaction> {
+    let mut tx = Eip1559TransactionRequest::new()
+        .to(to)
+        .value(value)
+        .chain_id(chain_id)
+        .max_fee_per_gas(max_fee)
+        .max_priority_fee_per_gas(priority_fee);
+
+    // If user specified gas_price, use it as max_fee
+    if let Some(gas_price) = &params.gas_price_gwei {
+        let gas_price_wei = decimal_to_units(*gas_price, GWEI_DECIMALS)?;
+        // Override EIP-1559 params with user-specified max fee
+        tx = tx.max_fee_per_gas(gas_price_wei).max_priority_fee_per_gas(priority_fee);
+    }
+
+    if let Some(gas_limit) = params.gas_limit {
+        tx = tx.gas(gas_limit);
+    }
+
+    Ok(tx.into())
+}
+
+/// ERC20 `transfer` call on `contract` paying `fees`, without a sender or nonce
+fn token_transfer_request<M: Middleware>(
+    contract: &ERC20<M>,
+    to: Address,
+    amount: U256,
+    chain_id: u64,
+    (max_fee, priority_fee): (U256, U256),
+    params: &TransferParams,
+) -> AppResult<TypedTransaction> {
+    let mut call = contract.transfer(to, amount);
+    call.tx.set_chain_id(chain_id);
+
+    if let Some(gas_limit) = params.gas_limit {
+        call = call.gas(gas_limit);
+    }
+
+    // Apply EIP-1559 gas settings
+    if let Some(gas_price) = &params.gas_price_gwei {
+        // User specified gas price - use as max_fee
+        let gas_price_wei = decimal_to_units(*gas_price, GWEI_DECIMALS)?;
+        call = call.gas_price(gas_price_wei);
+    } else {
+        // Use optimized EIP-1559 parameters
+        // Note: For contract calls, we need to use legacy gas_price or build tx manually
+        // Using effective gas price = max_fee for simplicity
+        call = call.gas_price(max_fee);
+        tracing::info!(
+            "Token transfer using optimized gas - max_fee: {} Gwei, priority_fee: {} Gwei",
+            format_units(max_fee, GWEI_DECIMALS),
+            format_units(priority_fee, GWEI_DECIMALS)
+        );
+    }
+
+    Ok(call.tx)
+}
+
+/// Nodes only accept a replacement transaction paying at least this much more
+const MIN_FEE_BUMP_PERCENT: u64 = 10;
+
+/// Smallest fee a node accepts to replace a transaction paying `old`
+fn min_replacement_fee(old: U256) -> U256 {
+    (old * (100 + MIN_FEE_BUMP_PERCENT) + 99) / 100
+}
+
+/// Reject a replacement fee below the node's minimum bump
+fn check_fee_bump(old: U256, new: U256) -> AppResult<()> {
+    let min = min_replacement_fee(old);
+    if new < min {
+        return Err(AppError::ValidationError(format!(
+            "New fee {} Gwei must be at least {}% above the original {} Gwei (minimum {} Gwei)",
+            format_units(new, GWEI_DECIMALS),
+            MIN_FEE_BUMP_PERCENT,
+            format_units(old, GWEI_DECIMALS),
+            format_units(min, GWEI_DECIMALS)
+        )));
+    }
+    Ok(())
+}
+
+/// Max fee and tip for an EIP-1559 replacement; both have to clear the node's bump
+///
+/// The max fee is raised to the bumped tip when the requested one falls below it,
+/// since a tip above the max fee would be capped and the replacement rejected.
+fn replacement_fees(old_max_fee: U256, old_priority_fee: U256, new_max_fee: U256) -> AppResult<(U256, U256)> {
+    check_fee_bump(old_max_fee, new_max_fee)?;
+    let priority_fee = min_replacement_fee(old_priority_fee);
+    Ok((new_max_fee.max(priority_fee), priority_fee))
+}
+
+/// Map an `eth_estimateGas` failure; a simulated revert means the transfer itself is invalid
+fn estimate_error(e: impl std::fmt::Display) -> AppError {
+    let message = e.to_string();
+    let lower = message.to_lowercase();
+    if lower.contains("revert") || lower.contains("insufficient funds") || lower.contains("exceeds balance") {
+        AppError::ValidationError(format!("Transfer would revert: {}", message))
+    } else {
+        AppError::BlockchainError(format!("Failed to estimate gas: {}", message))
+    }
+}
+
 /// Dynamic RPC configuration that can be updated at runtime
 pub struct RpcSettings {
     pub primary_rpc: String,
@@ -35,6 +193,28 @@ pub struct RpcSettings {
 pub struct EthereumClient {
     rpc_settings: RwLock<RpcSettings>,
     chain_id: u64,
+    /// Registry key, e.g. "ethereum" or "polygon"
+    key: String,
+    name: String,
+    native_symbol: String,
+    /// The built-in `ethereum` client; only it gets the mainnet ERC20 list and ENS
+    ethereum_l1: bool,
+    /// Local nonce cache so concurrent transfers from one wallet get distinct nonces
+    nonces: NonceManager,
+    /// Recent ENS forward and reverse resolutions
+    ens_cache: EnsCache,
+    /// Error rates and latency per RPC endpoint, used to order failover
+    rpc_health: Arc<RpcHealth>,
+    /// Set once Multicall3 turns out not to be deployed, so balances are read one by one
+    multicall_unavailable: AtomicBool,
+    /// ERC20 decimals and symbol, read once per contract
+    token_metadata: TokenMetadataCache,
+    /// Token contracts reported in balances; empty falls back to `SUPPORTED_TOKENS`
+    token_allowlist: RwLock<Vec<Address>>,
+    /// Block depth before a transfer counts as confirmed
+    confirmations: u64,
+    /// How long a submitted transaction is waited for to be mined; None never gives up
+    pending_timeout: Option<Duration>,
 }
 
 impl EthereumClient {
@@ -57,35 +237,136 @@ impl EthereumClient {
             .map_err(|e| AppError::BlockchainError(format!("Failed to create HTTP client: {}", e)))
     }
 
-    /// Create a provider with the given RPC URL and optional proxy
-    fn create_provider_with_proxy(rpc_url: &str, proxy_url: &Option<String>) -> AppResult<Provider<Http>> {
+    /// Create an HTTP transport with the given RPC URL and optional proxy
+    fn create_http_transport(rpc_url: &str, proxy_url: &Option<String>) -> AppResult<Http> {
         let client = Self::create_http_client(proxy_url)?;
         let url = Url::parse(rpc_url)
             .map_err(|e| AppError::BlockchainError(format!("Invalid RPC URL: {}", e)))?;
-        let http = Http::new_with_client(url, client);
-        Ok(Provider::new(http))
+        Ok(Http::new_with_client(url, client))
     }
+
+    /// Create a provider with the given RPC URL and optional proxy
+    fn create_provider_with_proxy(rpc_url: &str, proxy_url: &Option<String>) -> AppResult<Provider<Http>> {
+        Ok(Provider::new(Self::create_http_transport(rpc_url, proxy_url)?))
+    }
+}
+
+/// Chain identity and confirmation policy an `EthereumClient` is built with
+struct ChainSettings<'a> {
+    chain_id: u64,
+    /// Registry key, e.g. "ethereum" or "polygon"
+    key: &'a str,
+    name: &'a str,
+    native_symbol: &'a str,
+    ethereum_l1: bool,
+    confirmations: u64,
+    /// Zero waits for a pending transaction indefinitely
+    pending_timeout_minutes: u64,
 }
 
 impl EthereumClient {
     pub fn new(config: &EthereumConfig) -> AppResult<Self> {
+        Self::build(
+            RpcSettings {
+                primary_rpc: config.rpc_url.clone(),
+                fallback_rpcs: config.fallback_rpcs.clone(),
+                rpc_proxy: config.rpc_proxy.clone(),
+            },
+            ChainSettings {
+                chain_id: config.chain_id,
+                key: "ethereum",
+                name: "Ethereum Mainnet",
+                native_symbol: "ETH",
+                ethereum_l1: true,
+                confirmations: config.confirmations,
+                pending_timeout_minutes: config.pending_timeout_minutes,
+            },
+        )
+    }
+
+    /// Client for an additional EVM chain registered under `key` (e.g. "polygon")
+    pub fn for_evm_chain(key: &str, config: &EvmChainConfig) -> AppResult<Self> {
+        Self::build(
+            RpcSettings {
+                primary_rpc: config.rpc_url.clone(),
+                fallback_rpcs: config.fallback_rpcs.clone(),
+                rpc_proxy: config.rpc_proxy.clone(),
+            },
+            ChainSettings {
+                chain_id: config.chain_id,
+                key,
+                name: config.name.as_deref().unwrap_or(key),
+                native_symbol: &config.native_symbol,
+                ethereum_l1: false,
+                confirmations: config.confirmations,
+                pending_timeout_minutes: config.pending_timeout_minutes,
+            },
+        )
+    }
+
+    fn build(rpc_settings: RpcSettings, chain: ChainSettings<'_>) -> AppResult<Self> {
+        let ChainSettings {
+            chain_id,
+            key,
+            name,
+            native_symbol,
+            ethereum_l1,
+            confirmations,
+            pending_timeout_minutes,
+        } = chain;
+
         // Validate the initial RPC URL with proxy
-        Self::create_provider_with_proxy(&config.rpc_url, &config.rpc_proxy)?;
+        Self::create_provider_with_proxy(&rpc_settings.primary_rpc, &rpc_settings.rpc_proxy)?;
 
-        if config.rpc_proxy.is_some() {
-            tracing::info!("RPC proxy enabled: {}", config.rpc_proxy.as_ref().unwrap());
+        if let Some(proxy) = &rpc_settings.rpc_proxy {
+            tracing::info!("RPC proxy enabled for {}: {}", key, proxy);
         }
 
         Ok(Self {
-            rpc_settings: RwLock::new(RpcSettings {
-                primary_rpc: config.rpc_url.clone(),
-                fallback_rpcs: config.fallback_rpcs.clone(),
-                rpc_proxy: config.rpc_proxy.clone(),
-            }),
-            chain_id: config.chain_id,
+            rpc_settings: RwLock::new(rpc_settings),
+            chain_id,
+            key: key.to_string(),
+            name: name.to_string(),
+            native_symbol: native_symbol.to_uppercase(),
+            ethereum_l1,
+            nonces: NonceManager::new(),
+            ens_cache: EnsCache::new(ENS_CACHE_TTL),
+            rpc_health: Arc::new(RpcHealth::new(RPC_DEMOTE_COOLDOWN)),
+            multicall_unavailable: AtomicBool::new(false),
+            token_metadata: TokenMetadataCache::new(),
+            token_allowlist: RwLock::new(Vec::new()),
+            confirmations,
+            pending_timeout: (pending_timeout_minutes > 0)
+                .then(|| Duration::from_secs(pending_timeout_minutes * 60)),
         })
     }
 
+    /// Fail if the RPC serves a different network than the configured `chain_id`
+    pub async fn verify_chain_id(&self) -> AppResult<()> {
+        let provider = self.get_provider().await?;
+        let rpc_chain_id = provider
+            .get_chainid()
+            .await
+            .map_err(|e| AppError::BlockchainError(format!("Failed to get chain id: {}", e)))?;
+
+        if rpc_chain_id != U256::from(self.chain_id) {
+            return Err(AppError::ConfigError(format!(
+                "{} is configured with chain_id {} but its RPC reports {}",
+                self.key, self.chain_id, rpc_chain_id
+            )));
+        }
+        Ok(())
+    }
+
+    /// ERC20 token by symbol, if this chain supports it
+    fn token_info(&self, symbol: &str) -> Option<&'static TokenInfo> {
+        if self.ethereum_l1 {
+            get_token_info(symbol)
+        } else {
+            None
+        }
+    }
+
     /// Update RPC configuration dynamically (no restart required)
     pub async fn update_rpc(&self, primary_rpc: String, fallback_rpcs: Option<Vec<String>>) -> AppResult<()> {
         // Get current proxy setting
@@ -106,6 +387,7 @@ impl EthereumClient {
         if let Some(fallbacks) = fallback_rpcs {
             settings.fallback_rpcs = fallbacks;
         }
+        self.rpc_health.retain(&Self::endpoints(&settings));
 
         tracing::info!("RPC updated dynamically to: {}", primary_rpc);
         Ok(())
@@ -116,55 +398,102 @@ impl EthereumClient {
         self.rpc_settings.read().await.primary_rpc.clone()
     }
 
-    /// Try to get a working provider, falling back to alternative RPCs if needed
-    async fn get_provider(&self) -> AppResult<Arc<Provider<Http>>> {
+    /// The endpoint that served the latest request, which differs from the primary during failover
+    pub fn get_active_rpc(&self) -> Option<String> {
+        self.rpc_health.active()
+    }
+
+    /// Error rate, latency and demotion state of every configured endpoint
+    pub async fn get_rpc_health(&self) -> Vec<EndpointStatus> {
         let settings = self.rpc_settings.read().await;
-        let start = std::time::Instant::now();
-        let proxy = &settings.rpc_proxy;
+        self.rpc_health.status(&Self::endpoints(&settings))
+    }
 
-        tracing::debug!("Attempting to connect to primary RPC: {} (proxy: {:?})", settings.primary_rpc, proxy);
+    /// EIP-1559 transaction for a native transfer, without a nonce
+    async fn native_transfer_tx(
+        &self,
+        provider: &RpcProvider,
+        params: &TransferParams,
+    ) -> AppResult<TypedTransaction> {
+        let to = self.resolve_recipient(provider, &params.to_address).await?;
+        let value = decimal_to_units(params.amount, ETH_DECIMALS)?;
 
-        // First try the primary RPC
-        if let Ok(provider) = Self::create_provider_with_proxy(&settings.primary_rpc, proxy) {
-            match provider.get_block_number().await {
-                Ok(block) => {
-                    let elapsed = start.elapsed().as_millis();
-                    tracing::debug!(
-                        "Connected to primary RPC {} (block: {}, latency: {}ms)",
-                        settings.primary_rpc,
-                        block,
-                        elapsed
-                    );
-                    return Ok(Arc::new(provider));
-                }
+        // Use EIP-1559 transaction for better gas efficiency
+        let fees = self.calculate_eip1559_fees(provider).await?;
+
+        native_transfer_request(to, value, self.chain_id, fees, params)
+    }
+
+    /// ERC20 `transfer` call for a token transfer, without a sender or nonce
+    async fn token_transfer_tx(
+        &self,
+        provider: &Arc<RpcProvider>,
+        params: &TransferParams,
+    ) -> AppResult<TypedTransaction> {
+        let token_info = self.token_info(&params.token)
+            .ok_or_else(|| AppError::NotFound(format!("Token {} not supported", params.token)))?;
+
+        let token_addr = self.parse_address(&token_info.contract_address)?;
+        let to = self.resolve_recipient(provider, &params.to_address).await?;
+
+        let amount = decimal_to_units(params.amount, token_info.decimals as u32)?;
+
+        let contract = ERC20::new(token_addr, provider.clone());
+
+        // Calculate optimal EIP-1559 gas parameters
+        let fees = self.calculate_eip1559_fees(provider).await?;
+
+        token_transfer_request(&contract, to, amount, self.chain_id, fees, params)
+    }
+
+    /// Reserve the next nonce for `from`; release it if the transaction is not broadcast
+    async fn reserve_nonce(&self, provider: &RpcProvider, from: Address) -> AppResult<U256> {
+        self.nonces
+            .reserve(from, async {
+                provider
+                    .get_transaction_count(from, Some(BlockNumber::Pending.into()))
+                    .await
+                    .map_err(|e| AppError::BlockchainError(format!("Failed to get nonce: {}", e)))
+            })
+            .await
+    }
+
+    /// Try each configured RPC in health order and return the first one that answers
+    ///
+    /// Failing endpoints are demoted for a cooldown so later calls skip them; the
+    /// primary is preferred again as soon as it is healthy.
+    async fn get_provider(&self) -> AppResult<Arc<RpcProvider>> {
+        let settings = self.rpc_settings.read().await;
+        let proxy = &settings.rpc_proxy;
+        let endpoints = Self::endpoints(&settings);
+        let previous_active = self.rpc_health.active();
+
+        for rpc_url in self.rpc_health.order(&endpoints) {
+            let start = std::time::Instant::now();
+            // Every call made through the provider counts towards the endpoint's health
+            let provider = match Self::create_http_transport(&rpc_url, proxy) {
+                Ok(http) => Provider::new(TrackedHttp::new(http, &rpc_url, self.rpc_health.clone())),
                 Err(e) => {
-                    tracing::warn!(
-                        "Primary RPC {} unavailable: {}",
-                        settings.primary_rpc,
-                        e
-                    );
+                    tracing::warn!("Skipping RPC {}: {}", rpc_url, e);
+                    continue;
                 }
-            }
-        }
+            };
 
-        // Try fallback RPCs
-        for rpc_url in &settings.fallback_rpcs {
-            tracing::debug!("Trying fallback RPC: {}", rpc_url);
-            if let Ok(provider) = Self::create_provider_with_proxy(rpc_url, proxy) {
-                match provider.get_block_number().await {
-                    Ok(block) => {
-                        let elapsed = start.elapsed().as_millis();
+            match provider.get_block_number().await {
+                Ok(block) => {
+                    if previous_active.as_deref() != Some(rpc_url.as_str()) {
                         tracing::info!(
-                            "Using fallback RPC {} (block: {}, latency: {}ms)",
+                            "Using {} RPC {} (block: {}, latency: {}ms)",
+                            if rpc_url == settings.primary_rpc { "primary" } else { "fallback" },
                             rpc_url,
                             block,
-                            elapsed
+                            start.elapsed().as_millis()
                         );
-                        return Ok(Arc::new(provider));
-                    }
-                    Err(e) => {
-                        tracing::warn!("Fallback RPC {} failed: {}", rpc_url, e);
                     }
+                    return Ok(Arc::new(provider));
+                }
+                Err(e) => {
+                    tracing::warn!("RPC {} unavailable: {}", rpc_url, e);
                 }
             }
         }
@@ -175,12 +504,49 @@ impl EthereumClient {
         ))
     }
 
+    /// Primary first, then fallbacks in configured order
+    fn endpoints(settings: &RpcSettings) -> Vec<String> {
+        std::iter::once(settings.primary_rpc.clone())
+            .chain(settings.fallback_rpcs.iter().cloned())
+            .collect()
+    }
+
     fn parse_address(&self, address: &str) -> AppResult<Address> {
         address
             .parse::<Address>()
             .map_err(|e| AppError::ValidationError(format!("Invalid address: {}", e)))
     }
 
+    /// Parse a recipient, resolving ENS names (`*.eth`) through the registry and resolver
+    async fn resolve_recipient(&self, provider: &RpcProvider, value: &str) -> AppResult<Address> {
+        if !self.ethereum_l1 || !is_ens_name(value) {
+            return self.parse_address(value);
+        }
+
+        let name = normalize_name(value);
+        if let Some(address) = self.ens_cache.get_address(&name) {
+            return Ok(address);
+        }
+
+        let address = provider.resolve_name(&name).await.map_err(|e| match e {
+            ProviderError::EnsError(_) | ProviderError::EnsNotOwned(_) => {
+                AppError::ValidationError(format!("ENS name {} has no resolver", name))
+            }
+            e => AppError::BlockchainError(format!("Failed to resolve ENS name {}: {}", name, e)),
+        })?;
+
+        if address.is_zero() {
+            return Err(AppError::ValidationError(format!(
+                "ENS name {} has no address record",
+                name
+            )));
+        }
+
+        tracing::info!("Resolved ENS name {} to {:?}", name, address);
+        self.ens_cache.put_address(&name, address);
+        Ok(address)
+    }
+
     fn parse_private_key(&self, key: &str) -> AppResult<LocalWallet> {
         let key = key.strip_prefix("0x").unwrap_or(key);
         key.parse::<LocalWallet>()
@@ -201,18 +567,217 @@ impl EthereumClient {
             .await
             .map_err(|e| AppError::BlockchainError(format!("Failed to get token balance: {}", e)))?;
 
-        let decimals = contract
-            .decimals()
+        let decimals = match self.get_token_metadata(&contract).await {
+            Ok(metadata) => metadata.decimals,
+            Err(e) => {
+                tracing::warn!("Assuming 18 decimals for {:?}: {}", token_addr, e);
+                18
+            }
+        };
+
+        Ok((balance, decimals))
+    }
+
+    /// Cached `decimals()` and `symbol()`; only a successful read is cached
+    async fn get_token_metadata(&self, contract: &ERC20<RpcProvider>) -> AppResult<TokenMetadata> {
+        let address = contract.address();
+        if let Some(metadata) = self.token_metadata.get(self.chain_id, &address) {
+            return Ok(metadata);
+        }
+
+        let decimals_call = contract.decimals();
+        let symbol_call = contract.symbol();
+        let (decimals, symbol) = tokio::join!(decimals_call.call(), symbol_call.call());
+        let decimals = decimals
+            .map_err(|e| AppError::BlockchainError(format!("Failed to get token decimals: {}", e)))?;
+        // Some older tokens return bytes32 instead of a string symbol
+        let symbol = symbol.unwrap_or_default();
+
+        let metadata = TokenMetadata { symbol, decimals };
+        self.token_metadata.put(self.chain_id, address, metadata.clone());
+        Ok(metadata)
+    }
+
+    /// Tokens whose balances are reported: the allowlist when set, else the built-in list
+    async fn tracked_tokens(&self) -> AppResult<Vec<TokenInfo>> {
+        let allowlist = self.token_allowlist.read().await.clone();
+        if allowlist.is_empty() {
+            if !self.ethereum_l1 {
+                return Ok(Vec::new());
+            }
+            return Ok(SUPPORTED_TOKENS.values().cloned().collect());
+        }
+
+        let provider = self.get_provider().await?;
+        let mut tokens = Vec::with_capacity(allowlist.len());
+        for contract_address in allowlist {
+            let contract = ERC20::new(contract_address, provider.clone());
+            match self.get_token_metadata(&contract).await {
+                Ok(metadata) => {
+                    let contract_address = to_checksum(&contract_address, None);
+                    let symbol = if metadata.symbol.is_empty() {
+                        contract_address.clone()
+                    } else {
+                        metadata.symbol
+                    };
+                    tokens.push(TokenInfo {
+                        name: symbol.clone(),
+                        symbol,
+                        contract_address,
+                        decimals: metadata.decimals,
+                    });
+                }
+                Err(e) => tracing::warn!("Skipping allowlisted token {:?}: {}", contract_address, e),
+            }
+        }
+        Ok(tokens)
+    }
+
+    /// Read the native and every token balance in a single Multicall3 `aggregate3` call
+    ///
+    /// Returns `None` when the batch cannot be used, so the caller falls back to one call
+    /// per balance.
+    async fn get_balances_multicall(
+        &self,
+        address: &str,
+        tokens: &[TokenInfo],
+    ) -> Option<(Decimal, Vec<TokenBalance>)> {
+        if tokens.is_empty() || self.multicall_unavailable.load(Ordering::Relaxed) {
+            return None;
+        }
+
+        match self.try_multicall_balances(address, tokens).await {
+            Ok(balances) => Some(balances),
+            Err(e) => {
+                tracing::warn!("Multicall balance query failed, querying one by one: {}", e);
+                self.check_multicall_deployed().await;
+                None
+            }
+        }
+    }
+
+    async fn try_multicall_balances(
+        &self,
+        address: &str,
+        tokens: &[TokenInfo],
+    ) -> AppResult<(Decimal, Vec<TokenBalance>)> {
+        let start = std::time::Instant::now();
+        let provider = self.get_provider().await?;
+        let holder = self.parse_address(address)?;
+        let multicall_address = self.parse_address(MULTICALL3_ADDRESS)?;
+
+        let token_addresses = tokens
+            .iter()
+            .map(|token| self.parse_address(&token.contract_address))
+            .collect::<AppResult<Vec<_>>>()?;
+
+        let multicall = Multicall3::new(multicall_address, provider);
+        let results = multicall
+            .aggregate_3(balance_calls(multicall_address, holder, &token_addresses))
             .call()
             .await
-            .unwrap_or(18);
+            .map_err(|e| AppError::BlockchainError(format!("Multicall failed: {}", e)))?;
+        let (native, token_results) = decode_balances(&results, tokens.len())?;
 
-        Ok((balance, decimals))
+        let native_balance = units_to_decimal(native, ETH_DECIMALS)?;
+        let mut token_balances = Vec::new();
+        for (token, balance) in tokens.iter().zip(token_results) {
+            match balance {
+                Some(balance) if !balance.is_zero() => token_balances.push(TokenBalance {
+                    symbol: token.symbol.clone(),
+                    balance: units_to_decimal(balance, token.decimals as u32)?,
+                    decimals: token.decimals,
+                    contract_address: Some(token.contract_address.clone()),
+                }),
+                Some(_) => {}
+                None => tracing::warn!("Failed to get {} balance in multicall", token.symbol),
+            }
+        }
+
+        tracing::info!(
+            "Read {} balances with one multicall in {}ms",
+            token_addresses.len() + 1,
+            start.elapsed().as_millis()
+        );
+        Ok((native_balance, token_balances))
+    }
+
+    /// Stop trying Multicall3 on this chain if no contract is deployed at its address
+    async fn check_multicall_deployed(&self) {
+        let Ok(provider) = self.get_provider().await else {
+            return;
+        };
+        let Ok(multicall_address) = self.parse_address(MULTICALL3_ADDRESS) else {
+            return;
+        };
+
+        if let Ok(code) = provider.get_code(multicall_address, None).await {
+            if code.is_empty() {
+                tracing::warn!("Multicall3 is not deployed on {}, balances will be read one by one", self.key);
+                self.multicall_unavailable.store(true, Ordering::Relaxed);
+            }
+        }
+    }
+
+    /// Read the native balance and each token balance with separate parallel calls
+    async fn get_balances_individually(
+        &self,
+        address: &str,
+        tokens: &[TokenInfo],
+    ) -> AppResult<(Decimal, Vec<TokenBalance>)> {
+        let native_balance = self.get_native_balance(address).await?;
+        tracing::info!("ETH balance: {}", native_balance);
+
+        // Query all token balances in parallel
+        tracing::info!("Querying {} tokens in parallel...", tokens.len());
+
+        let token_futures: Vec<_> = tokens
+            .iter()
+            .map(|info| {
+                let symbol = info.symbol.clone();
+                let contract_address = info.contract_address.clone();
+                let address = address.to_string();
+                async move {
+                    let start = std::time::Instant::now();
+                    let result = self.get_erc20_balance(&contract_address, &address).await;
+                    let elapsed = start.elapsed().as_millis();
+                    (symbol, contract_address, result, elapsed)
+                }
+            })
+            .collect();
+
+        let results = futures::future::join_all(token_futures).await;
+
+        let mut token_balances = Vec::new();
+        for (symbol, contract_address, result, elapsed) in results {
+            match result {
+                Ok((balance, decimals)) => {
+                    if !balance.is_zero() {
+                        if let Ok(decimal_balance) = units_to_decimal(balance, decimals as u32) {
+                            tracing::info!("{} balance: {} ({}ms)", symbol, decimal_balance, elapsed);
+                            token_balances.push(TokenBalance {
+                                symbol,
+                                balance: decimal_balance,
+                                decimals,
+                                contract_address: Some(contract_address),
+                            });
+                        }
+                    } else {
+                        tracing::debug!("{} balance: 0 ({}ms)", symbol, elapsed);
+                    }
+                }
+                Err(e) => {
+                    tracing::warn!("Failed to get {} balance ({}ms): {}", symbol, elapsed, e);
+                }
+            }
+        }
+
+        Ok((native_balance, token_balances))
     }
 
     /// Calculate optimal EIP-1559 gas parameters
     /// Returns (max_fee_per_gas, max_priority_fee_per_gas) in Wei
-    async fn calculate_eip1559_fees(&self, provider: &Provider<Http>) -> AppResult<(U256, U256)> {
+    async fn calculate_eip1559_fees(&self, provider: &RpcProvider) -> AppResult<(U256, U256)> {
         // Get the latest block to read base fee
         let block = provider
             .get_block(BlockNumber::Latest)
@@ -250,10 +815,10 @@ impl EthereumClient {
 
         tracing::info!(
             "EIP-1559 fees calculated - base_fee: {} Gwei, priority_fee: {} Gwei (suggested: {} Gwei), max_fee: {} Gwei",
-            format_units(base_fee, "gwei").unwrap_or_default(),
-            format_units(priority_fee, "gwei").unwrap_or_default(),
-            format_units(suggested_priority_fee, "gwei").unwrap_or_default(),
-            format_units(max_fee, "gwei").unwrap_or_default()
+            format_units(base_fee, GWEI_DECIMALS),
+            format_units(priority_fee, GWEI_DECIMALS),
+            format_units(suggested_priority_fee, GWEI_DECIMALS),
+            format_units(max_fee, GWEI_DECIMALS)
         );
 
         Ok((max_fee, priority_fee))
@@ -263,15 +828,27 @@ impl EthereumClient {
 #[async_trait]
 impl ChainClient for EthereumClient {
     fn chain_id(&self) -> &str {
-        "ethereum"
+        &self.key
     }
 
     fn chain_name(&self) -> &str {
-        "Ethereum Mainnet"
+        &self.name
+    }
+
+    fn key_scheme(&self) -> &str {
+        "ethereum"
     }
 
     fn native_token_symbol(&self) -> &str {
-        "ETH"
+        &self.native_symbol
+    }
+
+    fn native_decimals(&self) -> u8 {
+        18
+    }
+
+    fn is_testnet(&self) -> bool {
+        is_testnet_chain_id(self.chain_id)
     }
 
     async fn get_native_balance(&self, address: &str) -> AppResult<Decimal> {
@@ -286,11 +863,7 @@ impl ChainClient for EthereumClient {
             .await
             .map_err(|e| AppError::BlockchainError(format!("Failed to get balance: {}", e)))?;
 
-        let balance_str = format_units(balance, "ether")
-            .map_err(|e| AppError::BlockchainError(format!("Failed to format balance: {}", e)))?;
-
-        let result = Decimal::from_str(&balance_str)
-            .map_err(|e| AppError::BlockchainError(format!("Failed to parse balance: {}", e)))?;
+        let result = units_to_decimal(balance, ETH_DECIMALS)?;
 
         tracing::debug!(
             "ETH balance for {}: {} (took {}ms)",
@@ -303,18 +876,14 @@ impl ChainClient for EthereumClient {
     }
 
     async fn get_token_balance(&self, address: &str, token_symbol: &str) -> AppResult<Decimal> {
-        let token_info = get_token_info(token_symbol)
+        let token_info = self.token_info(token_symbol)
             .ok_or_else(|| AppError::NotFound(format!("Token {} not supported", token_symbol)))?;
 
         let (balance, decimals) = self
             .get_erc20_balance(&token_info.contract_address, address)
             .await?;
 
-        let balance_str = format_units(balance, decimals as u32)
-            .map_err(|e| AppError::BlockchainError(format!("Failed to format balance: {}", e)))?;
-
-        Decimal::from_str(&balance_str)
-            .map_err(|e| AppError::BlockchainError(format!("Failed to parse balance: {}", e)))
+        units_to_decimal(balance, decimals as u32)
     }
 
     async fn get_all_balances(&self, address: &str) -> AppResult<(Decimal, Vec<TokenBalance>)> {
@@ -334,53 +903,11 @@ impl ChainClient for EthereumClient {
             proxy_info
         );
 
-        let native_balance = self.get_native_balance(address).await?;
-        tracing::info!("ETH balance: {}", native_balance);
-
-        // Query all token balances in parallel
-        let token_count = SUPPORTED_TOKENS.len();
-        tracing::info!("Querying {} tokens in parallel...", token_count);
-
-        let token_futures: Vec<_> = SUPPORTED_TOKENS
-            .iter()
-            .map(|(symbol, info)| {
-                let symbol = symbol.clone();
-                let contract_address = info.contract_address.clone();
-                let address = address.to_string();
-                async move {
-                    let start = std::time::Instant::now();
-                    let result = self.get_erc20_balance(&contract_address, &address).await;
-                    let elapsed = start.elapsed().as_millis();
-                    (symbol, contract_address, result, elapsed)
-                }
-            })
-            .collect();
-
-        let results = futures::future::join_all(token_futures).await;
-
-        let mut token_balances = Vec::new();
-        for (symbol, contract_address, result, elapsed) in results {
-            match result {
-                Ok((balance, decimals)) => {
-                    if !balance.is_zero() {
-                        let balance_str = format_units(balance, decimals as u32).unwrap_or_default();
-                        if let Ok(decimal_balance) = Decimal::from_str(&balance_str) {
-                            tracing::info!("{} balance: {} ({}ms)", symbol, decimal_balance, elapsed);
-                            token_balances.push(TokenBalance {
-                                symbol,
-                                balance: decimal_balance,
-                                contract_address: Some(contract_address),
-                            });
-                        }
-                    } else {
-                        tracing::debug!("{} balance: 0 ({}ms)", symbol, elapsed);
-                    }
-                }
-                Err(e) => {
-                    tracing::warn!("Failed to get {} balance ({}ms): {}", symbol, elapsed, e);
-                }
-            }
-        }
+        let tokens = self.tracked_tokens().await?;
+        let (native_balance, token_balances) = match self.get_balances_multicall(address, &tokens).await {
+            Some(balances) => balances,
+            None => self.get_balances_individually(address, &tokens).await?,
+        };
 
         tracing::info!(
             "Completed all balance queries for {} in {}ms (ETH: {}, tokens with balance: {})",
@@ -396,7 +923,7 @@ impl ChainClient for EthereumClient {
     async fn estimate_gas(&self, params: &TransferParams) -> AppResult<GasEstimate> {
         let provider = self.get_provider().await?;
         let from = self.parse_address(&params.from_address)?;
-        let to = self.parse_address(&params.to_address)?;
+        let to = self.resolve_recipient(&provider, &params.to_address).await?;
 
         // Get EIP-1559 parameters
         let block = provider
@@ -428,48 +955,55 @@ impl ChainClient for EthereumClient {
         // Max fee = base_fee * 2 + priority_fee
         let max_fee = base_fee * 2 + priority_fee;
 
-        let gas_limit = if params.token.to_uppercase() == "ETH" {
-            // Standard ETH transfer
-            U256::from(21000)
+        // eth_estimateGas simulates the transfer, so a transfer that would revert
+        // (e.g. more tokens than the sender holds) fails here instead of on chain
+        let gas_limit = if params.token.eq_ignore_ascii_case(self.native_token_symbol()) {
+            let value = decimal_to_units(params.amount, ETH_DECIMALS)?;
+            let tx: TypedTransaction = TransactionRequest::new().from(from).to(to).value(value).into();
+            provider.estimate_gas(&tx, None).await.map_err(estimate_error)?
         } else {
-            // ERC20 transfer - estimate or use default
-            let token_info = get_token_info(&params.token)
+            let token_info = self.token_info(&params.token)
                 .ok_or_else(|| AppError::NotFound(format!("Token {} not supported", params.token)))?;
 
             let token_addr = self.parse_address(&token_info.contract_address)?;
             let contract = ERC20::new(token_addr, provider.clone());
 
-            let amount = parse_units(&params.amount.to_string(), token_info.decimals as u32)
-                .map_err(|e| AppError::ValidationError(format!("Invalid amount: {}", e)))?;
+            let amount = decimal_to_units(params.amount, token_info.decimals as u32)?;
 
             contract
                 .transfer(to, amount.into())
                 .from(from)
                 .estimate_gas()
                 .await
-                .unwrap_or(U256::from(100000))
+                .map_err(estimate_error)?
         };
 
         // Format values
-        let base_fee_gwei_str = format_units(base_fee, "gwei")
-            .map_err(|e| AppError::BlockchainError(format!("Failed to format base fee: {}", e)))?;
-        let priority_fee_gwei_str = format_units(priority_fee, "gwei")
-            .map_err(|e| AppError::BlockchainError(format!("Failed to format priority fee: {}", e)))?;
-        let max_fee_gwei_str = format_units(max_fee, "gwei")
-            .map_err(|e| AppError::BlockchainError(format!("Failed to format max fee: {}", e)))?;
-
-        // Estimated fee using max_fee (worst case scenario)
-        let estimated_fee = gas_limit * max_fee;
-        let fee_eth = format_units(estimated_fee, "ether")
-            .map_err(|e| AppError::BlockchainError(format!("Failed to format fee: {}", e)))?;
+        let base_fee_gwei = units_to_decimal(base_fee, GWEI_DECIMALS)?;
+        let priority_fee_gwei = units_to_decimal(priority_fee, GWEI_DECIMALS)?;
+        let max_fee_gwei = units_to_decimal(max_fee, GWEI_DECIMALS)?;
+
+        // Estimated fee using max_fee (worst case scenario); a node can report values
+        // large enough to overflow, which must not panic the request
+        let out_of_range = || {
+            AppError::BlockchainError(format!(
+                "Estimated fee out of range (gas limit {}, max fee {} wei)",
+                gas_limit, max_fee
+            ))
+        };
+        let estimated_fee = gas_limit.checked_mul(max_fee).ok_or_else(out_of_range)?;
+        let estimated_fee_wei = u128::try_from(estimated_fee).map_err(|_| out_of_range())?;
+        let gas_limit = u64::try_from(gas_limit).map_err(|_| out_of_range())?;
+        let fee_eth = units_to_decimal(estimated_fee, ETH_DECIMALS)?;
 
         Ok(GasEstimate {
-            gas_limit: gas_limit.as_u64(),
-            gas_price_gwei: Decimal::from_str(&max_fee_gwei_str).unwrap_or_default(),
-            estimated_fee_eth: Decimal::from_str(&fee_eth).unwrap_or_default(),
-            base_fee_gwei: Some(Decimal::from_str(&base_fee_gwei_str).unwrap_or_default()),
-            priority_fee_gwei: Some(Decimal::from_str(&priority_fee_gwei_str).unwrap_or_default()),
-            max_fee_gwei: Some(Decimal::from_str(&max_fee_gwei_str).unwrap_or_default()),
+            gas_limit,
+            gas_price_gwei: max_fee_gwei,
+            estimated_fee_eth: fee_eth,
+            estimated_fee_wei: Some(estimated_fee_wei),
+            base_fee_gwei: Some(base_fee_gwei),
+            priority_fee_gwei: Some(priority_fee_gwei),
+            max_fee_gwei: Some(max_fee_gwei),
         })
     }
 
@@ -478,95 +1012,188 @@ impl ChainClient for EthereumClient {
         let wallet = self.parse_private_key(&params.private_key)?;
         let client = SignerMiddleware::new(provider.clone(), wallet);
 
-        let to = self.parse_address(&params.to_address)?;
-        let value = parse_units(&params.amount.to_string(), "ether")
-            .map_err(|e| AppError::ValidationError(format!("Invalid amount: {}", e)))?;
-
-        // Use EIP-1559 transaction for better gas efficiency
-        let (max_fee, priority_fee) = self.calculate_eip1559_fees(&provider).await?;
-
-        let mut tx = Eip1559TransactionRequest::new()
-            .to(to)
-            .value(value)
-            .max_fee_per_gas(max_fee)
-            .max_priority_fee_per_gas(priority_fee);
-
-        // If user specified gas_price, use it as max_fee
-        if let Some(gas_price) = &params.gas_price_gwei {
-            let gas_price_wei: U256 = parse_units(&gas_price.to_string(), "gwei")
-                .map_err(|e| AppError::ValidationError(format!("Invalid gas price: {}", e)))?
-                .into();
-            // Override EIP-1559 params with user-specified max fee
-            tx = tx.max_fee_per_gas(gas_price_wei).max_priority_fee_per_gas(priority_fee);
-        }
-
-        if let Some(gas_limit) = params.gas_limit {
-            tx = tx.gas(gas_limit);
-        }
+        let from = client.address();
+        let mut tx = self.native_transfer_tx(&provider, params).await?;
+        let nonce = self.reserve_nonce(&provider, from).await?;
+        tx.set_nonce(nonce);
 
-        let pending_tx = client
-            .send_transaction(tx, None)
-            .await
-            .map_err(|e| AppError::BlockchainError(format!("Failed to send transaction: {}", e)))?;
+        let pending_tx = match client.send_transaction(tx, None).await {
+            Ok(pending_tx) => pending_tx,
+            Err(e) => {
+                self.nonces.release(from, nonce).await;
+                return Err(AppError::BlockchainError(format!("Failed to send transaction: {}", e)));
+            }
+        };
 
         let tx_hash = format!("{:?}", pending_tx.tx_hash());
-        tracing::info!("ETH transfer submitted: {}", tx_hash);
+        tracing::info!("ETH transfer submitted: {} (nonce {})", tx_hash, nonce);
 
         Ok(tx_hash)
     }
 
     async fn transfer_token(&self, params: &TransferParams) -> AppResult<String> {
-        let token_info = get_token_info(&params.token)
-            .ok_or_else(|| AppError::NotFound(format!("Token {} not supported", params.token)))?;
+        let provider = self.get_provider().await?;
+        let wallet = self.parse_private_key(&params.private_key)?;
+        let from = wallet.address();
+        let client = SignerMiddleware::new(provider.clone(), wallet);
+
+        let mut tx = self.token_transfer_tx(&provider, params).await?;
+        tx.set_from(from);
+        let nonce = self.reserve_nonce(&provider, from).await?;
+        tx.set_nonce(nonce);
+
+        let pending_tx = match client.send_transaction(tx, None).await {
+            Ok(pending_tx) => pending_tx,
+            Err(e) => {
+                self.nonces.release(from, nonce).await;
+                return Err(AppError::BlockchainError(format!("Failed to send token transfer: {}", e)));
+            }
+        };
+
+        let tx_hash = format!("{:?}", pending_tx.tx_hash());
+        tracing::info!("{} transfer submitted: {} (nonce {})", params.token, tx_hash, nonce);
+
+        Ok(tx_hash)
+    }
 
+    async fn sign_transfer(&self, params: &TransferParams) -> AppResult<SignedTransfer> {
         let provider = self.get_provider().await?;
         let wallet = self.parse_private_key(&params.private_key)?;
-        let client = Arc::new(SignerMiddleware::new(provider.clone(), wallet));
+        let from = wallet.address();
 
-        let token_addr = self.parse_address(&token_info.contract_address)?;
-        let to = self.parse_address(&params.to_address)?;
+        let mut tx = if params.token.eq_ignore_ascii_case(self.native_token_symbol()) {
+            self.native_transfer_tx(&provider, params).await?
+        } else {
+            self.token_transfer_tx(&provider, params).await?
+        };
+        tx.set_from(from);
+
+        // Nothing is broadcast, so the nonce is read rather than reserved
+        let nonce = provider
+            .get_transaction_count(from, Some(BlockNumber::Pending.into()))
+            .await
+            .map_err(|e| AppError::BlockchainError(format!("Failed to get nonce: {}", e)))?;
+        tx.set_nonce(nonce);
 
-        let amount = parse_units(&params.amount.to_string(), token_info.decimals as u32)
-            .map_err(|e| AppError::ValidationError(format!("Invalid amount: {}", e)))?;
+        if tx.gas().is_none() {
+            let gas = provider.estimate_gas(&tx, None).await.map_err(estimate_error)?;
+            tx.set_gas(gas);
+        }
 
-        let contract = ERC20::new(token_addr, client);
+        let signature = wallet
+            .sign_transaction_sync(&tx)
+            .map_err(|e| AppError::InternalError(format!("Failed to sign transaction: {}", e)))?;
+        let raw = tx.rlp_signed(&signature);
+        let tx_hash = format!("{:?}", H256::from(ethers::utils::keccak256(&raw)));
 
-        // Calculate optimal EIP-1559 gas parameters
-        let (max_fee, priority_fee) = self.calculate_eip1559_fees(&provider).await?;
+        tracing::info!("{} transfer signed, not broadcast: {} (nonce {})", params.token, tx_hash, nonce);
 
-        let mut call = contract.transfer(to, amount.into());
+        Ok(SignedTransfer {
+            raw_tx: format!("0x{}", hex::encode(&raw)),
+            tx_hash,
+        })
+    }
 
-        if let Some(gas_limit) = params.gas_limit {
-            call = call.gas(gas_limit);
+    async fn replace_transaction(
+        &self,
+        tx_hash: &str,
+        private_key: &str,
+        new_max_fee_gwei: Decimal,
+    ) -> AppResult<String> {
+        let provider = self.get_provider().await?;
+        let wallet = self.parse_private_key(private_key)?;
+
+        let hash = tx_hash
+            .parse::<H256>()
+            .map_err(|e| AppError::ValidationError(format!("Invalid tx hash: {}", e)))?;
+
+        let original = provider
+            .get_transaction(hash)
+            .await
+            .map_err(|e| AppError::BlockchainError(format!("Failed to get transaction: {}", e)))?
+            .ok_or_else(|| {
+                AppError::NotFound(format!("Transaction {} not found in the mempool", tx_hash))
+            })?;
+
+        if let Some(block) = original.block_number {
+            return Err(AppError::ValidationError(format!(
+                "Transaction {} already confirmed in block {}",
+                tx_hash, block
+            )));
         }
 
-        // Apply EIP-1559 gas settings
-        if let Some(gas_price) = &params.gas_price_gwei {
-            // User specified gas price - use as max_fee
-            let gas_price_wei = parse_units(&gas_price.to_string(), "gwei")
-                .map_err(|e| AppError::ValidationError(format!("Invalid gas price: {}", e)))?;
-            call = call.gas_price(gas_price_wei);
-        } else {
-            // Use optimized EIP-1559 parameters
-            // Note: For contract calls, we need to use legacy gas_price or build tx manually
-            // Using effective gas price = max_fee for simplicity
-            call = call.gas_price(max_fee);
-            tracing::info!(
-                "Token transfer using optimized gas - max_fee: {} Gwei, priority_fee: {} Gwei",
-                format_units(max_fee, "gwei").unwrap_or_default(),
-                format_units(priority_fee, "gwei").unwrap_or_default()
-            );
+        if original.from != wallet.address() {
+            return Err(AppError::ValidationError(
+                "Transaction was not sent by this wallet".to_string(),
+            ));
         }
 
-        let pending_tx = call
-            .send()
+        let new_max_fee = decimal_to_units(new_max_fee_gwei, GWEI_DECIMALS)?;
+
+        // Same nonce, recipient, value and calldata; only the fee changes
+        let raw: Bytes = match (original.max_fee_per_gas, original.max_priority_fee_per_gas) {
+            (Some(old_max_fee), Some(old_priority_fee)) => {
+                let (max_fee, priority_fee) = replacement_fees(old_max_fee, old_priority_fee, new_max_fee)?;
+                if max_fee > new_max_fee {
+                    tracing::warn!(
+                        "Raising replacement max fee for {} to the bumped tip of {} Gwei",
+                        tx_hash,
+                        format_units(max_fee, GWEI_DECIMALS)
+                    );
+                }
+
+                let mut tx = Eip1559TransactionRequest::new()
+                    .from(original.from)
+                    .value(original.value)
+                    .data(original.input.clone())
+                    .nonce(original.nonce)
+                    .gas(original.gas)
+                    .chain_id(self.chain_id)
+                    .max_fee_per_gas(max_fee)
+                    .max_priority_fee_per_gas(priority_fee);
+                if let Some(to) = original.to {
+                    tx = tx.to(to);
+                }
+                let tx: TypedTransaction = tx.into();
+                let signature = wallet
+                    .sign_transaction_sync(&tx)
+                    .map_err(|e| AppError::InternalError(format!("Failed to sign transaction: {}", e)))?;
+                tx.rlp_signed(&signature)
+            }
+            _ => {
+                let old_gas_price = original.gas_price.unwrap_or_default();
+                check_fee_bump(old_gas_price, new_max_fee)?;
+
+                let mut tx = TransactionRequest::new()
+                    .from(original.from)
+                    .value(original.value)
+                    .data(original.input.clone())
+                    .nonce(original.nonce)
+                    .gas(original.gas)
+                    .chain_id(self.chain_id)
+                    .gas_price(new_max_fee);
+                if let Some(to) = original.to {
+                    tx = tx.to(to);
+                }
+                sign_legacy_transaction(&wallet, tx, self.chain_id)?
+            }
+        };
+
+        let pending_tx = provider
+            .send_raw_transaction(raw)
             .await
-            .map_err(|e| AppError::BlockchainError(format!("Failed to send token transfer: {}", e)))?;
+            .map_err(|e| AppError::BlockchainError(format!("Failed to send replacement transaction: {}", e)))?;
 
-        let tx_hash = format!("{:?}", pending_tx.tx_hash());
-        tracing::info!("{} transfer submitted: {}", params.token, tx_hash);
+        let new_hash = format!("{:?}", pending_tx.tx_hash());
+        tracing::info!(
+            "Replaced transaction {} (nonce {}) with {} at max fee {} Gwei",
+            tx_hash,
+            original.nonce,
+            new_hash,
+            new_max_fee_gwei
+        );
 
-        Ok(tx_hash)
+        Ok(new_hash)
     }
 
     async fn get_tx_status(&self, tx_hash: &str) -> AppResult<TxStatus> {
@@ -584,9 +1211,28 @@ impl ChainClient for EthereumClient {
         match receipt {
             Some(r) => {
                 if r.status == Some(U64::from(1)) {
+                    let Some(block_number) = r.block_number.map(|b| b.as_u64()) else {
+                        return Ok(TxStatus::Pending);
+                    };
+                    // Depth is recomputed against the tip on every poll, so a reorg shows up
+                    let tip = provider
+                        .get_block_number()
+                        .await
+                        .map_err(|e| AppError::BlockchainError(format!("Failed to get block number: {}", e)))?
+                        .as_u64();
+
+                    let fee = r
+                        .effective_gas_price
+                        .zip(r.gas_used)
+                        .and_then(|(price, gas)| price.checked_mul(gas))
+                        .filter(|fee| *fee <= U256::from(u128::MAX))
+                        .map(|fee| fee.as_u128());
+
                     Ok(TxStatus::Confirmed {
-                        block_number: r.block_number.map(|b| b.as_u64()).unwrap_or(0),
+                        block_number,
                         gas_used: r.gas_used.map(|g| g.as_u64()).unwrap_or(0),
+                        fee,
+                        confirmations: tip.saturating_sub(block_number) + 1,
                     })
                 } else {
                     Ok(TxStatus::Failed {
@@ -595,8 +1241,11 @@ impl ChainClient for EthereumClient {
                 }
             }
             None => {
-                // Check if transaction exists but not yet mined
-                let tx = provider.get_transaction(hash).await.ok().flatten();
+                // Check if transaction exists but not yet mined; an RPC error is not "not found"
+                let tx = provider
+                    .get_transaction(hash)
+                    .await
+                    .map_err(|e| AppError::BlockchainError(format!("Failed to get transaction: {}", e)))?;
                 if tx.is_some() {
                     Ok(TxStatus::Pending)
                 } else {
@@ -606,10 +1255,128 @@ impl ChainClient for EthereumClient {
         }
     }
 
+    fn required_confirmations(&self) -> u64 {
+        self.confirmations
+    }
+
+    fn pending_timeout(&self) -> Option<Duration> {
+        self.pending_timeout
+    }
+
+    async fn tx_nonce(&self, tx_hash: &str) -> AppResult<Option<u64>> {
+        let provider = self.get_provider().await?;
+        let hash = tx_hash
+            .parse::<H256>()
+            .map_err(|e| AppError::ValidationError(format!("Invalid tx hash: {}", e)))?;
+
+        let tx = provider
+            .get_transaction(hash)
+            .await
+            .map_err(|e| AppError::BlockchainError(format!("Failed to get transaction: {}", e)))?;
+        Ok(tx.map(|tx| tx.nonce.as_u64()))
+    }
+
+    async fn mined_nonce(&self, address: &str) -> AppResult<Option<u64>> {
+        let provider = self.get_provider().await?;
+        let address = self.parse_address(address)?;
+
+        let count = provider
+            .get_transaction_count(address, Some(BlockNumber::Latest.into()))
+            .await
+            .map_err(|e| AppError::BlockchainError(format!("Failed to get nonce: {}", e)))?;
+        Ok(Some(count.as_u64()))
+    }
+
+    async fn reset_nonce(&self, address: &str) {
+        if let Ok(address) = self.parse_address(address) {
+            self.nonces.forget(address).await;
+        }
+    }
+
+    async fn resolve_address(&self, address: &str) -> AppResult<String> {
+        if !self.ethereum_l1 || !is_ens_name(address) {
+            return Ok(address.to_string());
+        }
+        let provider = self.get_provider().await?;
+        let resolved = self.resolve_recipient(&provider, address).await?;
+        Ok(format!("{:?}", resolved))
+    }
+
+    async fn lookup_name(&self, address: &str) -> AppResult<Option<String>> {
+        let address = self.parse_address(address)?;
+        if !self.ethereum_l1 {
+            return Ok(None);
+        }
+        if let Some(name) = self.ens_cache.get_name(&address) {
+            return Ok(name);
+        }
+
+        let provider = self.get_provider().await?;
+        let name = match provider.lookup_address(address).await {
+            // Only trust a primary name that resolves back to the same address
+            Ok(name) if !name.is_empty() => match provider.resolve_name(&name).await {
+                Ok(forward) if forward == address => Some(name),
+                _ => None,
+            },
+            Ok(_) | Err(ProviderError::EnsError(_)) | Err(ProviderError::EnsNotOwned(_)) => None,
+            Err(e) => {
+                return Err(AppError::BlockchainError(format!(
+                    "Failed to look up ENS name for {:?}: {}",
+                    address, e
+                )))
+            }
+        };
+
+        self.ens_cache.put_name(address, name.clone());
+        Ok(name)
+    }
+
+    async fn token_allowlist(&self) -> Vec<String> {
+        self.token_allowlist
+            .read()
+            .await
+            .iter()
+            .map(|address| to_checksum(address, None))
+            .collect()
+    }
+
+    async fn set_token_allowlist(&self, contracts: &[String]) -> AppResult<Vec<String>> {
+        let addresses = parse_allowlist(contracts)?;
+        *self.token_allowlist.write().await = addresses;
+        Ok(self.token_allowlist().await)
+    }
+
+    async fn refresh_token_metadata(&self, token_symbol: &str) -> AppResult<TokenMetadata> {
+        // Allowlisted tokens are addressed by contract, built-in ones by symbol
+        let token_addr = match token_symbol.parse::<Address>() {
+            Ok(address) => address,
+            Err(_) => {
+                let token_info = self.token_info(token_symbol)
+                    .ok_or_else(|| AppError::NotFound(format!("Token {} not supported", token_symbol)))?;
+                self.parse_address(&token_info.contract_address)?
+            }
+        };
+
+        self.token_metadata.invalidate(self.chain_id, &token_addr);
+        let contract = ERC20::new(token_addr, self.get_provider().await?);
+        self.get_token_metadata(&contract).await
+    }
+
     fn validate_address(&self, address: &str) -> bool {
         address.parse::<Address>().is_ok()
     }
 
+    async fn get_block_height(&self) -> AppResult<u64> {
+        let provider = self.get_provider().await?;
+
+        let block_number = provider
+            .get_block_number()
+            .await
+            .map_err(|e| AppError::BlockchainError(format!("Failed to get block number: {}", e)))?;
+
+        Ok(block_number.as_u64())
+    }
+
     async fn get_gas_price(&self) -> AppResult<Decimal> {
         let provider = self.get_provider().await?;
 
@@ -618,10 +1385,141 @@ impl ChainClient for EthereumClient {
             .await
             .map_err(|e| AppError::BlockchainError(format!("Failed to get gas price: {}", e)))?;
 
-        let gas_price_gwei = format_units(gas_price, "gwei")
-            .map_err(|e| AppError::BlockchainError(format!("Failed to format gas price: {}", e)))?;
+        units_to_decimal(gas_price, GWEI_DECIMALS)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethers::utils::rlp::Rlp;
+
+    #[test]
+    fn test_testnet_chain_ids() {
+        assert!(is_testnet_chain_id(11155111));
+        assert!(is_testnet_chain_id(31337));
+        assert!(!is_testnet_chain_id(1));
+        assert!(!is_testnet_chain_id(137));
+    }
+
+    #[test]
+    fn test_legacy_transaction_is_eip155_protected() {
+        let wallet: LocalWallet =
+            "4c0883a69102937d6231471b5dbb6204fe5129617082792ae468d01a3f362318"
+                .parse()
+                .unwrap();
+        let tx = TransactionRequest::new()
+            .to("0x3535353535353535353535353535353535353535".parse::<Address>().unwrap())
+            .value(U256::exp10(18))
+            .gas(21_000)
+            .gas_price(U256::exp10(10))
+            .nonce(9);
+
+        let raw = sign_legacy_transaction(&wallet, tx, 1).unwrap();
+
+        let (decoded, signature) = TypedTransaction::decode_signed(&Rlp::new(&raw)).unwrap();
+        assert!(matches!(decoded, TypedTransaction::Legacy(_)));
+        assert_eq!(decoded.chain_id(), Some(U64::from(1)));
+        assert!(signature.v == 37 || signature.v == 38);
+        assert_eq!(signature.recover(decoded.sighash()).unwrap(), wallet.address());
+
+        // A different chain id must change v
+        let tx = TransactionRequest::new().to(Address::zero()).gas(21_000).gas_price(1).nonce(0);
+        let raw = sign_legacy_transaction(&wallet, tx, 5).unwrap();
+        let (_, signature) = TypedTransaction::decode_signed(&Rlp::new(&raw)).unwrap();
+        assert!(signature.v == 45 || signature.v == 46);
+    }
+
+    fn transfer_params(token: &str) -> TransferParams {
+        TransferParams {
+            from_address: String::new(),
+            to_address: "0x3535353535353535353535353535353535353535".to_string(),
+            private_key: String::new(),
+            token: token.to_string(),
+            amount: Decimal::ONE,
+            gas_price_gwei: None,
+            gas_limit: Some(60_000),
+        }
+    }
+
+    /// Sign `tx` the way `sign_transfer` does and decode what would be broadcast
+    fn sign_and_decode(wallet: &LocalWallet, mut tx: TypedTransaction) -> (TypedTransaction, Signature) {
+        tx.set_from(wallet.address());
+        tx.set_nonce(9);
+        let signature = wallet.sign_transaction_sync(&tx).unwrap();
+        TypedTransaction::decode_signed(&Rlp::new(&tx.rlp_signed(&signature))).unwrap()
+    }
+
+    #[test]
+    fn test_transfer_transactions_commit_to_the_chain_id() {
+        let wallet: LocalWallet =
+            "4c0883a69102937d6231471b5dbb6204fe5129617082792ae468d01a3f362318"
+                .parse()
+                .unwrap();
+        let to: Address = "0x3535353535353535353535353535353535353535".parse().unwrap();
+        let gwei = U256::exp10(9);
+        let fees = (gwei * 30, gwei * 2);
+
+        let tx = native_transfer_request(to, U256::exp10(18), 137, fees, &transfer_params("POL")).unwrap();
+        let (decoded, signature) = sign_and_decode(&wallet, tx);
+        assert!(matches!(decoded, TypedTransaction::Eip1559(_)));
+        assert_eq!(decoded.chain_id(), Some(U64::from(137)));
+        assert_eq!(signature.recover(decoded.sighash()).unwrap(), wallet.address());
+
+        let provider = Arc::new(Provider::<Http>::try_from("http://127.0.0.1:1").unwrap());
+        let contract = ERC20::new(Address::repeat_byte(0xaa), provider);
+        let tx = token_transfer_request(&contract, to, U256::exp10(6), 137, fees, &transfer_params("USDT")).unwrap();
+        let (decoded, signature) = sign_and_decode(&wallet, tx);
+        assert_eq!(decoded.chain_id(), Some(U64::from(137)));
+        assert_eq!(decoded.to_addr(), Some(&Address::repeat_byte(0xaa)));
+        assert_eq!(signature.recover(decoded.sighash()).unwrap(), wallet.address());
+
+        // The same transfer on another chain is a different transaction
+        let other = native_transfer_request(to, U256::exp10(18), 1, fees, &transfer_params("ETH")).unwrap();
+        assert_eq!(sign_and_decode(&wallet, other).0.chain_id(), Some(U64::from(1)));
+    }
+
+    #[test]
+    fn test_estimate_revert_is_a_validation_error() {
+        let error = estimate_error("(code: 3, message: execution reverted: ERC20: transfer amount exceeds balance)");
+        assert!(matches!(error, AppError::ValidationError(m) if m.starts_with("Transfer would revert")));
+
+        let error = estimate_error("insufficient funds for gas * price + value");
+        assert!(matches!(error, AppError::ValidationError(_)));
+
+        assert!(matches!(estimate_error("connection refused"), AppError::BlockchainError(_)));
+    }
+
+    #[test]
+    fn test_fee_bump_must_be_at_least_ten_percent() {
+        let gwei = U256::exp10(9);
+        let old = gwei * 20;
+
+        assert_eq!(min_replacement_fee(old), gwei * 22);
+        assert!(check_fee_bump(old, gwei * 22).is_ok());
+        assert!(check_fee_bump(old, gwei * 30).is_ok());
+        assert!(check_fee_bump(old, gwei * 22 - 1).is_err());
+        assert!(check_fee_bump(old, old).is_err());
+
+        // Rounds up so tiny fees still get a real bump
+        assert_eq!(min_replacement_fee(U256::from(1)), U256::from(2));
+    }
+
+    #[test]
+    fn test_replacement_max_fee_covers_bumped_tip() {
+        let gwei = U256::exp10(9);
+
+        // Usual case: the requested max fee already covers the bumped tip
+        let (max_fee, priority_fee) = replacement_fees(gwei * 20, gwei * 2, gwei * 22).unwrap();
+        assert_eq!(max_fee, gwei * 22);
+        assert_eq!(priority_fee, gwei * 22 / 10);
+
+        // The bumped tip exceeds the requested max fee: the max fee is raised to it
+        let (max_fee, priority_fee) = replacement_fees(gwei * 10, gwei * 20, gwei * 11).unwrap();
+        assert_eq!(priority_fee, gwei * 22);
+        assert_eq!(max_fee, priority_fee);
 
-        Decimal::from_str(&gas_price_gwei)
-            .map_err(|e| AppError::BlockchainError(format!("Failed to parse gas price: {}", e)))
+        // The max fee itself still has to clear the bump
+        assert!(replacement_fees(gwei * 20, gwei * 2, gwei * 21).is_err());
     }
 }
@@ -0,0 +1,94 @@
+use ethers::types::Address;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// How long ENS resolutions are reused before asking the resolver again
+pub const ENS_CACHE_TTL: Duration = Duration::from_secs(300);
+
+/// Whether a recipient should be resolved through ENS rather than parsed as 0x...
+pub fn is_ens_name(value: &str) -> bool {
+    let value = value.trim();
+    value.len() > ".eth".len() && value.to_ascii_lowercase().ends_with(".eth")
+}
+
+/// ENS names are case-insensitive for ASCII labels
+pub fn normalize_name(name: &str) -> String {
+    name.trim().to_ascii_lowercase()
+}
+
+/// Short-lived cache of forward (name -> address) and reverse (address -> name) lookups
+pub struct EnsCache {
+    ttl: Duration,
+    forward: Mutex<HashMap<String, (Address, Instant)>>,
+    reverse: Mutex<HashMap<Address, (Option<String>, Instant)>>,
+}
+
+impl EnsCache {
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            ttl,
+            forward: Mutex::new(HashMap::new()),
+            reverse: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn get_address(&self, name: &str) -> Option<Address> {
+        let forward = self.forward.lock().unwrap_or_else(|e| e.into_inner());
+        forward
+            .get(&normalize_name(name))
+            .filter(|(_, at)| at.elapsed() < self.ttl)
+            .map(|(address, _)| *address)
+    }
+
+    pub fn put_address(&self, name: &str, address: Address) {
+        let mut forward = self.forward.lock().unwrap_or_else(|e| e.into_inner());
+        forward.retain(|_, (_, at)| at.elapsed() < self.ttl);
+        forward.insert(normalize_name(name), (address, Instant::now()));
+    }
+
+    /// `Some(None)` means the address is cached as having no primary name
+    pub fn get_name(&self, address: &Address) -> Option<Option<String>> {
+        let reverse = self.reverse.lock().unwrap_or_else(|e| e.into_inner());
+        reverse
+            .get(address)
+            .filter(|(_, at)| at.elapsed() < self.ttl)
+            .map(|(name, _)| name.clone())
+    }
+
+    pub fn put_name(&self, address: Address, name: Option<String>) {
+        let mut reverse = self.reverse.lock().unwrap_or_else(|e| e.into_inner());
+        reverse.retain(|_, (_, at)| at.elapsed() < self.ttl);
+        reverse.insert(address, (name, Instant::now()));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_ens_name() {
+        assert!(is_ens_name("vitalik.eth"));
+        assert!(is_ens_name(" Pay.Vitalik.ETH "));
+        assert!(!is_ens_name(".eth"));
+        assert!(!is_ens_name("0xd8dA6BF26964aF9D7eEd9e03E53415D37aA96045"));
+        assert!(!is_ens_name("vitalik.xyz"));
+    }
+
+    #[test]
+    fn test_cache_is_case_insensitive_and_expires() {
+        let address = Address::random();
+        let cache = EnsCache::new(Duration::from_millis(20));
+
+        cache.put_address("Vitalik.eth", address);
+        assert_eq!(cache.get_address("vitalik.ETH"), Some(address));
+
+        cache.put_name(address, None);
+        assert_eq!(cache.get_name(&address), Some(None));
+
+        std::thread::sleep(Duration::from_millis(40));
+        assert_eq!(cache.get_address("vitalik.eth"), None);
+        assert_eq!(cache.get_name(&address), None);
+    }
+}
@@ -1,4 +1,10 @@
 mod client;
-mod tokens;
+mod ens;
+mod multicall;
+mod nonce;
+mod rpc_health;
+mod token_metadata;
+pub(crate) mod tokens;
 
 pub use client::EthereumClient;
+pub use rpc_health::EndpointStatus;
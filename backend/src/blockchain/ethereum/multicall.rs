@@ -0,0 +1,107 @@
+use ethers::abi::{AbiDecode, AbiEncode};
+use ethers::prelude::*;
+
+use crate::error::{AppError, AppResult};
+
+use super::client::BalanceOfCall;
+
+abigen!(
+    Multicall3,
+    r#"[
+        struct Call3 { address target; bool allowFailure; bytes callData; }
+        struct Call3Result { bool success; bytes returnData; }
+        function aggregate3(Call3[] calls) external payable returns (Call3Result[] returnData)
+        function getEthBalance(address addr) external view returns (uint256 balance)
+    ]"#
+);
+
+/// Multicall3 is deployed at the same address on Ethereum and most EVM chains
+pub const MULTICALL3_ADDRESS: &str = "0xcA11bde05977b3631167028862bE2a173976CA11";
+
+/// Calls reading the native balance of `holder`, then its balance of each token
+pub fn balance_calls(multicall: Address, holder: Address, tokens: &[Address]) -> Vec<Call3> {
+    let native = Call3 {
+        target: multicall,
+        allow_failure: false,
+        call_data: GetEthBalanceCall { addr: holder }.encode().into(),
+    };
+    let token_calls = tokens.iter().map(|token| Call3 {
+        target: *token,
+        // A token that reverts should not hide the other balances
+        allow_failure: true,
+        call_data: BalanceOfCall { account: holder }.encode().into(),
+    });
+
+    std::iter::once(native).chain(token_calls).collect()
+}
+
+/// Decode `aggregate3` results in call order: the native balance, then each token's
+/// balance (`None` where that call failed)
+pub fn decode_balances(results: &[Call3Result], token_count: usize) -> AppResult<(U256, Vec<Option<U256>>)> {
+    if results.len() != token_count + 1 {
+        return Err(AppError::BlockchainError(format!(
+            "Multicall returned {} results for {} calls",
+            results.len(),
+            token_count + 1
+        )));
+    }
+
+    let native = decode_uint(&results[0])
+        .ok_or_else(|| AppError::BlockchainError("Multicall native balance call failed".to_string()))?;
+    let tokens = results[1..].iter().map(decode_uint).collect();
+
+    Ok((native, tokens))
+}
+
+fn decode_uint(result: &Call3Result) -> Option<U256> {
+    if !result.success {
+        return None;
+    }
+    U256::decode(&result.return_data).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ok(value: u64) -> Call3Result {
+        Call3Result {
+            success: true,
+            return_data: U256::from(value).encode().into(),
+        }
+    }
+
+    #[test]
+    fn test_balance_calls_start_with_native() {
+        let multicall: Address = MULTICALL3_ADDRESS.parse().unwrap();
+        let holder = Address::repeat_byte(1);
+        let tokens = [Address::repeat_byte(2), Address::repeat_byte(3)];
+
+        let calls = balance_calls(multicall, holder, &tokens);
+
+        assert_eq!(calls.len(), 3);
+        assert_eq!(calls[0].target, multicall);
+        assert!(!calls[0].allow_failure);
+        assert_eq!(calls[2].target, tokens[1]);
+        assert_eq!(
+            BalanceOfCall::decode(&calls[2].call_data).unwrap().account,
+            holder
+        );
+    }
+
+    #[test]
+    fn test_decode_balances_in_order() {
+        let reverted = Call3Result {
+            success: false,
+            return_data: Bytes::default(),
+        };
+        let results = vec![ok(5), ok(7), reverted, ok(0)];
+
+        let (native, tokens) = decode_balances(&results, 3).unwrap();
+        assert_eq!(native, U256::from(5));
+        assert_eq!(tokens, vec![Some(U256::from(7)), None, Some(U256::zero())]);
+
+        // A result count that does not match the calls means the batch cannot be trusted
+        assert!(decode_balances(&results, 2).is_err());
+    }
+}
@@ -0,0 +1,174 @@
+use ethers::types::{Address, U256};
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+
+use crate::error::AppResult;
+
+/// Next nonce to hand out for one sender; None resyncs from the node
+type NonceSlot = Arc<tokio::sync::Mutex<Option<U256>>>;
+
+/// Hands out nonces per sender so concurrent transfers from one wallet don't collide
+///
+/// The node's pending nonce lags behind transactions still being broadcast, so
+/// the next nonce is `max(pending node nonce, last reserved + 1)`. Each client
+/// serves one chain, so senders are keyed by address alone.
+#[derive(Default)]
+pub struct NonceManager {
+    /// Per-sender slots; the map itself is only locked to look one up
+    senders: Mutex<HashMap<Address, NonceSlot>>,
+}
+
+impl NonceManager {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn slot(&self, address: Address) -> NonceSlot {
+        self.senders.lock().unwrap().entry(address).or_default().clone()
+    }
+
+    /// Reserve the next nonce for `address`
+    ///
+    /// `pending_nonce` fetches the node's pending transaction count; it runs under
+    /// the sender's lock so two callers never read the same value, while other
+    /// senders proceed.
+    pub async fn reserve<F>(&self, address: Address, pending_nonce: F) -> AppResult<U256>
+    where
+        F: Future<Output = AppResult<U256>>,
+    {
+        let slot = self.slot(address);
+        let mut next = slot.lock().await;
+        let node_nonce = pending_nonce.await?;
+
+        let nonce = match *next {
+            Some(local) => node_nonce.max(local),
+            None => node_nonce,
+        };
+        *next = Some(nonce + 1);
+
+        Ok(nonce)
+    }
+
+    /// Give back a nonce whose transaction was never broadcast
+    ///
+    /// Only the most recent reservation can be rolled back. If a later nonce is
+    /// already out, the cache is dropped so the next reservation resyncs from the
+    /// node and fills the gap.
+    pub async fn release(&self, address: Address, nonce: U256) {
+        let slot = self.slot(address);
+        let mut next = slot.lock().await;
+        *next = (*next == Some(nonce + 1)).then_some(nonce);
+    }
+
+    /// Drop the cached nonce so the next reservation resyncs from the node
+    ///
+    /// Used when a broadcast transaction was dropped and its nonce never mined.
+    pub async fn forget(&self, address: Address) {
+        *self.slot(address).lock().await = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn node(nonce: u64) -> impl Future<Output = AppResult<U256>> {
+        async move { Ok(U256::from(nonce)) }
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_reservations_are_distinct() {
+        let manager = Arc::new(NonceManager::new());
+        let address = Address::random();
+
+        // The node keeps reporting 7 while our transactions are still in flight
+        let handles: Vec<_> = (0..5)
+            .map(|_| {
+                let manager = manager.clone();
+                tokio::spawn(async move { manager.reserve(address, node(7)).await.unwrap() })
+            })
+            .collect();
+
+        let mut nonces = Vec::new();
+        for handle in handles {
+            nonces.push(handle.await.unwrap().as_u64());
+        }
+        nonces.sort();
+        assert_eq!(nonces, vec![7, 8, 9, 10, 11]);
+
+        // Other senders are independent
+        assert_eq!(manager.reserve(Address::random(), node(0)).await.unwrap(), U256::zero());
+    }
+
+    #[tokio::test]
+    async fn test_slow_node_call_does_not_block_other_senders() {
+        let manager = Arc::new(NonceManager::new());
+        let stalled = Address::random();
+        let (release_node, node_answers) = tokio::sync::oneshot::channel::<()>();
+
+        let pending = tokio::spawn({
+            let manager = manager.clone();
+            async move {
+                let node_nonce = async move {
+                    node_answers.await.ok();
+                    Ok(U256::from(3))
+                };
+                manager.reserve(stalled, node_nonce).await.unwrap()
+            }
+        });
+        tokio::task::yield_now().await;
+
+        // Another sender is served while the first one waits on its RPC call
+        let other = tokio::time::timeout(
+            std::time::Duration::from_secs(1),
+            manager.reserve(Address::random(), node(0)),
+        )
+        .await
+        .expect("another sender should not wait for the stalled RPC call");
+        assert_eq!(other.unwrap(), U256::zero());
+
+        release_node.send(()).unwrap();
+        assert_eq!(pending.await.unwrap(), U256::from(3));
+    }
+
+    #[tokio::test]
+    async fn test_node_ahead_of_cache_wins() {
+        let manager = NonceManager::new();
+        let address = Address::random();
+
+        assert_eq!(manager.reserve(address, node(3)).await.unwrap(), U256::from(3));
+        // Transactions sent from elsewhere moved the node past us
+        assert_eq!(manager.reserve(address, node(10)).await.unwrap(), U256::from(10));
+    }
+
+    #[tokio::test]
+    async fn test_release_rolls_back() {
+        let manager = NonceManager::new();
+        let address = Address::random();
+
+        let first = manager.reserve(address, node(5)).await.unwrap();
+        let second = manager.reserve(address, node(5)).await.unwrap();
+        assert_eq!(second, U256::from(6));
+
+        // Latest reservation failed to broadcast: reused next time
+        manager.release(address, second).await;
+        assert_eq!(manager.reserve(address, node(5)).await.unwrap(), U256::from(6));
+
+        // An older one failed: resync from the node to fill the gap
+        manager.release(address, first).await;
+        assert_eq!(manager.reserve(address, node(5)).await.unwrap(), first);
+    }
+
+    #[tokio::test]
+    async fn test_forget_resyncs_after_drop() {
+        let manager = NonceManager::new();
+        let address = Address::random();
+
+        assert_eq!(manager.reserve(address, node(4)).await.unwrap(), U256::from(4));
+        // Nonce 4 was dropped from the mempool, the node still reports 4
+        assert_eq!(manager.reserve(address, node(4)).await.unwrap(), U256::from(5));
+        manager.forget(address).await;
+        assert_eq!(manager.reserve(address, node(4)).await.unwrap(), U256::from(4));
+    }
+}
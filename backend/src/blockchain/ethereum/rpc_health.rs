@@ -0,0 +1,268 @@
+use async_trait::async_trait;
+use ethers::providers::{Http, HttpClientError, JsonRpcClient, RpcError};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fmt::Debug;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// How long a failing endpoint is skipped before it is tried again
+pub const RPC_DEMOTE_COOLDOWN: Duration = Duration::from_secs(60);
+
+/// Consecutive failures after which an endpoint is demoted
+const DEMOTE_AFTER_FAILURES: u32 = 2;
+
+/// Weight of the newest sample in the latency moving average
+const LATENCY_SMOOTHING: f64 = 0.3;
+
+#[derive(Debug, Default)]
+struct EndpointStats {
+    successes: u64,
+    failures: u64,
+    consecutive_failures: u32,
+    latency_ms: Option<f64>,
+    demoted_until: Option<Instant>,
+}
+
+impl EndpointStats {
+    fn is_demoted(&self, now: Instant) -> bool {
+        self.demoted_until.is_some_and(|until| until > now)
+    }
+}
+
+/// Health of one RPC endpoint, for observability
+#[derive(Debug, Clone, Serialize)]
+pub struct EndpointStatus {
+    pub url: String,
+    pub active: bool,
+    pub demoted: bool,
+    pub requests: u64,
+    pub error_rate: f64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub latency_ms: Option<u64>,
+}
+
+/// Per-endpoint error counts and latency, used to pick which RPC to try first
+#[derive(Debug)]
+pub struct RpcHealth {
+    cooldown: Duration,
+    stats: Mutex<HashMap<String, EndpointStats>>,
+    active: Mutex<Option<String>>,
+}
+
+impl RpcHealth {
+    pub fn new(cooldown: Duration) -> Self {
+        Self {
+            cooldown,
+            stats: Mutex::new(HashMap::new()),
+            active: Mutex::new(None),
+        }
+    }
+
+    /// Endpoints in the order to try them
+    ///
+    /// Healthy endpoints keep their configured order, so the primary is preferred
+    /// whenever it is healthy. Demoted endpoints go last, soonest-to-recover first,
+    /// and are still tried when everything else fails.
+    pub fn order(&self, endpoints: &[String]) -> Vec<String> {
+        let now = Instant::now();
+        let stats = self.stats.lock().unwrap();
+        let demoted_until = |url: &String| {
+            stats
+                .get(url)
+                .filter(|s| s.is_demoted(now))
+                .and_then(|s| s.demoted_until)
+        };
+
+        let (mut demoted, healthy): (Vec<_>, Vec<_>) = endpoints
+            .iter()
+            .map(|url| (url, demoted_until(url)))
+            .partition(|(_, until)| until.is_some());
+        demoted.sort_by_key(|(_, until)| *until);
+
+        healthy
+            .into_iter()
+            .chain(demoted)
+            .map(|(url, _)| url.clone())
+            .collect()
+    }
+
+    pub fn record_success(&self, url: &str, latency: Duration) {
+        let mut stats = self.stats.lock().unwrap();
+        let entry = stats.entry(url.to_string()).or_default();
+        let sample = latency.as_secs_f64() * 1000.0;
+
+        entry.successes += 1;
+        entry.consecutive_failures = 0;
+        entry.demoted_until = None;
+        entry.latency_ms = Some(match entry.latency_ms {
+            Some(avg) => avg + LATENCY_SMOOTHING * (sample - avg),
+            None => sample,
+        });
+
+        *self.active.lock().unwrap() = Some(url.to_string());
+    }
+
+    /// Count a failed request; returns true if this demoted the endpoint
+    pub fn record_failure(&self, url: &str) -> bool {
+        let now = Instant::now();
+        let mut stats = self.stats.lock().unwrap();
+        let entry = stats.entry(url.to_string()).or_default();
+
+        entry.failures += 1;
+        entry.consecutive_failures += 1;
+        if entry.consecutive_failures >= DEMOTE_AFTER_FAILURES && !entry.is_demoted(now) {
+            entry.demoted_until = Some(now + self.cooldown);
+            return true;
+        }
+        false
+    }
+
+    /// The endpoint that served the most recent successful request
+    pub fn active(&self) -> Option<String> {
+        self.active.lock().unwrap().clone()
+    }
+
+    /// Forget endpoints that are no longer configured
+    pub fn retain(&self, endpoints: &[String]) {
+        self.stats.lock().unwrap().retain(|url, _| endpoints.contains(url));
+        let mut active = self.active.lock().unwrap();
+        if active.as_ref().is_some_and(|url| !endpoints.contains(url)) {
+            *active = None;
+        }
+    }
+
+    pub fn status(&self, endpoints: &[String]) -> Vec<EndpointStatus> {
+        let now = Instant::now();
+        let active = self.active();
+        let stats = self.stats.lock().unwrap();
+
+        endpoints
+            .iter()
+            .map(|url| {
+                let s = stats.get(url);
+                let requests = s.map_or(0, |s| s.successes + s.failures);
+                EndpointStatus {
+                    url: url.clone(),
+                    active: active.as_ref() == Some(url),
+                    demoted: s.is_some_and(|s| s.is_demoted(now)),
+                    requests,
+                    error_rate: match s {
+                        Some(s) if requests > 0 => s.failures as f64 / requests as f64,
+                        _ => 0.0,
+                    },
+                    latency_ms: s.and_then(|s| s.latency_ms).map(|ms| ms.round() as u64),
+                }
+            })
+            .collect()
+    }
+}
+
+/// HTTP transport that counts every request against its endpoint's health
+///
+/// Errors the node answers with, such as a revert or a nonce that is too low, say
+/// nothing about the endpoint and are not counted.
+#[derive(Debug)]
+pub struct TrackedHttp {
+    inner: Http,
+    url: String,
+    health: Arc<RpcHealth>,
+}
+
+impl TrackedHttp {
+    pub fn new(inner: Http, url: &str, health: Arc<RpcHealth>) -> Self {
+        Self {
+            inner,
+            url: url.to_string(),
+            health,
+        }
+    }
+}
+
+#[async_trait]
+impl JsonRpcClient for TrackedHttp {
+    type Error = HttpClientError;
+
+    async fn request<T, R>(&self, method: &str, params: T) -> Result<R, Self::Error>
+    where
+        T: Debug + Serialize + Send + Sync,
+        R: DeserializeOwned + Send,
+    {
+        let start = Instant::now();
+        let result = self.inner.request(method, params).await;
+        match &result {
+            Ok(_) => self.health.record_success(&self.url, start.elapsed()),
+            Err(e) if e.as_error_response().is_some() => {}
+            Err(e) => {
+                tracing::debug!("RPC {} failed on {}: {}", self.url, method, e);
+                if self.health.record_failure(&self.url) {
+                    tracing::warn!(
+                        "Demoting RPC {} for {}s after repeated failures",
+                        self.url,
+                        self.health.cooldown.as_secs()
+                    );
+                }
+            }
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn endpoints() -> Vec<String> {
+        vec!["primary".to_string(), "fallback-1".to_string(), "fallback-2".to_string()]
+    }
+
+    #[test]
+    fn test_failing_primary_is_demoted_then_preferred_again() {
+        let health = RpcHealth::new(Duration::from_secs(60));
+
+        // A single failure only rotates the current request
+        assert!(!health.record_failure("primary"));
+        assert_eq!(health.order(&endpoints())[0], "primary");
+
+        assert!(health.record_failure("primary"));
+        health.record_success("fallback-1", Duration::from_millis(40));
+        assert_eq!(health.order(&endpoints()), vec!["fallback-1", "fallback-2", "primary"]);
+        assert_eq!(health.active().as_deref(), Some("fallback-1"));
+
+        let status = health.status(&endpoints());
+        assert!(status[0].demoted);
+        assert_eq!(status[0].error_rate, 1.0);
+        assert_eq!(status[1].latency_ms, Some(40));
+
+        // Recovering clears the demotion
+        health.record_success("primary", Duration::from_millis(10));
+        assert_eq!(health.order(&endpoints())[0], "primary");
+    }
+
+    #[tokio::test]
+    async fn test_failed_calls_after_selection_demote_the_endpoint() {
+        let health = Arc::new(RpcHealth::new(Duration::from_secs(60)));
+        let url = "http://127.0.0.1:1";
+        let transport = TrackedHttp::new(Http::new(url::Url::parse(url).unwrap()), url, health.clone());
+
+        // Any call, not just the block number probe, counts
+        let balance: Result<String, _> = transport.request("eth_getBalance", ("0x0", "latest")).await;
+        assert!(balance.is_err());
+        assert_eq!(health.order(&[url.to_string(), "fallback".to_string()])[0], url);
+
+        let nonce: Result<String, _> = transport.request("eth_getTransactionCount", ("0x0", "pending")).await;
+        assert!(nonce.is_err());
+        assert_eq!(health.order(&[url.to_string(), "fallback".to_string()])[0], "fallback");
+        assert_eq!(health.status(&[url.to_string()])[0].requests, 2);
+    }
+
+    #[test]
+    fn test_demotion_expires_after_cooldown() {
+        let health = RpcHealth::new(Duration::ZERO);
+        health.record_failure("primary");
+        health.record_failure("primary");
+
+        assert_eq!(health.order(&endpoints())[0], "primary");
+    }
+}
@@ -0,0 +1,60 @@
+use ethers::types::Address;
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use crate::blockchain::traits::TokenMetadata;
+
+/// ERC20 `decimals()`/`symbol()` per (chain id, contract)
+///
+/// A deployed token's metadata does not change, so entries never expire; a
+/// misread entry is dropped with `invalidate` and read again on next use.
+pub struct TokenMetadataCache {
+    entries: Mutex<HashMap<(u64, Address), TokenMetadata>>,
+}
+
+impl TokenMetadataCache {
+    pub fn new() -> Self {
+        Self {
+            entries: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn get(&self, chain_id: u64, contract: &Address) -> Option<TokenMetadata> {
+        let entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        entries.get(&(chain_id, *contract)).cloned()
+    }
+
+    pub fn put(&self, chain_id: u64, contract: Address, metadata: TokenMetadata) {
+        let mut entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        entries.insert((chain_id, contract), metadata);
+    }
+
+    /// Returns whether an entry was cached
+    pub fn invalidate(&self, chain_id: u64, contract: &Address) -> bool {
+        let mut entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        entries.remove(&(chain_id, *contract)).is_some()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cache_is_keyed_by_chain_and_contract() {
+        let cache = TokenMetadataCache::new();
+        let usdc: Address = "0xA0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48".parse().unwrap();
+        let metadata = TokenMetadata {
+            symbol: "USDC".to_string(),
+            decimals: 6,
+        };
+
+        cache.put(1, usdc, metadata.clone());
+        assert_eq!(cache.get(1, &usdc), Some(metadata));
+        assert_eq!(cache.get(137, &usdc), None);
+
+        assert!(cache.invalidate(1, &usdc));
+        assert!(!cache.invalidate(1, &usdc));
+        assert_eq!(cache.get(1, &usdc), None);
+    }
+}
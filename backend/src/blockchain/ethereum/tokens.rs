@@ -1,8 +1,11 @@
 #![allow(dead_code)]
 
+use ethers::types::Address;
 use std::collections::HashMap;
 use once_cell::sync::Lazy;
 
+use crate::error::{AppError, AppResult};
+
 #[derive(Debug, Clone)]
 pub struct TokenInfo {
     pub symbol: String,
@@ -62,3 +65,34 @@ pub static SUPPORTED_TOKENS: Lazy<HashMap<String, TokenInfo>> = Lazy::new(|| {
 pub fn get_token_info(symbol: &str) -> Option<&TokenInfo> {
     SUPPORTED_TOKENS.get(&symbol.to_uppercase())
 }
+
+/// Parse allowlisted token contracts, dropping duplicates but keeping the given order
+pub fn parse_allowlist(contracts: &[String]) -> AppResult<Vec<Address>> {
+    let mut addresses: Vec<Address> = Vec::with_capacity(contracts.len());
+    for contract in contracts {
+        let address = contract.trim().parse::<Address>().map_err(|_| {
+            AppError::ValidationError(format!("Invalid token contract address: {}", contract))
+        })?;
+        if !addresses.contains(&address) {
+            addresses.push(address);
+        }
+    }
+    Ok(addresses)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_allowlist_dedupes_case_insensitively() {
+        let contracts = vec![
+            "0xA0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48".to_string(),
+            " 0xa0b86991c6218b36c1d19d4a2e9eb0ce3606eb48 ".to_string(),
+            "0x6B175474E89094C44Da98b954EedeAC495271d0F".to_string(),
+        ];
+        assert_eq!(parse_allowlist(&contracts).unwrap().len(), 2);
+
+        assert!(parse_allowlist(&["0x1234".to_string()]).is_err());
+    }
+}
@@ -0,0 +1,149 @@
+//! Chain client for tests that never touches a node
+
+use async_trait::async_trait;
+use rust_decimal::Decimal;
+use std::sync::Mutex;
+
+use crate::error::{AppError, AppResult};
+
+use super::traits::{ChainClient, GasEstimate, SignedTransfer, TokenBalance, TransferParams, TxStatus};
+
+/// Answers balance, fee and broadcast calls with fixed values and records what was
+/// signed and broadcast
+pub struct MockChainClient {
+    chain_id: String,
+    pub native_balance: Decimal,
+    pub block_height: u64,
+    /// Txid returned by a broadcast, or the error the node reports
+    pub broadcast_result: Result<String, String>,
+    pub broadcasts: Mutex<Vec<String>>,
+    /// Raw transactions handed out by `sign_transfer`
+    pub signed: Mutex<Vec<String>>,
+}
+
+impl MockChainClient {
+    pub fn new(chain_id: &str) -> Self {
+        Self {
+            chain_id: chain_id.to_string(),
+            native_balance: Decimal::ZERO,
+            block_height: 2_800_000,
+            broadcast_result: Ok("ab".repeat(32)),
+            broadcasts: Mutex::new(Vec::new()),
+            signed: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Make every broadcast succeed with `txid`
+    pub fn accepting_broadcasts(mut self, txid: &str) -> Self {
+        self.broadcast_result = Ok(txid.to_string());
+        self
+    }
+
+    /// Make every broadcast fail with `error`
+    pub fn rejecting_broadcasts(mut self, error: &str) -> Self {
+        self.broadcast_result = Err(error.to_string());
+        self
+    }
+
+    pub fn broadcast_count(&self) -> usize {
+        self.broadcasts.lock().unwrap_or_else(|e| e.into_inner()).len()
+    }
+
+    pub fn signed_count(&self) -> usize {
+        self.signed.lock().unwrap_or_else(|e| e.into_inner()).len()
+    }
+}
+
+#[async_trait]
+impl ChainClient for MockChainClient {
+    fn chain_id(&self) -> &str {
+        &self.chain_id
+    }
+
+    fn chain_name(&self) -> &str {
+        &self.chain_id
+    }
+
+    fn key_scheme(&self) -> &str {
+        if self.chain_id == "zcash" { "zcash" } else { "ethereum" }
+    }
+
+    fn native_token_symbol(&self) -> &str {
+        if self.chain_id == "zcash" { "ZEC" } else { "ETH" }
+    }
+
+    fn native_decimals(&self) -> u8 {
+        if self.chain_id == "zcash" { 8 } else { 18 }
+    }
+
+    async fn get_native_balance(&self, _address: &str) -> AppResult<Decimal> {
+        Ok(self.native_balance)
+    }
+
+    async fn get_token_balance(&self, _address: &str, _token_symbol: &str) -> AppResult<Decimal> {
+        Ok(Decimal::ZERO)
+    }
+
+    async fn get_all_balances(&self, _address: &str) -> AppResult<(Decimal, Vec<TokenBalance>)> {
+        Ok((self.native_balance, Vec::new()))
+    }
+
+    async fn estimate_gas(&self, _params: &TransferParams) -> AppResult<GasEstimate> {
+        Ok(GasEstimate {
+            gas_limit: 21_000,
+            gas_price_gwei: Decimal::ONE,
+            estimated_fee_eth: Decimal::new(21, 6),
+            estimated_fee_wei: Some(21_000_000_000_000),
+            base_fee_gwei: None,
+            priority_fee_gwei: None,
+            max_fee_gwei: None,
+        })
+    }
+
+    async fn transfer_native(&self, _params: &TransferParams) -> AppResult<String> {
+        self.broadcast_raw_transaction("").await
+    }
+
+    async fn transfer_token(&self, _params: &TransferParams) -> AppResult<String> {
+        self.broadcast_raw_transaction("").await
+    }
+
+    /// Signs to a fake raw transaction whose hash is the txid a broadcast would return
+    async fn sign_transfer(&self, params: &TransferParams) -> AppResult<SignedTransfer> {
+        let raw_tx = hex::encode(format!("{}:{}:{}", params.to_address, params.token, params.amount));
+        self.signed
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .push(raw_tx.clone());
+        Ok(SignedTransfer {
+            raw_tx,
+            tx_hash: self.broadcast_result.clone().unwrap_or_else(|_| "ab".repeat(32)),
+        })
+    }
+
+    async fn get_tx_status(&self, _tx_hash: &str) -> AppResult<TxStatus> {
+        Ok(TxStatus::Pending)
+    }
+
+    fn validate_address(&self, address: &str) -> bool {
+        !address.is_empty()
+    }
+
+    async fn get_gas_price(&self) -> AppResult<Decimal> {
+        Ok(Decimal::ONE)
+    }
+
+    async fn get_block_height(&self) -> AppResult<u64> {
+        Ok(self.block_height)
+    }
+
+    async fn broadcast_raw_transaction(&self, raw_tx_hex: &str) -> AppResult<String> {
+        self.broadcasts
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .push(raw_tx_hex.to_string());
+        self.broadcast_result
+            .clone()
+            .map_err(AppError::BlockchainError)
+    }
+}
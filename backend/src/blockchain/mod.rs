@@ -1,4 +1,6 @@
 pub mod ethereum;
+#[cfg(test)]
+pub(crate) mod mock;
 pub mod registry;
 pub mod traits;
 pub mod zcash;
@@ -64,3 +64,59 @@ pub struct ChainInfo {
     pub name: String,
     pub native_token: String,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::blockchain::ethereum::{tokens::get_token_info, EthereumClient};
+    use crate::blockchain::zcash::ZcashClient;
+    use crate::config::{AppConfig, EvmChainConfig};
+
+    #[test]
+    fn test_native_and_token_decimals() {
+        let config = AppConfig::default();
+        let mut registry = ChainRegistry::new();
+        registry.register(Arc::new(EthereumClient::new(&config.ethereum).unwrap()));
+        registry.register(Arc::new(ZcashClient::new(&config.zcash).unwrap()));
+
+        let eth = registry.get("ethereum").unwrap();
+        assert_eq!(eth.native_token_symbol(), "ETH");
+        assert_eq!(eth.native_decimals(), 18);
+
+        let zec = registry.get("zcash").unwrap();
+        assert_eq!(zec.native_token_symbol(), "ZEC");
+        assert_eq!(zec.native_decimals(), 8);
+
+        assert_eq!(get_token_info("usdc").unwrap().decimals, 6);
+        assert_eq!(get_token_info("DAI").unwrap().decimals, 18);
+    }
+
+    #[test]
+    fn test_extra_evm_chain_registers_under_its_key() {
+        let config = AppConfig::default();
+        let polygon = EvmChainConfig {
+            name: Some("Polygon PoS".to_string()),
+            chain_id: 137,
+            rpc_url: "https://polygon-rpc.com".to_string(),
+            fallback_rpcs: vec![],
+            rpc_proxy: None,
+            native_symbol: "matic".to_string(),
+            confirmations: 64,
+            pending_timeout_minutes: 30,
+        };
+
+        let mut registry = ChainRegistry::new();
+        registry.register(Arc::new(EthereumClient::new(&config.ethereum).unwrap()));
+        registry.register(Arc::new(EthereumClient::for_evm_chain("polygon", &polygon).unwrap()));
+
+        let matic = registry.get("polygon").unwrap();
+        assert_eq!(matic.chain_name(), "Polygon PoS");
+        assert_eq!(matic.native_token_symbol(), "MATIC");
+        assert_eq!(matic.native_decimals(), 18);
+        // Wallets reuse Ethereum keys and addresses
+        assert_eq!(matic.key_scheme(), "ethereum");
+
+        assert_eq!(registry.get("ethereum").unwrap().native_token_symbol(), "ETH");
+        assert!(!registry.has_chain("bsc"));
+    }
+}
@@ -2,6 +2,7 @@
 
 use async_trait::async_trait;
 use rust_decimal::Decimal;
+use serde::Serialize;
 
 use crate::error::AppResult;
 
@@ -23,17 +24,30 @@ pub struct GasEstimate {
     pub gas_limit: u64,
     pub gas_price_gwei: Decimal,       // Legacy gas price for display
     pub estimated_fee_eth: Decimal,    // Estimated fee using max_fee
+    /// The same fee in wei, where the chain prices in gas
+    pub estimated_fee_wei: Option<u128>,
     // EIP-1559 specific fields
     pub base_fee_gwei: Option<Decimal>,
     pub priority_fee_gwei: Option<Decimal>,
     pub max_fee_gwei: Option<Decimal>,
 }
 
+/// A signed transfer that has not been broadcast
+#[derive(Debug, Clone, Serialize)]
+pub struct SignedTransfer {
+    /// Serialized signed transaction (hex), ready for `sendrawtransaction`/`eth_sendRawTransaction`
+    pub raw_tx: String,
+    /// Hash the network will know the transaction by
+    pub tx_hash: String,
+}
+
 /// Represents transaction status
 #[derive(Debug, Clone, PartialEq)]
 pub enum TxStatus {
     Pending,
-    Confirmed { block_number: u64, gas_used: u64 },
+    /// Included in a block; `confirmations` is its depth (1 = in the tip block)
+    /// and `fee` is in the chain's smallest unit, when the node reports it
+    Confirmed { block_number: u64, gas_used: u64, fee: Option<u128>, confirmations: u64 },
     Failed { reason: String },
     NotFound,
 }
@@ -43,9 +57,36 @@ pub enum TxStatus {
 pub struct TokenBalance {
     pub symbol: String,
     pub balance: Decimal,
+    pub decimals: u8,
     pub contract_address: Option<String>,
 }
 
+/// Metadata read from a token contract
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct TokenMetadata {
+    pub symbol: String,
+    pub decimals: u8,
+}
+
+/// Net change of one address's balance in one transaction (UTXO-based chains)
+#[derive(Debug, Clone)]
+pub struct AddressDelta {
+    pub txid: String,
+    pub height: u64,
+    /// In smallest unit; positive when the address received funds, negative when it spent
+    pub amount: i64,
+}
+
+/// Output paid to an address, whether spent since or not
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AddressOutput {
+    pub txid: String,
+    pub output_index: u32,
+    /// In smallest unit
+    pub value: u64,
+    pub height: u64,
+}
+
 /// UTXO (Unspent Transaction Output) for UTXO-based chains
 #[derive(Debug, Clone)]
 pub struct Utxo {
@@ -71,9 +112,23 @@ pub trait ChainClient: Send + Sync {
     /// Get the chain's display name
     fn chain_name(&self) -> &str;
 
+    /// Chain whose key derivation and address format this chain's wallets use
+    /// Default implementation is the chain itself (EVM chains share "ethereum")
+    fn key_scheme(&self) -> &str {
+        self.chain_id()
+    }
+
     /// Get the native token symbol (e.g., "ETH", "BNB")
     fn native_token_symbol(&self) -> &str;
 
+    /// Get the number of decimals of the native token (e.g., 18 for ETH)
+    fn native_decimals(&self) -> u8;
+
+    /// Whether this is a test network, whose coins have no market price
+    fn is_testnet(&self) -> bool {
+        false
+    }
+
     /// Get native token balance for an address
     async fn get_native_balance(&self, address: &str) -> AppResult<Decimal>;
 
@@ -92,12 +147,77 @@ pub trait ChainClient: Send + Sync {
     /// Execute an ERC20/BEP20 token transfer
     async fn transfer_token(&self, params: &TransferParams) -> AppResult<String>;
 
+    /// Whether `transfer_native_batch` can pay several recipients in one transaction
+    fn supports_batch_outputs(&self) -> bool {
+        false
+    }
+
+    /// Pay several `(to_address, amount)` outputs of the native token in one transaction
+    /// Default implementation returns an error (chains with one recipient per transaction)
+    async fn transfer_native_batch(
+        &self,
+        _from_address: &str,
+        _outputs: &[(String, Decimal)],
+        _private_key: &str,
+    ) -> AppResult<String> {
+        Err(crate::error::AppError::NotImplemented(
+            "Multi-output transfers not supported for this chain".to_string(),
+        ))
+    }
+
+    /// Build and sign a native or token transfer without broadcasting it
+    /// Default implementation returns an error (chains that cannot sign locally)
+    async fn sign_transfer(&self, _params: &TransferParams) -> AppResult<SignedTransfer> {
+        Err(crate::error::AppError::NotImplemented(
+            "Signing without broadcast not supported for this chain".to_string(),
+        ))
+    }
+
     /// Get transaction status
     async fn get_tx_status(&self, tx_hash: &str) -> AppResult<TxStatus>;
 
+    /// Depth a transaction must reach before its transfer is marked confirmed
+    fn required_confirmations(&self) -> u64 {
+        1
+    }
+
+    /// How long a submitted transaction may stay unmined before it counts as dropped or stuck
+    /// Default implementation returns None (never time out)
+    fn pending_timeout(&self) -> Option<std::time::Duration> {
+        None
+    }
+
+    /// Nonce of a broadcast transaction, for chains with account nonces
+    /// Default implementation returns None
+    async fn tx_nonce(&self, _tx_hash: &str) -> AppResult<Option<u64>> {
+        Ok(None)
+    }
+
+    /// Number of transactions from `address` already mined (its next unmined nonce)
+    /// Default implementation returns None
+    async fn mined_nonce(&self, _address: &str) -> AppResult<Option<u64>> {
+        Ok(None)
+    }
+
+    /// Forget locally reserved nonces after one of `address`'s transactions was dropped
+    /// Default implementation does nothing
+    async fn reset_nonce(&self, _address: &str) {}
+
     /// Validate an address format
     fn validate_address(&self, address: &str) -> bool;
 
+    /// Resolve a human-readable name (e.g. ENS `vitalik.eth`) to an address
+    /// Default implementation returns the input unchanged (chains without a naming service)
+    async fn resolve_address(&self, address: &str) -> AppResult<String> {
+        Ok(address.to_string())
+    }
+
+    /// Reverse-resolve an address to its primary name, for display
+    /// Default implementation returns None (chains without a naming service)
+    async fn lookup_name(&self, _address: &str) -> AppResult<Option<String>> {
+        Ok(None)
+    }
+
     /// Get current gas price in Gwei
     async fn get_gas_price(&self) -> AppResult<Decimal>;
 
@@ -121,12 +241,64 @@ pub trait ChainClient: Send + Sync {
         ))
     }
 
+    /// Replace a pending transaction with one using the same nonce and a higher fee (speed-up)
+    /// `new_max_fee_gwei` is the new max fee per gas (gas price for legacy transactions)
+    /// Default implementation returns an error (not applicable for chains without fee replacement)
+    async fn replace_transaction(
+        &self,
+        _tx_hash: &str,
+        _private_key: &str,
+        _new_max_fee_gwei: Decimal,
+    ) -> AppResult<String> {
+        Err(crate::error::AppError::NotImplemented(
+            "Fee replacement not supported for this chain".to_string(),
+        ))
+    }
+
+    /// Token contracts whose balances are reported; empty means the built-in token list
+    async fn token_allowlist(&self) -> Vec<String> {
+        Vec::new()
+    }
+
+    /// Replace the token allowlist, returning the validated, deduplicated contracts
+    /// Default implementation returns an error (chains without token contracts)
+    async fn set_token_allowlist(&self, _contracts: &[String]) -> AppResult<Vec<String>> {
+        Err(crate::error::AppError::NotImplemented(
+            "Token allowlist not supported for this chain".to_string(),
+        ))
+    }
+
+    /// Drop the cached metadata of a token and read it from the contract again
+    /// Default implementation returns an error (chains without token contracts)
+    async fn refresh_token_metadata(&self, _token_symbol: &str) -> AppResult<TokenMetadata> {
+        Err(crate::error::AppError::NotImplemented(
+            "Token metadata not supported for this chain".to_string(),
+        ))
+    }
+
     /// Get UTXOs for an address (used by UTXO-based chains like Zcash, Bitcoin)
     /// Default implementation returns empty vec (not applicable for account-based chains)
     async fn get_utxos(&self, _address: &str) -> AppResult<Vec<Utxo>> {
         Ok(vec![])
     }
 
+    /// Get the transaction history of an address, one delta per transaction
+    /// Default implementation returns empty vec (not applicable for account-based chains)
+    async fn get_address_history(&self, _address: &str) -> AppResult<Vec<AddressDelta>> {
+        Ok(vec![])
+    }
+
+    /// Get the outputs paid to an address in blocks `from_height..=to_height`, spent or not
+    /// Default implementation returns empty vec (not applicable for account-based chains)
+    async fn get_address_outputs(
+        &self,
+        _address: &str,
+        _from_height: u64,
+        _to_height: u64,
+    ) -> AppResult<Vec<AddressOutput>> {
+        Ok(vec![])
+    }
+
     /// Get the RPC URL for this chain
     /// Default implementation returns None (not all chains have RPC URLs exposed)
     async fn get_rpc_url(&self) -> Option<String> {
@@ -4,21 +4,24 @@ use async_trait::async_trait;
 use reqwest::Proxy;
 use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
-use std::str::FromStr;
 use tokio::sync::RwLock;
 
-use crate::blockchain::traits::{ChainClient, GasEstimate, TokenBalance, TransferParams, TxStatus, Utxo};
+use crate::blockchain::traits::{
+    AddressDelta, AddressOutput, ChainClient, GasEstimate, SignedTransfer, TokenBalance, TransferParams, TxStatus, Utxo,
+};
 use crate::blockchain::zcash::orchard::{
-    keys::OrchardKeyManager, scanner::{OrchardScanner, ShieldedBalance}, OrchardTransactionBuilder,
-    OrchardTransferParams, OrchardViewingKey, ScanProgress, ShieldedPool,
+    keys::OrchardKeyManager, scanner::{OrchardScanner, ShieldedBalance},
+    transfer::{broadcast_rejection_error, transaction_id, NetworkType}, OrchardTransactionBuilder, OrchardTransferParams,
+    OrchardViewingKey, ScanProgress, ShieldedPool,
 };
 use crate::config::ZcashConfig;
+use crate::crypto::units::{format_units, parse_units_u64, units_to_decimal, ZEC_DECIMALS};
 use crate::error::{AppError, AppResult};
 
 /// Dynamic RPC configuration that can be updated at runtime
+#[derive(Clone)]
 pub struct RpcSettings {
     pub primary_rpc: String,
-    #[allow(dead_code)]
     pub fallback_rpcs: Vec<String>,
     pub rpc_proxy: Option<String>,
     pub rpc_user: Option<String>,
@@ -29,6 +32,12 @@ pub struct ZcashClient {
     rpc_settings: RwLock<RpcSettings>,
     /// Orchard scanner for shielded note detection
     orchard_scanner: RwLock<Option<OrchardScanner>>,
+    /// Confirmations before an Orchard note is spendable
+    min_confirmations: u32,
+    /// Blocks between the chain tip and the spend anchor
+    anchor_offset: u32,
+    /// Network whose address encoding new wallets use
+    network: NetworkType,
 }
 
 // JSON-RPC request/response types
@@ -64,7 +73,7 @@ struct ValidateAddressResult {
 
 #[allow(dead_code)]
 #[derive(Debug, Deserialize)]
-struct GetBalanceResult(f64);
+struct GetBalanceResult(Decimal);
 
 #[derive(Debug, Deserialize)]
 struct ListUnspentEntry {
@@ -73,7 +82,7 @@ struct ListUnspentEntry {
     #[allow(dead_code)]
     vout: u32,
     address: String,
-    amount: f64,
+    amount: Decimal,
     confirmations: u32,
 }
 
@@ -91,6 +100,17 @@ struct AddressUtxo {
     height: u64,
 }
 
+/// Entry from getaddressdeltas RPC, one per input or output touching the address
+#[derive(Debug, Clone, Deserialize)]
+struct AddressDeltaEntry {
+    satoshis: i64,
+    txid: String,
+    /// Output index for outputs (positive amounts), input index for spends
+    #[serde(default)]
+    index: u32,
+    height: u64,
+}
+
 /// Blockchain info from getblockchaininfo RPC
 #[derive(Debug, Deserialize)]
 struct BlockchainInfo {
@@ -111,6 +131,9 @@ struct GetTransactionResult {
     blockhash: Option<String>,
     #[allow(dead_code)]
     txid: String,
+    /// Fee in ZEC, negative for sends; only present for wallet transactions
+    #[serde(default)]
+    fee: Option<Decimal>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -119,13 +142,14 @@ struct GetBlockResult {
 }
 
 #[derive(Debug, Deserialize)]
-struct EstimateFeeResult(f64);
+struct EstimateFeeResult(Decimal);
 
 /// z_sendmany recipient entry
 #[derive(Debug, Clone, Serialize)]
 struct ZSendManyRecipient {
     address: String,
-    amount: f64,
+    /// Decimal ZEC string; zcashd accepts amounts as numbers or strings
+    amount: String,
     #[serde(skip_serializing_if = "Option::is_none")]
     memo: Option<String>,
 }
@@ -235,19 +259,36 @@ impl ZcashClient {
                 rpc_password: config.rpc_password.clone(),
             }),
             orchard_scanner: RwLock::new(None),
+            min_confirmations: config.min_confirmations,
+            anchor_offset: config.anchor_offset,
+            network: config.network,
         })
     }
 
     /// Update RPC configuration dynamically (no restart required)
-    #[allow(dead_code)]
+    ///
+    /// `None` credentials keep the current value; an empty string clears it.
+    /// The new endpoint must answer `getblockchaininfo` before it is applied.
     pub async fn update_rpc(
         &self,
         primary_rpc: String,
         fallback_rpcs: Option<Vec<String>>,
+        rpc_user: Option<String>,
+        rpc_password: Option<String>,
     ) -> AppResult<()> {
-        // Test new RPC endpoint
-        let client = Self::create_http_client(&self.rpc_settings.read().await.rpc_proxy)?;
+        let settings = self.rpc_settings.read().await;
+        let client = Self::create_http_client(&settings.rpc_proxy)?;
+        let rpc_user = match rpc_user {
+            Some(user) => Some(user).filter(|u| !u.is_empty()),
+            None => settings.rpc_user.clone(),
+        };
+        let rpc_password = match rpc_password {
+            Some(password) => Some(password).filter(|p| !p.is_empty()),
+            None => settings.rpc_password.clone(),
+        };
+        drop(settings);
 
+        // Test new RPC endpoint
         let test_request = JsonRpcRequest {
             jsonrpc: "1.0",
             id: 1,
@@ -255,19 +296,22 @@ impl ZcashClient {
             params: (),
         };
 
-        let settings = self.rpc_settings.read().await;
         let mut request_builder = client.post(&primary_rpc);
-
-        if let (Some(user), Some(pass)) = (&settings.rpc_user, &settings.rpc_password) {
+        if let (Some(user), Some(pass)) = (&rpc_user, &rpc_password) {
             request_builder = request_builder.basic_auth(user, Some(pass));
         }
-        drop(settings);
 
-        request_builder
+        let response = request_builder
             .json(&test_request)
             .send()
             .await
             .map_err(|e| AppError::BlockchainError(format!("Failed to connect to new RPC: {}", e)))?;
+        if !response.status().is_success() {
+            return Err(AppError::BlockchainError(format!(
+                "New RPC rejected getblockchaininfo: HTTP {}",
+                response.status()
+            )));
+        }
 
         // Update settings
         let mut settings = self.rpc_settings.write().await;
@@ -275,11 +319,18 @@ impl ZcashClient {
         if let Some(fallbacks) = fallback_rpcs {
             settings.fallback_rpcs = fallbacks;
         }
+        settings.rpc_user = rpc_user;
+        settings.rpc_password = rpc_password;
 
         tracing::info!("Zcash RPC updated dynamically to: {}", primary_rpc);
         Ok(())
     }
 
+    /// Snapshot of the RPC settings currently in use
+    pub async fn rpc_settings(&self) -> RpcSettings {
+        self.rpc_settings.read().await.clone()
+    }
+
     /// Get current RPC URL
     #[allow(dead_code)]
     pub async fn get_current_rpc(&self) -> String {
@@ -337,15 +388,14 @@ impl ZcashClient {
             .unwrap_or_else(|_| Vec::new());
 
         if !unspent.is_empty() {
-            let total: f64 = unspent
+            let total: Decimal = unspent
                 .iter()
                 .filter(|u| u.address == address && u.confirmations > 0)
                 .map(|u| u.amount)
                 .sum();
 
             tracing::debug!("Got balance via listunspent RPC: {}", total);
-            return Ok(Decimal::from_str(&format!("{:.8}", total))
-                .unwrap_or_else(|_| Decimal::ZERO));
+            return Ok(total);
         }
 
         // If all local methods fail, return 0 with warning
@@ -371,20 +421,12 @@ impl ZcashClient {
             .await?;
 
         // Balance is in zatoshis (1 ZEC = 100000000 zatoshis)
-        let balance_zec = result.balance as f64 / 100_000_000.0;
-
-        Ok(Decimal::from_str(&format!("{:.8}", balance_zec))
-            .unwrap_or_else(|_| Decimal::ZERO))
+        units_to_decimal(result.balance.max(0) as u64, ZEC_DECIMALS)
     }
 
     /// Get balance using z_getbalance RPC (works for addresses in wallet)
     async fn get_balance_from_z_getbalance(&self, address: &str) -> AppResult<Decimal> {
-        let balance: f64 = self
-            .rpc_call("z_getbalance", (address,))
-            .await?;
-
-        Ok(Decimal::from_str(&format!("{:.8}", balance))
-            .unwrap_or_else(|_| Decimal::ZERO))
+        self.rpc_call("z_getbalance", (address,)).await
     }
 
     /// Get UTXOs for an address using getaddressutxos RPC (Zebra compatible)
@@ -397,13 +439,71 @@ impl ZcashClient {
         Ok(utxos)
     }
 
+    /// Get an address's balance changes using getaddressdeltas RPC (requires addressindex=1)
+    ///
+    /// The node reports each input and output separately; they are summed per transaction
+    /// so a spend with change shows up as a single net amount.
+    async fn get_address_deltas(&self, address: &str) -> AppResult<Vec<AddressDelta>> {
+        let entries: Vec<AddressDeltaEntry> = self
+            .rpc_call("getaddressdeltas", (serde_json::json!({"addresses": [address]}),))
+            .await?;
+
+        let mut deltas: Vec<AddressDelta> = Vec::new();
+        for entry in entries {
+            match deltas.iter_mut().find(|d| d.txid == entry.txid) {
+                Some(delta) => delta.amount += entry.satoshis,
+                None => deltas.push(AddressDelta {
+                    txid: entry.txid,
+                    height: entry.height,
+                    amount: entry.satoshis,
+                }),
+            }
+        }
+
+        tracing::debug!("Found {} transactions for address {}", deltas.len(), address);
+        Ok(deltas)
+    }
+
+    /// Get the outputs paid to an address within a block range using getaddressdeltas
+    ///
+    /// Unlike getaddressutxos this includes outputs spent since, so a deposit moved
+    /// on before it was looked at is still seen.
+    async fn get_address_output_deltas(
+        &self,
+        address: &str,
+        from_height: u64,
+        to_height: u64,
+    ) -> AppResult<Vec<AddressOutput>> {
+        let entries: Vec<AddressDeltaEntry> = self
+            .rpc_call(
+                "getaddressdeltas",
+                (serde_json::json!({
+                    "addresses": [address],
+                    "start": from_height.max(1),
+                    "end": to_height.max(1),
+                }),),
+            )
+            .await?;
+
+        Ok(entries
+            .into_iter()
+            .filter(|entry| entry.satoshis > 0)
+            .map(|entry| AddressOutput {
+                txid: entry.txid,
+                output_index: entry.index,
+                value: entry.satoshis as u64,
+                height: entry.height,
+            })
+            .collect())
+    }
+
     /// Send shielded transaction using z_sendmany RPC
     /// This is the proper way to send privacy transactions via zcashd
     ///
     /// # Arguments
     /// * `from_address` - Source address (transparent t1.., unified u1.., or shielded zs..)
     /// * `to_address` - Destination address
-    /// * `amount_zec` - Amount in ZEC
+    /// * `amount_zatoshis` - Amount in zatoshis
     /// * `memo` - Optional encrypted memo (for shielded recipients)
     ///
     /// # Returns
@@ -412,13 +512,13 @@ impl ZcashClient {
         &self,
         from_address: &str,
         to_address: &str,
-        amount_zec: f64,
+        amount_zatoshis: u64,
         memo: Option<String>,
     ) -> AppResult<String> {
         // Build recipient list
         let recipients = vec![ZSendManyRecipient {
             address: to_address.to_string(),
-            amount: amount_zec,
+            amount: format_units(amount_zatoshis, ZEC_DECIMALS),
             memo: memo.map(|m| hex::encode(m.as_bytes())), // Memo must be hex-encoded
         }];
 
@@ -543,9 +643,10 @@ impl ZcashClient {
             &raw_tx_hex[..std::cmp::min(64, raw_tx_hex.len())]
         );
 
-        let result: Result<String, _> = self
+        let result: AppResult<String> = self
             .rpc_call("sendrawtransaction", (raw_tx_hex,))
-            .await;
+            .await
+            .map_err(|e| broadcast_rejection_error(&e.to_string()).into());
 
         match &result {
             Ok(tx_hash) => {
@@ -572,15 +673,52 @@ impl ZcashClient {
         to_address: &str,
         amount: Decimal,
         private_key: &str,
+    ) -> AppResult<String> {
+        let raw_tx_hex = self
+            .build_zec_transaction(from_address, to_address, amount, private_key)
+            .await?;
+
+        // Send the raw transaction
+        let tx_hash = self.send_raw_transaction(&raw_tx_hex).await?;
+
+        tracing::info!("ZEC transfer submitted via sendrawtransaction: {}", tx_hash);
+        Ok(tx_hash)
+    }
+
+    /// Build and sign a transparent ZEC transfer, returning the raw transaction hex
+    async fn build_zec_transaction(
+        &self,
+        from_address: &str,
+        to_address: &str,
+        amount: Decimal,
+        private_key: &str,
+    ) -> AppResult<String> {
+        self.build_zec_multi_transaction(from_address, &[(to_address.to_string(), amount)], private_key)
+            .await
+    }
+
+    /// Build and sign a transparent ZEC transaction paying each `(to_address, amount)` output
+    async fn build_zec_multi_transaction(
+        &self,
+        from_address: &str,
+        outputs: &[(String, Decimal)],
+        private_key: &str,
     ) -> AppResult<String> {
         use crate::blockchain::zcash::transaction::{build_and_sign_transaction, TransactionBuilder};
 
-        // Convert amount to zatoshis (1 ZEC = 100,000,000 zatoshis)
-        let amount_zec_str = amount.to_string();
-        let amount_f64: f64 = amount_zec_str
-            .parse()
-            .map_err(|_| AppError::ValidationError("Invalid amount".to_string()))?;
-        let amount_zatoshis = (amount_f64 * 100_000_000.0) as u64;
+        if outputs.is_empty() {
+            return Err(AppError::ValidationError("Transaction has no outputs".to_string()));
+        }
+
+        // Convert amounts to zatoshis (1 ZEC = 100,000,000 zatoshis)
+        let outputs_zatoshis = outputs
+            .iter()
+            .map(|(to, amount)| Ok((to.as_str(), parse_units_u64(&amount.to_string(), ZEC_DECIMALS)?)))
+            .collect::<AppResult<Vec<_>>>()?;
+        let amount_zatoshis = outputs_zatoshis
+            .iter()
+            .try_fold(0u64, |sum, (_, value)| sum.checked_add(*value))
+            .ok_or_else(|| AppError::ValidationError("Total amount overflows".to_string()))?;
 
         // Get UTXOs for the from address
         let utxos = self.get_address_utxos(from_address).await?;
@@ -666,8 +804,10 @@ impl ZcashClient {
             }
         }
 
-        // Add output to recipient
-        builder.add_output(to_address, amount_zatoshis)?;
+        // Add an output per recipient
+        for (to_address, value) in &outputs_zatoshis {
+            builder.add_output(to_address, *value)?;
+        }
 
         // Add change output if needed
         let change = input_total - amount_zatoshis - fee_zatoshis;
@@ -689,11 +829,7 @@ impl ZcashClient {
             raw_tx_hex.len() / 2
         );
 
-        // Send the raw transaction
-        let tx_hash = self.send_raw_transaction(&raw_tx_hex).await?;
-
-        tracing::info!("ZEC transfer submitted via sendrawtransaction: {}", tx_hash);
-        Ok(tx_hash)
+        Ok(raw_tx_hex)
     }
 
     // =========================================================================
@@ -741,7 +877,7 @@ impl ZcashClient {
 
         let current_height = self.get_block_count().await?;
         let total = scanner.get_balance(wallet_id);
-        let spendable = scanner.get_spendable_balance(wallet_id, current_height);
+        let spendable = scanner.get_spendable_balance(wallet_id, current_height, self.min_confirmations);
         let notes = scanner.get_unspent_notes(wallet_id);
 
         Ok(ShieldedBalance::new(
@@ -779,7 +915,8 @@ impl ZcashClient {
         })?;
 
         let current_height = self.get_block_count().await?;
-        let spendable_notes = scanner.get_spendable_notes(params.wallet_id, current_height);
+        let spendable_notes =
+            scanner.get_spendable_notes(params.wallet_id, current_height, self.min_confirmations);
 
         if spendable_notes.is_empty() {
             return Err(AppError::ValidationError(
@@ -789,7 +926,7 @@ impl ZcashClient {
 
         // Get anchor from scanner
         let anchor = scanner.get_anchor();
-        let anchor_height = current_height.saturating_sub(10);
+        let anchor_height = current_height.saturating_sub(self.anchor_offset as u64);
         drop(scanner_lock);
 
         // Get blockchain info
@@ -937,13 +1074,31 @@ impl ChainClient for ZcashClient {
     }
 
     fn chain_name(&self) -> &str {
-        "Zcash Mainnet"
+        match self.network {
+            NetworkType::Mainnet => "Zcash Mainnet",
+            NetworkType::Testnet => "Zcash Testnet",
+        }
+    }
+
+    fn key_scheme(&self) -> &str {
+        match self.network {
+            NetworkType::Mainnet => "zcash",
+            NetworkType::Testnet => "zcash-testnet",
+        }
+    }
+
+    fn is_testnet(&self) -> bool {
+        matches!(self.network, NetworkType::Testnet)
     }
 
     fn native_token_symbol(&self) -> &str {
         "ZEC"
     }
 
+    fn native_decimals(&self) -> u8 {
+        8
+    }
+
     async fn get_native_balance(&self, address: &str) -> AppResult<Decimal> {
         let start = std::time::Instant::now();
         tracing::debug!("Getting ZEC balance for {}", address);
@@ -978,26 +1133,20 @@ impl ChainClient for ZcashClient {
     async fn estimate_gas(&self, _params: &TransferParams) -> AppResult<GasEstimate> {
         // Zcash uses transaction fees, not gas
         // Estimate fee using estimatefee RPC
-        let fee_per_kb: f64 = self
+        let fee_per_kb: Decimal = self
             .rpc_call::<EstimateFeeResult, _>("estimatefee", (6,))
             .await
             .map(|r| r.0)
-            .unwrap_or(0.0001); // Default fee if estimation fails
+            .unwrap_or(Decimal::new(1, 4)); // Default fee (0.0001) if estimation fails
 
         // Typical Zcash transparent transaction is ~250 bytes
         // For shielded transactions it can be much larger
-        let estimated_tx_size_kb = 0.25;
+        let estimated_tx_size_kb = Decimal::new(25, 2);
         let estimated_fee = fee_per_kb * estimated_tx_size_kb;
 
-        // Ensure minimum fee
-        let min_fee = 0.00001;
-        let fee = if estimated_fee < min_fee {
-            min_fee
-        } else {
-            estimated_fee
-        };
-
-        let fee_decimal = Decimal::from_str(&format!("{:.8}", fee)).unwrap_or_else(|_| Decimal::ZERO);
+        // Ensure minimum fee (0.00001 ZEC), rounded to whole zatoshis
+        let min_fee = Decimal::new(1, 5);
+        let fee_decimal = estimated_fee.max(min_fee).round_dp(ZEC_DECIMALS);
 
         // For Zcash, we use fee terminology instead of gas
         // But we map to the same structure for API consistency
@@ -1005,6 +1154,7 @@ impl ChainClient for ZcashClient {
             gas_limit: 1, // Not applicable for Zcash, use 1 as placeholder
             gas_price_gwei: fee_decimal, // Use this field to represent fee in ZEC
             estimated_fee_eth: fee_decimal, // Fee in ZEC
+            estimated_fee_wei: None,
             base_fee_gwei: None,
             priority_fee_gwei: None,
             max_fee_gwei: Some(fee_decimal),
@@ -1025,6 +1175,41 @@ impl ChainClient for ZcashClient {
         Ok(tx_hash)
     }
 
+    fn supports_batch_outputs(&self) -> bool {
+        true
+    }
+
+    async fn transfer_native_batch(
+        &self,
+        from_address: &str,
+        outputs: &[(String, Decimal)],
+        private_key: &str,
+    ) -> AppResult<String> {
+        let raw_tx_hex = self
+            .build_zec_multi_transaction(from_address, outputs, private_key)
+            .await?;
+        let tx_hash = self.send_raw_transaction(&raw_tx_hex).await?;
+
+        tracing::info!("ZEC transfer to {} recipients submitted: {}", outputs.len(), tx_hash);
+        Ok(tx_hash)
+    }
+
+    async fn sign_transfer(&self, params: &TransferParams) -> AppResult<SignedTransfer> {
+        if !params.token.eq_ignore_ascii_case(self.native_token_symbol()) {
+            return Err(AppError::NotFound(format!("Token {} not supported on Zcash", params.token)));
+        }
+
+        let raw_tx = self
+            .build_zec_transaction(&params.from_address, &params.to_address, params.amount, &params.private_key)
+            .await?;
+        let raw_bytes = hex::decode(&raw_tx)
+            .map_err(|e| AppError::InternalError(format!("Invalid raw transaction hex: {}", e)))?;
+        let tx_hash = transaction_id(&raw_bytes)?;
+
+        tracing::info!("ZEC transfer signed, not broadcast: {}", tx_hash);
+        Ok(SignedTransfer { raw_tx, tx_hash })
+    }
+
     async fn transfer_token(&self, params: &TransferParams) -> AppResult<String> {
         // Zcash doesn't support tokens like ERC20
         Err(AppError::NotFound(format!(
@@ -1052,9 +1237,16 @@ impl ChainClient for ZcashClient {
                         0
                     };
 
+                    let fee = tx
+                        .fee
+                        .and_then(|fee| parse_units_u64(&fee.abs().to_string(), ZEC_DECIMALS).ok())
+                        .map(u128::from);
+
                     Ok(TxStatus::Confirmed {
                         block_number,
                         gas_used: 0, // Zcash doesn't have gas concept
+                        fee,
+                        confirmations: confirmations as u64,
                     })
                 } else if confirmations == 0 {
                     Ok(TxStatus::Pending)
@@ -1070,37 +1262,42 @@ impl ChainClient for ZcashClient {
     }
 
     fn validate_address(&self, address: &str) -> bool {
-        // Zcash transparent addresses start with 't1' or 't3' (mainnet)
-        // Shielded addresses start with 'zs' (Sapling) or 'zc' (Sprout)
-        // Unified addresses start with 'u1'
+        // Zcash transparent addresses start with 't1' or 't3' on mainnet, 'tm' or 't2' on testnet
+        // Shielded addresses start with 'zs' (Sapling) or 'zc' (Sprout), 'ztestsapling' on testnet
+        // Unified addresses start with 'u1', 'utest1' on testnet
         if address.is_empty() {
             return false;
         }
 
+        let (transparent, sapling, unified) = match self.network {
+            NetworkType::Mainnet => (["t1", "t3"], "zs", "u1"),
+            NetworkType::Testnet => (["tm", "t2"], "ztestsapling", "utest1"),
+        };
+
         // Basic format validation for transparent addresses
-        let is_transparent = (address.starts_with("t1") || address.starts_with("t3"))
+        let is_transparent = transparent.iter().any(|prefix| address.starts_with(prefix))
             && address.len() >= 34
             && address.len() <= 36;
 
         // Basic format validation for shielded addresses
-        let is_sapling = address.starts_with("zs") && address.len() >= 78;
-        let is_sprout = address.starts_with("zc") && address.len() >= 95;
+        let is_sapling = address.starts_with(sapling) && address.len() >= 78;
+        let is_sprout = self.network == NetworkType::Mainnet && address.starts_with("zc") && address.len() >= 95;
 
         // Basic format validation for unified addresses
-        let is_unified = address.starts_with("u1") && address.len() >= 100;
+        let is_unified = address.starts_with(unified) && address.len() >= 100;
 
         is_transparent || is_sapling || is_sprout || is_unified
     }
 
     async fn get_gas_price(&self) -> AppResult<Decimal> {
         // Return estimated fee per KB for Zcash
-        let fee_per_kb: f64 = self
+        let fee_per_kb: Decimal = self
             .rpc_call::<EstimateFeeResult, _>("estimatefee", (6,))
             .await
             .map(|r| r.0)
-            .unwrap_or(0.0001);
+            .unwrap_or(Decimal::new(1, 4));
 
-        Ok(Decimal::from_str(&format!("{:.8}", fee_per_kb)).unwrap_or_else(|_| Decimal::ZERO))
+        Ok(fee_per_kb.round_dp(ZEC_DECIMALS))
     }
 
     async fn import_address_for_tracking(&self, address: &str, label: &str) -> AppResult<()> {
@@ -1129,6 +1326,19 @@ impl ChainClient for ZcashClient {
             .collect())
     }
 
+    async fn get_address_history(&self, address: &str) -> AppResult<Vec<AddressDelta>> {
+        self.get_address_deltas(address).await
+    }
+
+    async fn get_address_outputs(
+        &self,
+        address: &str,
+        from_height: u64,
+        to_height: u64,
+    ) -> AppResult<Vec<AddressOutput>> {
+        self.get_address_output_deltas(address, from_height, to_height).await
+    }
+
     async fn send_shielded(
         &self,
         from_address: &str,
@@ -1136,12 +1346,9 @@ impl ChainClient for ZcashClient {
         amount: Decimal,
         memo: Option<String>,
     ) -> AppResult<String> {
-        let amount_f64: f64 = amount
-            .to_string()
-            .parse()
-            .map_err(|_| AppError::ValidationError("Invalid amount".to_string()))?;
+        let amount_zatoshis = parse_units_u64(&amount.to_string(), ZEC_DECIMALS)?;
 
-        self.z_sendmany(from_address, to_address, amount_f64, memo).await
+        self.z_sendmany(from_address, to_address, amount_zatoshis, memo).await
     }
 
     async fn get_rpc_url(&self) -> Option<String> {
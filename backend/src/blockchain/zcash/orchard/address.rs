@@ -7,11 +7,15 @@
 
 #![allow(dead_code)]
 
-use super::{keys::OrchardViewingKey, OrchardError, OrchardResult};
+use super::{
+    keys::OrchardViewingKey,
+    sapling::SaplingViewingKey,
+    transfer::{transparent_prefixes, NetworkType},
+    OrchardError, OrchardResult,
+};
 use orchard::Address as OrchardAddress;
 use serde::{Deserialize, Serialize};
 use zcash_address::unified::{self, Container, Encoding, Receiver};
-use zcash_protocol::consensus::NetworkType;
 
 /// Information about a unified address
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -52,6 +56,10 @@ pub struct OrchardAddressManager {
     viewing_key: OrchardViewingKey,
     /// Next address index to use
     next_index: u32,
+    /// Network addresses are encoded for
+    network: NetworkType,
+    /// Sapling key of the same account, for the Sapling receiver
+    sapling_key: Option<SaplingViewingKey>,
 }
 
 impl OrchardAddressManager {
@@ -60,13 +68,27 @@ impl OrchardAddressManager {
         Self {
             viewing_key,
             next_index: 0,
+            network: NetworkType::default(),
+            sapling_key: None,
         }
     }
 
+    /// Encode addresses for `network` instead of mainnet
+    pub fn with_network(mut self, network: NetworkType) -> Self {
+        self.network = network;
+        self
+    }
+
+    /// Include a Sapling receiver derived from the account's Sapling key
+    pub fn with_sapling_key(mut self, sapling_key: SaplingViewingKey) -> Self {
+        self.sapling_key = Some(sapling_key);
+        self
+    }
+
     /// Generate a new unified address with all receivers
     ///
-    /// The address will contain Orchard, Sapling, and transparent receivers
-    /// for maximum compatibility.
+    /// The address will contain Orchard and transparent receivers, and a Sapling
+    /// receiver when the account's Sapling key is known.
     pub fn generate_unified_address(&mut self) -> OrchardResult<UnifiedAddressInfo> {
         let index = self.next_index;
         self.next_index += 1;
@@ -80,9 +102,16 @@ impl OrchardAddressManager {
         let orchard_address = self.viewing_key.address_at(index);
         let orchard_receiver = orchard_address.to_raw_address_bytes();
 
-        // For Sapling and transparent, we still use placeholders
-        // In a full implementation, these would be derived from separate keys
-        let sapling_receiver = self.derive_sapling_receiver_placeholder(index)?;
+        // Without the Sapling key there is no receiver we could decrypt, so none is included
+        let sapling_receiver = self
+            .sapling_key
+            .as_ref()
+            .and_then(|key| key.address_at(index))
+            .map(|address| address.to_bytes().to_vec())
+            .unwrap_or_default();
+
+        // For transparent, we still use a placeholder
+        // In a full implementation, this would be derived from a separate key
         let transparent_address = self.derive_transparent_address(index)?;
 
         // Encode as unified address
@@ -92,7 +121,7 @@ impl OrchardAddressManager {
         Ok(UnifiedAddressInfo {
             address: unified_address,
             has_orchard: true,
-            has_sapling: true,
+            has_sapling: !sapling_receiver.is_empty(),
             has_transparent: true,
             transparent_address: Some(transparent_address),
             address_index: index,
@@ -122,11 +151,15 @@ impl OrchardAddressManager {
 
     /// Parse a unified address to extract its components
     pub fn parse_unified_address(address: &str) -> OrchardResult<UnifiedAddressInfo> {
-        if !address.starts_with("u1") {
+        let network = if address.starts_with("u1") {
+            NetworkType::Mainnet
+        } else if address.starts_with("utest1") {
+            NetworkType::Testnet
+        } else {
             return Err(OrchardError::InvalidUnifiedAddress(
-                "Unified address must start with 'u1'".to_string(),
+                "Unified address must start with 'u1' or 'utest1'".to_string(),
             ));
-        }
+        };
 
         // Decode and parse the unified address
         // In a real implementation, this would use F4Jumble decoding
@@ -139,7 +172,7 @@ impl OrchardAddressManager {
         let transparent_address = decoded
             .iter()
             .find(|(t, _)| *t == ReceiverType::Transparent)
-            .map(|(_, data)| Self::encode_transparent_address(data));
+            .map(|(_, data)| Self::encode_transparent_address(data, network));
 
         Ok(UnifiedAddressInfo {
             address: address.to_string(),
@@ -154,7 +187,7 @@ impl OrchardAddressManager {
 
     /// Validate a unified address
     pub fn validate_unified_address(address: &str) -> bool {
-        if !address.starts_with("u1") {
+        if !address.starts_with("u1") && !address.starts_with("utest1") {
             return false;
         }
 
@@ -206,22 +239,6 @@ impl OrchardAddressManager {
             })
     }
 
-    /// Derive Sapling receiver placeholder from index
-    /// Note: This is a placeholder - real Sapling support would require separate Sapling keys
-    fn derive_sapling_receiver_placeholder(&self, index: u32) -> OrchardResult<Vec<u8>> {
-        // Sapling receiver is 43 bytes
-        // This is a placeholder - real implementation would use Sapling keys
-        let mut hasher = blake2b_simd::Params::new()
-            .hash_length(43)
-            .personal(b"ZcashSaplingRcv")
-            .to_state();
-        hasher.update(&self.viewing_key.fvk_bytes());
-        hasher.update(&index.to_le_bytes());
-        let result = hasher.finalize();
-
-        Ok(result.as_bytes().to_vec())
-    }
-
     /// Derive transparent address from index
     /// Note: This is a placeholder - real implementation would use transparent keys
     fn derive_transparent_address(&self, index: u32) -> OrchardResult<String> {
@@ -243,7 +260,8 @@ impl OrchardAddressManager {
         let hash160 = Ripemd160::digest(&sha256_hash);
 
         // Encode as t-address
-        let mut payload = vec![0x1C, 0xB8]; // Zcash mainnet t1 prefix
+        let (p2pkh, _) = transparent_prefixes(self.network);
+        let mut payload = p2pkh.to_vec();
         payload.extend_from_slice(&hash160);
 
         // Add checksum
@@ -290,8 +308,7 @@ impl OrchardAddressManager {
             OrchardError::InvalidUnifiedAddress(format!("Failed to create unified address: {:?}", e))
         })?;
 
-        // Encode for mainnet
-        let address = ua.encode(&NetworkType::Main);
+        let address = ua.encode(&self.network.address_network());
 
         Ok(address)
     }
@@ -339,21 +356,16 @@ impl OrchardAddressManager {
             OrchardError::InvalidUnifiedAddress(format!("Failed to create unified address: {:?}", e))
         })?;
 
-        Ok(ua.encode(&NetworkType::Main))
+        Ok(ua.encode(&self.network.address_network()))
     }
 
     /// Decode unified address to receivers using the proper zcash_address crate
     fn decode_unified_address(address: &str) -> OrchardResult<Vec<(ReceiverType, Vec<u8>)>> {
         // Parse the unified address using zcash_address crate
-        let (network, ua) = unified::Address::decode(address).map_err(|e| {
+        let (_, ua) = unified::Address::decode(address).map_err(|e| {
             OrchardError::InvalidUnifiedAddress(format!("Failed to decode address: {:?}", e))
         })?;
 
-        // Check network (mainnet)
-        if network != NetworkType::Main {
-            tracing::warn!("Address is for network {:?}, expected mainnet", network);
-        }
-
         // Extract receivers
         let mut receivers = Vec::new();
 
@@ -387,10 +399,11 @@ impl OrchardAddressManager {
     }
 
     /// Encode transparent address from pubkey hash
-    fn encode_transparent_address(pubkey_hash: &[u8]) -> String {
+    fn encode_transparent_address(pubkey_hash: &[u8], network: NetworkType) -> String {
         use sha2::{Digest, Sha256};
 
-        let mut payload = vec![0x1C, 0xB8];
+        let (p2pkh, _) = transparent_prefixes(network);
+        let mut payload = p2pkh.to_vec();
         payload.extend_from_slice(pubkey_hash);
 
         let checksum = Sha256::digest(&Sha256::digest(&payload));
@@ -410,8 +423,9 @@ mod tests {
     fn test_generate_unified_address() {
         let seed = vec![0u8; 64];
         let (_, vk) = OrchardKeyManager::derive_from_seed(&seed, 0, 2000000).unwrap();
+        let sapling_key = SaplingViewingKey::derive_from_seed(&seed, 0).unwrap();
 
-        let mut manager = OrchardAddressManager::new(vk);
+        let mut manager = OrchardAddressManager::new(vk.clone()).with_sapling_key(sapling_key.clone());
         let addr_info = manager.generate_unified_address().unwrap();
 
         assert!(addr_info.address.starts_with("u1"));
@@ -419,6 +433,15 @@ mod tests {
         assert!(addr_info.has_sapling);
         assert!(addr_info.has_transparent);
         assert!(addr_info.transparent_address.is_some());
+
+        // The Sapling receiver is an address of the wallet's own Sapling key
+        let receivers = OrchardAddressManager::decode_unified_address(&addr_info.address).unwrap();
+        let (_, sapling_receiver) = receivers.iter().find(|(t, _)| *t == ReceiverType::Sapling).unwrap();
+        assert_eq!(sapling_receiver[..], sapling_key.address_at(0).unwrap().to_bytes()[..]);
+
+        // Without the Sapling key no receiver is made up
+        let orchard_and_transparent = OrchardAddressManager::new(vk).generate_address_at_index(0).unwrap();
+        assert!(!orchard_and_transparent.has_sapling);
     }
 
     #[test]
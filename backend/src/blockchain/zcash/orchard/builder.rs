@@ -5,9 +5,11 @@
 #![allow(dead_code)]
 
 use super::{
-    constants::{DEFAULT_FEE_ZATOSHIS, GRACE_ACTIONS, MARGINAL_FEE_ZATOSHIS},
+    constants::DEFAULT_FEE_ZATOSHIS,
     keys::OrchardSpendingKey,
+    memo::encode_text_memo,
     scanner::OrchardNote,
+    transfer::zip317_fee,
     OrchardError, OrchardResult, ShieldedPool,
 };
 use serde::{Deserialize, Serialize};
@@ -134,13 +136,7 @@ impl OrchardTransactionBuilder {
         // Parse recipient address
         let recipient_bytes = self.parse_recipient_address(recipient)?;
 
-        // Prepare memo
-        let mut memo_bytes = [0u8; 512];
-        if let Some(m) = memo {
-            let m_bytes = m.as_bytes();
-            let len = m_bytes.len().min(512);
-            memo_bytes[..len].copy_from_slice(&m_bytes[..len]);
-        }
+        let memo_bytes = encode_text_memo(memo)?;
 
         // Generate randomness for the output
         let mut rng = rand::thread_rng();
@@ -173,13 +169,7 @@ impl OrchardTransactionBuilder {
 
     /// Calculate the required fee using ZIP 317 formula
     pub fn calculate_fee(&self) -> u64 {
-        let num_actions = self.actions.len() as u32;
-
-        if num_actions <= GRACE_ACTIONS {
-            DEFAULT_FEE_ZATOSHIS
-        } else {
-            DEFAULT_FEE_ZATOSHIS + (num_actions - GRACE_ACTIONS) as u64 * MARGINAL_FEE_ZATOSHIS
-        }
+        zip317_fee(0, 0, self.actions.len())
     }
 
     /// Build the transaction bundle
@@ -0,0 +1,247 @@
+//! Transparent UTXO selection for shielding and transparent sends
+//!
+//! Under ZIP-317 every transparent input past the grace window adds
+//! `MARGINAL_FEE_ZATOSHIS` to the fee, so an input worth less than that costs
+//! more to spend than it contributes and is never selected.
+
+use serde::{Deserialize, Serialize};
+
+use super::constants::MARGINAL_FEE_ZATOSHIS;
+use super::{OrchardError, OrchardResult};
+use crate::blockchain::traits::Utxo;
+
+/// Combinations tried by the minimize-change search before settling for the best so far
+const MAX_BNB_TRIES: usize = 100_000;
+
+/// Order in which UTXOs are picked
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SelectionStrategy {
+    /// Fewest inputs, so the lowest fee
+    #[default]
+    LargestFirst,
+    /// Spends small UTXOs first, consolidating them over time
+    SmallestFirst,
+    /// Branch-and-bound search for the set leaving the least change
+    MinimizeChange,
+}
+
+/// UTXOs chosen to fund a transaction
+#[derive(Debug, Clone)]
+pub struct UtxoSelection {
+    pub utxos: Vec<Utxo>,
+    pub total: u64,
+    /// Fee for the selected input count
+    pub fee: u64,
+    /// `total - target - fee`
+    pub change: u64,
+}
+
+/// Select UTXOs covering `target` plus the fee for the number of inputs chosen
+///
+/// `fee_for_inputs` gives the transaction fee for a given input count, so the
+/// caller decides how the rest of the transaction (outputs, Orchard actions)
+/// counts towards ZIP-317.
+pub fn select_utxos(
+    utxos: &[Utxo],
+    target: u64,
+    strategy: SelectionStrategy,
+    fee_for_inputs: impl Fn(usize) -> u64,
+) -> OrchardResult<UtxoSelection> {
+    // Inputs that do not pay for their own fee contribution are never worth spending
+    let mut candidates: Vec<&Utxo> = utxos
+        .iter()
+        .filter(|u| u.value > MARGINAL_FEE_ZATOSHIS)
+        .collect();
+
+    let chosen = match strategy {
+        SelectionStrategy::LargestFirst => {
+            candidates.sort_by(|a, b| b.value.cmp(&a.value));
+            accumulate(&candidates, target, &fee_for_inputs)
+        }
+        SelectionStrategy::SmallestFirst => {
+            candidates.sort_by(|a, b| a.value.cmp(&b.value));
+            accumulate(&candidates, target, &fee_for_inputs)
+        }
+        SelectionStrategy::MinimizeChange => {
+            candidates.sort_by(|a, b| b.value.cmp(&a.value));
+            branch_and_bound(&candidates, target, &fee_for_inputs)
+                .or_else(|| accumulate(&candidates, target, &fee_for_inputs))
+        }
+    };
+
+    let Some(chosen) = chosen else {
+        let available = candidates.iter().map(|u| u.value).sum();
+        return Err(OrchardError::InsufficientBalance {
+            available,
+            required: target + fee_for_inputs(candidates.len().max(1)),
+        });
+    };
+
+    let total: u64 = chosen.iter().map(|u| u.value).sum();
+    let fee = fee_for_inputs(chosen.len());
+    Ok(UtxoSelection {
+        utxos: chosen.into_iter().cloned().collect(),
+        total,
+        fee,
+        change: total - target - fee,
+    })
+}
+
+/// Take UTXOs in order until they cover the target and the fee for that many inputs
+fn accumulate<'a>(
+    candidates: &[&'a Utxo],
+    target: u64,
+    fee_for_inputs: &impl Fn(usize) -> u64,
+) -> Option<Vec<&'a Utxo>> {
+    let mut chosen = Vec::new();
+    let mut total = 0u64;
+    for utxo in candidates {
+        chosen.push(*utxo);
+        total += utxo.value;
+        if total >= target + fee_for_inputs(chosen.len()) {
+            return Some(chosen);
+        }
+    }
+    None
+}
+
+/// Depth-first search over the candidates (sorted largest first) for the covering set
+/// with the least change, preferring fewer inputs on ties
+///
+/// Each candidate is worth more than its marginal fee, so adding an input to a
+/// covering set only increases the change; such branches are not explored.
+fn branch_and_bound<'a>(
+    candidates: &[&'a Utxo],
+    target: u64,
+    fee_for_inputs: &impl Fn(usize) -> u64,
+) -> Option<Vec<&'a Utxo>> {
+    // remaining[i] = value of candidates[i..]
+    let mut remaining = vec![0u64; candidates.len() + 1];
+    for i in (0..candidates.len()).rev() {
+        remaining[i] = remaining[i + 1] + candidates[i].value;
+    }
+
+    let mut search = Search {
+        candidates,
+        remaining: &remaining,
+        target,
+        fee_for_inputs,
+        path: Vec::new(),
+        best: None,
+        tries: 0,
+    };
+    search.visit(0, 0);
+
+    search.best.map(|(_, indexes)| indexes.into_iter().map(|i| candidates[i]).collect())
+}
+
+/// Branch-and-bound state: the fixed inputs plus the current path and best set found
+struct Search<'s, F> {
+    candidates: &'s [&'s Utxo],
+    remaining: &'s [u64],
+    target: u64,
+    fee_for_inputs: &'s F,
+    path: Vec<usize>,
+    /// Change and candidate indexes of the best covering set so far
+    best: Option<(u64, Vec<usize>)>,
+    tries: usize,
+}
+
+impl<F: Fn(usize) -> u64> Search<'_, F> {
+    fn visit(&mut self, index: usize, total: u64) {
+        self.tries += 1;
+        if self.tries > MAX_BNB_TRIES {
+            return;
+        }
+
+        if !self.path.is_empty() {
+            let needed = self.target + (self.fee_for_inputs)(self.path.len());
+            if total >= needed {
+                let change = total - needed;
+                let better = match &self.best {
+                    Some((best_change, best_path)) => {
+                        change < *best_change || (change == *best_change && self.path.len() < best_path.len())
+                    }
+                    None => true,
+                };
+                if better {
+                    self.best = Some((change, self.path.clone()));
+                }
+                return;
+            }
+        }
+
+        // Even taking every remaining candidate cannot cover the target
+        if index >= self.candidates.len()
+            || total + self.remaining[index] < self.target + (self.fee_for_inputs)(self.path.len() + 1)
+        {
+            return;
+        }
+        // An exact match cannot be beaten
+        if matches!(self.best, Some((0, _))) {
+            return;
+        }
+
+        let with_value = total + self.candidates[index].value;
+        self.path.push(index);
+        self.visit(index + 1, with_value);
+        self.path.pop();
+        self.visit(index + 1, total);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::blockchain::zcash::orchard::transfer::transparent_transfer_fee;
+
+    fn utxos(values: &[u64]) -> Vec<Utxo> {
+        values
+            .iter()
+            .enumerate()
+            .map(|(i, value)| Utxo {
+                txid: format!("{:064x}", i),
+                output_index: 0,
+                script: String::new(),
+                value: *value,
+                height: 0,
+            })
+            .collect()
+    }
+
+    fn fee(inputs: usize) -> u64 {
+        transparent_transfer_fee(inputs, 2)
+    }
+
+    #[test]
+    fn test_strategies() {
+        let utxos = utxos(&[4_000, 50_000, 120_000, 30_000, 300_000]);
+
+        let largest = select_utxos(&utxos, 100_000, SelectionStrategy::LargestFirst, fee).unwrap();
+        assert_eq!(largest.utxos.len(), 1);
+        assert_eq!(largest.total, 300_000);
+
+        // The 4,000 UTXO is below the marginal fee and skipped
+        let smallest = select_utxos(&utxos, 100_000, SelectionStrategy::SmallestFirst, fee).unwrap();
+        let values: Vec<u64> = smallest.utxos.iter().map(|u| u.value).collect();
+        assert_eq!(values, vec![30_000, 50_000, 120_000]);
+        assert_eq!(smallest.fee, fee(3));
+
+        let minimal = select_utxos(&utxos, 100_000, SelectionStrategy::MinimizeChange, fee).unwrap();
+        assert_eq!(minimal.total, 120_000);
+        assert_eq!(minimal.change, 120_000 - 100_000 - fee(1));
+    }
+
+    #[test]
+    fn test_minimize_change_finds_exact_match() {
+        let utxos = utxos(&[70_000, 60_000, 50_000, 40_000]);
+        // 60,000 + 50,000 pays 100,000 and the two-input fee of 10,000 exactly
+        let selection = select_utxos(&utxos, 100_000, SelectionStrategy::MinimizeChange, fee).unwrap();
+        assert_eq!(selection.change, 0);
+        assert_eq!(selection.utxos.len(), 2);
+
+        let error = select_utxos(&utxos, 500_000, SelectionStrategy::MinimizeChange, fee).unwrap_err();
+        assert!(matches!(error, OrchardError::InsufficientBalance { available: 220_000, .. }));
+    }
+}
@@ -211,6 +211,13 @@ impl OrchardKeyManager {
         account_index: u32,
         birthday_height: u64,
     ) -> OrchardResult<(OrchardSpendingKey, OrchardViewingKey)> {
+        let seed = Self::seed_from_private_key(private_key_hex)?;
+        Self::derive_from_seed(&seed, account_index, birthday_height)
+    }
+
+    /// Expand a 32-byte transparent private key (hex) to the 64-byte seed its
+    /// shielded keys are derived from
+    pub fn seed_from_private_key(private_key_hex: &str) -> OrchardResult<[u8; 64]> {
         let pk_bytes = hex::decode(private_key_hex)
             .map_err(|e| OrchardError::KeyDerivation(format!("Invalid private key hex: {}", e)))?;
 
@@ -228,7 +235,9 @@ impl OrchardKeyManager {
         hasher.update(&pk_bytes);
         let seed = hasher.finalize();
 
-        Self::derive_from_seed(seed.as_bytes(), account_index, birthday_height)
+        let mut out = [0u8; 64];
+        out.copy_from_slice(seed.as_bytes());
+        Ok(out)
     }
 
     /// Derive a viewing key only (for watch-only wallets)
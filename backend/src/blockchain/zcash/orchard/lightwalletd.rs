@@ -0,0 +1,365 @@
+//! lightwalletd gRPC sync backend
+//!
+//! Streams compact blocks from a `lightwalletd` `CompactTxStreamer` instead of
+//! fetching verbose blocks from Zebra. Compact blocks only carry the first 52
+//! bytes of each note ciphertext, so memos are not recovered on this path.
+//!
+//! The message types mirror `compact_formats.proto` and `service.proto` from
+//! lightwalletd; only the fields the scanner reads are declared.
+
+use super::sapling::CompactSaplingOutput;
+use super::scanner::{CompactBlock, CompactOrchardAction, CompactTransaction};
+use super::{OrchardError, OrchardResult};
+use serde::Deserialize;
+use std::time::Duration;
+use tonic::codec::ProstCodec;
+use tonic::codegen::http::uri::PathAndQuery;
+use tonic::transport::{Channel, ClientTlsConfig, Endpoint};
+
+const GET_LATEST_BLOCK: &str = "/cash.z.wallet.sdk.rpc.CompactTxStreamer/GetLatestBlock";
+const GET_BLOCK_RANGE: &str = "/cash.z.wallet.sdk.rpc.CompactTxStreamer/GetBlockRange";
+
+/// Where the Orchard sync fetches blocks from (`zcash.sync_backend`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SyncBackend {
+    /// Verbose `getblock` calls against the Zebra JSON-RPC
+    #[default]
+    ZebraRpc,
+    /// Compact block stream from lightwalletd
+    Lightwalletd,
+}
+
+pub mod proto {
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct ChainSpec {}
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct BlockId {
+        #[prost(uint64, tag = "1")]
+        pub height: u64,
+        #[prost(bytes = "vec", tag = "2")]
+        pub hash: Vec<u8>,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct BlockRange {
+        #[prost(message, optional, tag = "1")]
+        pub start: Option<BlockId>,
+        #[prost(message, optional, tag = "2")]
+        pub end: Option<BlockId>,
+    }
+
+    /// Commitment tree sizes at the end of the block
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct ChainMetadata {
+        #[prost(uint32, tag = "1")]
+        pub sapling_commitment_tree_size: u32,
+        #[prost(uint32, tag = "2")]
+        pub orchard_commitment_tree_size: u32,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct CompactBlock {
+        #[prost(uint64, tag = "2")]
+        pub height: u64,
+        /// Block hash in internal (little-endian) byte order
+        #[prost(bytes = "vec", tag = "3")]
+        pub hash: Vec<u8>,
+        #[prost(message, repeated, tag = "7")]
+        pub vtx: Vec<CompactTx>,
+        #[prost(message, optional, tag = "8")]
+        pub chain_metadata: Option<ChainMetadata>,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct CompactTx {
+        /// Transaction id in internal (little-endian) byte order
+        #[prost(bytes = "vec", tag = "2")]
+        pub hash: Vec<u8>,
+        #[prost(message, repeated, tag = "4")]
+        pub spends: Vec<CompactSaplingSpend>,
+        #[prost(message, repeated, tag = "5")]
+        pub outputs: Vec<CompactSaplingOutput>,
+        #[prost(message, repeated, tag = "6")]
+        pub actions: Vec<CompactOrchardAction>,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct CompactSaplingSpend {
+        #[prost(bytes = "vec", tag = "1")]
+        pub nf: Vec<u8>,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct CompactSaplingOutput {
+        #[prost(bytes = "vec", tag = "1")]
+        pub cmu: Vec<u8>,
+        #[prost(bytes = "vec", tag = "2")]
+        pub ephemeral_key: Vec<u8>,
+        #[prost(bytes = "vec", tag = "3")]
+        pub ciphertext: Vec<u8>,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub struct CompactOrchardAction {
+        #[prost(bytes = "vec", tag = "1")]
+        pub nullifier: Vec<u8>,
+        #[prost(bytes = "vec", tag = "2")]
+        pub cmx: Vec<u8>,
+        #[prost(bytes = "vec", tag = "3")]
+        pub ephemeral_key: Vec<u8>,
+        #[prost(bytes = "vec", tag = "4")]
+        pub ciphertext: Vec<u8>,
+    }
+}
+
+/// Client for the lightwalletd `CompactTxStreamer` service
+#[derive(Clone)]
+pub struct LightwalletdClient {
+    channel: Channel,
+    tip_timeout: Duration,
+}
+
+impl LightwalletdClient {
+    /// Prepare a lazily connected channel; `https://` URLs use TLS
+    pub fn connect(url: &str, timeout_secs: u64, tip_timeout_secs: u64) -> OrchardResult<Self> {
+        let mut endpoint = Endpoint::from_shared(url.to_string())
+            .map_err(|e| OrchardError::RpcError(format!("Invalid lightwalletd URL: {}", e)))?
+            .timeout(Duration::from_secs(timeout_secs));
+
+        if url.starts_with("https://") {
+            endpoint = endpoint
+                .tls_config(ClientTlsConfig::new())
+                .map_err(|e| OrchardError::RpcError(format!("lightwalletd TLS setup failed: {}", e)))?;
+        }
+
+        Ok(Self {
+            channel: endpoint.connect_lazy(),
+            tip_timeout: Duration::from_secs(tip_timeout_secs),
+        })
+    }
+
+    async fn grpc(&self) -> OrchardResult<tonic::client::Grpc<Channel>> {
+        let mut grpc = tonic::client::Grpc::new(self.channel.clone());
+        grpc.ready()
+            .await
+            .map_err(|e| OrchardError::RpcError(format!("lightwalletd unavailable: {}", e)))?;
+        Ok(grpc)
+    }
+
+    /// Height of the latest block lightwalletd has ingested
+    pub async fn latest_height(&self) -> OrchardResult<u64> {
+        let mut request = tonic::Request::new(proto::ChainSpec {});
+        request.set_timeout(self.tip_timeout);
+
+        let block: proto::BlockId = self
+            .grpc()
+            .await?
+            .unary(request, PathAndQuery::from_static(GET_LATEST_BLOCK), ProstCodec::default())
+            .await
+            .map_err(|e| OrchardError::RpcError(format!("GetLatestBlock failed: {}", e)))?
+            .into_inner();
+
+        Ok(block.height)
+    }
+
+    /// Stream the compact blocks in `from_height..=to_height`
+    pub async fn block_range(&self, from_height: u64, to_height: u64) -> OrchardResult<Vec<CompactBlock>> {
+        let range = proto::BlockRange {
+            start: Some(proto::BlockId { height: from_height, hash: Vec::new() }),
+            end: Some(proto::BlockId { height: to_height, hash: Vec::new() }),
+        };
+
+        let mut stream = self
+            .grpc()
+            .await?
+            .server_streaming(
+                tonic::Request::new(range),
+                PathAndQuery::from_static(GET_BLOCK_RANGE),
+                ProstCodec::<proto::BlockRange, proto::CompactBlock>::default(),
+            )
+            .await
+            .map_err(|e| OrchardError::RpcError(format!("GetBlockRange failed: {}", e)))?
+            .into_inner();
+
+        let mut blocks = Vec::with_capacity((to_height.saturating_sub(from_height) + 1) as usize);
+        while let Some(block) = stream
+            .message()
+            .await
+            .map_err(|e| OrchardError::RpcError(format!("GetBlockRange stream failed: {}", e)))?
+        {
+            blocks.push(convert_block(block)?);
+        }
+
+        Ok(blocks)
+    }
+}
+
+/// Map a lightwalletd compact block onto the scanner's block type
+///
+/// Hashes are flipped to the display order Zebra's RPC uses, so both backends
+/// store the same txids.
+pub(crate) fn convert_block(block: proto::CompactBlock) -> OrchardResult<CompactBlock> {
+    // Sapling positions count back from the tree size after this block; servers
+    // that omit chain metadata leave Sapling unscanned, as old Zebra nodes do
+    let block_sapling_outputs: u64 = block.vtx.iter().map(|tx| tx.outputs.len() as u64).sum();
+    let mut sapling_position = block
+        .chain_metadata
+        .as_ref()
+        .map(|m| (m.sapling_commitment_tree_size as u64).saturating_sub(block_sapling_outputs));
+
+    let mut transactions = Vec::new();
+    for tx in block.vtx {
+        let orchard_actions = tx
+            .actions
+            .into_iter()
+            .map(|action| {
+                Ok(CompactOrchardAction {
+                    cmx: to_array(&action.cmx, "cmx")?,
+                    nullifier: to_array(&action.nullifier, "nullifier")?,
+                    ephemeral_key: to_array(&action.ephemeral_key, "ephemeralKey")?,
+                    ciphertext: action.ciphertext,
+                })
+            })
+            .collect::<OrchardResult<Vec<_>>>()?;
+
+        let mut sapling_outputs = Vec::new();
+        if let Some(position) = sapling_position.as_mut() {
+            for output in tx.outputs {
+                sapling_outputs.push(CompactSaplingOutput {
+                    cmu: to_array(&output.cmu, "cmu")?,
+                    ephemeral_key: to_array(&output.ephemeral_key, "ephemeralKey")?,
+                    ciphertext: output.ciphertext,
+                    position: *position,
+                });
+                *position += 1;
+            }
+        }
+
+        let sapling_nullifiers = tx
+            .spends
+            .iter()
+            .map(|spend| to_array(&spend.nf, "nf"))
+            .collect::<OrchardResult<Vec<_>>>()?;
+
+        if !orchard_actions.is_empty() || !sapling_outputs.is_empty() || !sapling_nullifiers.is_empty() {
+            transactions.push(CompactTransaction {
+                hash: display_hex(&tx.hash),
+                orchard_actions,
+                sapling_outputs,
+                sapling_nullifiers,
+            });
+        }
+    }
+
+    let mut hash = to_array(&block.hash, "block hash")?;
+    hash.reverse();
+
+    Ok(CompactBlock {
+        height: block.height,
+        hash,
+        transactions,
+    })
+}
+
+fn to_array(bytes: &[u8], field: &str) -> OrchardResult<[u8; 32]> {
+    bytes.try_into().map_err(|_| {
+        OrchardError::RpcError(format!("Invalid {}: expected 32 bytes, got {}", field, bytes.len()))
+    })
+}
+
+fn display_hex(bytes: &[u8]) -> String {
+    let mut reversed = bytes.to_vec();
+    reversed.reverse();
+    hex::encode(reversed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn action(byte: u8) -> proto::CompactOrchardAction {
+        proto::CompactOrchardAction {
+            nullifier: vec![byte; 32],
+            cmx: vec![byte; 32],
+            ephemeral_key: vec![byte; 32],
+            ciphertext: vec![byte; 52],
+        }
+    }
+
+    fn sapling_output() -> proto::CompactSaplingOutput {
+        proto::CompactSaplingOutput {
+            cmu: vec![7; 32],
+            ephemeral_key: vec![8; 32],
+            ciphertext: vec![9; 52],
+        }
+    }
+
+    #[test]
+    fn test_convert_block() {
+        let mut txid = vec![0u8; 32];
+        txid[0] = 0xab;
+
+        let block = proto::CompactBlock {
+            height: 2_800_000,
+            hash: vec![1; 32],
+            vtx: vec![
+                proto::CompactTx {
+                    hash: txid,
+                    spends: vec![proto::CompactSaplingSpend { nf: vec![5; 32] }],
+                    outputs: vec![sapling_output(), sapling_output()],
+                    actions: vec![action(1), action(2)],
+                },
+                // Transparent-only transactions are dropped
+                proto::CompactTx::default(),
+            ],
+            chain_metadata: Some(proto::ChainMetadata {
+                sapling_commitment_tree_size: 100,
+                orchard_commitment_tree_size: 50,
+            }),
+        };
+
+        let converted = convert_block(block).unwrap();
+        assert_eq!(converted.height, 2_800_000);
+        assert_eq!(converted.transactions.len(), 1);
+
+        let tx = &converted.transactions[0];
+        assert!(tx.hash.ends_with("ab"));
+        assert_eq!(tx.orchard_actions.len(), 2);
+        assert_eq!(tx.orchard_actions[1].cmx, [2; 32]);
+        assert_eq!(tx.sapling_nullifiers, vec![[5; 32]]);
+        let positions: Vec<u64> = tx.sapling_outputs.iter().map(|o| o.position).collect();
+        assert_eq!(positions, vec![98, 99]);
+    }
+
+    #[test]
+    fn test_convert_block_without_metadata_skips_sapling() {
+        let block = proto::CompactBlock {
+            height: 1,
+            hash: vec![0; 32],
+            vtx: vec![proto::CompactTx {
+                hash: vec![0; 32],
+                outputs: vec![sapling_output()],
+                ..Default::default()
+            }],
+            chain_metadata: None,
+        };
+
+        assert!(convert_block(block).unwrap().transactions.is_empty());
+    }
+
+    #[test]
+    fn test_convert_block_rejects_short_fields() {
+        let mut bad = action(1);
+        bad.cmx.truncate(31);
+        let block = proto::CompactBlock {
+            height: 1,
+            hash: vec![0; 32],
+            vtx: vec![proto::CompactTx { actions: vec![bad], ..Default::default() }],
+            chain_metadata: None,
+        };
+
+        assert!(convert_block(block).is_err());
+    }
+}
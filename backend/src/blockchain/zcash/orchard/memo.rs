@@ -0,0 +1,205 @@
+//! Shielded memo encoding (ZIP 302)
+//!
+//! Every Orchard output carries a 512-byte memo. The first byte tells how to read it:
+//! `<= 0xF4` is UTF-8 text padded with zeros, `0xF6` followed by zeros is "no memo",
+//! and everything else is binary data this wallet does not interpret.
+
+use orchard::keys::{IncomingViewingKey, PreparedIncomingViewingKey};
+use orchard::note::{ExtractedNoteCommitment, Nullifier};
+use orchard::note_encryption::{CompactAction, OrchardDomain};
+use zcash_note_encryption::{
+    try_note_decryption, EphemeralKeyBytes, ShieldedOutput, COMPACT_NOTE_SIZE, ENC_CIPHERTEXT_SIZE,
+};
+
+use super::{OrchardError, OrchardResult};
+
+/// Size of a memo field in bytes
+pub const MEMO_SIZE: usize = 512;
+
+/// Lead byte of the "no memo" encoding
+const NO_MEMO: u8 = 0xF6;
+
+/// Highest lead byte of a text memo (the largest UTF-8 lead byte)
+const MAX_TEXT_LEAD_BYTE: u8 = 0xF4;
+
+/// A memo as read by the wallet
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Memo {
+    Empty,
+    Text(String),
+    /// Non-text memo, with trailing zero padding removed
+    Binary(Vec<u8>),
+}
+
+/// Check that text can be sent as a ZIP 302 text memo
+///
+/// Text memos have no separate lead byte: the first UTF-8 byte (never above 0xF4)
+/// is the lead byte and all 512 bytes hold the text. Zero bytes are padding, so
+/// text containing NUL would not read back the same and is rejected.
+pub fn validate_text_memo(text: &str) -> OrchardResult<()> {
+    let bytes = text.as_bytes();
+    if bytes.len() > MEMO_SIZE {
+        return Err(OrchardError::TransactionBuild(format!(
+            "Memo is {} bytes, the limit is {}",
+            bytes.len(),
+            MEMO_SIZE
+        )));
+    }
+    if bytes.contains(&0) {
+        return Err(OrchardError::TransactionBuild(
+            "Memo must not contain NUL characters".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+/// Encode an optional text memo, rejecting text that does not fit in 512 bytes
+pub fn encode_text_memo(text: Option<&str>) -> OrchardResult<[u8; MEMO_SIZE]> {
+    let mut memo = [0u8; MEMO_SIZE];
+    match text.filter(|t| !t.is_empty()) {
+        None => memo[0] = NO_MEMO,
+        Some(text) => {
+            validate_text_memo(text)?;
+            memo[..text.len()].copy_from_slice(text.as_bytes());
+        }
+    }
+    Ok(memo)
+}
+
+/// Decode memo bytes following the ZIP 302 lead-byte convention
+pub fn decode_memo(bytes: &[u8]) -> OrchardResult<Memo> {
+    if bytes.len() > MEMO_SIZE {
+        return Err(OrchardError::NoteDecryption(format!(
+            "Memo is {} bytes, the limit is {}",
+            bytes.len(),
+            MEMO_SIZE
+        )));
+    }
+
+    let end = bytes.iter().rposition(|b| *b != 0).map_or(0, |i| i + 1);
+    let trimmed = &bytes[..end];
+
+    match trimmed.first() {
+        // All zeros is how older senders (including this wallet) wrote "no memo"
+        None => Ok(Memo::Empty),
+        Some(&NO_MEMO) if trimmed.len() == 1 => Ok(Memo::Empty),
+        Some(&lead) if lead <= MAX_TEXT_LEAD_BYTE => match std::str::from_utf8(trimmed) {
+            Ok(text) => Ok(Memo::Text(text.to_string())),
+            Err(_) => Ok(Memo::Binary(trimmed.to_vec())),
+        },
+        Some(_) => Ok(Memo::Binary(trimmed.to_vec())),
+    }
+}
+
+/// An Orchard action with its full 580-byte ciphertext, as `getblock` reports it
+struct FullOrchardOutput<'a> {
+    cmx: [u8; 32],
+    ephemeral_key: [u8; 32],
+    enc_ciphertext: &'a [u8; ENC_CIPHERTEXT_SIZE],
+}
+
+impl ShieldedOutput<OrchardDomain, ENC_CIPHERTEXT_SIZE> for FullOrchardOutput<'_> {
+    fn ephemeral_key(&self) -> EphemeralKeyBytes {
+        EphemeralKeyBytes(self.ephemeral_key)
+    }
+
+    fn cmstar_bytes(&self) -> [u8; 32] {
+        self.cmx
+    }
+
+    fn enc_ciphertext(&self) -> &[u8; ENC_CIPHERTEXT_SIZE] {
+        self.enc_ciphertext
+    }
+}
+
+/// Decrypt the memo of an action already known to belong to `ivk`
+///
+/// Compact trial decryption only covers the first 52 bytes of the ciphertext, so the
+/// memo needs a second, full decryption. Returns `None` for truncated ciphertexts.
+pub fn try_decrypt_memo(
+    ivk: &IncomingViewingKey,
+    nullifier: &[u8; 32],
+    cmx: &[u8; 32],
+    ephemeral_key: &[u8; 32],
+    ciphertext: &[u8],
+) -> Option<[u8; MEMO_SIZE]> {
+    let enc_ciphertext: &[u8; ENC_CIPHERTEXT_SIZE] = ciphertext.try_into().ok()?;
+    let nullifier = Option::from(Nullifier::from_bytes(nullifier))?;
+    let cmx_value = Option::from(ExtractedNoteCommitment::from_bytes(cmx))?;
+    let compact_ciphertext: [u8; COMPACT_NOTE_SIZE] = enc_ciphertext[..COMPACT_NOTE_SIZE].try_into().ok()?;
+
+    // The domain only depends on rho, which is the action's nullifier
+    let domain = OrchardDomain::for_compact_action(&CompactAction::from_parts(
+        nullifier,
+        cmx_value,
+        EphemeralKeyBytes(*ephemeral_key),
+        compact_ciphertext,
+    ));
+    let output = FullOrchardOutput {
+        cmx: *cmx,
+        ephemeral_key: *ephemeral_key,
+        enc_ciphertext,
+    };
+
+    try_note_decryption(&domain, &PreparedIncomingViewingKey::new(ivk), &output).map(|(_, _, memo)| memo)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::blockchain::zcash::orchard::keys::OrchardKeyManager;
+    use orchard::keys::Scope;
+    use orchard::note::{RandomSeed, Rho};
+    use orchard::note_encryption::OrchardNoteEncryption;
+    use orchard::value::NoteValue;
+    use orchard::Note;
+    use zcash_note_encryption::Domain;
+
+    #[test]
+    fn test_zip302_decoding() {
+        let text = encode_text_memo(Some("INV-2024-001 ✓")).unwrap();
+        assert_eq!(decode_memo(&text).unwrap(), Memo::Text("INV-2024-001 ✓".to_string()));
+
+        assert_eq!(decode_memo(&encode_text_memo(None).unwrap()).unwrap(), Memo::Empty);
+        assert_eq!(decode_memo(&encode_text_memo(Some("")).unwrap()).unwrap(), Memo::Empty);
+        assert_eq!(decode_memo(&[0u8; MEMO_SIZE]).unwrap(), Memo::Empty);
+
+        // 0xFF marks arbitrary data; invalid UTF-8 after a text lead byte is binary too
+        let mut binary = [0u8; MEMO_SIZE];
+        binary[..3].copy_from_slice(&[0xFF, 0x01, 0x02]);
+        assert_eq!(decode_memo(&binary).unwrap(), Memo::Binary(vec![0xFF, 0x01, 0x02]));
+        assert_eq!(decode_memo(&[0x61, 0xC3, 0x28]).unwrap(), Memo::Binary(vec![0x61, 0xC3, 0x28]));
+
+        assert!(encode_text_memo(Some(&"a".repeat(MEMO_SIZE))).is_ok());
+        assert!(encode_text_memo(Some(&"a".repeat(MEMO_SIZE + 1))).is_err());
+        // Multi-byte characters count by their UTF-8 length
+        assert!(validate_text_memo(&"✓".repeat(170)).is_ok());
+        assert!(validate_text_memo(&"✓".repeat(171)).is_err());
+        assert!(validate_text_memo("ab\0").is_err());
+        assert!(decode_memo(&[0x61; MEMO_SIZE + 1]).is_err());
+    }
+
+    #[test]
+    fn test_decrypts_memo_of_received_note() {
+        let (_, vk) = OrchardKeyManager::derive_from_seed(&[7u8; 64], 0, 0).unwrap();
+        let nullifier = [0u8; 32];
+        let rho = Rho::from_bytes(&nullifier).unwrap();
+        let rseed = (0u8..=255)
+            .find_map(|i| Option::from(RandomSeed::from_bytes([i; 32], &rho)))
+            .unwrap();
+        let note = Note::from_parts(vk.address_at(0), NoteValue::from_raw(50_000), rho, rseed).unwrap();
+
+        let memo = encode_text_memo(Some("thanks for lunch")).unwrap();
+        let encryption = OrchardNoteEncryption::new(None, note, memo);
+        let ciphertext = encryption.encrypt_note_plaintext();
+        let ephemeral_key = OrchardDomain::epk_bytes(encryption.epk()).0;
+        let cmx = ExtractedNoteCommitment::from(note.commitment()).to_bytes();
+
+        let ivk = vk.fvk().to_ivk(Scope::External);
+        let decrypted = try_decrypt_memo(&ivk, &nullifier, &cmx, &ephemeral_key, &ciphertext).unwrap();
+        assert_eq!(decode_memo(&decrypted).unwrap(), Memo::Text("thanks for lunch".to_string()));
+
+        // A compact (52-byte) ciphertext has no memo to recover
+        assert!(try_decrypt_memo(&ivk, &nullifier, &cmx, &ephemeral_key, &ciphertext[..COMPACT_NOTE_SIZE]).is_none());
+    }
+}
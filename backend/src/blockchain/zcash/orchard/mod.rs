@@ -7,7 +7,12 @@
 
 pub mod address;
 pub mod builder;
+pub mod coin_selection;
 pub mod keys;
+pub mod lightwalletd;
+pub mod memo;
+pub mod offline;
+pub mod sapling;
 pub mod scanner;
 pub mod sync;
 pub mod transfer;
@@ -17,16 +22,79 @@ pub mod witness_sync;
 pub use address::UnifiedAddressInfo;
 pub use builder::{OrchardTransactionBuilder, OrchardTransferParams};
 pub use keys::OrchardViewingKey;
+pub use self::sapling::SaplingViewingKey;
 pub use scanner::ScanProgress;
 pub use transfer::init_proving_key;
 
 /// Orchard protocol constants
 pub mod constants {
-    /// Minimum confirmations before considering a note spendable
-    pub const MIN_CONFIRMATIONS: u32 = 10;
+    /// NU5 activation on mainnet, the first block that can hold Orchard notes
+    pub const ORCHARD_ACTIVATION_HEIGHT: u64 = 1_687_104;
 
-    /// Orchard anchor depth for security
-    pub const ANCHOR_OFFSET: u32 = 10;
+    /// Default confirmations before considering a note spendable
+    /// (configurable via `zcash.min_confirmations`)
+    pub const DEFAULT_MIN_CONFIRMATIONS: u32 = 10;
+
+    /// Fewer confirmations than this leave received notes exposed to ordinary reorgs
+    pub const SAFE_MIN_CONFIRMATIONS: u32 = 3;
+
+    /// Default Orchard anchor depth for security (configurable via `zcash.anchor_offset`)
+    pub const DEFAULT_ANCHOR_OFFSET: u32 = 10;
+
+    /// Shielded spends are refused while the scan is further behind the tip than this,
+    /// since notes and witnesses may still be incomplete
+    pub const SPEND_MAX_SYNC_LAG_BLOCKS: u64 = 10;
+
+    /// Blocks between persisted tree/witness checkpoints during a scan
+    pub const TREE_CHECKPOINT_INTERVAL: u64 = 10_000;
+
+    /// Checkpoints kept in the database; older ones are pruned
+    pub const TREE_CHECKPOINTS_KEPT: u32 = 10;
+
+    /// Deepest chain reorg the sync walks back through looking for a common ancestor
+    pub const MAX_REORG_DEPTH: u64 = 100;
+
+    /// Recent block hashes kept for reorg detection; older blocks are considered final
+    pub const BLOCK_HASH_RETENTION: u64 = 1_000;
+
+    /// Default timeout for heavy RPC calls such as block batches
+    /// (configurable via `zcash.rpc_timeout_secs`)
+    pub const DEFAULT_RPC_TIMEOUT_SECS: u64 = 120;
+
+    /// Default timeout for lightweight chain tip queries used by health and progress
+    /// (configurable via `zcash.tip_rpc_timeout_secs`)
+    pub const DEFAULT_TIP_RPC_TIMEOUT_SECS: u64 = 5;
+
+    /// Default blocks fetched per Orchard sync round (configurable via `zcash.sync_batch_size`)
+    pub const DEFAULT_SYNC_BATCH_SIZE: u64 = 500;
+
+    /// Largest accepted `zcash.sync_batch_size`
+    pub const MAX_SYNC_BATCH_SIZE: u64 = 10_000;
+
+    /// Default concurrent block fetches during Orchard sync
+    /// (configurable via `zcash.sync_parallel_fetches`)
+    ///
+    /// Each fetch is a `getblockhash` + verbose `getblock` pair. 25 in flight keeps a
+    /// local Zebra node busy without queueing requests behind its state service, while
+    /// staying polite to hosted endpoints. zcashd rejects requests beyond its
+    /// `-rpcworkqueue` (16 by default), so lower this when syncing against zcashd.
+    pub const DEFAULT_SYNC_PARALLEL_FETCHES: usize = 25;
+
+    /// Largest accepted `zcash.sync_parallel_fetches`
+    pub const MAX_SYNC_PARALLEL_FETCHES: usize = 100;
+
+    const _: () = assert!(DEFAULT_SYNC_PARALLEL_FETCHES <= MAX_SYNC_PARALLEL_FETCHES);
+    const _: () = assert!(DEFAULT_SYNC_BATCH_SIZE <= MAX_SYNC_BATCH_SIZE);
+
+    /// How long a transfer holds the notes it spends before broadcasting them
+    pub const NOTE_RESERVATION_SECS: u64 = 300;
+
+    /// Highest fee a transfer may pay; anything above indicates a misconfiguration
+    pub const MAX_FEE_ZATOSHIS: u64 = 100_000;
+
+    /// Most notes one consolidation spends; each is a separate Orchard action to prove,
+    /// and 20 actions already reach the 0.001 ZEC fee ceiling enforced on execute
+    pub const MAX_CONSOLIDATION_NOTES: usize = 20;
 
     /// Default fee for Orchard transactions (in zatoshis)
     /// Orchard actions are more expensive than transparent transactions
@@ -37,6 +105,9 @@ pub mod constants {
     pub const GRACE_ACTIONS: u32 = 2;
     pub const P2PKH_STANDARD_INPUT_SIZE: u64 = 150;
     pub const P2PKH_STANDARD_OUTPUT_SIZE: u64 = 34;
+
+    /// Smallest P2PKH output relayed by zcashd; smaller change is left to the fee
+    pub const TRANSPARENT_DUST_ZATOSHIS: u64 = 54;
 }
 
 /// Error types for Orchard operations
@@ -72,6 +143,9 @@ pub enum OrchardError {
     #[error("Invalid unified address: {0}")]
     InvalidUnifiedAddress(String),
 
+    #[error("Invalid recipient address: {0}")]
+    InvalidAddress(String),
+
     #[error("RPC error: {0}")]
     RpcError(String),
 
@@ -81,7 +155,11 @@ pub enum OrchardError {
 
 impl From<OrchardError> for crate::error::AppError {
     fn from(err: OrchardError) -> Self {
-        crate::error::AppError::BlockchainError(err.to_string())
+        match err {
+            // A bad recipient is the caller's mistake, not a chain failure
+            OrchardError::InvalidAddress(_) => crate::error::AppError::ValidationError(err.to_string()),
+            _ => crate::error::AppError::BlockchainError(err.to_string()),
+        }
     }
 }
 
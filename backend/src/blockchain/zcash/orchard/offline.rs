@@ -0,0 +1,394 @@
+//! Unsigned transfer packages for air-gapped signing
+//!
+//! A watch-only instance prepares everything a spend needs except the keys: the
+//! proposal, the selected notes with their witnesses, any transparent UTXOs and the
+//! anchor the witnesses are valid against. The package travels as JSON to an offline
+//! instance holding the spending key, which signs it into a raw transaction without
+//! touching the network. The raw transaction is then brought back for broadcast.
+//!
+//! Format (`format` = `"orchard-unsigned-transfer"`, `version` = 1):
+//!
+//! ```text
+//! {
+//!   "format": "orchard-unsigned-transfer",
+//!   "version": 1,
+//!   "network": "mainnet",
+//!   "viewing_key": "ufvk:<account>:<birthday>:<hex>",
+//!   "proposal": { ...TransferProposal... },
+//!   "anchor": "<32-byte hex>",
+//!   "anchor_height": 2800000,
+//!   "notes": [{ "value_zatoshis", "position", "nullifier", "recipient", "rho", "rseed",
+//!               "auth_path": ["<32-byte hex>", ...32 entries] }],
+//!   "transparent_inputs": [{ "prev_tx_hash", "prev_tx_index", "script_pubkey", "value", "sequence" }]
+//! }
+//! ```
+
+use serde::{Deserialize, Serialize};
+
+use super::{
+    keys::{OrchardKeyManager, OrchardViewingKey},
+    scanner::OrchardNote,
+    transfer::{NetworkType, OrchardTransferService, TransferProposal, TransferResult, TransparentInput},
+    tree::WitnessData,
+    OrchardError, OrchardResult,
+};
+use orchard::tree::{Anchor, MerklePath};
+
+pub const PACKAGE_FORMAT: &str = "orchard-unsigned-transfer";
+pub const PACKAGE_VERSION: u32 = 1;
+
+/// A shielded note to spend, with the witness proving it is in the tree
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PackagedNote {
+    pub value_zatoshis: u64,
+    pub position: u64,
+    pub nullifier: String,
+    pub recipient: String,
+    pub rho: String,
+    pub rseed: String,
+    pub auth_path: Vec<String>,
+}
+
+/// A transparent UTXO to shield
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PackagedTransparentInput {
+    pub prev_tx_hash: String,
+    pub prev_tx_index: u32,
+    pub script_pubkey: String,
+    pub value: u64,
+    pub sequence: u32,
+}
+
+/// Everything needed to sign a transfer offline
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UnsignedTransferPackage {
+    pub format: String,
+    pub version: u32,
+    pub network: NetworkType,
+    /// Viewing key of the spending account; the signer checks its key matches
+    pub viewing_key: String,
+    pub proposal: TransferProposal,
+    pub anchor: String,
+    pub anchor_height: u64,
+    pub notes: Vec<PackagedNote>,
+    pub transparent_inputs: Vec<PackagedTransparentInput>,
+}
+
+impl UnsignedTransferPackage {
+    pub fn new(
+        network: NetworkType,
+        viewing_key: &OrchardViewingKey,
+        proposal: TransferProposal,
+        notes: &[(OrchardNote, WitnessData)],
+        transparent_inputs: &[TransparentInput],
+        anchor: &Anchor,
+        anchor_height: u64,
+    ) -> Self {
+        Self {
+            format: PACKAGE_FORMAT.to_string(),
+            version: PACKAGE_VERSION,
+            network,
+            viewing_key: viewing_key.encode(),
+            proposal,
+            anchor: hex::encode(anchor.to_bytes()),
+            anchor_height,
+            notes: notes
+                .iter()
+                .map(|(note, witness)| PackagedNote {
+                    value_zatoshis: note.value_zatoshis,
+                    position: witness.position,
+                    nullifier: hex::encode(note.nullifier),
+                    recipient: hex::encode(note.recipient),
+                    rho: hex::encode(note.rho),
+                    rseed: hex::encode(note.rseed),
+                    auth_path: witness.auth_path.iter().map(hex::encode).collect(),
+                })
+                .collect(),
+            transparent_inputs: transparent_inputs
+                .iter()
+                .map(|input| PackagedTransparentInput {
+                    prev_tx_hash: hex::encode(input.prev_tx_hash),
+                    prev_tx_index: input.prev_tx_index,
+                    script_pubkey: hex::encode(&input.script_pubkey),
+                    value: input.value,
+                    sequence: input.sequence,
+                })
+                .collect(),
+        }
+    }
+
+    pub fn to_json(&self) -> OrchardResult<String> {
+        serde_json::to_string_pretty(self)
+            .map_err(|e| OrchardError::TransactionBuild(format!("Failed to serialize package: {}", e)))
+    }
+
+    /// Parse a package, rejecting other formats and versions
+    pub fn from_json(json: &str) -> OrchardResult<Self> {
+        let package: Self = serde_json::from_str(json)
+            .map_err(|e| OrchardError::TransactionBuild(format!("Invalid transfer package: {}", e)))?;
+        package.check_format()?;
+        Ok(package)
+    }
+
+    pub fn check_format(&self) -> OrchardResult<()> {
+        if self.format != PACKAGE_FORMAT || self.version != PACKAGE_VERSION {
+            return Err(OrchardError::TransactionBuild(format!(
+                "Unsupported transfer package {} v{} (expected {} v{})",
+                self.format, self.version, PACKAGE_FORMAT, PACKAGE_VERSION
+            )));
+        }
+        Ok(())
+    }
+
+    pub fn anchor(&self) -> OrchardResult<Anchor> {
+        let bytes: [u8; 32] = decode_array(&self.anchor, "anchor")?;
+        Option::from(Anchor::from_bytes(bytes))
+            .ok_or_else(|| OrchardError::TransactionBuild("Invalid anchor in package".to_string()))
+    }
+
+    /// Notes with the Merkle paths the transfer builder spends them with
+    pub fn notes_with_paths(&self) -> OrchardResult<Vec<(OrchardNote, MerklePath)>> {
+        self.notes
+            .iter()
+            .map(|packaged| {
+                let witness = WitnessData {
+                    position: packaged.position,
+                    auth_path: packaged
+                        .auth_path
+                        .iter()
+                        .map(|hash| decode_array(hash, "auth_path"))
+                        .collect::<OrchardResult<_>>()?,
+                    root: decode_array(&self.anchor, "anchor")?,
+                };
+                let merkle_path = witness
+                    .to_merkle_path()
+                    .map_err(|e| OrchardError::TransactionBuild(format!("Invalid witness in package: {}", e)))?;
+
+                let note = OrchardNote {
+                    id: None,
+                    wallet_id: None,
+                    account_id: 0,
+                    tx_hash: String::new(),
+                    block_height: 0,
+                    note_commitment: [0u8; 32],
+                    nullifier: decode_array(&packaged.nullifier, "nullifier")?,
+                    value_zatoshis: packaged.value_zatoshis,
+                    position: packaged.position,
+                    is_spent: false,
+                    memo: None,
+                    merkle_path: None,
+                    recipient: decode_array(&packaged.recipient, "recipient")?,
+                    rho: decode_array(&packaged.rho, "rho")?,
+                    rseed: decode_array(&packaged.rseed, "rseed")?,
+                    witness_data: Some(witness),
+                };
+                Ok((note, merkle_path))
+            })
+            .collect()
+    }
+
+    pub fn transparent_inputs(&self) -> OrchardResult<Vec<TransparentInput>> {
+        self.transparent_inputs
+            .iter()
+            .map(|input| {
+                Ok(TransparentInput {
+                    prev_tx_hash: decode_array(&input.prev_tx_hash, "prev_tx_hash")?,
+                    prev_tx_index: input.prev_tx_index,
+                    script_pubkey: hex::decode(&input.script_pubkey).map_err(|e| {
+                        OrchardError::TransactionBuild(format!("Invalid script_pubkey in package: {}", e))
+                    })?,
+                    value: input.value,
+                    sequence: input.sequence,
+                })
+            })
+            .collect()
+    }
+
+    /// Sign the package with the account's private key, producing a raw transaction
+    ///
+    /// Runs entirely offline. Fails if the key does not belong to the account the
+    /// package was prepared for.
+    pub fn sign(&self, private_key_hex: &str) -> OrchardResult<TransferResult> {
+        let expected = OrchardViewingKey::decode(&self.viewing_key)?;
+        let (spending_key, viewing_key) =
+            OrchardKeyManager::derive_from_private_key(private_key_hex, expected.account_index, expected.birthday_height)?;
+        if viewing_key.fvk_bytes() != expected.fvk_bytes() {
+            return Err(OrchardError::KeyDerivation(
+                "Private key does not match the package's viewing key".to_string(),
+            ));
+        }
+
+        OrchardTransferService::new(self.network).build_transaction(
+            &self.proposal,
+            &spending_key,
+            private_key_hex,
+            self.notes_with_paths()?,
+            self.transparent_inputs()?,
+            self.anchor_height,
+            self.anchor()?,
+        )
+    }
+}
+
+fn decode_array<const N: usize>(value: &str, field: &str) -> OrchardResult<[u8; N]> {
+    hex::decode(value)
+        .ok()
+        .and_then(|bytes| <[u8; N]>::try_from(bytes).ok())
+        .ok_or_else(|| OrchardError::TransactionBuild(format!("Invalid {} in package", field)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::blockchain::zcash::orchard::transfer::{orchard_nullifiers, FundSource};
+    use crate::blockchain::zcash::orchard::tree::OrchardTreeTracker;
+    use orchard::note::{ExtractedNoteCommitment, RandomSeed, Rho};
+    use orchard::value::NoteValue;
+
+    const PRIVATE_KEY: &str = "0123456789abcdef0123456789abcdef0123456789abcdef0123456789abcdef";
+
+    /// Real notes to the account's own address, witnessed in a fresh tree
+    fn notes_in_tree(values: &[u64]) -> (Vec<(OrchardNote, WitnessData)>, Anchor, OrchardViewingKey) {
+        let (_, viewing_key) = OrchardKeyManager::derive_from_private_key(PRIVATE_KEY, 0, 2_800_000).unwrap();
+        let fvk = viewing_key.fvk().clone();
+        let recipient = viewing_key.address_at(0);
+
+        let mut tree = OrchardTreeTracker::new();
+        let mut notes = Vec::new();
+        for (i, value) in values.iter().enumerate() {
+            let mut rho_bytes = [0u8; 32];
+            rho_bytes[0] = i as u8 + 1;
+            let rho = Rho::from_bytes(&rho_bytes).unwrap();
+            let (rseed, note) = (0u8..=255)
+                .find_map(|seed| {
+                    let rseed: Option<RandomSeed> = RandomSeed::from_bytes([seed; 32], &rho).into();
+                    let rseed = rseed?;
+                    let note: Option<orchard::Note> =
+                        orchard::Note::from_parts(recipient, NoteValue::from_raw(*value), rho, rseed).into();
+                    note.map(|note| ([seed; 32], note))
+                })
+                .unwrap();
+
+            let cmx = ExtractedNoteCommitment::from(note.commitment()).to_bytes();
+            let position = tree.append_and_mark(&cmx).unwrap();
+            notes.push((
+                OrchardNote {
+                    id: None,
+                    wallet_id: Some(1),
+                    account_id: 0,
+                    tx_hash: String::new(),
+                    block_height: 2_800_000,
+                    note_commitment: cmx,
+                    nullifier: note.nullifier(&fvk).to_bytes(),
+                    value_zatoshis: *value,
+                    position,
+                    is_spent: false,
+                    memo: None,
+                    merkle_path: None,
+                    recipient: recipient.to_raw_address_bytes(),
+                    rho: rho.to_bytes(),
+                    rseed,
+                    witness_data: None,
+                },
+                position,
+            ));
+        }
+
+        let notes = notes
+            .into_iter()
+            .map(|(note, position)| (note, tree.get_witness(position).unwrap()))
+            .collect();
+        (notes, tree.get_anchor(), viewing_key)
+    }
+
+    fn proposal(to_address: String) -> TransferProposal {
+        TransferProposal {
+            proposal_id: "offline".to_string(),
+            amount_zatoshis: 250_000,
+            fee_zatoshis: 10_000,
+            fund_source: FundSource::Shielded,
+            is_shielding: false,
+            is_deshielding: false,
+            to_address,
+            memo: None,
+            expiry_height: 2_800_040,
+            coin_selection: Default::default(),
+        }
+    }
+
+    fn recipient_address() -> String {
+        let other_key = "fedcba9876543210fedcba9876543210fedcba9876543210fedcba9876543210";
+        let (address, _) = crate::crypto::zcash::enable_orchard_for_wallet(other_key, 2_800_000, NetworkType::Mainnet).unwrap();
+        address.address
+    }
+
+    fn package(values: &[u64]) -> (UnsignedTransferPackage, Vec<(OrchardNote, WitnessData)>) {
+        let (notes, anchor, viewing_key) = notes_in_tree(values);
+        let input = TransparentInput {
+            prev_tx_hash: [9u8; 32],
+            prev_tx_index: 1,
+            script_pubkey: vec![0x76, 0xa9, 0x14],
+            value: 5_000,
+            sequence: 0xfffffffe,
+        };
+        let package = UnsignedTransferPackage::new(
+            NetworkType::Mainnet,
+            &viewing_key,
+            proposal(recipient_address()),
+            &notes,
+            &[input],
+            &anchor,
+            2_800_000,
+        );
+        (package, notes)
+    }
+
+    #[test]
+    fn test_package_round_trips() {
+        let (package, notes) = package(&[100_000, 200_000]);
+        let parsed = UnsignedTransferPackage::from_json(&package.to_json().unwrap()).unwrap();
+
+        assert_eq!(parsed.notes, package.notes);
+        assert_eq!(parsed.transparent_inputs, package.transparent_inputs);
+        assert_eq!(parsed.proposal.amount_zatoshis, 250_000);
+        assert_eq!(parsed.anchor().unwrap().to_bytes(), package.anchor().unwrap().to_bytes());
+
+        // Each restored path still leads from the note to the packaged anchor
+        let restored = parsed.notes_with_paths().unwrap();
+        for ((note, path), (original, _)) in restored.iter().zip(&notes) {
+            assert_eq!(note.nullifier, original.nullifier);
+            assert_eq!(note.rseed, original.rseed);
+            let cmx = ExtractedNoteCommitment::from_bytes(&original.note_commitment).unwrap();
+            assert_eq!(path.root(cmx).to_bytes(), parsed.anchor().unwrap().to_bytes());
+        }
+        assert_eq!(parsed.transparent_inputs().unwrap()[0].prev_tx_hash, [9u8; 32]);
+    }
+
+    #[test]
+    fn test_package_rejects_other_versions() {
+        let (mut package, _) = package(&[100_000]);
+        package.version = 2;
+        let err = UnsignedTransferPackage::from_json(&package.to_json().unwrap()).unwrap_err();
+        assert!(err.to_string().contains("Unsupported transfer package"));
+    }
+
+    #[test]
+    fn test_offline_signing_produces_broadcastable_transaction() {
+        // The payment needs both notes
+        let (mut package, notes) = package(&[100_000, 200_000]);
+        package.transparent_inputs.clear();
+
+        // Only the account's own key can sign
+        let other_key = "fedcba9876543210fedcba9876543210fedcba9876543210fedcba9876543210";
+        assert!(package.sign(other_key).is_err());
+
+        let signed = package.sign(PRIVATE_KEY).unwrap();
+        let raw_tx = hex::decode(signed.raw_tx.unwrap()).unwrap();
+
+        // Parses as a consensus v5 transaction spending our notes
+        let revealed = orchard_nullifiers(&raw_tx).unwrap();
+        for (note, _) in &notes {
+            assert!(revealed.contains(&note.nullifier));
+        }
+    }
+}
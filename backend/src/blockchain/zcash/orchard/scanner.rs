@@ -6,11 +6,12 @@
 #![allow(dead_code)]
 
 use super::{
-    constants::MIN_CONFIRMATIONS,
     keys::OrchardViewingKey,
+    sapling::CompactSaplingOutput,
     tree::{OrchardTreeTracker, WitnessData},
     OrchardResult, ShieldedPool,
 };
+use crate::crypto::units::{format_units, ZEC_DECIMALS};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
@@ -38,6 +39,10 @@ pub struct ScanProgress {
     /// Estimated time remaining in seconds
     pub estimated_seconds_remaining: Option<u64>,
 
+    /// Scan speed of the current run
+    #[serde(default)]
+    pub blocks_per_second: Option<f64>,
+
     /// Whether scanning is currently active
     pub is_scanning: bool,
 
@@ -55,6 +60,7 @@ impl ScanProgress {
             chain_tip_height: chain_tip,
             progress_percent: 0.0,
             estimated_seconds_remaining: None,
+            blocks_per_second: None,
             is_scanning: false,
             notes_found: 0,
         }
@@ -73,6 +79,7 @@ impl ScanProgress {
 
             if scanned_blocks > 0 && elapsed_secs > 0.0 {
                 let blocks_per_sec = scanned_blocks as f64 / elapsed_secs;
+                self.blocks_per_second = Some(blocks_per_sec);
                 let remaining_blocks = self.chain_tip_height - scanned_to;
                 self.estimated_seconds_remaining = Some((remaining_blocks as f64 / blocks_per_sec) as u64);
             }
@@ -125,7 +132,7 @@ pub struct OrchardNote {
     /// Whether this note has been spent
     pub is_spent: bool,
 
-    /// Decrypted memo (if any)
+    /// Hex-encoded memo bytes (if any)
     pub memo: Option<String>,
 
     /// Merkle path for spending (populated when needed)
@@ -318,7 +325,12 @@ impl OrchardScanner {
     /// IMPORTANT: Always fetches fresh witness data from tree_tracker.
     /// The tree_tracker maintains up-to-date witnesses as new commitments are appended.
     /// The witness root must match the current tree state for valid transactions.
-    pub fn get_spendable_notes(&self, wallet_id: i32, current_height: u64) -> Vec<OrchardNote> {
+    pub fn get_spendable_notes(
+        &self,
+        wallet_id: i32,
+        current_height: u64,
+        min_confirmations: u32,
+    ) -> Vec<OrchardNote> {
         self.notes
             .get(&wallet_id)
             .map(|notes| {
@@ -326,7 +338,7 @@ impl OrchardScanner {
                     .iter()
                     .filter(|n| {
                         !n.is_spent
-                            && current_height >= n.block_height + MIN_CONFIRMATIONS as u64
+                            && current_height >= n.block_height + min_confirmations as u64
                     })
                     .cloned()
                     .map(|mut note| {
@@ -357,7 +369,7 @@ impl OrchardScanner {
     }
 
     /// Get spendable balance (confirmed notes only) for a wallet
-    pub fn get_spendable_balance(&self, wallet_id: i32, current_height: u64) -> u64 {
+    pub fn get_spendable_balance(&self, wallet_id: i32, current_height: u64, min_confirmations: u32) -> u64 {
         self.notes
             .get(&wallet_id)
             .map(|notes| {
@@ -365,7 +377,7 @@ impl OrchardScanner {
                     .iter()
                     .filter(|n| {
                         !n.is_spent
-                            && current_height >= n.block_height + MIN_CONFIRMATIONS as u64
+                            && current_height >= n.block_height + min_confirmations as u64
                     })
                     .map(|n| n.value_zatoshis)
                     .sum()
@@ -474,11 +486,11 @@ impl OrchardScanner {
                         note.witness_data = self.tree_tracker.get_witness(position);
 
                         tracing::info!(
-                            "[Orchard Scan] 🎉 Found note! block={}, tx={}, value={} zatoshis ({:.8} ZEC), position={}, has_witness={}",
+                            "[Orchard Scan] 🎉 Found note! block={}, tx={}, value={} zatoshis ({} ZEC), position={}, has_witness={}",
                             block.height,
                             &tx.hash[..16],
                             note.value_zatoshis,
-                            note.value_zatoshis as f64 / 100_000_000.0,
+                            format_units(note.value_zatoshis, ZEC_DECIMALS),
                             position,
                             note.witness_data.is_some()
                         );
@@ -864,6 +876,10 @@ pub struct CompactTransaction {
     pub hash: String,
     /// Orchard actions
     pub orchard_actions: Vec<CompactOrchardAction>,
+    /// Sapling outputs, trial-decrypted for balance reporting
+    pub sapling_outputs: Vec<CompactSaplingOutput>,
+    /// Nullifiers revealed by Sapling spends
+    pub sapling_nullifiers: Vec<[u8; 32]>,
 }
 
 /// Compact Orchard action data
@@ -911,14 +927,14 @@ impl ShieldedBalance {
         }
     }
 
-    /// Get balance in ZEC (decimal)
-    pub fn total_zec(&self) -> f64 {
-        self.total_zatoshis as f64 / 100_000_000.0
+    /// Get balance in ZEC (decimal string)
+    pub fn total_zec(&self) -> String {
+        format_units(self.total_zatoshis, ZEC_DECIMALS)
     }
 
     /// Get spendable balance in ZEC
-    pub fn spendable_zec(&self) -> f64 {
-        self.spendable_zatoshis as f64 / 100_000_000.0
+    pub fn spendable_zec(&self) -> String {
+        format_units(self.spendable_zatoshis, ZEC_DECIMALS)
     }
 }
 
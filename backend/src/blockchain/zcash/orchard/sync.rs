@@ -16,6 +16,7 @@ use super::{
     OrchardError, OrchardResult, ShieldedPool,
 };
 use crate::db::repositories::OrchardRepository;
+use crate::metrics::METRICS;
 use orchard::keys::IncomingViewingKey;
 use serde::Deserialize;
 use sqlx::MySqlPool;
@@ -39,6 +40,14 @@ pub struct SyncConfig {
     pub birthday_height: u64,
     /// Number of concurrent block fetches
     pub parallel_fetches: usize,
+    /// Confirmations before a note is considered spendable
+    pub min_confirmations: u32,
+    /// Timeout for block batch and other heavy RPC calls
+    pub rpc_timeout_secs: u64,
+    /// Timeout for chain tip queries
+    pub tip_rpc_timeout_secs: u64,
+    /// HTTP/HTTPS/SOCKS5 proxy for RPC requests (optional)
+    pub rpc_proxy: Option<String>,
 }
 
 impl Default for SyncConfig {
@@ -47,9 +56,13 @@ impl Default for SyncConfig {
             rpc_url: "http://127.0.0.1:8232".to_string(),
             rpc_user: None,
             rpc_password: None,
-            batch_size: 500,  // Process 500 blocks per round
+            batch_size: super::constants::DEFAULT_SYNC_BATCH_SIZE,
             birthday_height: 1_687_104,  // Orchard activation height
-            parallel_fetches: 25,  // Smaller batch = faster response, more parallel
+            parallel_fetches: super::constants::DEFAULT_SYNC_PARALLEL_FETCHES,
+            min_confirmations: super::constants::DEFAULT_MIN_CONFIRMATIONS,
+            rpc_timeout_secs: super::constants::DEFAULT_RPC_TIMEOUT_SECS,
+            tip_rpc_timeout_secs: super::constants::DEFAULT_TIP_RPC_TIMEOUT_SECS,
+            rpc_proxy: None,
         }
     }
 }
@@ -133,10 +146,57 @@ struct OrchardCommitments {
     final_state: String,
 }
 
+/// Parse the Orchard root returned by `z_gettreestate` into an anchor
+pub(crate) fn parse_anchor_hex(root_hex: &str) -> OrchardResult<[u8; 32]> {
+    let root_bytes = hex::decode(root_hex)
+        .map_err(|e| OrchardError::RpcError(format!("Invalid root hex: {}", e)))?;
+
+    if root_bytes.len() != 32 {
+        return Err(OrchardError::RpcError(format!(
+            "Invalid root length: expected 32, got {}",
+            root_bytes.len()
+        )));
+    }
+
+    let mut anchor = [0u8; 32];
+    anchor.copy_from_slice(&root_bytes);
+    Ok(anchor)
+}
+
+/// Client builder for Zebra RPC, routed through `rpc_proxy` when one is set
+///
+/// Takes the same http/https/socks5 URLs as the Zcash RPC client.
+pub(crate) fn rpc_client_builder(
+    timeout_secs: u64,
+    rpc_proxy: Option<&str>,
+) -> OrchardResult<reqwest::ClientBuilder> {
+    let builder = reqwest::Client::builder().timeout(std::time::Duration::from_secs(timeout_secs));
+
+    match rpc_proxy.filter(|p| !p.is_empty()) {
+        Some(url) => {
+            let proxy = reqwest::Proxy::all(url)
+                .map_err(|e| OrchardError::RpcError(format!("Invalid proxy URL: {}", e)))?;
+            tracing::debug!("Orchard sync RPC proxy configured");
+            Ok(builder.proxy(proxy))
+        }
+        None => Ok(builder),
+    }
+}
+
+/// Lightweight client for `getblockcount`, so a slow node fails tip checks fast
+/// instead of holding health and progress requests for the full batch timeout
+pub(crate) fn create_tip_client(timeout_secs: u64, rpc_proxy: Option<&str>) -> OrchardResult<reqwest::Client> {
+    rpc_client_builder(timeout_secs, rpc_proxy)?
+        .build()
+        .map_err(|e| OrchardError::RpcError(format!("Failed to create HTTP client: {}", e)))
+}
+
 /// Orchard synchronization service with database persistence
 pub struct OrchardSyncService {
     config: SyncConfig,
     client: reqwest::Client,
+    /// Short-timeout client for chain tip queries
+    tip_client: reqwest::Client,
     scanner: Arc<RwLock<OrchardScanner>>,
     /// Stored notes by wallet_id (memory cache)
     notes_by_wallet: Arc<RwLock<HashMap<i32, Vec<OrchardNote>>>>,
@@ -150,40 +210,41 @@ pub struct OrchardSyncService {
 
 impl OrchardSyncService {
     /// Create HTTP client with high connection pool for parallel fetching
-    fn create_http_client() -> reqwest::Client {
-        reqwest::Client::builder()
-            .timeout(std::time::Duration::from_secs(120))  // 2 min timeout for batch requests
+    fn create_http_client(timeout_secs: u64, rpc_proxy: Option<&str>) -> OrchardResult<reqwest::Client> {
+        rpc_client_builder(timeout_secs, rpc_proxy)?
             .pool_max_idle_per_host(100)  // Allow many idle connections
             .pool_idle_timeout(std::time::Duration::from_secs(60))
             .tcp_keepalive(std::time::Duration::from_secs(30))
             .build()
-            .unwrap_or_default()
+            .map_err(|e| OrchardError::RpcError(format!("Failed to create HTTP client: {}", e)))
     }
 
     /// Create a new sync service without database (memory only)
-    pub fn new(config: SyncConfig) -> Self {
-        Self {
+    pub fn new(config: SyncConfig) -> OrchardResult<Self> {
+        Ok(Self {
+            client: Self::create_http_client(config.rpc_timeout_secs, config.rpc_proxy.as_deref())?,
+            tip_client: create_tip_client(config.tip_rpc_timeout_secs, config.rpc_proxy.as_deref())?,
             config,
-            client: Self::create_http_client(),
             scanner: Arc::new(RwLock::new(OrchardScanner::new(vec![]))),
             notes_by_wallet: Arc::new(RwLock::new(HashMap::new())),
             wallet_keys: Arc::new(RwLock::new(HashMap::new())),
             db_repo: None,
             wallet_scan_heights: Arc::new(RwLock::new(HashMap::new())),
-        }
+        })
     }
 
     /// Create a new sync service with database persistence
-    pub fn new_with_db(config: SyncConfig, pool: MySqlPool) -> Self {
-        Self {
+    pub fn new_with_db(config: SyncConfig, pool: MySqlPool) -> OrchardResult<Self> {
+        Ok(Self {
+            client: Self::create_http_client(config.rpc_timeout_secs, config.rpc_proxy.as_deref())?,
+            tip_client: create_tip_client(config.tip_rpc_timeout_secs, config.rpc_proxy.as_deref())?,
             config,
-            client: Self::create_http_client(),
             scanner: Arc::new(RwLock::new(OrchardScanner::new(vec![]))),
             notes_by_wallet: Arc::new(RwLock::new(HashMap::new())),
             wallet_keys: Arc::new(RwLock::new(HashMap::new())),
             db_repo: Some(Arc::new(OrchardRepository::new(pool))),
             wallet_scan_heights: Arc::new(RwLock::new(HashMap::new())),
-        }
+        })
     }
 
     /// Register a wallet's viewing key for scanning
@@ -232,7 +293,8 @@ impl OrchardSyncService {
 
     /// Get current chain height from Zebra
     pub async fn get_chain_height(&self) -> OrchardResult<u64> {
-        let response: RpcResponse<u64> = self.rpc_call("getblockcount", serde_json::json!([])).await?;
+        let response: RpcResponse<u64> =
+            Self::rpc_call_with_client(&self.tip_client, &self.config, "getblockcount", serde_json::json!([])).await?;
         response.result.ok_or_else(|| {
             OrchardError::RpcError(response.error.map(|e| e.message).unwrap_or_default())
         })
@@ -300,20 +362,7 @@ impl OrchardSyncService {
     /// Use this to validate that our locally-computed tree root matches the chain state.
     pub async fn get_expected_anchor(&self, height: u64) -> OrchardResult<[u8; 32]> {
         let (_frontier_hex, root_hex, _) = self.get_tree_state(height).await?;
-
-        // Parse the hex root string
-        let root_bytes = hex::decode(&root_hex)
-            .map_err(|e| OrchardError::RpcError(format!("Invalid root hex: {}", e)))?;
-
-        if root_bytes.len() != 32 {
-            return Err(OrchardError::RpcError(format!(
-                "Invalid root length: expected 32, got {}",
-                root_bytes.len()
-            )));
-        }
-
-        let mut anchor = [0u8; 32];
-        anchor.copy_from_slice(&root_bytes);
+        let anchor = parse_anchor_hex(&root_hex)?;
 
         tracing::debug!(
             "[Orchard Sync] Expected anchor at height {}: {}",
@@ -606,6 +655,8 @@ impl OrchardSyncService {
                     transactions.push(CompactTransaction {
                         hash: tx.txid.clone(),
                         orchard_actions: actions,
+                        sapling_outputs: Vec::new(),
+                        sapling_nullifiers: Vec::new(),
                     });
                 }
             }
@@ -874,23 +925,37 @@ impl OrchardSyncService {
                 last_persist_height = end_height;
             }
 
-            // Log progress every 500 blocks
             let blocks_scanned = current_height - start_height - 1;
-            if blocks_scanned % 500 == 0 || blocks_scanned == blocks_to_scan {
-                let elapsed = sync_start.elapsed().as_secs_f64();
-                let blocks_per_sec = if elapsed > 0.0 {
-                    blocks_scanned as f64 / elapsed
-                } else {
-                    0.0
-                };
-                let remaining_blocks = chain_tip.saturating_sub(current_height) + 1;
-                let eta_secs = if blocks_per_sec > 0.0 {
-                    remaining_blocks as f64 / blocks_per_sec
-                } else {
-                    0.0
-                };
-                let progress_pct = (blocks_scanned as f64 / blocks_to_scan as f64) * 100.0;
+            let elapsed = sync_start.elapsed().as_secs_f64();
+            let blocks_per_sec = if elapsed > 0.0 {
+                blocks_scanned as f64 / elapsed
+            } else {
+                0.0
+            };
+            let remaining_blocks = chain_tip.saturating_sub(current_height) + 1;
+            let eta_secs = if blocks_per_sec > 0.0 {
+                remaining_blocks as f64 / blocks_per_sec
+            } else {
+                0.0
+            };
+            let progress_pct = (blocks_scanned as f64 / blocks_to_scan as f64) * 100.0;
+
+            // Publish to metrics
+            let progress = ScanProgress {
+                chain: "zcash".to_string(),
+                scan_type: "orchard".to_string(),
+                last_scanned_height: end_height,
+                chain_tip_height: chain_tip,
+                progress_percent: progress_pct.min(100.0),
+                estimated_seconds_remaining: Some(eta_secs as u64),
+                blocks_per_second: Some(blocks_per_sec),
+                is_scanning: current_height <= chain_tip,
+                notes_found: total_notes_found as u64,
+            };
+            METRICS.record_sync_progress(&progress);
 
+            // Log progress every 500 blocks
+            if blocks_scanned % 500 == 0 || blocks_scanned == blocks_to_scan {
                 tracing::info!(
                     "[Orchard Sync] Progress: {:.1}% ({}/{} blocks), {:.1} blocks/sec, ETA: {:.0}s, notes found: {}",
                     progress_pct,
@@ -1021,7 +1086,7 @@ impl OrchardSyncService {
         if let Some(repo) = &self.db_repo {
             for spent in spent_notes {
                 let nullifier_hex = hex::encode(spent.nullifier);
-                match repo.mark_note_spent(&nullifier_hex, &spent.spent_in_tx).await {
+                match repo.mark_note_spent_at(&nullifier_hex, &spent.spent_in_tx, spent.block_height).await {
                     Ok(updated) => {
                         if updated {
                             tracing::info!(
@@ -1221,7 +1286,7 @@ impl OrchardSyncService {
         let chain_height = self.get_chain_height().await.unwrap_or(0);
 
         let total = scanner.get_balance(wallet_id);
-        let spendable = scanner.get_spendable_balance(wallet_id, chain_height);
+        let spendable = scanner.get_spendable_balance(wallet_id, chain_height, self.config.min_confirmations);
         let unspent_notes = scanner.get_unspent_notes(wallet_id);
 
         ShieldedBalance::new(
@@ -1244,7 +1309,7 @@ impl OrchardSyncService {
         let scanner = self.scanner.read().await;
         let chain_height = self.get_chain_height().await.unwrap_or(0);
         // Now uses wallet_id directly - no filtering needed
-        scanner.get_spendable_notes(wallet_id, chain_height)
+        scanner.get_spendable_notes(wallet_id, chain_height, self.config.min_confirmations)
     }
 
     /// Get current scan progress
@@ -1300,7 +1365,7 @@ impl OrchardSyncService {
         let chain_height = self.get_chain_height().await.unwrap_or(0);
 
         // Now uses wallet_id directly - notes are properly isolated by wallet_id in scanner
-        let memory_notes = scanner.get_spendable_notes(wallet_id, chain_height);
+        let memory_notes = scanner.get_spendable_notes(wallet_id, chain_height, self.config.min_confirmations);
 
         // Check how many have witness data in memory
         let notes_with_memory_witness = memory_notes.iter().filter(|n| n.witness_data.is_some()).count();
@@ -1813,7 +1878,7 @@ impl OrchardSyncService {
         // Get fresh witnesses from scanner (now uses wallet_id directly)
         let notes_with_witnesses = {
             let scanner = self.scanner.read().await;
-            scanner.get_spendable_notes(wallet_id, chain_tip)
+            scanner.get_spendable_notes(wallet_id, chain_tip, self.config.min_confirmations)
                 .into_iter()
                 .filter(|n| note_nullifiers.contains(&n.nullifier) && n.witness_data.is_some())
                 .collect::<Vec<_>>()
@@ -1923,7 +1988,7 @@ impl OrchardSyncService {
             let mut processed_nullifiers = std::collections::HashSet::new();
 
             for (wallet_id, _vk) in keys.iter() {
-                let notes = scanner.get_spendable_notes(*wallet_id, chain_height);
+                let notes = scanner.get_spendable_notes(*wallet_id, chain_height, self.config.min_confirmations);
 
                 for note in notes {
                     // Skip if already processed (deduplication)
@@ -2009,21 +2074,50 @@ pub fn try_decrypt_orchard_note(
 #[cfg(test)]
 mod tests {
     use super::*;
+    use super::super::constants::{DEFAULT_SYNC_BATCH_SIZE, DEFAULT_SYNC_PARALLEL_FETCHES};
 
     #[test]
     fn test_sync_config_default() {
         let config = SyncConfig::default();
         assert_eq!(config.rpc_url, "http://127.0.0.1:8232");
-        assert_eq!(config.batch_size, 500);
-        assert_eq!(config.parallel_fetches, 10);
+        assert_eq!(config.batch_size, DEFAULT_SYNC_BATCH_SIZE);
+        assert_eq!(config.parallel_fetches, DEFAULT_SYNC_PARALLEL_FETCHES);
+    }
+
+    #[test]
+    fn test_sync_config_matches_zcash_config_defaults() {
+        let sync = SyncConfig::default();
+        let zcash = crate::config::AppConfig::default().zcash;
+        assert_eq!(sync.batch_size, zcash.sync_batch_size);
+        assert_eq!(sync.parallel_fetches, zcash.sync_parallel_fetches);
+        assert_eq!(sync.min_confirmations, zcash.min_confirmations);
+        assert_eq!(sync.rpc_timeout_secs, zcash.rpc_timeout_secs);
+        assert_eq!(sync.tip_rpc_timeout_secs, zcash.tip_rpc_timeout_secs);
     }
 
     #[tokio::test]
     async fn test_sync_service_creation() {
         let config = SyncConfig::default();
-        let service = OrchardSyncService::new(config);
+        let service = OrchardSyncService::new(config).unwrap();
 
         let progress = service.get_progress().await;
         assert_eq!(progress.notes_found, 0);
     }
+
+    #[test]
+    fn test_sync_service_rpc_proxy() {
+        for proxy in ["socks5://127.0.0.1:9050", "http://proxy.local:3128", ""] {
+            let config = SyncConfig {
+                rpc_proxy: Some(proxy.to_string()),
+                ..SyncConfig::default()
+            };
+            assert!(OrchardSyncService::new(config).is_ok(), "{}", proxy);
+        }
+
+        let config = SyncConfig {
+            rpc_proxy: Some("not a proxy".to_string()),
+            ..SyncConfig::default()
+        };
+        assert!(OrchardSyncService::new(config).is_err());
+    }
 }
@@ -5,15 +5,23 @@
 //! - Shielded to shielded transfers (maximum privacy)
 //! - Transparent to shielded transfers (shielding)
 //! - Shielded to transparent transfers (deshielding)
+//! - Transparent to transparent transfers, which touch no shielded pool
 
 #![allow(dead_code)]
 
 use super::{
-    constants::DEFAULT_FEE_ZATOSHIS,
+    coin_selection::SelectionStrategy,
+    constants::{DEFAULT_FEE_ZATOSHIS, GRACE_ACTIONS, MARGINAL_FEE_ZATOSHIS, TRANSPARENT_DUST_ZATOSHIS},
     keys::OrchardSpendingKey,
+    memo::{encode_text_memo, validate_text_memo},
     scanner::{OrchardNote, ShieldedBalance},
     OrchardError, OrchardResult,
 };
+use crate::blockchain::zcash::transaction::{
+    CONSENSUS_BRANCH_ID_NU5, CONSENSUS_BRANCH_ID_NU6, CONSENSUS_BRANCH_ID_NU6_1,
+};
+use crate::crypto::units::{parse_units_u64, ZEC_DECIMALS};
+use crate::metrics::METRICS;
 use serde::{Deserialize, Serialize};
 
 use orchard::{
@@ -41,6 +49,7 @@ const ZCASH_SEQUENCE_HASH: &[u8] = b"ZTxIdSequencHash";
 const ZCASH_OUTPUTS_HASH: &[u8] = b"ZTxIdOutputsHash";
 const ZCASH_TX_HASH: &[u8] = b"ZcashTxHash_";
 const ZCASH_TRANSPARENT_SIG: &[u8] = b"Zcash___TxInHash";
+const ZCASH_ORCHARD_HASH: &[u8] = b"ZTxIdOrchardHash";
 
 // Transaction constants
 const TX_VERSION_WITH_OVERWINTERED: u32 = 0x80000005;
@@ -56,11 +65,13 @@ pub fn init_proving_key() {
 }
 
 /// Get or build the Orchard proving key
+///
+/// Rebuilt on every start rather than cached on disk, since `ProvingKey` is not serializable.
 fn get_proving_key() -> &'static ProvingKey {
     ORCHARD_PROVING_KEY.get_or_init(|| {
-        tracing::info!("Building Orchard proving key (this may take a moment)...");
+        let started = std::time::Instant::now();
         let pk = ProvingKey::build();
-        tracing::info!("Orchard proving key built successfully");
+        tracing::info!("Orchard proving key built in {:.1}s", started.elapsed().as_secs_f64());
         pk
     })
 }
@@ -70,6 +81,9 @@ fn get_proving_key() -> &'static ProvingKey {
 #[serde(rename_all = "lowercase")]
 pub enum FundSource {
     /// Automatically select funds (prefer shielded, fallback to transparent)
+    ///
+    /// As the fund source of a proposal, both pools: shielded notes and transparent
+    /// UTXOs are spent together because neither covers the transfer alone.
     Auto,
     /// Only use shielded funds
     Shielded,
@@ -108,14 +122,22 @@ impl TransferRequest {
             return Ok(zatoshis);
         }
 
-        let zec: f64 = self.amount_zec.parse()
-            .map_err(|_| OrchardError::TransactionBuild("Invalid amount format".to_string()))?;
+        let zatoshis = parse_units_u64(&self.amount_zec, ZEC_DECIMALS)
+            .map_err(|e| OrchardError::TransactionBuild(e.to_string()))?;
 
-        if zec <= 0.0 {
+        if zatoshis == 0 {
             return Err(OrchardError::TransactionBuild("Amount must be positive".to_string()));
         }
 
-        Ok((zec * 100_000_000.0) as u64)
+        Ok(zatoshis)
+    }
+
+    /// Reject a memo that would not fit in the 512-byte memo field
+    pub fn validate_memo(&self) -> OrchardResult<()> {
+        match self.memo.as_deref() {
+            Some(memo) => validate_text_memo(memo),
+            None => Ok(()),
+        }
     }
 }
 
@@ -140,6 +162,49 @@ pub struct TransferProposal {
     pub memo: Option<String>,
     /// Expiry height for the transaction
     pub expiry_height: u64,
+    /// How transparent inputs are picked when the transfer spends UTXOs
+    #[serde(default)]
+    pub coin_selection: SelectionStrategy,
+}
+
+impl TransferProposal {
+    /// Whether this is a plain transparent-to-transparent send
+    pub fn is_transparent_only(&self) -> bool {
+        self.fund_source == FundSource::Transparent
+            && !self.is_shielding
+            && is_transparent_address(&self.to_address)
+    }
+}
+
+/// Fee and funding decision for a transfer, before any proof is built
+#[derive(Debug, Clone, Serialize)]
+pub struct TransferEstimate {
+    pub amount_zatoshis: u64,
+    pub fee_zatoshis: u64,
+    pub fund_source: FundSource,
+    pub is_shielding: bool,
+    pub is_deshielding: bool,
+    /// Balance of the pool(s) the transfer draws from
+    pub available_zatoshis: u64,
+    /// Whether the available balance covers amount and fee
+    pub sufficient: bool,
+}
+
+impl TransferEstimate {
+    /// Kind of transfer: shielding, deshielding, shielded or transparent
+    pub fn operation(&self) -> &'static str {
+        if self.is_shielding {
+            "shielding"
+        } else if self.fund_source == FundSource::Auto {
+            "combined"
+        } else if self.is_deshielding {
+            "deshielding"
+        } else if self.fund_source == FundSource::Transparent {
+            "transparent"
+        } else {
+            "shielded"
+        }
+    }
 }
 
 /// Result of executing a transfer
@@ -173,34 +238,179 @@ pub enum TransferStatus {
     Failed,
 }
 
+impl TransferResult {
+    /// Mark a signed transaction as accepted by the node
+    ///
+    /// The txid returned by `sendrawtransaction` is authoritative; a mismatch with
+    /// the locally computed id is logged so it can be investigated.
+    pub fn into_submitted(self, node_tx_id: &str) -> Self {
+        if !self.tx_id.eq_ignore_ascii_case(node_tx_id) {
+            tracing::warn!(
+                "Broadcast txid mismatch: local={}, node={}",
+                self.tx_id,
+                node_tx_id
+            );
+        }
+
+        Self {
+            tx_id: node_tx_id.to_string(),
+            status: TransferStatus::Submitted,
+            ..self
+        }
+    }
+}
+
+/// Parse a serialized v5 transaction
+///
+/// The branch ID is read from the v5 header, which commits to it, so the caller
+/// does not need to know the upgrade in force.
+fn read_transaction(raw_tx: &[u8]) -> OrchardResult<zcash_primitives::transaction::Transaction> {
+    use zcash_primitives::transaction::Transaction;
+    use zcash_protocol::consensus::BranchId;
+
+    // version (4) | version group id (4) | consensus branch id (4)
+    let consensus_branch_id = raw_tx
+        .get(8..12)
+        .map(|bytes| u32::from_le_bytes(bytes.try_into().unwrap()))
+        .ok_or_else(|| OrchardError::TransactionBuild("Transaction is too short".to_string()))?;
+    let branch_id = BranchId::try_from(consensus_branch_id).map_err(|e| {
+        OrchardError::TransactionBuild(format!(
+            "Unknown consensus branch id {:#010x}: {}",
+            consensus_branch_id, e
+        ))
+    })?;
+
+    Transaction::read(raw_tx, branch_id)
+        .map_err(|e| OrchardError::TransactionBuild(format!("Failed to parse transaction: {}", e)))
+}
+
+/// Nullifiers revealed by the Orchard actions of a serialized transaction
+///
+/// Includes the nullifiers of dummy spends, so callers should match them
+/// against their own notes.
+pub fn orchard_nullifiers(raw_tx: &[u8]) -> OrchardResult<Vec<[u8; 32]>> {
+    let tx = read_transaction(raw_tx)?;
+
+    Ok(tx
+        .orchard_bundle()
+        .map(|bundle| {
+            bundle
+                .actions()
+                .iter()
+                .map(|action| action.nullifier().to_bytes())
+                .collect()
+        })
+        .unwrap_or_default())
+}
+
+/// Last block height a serialized transaction can be mined in
+pub fn transaction_expiry_height(raw_tx: &[u8]) -> OrchardResult<u64> {
+    Ok(u32::from(read_transaction(raw_tx)?.expiry_height()) as u64)
+}
+
+/// ZIP-244 txid of a serialized transaction, as the node will report it
+pub fn transaction_id(raw_tx: &[u8]) -> OrchardResult<String> {
+    Ok(read_transaction(raw_tx)?.txid().to_string())
+}
+
+/// Pick the wallet's own notes (hex nullifiers) out of the nullifiers a transaction reveals
+pub fn spent_wallet_nullifiers(
+    revealed: &[[u8; 32]],
+    wallet_nullifiers: &std::collections::HashSet<String>,
+) -> Vec<String> {
+    revealed
+        .iter()
+        .map(hex::encode)
+        .filter(|nf| wallet_nullifiers.contains(nf))
+        .collect()
+}
+
+/// Map a `sendrawtransaction` rejection to an error that explains why the node refused it
+pub fn broadcast_rejection_error(message: &str) -> OrchardError {
+    let reason = if message.contains("missing-inputs") {
+        Some("inputs are already spent or unknown to the node")
+    } else if message.contains("bad-txns-anchor") {
+        Some("Orchard anchor is not a recent tree root, resync witnesses and retry")
+    } else if message.contains("tx-expiring-soon") || message.contains("tx-expired") {
+        Some("transaction expiry height has passed, create a new proposal")
+    } else if message.contains("already-in-mempool") || message.contains("already in block chain") {
+        Some("transaction was already submitted")
+    } else if message.contains("insufficient fee") || message.contains("min relay fee") {
+        Some("fee is below the node's relay minimum")
+    } else {
+        None
+    };
+
+    match reason {
+        Some(reason) => OrchardError::RpcError(format!("Node rejected transaction ({}): {}", reason, message)),
+        None => OrchardError::RpcError(message.to_string()),
+    }
+}
+
 /// Orchard transfer service
 pub struct OrchardTransferService {
     /// Network parameters
     network: NetworkType,
+    /// Chain tip when building, which decides the consensus branch
+    chain_height: Option<u64>,
 }
 
+/// Blocks after the current height at which an unmined transaction expires
+pub const TX_EXPIRY_DELTA: u64 = 40;
+
 /// Network type
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
 pub enum NetworkType {
+    #[default]
     Mainnet,
     Testnet,
 }
 
 impl NetworkType {
-    /// Get the consensus branch ID for the current network upgrade (NU6.1)
-    /// NU6.1 activated at height 3,146,400 on mainnet
-    pub fn consensus_branch_id(&self) -> u32 {
+    /// (activation height, branch ID) of each upgrade this wallet builds for, oldest first
+    fn upgrades(&self) -> [(u64, u32); 3] {
         match self {
-            NetworkType::Mainnet => 0x4dec4df0, // NU6.1 mainnet
-            NetworkType::Testnet => 0x4dec4df0, // NU6.1 testnet
+            NetworkType::Mainnet => [
+                (1_687_104, CONSENSUS_BRANCH_ID_NU5),
+                (2_726_400, CONSENSUS_BRANCH_ID_NU6),
+                (3_146_400, CONSENSUS_BRANCH_ID_NU6_1),
+            ],
+            NetworkType::Testnet => [
+                (1_842_420, CONSENSUS_BRANCH_ID_NU5),
+                (2_976_000, CONSENSUS_BRANCH_ID_NU6),
+                (3_536_500, CONSENSUS_BRANCH_ID_NU6_1),
+            ],
         }
     }
 
+    /// Consensus branch ID a transaction mined at `height` must commit to
+    pub fn consensus_branch_id_at(&self, height: u64) -> u32 {
+        self.upgrades()
+            .iter()
+            .rev()
+            .find(|(activation, _)| height >= *activation)
+            .map_or(CONSENSUS_BRANCH_ID_NU5, |(_, branch_id)| *branch_id)
+    }
+
     /// Get the activation height for Orchard
     pub fn orchard_activation_height(&self) -> u64 {
+        self.upgrades()[0].0
+    }
+
+    /// Get the activation height for Sapling, the oldest pool whose notes are discovered
+    pub fn sapling_activation_height(&self) -> u64 {
         match self {
-            NetworkType::Mainnet => 1687104, // NU5 activation on mainnet
-            NetworkType::Testnet => 1842420, // NU5 activation on testnet
+            NetworkType::Mainnet => 419_200,
+            NetworkType::Testnet => 280_000,
+        }
+    }
+
+    /// Network unified addresses are encoded for (u1 on mainnet, utest1 on testnet)
+    pub fn address_network(&self) -> zcash_protocol::consensus::NetworkType {
+        match self {
+            NetworkType::Mainnet => zcash_protocol::consensus::NetworkType::Main,
+            NetworkType::Testnet => zcash_protocol::consensus::NetworkType::Test,
         }
     }
 }
@@ -208,33 +418,70 @@ impl NetworkType {
 impl OrchardTransferService {
     /// Create a new transfer service
     pub fn new(network: NetworkType) -> Self {
-        Self { network }
+        Self {
+            network,
+            chain_height: None,
+        }
     }
 
-    /// Create a transfer proposal
+    /// Build for the block after `height`, the current chain tip
+    pub fn with_chain_height(mut self, height: u64) -> Self {
+        self.chain_height = Some(height);
+        self
+    }
+
+    /// Branch ID the next block will enforce
     ///
-    /// This validates the request and calculates fees without building the transaction.
-    pub fn create_proposal(
+    /// Without a known chain tip (e.g. offline signing) the tip is taken to be the
+    /// height the proposal was created at, recovered from its expiry height.
+    fn branch_id(&self, expiry_height: u64) -> u32 {
+        let tip = self
+            .chain_height
+            .unwrap_or_else(|| expiry_height.saturating_sub(TX_EXPIRY_DELTA));
+        self.network.consensus_branch_id_at(tip + 1)
+    }
+
+    /// Work out how a transfer would be funded and what it would cost
+    ///
+    /// Validates the amount and recipient but, unlike `create_proposal`, reports
+    /// insufficient funds in the estimate instead of failing.
+    pub fn estimate_transfer(
         &self,
         request: &TransferRequest,
         transparent_balance_zatoshis: u64,
         shielded_balance: Option<&ShieldedBalance>,
-        current_height: u64,
-    ) -> OrchardResult<TransferProposal> {
+    ) -> OrchardResult<TransferEstimate> {
         let amount = request.get_zatoshis()?;
 
-        // Check if target address is transparent (deshielding operation)
-        let is_deshielding = is_transparent_address(&request.to_address);
-
-        // Determine effective fund source and validate balance
-        let (fund_source, is_shielding) = if is_deshielding {
+        // Reject mistyped or wrong-network recipients before any balance or proof work
+        let recipient = validate_zcash_address(&request.to_address, self.network)?;
+
+        // A transparent recipient is paid from transparent funds when asked to, or when
+        // the shielded balance cannot cover it; otherwise the payment is deshielded
+        let shielded_available = shielded_balance.map(|b| b.spendable_zatoshis).unwrap_or(0);
+        let combined = self.combines_pools(
+            request.fund_source,
+            amount,
+            transparent_balance_zatoshis,
+            shielded_balance,
+            recipient == RecipientKind::Transparent,
+        );
+        let transparent_only = !combined
+            && recipient == RecipientKind::Transparent
+            && match request.fund_source {
+                FundSource::Transparent => true,
+                FundSource::Shielded => false,
+                FundSource::Auto => shielded_available < amount + DEFAULT_FEE_ZATOSHIS,
+            };
+        let is_deshielding = recipient == RecipientKind::Transparent && !transparent_only;
+
+        // Determine effective fund source
+        let (fund_source, is_shielding) = if combined {
+            (FundSource::Auto, false)
+        } else if transparent_only {
+            (FundSource::Transparent, false)
+        } else if is_deshielding {
             // Deshielding: must use shielded funds to send to transparent address
-            let shielded_available = shielded_balance.map(|b| b.spendable_zatoshis).unwrap_or(0);
-            if shielded_available == 0 {
-                return Err(OrchardError::TransactionBuild(
-                    "Deshielding requires shielded balance but none is available".to_string()
-                ));
-            }
             (FundSource::Shielded, false)
         } else {
             self.determine_fund_source(
@@ -242,32 +489,70 @@ impl OrchardTransferService {
                 amount,
                 transparent_balance_zatoshis,
                 shielded_balance,
-            )?
+            )
+            .unwrap_or(match request.fund_source {
+                // Not enough anywhere: report the pool the transfer would draw from
+                FundSource::Shielded => (FundSource::Shielded, false),
+                FundSource::Transparent => (FundSource::Transparent, true),
+                FundSource::Auto if shielded_available > 0 => (FundSource::Shielded, false),
+                FundSource::Auto => (FundSource::Transparent, true),
+            })
         };
 
         // Calculate fee based on action count
         // For deshielding, we have 1 transparent output which must be included in fee calculation
-        let fee = if is_deshielding {
+        let fee = if transparent_only {
+            // Payment and change, assuming one input until UTXOs are selected
+            transparent_transfer_fee(1, 2)
+        } else if combined {
+            // Every note is spent; one UTXO is assumed until they are selected
+            let notes = shielded_balance.map_or(1, |b| b.note_count.max(1) as usize);
+            combined_transfer_fee(notes, 1, is_deshielding)
+        } else if is_deshielding {
             self.calculate_fee_with_transparent_outputs(1, fund_source, 1) // 1 transparent output
         } else {
             self.calculate_fee(1, fund_source)
         };
-        let total_needed = amount + fee;
 
-        // Validate sufficient funds
         let available = match fund_source {
             FundSource::Transparent => transparent_balance_zatoshis,
-            FundSource::Shielded => shielded_balance.map(|b| b.spendable_zatoshis).unwrap_or(0),
-            FundSource::Auto => {
-                let shielded = shielded_balance.map(|b| b.spendable_zatoshis).unwrap_or(0);
-                shielded + transparent_balance_zatoshis
-            }
+            FundSource::Shielded => shielded_available,
+            FundSource::Auto => shielded_available + transparent_balance_zatoshis,
         };
 
-        if available < total_needed {
+        Ok(TransferEstimate {
+            amount_zatoshis: amount,
+            fee_zatoshis: fee,
+            fund_source,
+            is_shielding,
+            is_deshielding,
+            available_zatoshis: available,
+            sufficient: available >= amount + fee,
+        })
+    }
+
+    /// Create a transfer proposal
+    ///
+    /// This validates the request and calculates fees without building the transaction.
+    pub fn create_proposal(
+        &self,
+        request: &TransferRequest,
+        transparent_balance_zatoshis: u64,
+        shielded_balance: Option<&ShieldedBalance>,
+        current_height: u64,
+    ) -> OrchardResult<TransferProposal> {
+        request.validate_memo()?;
+        let estimate = self.estimate_transfer(request, transparent_balance_zatoshis, shielded_balance)?;
+
+        if estimate.is_deshielding && estimate.available_zatoshis == 0 {
+            return Err(OrchardError::TransactionBuild(
+                "Deshielding requires shielded balance but none is available".to_string()
+            ));
+        }
+        if !estimate.sufficient {
             return Err(OrchardError::InsufficientBalance {
-                available,
-                required: total_needed,
+                available: estimate.available_zatoshis,
+                required: estimate.amount_zatoshis + estimate.fee_zatoshis,
             });
         }
 
@@ -275,18 +560,19 @@ impl OrchardTransferService {
         let proposal_id = self.generate_proposal_id();
 
         // Calculate expiry height (default: current + 40 blocks, ~40 minutes)
-        let expiry_height = current_height + 40;
+        let expiry_height = current_height + TX_EXPIRY_DELTA;
 
         Ok(TransferProposal {
             proposal_id,
-            amount_zatoshis: amount,
-            fee_zatoshis: fee,
-            fund_source,
-            is_shielding,
-            is_deshielding,
+            amount_zatoshis: estimate.amount_zatoshis,
+            fee_zatoshis: estimate.fee_zatoshis,
+            fund_source: estimate.fund_source,
+            is_shielding: estimate.is_shielding,
+            is_deshielding: estimate.is_deshielding,
             to_address: request.to_address.clone(),
             memo: request.memo.clone(),
             expiry_height,
+            coin_selection: SelectionStrategy::default(),
         })
     }
 
@@ -335,6 +621,10 @@ impl OrchardTransferService {
             ));
         }
 
+        if proposal.is_transparent_only() {
+            return self.build_transparent_transfer(proposal, private_key_hex, &transparent_inputs);
+        }
+
         // Log transparent inputs
         let total_input: u64 = transparent_inputs.iter().map(|i| i.value).sum();
         tracing::info!(
@@ -369,6 +659,36 @@ impl OrchardTransferService {
             ));
         }
 
+        if proposal.fund_source == FundSource::Auto && (spendable_notes.is_empty() || transparent_inputs.is_empty()) {
+            return Err(OrchardError::TransactionBuild(
+                "A transfer spending both pools needs shielded notes and transparent inputs".to_string()
+            ));
+        }
+
+        // Both pools: notes and UTXOs in one transaction, to either kind of recipient.
+        // The fee depends on how many of each were selected, so it is reported as built.
+        if proposal.fund_source == FundSource::Auto {
+            let mut tx_data = Vec::new();
+            self.write_tx_header(&mut tx_data, proposal.expiry_height);
+            let fee = self.build_combined_bundle(
+                &mut tx_data,
+                proposal,
+                spending_key,
+                private_key_hex,
+                spendable_notes,
+                transparent_inputs,
+                anchor,
+            )?;
+
+            return Ok(TransferResult {
+                tx_id: self.compute_tx_id(&tx_data),
+                status: TransferStatus::Signed,
+                raw_tx: Some(hex::encode(&tx_data)),
+                amount_zatoshis: proposal.amount_zatoshis,
+                fee_zatoshis: fee,
+            });
+        }
+
         // Build the transaction
         let tx_data = self.build_orchard_transaction(
             proposal,
@@ -392,6 +712,169 @@ impl OrchardTransferService {
         })
     }
 
+    /// Build and sign a consolidation: spend up to `max_notes` of the smallest notes
+    /// into a single note at the wallet's own internal address
+    ///
+    /// The returned result's amount is the value of the consolidated note.
+    pub fn build_consolidation(
+        &self,
+        spending_key: &OrchardSpendingKey,
+        notes_with_paths: Vec<(OrchardNote, MerklePath)>,
+        max_notes: usize,
+        expiry_height: u64,
+        anchor: Anchor,
+    ) -> OrchardResult<TransferResult> {
+        use orchard::keys::{Diversifier, Scope};
+
+        let (selected, total_input, fee) = select_consolidation_notes(notes_with_paths, max_notes)?;
+        let consolidated_value = total_input - fee;
+
+        tracing::info!(
+            "Building consolidation: {} notes, total={} zatoshis, fee={} zatoshis, output={} zatoshis",
+            selected.len(),
+            total_input,
+            fee,
+            consolidated_value
+        );
+
+        let fvk = spending_key.to_fvk();
+        let mut builder = OrchardBuilder::new(BundleType::DEFAULT, anchor);
+
+        for (idx, (note, merkle_path)) in selected.iter().enumerate() {
+            let orchard_note = self.rebuild_spend_note(idx, note)?;
+            builder
+                .add_spend(fvk.clone(), orchard_note, merkle_path.clone())
+                .map_err(|e| OrchardError::TransactionBuild(format!("Failed to add spend: {:?}", e)))?;
+        }
+
+        // Same internal address the change of regular transfers goes to
+        let internal_address = fvk.address(Diversifier::from_bytes([0u8; 11]), Scope::Internal);
+        builder
+            .add_output(
+                Some(spending_key.to_ovk()),
+                internal_address,
+                NoteValue::from_raw(consolidated_value),
+                [0u8; 512],
+            )
+            .map_err(|e| OrchardError::TransactionBuild(format!("Failed to add consolidated output: {:?}", e)))?;
+
+        let mut tx_data = Vec::new();
+        self.write_tx_header(&mut tx_data, expiry_height);
+        self.finish_shielded_bundle(builder, spending_key, selected.len(), expiry_height, &mut tx_data)?;
+
+        Ok(TransferResult {
+            tx_id: self.compute_tx_id(&tx_data),
+            status: TransferStatus::Signed,
+            raw_tx: Some(hex::encode(&tx_data)),
+            amount_zatoshis: consolidated_value,
+            fee_zatoshis: fee,
+        })
+    }
+
+    /// Build and sign a transparent-to-transparent transfer
+    ///
+    /// The transaction has only a transparent bundle: the selected UTXOs as inputs, the
+    /// payment and a change output back to the script of the first input (the sender).
+    /// Change below the dust threshold is added to the fee instead.
+    pub fn build_transparent_transfer(
+        &self,
+        proposal: &TransferProposal,
+        private_key_hex: &str,
+        inputs: &[TransparentInput],
+    ) -> OrchardResult<TransferResult> {
+        let first_input = inputs.first().ok_or_else(|| {
+            OrchardError::TransactionBuild("No transparent inputs provided".to_string())
+        })?;
+
+        let total_input: u64 = inputs.iter().map(|i| i.value).sum();
+        let mut fee = std::cmp::max(proposal.fee_zatoshis, transparent_transfer_fee(inputs.len(), 2));
+        let required = proposal.amount_zatoshis + fee;
+        if total_input < required {
+            return Err(OrchardError::InsufficientBalance {
+                available: total_input,
+                required,
+            });
+        }
+
+        let mut change = total_input - required;
+        if change < TRANSPARENT_DUST_ZATOSHIS {
+            fee += change;
+            change = 0;
+        }
+
+        let mut outputs = self.build_transparent_output(&proposal.to_address, proposal.amount_zatoshis)?;
+        let mut output_count = 1;
+        if change > 0 {
+            outputs.extend_from_slice(&serialize_transparent_output(change, &first_input.script_pubkey));
+            output_count += 1;
+        }
+
+        tracing::info!(
+            "Building transparent transfer: {} inputs totaling {} zatoshis, amount={}, change={}, fee={}",
+            inputs.len(),
+            total_input,
+            proposal.amount_zatoshis,
+            change,
+            fee
+        );
+
+        let expiry_height = proposal.expiry_height as u32;
+        let branch_id = self.branch_id(proposal.expiry_height);
+        let orchard_digest = blake2b_256(ZCASH_ORCHARD_HASH, &[]);
+        let signed_inputs = sign_transparent_inputs(inputs, private_key_hex, |index| {
+            Ok(calculate_transparent_sighash(
+                inputs,
+                index,
+                &outputs,
+                expiry_height,
+                branch_id,
+                orchard_digest,
+            ))
+        })?;
+
+        let mut tx_data = Vec::new();
+        self.write_tx_header(&mut tx_data, proposal.expiry_height);
+        write_signed_inputs(&mut tx_data, &signed_inputs);
+        tx_data.extend_from_slice(&serialize_compact_size(output_count));
+        tx_data.extend_from_slice(&outputs);
+        // No Sapling spends, Sapling outputs or Orchard actions
+        tx_data.extend_from_slice(&[0x00, 0x00, 0x00]);
+
+        Ok(TransferResult {
+            tx_id: self.compute_tx_id(&tx_data),
+            status: TransferStatus::Signed,
+            raw_tx: Some(hex::encode(&tx_data)),
+            amount_zatoshis: proposal.amount_zatoshis,
+            fee_zatoshis: fee,
+        })
+    }
+
+    /// Whether an `Auto` transfer must spend shielded notes and transparent UTXOs together
+    ///
+    /// Only when neither pool covers amount and fee on its own, but both together
+    /// cover it including the larger fee of the combined transaction.
+    fn combines_pools(
+        &self,
+        requested: FundSource,
+        amount: u64,
+        transparent_balance: u64,
+        shielded_balance: Option<&ShieldedBalance>,
+        to_transparent: bool,
+    ) -> bool {
+        let Some(shielded) = shielded_balance.filter(|b| b.spendable_zatoshis > 0) else {
+            return false;
+        };
+        if requested != FundSource::Auto || transparent_balance == 0 {
+            return false;
+        }
+
+        let single_pool_needed = amount + DEFAULT_FEE_ZATOSHIS;
+        let fee = combined_transfer_fee(shielded.note_count.max(1) as usize, 1, to_transparent);
+        shielded.spendable_zatoshis < single_pool_needed
+            && transparent_balance < single_pool_needed
+            && shielded.spendable_zatoshis + transparent_balance >= amount + fee
+    }
+
     /// Determine the effective fund source based on availability
     fn determine_fund_source(
         &self,
@@ -456,40 +939,23 @@ impl OrchardTransferService {
 
     /// Calculate transaction fee with explicit transparent output count (for deshielding)
     fn calculate_fee_with_transparent_outputs(&self, num_outputs: u32, fund_source: FundSource, transparent_outputs: u32) -> u64 {
-        // ZIP-317 constants
-        const MARGINAL_FEE: u64 = 5000;
-        const GRACE_ACTIONS: u32 = 2;
-
-        // Calculate logical actions based on fund source
-        let (transparent_inputs, orchard_actions) = match fund_source {
-            FundSource::Shielded => {
-                // Pure shielded: no transparent inputs
-                // Orchard actions = max(spends, outputs), minimum 2 due to padding
-                // With payment + change = 2 outputs, need 2 actions
-                (0u32, std::cmp::max(2, num_outputs + 1)) // +1 for potential change
-            }
-            FundSource::Transparent => {
-                // Shielding: transparent inputs + Orchard outputs
-                // Assume 1 transparent input (will be adjusted if more UTXOs needed)
-                // Orchard outputs = payment + change = 2, so 2 actions
-                (1u32, std::cmp::max(2, num_outputs + 1)) // +1 for change
-            }
-            FundSource::Auto => {
-                // Could be either, assume worst case (shielding with change)
-                (1u32, std::cmp::max(2, num_outputs + 1))
-            }
+        // Orchard outputs are the payment plus change. A shielded source is assumed to
+        // spend one note and a transparent one to use one UTXO; the builders recompute
+        // once the real inputs are selected.
+        let orchard_outputs = num_outputs as usize + 1;
+        let (transparent_inputs, orchard_spends) = match fund_source {
+            FundSource::Shielded => (0, 1),
+            // Auto could be either, assume the shielding case
+            FundSource::Transparent | FundSource::Auto => (1, 0),
         };
-
-        // logical_actions includes transparent inputs, transparent outputs, and orchard actions
-        let logical_actions = transparent_inputs + transparent_outputs + orchard_actions;
-        let fee = MARGINAL_FEE * std::cmp::max(GRACE_ACTIONS, logical_actions) as u64;
+        let orchard_actions = orchard_action_count(orchard_spends, orchard_outputs);
+        let fee = zip317_fee(transparent_inputs, transparent_outputs as usize, orchard_actions);
 
         tracing::debug!(
-            "ZIP-317 fee calculation: transparent_inputs={}, transparent_outputs={}, orchard_actions={}, logical_actions={}, fee={}",
+            "ZIP-317 fee calculation: transparent_inputs={}, transparent_outputs={}, orchard_actions={}, fee={}",
             transparent_inputs,
             transparent_outputs,
             orchard_actions,
-            logical_actions,
             fee
         );
 
@@ -517,24 +983,7 @@ impl OrchardTransferService {
     ) -> OrchardResult<Vec<u8>> {
         // Create transaction builder
         let mut tx_data = Vec::new();
-
-        // Transaction header (v5 format for Orchard support)
-        // Version: 5 with overwinter flag (0x80000005 in little-endian)
-        const TX_VERSION_V5_OVERWINTERED: u32 = 0x80000005;
-        tx_data.extend_from_slice(&TX_VERSION_V5_OVERWINTERED.to_le_bytes());
-
-        // Version group ID for v5 (0x26A7270A in little-endian)
-        const VERSION_GROUP_ID_V5: u32 = 0x26A7270A;
-        tx_data.extend_from_slice(&VERSION_GROUP_ID_V5.to_le_bytes());
-
-        // Consensus branch ID
-        tx_data.extend_from_slice(&self.network.consensus_branch_id().to_le_bytes());
-
-        // Lock time (0 = no lock)
-        tx_data.extend_from_slice(&[0x00, 0x00, 0x00, 0x00]);
-
-        // Expiry height
-        tx_data.extend_from_slice(&(proposal.expiry_height as u32).to_le_bytes());
+        self.write_tx_header(&mut tx_data, proposal.expiry_height);
 
         // Check if this is a deshielding operation (Z → T)
         let is_deshielding = is_transparent_address(&proposal.to_address);
@@ -577,38 +1026,33 @@ impl OrchardTransferService {
                         anchor,
                     )?;
                 }
-                FundSource::Auto => {
-                    // Prefer shielded spending for better privacy
-                    if !spendable_notes.is_empty() {
-                        self.build_shielded_bundle(
-                            &mut tx_data,
-                            proposal,
-                            spending_key,
-                            spendable_notes,
-                            anchor,
-                        )?;
-                    } else if !transparent_inputs.is_empty() {
-                        // Fall back to transparent (shielding operation)
-                        self.build_shielding_bundle(
-                            &mut tx_data,
-                            proposal,
-                            spending_key,
-                            private_key_hex,
-                            transparent_inputs,
-                            anchor,
-                        )?;
-                    } else {
-                        return Err(OrchardError::TransactionBuild(
-                            "No funds available: no transparent UTXOs or shielded notes found".to_string()
-                        ));
-                    }
-                }
+                FundSource::Auto => unreachable!("combined transfers are built by build_transaction"),
             }
         }
 
         Ok(tx_data)
     }
 
+    /// Write the v5 transaction header
+    fn write_tx_header(&self, tx_data: &mut Vec<u8>, expiry_height: u64) {
+        // Version: 5 with overwinter flag (0x80000005 in little-endian)
+        const TX_VERSION_V5_OVERWINTERED: u32 = 0x80000005;
+        tx_data.extend_from_slice(&TX_VERSION_V5_OVERWINTERED.to_le_bytes());
+
+        // Version group ID for v5 (0x26A7270A in little-endian)
+        const VERSION_GROUP_ID_V5: u32 = 0x26A7270A;
+        tx_data.extend_from_slice(&VERSION_GROUP_ID_V5.to_le_bytes());
+
+        // Consensus branch ID
+        tx_data.extend_from_slice(&self.branch_id(expiry_height).to_le_bytes());
+
+        // Lock time (0 = no lock)
+        tx_data.extend_from_slice(&[0x00, 0x00, 0x00, 0x00]);
+
+        // Expiry height
+        tx_data.extend_from_slice(&(expiry_height as u32).to_le_bytes());
+    }
+
     /// Build shielded-to-shielded bundle (spending from shielded pool)
     ///
     /// This creates a transaction that spends from shielded notes and sends to shielded addresses.
@@ -646,9 +1090,6 @@ impl OrchardTransferService {
         // Calculate change
         let change_amount = total_input - total_needed;
 
-        // Get the proving key
-        let pk = get_proving_key();
-
         // Get FVK from spending key
         let fvk = spending_key.to_fvk();
 
@@ -658,57 +1099,7 @@ impl OrchardTransferService {
 
         // Add spends (reconstruct Note from stored data, use MerklePath directly)
         for (idx, (note, merkle_path)) in selected_notes_with_paths.iter().enumerate() {
-            // Reconstruct the orchard::Address from stored bytes
-            let recipient_addr = orchard::Address::from_raw_address_bytes(&note.recipient);
-            if recipient_addr.is_none().into() {
-                tracing::error!("Failed to reconstruct address for note {}", idx);
-                return Err(OrchardError::TransactionBuild(
-                    format!("Invalid recipient address data for note {}", idx)
-                ));
-            }
-            let recipient_addr = recipient_addr.unwrap();
-
-            // Reconstruct Rho from stored bytes
-            let rho = orchard::note::Rho::from_bytes(&note.rho);
-            if rho.is_none().into() {
-                tracing::error!("Failed to reconstruct rho for note {}", idx);
-                return Err(OrchardError::TransactionBuild(
-                    format!("Invalid rho data for note {}", idx)
-                ));
-            }
-            let rho = rho.unwrap();
-
-            // Reconstruct RandomSeed from stored bytes
-            let rseed = orchard::note::RandomSeed::from_bytes(note.rseed, &rho);
-            if rseed.is_none().into() {
-                tracing::error!("Failed to reconstruct rseed for note {}", idx);
-                return Err(OrchardError::TransactionBuild(
-                    format!("Invalid rseed data for note {}", idx)
-                ));
-            }
-            let rseed = rseed.unwrap();
-
-            // Reconstruct the Note
-            let value = NoteValue::from_raw(note.value_zatoshis);
-            let orchard_note = orchard::Note::from_parts(recipient_addr, value, rho, rseed);
-            if orchard_note.is_none().into() {
-                tracing::error!("Failed to reconstruct note {}", idx);
-                return Err(OrchardError::TransactionBuild(
-                    format!("Failed to reconstruct Orchard note {}", idx)
-                ));
-            }
-            let orchard_note = orchard_note.unwrap();
-
-            // Verify that the reconstructed note's commitment matches the stored one
-            let extracted_cmx = orchard::note::ExtractedNoteCommitment::from(orchard_note.commitment());
-            let reconstructed_cmx = extracted_cmx.to_bytes();
-
-            tracing::info!(
-                "Note {} commitment: {}, position={}",
-                idx,
-                hex::encode(&reconstructed_cmx[..8]),
-                note.position
-            );
+            let orchard_note = self.rebuild_spend_note(idx, note)?;
 
             // Add the spend using the MerklePath directly (from proper conversion)
             match builder.add_spend(fvk.clone(), orchard_note, merkle_path.clone()) {
@@ -732,14 +1123,7 @@ impl OrchardTransferService {
         let recipient_address = OrchardAddressManager::extract_orchard_address(&proposal.to_address)?;
         let ovk = Some(spending_key.to_ovk());
         let payment_value = NoteValue::from_raw(proposal.amount_zatoshis);
-        let memo_bytes: [u8; 512] = {
-            let mut bytes = [0u8; 512];
-            if let Some(m) = proposal.memo.as_ref() {
-                let len = std::cmp::min(m.as_bytes().len(), 512);
-                bytes[..len].copy_from_slice(&m.as_bytes()[..len]);
-            }
-            bytes
-        };
+        let memo_bytes = encode_text_memo(proposal.memo.as_deref())?;
 
         builder.add_output(ovk.clone(), recipient_address, payment_value, memo_bytes)
             .map_err(|e| OrchardError::TransactionBuild(format!("Failed to add payment output: {:?}", e)))?;
@@ -762,6 +1146,91 @@ impl OrchardTransferService {
             tracing::info!("Added change output: {} zatoshis", change_amount);
         }
 
+        self.finish_shielded_bundle(
+            builder,
+            spending_key,
+            selected_notes_with_paths.len(),
+            proposal.expiry_height,
+            tx_data,
+        )?;
+
+        tracing::info!(
+            "Built shielded transaction: {} spends, {} outputs, {} bytes",
+            selected_notes_with_paths.len(),
+            if change_amount > 0 { 2 } else { 1 },
+            tx_data.len()
+        );
+
+        Ok(())
+    }
+
+    /// Reconstruct a stored note for spending
+    fn rebuild_spend_note(&self, idx: usize, note: &OrchardNote) -> OrchardResult<orchard::Note> {
+        // Reconstruct the orchard::Address from stored bytes
+        let recipient_addr = orchard::Address::from_raw_address_bytes(&note.recipient);
+        if recipient_addr.is_none().into() {
+            tracing::error!("Failed to reconstruct address for note {}", idx);
+            return Err(OrchardError::TransactionBuild(
+                format!("Invalid recipient address data for note {}", idx)
+            ));
+        }
+        let recipient_addr = recipient_addr.unwrap();
+
+        // Reconstruct Rho from stored bytes
+        let rho = orchard::note::Rho::from_bytes(&note.rho);
+        if rho.is_none().into() {
+            tracing::error!("Failed to reconstruct rho for note {}", idx);
+            return Err(OrchardError::TransactionBuild(
+                format!("Invalid rho data for note {}", idx)
+            ));
+        }
+        let rho = rho.unwrap();
+
+        // Reconstruct RandomSeed from stored bytes
+        let rseed = orchard::note::RandomSeed::from_bytes(note.rseed, &rho);
+        if rseed.is_none().into() {
+            tracing::error!("Failed to reconstruct rseed for note {}", idx);
+            return Err(OrchardError::TransactionBuild(
+                format!("Invalid rseed data for note {}", idx)
+            ));
+        }
+        let rseed = rseed.unwrap();
+
+        // Reconstruct the Note
+        let value = NoteValue::from_raw(note.value_zatoshis);
+        let orchard_note = orchard::Note::from_parts(recipient_addr, value, rho, rseed);
+        if orchard_note.is_none().into() {
+            tracing::error!("Failed to reconstruct note {}", idx);
+            return Err(OrchardError::TransactionBuild(
+                format!("Failed to reconstruct Orchard note {}", idx)
+            ));
+        }
+        let orchard_note = orchard_note.unwrap();
+
+        // Verify that the reconstructed note's commitment matches the stored one
+        let extracted_cmx = orchard::note::ExtractedNoteCommitment::from(orchard_note.commitment());
+        let reconstructed_cmx = extracted_cmx.to_bytes();
+
+        tracing::info!(
+            "Note {} commitment: {}, position={}",
+            idx,
+            hex::encode(&reconstructed_cmx[..8]),
+            note.position
+        );
+
+        Ok(orchard_note)
+    }
+
+    /// Build, prove and sign a purely shielded bundle, then append it to `tx_data`
+    /// (no transparent or Sapling parts)
+    fn finish_shielded_bundle(
+        &self,
+        builder: OrchardBuilder,
+        spending_key: &OrchardSpendingKey,
+        num_spends: usize,
+        expiry_height: u64,
+        tx_data: &mut Vec<u8>,
+    ) -> OrchardResult<()> {
         // Build the bundle
         tracing::info!("Building Orchard bundle...");
         let (unauthorized_bundle, _meta) = builder
@@ -778,23 +1247,25 @@ impl OrchardTransferService {
         tracing::info!("Creating Orchard proof (this may take a few seconds)...");
         let proof_start = std::time::Instant::now();
         let proven_bundle = unauthorized_bundle
-            .create_proof(pk, &mut OsRng)
+            .create_proof(get_proving_key(), &mut OsRng)
             .map_err(|e| OrchardError::TransactionBuild(format!("Failed to create proof: {:?}", e)))?;
-        tracing::info!("Proof created in {:.2}s", proof_start.elapsed().as_secs_f64());
+        let proof_secs = proof_start.elapsed().as_secs_f64();
+        METRICS.observe_proof_seconds(proof_secs);
+        tracing::info!("Proof created in {:.2}s", proof_secs);
 
         // Compute proper sighash for signatures (ZIP 244)
         // For shielded-to-shielded, there are no transparent inputs
         let sighash = self.compute_shielded_sighash(
             &[], // no transparent inputs
-            proposal.expiry_height as u32,
-            self.network.consensus_branch_id(),
+            &[],
+            expiry_height as u32,
+            self.branch_id(expiry_height),
             &proven_bundle,
         )?;
         tracing::info!("Computed shielded sighash: {}", hex::encode(&sighash));
 
         // Apply signatures (spend auth + binding)
-        let saks: Vec<orchard::keys::SpendAuthorizingKey> = selected_notes_with_paths
-            .iter()
+        let saks: Vec<orchard::keys::SpendAuthorizingKey> = (0..num_spends)
             .map(|_| orchard::keys::SpendAuthorizingKey::from(spending_key.sk()))
             .collect();
 
@@ -812,16 +1283,7 @@ impl OrchardTransferService {
         tx_data.push(0x00); // nOutputsSapling
 
         // Serialize Orchard bundle
-        self.serialize_orchard_bundle(&authorized_bundle, tx_data)?;
-
-        tracing::info!(
-            "Built shielded transaction: {} spends, {} outputs, {} bytes",
-            selected_notes_with_paths.len(),
-            if change_amount > 0 { 2 } else { 1 },
-            tx_data.len()
-        );
-
-        Ok(())
+        self.serialize_orchard_bundle(&authorized_bundle, tx_data)
     }
 
     /// Build deshielding bundle (shielded to transparent transfer, Z → T)
@@ -983,14 +1445,16 @@ impl OrchardTransferService {
         let proven_bundle = unauthorized_bundle
             .create_proof(pk, &mut OsRng)
             .map_err(|e| OrchardError::TransactionBuild(format!("Failed to create proof: {:?}", e)))?;
-        tracing::info!("Proof created in {:.2}s", proof_start.elapsed().as_secs_f64());
+        let proof_secs = proof_start.elapsed().as_secs_f64();
+        METRICS.observe_proof_seconds(proof_secs);
+        tracing::info!("Proof created in {:.2}s", proof_secs);
 
         // Compute sighash AFTER creating proof (from proven bundle, like Z→Z)
         // For deshielding, we need to include transparent outputs in the sighash
         let sighash = self.compute_deshielding_sighash(
             &transparent_output,
             proposal.expiry_height as u32,
-            self.network.consensus_branch_id(),
+            self.branch_id(proposal.expiry_height),
             &proven_bundle,
         )?;
         tracing::info!("Computed deshielding sighash: {}", hex::encode(&sighash));
@@ -1285,104 +1749,245 @@ impl OrchardTransferService {
         let total_transparent_input: u64 = transparent_inputs.iter().map(|i| i.value).sum();
         let num_inputs = transparent_inputs.len() as u64;
 
-        // Recalculate fee based on actual input count (ZIP-317)
-        // fee = 5000 * max(2, transparent_inputs + orchard_actions)
-        // orchard_actions = 2 (payment + change, padded to even number)
-        let orchard_actions: u64 = 2;
-        let logical_actions = num_inputs + orchard_actions;
-        let actual_fee = 5000 * std::cmp::max(2, logical_actions);
+        // Same fee the UTXO selection budgeted for, so the inputs always cover it
+        let effective_fee = effective_shielding_fee(proposal.fee_zatoshis, transparent_inputs.len());
 
         tracing::info!(
-            "build_shielding_bundle: {} inputs totaling {} zatoshis, amount={}, actual_fee={} (proposal_fee={})",
+            "build_shielding_bundle: {} inputs totaling {} zatoshis, amount={}, effective_fee={} (proposal_fee={})",
             num_inputs,
             total_transparent_input,
             proposal.amount_zatoshis,
-            actual_fee,
+            effective_fee,
             proposal.fee_zatoshis
         );
 
-        // Use the higher of proposal fee or actual required fee
-        let effective_fee = std::cmp::max(proposal.fee_zatoshis, actual_fee);
+        // Step 1: Build the Orchard proven bundle (without binding signature yet)
+        let proven_bundle = self.create_orchard_proven_bundle_with_fee(
+            proposal,
+            spending_key,
+            total_transparent_input,
+            effective_fee,
+        )?;
+
+        // Step 2: Compute the shielded sighash for binding signature
+        // For shielding tx, we need: header_digest, transparent_txid_digest, sapling_digest, orchard_digest
+        let shielded_sighash = self.compute_shielded_sighash(
+            &transparent_inputs,
+            &[], // no transparent outputs
+            proposal.expiry_height as u32,
+            self.branch_id(proposal.expiry_height),
+            &proven_bundle,
+        )?;
+
+        tracing::debug!("Shielded sighash for binding sig: {}", hex::encode(&shielded_sighash));
+
+        // Step 3: Apply signatures with correct sighash
+        let saks: &[SpendAuthorizingKey] = &[]; // No spend auth keys for output-only
+        let orchard_bundle = proven_bundle
+            .apply_signatures(OsRng, shielded_sighash, saks)
+            .map_err(|e| OrchardError::TransactionBuild(format!("Failed to apply signatures: {:?}", e)))?;
+
+        // Step 4: Sign the transparent inputs using the authorized bundle
+        let signed_inputs = sign_transparent_inputs_with_bundle(
+            &transparent_inputs,
+            private_key_hex,
+            proposal.expiry_height as u32,
+            self.branch_id(proposal.expiry_height),
+            &orchard_bundle,
+        )?;
+
+        // Step 5: Write signed transparent inputs
+        write_signed_inputs(tx_data, &signed_inputs);
+
+        // No transparent outputs (all going to shielded)
+        tx_data.push(0x00); // vout count
+
+        // No Sapling spends
+        tx_data.push(0x00);
+
+        // No Sapling outputs
+        tx_data.push(0x00);
+
+        // Serialize and write the Orchard bundle
+        let bundle_start = tx_data.len();
+        self.serialize_orchard_bundle(&orchard_bundle, tx_data)?;
+        let bundle_len = tx_data.len() - bundle_start;
+
+        tracing::info!(
+            "Built shielding transaction: {} transparent inputs, {} bytes Orchard bundle",
+            signed_inputs.len(),
+            bundle_len
+        );
+
+        Ok(())
+    }
+
+    /// Build a transaction spending shielded notes and transparent UTXOs together
+    ///
+    /// Every note passed in is spent, along with every UTXO (the caller selects only
+    /// the ones needed on top of the notes). The payment is an Orchard output, or a
+    /// transparent output for a transparent recipient; change always goes back to the
+    /// sender's internal Orchard address.
+    fn build_combined_bundle(
+        &self,
+        tx_data: &mut Vec<u8>,
+        proposal: &TransferProposal,
+        spending_key: &OrchardSpendingKey,
+        private_key_hex: &str,
+        notes_with_paths: Vec<(OrchardNote, MerklePath)>,
+        transparent_inputs: Vec<TransparentInput>,
+        anchor: Anchor,
+    ) -> OrchardResult<u64> {
+        use super::address::OrchardAddressManager;
+        use orchard::keys::{Diversifier, Scope};
+
+        let to_transparent = is_transparent_address(&proposal.to_address);
+        let shielded_input: u64 = notes_with_paths.iter().map(|(note, _)| note.value_zatoshis).sum();
+        let transparent_input: u64 = transparent_inputs.iter().map(|i| i.value).sum();
+        let fee = std::cmp::max(
+            proposal.fee_zatoshis,
+            combined_transfer_fee(notes_with_paths.len(), transparent_inputs.len(), to_transparent),
+        );
+
+        let required = proposal.amount_zatoshis + fee;
+        let total_input = shielded_input + transparent_input;
+        if total_input < required {
+            return Err(OrchardError::InsufficientBalance {
+                available: total_input,
+                required,
+            });
+        }
+        let change_amount = total_input - required;
+
+        tracing::info!(
+            "Building combined transaction: {} notes ({} zatoshis) + {} UTXOs ({} zatoshis), amount={}, fee={}, change={}",
+            notes_with_paths.len(),
+            shielded_input,
+            transparent_inputs.len(),
+            transparent_input,
+            proposal.amount_zatoshis,
+            fee,
+            change_amount
+        );
+
+        let fvk = spending_key.to_fvk();
+        let ovk = Some(spending_key.to_ovk());
+        let mut builder = OrchardBuilder::new(BundleType::DEFAULT, anchor);
+
+        for (idx, (note, merkle_path)) in notes_with_paths.iter().enumerate() {
+            let orchard_note = self.rebuild_spend_note(idx, note)?;
+            builder
+                .add_spend(fvk.clone(), orchard_note, merkle_path.clone())
+                .map_err(|e| OrchardError::TransactionBuild(format!("Failed to add spend: {:?}", e)))?;
+        }
+
+        let mut shielded_output = change_amount;
+        let transparent_outputs = if to_transparent {
+            self.build_transparent_output(&proposal.to_address, proposal.amount_zatoshis)?
+        } else {
+            let recipient_address = OrchardAddressManager::extract_orchard_address(&proposal.to_address)?;
+            let memo_bytes = encode_text_memo(proposal.memo.as_deref())?;
+            builder
+                .add_output(
+                    ovk.clone(),
+                    recipient_address,
+                    NoteValue::from_raw(proposal.amount_zatoshis),
+                    memo_bytes,
+                )
+                .map_err(|e| OrchardError::TransactionBuild(format!("Failed to add payment output: {:?}", e)))?;
+            shielded_output += proposal.amount_zatoshis;
+            Vec::new()
+        };
+
+        if change_amount > 0 {
+            let change_address = fvk.address(Diversifier::from_bytes([0u8; 11]), Scope::Internal);
+            builder
+                .add_output(ovk, change_address, NoteValue::from_raw(change_amount), [0u8; 512])
+                .map_err(|e| OrchardError::TransactionBuild(format!("Failed to add change output: {:?}", e)))?;
+        }
+
+        let (unauthorized_bundle, _meta) = builder
+            .build::<i64>(&mut OsRng)
+            .map_err(|e| OrchardError::TransactionBuild(format!("Failed to build bundle: {:?}", e)))?
+            .ok_or_else(|| OrchardError::TransactionBuild("Empty bundle".to_string()))?;
 
-        // Step 1: Build the Orchard proven bundle (without binding signature yet)
-        let proven_bundle = self.create_orchard_proven_bundle_with_fee(
-            proposal,
-            spending_key,
-            total_transparent_input,
-            effective_fee,
-        )?;
+        // Value leaving the Orchard pool: spent notes minus the Orchard outputs.
+        // Negative when the UTXOs put more into the pool than the notes take out.
+        let expected_value_balance = shielded_input as i64 - shielded_output as i64;
+        let value_balance = *unauthorized_bundle.value_balance();
+        if value_balance != expected_value_balance {
+            return Err(OrchardError::TransactionBuild(format!(
+                "Transaction balance mismatch: Orchard value balance {} != expected {}",
+                value_balance, expected_value_balance
+            )));
+        }
 
-        // Step 2: Compute the shielded sighash for binding signature
-        // For shielding tx, we need: header_digest, transparent_txid_digest, sapling_digest, orchard_digest
-        let shielded_sighash = self.compute_shielded_sighash(
+        let proof_start = std::time::Instant::now();
+        let proven_bundle = unauthorized_bundle
+            .create_proof(get_proving_key(), &mut OsRng)
+            .map_err(|e| OrchardError::TransactionBuild(format!("Failed to create proof: {:?}", e)))?;
+        METRICS.observe_proof_seconds(proof_start.elapsed().as_secs_f64());
+
+        let expiry_height = proposal.expiry_height as u32;
+        let branch_id = self.branch_id(proposal.expiry_height);
+        let sighash = self.compute_shielded_sighash(
             &transparent_inputs,
-            proposal.expiry_height as u32,
-            self.network.consensus_branch_id(),
+            &transparent_outputs,
+            expiry_height,
+            branch_id,
             &proven_bundle,
         )?;
 
-        tracing::debug!("Shielded sighash for binding sig: {}", hex::encode(&shielded_sighash));
-
-        // Step 3: Apply signatures with correct sighash
-        let saks: &[SpendAuthorizingKey] = &[]; // No spend auth keys for output-only
+        let saks: Vec<SpendAuthorizingKey> = notes_with_paths
+            .iter()
+            .map(|_| SpendAuthorizingKey::from(spending_key.sk()))
+            .collect();
         let orchard_bundle = proven_bundle
-            .apply_signatures(OsRng, shielded_sighash, saks)
+            .apply_signatures(OsRng, sighash, &saks)
             .map_err(|e| OrchardError::TransactionBuild(format!("Failed to apply signatures: {:?}", e)))?;
 
-        // Step 4: Sign the transparent inputs using the authorized bundle
-        let signed_inputs = sign_transparent_inputs_with_bundle(
-            &transparent_inputs,
-            private_key_hex,
-            proposal.expiry_height as u32,
-            self.network.consensus_branch_id(),
-            &orchard_bundle,
-        )?;
-
-        // Step 5: Write signed transparent inputs
-        tx_data.extend_from_slice(&serialize_compact_size(signed_inputs.len() as u64));
-
-        for input in &signed_inputs {
-            // Previous output hash (32 bytes, little-endian)
-            let mut txid_le = input.prev_tx_hash;
-            txid_le.reverse();
-            tx_data.extend_from_slice(&txid_le);
-            // Previous output index (4 bytes)
-            tx_data.extend_from_slice(&input.prev_tx_index.to_le_bytes());
-            // Script sig length + script sig
-            tx_data.extend_from_slice(&serialize_compact_size(input.script_sig.len() as u64));
-            tx_data.extend_from_slice(&input.script_sig);
-            // Sequence (4 bytes)
-            tx_data.extend_from_slice(&input.sequence.to_le_bytes());
+        let orchard_digest = compute_orchard_digest(&orchard_bundle);
+        let signed_inputs = sign_transparent_inputs(&transparent_inputs, private_key_hex, |index| {
+            Ok(calculate_transparent_sighash(
+                &transparent_inputs,
+                index,
+                &transparent_outputs,
+                expiry_height,
+                branch_id,
+                orchard_digest,
+            ))
+        })?;
+
+        write_signed_inputs(tx_data, &signed_inputs);
+        if to_transparent {
+            tx_data.extend_from_slice(&serialize_compact_size(1));
+            tx_data.extend_from_slice(&transparent_outputs);
+        } else {
+            tx_data.push(0x00); // vout count
         }
-
-        // No transparent outputs (all going to shielded)
-        tx_data.push(0x00); // vout count
-
-        // No Sapling spends
-        tx_data.push(0x00);
-
-        // No Sapling outputs
-        tx_data.push(0x00);
-
-        // Serialize and write the Orchard bundle
-        let bundle_start = tx_data.len();
+        tx_data.push(0x00); // nSpendsSapling
+        tx_data.push(0x00); // nOutputsSapling
         self.serialize_orchard_bundle(&orchard_bundle, tx_data)?;
-        let bundle_len = tx_data.len() - bundle_start;
 
         tracing::info!(
-            "Built shielding transaction: {} transparent inputs, {} bytes Orchard bundle",
+            "Built combined transaction: {} spends, {} transparent inputs, {} bytes",
+            notes_with_paths.len(),
             signed_inputs.len(),
-            bundle_len
+            tx_data.len()
         );
 
-        Ok(())
+        Ok(fee)
     }
 
     /// Compute shielded sighash for binding signature (SignableInput::Shielded equivalent)
     /// This follows ZIP 244 for v5 transactions with SignableInput::Shielded
+    ///
+    /// `transparent_outputs` is the serialized outputs (without the count). Without
+    /// transparent inputs it must be empty; deshielding uses `compute_deshielding_sighash`.
     fn compute_shielded_sighash<V: Copy + Into<i64>>(
         &self,
         transparent_inputs: &[TransparentInput],
+        transparent_outputs: &[u8],
         expiry_height: u32,
         consensus_branch_id: u32,
         bundle: &orchard::bundle::Bundle<InProgress<Proof, Unauthorized>, V>,
@@ -1413,8 +2018,7 @@ impl OrchardTransferService {
             let amounts_digest = hash_amounts(transparent_inputs);
             let scripts_digest = hash_script_pubkeys(transparent_inputs);
             let sequences_digest = hash_sequences(transparent_inputs);
-            // outputs_digest is empty since no transparent outputs
-            let outputs_digest = blake2b_256(ZCASH_OUTPUTS_HASH, &[]);
+            let outputs_digest = blake2b_256(ZCASH_OUTPUTS_HASH, transparent_outputs);
             // txin_sig_digest is empty for SignableInput::Shielded
             let txin_sig_digest = blake2b_256(ZCASH_TRANSPARENT_SIG, &[]);
 
@@ -1590,15 +2194,7 @@ impl OrchardTransferService {
         );
 
         let payment_value = NoteValue::from_raw(proposal.amount_zatoshis);
-        let memo_bytes: [u8; 512] = {
-            let mut bytes = [0u8; 512];
-            if let Some(m) = proposal.memo.as_ref() {
-                let memo_data = m.as_bytes();
-                let len = std::cmp::min(memo_data.len(), 512);
-                bytes[..len].copy_from_slice(&memo_data[..len]);
-            }
-            bytes
-        };
+        let memo_bytes = encode_text_memo(proposal.memo.as_deref())?;
 
         builder
             .add_output(ovk.clone(), recipient_address, payment_value, memo_bytes)
@@ -1692,9 +2288,11 @@ impl OrchardTransferService {
         );
 
         // Create proof - returns a proven but not yet signed bundle
+        let proof_start = std::time::Instant::now();
         let proven_bundle = unauthorized_bundle
             .create_proof(pk, &mut OsRng)
             .map_err(|e| OrchardError::TransactionBuild(format!("Failed to create proof: {:?}", e)))?;
+        METRICS.observe_proof_seconds(proof_start.elapsed().as_secs_f64());
 
         Ok(proven_bundle)
     }
@@ -1993,13 +2591,7 @@ impl OrchardTransferService {
         plaintext.extend_from_slice(&rseed);
 
         // Memo (512 bytes)
-        let mut memo = [0u8; 512];
-        if let Some(ref m) = proposal.memo {
-            let bytes = m.as_bytes();
-            let len = std::cmp::min(bytes.len(), 512);
-            memo[..len].copy_from_slice(&bytes[..len]);
-        }
-        plaintext.extend_from_slice(&memo);
+        plaintext.extend_from_slice(&encode_text_memo(proposal.memo.as_deref())?);
 
         // In real implementation, encrypt with recipient's key using ChaCha20Poly1305
         // For now, just pad to 580 bytes (encrypted size with tag)
@@ -2149,6 +2741,29 @@ fn calculate_shielding_sighash(
     consensus_branch_id: u32,
     bundle: &orchard::Bundle<orchard::bundle::Authorized, i64>,
 ) -> OrchardResult<[u8; 32]> {
+    // No transparent outputs, everything goes to the Orchard bundle
+    Ok(calculate_transparent_sighash(
+        inputs,
+        input_index,
+        &[],
+        expiry_height,
+        consensus_branch_id,
+        compute_orchard_digest(bundle),
+    ))
+}
+
+/// Calculate the ZIP 244 SIGHASH_ALL digest for one transparent input
+///
+/// `outputs` is the serialized transparent outputs (without the count) and
+/// `orchard_digest` the digest of the Orchard bundle, or of the empty bundle.
+fn calculate_transparent_sighash(
+    inputs: &[TransparentInput],
+    input_index: usize,
+    outputs: &[u8],
+    expiry_height: u32,
+    consensus_branch_id: u32,
+    orchard_digest: [u8; 32],
+) -> [u8; 32] {
     let input = &inputs[input_index];
 
     tracing::debug!(
@@ -2177,8 +2792,8 @@ fn calculate_shielding_sighash(
     let sequences_digest = hash_sequences(inputs);
     tracing::debug!("sequences_digest: {}", hex::encode(&sequences_digest));
 
-    // Build outputs_sig_digest (empty for shielding - no transparent outputs)
-    let outputs_digest = blake2b_256(ZCASH_OUTPUTS_HASH, &[]);
+    // Build outputs_sig_digest
+    let outputs_digest = blake2b_256(ZCASH_OUTPUTS_HASH, outputs);
     tracing::debug!("outputs_digest: {}", hex::encode(&outputs_digest));
 
     // Build txin_sig_digest for this input
@@ -2233,8 +2848,6 @@ fn calculate_shielding_sighash(
     let sapling_digest = blake2b_256(b"ZTxIdSaplingHash", &[]);
     tracing::debug!("sapling_digest: {}", hex::encode(&sapling_digest));
 
-    // Compute orchard_digest according to ZIP 244 T.4
-    let orchard_digest = compute_orchard_digest(bundle);
     tracing::debug!("orchard_digest: {}", hex::encode(&orchard_digest));
 
     // Build the final sighash
@@ -2250,7 +2863,7 @@ fn calculate_shielding_sighash(
     let sighash = blake2b_256(&personalization, &sig_data);
     tracing::info!("Final sighash: {}", hex::encode(&sighash));
 
-    Ok(sighash)
+    sighash
 }
 
 /// Compute orchard_digest according to ZIP 244 T.4
@@ -2320,6 +2933,17 @@ pub fn sign_transparent_inputs_with_bundle(
     expiry_height: u32,
     consensus_branch_id: u32,
     bundle: &orchard::Bundle<orchard::bundle::Authorized, i64>,
+) -> OrchardResult<Vec<SignedTransparentInput>> {
+    sign_transparent_inputs(inputs, private_key_hex, |index| {
+        calculate_shielding_sighash(inputs, index, expiry_height, consensus_branch_id, bundle)
+    })
+}
+
+/// Sign each transparent input with the P2PKH key, using `sighash` for the input's digest
+fn sign_transparent_inputs(
+    inputs: &[TransparentInput],
+    private_key_hex: &str,
+    sighash: impl Fn(usize) -> OrchardResult<[u8; 32]>,
 ) -> OrchardResult<Vec<SignedTransparentInput>> {
     let secp = Secp256k1::new();
 
@@ -2352,17 +2976,7 @@ pub fn sign_transparent_inputs_with_bundle(
     // Sign each input
     let mut signed_inputs = Vec::new();
     for (i, input) in inputs.iter().enumerate() {
-        // Calculate sighash with proper orchard_digest
-        let sighash = calculate_shielding_sighash(
-            inputs,
-            i,
-            expiry_height,
-            consensus_branch_id,
-            bundle,
-        )?;
-
-        // Sign
-        let signature = sign_transparent_input(&secp, &secret_key, &sighash)?;
+        let signature = sign_transparent_input(&secp, &secret_key, &sighash(i)?)?;
         tracing::debug!("Input {} signature ({} bytes): {}", i, signature.len(), hex::encode(&signature));
 
         // Build scriptSig
@@ -2377,15 +2991,133 @@ pub fn sign_transparent_inputs_with_bundle(
         });
     }
 
-    tracing::info!("Signed {} transparent inputs for shielding", signed_inputs.len());
+    tracing::info!("Signed {} transparent inputs", signed_inputs.len());
     Ok(signed_inputs)
 }
 
-/// Check if an address is a Zcash transparent address (t1... or t3...)
+/// Write the transparent input count and the signed inputs
+fn write_signed_inputs(tx_data: &mut Vec<u8>, inputs: &[SignedTransparentInput]) {
+    tx_data.extend_from_slice(&serialize_compact_size(inputs.len() as u64));
+
+    for input in inputs {
+        // Previous output hash (32 bytes, little-endian)
+        let mut txid_le = input.prev_tx_hash;
+        txid_le.reverse();
+        tx_data.extend_from_slice(&txid_le);
+        // Previous output index (4 bytes)
+        tx_data.extend_from_slice(&input.prev_tx_index.to_le_bytes());
+        // Script sig length + script sig
+        tx_data.extend_from_slice(&serialize_compact_size(input.script_sig.len() as u64));
+        tx_data.extend_from_slice(&input.script_sig);
+        // Sequence (4 bytes)
+        tx_data.extend_from_slice(&input.sequence.to_le_bytes());
+    }
+}
+
+/// Serialize a transparent output paying `value_zatoshis` to `script_pubkey`
+fn serialize_transparent_output(value_zatoshis: u64, script_pubkey: &[u8]) -> Vec<u8> {
+    let mut output = (value_zatoshis as i64).to_le_bytes().to_vec();
+    output.extend_from_slice(&serialize_compact_size(script_pubkey.len() as u64));
+    output.extend_from_slice(script_pubkey);
+    output
+}
+
+/// ZIP-317 conventional fee
+///
+/// Logical actions are `max(transparent inputs, transparent outputs)` plus the Orchard
+/// actions, each charged `MARGINAL_FEE_ZATOSHIS` with a minimum of `GRACE_ACTIONS`.
+/// Transparent inputs and outputs are P2PKH, so each one is a single logical action.
+pub fn zip317_fee(transparent_inputs: usize, transparent_outputs: usize, orchard_actions: usize) -> u64 {
+    let logical_actions = std::cmp::max(transparent_inputs, transparent_outputs) + orchard_actions;
+    MARGINAL_FEE_ZATOSHIS * std::cmp::max(GRACE_ACTIONS as u64, logical_actions as u64)
+}
+
+/// Actions in an Orchard bundle with the given spends and outputs
+///
+/// Each action holds one spend and one output, and a non-empty bundle is padded to
+/// at least two actions.
+pub fn orchard_action_count(spends: usize, outputs: usize) -> usize {
+    match std::cmp::max(spends, outputs) {
+        0 => 0,
+        actions => std::cmp::max(actions, 2),
+    }
+}
+
+/// ZIP-317 fee of a transparent-only transaction
+pub fn transparent_transfer_fee(num_inputs: usize, num_outputs: usize) -> u64 {
+    zip317_fee(num_inputs, num_outputs, 0)
+}
+
+/// ZIP-317 fee of shielding `num_inputs` UTXOs into Orchard payment and change outputs
+pub fn shielding_fee(num_inputs: usize) -> u64 {
+    zip317_fee(num_inputs, 0, orchard_action_count(0, 2))
+}
+
+/// Fee a shielding transaction with `num_inputs` UTXOs actually pays
+///
+/// UTXO selection and `build_shielding_bundle` both use this, so the selected inputs
+/// always cover the fee the builder deducts before computing change.
+pub fn effective_shielding_fee(proposal_fee: u64, num_inputs: usize) -> u64 {
+    std::cmp::max(proposal_fee, shielding_fee(num_inputs))
+}
+
+/// ZIP-317 fee of spending `num_notes` notes and `num_inputs` UTXOs in one transaction
+///
+/// Change is an Orchard output; the payment is a transparent output for a transparent
+/// recipient and another Orchard output otherwise.
+pub fn combined_transfer_fee(num_notes: usize, num_inputs: usize, to_transparent: bool) -> u64 {
+    let (transparent_outputs, orchard_outputs) = if to_transparent { (1, 1) } else { (0, 2) };
+    zip317_fee(num_inputs, transparent_outputs, orchard_action_count(num_notes, orchard_outputs))
+}
+
+/// ZIP-317 fee of a consolidation spending `num_notes` notes
+///
+/// Each spend is one action and the single output shares the first one, so the
+/// action count is the note count (with the usual two-action grace minimum).
+pub fn consolidation_fee(num_notes: usize) -> u64 {
+    zip317_fee(0, 0, orchard_action_count(num_notes, 1))
+}
+
+/// Pick up to `max_notes` notes for a consolidation, smallest first
+///
+/// This is the opposite of regular note selection (largest first): the point is to
+/// clear out dust. Returns the selected notes, their total value and the fee.
+pub fn select_consolidation_notes(
+    mut notes_with_paths: Vec<(OrchardNote, MerklePath)>,
+    max_notes: usize,
+) -> OrchardResult<(Vec<(OrchardNote, MerklePath)>, u64, u64)> {
+    if max_notes < 2 {
+        return Err(OrchardError::TransactionBuild(
+            "Consolidation needs to spend at least 2 notes".to_string(),
+        ));
+    }
+    if notes_with_paths.len() < 2 {
+        return Err(OrchardError::TransactionBuild(format!(
+            "Nothing to consolidate: wallet has {} spendable note(s)",
+            notes_with_paths.len()
+        )));
+    }
+
+    notes_with_paths.sort_by(|a, b| a.0.value_zatoshis.cmp(&b.0.value_zatoshis));
+    notes_with_paths.truncate(max_notes);
+
+    let total: u64 = notes_with_paths.iter().map(|(note, _)| note.value_zatoshis).sum();
+    let fee = consolidation_fee(notes_with_paths.len());
+    if total <= fee {
+        return Err(OrchardError::InsufficientBalance {
+            available: total,
+            required: fee + 1,
+        });
+    }
+
+    Ok((notes_with_paths, total, fee))
+}
+
+/// Check if an address is a Zcash transparent address (t1/t3 on mainnet, tm/t2 on testnet)
 pub fn is_transparent_address(address: &str) -> bool {
-    // Zcash mainnet transparent addresses start with t1 (P2PKH) or t3 (P2SH)
+    // Zcash transparent addresses start with t1/tm (P2PKH) or t3/t2 (P2SH)
     // Length should be 34-35 characters for base58check encoded address
-    (address.starts_with("t1") || address.starts_with("t3"))
+    ["t1", "t3", "tm", "t2"].iter().any(|prefix| address.starts_with(prefix))
         && address.len() >= 34
         && address.len() <= 36
         && address.chars().all(|c| {
@@ -2399,6 +3131,89 @@ pub fn is_unified_address(address: &str) -> bool {
     address.starts_with("u1") && address.len() >= 100
 }
 
+/// What kind of recipient a validated address is
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RecipientKind {
+    Transparent,
+    Unified,
+}
+
+/// Base58check version bytes of P2PKH and P2SH transparent addresses
+pub(crate) fn transparent_prefixes(network: NetworkType) -> ([u8; 2], [u8; 2]) {
+    match network {
+        NetworkType::Mainnet => ([0x1C, 0xB8], [0x1C, 0xBD]), // t1, t3
+        NetworkType::Testnet => ([0x1D, 0x25], [0x1C, 0xBA]), // tm, t2
+    }
+}
+
+/// Fully validate a recipient address for `network`
+///
+/// Transparent addresses must pass the base58check checksum and carry the network's
+/// P2PKH prefix. Unified addresses must decode (bech32m and F4Jumble), belong to the
+/// network and contain a valid Orchard receiver, since that is the receiver we pay.
+pub fn validate_zcash_address(address: &str, network: NetworkType) -> OrchardResult<RecipientKind> {
+    use sha2::{Digest, Sha256};
+    use zcash_address::unified::{self, Container, Encoding, Receiver};
+    use zcash_protocol::consensus::NetworkType as AddressNetwork;
+
+    let address = address.trim();
+    let invalid = |reason: String| OrchardError::InvalidAddress(reason);
+
+    if address.starts_with('t') {
+        let decoded = bs58::decode(address)
+            .into_vec()
+            .map_err(|e| invalid(format!("{} is not valid base58: {}", address, e)))?;
+        if decoded.len() != 26 {
+            return Err(invalid(format!(
+                "Transparent address decodes to {} bytes, expected 26",
+                decoded.len()
+            )));
+        }
+
+        let checksum = Sha256::digest(Sha256::digest(&decoded[..22]));
+        if checksum[..4] != decoded[22..] {
+            return Err(invalid(format!("Checksum mismatch in {}; check for a typo", address)));
+        }
+
+        let (p2pkh, p2sh) = transparent_prefixes(network);
+        let prefix = [decoded[0], decoded[1]];
+        if prefix == p2sh {
+            return Err(invalid("P2SH recipients are not supported, use a P2PKH address".to_string()));
+        }
+        if prefix != p2pkh {
+            return Err(invalid(format!("{} is not a {:?} transparent address", address, network)));
+        }
+        return Ok(RecipientKind::Transparent);
+    }
+
+    let (address_network, ua) = unified::Address::decode(address)
+        .map_err(|e| invalid(format!("{} is not a valid unified address: {}", address, e)))?;
+    let expected = match network {
+        NetworkType::Mainnet => AddressNetwork::Main,
+        NetworkType::Testnet => AddressNetwork::Test,
+    };
+    if address_network != expected {
+        return Err(invalid(format!(
+            "Unified address is for {:?}, this wallet is on {:?}",
+            address_network, network
+        )));
+    }
+
+    let orchard_receiver = ua
+        .items()
+        .into_iter()
+        .find_map(|receiver| match receiver {
+            Receiver::Orchard(data) => Some(data),
+            _ => None,
+        })
+        .ok_or_else(|| invalid("Unified address has no Orchard receiver".to_string()))?;
+    if bool::from(orchard::Address::from_raw_address_bytes(&orchard_receiver).is_none()) {
+        return Err(invalid("Unified address has an invalid Orchard receiver".to_string()));
+    }
+
+    Ok(RecipientKind::Unified)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -2416,6 +3231,13 @@ mod tests {
 
         let zatoshis = request.get_zatoshis().unwrap();
         assert_eq!(zatoshis, 150_000_000);
+
+        // f64 would truncate this to 28999999
+        let request = TransferRequest { amount_zec: "0.29".to_string(), ..request };
+        assert_eq!(request.get_zatoshis().unwrap(), 29_000_000);
+
+        let request = TransferRequest { amount_zec: "0.000000001".to_string(), ..request };
+        assert!(request.get_zatoshis().is_err());
     }
 
     #[test]
@@ -2431,13 +3253,114 @@ mod tests {
         assert!(fee > DEFAULT_FEE_ZATOSHIS);
     }
 
+    /// ZIP-317 worked out by hand: 5000 × max(2, max(t_in, t_out) + orchard_actions)
+    #[test]
+    fn test_zip317_fee_table() {
+        for inputs in 1..=10usize {
+            // Shielding: n UTXOs, payment and change as 2 Orchard actions
+            let expected = 5000 * (inputs as u64 + 2);
+            assert_eq!(shielding_fee(inputs), expected, "shield {} inputs", inputs);
+
+            // Transparent-only: payment and change outputs
+            let expected = 5000 * std::cmp::max(2, inputs as u64);
+            assert_eq!(transparent_transfer_fee(inputs, 2), expected, "transparent {} inputs", inputs);
+
+            // Deshielding: n notes spent, change stays shielded, 1 transparent output
+            let expected = 5000 * (1 + std::cmp::max(2, inputs as u64));
+            assert_eq!(zip317_fee(0, 1, orchard_action_count(inputs, 1)), expected, "deshield {} notes", inputs);
+
+            // Fully shielded: n notes, payment and change
+            let expected = 5000 * std::cmp::max(2, inputs as u64);
+            assert_eq!(zip317_fee(0, 0, orchard_action_count(inputs, 2)), expected, "shielded {} notes", inputs);
+        }
+
+        // Shielding 5 UTXOs: 5 inputs + 2 actions = 7 logical actions
+        assert_eq!(shielding_fee(5), 35_000);
+        assert_eq!(orchard_action_count(0, 0), 0);
+        assert_eq!(orchard_action_count(1, 0), 2);
+    }
+
+    #[test]
+    fn test_proposal_fees_match_builder_fees() {
+        let service = OrchardTransferService::new(NetworkType::Mainnet);
+
+        // Estimates assume a single input, which is exactly what the builders charge for one
+        assert_eq!(service.calculate_fee(1, FundSource::Transparent), shielding_fee(1));
+        assert_eq!(service.calculate_fee(1, FundSource::Shielded), zip317_fee(0, 0, orchard_action_count(1, 2)));
+        assert_eq!(
+            service.calculate_fee_with_transparent_outputs(1, FundSource::Shielded, 1),
+            zip317_fee(0, 1, orchard_action_count(1, 1)),
+        );
+
+        // More UTXOs only ever raise the fee above the proposal's, never lower it
+        let proposal_fee = service.calculate_fee(1, FundSource::Transparent);
+        for inputs in 1..=10 {
+            let fee = effective_shielding_fee(proposal_fee, inputs);
+            assert_eq!(fee, std::cmp::max(proposal_fee, shielding_fee(inputs)));
+            assert!(fee >= proposal_fee);
+        }
+    }
+
+    fn unified_recipient() -> String {
+        let key = "fedcba9876543210fedcba9876543210fedcba9876543210fedcba9876543210";
+        crate::crypto::zcash::enable_orchard_for_wallet(key, 2_800_000, NetworkType::Mainnet).unwrap().0.address
+    }
+
+    #[test]
+    fn test_validate_zcash_address() {
+        use zcash_address::unified::{self, Encoding};
+
+        let (transparent, _) = crate::crypto::zcash::generate_zcash_wallet(NetworkType::Mainnet).unwrap();
+        let unified = unified_recipient();
+        assert_eq!(validate_zcash_address(&transparent, NetworkType::Mainnet).unwrap(), RecipientKind::Transparent);
+        assert_eq!(validate_zcash_address(&unified, NetworkType::Mainnet).unwrap(), RecipientKind::Unified);
+
+        // One changed character breaks the checksum, even though the loose check passes
+        let last = if transparent.ends_with('a') { 'b' } else { 'a' };
+        let typo = format!("{}{}", &transparent[..transparent.len() - 1], last);
+        assert!(is_transparent_address(&typo));
+        let error = validate_zcash_address(&typo, NetworkType::Mainnet).unwrap_err();
+        let OrchardError::InvalidAddress(message) = error else {
+            panic!("expected an address error");
+        };
+        assert!(message.contains("Checksum mismatch"));
+
+        let typo = format!("{}{}", &unified[..unified.len() - 1], if unified.ends_with('q') { 'p' } else { 'q' });
+        assert!(validate_zcash_address(&typo, NetworkType::Mainnet).is_err());
+
+        // The right address on the wrong network
+        assert!(validate_zcash_address(&transparent, NetworkType::Testnet).is_err());
+        let (_, ua) = unified::Address::decode(&unified).unwrap();
+        let testnet = ua.encode(&zcash_protocol::consensus::NetworkType::Test);
+        assert!(validate_zcash_address(&testnet, NetworkType::Testnet).is_ok());
+        assert!(validate_zcash_address(&testnet, NetworkType::Mainnet).is_err());
+    }
+
+    #[test]
+    fn test_branch_id_follows_network_upgrades() {
+        // NU6.1 is live on mainnet at heights where testnet is still on NU6
+        assert_eq!(NetworkType::Mainnet.consensus_branch_id_at(3_200_000), CONSENSUS_BRANCH_ID_NU6_1);
+        assert_eq!(NetworkType::Testnet.consensus_branch_id_at(3_200_000), CONSENSUS_BRANCH_ID_NU6);
+        assert_eq!(NetworkType::Mainnet.consensus_branch_id_at(2_000_000), CONSENSUS_BRANCH_ID_NU5);
+        assert_eq!(NetworkType::Testnet.orchard_activation_height(), 1_842_420);
+
+        // The tip decides the branch: one block before NU6.1 the next block is already on it
+        let service = OrchardTransferService::new(NetworkType::Mainnet);
+        assert_eq!(service.branch_id(3_146_399 + TX_EXPIRY_DELTA), CONSENSUS_BRANCH_ID_NU6_1);
+        let service = service.with_chain_height(3_146_398);
+        assert_eq!(service.branch_id(3_146_399 + TX_EXPIRY_DELTA), CONSENSUS_BRANCH_ID_NU6);
+
+        let network: NetworkType = serde_json::from_str("\"testnet\"").unwrap();
+        assert_eq!(network, NetworkType::Testnet);
+    }
+
     #[test]
     fn test_create_proposal() {
         let service = OrchardTransferService::new(NetworkType::Mainnet);
 
         let request = TransferRequest {
             wallet_id: 1,
-            to_address: "u1test".to_string(),
+            to_address: unified_recipient(),
             amount_zec: "0.001".to_string(),
             amount_zatoshis: None,
             memo: Some("Test memo".to_string()),
@@ -2454,5 +3377,275 @@ mod tests {
         assert_eq!(proposal.amount_zatoshis, 100_000);
         assert!(proposal.is_shielding);
         assert_eq!(proposal.fund_source, FundSource::Transparent);
+
+        // An over-length memo is rejected rather than cut to fit
+        let long_memo = TransferRequest {
+            memo: Some("x".repeat(600)),
+            ..request
+        };
+        assert!(service.create_proposal(&long_memo, 1_000_000, None, 2_500_000).is_err());
+    }
+
+    #[test]
+    fn test_estimate_reports_shortfall() {
+        let service = OrchardTransferService::new(NetworkType::Mainnet);
+
+        let request = TransferRequest {
+            wallet_id: 1,
+            to_address: unified_recipient(),
+            amount_zec: "0.001".to_string(),
+            amount_zatoshis: None,
+            memo: None,
+            fund_source: FundSource::Auto,
+        };
+
+        let estimate = service.estimate_transfer(&request, 1_000_000, None).unwrap();
+        assert!(estimate.sufficient);
+        assert_eq!(estimate.operation(), "shielding");
+        assert_eq!(estimate.fee_zatoshis, service.calculate_fee(1, FundSource::Transparent));
+
+        // Too little anywhere: still an estimate, just not a sufficient one
+        let estimate = service.estimate_transfer(&request, 50_000, None).unwrap();
+        assert!(!estimate.sufficient);
+        assert_eq!(estimate.fund_source, FundSource::Transparent);
+        assert_eq!(estimate.available_zatoshis, 50_000);
+        assert!(service.create_proposal(&request, 50_000, None, 2_500_000).is_err());
+    }
+
+    #[test]
+    fn test_auto_combines_pools_when_neither_suffices() {
+        use super::super::ShieldedPool;
+
+        let service = OrchardTransferService::new(NetworkType::Mainnet);
+        let request = TransferRequest {
+            wallet_id: 1,
+            to_address: unified_recipient(),
+            amount_zec: "0.001".to_string(),
+            amount_zatoshis: None,
+            memo: None,
+            fund_source: FundSource::Auto,
+        };
+        let shielded = ShieldedBalance::new(ShieldedPool::Orchard, 70_000, 70_000, 2);
+
+        // 70k shielded + 60k transparent: only both together cover 100k plus fee
+        let estimate = service.estimate_transfer(&request, 60_000, Some(&shielded)).unwrap();
+        assert_eq!(estimate.fund_source, FundSource::Auto);
+        assert_eq!(estimate.operation(), "combined");
+        assert!(!estimate.is_shielding);
+        assert_eq!(estimate.fee_zatoshis, combined_transfer_fee(2, 1, false));
+        assert_eq!(estimate.available_zatoshis, 130_000);
+        assert!(estimate.sufficient);
+        let proposal = service.create_proposal(&request, 60_000, Some(&shielded), 2_500_000).unwrap();
+        assert_eq!(proposal.fund_source, FundSource::Auto);
+
+        // One pool is enough: no combining
+        let estimate = service.estimate_transfer(&request, 200_000, Some(&shielded)).unwrap();
+        assert_eq!(estimate.fund_source, FundSource::Transparent);
+
+        // Not even both together; and only Auto may combine
+        let estimate = service.estimate_transfer(&request, 30_000, Some(&shielded)).unwrap();
+        assert!(!estimate.sufficient);
+        let shielded_only = TransferRequest { fund_source: FundSource::Shielded, ..request };
+        let estimate = service.estimate_transfer(&shielded_only, 60_000, Some(&shielded)).unwrap();
+        assert_eq!(estimate.fund_source, FundSource::Shielded);
+        assert!(!estimate.sufficient);
+    }
+
+    #[test]
+    fn test_combined_transfer_fee() {
+        // 2 notes and 1 UTXO to a unified address: 1 input + 2 actions
+        assert_eq!(combined_transfer_fee(2, 1, false), 15_000);
+        // 3 notes, 4 UTXOs, transparent payment: max(4, 1) + 3 actions
+        assert_eq!(combined_transfer_fee(3, 4, true), 35_000);
+    }
+
+    #[test]
+    fn test_transparent_transfer() {
+        use zcash_primitives::transaction::Transaction;
+        use zcash_protocol::consensus::BranchId;
+
+        let (sender, private_key) = crate::crypto::zcash::generate_zcash_wallet(NetworkType::Mainnet).unwrap();
+        let (recipient, _) = crate::crypto::zcash::generate_zcash_wallet(NetworkType::Mainnet).unwrap();
+        let service = OrchardTransferService::new(NetworkType::Mainnet).with_chain_height(2_000_000);
+
+        let request = TransferRequest {
+            wallet_id: 1,
+            to_address: recipient,
+            amount_zec: "0.001".to_string(),
+            amount_zatoshis: None,
+            memo: None,
+            fund_source: FundSource::Auto,
+        };
+        // Without shielded funds, a transparent recipient is paid from transparent funds
+        let proposal = service.create_proposal(&request, 1_000_000, None, 2_000_000).unwrap();
+        assert!(proposal.is_transparent_only());
+        assert!(!proposal.is_deshielding);
+        assert_eq!(proposal.fee_zatoshis, transparent_transfer_fee(1, 2));
+
+        let decoded = bs58::decode(&sender).into_vec().unwrap();
+        let script: Vec<u8> = [0x76, 0xa9, 0x14]
+            .iter()
+            .chain(&decoded[2..22])
+            .chain(&[0x88, 0xac])
+            .copied()
+            .collect();
+        let utxo = |prev_tx_index, value| TransparentInput {
+            prev_tx_hash: [7u8; 32],
+            prev_tx_index,
+            script_pubkey: script.clone(),
+            value,
+            sequence: 0xfffffffe,
+        };
+        let parse = |result: TransferResult| {
+            let raw = hex::decode(result.raw_tx.unwrap()).unwrap();
+            Transaction::read(&raw[..], BranchId::try_from(CONSENSUS_BRANCH_ID_NU5).unwrap()).unwrap()
+        };
+
+        let result = service
+            .build_transparent_transfer(&proposal, &private_key, &[utxo(0, 80_000), utxo(1, 50_000)])
+            .unwrap();
+        assert_eq!(result.fee_zatoshis, 10_000);
+        let tx = parse(result);
+        assert!(tx.sapling_bundle().is_none() && tx.orchard_bundle().is_none());
+        let bundle = tx.transparent_bundle().unwrap();
+        // Payment and change
+        assert_eq!((bundle.vin.len(), bundle.vout.len()), (2, 2));
+
+        // Dust change goes to the fee instead of an output
+        let result = service
+            .build_transparent_transfer(&proposal, &private_key, &[utxo(0, 110_040)])
+            .unwrap();
+        assert_eq!(result.fee_zatoshis, 10_040);
+        assert_eq!(parse(result).transparent_bundle().unwrap().vout.len(), 1);
+
+        assert!(matches!(
+            service.build_transparent_transfer(&proposal, &private_key, &[utxo(0, 100_000)]),
+            Err(OrchardError::InsufficientBalance { .. })
+        ));
+    }
+
+    #[test]
+    fn test_into_submitted_uses_node_txid() {
+        let result = TransferResult {
+            tx_id: "aa".repeat(32),
+            status: TransferStatus::Signed,
+            raw_tx: Some("00".to_string()),
+            amount_zatoshis: 100_000,
+            fee_zatoshis: 10_000,
+        };
+
+        let submitted = result.into_submitted(&"bb".repeat(32));
+        assert_eq!(submitted.status, TransferStatus::Submitted);
+        assert_eq!(submitted.tx_id, "bb".repeat(32));
+        assert_eq!(submitted.amount_zatoshis, 100_000);
+    }
+
+    #[test]
+    fn test_spent_wallet_nullifiers_ignores_dummy_spends() {
+        let ours = [1u8; 32];
+        let dummy = [2u8; 32];
+        let wallet_nullifiers: std::collections::HashSet<String> =
+            [hex::encode(ours), hex::encode([3u8; 32])].into_iter().collect();
+
+        let spent = spent_wallet_nullifiers(&[ours, dummy], &wallet_nullifiers);
+        assert_eq!(spent, vec![hex::encode(ours)]);
+    }
+
+    /// Marked notes of the given values, each with a real path in one tree
+    fn notes_with_paths(values: &[u64]) -> Vec<(OrchardNote, MerklePath)> {
+        let mut tree = crate::blockchain::zcash::orchard::tree::OrchardTreeTracker::new();
+        let positions: Vec<u64> = values
+            .iter()
+            .enumerate()
+            .map(|(i, _)| {
+                let mut cmx = [0u8; 32];
+                cmx[0] = i as u8 + 1;
+                tree.append_and_mark(&cmx).unwrap()
+            })
+            .collect();
+
+        values
+            .iter()
+            .zip(positions)
+            .map(|(value, position)| {
+                let note = OrchardNote {
+                    id: None,
+                    wallet_id: Some(1),
+                    account_id: 0,
+                    tx_hash: String::new(),
+                    block_height: 2_800_000,
+                    note_commitment: [0u8; 32],
+                    nullifier: [position as u8; 32],
+                    value_zatoshis: *value,
+                    position,
+                    is_spent: false,
+                    memo: None,
+                    merkle_path: None,
+                    recipient: [0u8; 43],
+                    rho: [0u8; 32],
+                    rseed: [0u8; 32],
+                    witness_data: None,
+                };
+                (note, tree.get_orchard_merkle_path(position).unwrap())
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_consolidation_sweeps_dust_smallest_first() {
+        // 20 dust notes plus one large note that should be left alone
+        let mut values: Vec<u64> = (1..=20).map(|i| 10_000 + i * 100).collect();
+        values.push(500_000_000);
+
+        let (selected, total, fee) = select_consolidation_notes(notes_with_paths(&values), 20).unwrap();
+        assert_eq!(selected.len(), 20);
+        assert!(selected.iter().all(|(note, _)| note.value_zatoshis < 500_000_000));
+        assert_eq!(total, values[..20].iter().sum::<u64>());
+        assert_eq!(fee, consolidation_fee(20));
+        assert_eq!(fee, 20 * MARGINAL_FEE_ZATOSHIS);
+
+        // Capped at max_notes, still taking the smallest
+        let (selected, _, fee) = select_consolidation_notes(notes_with_paths(&values), 5).unwrap();
+        let picked: Vec<u64> = selected.iter().map(|(note, _)| note.value_zatoshis).collect();
+        assert_eq!(picked, values[..5].to_vec());
+        assert_eq!(fee, 5 * MARGINAL_FEE_ZATOSHIS);
+    }
+
+    #[test]
+    fn test_consolidation_guards() {
+        // A single note is already consolidated
+        let err = select_consolidation_notes(notes_with_paths(&[1_000_000]), 10).unwrap_err();
+        assert!(err.to_string().contains("Nothing to consolidate"));
+
+        assert!(select_consolidation_notes(notes_with_paths(&[1_000_000, 2_000_000]), 1).is_err());
+
+        // Notes worth less than the fee to spend them
+        let dust = notes_with_paths(&[1_000; 10]);
+        assert!(matches!(
+            select_consolidation_notes(dust, 10),
+            Err(OrchardError::InsufficientBalance { .. })
+        ));
+
+        assert_eq!(consolidation_fee(1), consolidation_fee(2));
+    }
+
+    #[test]
+    fn test_orchard_nullifiers_rejects_garbage() {
+        assert!(orchard_nullifiers(&[0u8; 4]).is_err());
+        assert!(orchard_nullifiers(&[0u8; 16]).is_err());
+        assert!(transaction_id(&[0u8; 16]).is_err());
+        assert!(transaction_expiry_height(&[0u8; 16]).is_err());
+    }
+
+    #[test]
+    fn test_broadcast_rejection_error() {
+        let err = broadcast_rejection_error("RPC error -25: bad-txns-anchor");
+        assert!(matches!(err, OrchardError::RpcError(ref msg) if msg.contains("resync witnesses")));
+
+        let err = broadcast_rejection_error("RPC error -25: missing-inputs");
+        assert!(matches!(err, OrchardError::RpcError(ref msg) if msg.contains("already spent")));
+
+        let err = broadcast_rejection_error("RPC request failed: timeout");
+        assert!(matches!(err, OrchardError::RpcError(ref msg) if msg == "RPC request failed: timeout"));
     }
 }
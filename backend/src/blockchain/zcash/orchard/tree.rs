@@ -452,6 +452,93 @@ impl WitnessData {
 
         Ok(OrchardMerklePath::from_parts(self.position as u32, auth_path))
     }
+
+    /// Sanity check a refreshed witness before it is used to spend a note
+    ///
+    /// Catches tree-tracking regressions that would otherwise only surface as an
+    /// unspendable transaction rejected by the node.
+    pub fn verify_for_spend(
+        &self,
+        note_position: u64,
+        tree_size: u64,
+        tree_root: &[u8; 32],
+    ) -> Result<(), TreeError> {
+        if self.auth_path.len() != ORCHARD_TREE_DEPTH as usize {
+            return Err(TreeError::InvalidWitness(format!(
+                "Expected {} auth path elements for note at position {}, got {}",
+                ORCHARD_TREE_DEPTH,
+                note_position,
+                self.auth_path.len()
+            )));
+        }
+
+        if self.position != note_position {
+            return Err(TreeError::InvalidWitness(format!(
+                "Witness position {} does not match note position {}",
+                self.position, note_position
+            )));
+        }
+
+        if self.position >= tree_size {
+            return Err(TreeError::InvalidPosition(self.position));
+        }
+
+        if &self.root != tree_root {
+            return Err(TreeError::InvalidWitness(format!(
+                "Witness root {} for note at position {} does not match tree root {}",
+                hex::encode(self.root),
+                note_position,
+                hex::encode(tree_root)
+            )));
+        }
+
+        Ok(())
+    }
+}
+
+/// Verify every (note position, witness) pair selected for a spend
+///
+/// Besides the per-witness checks, no two notes may claim the same tree position.
+pub fn verify_spend_witnesses(
+    witnesses: &[(u64, WitnessData)],
+    tree_size: u64,
+    tree_root: &[u8; 32],
+) -> Result<(), TreeError> {
+    for (note_position, witness) in witnesses {
+        witness.verify_for_spend(*note_position, tree_size, tree_root)?;
+    }
+
+    let mut positions: Vec<u64> = witnesses.iter().map(|(pos, _)| *pos).collect();
+    positions.sort_unstable();
+    if let Some(pair) = positions.windows(2).find(|pair| pair[0] == pair[1]) {
+        return Err(TreeError::InvalidWitness(format!(
+            "Multiple notes share tree position {}",
+            pair[0]
+        )));
+    }
+
+    Ok(())
+}
+
+/// How a note's witness root compares with the anchor the node expects
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AnchorStatus {
+    /// Witness root equals the expected anchor; the note can be spent
+    Match,
+    /// Witness root differs; a spend would be rejected by the node
+    Mismatch,
+    /// No witness could be computed for the note
+    MissingWitness,
+}
+
+/// Compare a note's witness root against the node's expected anchor
+pub fn witness_anchor_status(witness: Option<&WitnessData>, expected_anchor: &[u8; 32]) -> AnchorStatus {
+    match witness {
+        Some(witness) if &witness.root == expected_anchor => AnchorStatus::Match,
+        Some(_) => AnchorStatus::Mismatch,
+        None => AnchorStatus::MissingWitness,
+    }
 }
 
 /// Serializable tree state for persistence
@@ -567,4 +654,55 @@ mod tests {
         let witness = tracker.get_witness(2);
         assert!(witness.is_some());
     }
+
+    fn witness_at(position: u64, root: [u8; 32]) -> WitnessData {
+        WitnessData {
+            position,
+            auth_path: vec![[0u8; 32]; ORCHARD_TREE_DEPTH as usize],
+            root,
+        }
+    }
+
+    #[test]
+    fn test_verify_for_spend_accepts_consistent_witness() {
+        let root = [7u8; 32];
+        let witnesses = vec![(3, witness_at(3, root)), (10, witness_at(10, root))];
+        assert!(verify_spend_witnesses(&witnesses, 11, &root).is_ok());
+    }
+
+    #[test]
+    fn test_verify_for_spend_rejects_wrong_depth() {
+        let root = [7u8; 32];
+        let mut witness = witness_at(3, root);
+        witness.auth_path.truncate(31);
+
+        let err = witness.verify_for_spend(3, 11, &root).unwrap_err();
+        assert!(err.to_string().contains("Expected 32 auth path elements"));
+    }
+
+    #[test]
+    fn test_verify_for_spend_rejects_inconsistent_witness() {
+        let root = [7u8; 32];
+
+        // Position does not match the note
+        assert!(witness_at(4, root).verify_for_spend(3, 11, &root).is_err());
+        // Position beyond the tree
+        assert!(witness_at(11, root).verify_for_spend(11, 11, &root).is_err());
+        // Stale root
+        assert!(witness_at(3, [8u8; 32]).verify_for_spend(3, 11, &root).is_err());
+        // Duplicate positions
+        let witnesses = vec![(3, witness_at(3, root)), (3, witness_at(3, root))];
+        assert!(verify_spend_witnesses(&witnesses, 11, &root).is_err());
+    }
+
+    #[test]
+    fn test_witness_anchor_status_distinguishes_notes() {
+        let anchor = [7u8; 32];
+        let matching = witness_at(3, anchor);
+        let stale = witness_at(4, [8u8; 32]);
+
+        assert_eq!(witness_anchor_status(Some(&matching), &anchor), AnchorStatus::Match);
+        assert_eq!(witness_anchor_status(Some(&stale), &anchor), AnchorStatus::Mismatch);
+        assert_eq!(witness_anchor_status(None, &anchor), AnchorStatus::MissingWitness);
+    }
 }
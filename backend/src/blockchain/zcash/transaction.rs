@@ -18,11 +18,10 @@ const TX_VERSION: u32 = 5;
 const TX_VERSION_WITH_OVERWINTERED: u32 = TX_VERSION | (1 << 31); // 0x80000005
 const VERSION_GROUP_ID_V5: u32 = 0x26A7270A;
 
-// Consensus branch IDs for different network upgrades
-#[allow(dead_code)]
-const CONSENSUS_BRANCH_ID_NU5: u32 = 0xc2d6d0b4; // NU5
-const CONSENSUS_BRANCH_ID_NU6: u32 = 0xc8e71055; // NU6
-const CONSENSUS_BRANCH_ID_NU6_1: u32 = 0x4dec4df0; // NU6.1
+// Consensus branch IDs for different network upgrades, the same on every network
+pub const CONSENSUS_BRANCH_ID_NU5: u32 = 0xc2d6d0b4; // NU5
+pub const CONSENSUS_BRANCH_ID_NU6: u32 = 0xc8e71055; // NU6
+pub const CONSENSUS_BRANCH_ID_NU6_1: u32 = 0x4dec4df0; // NU6.1
 
 // Network upgrade activation heights (mainnet)
 const NU6_ACTIVATION_HEIGHT: u32 = 2_726_400;
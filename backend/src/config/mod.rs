@@ -1,10 +1,79 @@
 use config::{Config, ConfigError, Environment, File};
+use rust_decimal::Decimal;
 use serde::Deserialize;
+use std::collections::HashMap;
+
+use crate::blockchain::zcash::orchard::constants;
+use crate::blockchain::zcash::orchard::lightwalletd::SyncBackend;
+use crate::blockchain::zcash::orchard::transfer::NetworkType;
+use crate::error::{AppError, AppResult};
 
 #[derive(Debug, Clone, Deserialize)]
 pub struct ServerConfig {
     pub host: String,
     pub port: u16,
+    /// Reverse proxies in front of the server whose X-Forwarded-For entries are trusted
+    #[serde(default)]
+    pub trusted_proxies: usize,
+    /// Largest JSON request body accepted, in bytes
+    #[serde(default = "default_json_limit_bytes")]
+    pub json_limit_bytes: usize,
+    /// Origins allowed to call the API from a browser, e.g. `https://wallet.example.com`
+    /// (comma-separated in WEB3_SERVER__CORS_ALLOWED_ORIGINS)
+    #[serde(default = "default_cors_allowed_origins")]
+    pub cors_allowed_origins: Vec<String>,
+    /// Accept every origin; for local development only
+    #[serde(default)]
+    pub cors_allow_any_origin: bool,
+    /// Let browsers send cookies and auth headers cross-origin; needs explicit origins
+    #[serde(default)]
+    pub cors_allow_credentials: bool,
+    /// How long browsers may cache a preflight response, in seconds
+    #[serde(default = "default_cors_max_age_secs")]
+    pub cors_max_age_secs: usize,
+}
+
+/// 1 MiB leaves room for a pasted raw transaction while capping what one request can buffer
+fn default_json_limit_bytes() -> usize {
+    1024 * 1024
+}
+
+/// The frontend's Vite dev server
+fn default_cors_allowed_origins() -> Vec<String> {
+    vec!["http://localhost:5173".to_string(), "http://127.0.0.1:5173".to_string()]
+}
+
+fn default_cors_max_age_secs() -> usize {
+    3600
+}
+
+impl ServerConfig {
+    fn validate_cors(&self) -> Result<(), ConfigError> {
+        if self.cors_allow_credentials && self.cors_allow_any_origin {
+            return Err(ConfigError::Message(
+                "server.cors_allow_credentials cannot be combined with server.cors_allow_any_origin"
+                    .to_string(),
+            ));
+        }
+        for origin in &self.cors_allowed_origins {
+            if origin == "*" {
+                return Err(ConfigError::Message(
+                    "server.cors_allowed_origins cannot contain \"*\"; set server.cors_allow_any_origin for development"
+                        .to_string(),
+                ));
+            }
+            // An origin is scheme://host[:port], without a path or trailing slash
+            let valid = url::Url::parse(origin)
+                .is_ok_and(|url| url.origin().ascii_serialization() == *origin);
+            if !valid {
+                return Err(ConfigError::Message(format!(
+                    "Invalid CORS origin '{}', expected e.g. https://wallet.example.com",
+                    origin
+                )));
+            }
+        }
+        Ok(())
+    }
 }
 
 #[derive(Debug, Clone, Deserialize)]
@@ -29,12 +98,106 @@ impl DatabaseConfig {
 #[derive(Debug, Clone, Deserialize)]
 pub struct JwtConfig {
     pub secret: String,
+    /// Token lifetime when refresh tokens are disabled
     pub expire_hours: u64,
+    /// Issue short-lived access tokens with rotating refresh tokens instead
+    #[serde(default)]
+    pub refresh_tokens: bool,
+    #[serde(default = "default_access_token_minutes")]
+    pub access_token_minutes: u64,
+    #[serde(default = "default_refresh_token_days")]
+    pub refresh_token_days: u64,
 }
 
+fn default_access_token_minutes() -> u64 {
+    15
+}
+
+fn default_refresh_token_days() -> u64 {
+    30
+}
+
+/// Encryption keys shipped as defaults or printed in the docs
+pub const INSECURE_ENCRYPTION_KEYS: [&str; 2] = [
+    "32-byte-encryption-key-here!!!!!",
+    "uK7m2VxQ9nL3aT1aR8c26yH0uJ4bZ5wE",
+];
+
+/// JWT secrets shipped as defaults or printed in the docs
+pub const INSECURE_JWT_SECRETS: [&str; 4] = [
+    "change-me-in-production-please!",
+    "change-me-in-production",
+    "your-very-secure-jwt-secret",
+    "your-secure-jwt-secret-key",
+];
+
+/// Password the seeded admin account gets unless one is configured
+pub const INSECURE_ADMIN_PASSWORD: &str = "admin123";
+
 #[derive(Debug, Clone, Deserialize)]
 pub struct SecurityConfig {
     pub encryption_key: String,
+    /// Key being rotated away from; still accepted for reads until every wallet is re-encrypted
+    #[serde(default)]
+    pub previous_encryption_key: Option<String>,
+    /// Password of the admin account created on first start
+    #[serde(default = "default_admin_password")]
+    pub admin_password: String,
+    /// Start even with the published default key, JWT secret or admin password; for development only
+    #[serde(default)]
+    pub allow_insecure_defaults: bool,
+}
+
+fn default_admin_password() -> String {
+    INSECURE_ADMIN_PASSWORD.to_string()
+}
+
+impl SecurityConfig {
+    /// Refuse secrets anyone could look up, unless explicitly allowed
+    fn validate(&self, jwt: &JwtConfig) -> Result<(), ConfigError> {
+        if self.allow_insecure_defaults {
+            return Ok(());
+        }
+        if INSECURE_ENCRYPTION_KEYS.contains(&self.encryption_key.as_str()) {
+            return Err(ConfigError::Message(
+                "security.encryption_key is a published default; generate a random 32-byte key".to_string(),
+            ));
+        }
+        if INSECURE_JWT_SECRETS.contains(&jwt.secret.as_str()) {
+            return Err(ConfigError::Message(
+                "jwt.secret is a published default; set a random secret".to_string(),
+            ));
+        }
+        if self.admin_password == INSECURE_ADMIN_PASSWORD {
+            return Err(ConfigError::Message(
+                "security.admin_password is the published default; set the password the admin account is created with".to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Warn when the published-defaults check is switched off; called once logging is up
+    pub fn warn_unsafe_settings(&self) {
+        if self.allow_insecure_defaults {
+            tracing::warn!("security.allow_insecure_defaults is set; published default secrets are accepted, never use this in production");
+        }
+    }
+
+    /// Decrypt stored data with the current key, falling back to the previous one
+    pub fn decrypt(&self, data: &str) -> AppResult<String> {
+        match crate::crypto::decrypt(data, &self.encryption_key) {
+            Ok(plaintext) => Ok(plaintext),
+            Err(e) => match &self.previous_encryption_key {
+                Some(previous) => crate::crypto::decrypt(data, previous),
+                None => Err(e),
+            },
+        }
+    }
+
+    /// Whether `key` is one the running service can decrypt with
+    pub fn accepts_key(&self, key: &str) -> bool {
+        key == self.encryption_key || self.previous_encryption_key.as_deref() == Some(key)
+    }
 }
 
 #[derive(Debug, Clone, Deserialize)]
@@ -44,10 +207,53 @@ pub struct EthereumConfig {
     pub fallback_rpcs: Vec<String>,
     /// HTTP/HTTPS/SOCKS5 proxy for RPC requests (e.g., "http://127.0.0.1:7890" or "socks5://127.0.0.1:1080")
     pub rpc_proxy: Option<String>,
+    /// Blocks a transfer must be buried under before it is marked confirmed
+    pub confirmations: u64,
+    /// Minutes a submitted transaction may stay unmined before its transfer is marked
+    /// failed as dropped, or flagged as stuck while still in the mempool (0 disables)
+    pub pending_timeout_minutes: u64,
+}
+
+/// Confirmations before an EVM transfer counts as final; 12 blocks is the common
+/// pre-merge rule of thumb and still covers ordinary reorgs
+pub const DEFAULT_EVM_CONFIRMATIONS: u64 = 12;
+
+fn default_evm_confirmations() -> u64 {
+    DEFAULT_EVM_CONFIRMATIONS
+}
+
+/// geth evicts transactions that sat in its pool for three hours
+pub const DEFAULT_EVM_PENDING_TIMEOUT_MINUTES: u64 = 180;
+
+fn default_evm_pending_timeout_minutes() -> u64 {
+    DEFAULT_EVM_PENDING_TIMEOUT_MINUTES
+}
+
+/// An additional EVM chain served by its own `EthereumClient`, keyed by registry
+/// name (e.g. WEB3_EVM_CHAINS__POLYGON__CHAIN_ID=137)
+#[derive(Debug, Clone, Deserialize)]
+pub struct EvmChainConfig {
+    /// Display name, e.g. "Polygon PoS" (defaults to the registry key)
+    pub name: Option<String>,
+    pub chain_id: u64,
+    pub rpc_url: String,
+    #[serde(default)]
+    pub fallback_rpcs: Vec<String>,
+    #[serde(default)]
+    pub rpc_proxy: Option<String>,
+    /// Native coin symbol, e.g. "MATIC"
+    pub native_symbol: String,
+    #[serde(default = "default_evm_confirmations")]
+    pub confirmations: u64,
+    #[serde(default = "default_evm_pending_timeout_minutes")]
+    pub pending_timeout_minutes: u64,
 }
 
 #[derive(Debug, Clone, Deserialize)]
 pub struct ZcashConfig {
+    /// "mainnet" or "testnet"; selects activation heights, branch IDs and address encodings
+    #[serde(default)]
+    pub network: NetworkType,
     pub rpc_url: String,
     pub fallback_rpcs: Vec<String>,
     /// HTTP/HTTPS/SOCKS5 proxy for RPC requests
@@ -56,6 +262,237 @@ pub struct ZcashConfig {
     pub rpc_user: Option<String>,
     /// RPC password for authentication
     pub rpc_password: Option<String>,
+    /// Sanity check Orchard witnesses after refresh, before building a spend
+    pub verify_witnesses: bool,
+    /// Confirmations before a received Orchard note is spendable
+    pub min_confirmations: u32,
+    /// Blocks between the chain tip and the anchor used for Orchard spends
+    pub anchor_offset: u32,
+    /// Timeout for Orchard block batch RPC calls
+    pub rpc_timeout_secs: u64,
+    /// Timeout for chain tip queries used by health and progress endpoints
+    pub tip_rpc_timeout_secs: u64,
+    /// Blocks fetched per Orchard sync round
+    pub sync_batch_size: u64,
+    /// Concurrent block fetches during Orchard sync; lower for rate-limited nodes
+    pub sync_parallel_fetches: usize,
+    /// Reject shielded sends without a memo (deshielding is exempt)
+    pub require_memo: bool,
+    /// Register a new wallet's viewing key with the running sync on create/import
+    pub auto_register_wallets: bool,
+    /// Block source for Orchard sync: "zebra_rpc" or "lightwalletd"
+    #[serde(default)]
+    pub sync_backend: SyncBackend,
+    /// lightwalletd gRPC endpoint, required when `sync_backend` is "lightwalletd"
+    pub lightwalletd_url: Option<String>,
+    /// Seconds between polls for transparent deposits to wallet t-addresses (0 = off)
+    pub deposit_poll_secs: u64,
+}
+
+/// Per-chain safety limits for a single transfer, keyed by chain id
+/// (e.g. WEB3_TRANSFER_LIMITS__MAX_AMOUNT__ETHEREUM=10)
+#[derive(Debug, Clone, Deserialize)]
+pub struct TransferLimitsConfig {
+    /// Hard upper bound for a single transfer amount
+    #[serde(default)]
+    pub max_amount: HashMap<String, Decimal>,
+    /// Transfers above this amount require an explicit `confirm_large=true`
+    #[serde(default)]
+    pub confirm_threshold: HashMap<String, Decimal>,
+    /// Transfers above this amount must be approved by a second user before execution
+    #[serde(default)]
+    pub approval_threshold: HashMap<String, Decimal>,
+    /// Transfers per wallet allowed in the build/broadcast phase at once
+    #[serde(default = "default_max_concurrent_per_wallet")]
+    pub max_concurrent_per_wallet: usize,
+    /// Wait for a free slot instead of rejecting with "transfer in progress"
+    #[serde(default)]
+    pub queue_when_busy: bool,
+    /// Reject a transfer identical to one created this many seconds ago (0 = off)
+    #[serde(default)]
+    pub duplicate_window_secs: u64,
+    /// How long an `Idempotency-Key` keeps returning the transfer it created
+    #[serde(default = "default_idempotency_window_secs")]
+    pub idempotency_window_secs: u64,
+    /// Only send to addresses in the address book
+    #[serde(default)]
+    pub whitelist_only: bool,
+}
+
+fn default_max_concurrent_per_wallet() -> usize {
+    1
+}
+
+fn default_idempotency_window_secs() -> u64 {
+    24 * 3600
+}
+
+impl Default for TransferLimitsConfig {
+    fn default() -> Self {
+        Self {
+            max_amount: HashMap::new(),
+            confirm_threshold: HashMap::new(),
+            approval_threshold: HashMap::new(),
+            max_concurrent_per_wallet: default_max_concurrent_per_wallet(),
+            queue_when_busy: false,
+            duplicate_window_secs: 0,
+            idempotency_window_secs: default_idempotency_window_secs(),
+            whitelist_only: false,
+        }
+    }
+}
+
+impl TransferLimitsConfig {
+    /// Reject transfers above the chain's maximum, or above the confirmation
+    /// threshold when the caller did not confirm the large amount
+    pub fn check(&self, chain: &str, amount: Decimal, confirm_large: bool) -> AppResult<()> {
+        if let Some(max) = self.max_amount.get(chain) {
+            if amount > *max {
+                return Err(AppError::ValidationError(format!(
+                    "Amount {} exceeds the maximum single transfer of {} on {}",
+                    amount, max, chain
+                )));
+            }
+        }
+
+        if let Some(threshold) = self.confirm_threshold.get(chain) {
+            if amount > *threshold && !confirm_large {
+                return Err(AppError::ValidationError(format!(
+                    "Amount {} exceeds the large-transfer threshold of {} on {}. \
+                     Resubmit with confirm_large=true to proceed",
+                    amount, threshold, chain
+                )));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Whether a transfer of `amount` needs a second user's approval
+    pub fn requires_approval(&self, chain: &str, amount: Decimal) -> bool {
+        self.approval_threshold
+            .get(chain)
+            .is_some_and(|threshold| amount > *threshold)
+    }
+}
+
+/// Per-client request limits (token bucket refilled over `window_secs`)
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct RateLimitConfig {
+    pub enabled: bool,
+    pub window_secs: u64,
+    /// Requests per window for regular endpoints, per IP and per user
+    pub requests_per_window: u32,
+    /// Requests per window for `/auth/login`, per IP
+    pub login_requests_per_window: u32,
+}
+
+impl Default for RateLimitConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            window_secs: 60,
+            requests_per_window: 120,
+            login_requests_per_window: 5,
+        }
+    }
+}
+
+/// Address balances served from memory instead of the chain for a short while
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct BalanceCacheConfig {
+    /// Seconds a fetched balance is reused (0 disables the cache)
+    pub ttl_secs: u64,
+}
+
+impl Default for BalanceCacheConfig {
+    fn default() -> Self {
+        Self { ttl_secs: 15 }
+    }
+}
+
+/// Source of fiat prices for transfer fees
+/// (e.g. WEB3_PRICE__URL=https://api.coinbase.com/v2/prices/{symbol}-{currency}/spot)
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct PriceConfig {
+    /// `{symbol}` (e.g. ETH) and `{currency}` are filled in; fiat fees are left out when unset
+    pub url: Option<String>,
+    /// JSON pointer to the price in the response
+    pub price_pointer: String,
+    pub currency: String,
+    pub cache_secs: u64,
+    pub timeout_secs: u64,
+}
+
+impl Default for PriceConfig {
+    fn default() -> Self {
+        Self {
+            url: None,
+            price_pointer: "/data/amount".to_string(),
+            currency: "USD".to_string(),
+            cache_secs: 300,
+            timeout_secs: 5,
+        }
+    }
+}
+
+/// Callback POSTed on transfer status changes
+/// (e.g. WEB3_WEBHOOK__URL=https://example.com/hooks/transfers)
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct WebhookConfig {
+    /// Disabled when unset
+    pub url: Option<String>,
+    /// HMAC-SHA256 key for the `X-Webhook-Signature` header
+    pub secret: String,
+    /// Deliveries tried before the event goes to the dead-letter table
+    pub max_attempts: u32,
+    pub timeout_secs: u64,
+}
+
+impl Default for WebhookConfig {
+    fn default() -> Self {
+        Self {
+            url: None,
+            secret: String::new(),
+            max_attempts: 5,
+            timeout_secs: 10,
+        }
+    }
+}
+
+/// Log line format
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LogFormat {
+    /// Human-readable lines
+    #[default]
+    Text,
+    /// One JSON object per event, with the request span's fields, for Loki/ELK
+    Json,
+}
+
+/// Log output (e.g. WEB3_LOG__FORMAT=json, or WEB3_LOG__FILE_FORMAT=json for the file only)
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct LogConfig {
+    /// Format of both outputs unless overridden below
+    pub format: LogFormat,
+    pub console_format: Option<LogFormat>,
+    pub file_format: Option<LogFormat>,
+}
+
+impl LogConfig {
+    pub fn console(&self) -> LogFormat {
+        self.console_format.unwrap_or(self.format)
+    }
+
+    pub fn file(&self) -> LogFormat {
+        self.file_format.unwrap_or(self.format)
+    }
 }
 
 #[derive(Debug, Clone, Deserialize)]
@@ -66,6 +503,21 @@ pub struct AppConfig {
     pub security: SecurityConfig,
     pub ethereum: EthereumConfig,
     pub zcash: ZcashConfig,
+    /// Extra EVM chains beyond `ethereum`, keyed by the chain name wallets and transfers use
+    #[serde(default)]
+    pub evm_chains: HashMap<String, EvmChainConfig>,
+    #[serde(default)]
+    pub transfer_limits: TransferLimitsConfig,
+    #[serde(default)]
+    pub rate_limit: RateLimitConfig,
+    #[serde(default)]
+    pub balance_cache: BalanceCacheConfig,
+    #[serde(default)]
+    pub price: PriceConfig,
+    #[serde(default)]
+    pub webhook: WebhookConfig,
+    #[serde(default)]
+    pub log: LogConfig,
 }
 
 impl AppConfig {
@@ -85,10 +537,10 @@ impl AppConfig {
             .set_default("database.name", "web3_wallet")?
             .set_default("database.max_connections", 20)?
             // JWT defaults
-            .set_default("jwt.secret", "change-me-in-production-please!")?
+            .set_default("jwt.secret", INSECURE_JWT_SECRETS[0])?
             .set_default("jwt.expire_hours", 24)?
             // Security defaults
-            .set_default("security.encryption_key", "32-byte-encryption-key-here!!!!!")?
+            .set_default("security.encryption_key", INSECURE_ENCRYPTION_KEYS[0])?
             // Ethereum defaults
             .set_default("ethereum.chain_id", 1)?
             .set_default("ethereum.rpc_url", "https://eth.llamarpc.com")?
@@ -102,12 +554,25 @@ impl AppConfig {
             )?
             // RPC proxy (optional) - can be set via WEB3_ETHEREUM__RPC_PROXY env var
             .set_default("ethereum.rpc_proxy", Option::<String>::None)?
+            .set_default("ethereum.confirmations", DEFAULT_EVM_CONFIRMATIONS)?
+            .set_default("ethereum.pending_timeout_minutes", DEFAULT_EVM_PENDING_TIMEOUT_MINUTES)?
             // Zcash defaults
             .set_default("zcash.rpc_url", "http://127.0.0.1:8232")?
             .set_default("zcash.fallback_rpcs", Vec::<String>::new())?
             .set_default("zcash.rpc_proxy", Option::<String>::None)?
             .set_default("zcash.rpc_user", Option::<String>::None)?
             .set_default("zcash.rpc_password", Option::<String>::None)?
+            .set_default("zcash.verify_witnesses", true)?
+            .set_default("zcash.min_confirmations", constants::DEFAULT_MIN_CONFIRMATIONS as i64)?
+            .set_default("zcash.anchor_offset", constants::DEFAULT_ANCHOR_OFFSET as i64)?
+            .set_default("zcash.rpc_timeout_secs", constants::DEFAULT_RPC_TIMEOUT_SECS as i64)?
+            .set_default("zcash.tip_rpc_timeout_secs", constants::DEFAULT_TIP_RPC_TIMEOUT_SECS as i64)?
+            .set_default("zcash.sync_batch_size", constants::DEFAULT_SYNC_BATCH_SIZE as i64)?
+            .set_default("zcash.sync_parallel_fetches", constants::DEFAULT_SYNC_PARALLEL_FETCHES as i64)?
+            .set_default("zcash.require_memo", false)?
+            .set_default("zcash.auto_register_wallets", true)?
+            .set_default("zcash.lightwalletd_url", Option::<String>::None)?
+            .set_default("zcash.deposit_poll_secs", 60)?
             // Load from config.toml if exists
             .add_source(File::with_name("config").required(false))
             // Override with environment variables (prefix: WEB3_)
@@ -116,7 +581,9 @@ impl AppConfig {
                 Environment::with_prefix("WEB3")
                     .prefix_separator("_")
                     .separator("__")
-                    .try_parsing(true),
+                    .try_parsing(true)
+                    .list_separator(",")
+                    .with_list_parse_key("server.cors_allowed_origins"),
             )
             .build()?;
 
@@ -135,6 +602,22 @@ impl AppConfig {
                 "Encryption key must be exactly 32 bytes".to_string(),
             ));
         }
+        if let Some(previous) = &self.security.previous_encryption_key {
+            if previous.len() != 32 {
+                return Err(ConfigError::Message(
+                    "Previous encryption key must be exactly 32 bytes".to_string(),
+                ));
+            }
+        }
+
+        self.security.validate(&self.jwt)?;
+
+        if self.server.json_limit_bytes == 0 {
+            return Err(ConfigError::Message(
+                "server.json_limit_bytes must be greater than 0".to_string(),
+            ));
+        }
+        self.server.validate_cors()?;
 
         // Validate JWT secret is not empty
         if self.jwt.secret.is_empty() {
@@ -142,6 +625,13 @@ impl AppConfig {
                 "JWT secret cannot be empty".to_string(),
             ));
         }
+        if self.jwt.refresh_tokens
+            && (self.jwt.access_token_minutes == 0 || self.jwt.refresh_token_days == 0)
+        {
+            return Err(ConfigError::Message(
+                "Access and refresh token lifetimes must be greater than 0".to_string(),
+            ));
+        }
 
         // Validate database config
         if self.database.host.is_empty() {
@@ -155,6 +645,128 @@ impl AppConfig {
             ));
         }
 
+        // Unsigned callbacks could be forged by anyone who learns the URL
+        if self.webhook.url.is_some() && self.webhook.secret.is_empty() {
+            return Err(ConfigError::Message(
+                "Webhook secret is required when a webhook URL is set".to_string(),
+            ));
+        }
+        if self.webhook.max_attempts == 0 {
+            return Err(ConfigError::Message(
+                "Webhook max_attempts must be at least 1".to_string(),
+            ));
+        }
+
+        if self.ethereum.confirmations == 0 {
+            return Err(ConfigError::Message(
+                "ethereum.confirmations must be at least 1".to_string(),
+            ));
+        }
+        for (key, chain) in &self.evm_chains {
+            chain.validate(key)?;
+        }
+
+        self.zcash.validate()
+    }
+}
+
+impl EvmChainConfig {
+    fn validate(&self, key: &str) -> Result<(), ConfigError> {
+        if key == "ethereum" || key == "zcash" {
+            return Err(ConfigError::Message(format!(
+                "evm_chains.{} clashes with a built-in chain",
+                key
+            )));
+        }
+        if self.chain_id == 0 || self.rpc_url.is_empty() || self.native_symbol.is_empty() {
+            return Err(ConfigError::Message(format!(
+                "evm_chains.{} needs chain_id, rpc_url and native_symbol",
+                key
+            )));
+        }
+        if self.confirmations == 0 {
+            return Err(ConfigError::Message(format!(
+                "evm_chains.{}.confirmations must be at least 1",
+                key
+            )));
+        }
+        Ok(())
+    }
+}
+
+impl ZcashConfig {
+    fn validate(&self) -> Result<(), ConfigError> {
+        if self.anchor_offset < 1 {
+            return Err(ConfigError::Message(
+                "Zcash anchor_offset must be at least 1".to_string(),
+            ));
+        }
+
+        if self.rpc_timeout_secs == 0 || self.tip_rpc_timeout_secs == 0 {
+            return Err(ConfigError::Message(
+                "Zcash RPC timeouts must be at least 1 second".to_string(),
+            ));
+        }
+
+        if !(1..=constants::MAX_SYNC_BATCH_SIZE).contains(&self.sync_batch_size) {
+            return Err(ConfigError::Message(format!(
+                "Zcash sync_batch_size must be between 1 and {}",
+                constants::MAX_SYNC_BATCH_SIZE
+            )));
+        }
+
+        if !(1..=constants::MAX_SYNC_PARALLEL_FETCHES).contains(&self.sync_parallel_fetches) {
+            return Err(ConfigError::Message(format!(
+                "Zcash sync_parallel_fetches must be between 1 and {}",
+                constants::MAX_SYNC_PARALLEL_FETCHES
+            )));
+        }
+
+        if self.sync_backend == SyncBackend::Lightwalletd {
+            let url = self.lightwalletd_url.as_deref().unwrap_or_default();
+            if !url.starts_with("http://") && !url.starts_with("https://") {
+                return Err(ConfigError::Message(
+                    "zcash.lightwalletd_url must be an http:// or https:// URL when sync_backend is lightwalletd".to_string(),
+                ));
+            }
+        }
+
+        // Orchard sync builds its own clients; catch a bad proxy before the first sync
+        if let Some(proxy) = self.rpc_proxy.as_deref().filter(|p| !p.is_empty()) {
+            reqwest::Proxy::all(proxy).map_err(|e| {
+                ConfigError::Message(format!("Invalid zcash.rpc_proxy '{}': {}", proxy, e))
+            })?;
+        }
+
+        Ok(())
+    }
+
+    /// Warn about settings that are allowed but unsafe; called once logging is up
+    pub fn warn_unsafe_settings(&self) {
+        if self.min_confirmations < constants::SAFE_MIN_CONFIRMATIONS {
+            tracing::warn!(
+                "Zcash min_confirmations={} is below the safe minimum of {}; received notes may be spent before they are final",
+                self.min_confirmations,
+                constants::SAFE_MIN_CONFIRMATIONS
+            );
+        }
+    }
+
+    /// Enforce `require_memo` on a shielded send
+    ///
+    /// Sends to a transparent address cannot carry a memo, so they are always allowed.
+    pub fn check_memo(&self, to_transparent: bool, memo: Option<&str>) -> AppResult<()> {
+        if !self.require_memo || to_transparent {
+            return Ok(());
+        }
+
+        let has_memo = memo.is_some_and(|m| !m.trim().is_empty());
+        if !has_memo {
+            return Err(AppError::ValidationError(
+                "A memo is required for shielded transfers".to_string(),
+            ));
+        }
+
         Ok(())
     }
 }
@@ -165,6 +777,12 @@ impl Default for AppConfig {
             server: ServerConfig {
                 host: "127.0.0.1".to_string(),
                 port: 8080,
+                trusted_proxies: 0,
+                json_limit_bytes: default_json_limit_bytes(),
+                cors_allowed_origins: default_cors_allowed_origins(),
+                cors_allow_any_origin: false,
+                cors_allow_credentials: false,
+                cors_max_age_secs: default_cors_max_age_secs(),
             },
             database: DatabaseConfig {
                 host: "localhost".to_string(),
@@ -175,11 +793,18 @@ impl Default for AppConfig {
                 max_connections: 20,
             },
             jwt: JwtConfig {
-                secret: "change-me-in-production".to_string(),
+                secret: INSECURE_JWT_SECRETS[1].to_string(),
                 expire_hours: 24,
+                refresh_tokens: false,
+                access_token_minutes: default_access_token_minutes(),
+                refresh_token_days: default_refresh_token_days(),
             },
             security: SecurityConfig {
-                encryption_key: "32-byte-encryption-key-here!!!!!".to_string(),
+                encryption_key: INSECURE_ENCRYPTION_KEYS[0].to_string(),
+                previous_encryption_key: None,
+                admin_password: default_admin_password(),
+                // The default config carries the published secrets and is only fit for tests
+                allow_insecure_defaults: true,
             },
             ethereum: EthereumConfig {
                 rpc_url: "https://eth.llamarpc.com".to_string(),
@@ -190,14 +815,245 @@ impl Default for AppConfig {
                     "https://1rpc.io/eth".to_string(),
                 ],
                 rpc_proxy: None,
+                confirmations: DEFAULT_EVM_CONFIRMATIONS,
+                pending_timeout_minutes: DEFAULT_EVM_PENDING_TIMEOUT_MINUTES,
             },
             zcash: ZcashConfig {
+                network: NetworkType::Mainnet,
                 rpc_url: "http://127.0.0.1:8232".to_string(),
                 fallback_rpcs: vec![],
                 rpc_proxy: None,
                 rpc_user: None,
                 rpc_password: None,
+                verify_witnesses: true,
+                min_confirmations: constants::DEFAULT_MIN_CONFIRMATIONS,
+                anchor_offset: constants::DEFAULT_ANCHOR_OFFSET,
+                rpc_timeout_secs: constants::DEFAULT_RPC_TIMEOUT_SECS,
+                tip_rpc_timeout_secs: constants::DEFAULT_TIP_RPC_TIMEOUT_SECS,
+                sync_batch_size: constants::DEFAULT_SYNC_BATCH_SIZE,
+                sync_parallel_fetches: constants::DEFAULT_SYNC_PARALLEL_FETCHES,
+                require_memo: false,
+                auto_register_wallets: true,
+                sync_backend: SyncBackend::ZebraRpc,
+                lightwalletd_url: None,
+                deposit_poll_secs: 60,
             },
+            evm_chains: HashMap::new(),
+            transfer_limits: TransferLimitsConfig::default(),
+            rate_limit: RateLimitConfig::default(),
+            balance_cache: BalanceCacheConfig::default(),
+            price: PriceConfig::default(),
+            webhook: WebhookConfig::default(),
+            log: LogConfig::default(),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    fn limits() -> TransferLimitsConfig {
+        let mut limits = TransferLimitsConfig::default();
+        limits.max_amount.insert("ethereum".to_string(), Decimal::from(100));
+        limits.confirm_threshold.insert("ethereum".to_string(), Decimal::from(10));
+        limits
+    }
+
+    #[test]
+    fn test_large_transfer_requires_confirmation() {
+        let limits = limits();
+        let amount = Decimal::from_str("25.5").unwrap();
+
+        assert!(limits.check("ethereum", amount, false).is_err());
+        assert!(limits.check("ethereum", amount, true).is_ok());
+    }
+
+    #[test]
+    fn test_transfer_below_threshold_passes() {
+        let limits = limits();
+        assert!(limits.check("ethereum", Decimal::from(10), false).is_ok());
+        assert!(limits.check("ethereum", Decimal::from_str("0.5").unwrap(), false).is_ok());
+    }
+
+    #[test]
+    fn test_max_amount_rejected_even_when_confirmed() {
+        let limits = limits();
+        assert!(limits.check("ethereum", Decimal::from(101), true).is_err());
+    }
+
+    #[test]
+    fn test_unconfigured_chain_is_unlimited() {
+        let limits = limits();
+        assert!(limits.check("zcash", Decimal::from(1_000_000), false).is_ok());
+        assert!(!limits.requires_approval("zcash", Decimal::from(1_000_000)));
+    }
+
+    #[test]
+    fn test_approval_threshold() {
+        let mut limits = limits();
+        limits.approval_threshold.insert("ethereum".to_string(), Decimal::from(5));
+
+        assert!(!limits.requires_approval("ethereum", Decimal::from(5)));
+        assert!(limits.requires_approval("ethereum", Decimal::from_str("5.01").unwrap()));
+    }
+
+    #[test]
+    fn test_log_format_overrides() {
+        let log: LogConfig = serde_json::from_str(r#"{"format": "json", "console_format": "text"}"#).unwrap();
+        assert_eq!(log.console(), LogFormat::Text);
+        assert_eq!(log.file(), LogFormat::Json);
+
+        assert_eq!(LogConfig::default().file(), LogFormat::Text);
+    }
+
+    #[test]
+    fn test_cors_validation() {
+        let mut server = AppConfig::default().server;
+        assert!(server.validate_cors().is_ok());
+
+        server.cors_allowed_origins = vec!["https://wallet.example.com".to_string()];
+        server.cors_allow_credentials = true;
+        assert!(server.validate_cors().is_ok());
+
+        // Credentials are never sent to any origin
+        server.cors_allow_any_origin = true;
+        assert!(server.validate_cors().is_err());
+
+        server.cors_allow_any_origin = false;
+        for origin in ["*", "https://wallet.example.com/", "wallet.example.com"] {
+            server.cors_allowed_origins = vec![origin.to_string()];
+            assert!(server.validate_cors().is_err(), "{}", origin);
+        }
+    }
+
+    #[test]
+    fn test_evm_chain_validation() {
+        let polygon = EvmChainConfig {
+            name: Some("Polygon PoS".to_string()),
+            chain_id: 137,
+            rpc_url: "https://polygon-rpc.com".to_string(),
+            fallback_rpcs: vec![],
+            rpc_proxy: None,
+            native_symbol: "MATIC".to_string(),
+            confirmations: DEFAULT_EVM_CONFIRMATIONS,
+            pending_timeout_minutes: DEFAULT_EVM_PENDING_TIMEOUT_MINUTES,
+        };
+        assert!(polygon.validate("polygon").is_ok());
+
+        // Built-in chain names are reserved
+        assert!(polygon.validate("ethereum").is_err());
+
+        let mut missing_symbol = polygon.clone();
+        missing_symbol.native_symbol = String::new();
+        assert!(missing_symbol.validate("polygon").is_err());
+    }
+
+    #[test]
+    fn test_previous_encryption_key_still_decrypts() {
+        let old = "0123456789abcdef0123456789abcdef";
+        let mut security = AppConfig::default().security;
+        let data = crate::crypto::encrypt("secret", old).unwrap();
+        assert!(security.decrypt(&data).is_err());
+
+        security.previous_encryption_key = Some(old.to_string());
+        assert_eq!(security.decrypt(&data).unwrap(), "secret");
+        assert!(security.accepts_key(old));
+    }
+
+    #[test]
+    fn test_insecure_defaults_rejected() {
+        let mut config = AppConfig::default();
+        config.security.allow_insecure_defaults = false;
+        assert!(config.validate().is_err());
+
+        config.security.encryption_key = "0123456789abcdef0123456789abcdef".to_string();
+        config.jwt.secret = "a-secret-nobody-has-published".to_string();
+        assert!(config.validate().is_err());
+
+        config.security.admin_password = "correct horse battery staple".to_string();
+        assert!(config.validate().is_ok());
+    }
+
+    #[test]
+    fn test_refresh_token_lifetimes_validated() {
+        let mut config = AppConfig::default();
+        config.jwt.refresh_tokens = true;
+        assert!(config.validate().is_ok());
+
+        config.jwt.access_token_minutes = 0;
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn test_zcash_depth_validation() {
+        let mut zcash = AppConfig::default().zcash;
+        assert!(zcash.validate().is_ok());
+
+        // Low confirmations only warn
+        zcash.min_confirmations = 1;
+        assert!(zcash.validate().is_ok());
+
+        zcash.anchor_offset = 0;
+        assert!(zcash.validate().is_err());
+    }
+
+    #[test]
+    fn test_zcash_proxy_validation() {
+        let mut zcash = AppConfig::default().zcash;
+        zcash.rpc_proxy = Some("socks5://127.0.0.1:9050".to_string());
+        assert!(zcash.validate().is_ok());
+
+        zcash.rpc_proxy = Some("not a proxy".to_string());
+        assert!(zcash.validate().is_err());
+    }
+
+    #[test]
+    fn test_zcash_sync_throughput_bounds() {
+        let mut zcash = AppConfig::default().zcash;
+
+        // Rate-limited public node
+        zcash.sync_batch_size = 50;
+        zcash.sync_parallel_fetches = 2;
+        assert!(zcash.validate().is_ok());
+
+        // Local node
+        zcash.sync_batch_size = 5_000;
+        zcash.sync_parallel_fetches = 100;
+        assert!(zcash.validate().is_ok());
+
+        zcash.sync_parallel_fetches = 0;
+        assert!(zcash.validate().is_err());
+        zcash.sync_parallel_fetches = 101;
+        assert!(zcash.validate().is_err());
+
+        zcash.sync_parallel_fetches = 25;
+        zcash.sync_batch_size = 0;
+        assert!(zcash.validate().is_err());
+    }
+
+    #[test]
+    fn test_lightwalletd_backend_requires_url() {
+        let mut zcash = AppConfig::default().zcash;
+        zcash.sync_backend = SyncBackend::Lightwalletd;
+        assert!(zcash.validate().is_err());
+
+        zcash.lightwalletd_url = Some("https://zec.rocks:443".to_string());
+        assert!(zcash.validate().is_ok());
+    }
+
+    #[test]
+    fn test_require_memo() {
+        let mut zcash = AppConfig::default().zcash;
+        assert!(zcash.check_memo(false, None).is_ok());
+
+        zcash.require_memo = true;
+        assert!(matches!(zcash.check_memo(false, None), Err(AppError::ValidationError(_))));
+        assert!(zcash.check_memo(false, Some("  ")).is_err());
+        assert!(zcash.check_memo(false, Some("INV-2024-001")).is_ok());
+
+        // Deshielding cannot carry a memo
+        assert!(zcash.check_memo(true, None).is_ok());
+    }
+}
@@ -9,8 +9,13 @@ use crate::error::{AppError, AppResult};
 
 const NONCE_SIZE: usize = 12;
 
+/// Leading byte of the current format: version || nonce || ciphertext+tag
+///
+/// Data written before the version byte existed is just nonce || ciphertext+tag.
+const FORMAT_V1: u8 = 1;
+
 /// Encrypts data using AES-256-GCM
-/// Returns base64 encoded string with nonce prepended
+/// Returns base64 encoded string with version byte and nonce prepended
 pub fn encrypt(data: &str, key: &str) -> AppResult<String> {
     if key.len() != 32 {
         return Err(AppError::EncryptionError(
@@ -31,15 +36,18 @@ pub fn encrypt(data: &str, key: &str) -> AppResult<String> {
         .encrypt(nonce, data.as_bytes())
         .map_err(|e| AppError::EncryptionError(format!("Encryption failed: {}", e)))?;
 
-    // Prepend nonce to ciphertext and encode as base64
-    let mut result = nonce_bytes.to_vec();
+    // Prepend version and nonce to ciphertext and encode as base64
+    let mut result = vec![FORMAT_V1];
+    result.extend(nonce_bytes);
     result.extend(ciphertext);
 
     Ok(STANDARD.encode(result))
 }
 
 /// Decrypts AES-256-GCM encrypted data
-/// Expects base64 encoded string with nonce prepended
+/// Expects base64 encoded string with nonce prepended, with or without the version byte
+///
+/// The GCM tag is verified, so corrupted or tampered data is an error rather than garbage.
 pub fn decrypt(encrypted_data: &str, key: &str) -> AppResult<String> {
     if key.len() != 32 {
         return Err(AppError::EncryptionError(
@@ -61,26 +69,40 @@ pub fn decrypt(encrypted_data: &str, key: &str) -> AppResult<String> {
         ));
     }
 
-    // Extract nonce and ciphertext
-    let (nonce_bytes, ciphertext) = data.split_at(NONCE_SIZE);
-    let nonce = Nonce::from_slice(nonce_bytes);
-
-    // Decrypt the data
-    let plaintext = cipher
-        .decrypt(nonce, ciphertext)
-        .map_err(|e| AppError::EncryptionError(format!("Decryption failed: {}", e)))?;
+    // A legacy nonce can also start with the version byte; the tag tells the formats apart
+    let plaintext = match data.split_first() {
+        Some((&FORMAT_V1, versioned)) => decrypt_payload(&cipher, versioned)
+            .or_else(|| decrypt_payload(&cipher, &data)),
+        _ => decrypt_payload(&cipher, &data),
+    }
+    .ok_or_else(|| {
+        AppError::EncryptionError(
+            "Decryption failed: data is corrupted or the key is wrong".to_string(),
+        )
+    })?;
 
     String::from_utf8(plaintext)
         .map_err(|e| AppError::EncryptionError(format!("UTF-8 decode failed: {}", e)))
 }
 
+/// Decrypt nonce || ciphertext+tag, `None` if it does not authenticate
+fn decrypt_payload(cipher: &Aes256Gcm, payload: &[u8]) -> Option<Vec<u8>> {
+    if payload.len() < NONCE_SIZE {
+        return None;
+    }
+
+    // Extract nonce and ciphertext
+    let (nonce_bytes, ciphertext) = payload.split_at(NONCE_SIZE);
+    cipher.decrypt(Nonce::from_slice(nonce_bytes), ciphertext).ok()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
     #[test]
     fn test_encrypt_decrypt() {
-        let key = "32-byte-encryption-key-here!!!!";
+        let key = "32-byte-encryption-key-here!!!!!";
         let data = "0x1234567890abcdef1234567890abcdef1234567890abcdef1234567890abcdef";
 
         let encrypted = encrypt(data, key).unwrap();
@@ -91,7 +113,7 @@ mod tests {
 
     #[test]
     fn test_different_encryptions() {
-        let key = "32-byte-encryption-key-here!!!!";
+        let key = "32-byte-encryption-key-here!!!!!";
         let data = "test data";
 
         let encrypted1 = encrypt(data, key).unwrap();
@@ -105,6 +127,38 @@ mod tests {
         assert_eq!(decrypt(&encrypted2, key).unwrap(), data);
     }
 
+    #[test]
+    fn test_legacy_format_still_decrypts() {
+        let key = "32-byte-encryption-key-here!!!!!";
+        let cipher = Aes256Gcm::new_from_slice(key.as_bytes()).unwrap();
+
+        // Unversioned nonce || ciphertext, including a nonce that starts with the version byte
+        for first_byte in [0u8, FORMAT_V1] {
+            let nonce_bytes = [first_byte; NONCE_SIZE];
+            let mut legacy = nonce_bytes.to_vec();
+            legacy.extend(cipher.encrypt(Nonce::from_slice(&nonce_bytes), b"old key".as_ref()).unwrap());
+
+            assert_eq!(decrypt(&STANDARD.encode(legacy), key).unwrap(), "old key");
+        }
+
+        let current = STANDARD.decode(encrypt("new key", key).unwrap()).unwrap();
+        assert_eq!(current[0], FORMAT_V1);
+    }
+
+    #[test]
+    fn test_tampering_is_detected() {
+        let key = "32-byte-encryption-key-here!!!!!";
+        let mut data = STANDARD.decode(encrypt("private key", key).unwrap()).unwrap();
+        let last = data.len() - 1;
+        data[last] ^= 0x01;
+
+        assert!(matches!(
+            decrypt(&STANDARD.encode(&data), key),
+            Err(AppError::EncryptionError(_))
+        ));
+        assert!(decrypt(&encrypt("private key", key).unwrap(), "fedcba9876543210fedcba9876543210").is_err());
+    }
+
     #[test]
     fn test_invalid_key_length() {
         let key = "short-key";
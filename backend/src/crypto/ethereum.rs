@@ -1,4 +1,6 @@
 use ethers::signers::{LocalWallet, Signer};
+use ethers::types::transaction::eip712::{Eip712, TypedData};
+use ethers::types::{H256, U256};
 use rand::thread_rng;
 
 use crate::error::{AppError, AppResult};
@@ -24,6 +26,170 @@ pub fn import_ethereum_wallet(private_key_hex: &str) -> AppResult<String> {
     Ok(format!("{:?}", wallet.address()))
 }
 
+/// Longest message accepted for `personal_sign`, in bytes
+pub const MAX_SIGN_MESSAGE_BYTES: usize = 16 * 1024;
+
+/// Sign `message` the way `personal_sign` does (EIP-191 version 0x45)
+///
+/// The message is prefixed with `"\x19Ethereum Signed Message:\n" + len` and hashed
+/// with keccak256. Returns the 65-byte `r || s || v` signature as 0x hex, v being 27 or 28.
+pub fn sign_personal_message(private_key_hex: &str, message: &[u8]) -> AppResult<String> {
+    if message.len() > MAX_SIGN_MESSAGE_BYTES {
+        return Err(AppError::ValidationError(format!(
+            "Message is {} bytes, the limit is {}",
+            message.len(),
+            MAX_SIGN_MESSAGE_BYTES
+        )));
+    }
+
+    let wallet = signing_wallet(private_key_hex)?;
+    let signature = wallet
+        .sign_hash(ethers::utils::hash_message(message))
+        .map_err(|e| AppError::InternalError(format!("Failed to sign message: {}", e)))?;
+    Ok(format!("0x{}", signature))
+}
+
+/// Parse an EIP-712 payload (`types`, `primaryType`, `domain`, `message`)
+///
+/// Accepts the object itself or the JSON string `eth_signTypedData_v4` passes around,
+/// and rejects type definitions that reference unknown or malformed types.
+pub fn parse_typed_data(value: serde_json::Value) -> AppResult<TypedData> {
+    let typed_data: TypedData = match value {
+        serde_json::Value::String(json) => serde_json::from_str(&json),
+        value => serde_json::from_value(value),
+    }
+    .map_err(|e| AppError::ValidationError(format!("Invalid EIP-712 payload: {}", e)))?;
+
+    validate_typed_data_types(&typed_data)?;
+    Ok(typed_data)
+}
+
+fn validate_typed_data_types(typed_data: &TypedData) -> AppResult<()> {
+    let invalid = |msg: String| Err(AppError::ValidationError(format!("Invalid EIP-712 types: {}", msg)));
+
+    if typed_data.primary_type == "EIP712Domain" || !typed_data.types.contains_key(&typed_data.primary_type) {
+        return invalid(format!("primary type '{}' is not defined", typed_data.primary_type));
+    }
+
+    for (name, fields) in typed_data.types.iter() {
+        if !is_identifier(name) {
+            return invalid(format!("'{}' is not a valid type name", name));
+        }
+        let mut seen = std::collections::HashSet::new();
+        for field in fields {
+            if !is_identifier(&field.name) || !seen.insert(field.name.as_str()) {
+                return invalid(format!("{} has an invalid or duplicate field '{}'", name, field.name));
+            }
+            let base = strip_array_suffixes(&field.r#type)
+                .ok_or_else(|| AppError::ValidationError(format!(
+                    "Invalid EIP-712 types: {}.{} has malformed type '{}'",
+                    name, field.name, field.r#type
+                )))?;
+            if !is_atomic_type(base) && !typed_data.types.contains_key(base) {
+                return invalid(format!("{}.{} uses undefined type '{}'", name, field.name, base));
+            }
+        }
+    }
+    Ok(())
+}
+
+fn is_identifier(name: &str) -> bool {
+    let mut chars = name.chars();
+    matches!(chars.next(), Some(c) if c.is_ascii_alphabetic() || c == '_' || c == '$')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '$')
+}
+
+/// `Foo[2][]` -> `Foo`; None if a suffix is not `[]` or `[n]`
+fn strip_array_suffixes(mut ty: &str) -> Option<&str> {
+    while let Some(open) = ty.rfind('[') {
+        let len = ty[open..].strip_prefix('[')?.strip_suffix(']')?;
+        if !len.is_empty() && len.parse::<usize>().ok().filter(|n| *n > 0).is_none() {
+            return None;
+        }
+        ty = &ty[..open];
+    }
+    Some(ty)
+}
+
+fn is_atomic_type(ty: &str) -> bool {
+    let sized = |digits: &str, valid: fn(usize) -> bool| {
+        !digits.starts_with('0')
+            && digits.bytes().all(|b| b.is_ascii_digit())
+            && digits.parse::<usize>().is_ok_and(valid)
+    };
+    match ty {
+        "address" | "bool" | "string" | "bytes" => true,
+        _ => {
+            if let Some(n) = ty.strip_prefix("bytes") {
+                sized(n, |n| (1..=32).contains(&n))
+            } else if let Some(n) = ty.strip_prefix("uint").or_else(|| ty.strip_prefix("int")) {
+                sized(n, |n| n % 8 == 0 && (8..=256).contains(&n))
+            } else {
+                false
+            }
+        }
+    }
+}
+
+/// Sign the EIP-712 digest `keccak256(0x1901 || domainSeparator || hashStruct(message))`
+///
+/// Returns the 0x hex signature and the digest that was signed.
+pub fn sign_typed_data(private_key_hex: &str, typed_data: &TypedData) -> AppResult<(String, String)> {
+    let digest = typed_data
+        .encode_eip712()
+        .map_err(|e| AppError::ValidationError(format!("Cannot encode EIP-712 payload: {}", e)))?;
+
+    let wallet = signing_wallet(private_key_hex)?;
+    let signature = wallet
+        .sign_hash(H256::from(digest))
+        .map_err(|e| AppError::InternalError(format!("Failed to sign typed data: {}", e)))?;
+    Ok((format!("0x{}", signature), format!("0x{}", hex::encode(digest))))
+}
+
+/// Token contract and raw amount of the allowance a permit signature grants
+///
+/// Recognizes EIP-2612 `Permit` (DAI's `allowed: true` being unlimited) and Permit2's
+/// `PermitSingle` and `PermitTransferFrom`. `None` for any other payload.
+pub fn permit_allowance(typed_data: &TypedData) -> Option<(String, U256)> {
+    let message = &typed_data.message;
+    let (token, amount) = match typed_data.primary_type.as_str() {
+        "Permit" => {
+            let token = format!("{:?}", typed_data.domain.verifying_contract?);
+            match message.get("allowed") {
+                Some(serde_json::Value::Bool(true)) => return Some((token, U256::MAX)),
+                Some(serde_json::Value::Bool(false)) => return Some((token, U256::zero())),
+                _ => (token, message.get("value")?),
+            }
+        }
+        "PermitSingle" => {
+            let details = message.get("details")?;
+            (details.get("token")?.as_str()?.to_string(), details.get("amount")?)
+        }
+        "PermitTransferFrom" => {
+            let permitted = message.get("permitted")?;
+            (permitted.get("token")?.as_str()?.to_string(), permitted.get("amount")?)
+        }
+        _ => return None,
+    };
+
+    let amount = match amount {
+        serde_json::Value::Number(number) => U256::from_dec_str(&number.to_string()).ok()?,
+        serde_json::Value::String(text) => match text.strip_prefix("0x") {
+            Some(hex) => U256::from_str_radix(hex, 16).ok()?,
+            None => U256::from_dec_str(text).ok()?,
+        },
+        _ => return None,
+    };
+    Some((token, amount))
+}
+
+fn signing_wallet(private_key_hex: &str) -> AppResult<LocalWallet> {
+    let key_hex = private_key_hex.strip_prefix("0x").unwrap_or(private_key_hex);
+    key_hex
+        .parse()
+        .map_err(|e| AppError::EncryptionError(format!("Stored private key is invalid: {}", e)))
+}
+
 /// Validate an Ethereum address format
 #[allow(dead_code)]
 pub(crate) fn validate_ethereum_address(address: &str) -> bool {
@@ -81,6 +247,134 @@ mod tests {
         assert_eq!(original_address.to_lowercase(), imported_address.to_lowercase());
     }
 
+    #[test]
+    fn test_personal_sign_recovers_signer() {
+        let (address, private_key) = generate_ethereum_wallet().unwrap();
+        let signature = sign_personal_message(&private_key, b"hello").unwrap();
+
+        assert!(signature.starts_with("0x"));
+        assert_eq!(signature.len(), 2 + 130);
+        let signature: ethers::types::Signature = signature.parse().unwrap();
+        assert!(signature.v == 27 || signature.v == 28);
+        assert_eq!(format!("{:?}", signature.recover("hello").unwrap()), address);
+
+        let too_long = vec![b'a'; MAX_SIGN_MESSAGE_BYTES + 1];
+        assert!(sign_personal_message(&private_key, &too_long).is_err());
+    }
+
+    fn mail_typed_data() -> serde_json::Value {
+        serde_json::json!({
+            "types": {
+                "EIP712Domain": [
+                    { "name": "name", "type": "string" },
+                    { "name": "version", "type": "string" },
+                    { "name": "chainId", "type": "uint256" },
+                    { "name": "verifyingContract", "type": "address" }
+                ],
+                "Person": [
+                    { "name": "name", "type": "string" },
+                    { "name": "wallet", "type": "address" }
+                ],
+                "Mail": [
+                    { "name": "from", "type": "Person" },
+                    { "name": "to", "type": "Person" },
+                    { "name": "contents", "type": "string" }
+                ]
+            },
+            "primaryType": "Mail",
+            "domain": {
+                "name": "Ether Mail",
+                "version": "1",
+                "chainId": 1,
+                "verifyingContract": "0xCcCCccccCCCCcCCCCCCcCcCccCcCCCcCcccccccC"
+            },
+            "message": {
+                "from": { "name": "Cow", "wallet": "0xCD2a3d9F938E13CD947Ec05AbC7FE734Df8DD826" },
+                "to": { "name": "Bob", "wallet": "0xbBbBBBBbbBBBbbbBbbBbbbbBBbBbbbbBbBbbBBbB" },
+                "contents": "Hello, Bob!"
+            }
+        })
+    }
+
+    #[test]
+    fn test_sign_typed_data_matches_eip712_example() {
+        // Key and expected values from the example in the EIP-712 specification
+        let private_key = "c85ef7d79691fe79573b1a7064c19c1a9819ebdbd1faaab1a8ec92344438aaf4";
+        let typed_data = parse_typed_data(mail_typed_data()).unwrap();
+        let (signature, digest) = sign_typed_data(private_key, &typed_data).unwrap();
+
+        assert_eq!(digest, "0xbe609aee343fb3c4b28e1df9e632fca64fcfaede20f02e86244efddf30957bd2");
+        assert_eq!(
+            signature,
+            "0x4355c47d63924e8a72e509b65029052eb6c299d53a04e167c5775fd466751c9d\
+             07299936d304c153f6443dfa05f40ff007d72911b6f72307f996231605b915621c"
+        );
+
+        // The JSON string form is accepted too
+        let as_string = serde_json::Value::String(mail_typed_data().to_string());
+        assert!(parse_typed_data(as_string).is_ok());
+    }
+
+    #[test]
+    fn test_malformed_typed_data_rejected() {
+        let mut undefined = mail_typed_data();
+        undefined["types"]["Mail"][0]["type"] = "Persn".into();
+        assert!(parse_typed_data(undefined).is_err());
+
+        let mut bad_width = mail_typed_data();
+        bad_width["types"]["Person"][1]["type"] = "uint7".into();
+        assert!(parse_typed_data(bad_width).is_err());
+
+        let mut bad_array = mail_typed_data();
+        bad_array["types"]["Mail"][2]["type"] = "string[x]".into();
+        assert!(parse_typed_data(bad_array).is_err());
+
+        let mut no_primary = mail_typed_data();
+        no_primary["primaryType"] = "Letter".into();
+        assert!(parse_typed_data(no_primary).is_err());
+
+        let mut missing_message = mail_typed_data();
+        missing_message.as_object_mut().unwrap().remove("message");
+        assert!(parse_typed_data(missing_message).is_err());
+    }
+
+    #[test]
+    fn test_permit_allowance() {
+        let permit = serde_json::json!({
+            "types": {
+                "EIP712Domain": [
+                    { "name": "name", "type": "string" },
+                    { "name": "verifyingContract", "type": "address" }
+                ],
+                "Permit": [
+                    { "name": "owner", "type": "address" },
+                    { "name": "spender", "type": "address" },
+                    { "name": "value", "type": "uint256" },
+                    { "name": "nonce", "type": "uint256" },
+                    { "name": "deadline", "type": "uint256" }
+                ]
+            },
+            "primaryType": "Permit",
+            "domain": {
+                "name": "USD Coin",
+                "verifyingContract": "0xA0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48"
+            },
+            "message": {
+                "owner": "0xCD2a3d9F938E13CD947Ec05AbC7FE734Df8DD826",
+                "spender": "0xbBbBBBBbbBBBbbbBbbBbbbbBBbBbbbbBbBbbBBbB",
+                "value": "2500000",
+                "nonce": 0,
+                "deadline": 1700000000
+            }
+        });
+        let (token, amount) = permit_allowance(&parse_typed_data(permit).unwrap()).unwrap();
+        assert!(token.eq_ignore_ascii_case("0xA0b86991c6218b36c1d19D4a2e9Eb0cE3606eB48"));
+        assert_eq!(amount, U256::from(2_500_000u64));
+
+        // Not an allowance
+        assert!(permit_allowance(&parse_typed_data(mail_typed_data()).unwrap()).is_none());
+    }
+
     #[test]
     fn test_validate_ethereum_address() {
         // Valid addresses
@@ -0,0 +1,319 @@
+//! Web3 Secret Storage (v3 keystore) for Ethereum keys
+//!
+//! The format geth and MetaMask use for key files: a KDF (scrypt or PBKDF2-SHA256)
+//! stretches the passphrase into 32 bytes, the first 16 encrypt the key with
+//! AES-128-CTR and the last 16 authenticate the ciphertext with
+//! `keccak256(dk[16..32] || ciphertext)`.
+
+use aes::cipher::{KeyIvInit, StreamCipher};
+use ethers::utils::keccak256;
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use subtle::ConstantTimeEq;
+
+use super::ethereum::import_ethereum_wallet;
+use crate::error::{AppError, AppResult};
+
+type Aes128Ctr = ctr::Ctr128BE<aes::Aes128>;
+
+/// Shortest passphrase accepted when exporting a keystore
+pub const MIN_KEYSTORE_PASSPHRASE_LEN: usize = 8;
+
+/// scrypt cost used by geth's "standard" keystores (N = 2^18, r = 8, p = 1)
+pub const STANDARD_SCRYPT_LOG_N: u8 = 18;
+
+/// Largest KDF costs accepted on import, so a crafted file cannot pin the server
+///
+/// geth's standard parameters: scrypt needs 128 * r * N bytes, 256 MiB at this cost.
+const MAX_SCRYPT_LOG_N: u8 = STANDARD_SCRYPT_LOG_N;
+const MAX_SCRYPT_R: u32 = 8;
+const MAX_SCRYPT_P: u32 = 1;
+const MAX_PBKDF2_ROUNDS: u32 = 10_000_000;
+
+const DKLEN: usize = 32;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Keystore {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub address: Option<String>,
+    /// Older geth files spell this `Crypto`
+    #[serde(alias = "Crypto")]
+    pub crypto: KeystoreCrypto,
+    pub id: String,
+    pub version: u8,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KeystoreCrypto {
+    pub cipher: String,
+    pub cipherparams: CipherParams,
+    pub ciphertext: String,
+    /// `scrypt` or `pbkdf2`, naming the shape of `kdfparams`
+    pub kdf: String,
+    pub kdfparams: KdfParams,
+    pub mac: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CipherParams {
+    pub iv: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum KdfParams {
+    Scrypt {
+        dklen: usize,
+        n: u32,
+        r: u32,
+        p: u32,
+        salt: String,
+    },
+    Pbkdf2 {
+        dklen: usize,
+        c: u32,
+        prf: String,
+        salt: String,
+    },
+}
+
+impl KdfParams {
+    fn name(&self) -> &'static str {
+        match self {
+            KdfParams::Scrypt { .. } => "scrypt",
+            KdfParams::Pbkdf2 { .. } => "pbkdf2",
+        }
+    }
+
+    fn derive_key(&self, passphrase: &str) -> AppResult<[u8; DKLEN]> {
+        let invalid = |msg: &str| AppError::ValidationError(format!("Invalid keystore: {}", msg));
+        let mut key = [0u8; DKLEN];
+
+        match self {
+            KdfParams::Scrypt { dklen, n, r, p, salt } => {
+                if *dklen != DKLEN {
+                    return Err(invalid("dklen must be 32"));
+                }
+                if !n.is_power_of_two() || *n < 2 {
+                    return Err(invalid("scrypt n must be a power of two"));
+                }
+                let log_n = n.trailing_zeros() as u8;
+                if log_n > MAX_SCRYPT_LOG_N || *r > MAX_SCRYPT_R || *p > MAX_SCRYPT_P {
+                    return Err(invalid("scrypt parameters are too expensive"));
+                }
+                let salt = hex::decode(salt).map_err(|_| invalid("salt is not hex"))?;
+                let params = scrypt::Params::new(log_n, *r, *p, DKLEN)
+                    .map_err(|e| invalid(&format!("scrypt parameters: {}", e)))?;
+                scrypt::scrypt(passphrase.as_bytes(), &salt, &params, &mut key)
+                    .map_err(|e| AppError::EncryptionError(format!("scrypt failed: {}", e)))?;
+            }
+            KdfParams::Pbkdf2 { dklen, c, prf, salt } => {
+                if *dklen != DKLEN {
+                    return Err(invalid("dklen must be 32"));
+                }
+                if prf != "hmac-sha256" {
+                    return Err(invalid("only hmac-sha256 is supported for pbkdf2"));
+                }
+                if *c == 0 || *c > MAX_PBKDF2_ROUNDS {
+                    return Err(invalid("pbkdf2 round count is out of range"));
+                }
+                let salt = hex::decode(salt).map_err(|_| invalid("salt is not hex"))?;
+                pbkdf2::pbkdf2_hmac::<Sha256>(passphrase.as_bytes(), &salt, *c, &mut key);
+            }
+        }
+
+        Ok(key)
+    }
+}
+
+fn keystore_mac(derived_key: &[u8; DKLEN], ciphertext: &[u8]) -> [u8; 32] {
+    let mut input = derived_key[16..].to_vec();
+    input.extend_from_slice(ciphertext);
+    keccak256(input)
+}
+
+fn apply_aes_ctr(key: &[u8], iv: &[u8], data: &mut [u8]) -> AppResult<()> {
+    let mut cipher = Aes128Ctr::new_from_slices(key, iv)
+        .map_err(|_| AppError::ValidationError("Invalid keystore: iv must be 16 bytes".to_string()))?;
+    cipher.apply_keystream(data);
+    Ok(())
+}
+
+/// Decrypt a v3 keystore, returning the private key as hex (no 0x) and its address
+///
+/// A wrong passphrase shows up as a MAC mismatch and is reported as such.
+pub fn decrypt_keystore(keystore: &Keystore, passphrase: &str) -> AppResult<(String, String)> {
+    if keystore.version != 3 {
+        return Err(AppError::ValidationError(format!(
+            "Unsupported keystore version {}, expected 3",
+            keystore.version
+        )));
+    }
+    let crypto = &keystore.crypto;
+    if crypto.cipher != "aes-128-ctr" {
+        return Err(AppError::ValidationError(format!(
+            "Unsupported keystore cipher '{}', expected aes-128-ctr",
+            crypto.cipher
+        )));
+    }
+
+    let invalid = |field: &str| AppError::ValidationError(format!("Invalid keystore: {} is not hex", field));
+    let mut ciphertext = hex::decode(&crypto.ciphertext).map_err(|_| invalid("ciphertext"))?;
+    let iv = hex::decode(&crypto.cipherparams.iv).map_err(|_| invalid("iv"))?;
+    let mac = hex::decode(&crypto.mac).map_err(|_| invalid("mac"))?;
+
+    if crypto.kdf != crypto.kdfparams.name() {
+        return Err(AppError::ValidationError(format!(
+            "Invalid keystore: kdfparams do not match kdf '{}'",
+            crypto.kdf
+        )));
+    }
+    let derived_key = crypto.kdfparams.derive_key(passphrase)?;
+    if !bool::from(keystore_mac(&derived_key, &ciphertext)[..].ct_eq(mac.as_slice())) {
+        return Err(AppError::ValidationError(
+            "Keystore MAC mismatch: wrong passphrase or corrupted file".to_string(),
+        ));
+    }
+
+    apply_aes_ctr(&derived_key[..16], &iv, &mut ciphertext)?;
+    let private_key = hex::encode(&ciphertext);
+    let address = import_ethereum_wallet(&private_key)?;
+
+    if let Some(expected) = &keystore.address {
+        let expected = expected.strip_prefix("0x").unwrap_or(expected);
+        if !address[2..].eq_ignore_ascii_case(expected) {
+            return Err(AppError::ValidationError(format!(
+                "Keystore address 0x{} does not match its key ({})",
+                expected, address
+            )));
+        }
+    }
+
+    Ok((private_key, address))
+}
+
+/// Encrypt a private key into a v3 keystore using scrypt with cost `2^log_n`
+pub fn encrypt_keystore(private_key_hex: &str, passphrase: &str, log_n: u8) -> AppResult<Keystore> {
+    if passphrase.chars().count() < MIN_KEYSTORE_PASSPHRASE_LEN {
+        return Err(AppError::ValidationError(format!(
+            "Keystore passphrase must be at least {} characters",
+            MIN_KEYSTORE_PASSPHRASE_LEN
+        )));
+    }
+
+    let key_hex = private_key_hex.strip_prefix("0x").unwrap_or(private_key_hex);
+    let address = import_ethereum_wallet(key_hex)?;
+    let mut plaintext = hex::decode(key_hex)
+        .map_err(|e| AppError::EncryptionError(format!("Stored private key is not hex: {}", e)))?;
+
+    let mut rng = rand::thread_rng();
+    let mut salt = [0u8; 32];
+    let mut iv = [0u8; 16];
+    let mut id = [0u8; 16];
+    rng.fill_bytes(&mut salt);
+    rng.fill_bytes(&mut iv);
+    rng.fill_bytes(&mut id);
+
+    let kdfparams = KdfParams::Scrypt {
+        dklen: DKLEN,
+        n: 1 << log_n,
+        r: 8,
+        p: 1,
+        salt: hex::encode(salt),
+    };
+    let derived_key = kdfparams.derive_key(passphrase)?;
+    apply_aes_ctr(&derived_key[..16], &iv, &mut plaintext)?;
+    let mac = keystore_mac(&derived_key, &plaintext);
+
+    Ok(Keystore {
+        address: Some(address[2..].to_lowercase()),
+        crypto: KeystoreCrypto {
+            cipher: "aes-128-ctr".to_string(),
+            cipherparams: CipherParams { iv: hex::encode(iv) },
+            ciphertext: hex::encode(&plaintext),
+            kdf: kdfparams.name().to_string(),
+            kdfparams,
+            mac: hex::encode(mac),
+        },
+        id: format_uuid_v4(id),
+        version: 3,
+    })
+}
+
+fn format_uuid_v4(mut bytes: [u8; 16]) -> String {
+    bytes[6] = (bytes[6] & 0x0f) | 0x40;
+    bytes[8] = (bytes[8] & 0x3f) | 0x80;
+    let hex = hex::encode(bytes);
+    format!("{}-{}-{}-{}-{}", &hex[..8], &hex[8..12], &hex[12..16], &hex[16..20], &hex[20..])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decrypts_secret_storage_pbkdf2_vector() {
+        // PBKDF2 test vector from the Web3 Secret Storage definition
+        let keystore: Keystore = serde_json::from_value(serde_json::json!({
+            "crypto": {
+                "cipher": "aes-128-ctr",
+                "cipherparams": { "iv": "6087dab2f9fdbbfaddc31a909735c1e6" },
+                "ciphertext": "5318b4d5bcd28de64ee5559e671353e16f075ecae9f99c7a79a38af5f869aa46",
+                "kdf": "pbkdf2",
+                "kdfparams": {
+                    "c": 262144,
+                    "dklen": 32,
+                    "prf": "hmac-sha256",
+                    "salt": "ae3cd4e7013836a3df6bd7241b12db061dbe2c6785853cce422d148a624ce0bd"
+                },
+                "mac": "517ead924a9d0dc3124507e3393d175ce3ff7c1e96529c6c555ce9e51205e9b2"
+            },
+            "id": "3198bc9c-6672-5ab3-d995-4942343ae5b6",
+            "version": 3
+        }))
+        .unwrap();
+
+        let (private_key, _) = decrypt_keystore(&keystore, "testpassword").unwrap();
+        assert_eq!(private_key, "7a28b5ba57c53603b0b07b56bba752f7784bf506fa95edc395f5cf6c7514fe9d");
+
+        let err = decrypt_keystore(&keystore, "wrongpassword").unwrap_err();
+        assert!(err.to_string().contains("MAC mismatch"));
+    }
+
+    #[test]
+    fn test_keystore_round_trip() {
+        let (address, private_key) = super::super::generate_ethereum_wallet().unwrap();
+
+        // A cheap scrypt cost keeps the test fast; exports use STANDARD_SCRYPT_LOG_N
+        let keystore = encrypt_keystore(&private_key, "correct horse", 10).unwrap();
+        let json = serde_json::to_string(&keystore).unwrap();
+        assert!(json.contains("\"kdf\":\"scrypt\""));
+
+        let parsed: Keystore = serde_json::from_str(&json).unwrap();
+        let (decrypted, decrypted_address) = decrypt_keystore(&parsed, "correct horse").unwrap();
+        assert_eq!(decrypted, private_key);
+        assert_eq!(decrypted_address, address);
+
+        assert!(decrypt_keystore(&parsed, "wrong horse").is_err());
+        assert!(encrypt_keystore(&private_key, "short", 10).is_err());
+    }
+
+    #[test]
+    fn test_rejects_scrypt_costs_above_geth_standard() {
+        let (_, private_key) = super::super::generate_ethereum_wallet().unwrap();
+        let keystore = encrypt_keystore(&private_key, "correct horse", 10).unwrap();
+
+        for (n, r, p) in [(1u32 << 19, 8, 1), (1 << 10, 16, 1), (1 << 10, 8, 2)] {
+            let mut json = serde_json::to_value(&keystore).unwrap();
+            let params = &mut json["crypto"]["kdfparams"];
+            params["n"] = n.into();
+            params["r"] = r.into();
+            params["p"] = p.into();
+            let expensive: Keystore = serde_json::from_value(json).unwrap();
+
+            let err = decrypt_keystore(&expensive, "correct horse").unwrap_err();
+            assert!(err.to_string().contains("too expensive"), "n={} r={} p={}", n, r, p);
+        }
+    }
+}
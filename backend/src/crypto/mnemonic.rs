@@ -0,0 +1,165 @@
+use ethers::signers::coins_bip39::{English, Mnemonic};
+use ethers::signers::MnemonicBuilder;
+use rand::thread_rng;
+
+use crate::crypto::import_wallet_for_chain;
+use crate::error::{AppError, AppResult};
+
+/// Supported BIP39 phrase lengths
+const SUPPORTED_WORD_COUNTS: [usize; 2] = [12, 24];
+
+/// BIP44 derivation path for Ethereum (MetaMask compatible)
+fn ethereum_derivation_path(account_index: u32) -> String {
+    format!("m/44'/60'/0'/0/{}", account_index)
+}
+
+/// Derivation path for Zcash keys (ZIP-32 purpose / coin type)
+fn zcash_derivation_path(account_index: u32) -> String {
+    format!("m/32'/133'/{}'", account_index)
+}
+
+/// Generate a new BIP39 mnemonic and derive the first account for the given chain
+/// Returns (address, private_key_hex, phrase)
+pub fn generate_mnemonic_wallet(chain: &str, word_count: usize) -> AppResult<(String, String, String)> {
+    if !SUPPORTED_WORD_COUNTS.contains(&word_count) {
+        return Err(AppError::ValidationError(format!(
+            "Unsupported mnemonic word count: {} (expected 12 or 24)",
+            word_count
+        )));
+    }
+
+    let mnemonic = Mnemonic::<English>::new_with_count(&mut thread_rng(), word_count)
+        .map_err(|e| AppError::InternalError(format!("Failed to generate mnemonic: {}", e)))?;
+    let phrase = mnemonic.to_phrase();
+
+    let (address, private_key) = import_from_mnemonic(&phrase, chain, 0)?;
+    Ok((address, private_key, phrase))
+}
+
+/// Derive a wallet from an existing BIP39 mnemonic
+/// Returns (address, private_key_hex)
+pub fn import_from_mnemonic(phrase: &str, chain: &str, account_index: u32) -> AppResult<(String, String)> {
+    let phrase = normalize_phrase(phrase);
+    validate_mnemonic(&phrase)?;
+
+    let path = match chain {
+        "ethereum" => ethereum_derivation_path(account_index),
+        "zcash" | "zcash-testnet" => zcash_derivation_path(account_index),
+        _ => {
+            return Err(AppError::ValidationError(format!(
+                "Mnemonic wallets are not supported for chain '{}'",
+                chain
+            )))
+        }
+    };
+
+    let wallet = MnemonicBuilder::<English>::default()
+        .phrase(phrase.as_str())
+        .derivation_path(&path)
+        .map_err(|e| AppError::InternalError(format!("Invalid derivation path {}: {}", path, e)))?
+        .build()
+        .map_err(|e| AppError::ValidationError(format!("Failed to derive key from mnemonic: {}", e)))?;
+
+    let private_key = hex::encode(wallet.signer().to_bytes());
+
+    let address = import_wallet_for_chain(chain, &private_key)?;
+
+    Ok((address, private_key))
+}
+
+/// Address at a BIP44 index of a phrase, without keeping the private key
+pub fn derive_address(phrase: &str, chain: &str, index: u32) -> AppResult<String> {
+    import_from_mnemonic(phrase, chain, index).map(|(address, _)| address)
+}
+
+/// Validate word list membership, word count and checksum of a phrase
+pub fn validate_mnemonic(phrase: &str) -> AppResult<()> {
+    let phrase = normalize_phrase(phrase);
+    let word_count = phrase.split(' ').count();
+
+    if !SUPPORTED_WORD_COUNTS.contains(&word_count) {
+        return Err(AppError::ValidationError(format!(
+            "Mnemonic must have 12 or 24 words, got {}",
+            word_count
+        )));
+    }
+
+    Mnemonic::<English>::new_from_phrase(&phrase)
+        .map_err(|e| AppError::ValidationError(format!("Invalid mnemonic: {}", e)))?;
+
+    Ok(())
+}
+
+/// Collapse whitespace and lowercase the phrase so equivalent inputs derive the same keys
+pub fn normalize_phrase(phrase: &str) -> String {
+    phrase
+        .split_whitespace()
+        .map(|w| w.to_lowercase())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TEST_PHRASE: &str =
+        "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about";
+
+    #[test]
+    fn test_known_ethereum_vector() {
+        let (address, _) = import_from_mnemonic(TEST_PHRASE, "ethereum", 0).unwrap();
+        assert_eq!(
+            address.to_lowercase(),
+            "0x9858effd232b4033e47d90003d41ec34ecaeda94"
+        );
+    }
+
+    #[test]
+    fn test_generate_and_reimport() {
+        for chain in ["ethereum", "zcash"] {
+            let (address, private_key, phrase) = generate_mnemonic_wallet(chain, 12).unwrap();
+            assert_eq!(phrase.split(' ').count(), 12);
+
+            let (reimported, reimported_key) = import_from_mnemonic(&phrase, chain, 0).unwrap();
+            assert_eq!(address, reimported);
+            assert_eq!(private_key, reimported_key);
+        }
+
+        let (_, _, phrase) = generate_mnemonic_wallet("ethereum", 24).unwrap();
+        assert_eq!(phrase.split(' ').count(), 24);
+    }
+
+    #[test]
+    fn test_derive_address_matches_import() {
+        let (first, _) = import_from_mnemonic(TEST_PHRASE, "ethereum", 0).unwrap();
+        assert_eq!(derive_address(TEST_PHRASE, "ethereum", 0).unwrap(), first);
+        assert_ne!(derive_address(TEST_PHRASE, "ethereum", 5).unwrap(), first);
+    }
+
+    #[test]
+    fn test_account_index_changes_address() {
+        let (first, _) = import_from_mnemonic(TEST_PHRASE, "zcash", 0).unwrap();
+        let (second, _) = import_from_mnemonic(TEST_PHRASE, "zcash", 1).unwrap();
+        assert!(first.starts_with("t1"));
+        assert_ne!(first, second);
+    }
+
+    #[test]
+    fn test_invalid_mnemonics_rejected() {
+        // Bad checksum (last word changed)
+        let bad_checksum = TEST_PHRASE.replace("about", "abandon");
+        assert!(validate_mnemonic(&bad_checksum).is_err());
+
+        // Word not in the English list
+        let bad_word = TEST_PHRASE.replace("about", "bitcoinz");
+        assert!(validate_mnemonic(&bad_word).is_err());
+
+        // Unsupported length
+        assert!(validate_mnemonic("abandon abandon abandon").is_err());
+        assert!(generate_mnemonic_wallet("ethereum", 15).is_err());
+
+        // Unsupported chain
+        assert!(import_from_mnemonic(TEST_PHRASE, "bitcoin", 0).is_err());
+    }
+}
@@ -1,8 +1,74 @@
 pub mod encryption;
 pub mod ethereum;
+pub mod keystore;
+pub mod mnemonic;
 pub mod password;
+pub mod units;
 pub mod zcash;
 
 pub use encryption::{decrypt, encrypt};
 pub use ethereum::{generate_ethereum_wallet, import_ethereum_wallet};
+pub use mnemonic::{generate_mnemonic_wallet, import_from_mnemonic};
 pub use zcash::{generate_zcash_wallet, import_zcash_wallet};
+
+use crate::blockchain::zcash::orchard::transfer::NetworkType;
+use crate::error::{AppError, AppResult};
+
+/// Generate a new key pair for the given chain
+/// Returns (address, private_key_hex)
+pub fn generate_wallet_for_chain(chain: &str) -> AppResult<(String, String)> {
+    match chain {
+        "ethereum" => generate_ethereum_wallet(),
+        "zcash" => generate_zcash_wallet(NetworkType::Mainnet),
+        "zcash-testnet" => generate_zcash_wallet(NetworkType::Testnet),
+        _ => Err(unsupported_chain(chain)),
+    }
+}
+
+/// Derive the address of an existing private key for the given chain
+pub fn import_wallet_for_chain(chain: &str, private_key: &str) -> AppResult<String> {
+    match chain {
+        "ethereum" => import_ethereum_wallet(private_key),
+        "zcash" => import_zcash_wallet(private_key, NetworkType::Mainnet),
+        "zcash-testnet" => import_zcash_wallet(private_key, NetworkType::Testnet),
+        _ => Err(unsupported_chain(chain)),
+    }
+}
+
+fn unsupported_chain(chain: &str) -> AppError {
+    AppError::ValidationError(format!("Unsupported chain '{}'", chain))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unknown_chain_rejected() {
+        assert!(matches!(
+            generate_wallet_for_chain("bitcoin"),
+            Err(AppError::ValidationError(_))
+        ));
+
+        let (_, private_key) = generate_ethereum_wallet().unwrap();
+        assert!(matches!(
+            import_wallet_for_chain("etherium", &private_key),
+            Err(AppError::ValidationError(_))
+        ));
+    }
+
+    #[test]
+    fn test_known_chains_supported() {
+        let (address, private_key) = generate_wallet_for_chain("ethereum").unwrap();
+        assert!(address.starts_with("0x"));
+        assert_eq!(import_wallet_for_chain("ethereum", &private_key).unwrap(), address);
+
+        let (address, private_key) = generate_wallet_for_chain("zcash").unwrap();
+        assert!(address.starts_with("t1"));
+        assert_eq!(import_wallet_for_chain("zcash", &private_key).unwrap(), address);
+
+        let (address, private_key) = generate_wallet_for_chain("zcash-testnet").unwrap();
+        assert!(address.starts_with("tm"));
+        assert_eq!(import_wallet_for_chain("zcash-testnet", &private_key).unwrap(), address);
+    }
+}
@@ -28,6 +28,35 @@ pub fn verify_password(password: &str, hash: &str) -> AppResult<bool> {
         .is_ok())
 }
 
+/// Minimum accepted password length
+pub const MIN_PASSWORD_LENGTH: usize = 8;
+
+/// Enforce the minimum password policy: length, letters and digits, not the username
+pub fn validate_password_strength(password: &str, username: &str) -> AppResult<()> {
+    if password.chars().count() < MIN_PASSWORD_LENGTH {
+        return Err(AppError::ValidationError(format!(
+            "Password must be at least {} characters",
+            MIN_PASSWORD_LENGTH
+        )));
+    }
+
+    if password.eq_ignore_ascii_case(username) {
+        return Err(AppError::ValidationError(
+            "Password must not be the same as the username".to_string(),
+        ));
+    }
+
+    let has_letter = password.chars().any(|c| c.is_alphabetic());
+    let has_digit = password.chars().any(|c| c.is_ascii_digit());
+    if !has_letter || !has_digit {
+        return Err(AppError::ValidationError(
+            "Password must contain both letters and digits".to_string(),
+        ));
+    }
+
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -54,4 +83,16 @@ mod tests {
         assert!(verify_password(password, &hash1).unwrap());
         assert!(verify_password(password, &hash2).unwrap());
     }
+
+    #[test]
+    fn test_password_strength() {
+        assert!(validate_password_strength("correct9horse", "admin").is_ok());
+
+        // Too short
+        assert!(validate_password_strength("abc123", "admin").is_err());
+        // Same as username
+        assert!(validate_password_strength("Operator01", "operator01").is_err());
+        // Missing digits
+        assert!(validate_password_strength("onlyletters", "admin").is_err());
+    }
 }
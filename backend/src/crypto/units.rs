@@ -0,0 +1,297 @@
+//! Exact conversion between decimal amounts and integer base units
+//!
+//! ZEC has 8 decimals (zatoshis), ETH and most ERC20 tokens 18 (wei).
+//! All arithmetic is done on integers so amounts never pass through `f64`.
+
+use ethers::types::U256;
+use rust_decimal::Decimal;
+use std::str::FromStr;
+
+use crate::error::{AppError, AppResult};
+
+/// Decimals of ZEC (1 ZEC = 10^8 zatoshis)
+pub const ZEC_DECIMALS: u32 = 8;
+
+/// Decimals of ETH (1 ETH = 10^18 wei)
+pub const ETH_DECIMALS: u32 = 18;
+
+/// Decimals of a gwei amount expressed in wei
+pub const GWEI_DECIMALS: u32 = 9;
+
+/// 10^77 is the largest power of ten that fits in a U256
+const MAX_DECIMALS: u32 = 77;
+
+/// Parse a decimal string such as "1.5" into base units
+///
+/// Rejects negative values, exponents and digits beyond `decimals` that are
+/// not zero, instead of silently rounding them away.
+pub fn parse_units(value: &str, decimals: u32) -> AppResult<U256> {
+    if decimals > MAX_DECIMALS {
+        return Err(AppError::ValidationError(format!(
+            "Unsupported number of decimals: {}",
+            decimals
+        )));
+    }
+
+    let value = value.trim();
+    let invalid = || AppError::ValidationError(format!("Invalid amount: '{}'", value));
+
+    let (integer, fraction) = match value.split_once('.') {
+        Some((integer, fraction)) => (integer, fraction),
+        None => (value, ""),
+    };
+
+    if integer.is_empty() && fraction.is_empty() {
+        return Err(invalid());
+    }
+    if !integer.bytes().chain(fraction.bytes()).all(|b| b.is_ascii_digit()) {
+        return Err(invalid());
+    }
+
+    let decimals = decimals as usize;
+    let fraction = if fraction.len() > decimals {
+        let (kept, excess) = fraction.split_at(decimals);
+        if excess.bytes().any(|b| b != b'0') {
+            return Err(AppError::ValidationError(format!(
+                "Amount '{}' has more than {} decimal places",
+                value, decimals
+            )));
+        }
+        kept.to_string()
+    } else {
+        format!("{:0<width$}", fraction, width = decimals)
+    };
+
+    let digits = format!("{}{}", integer, fraction);
+    let digits = digits.trim_start_matches('0');
+    if digits.is_empty() {
+        return Ok(U256::zero());
+    }
+
+    U256::from_dec_str(digits)
+        .map_err(|_| AppError::ValidationError(format!("Amount '{}' is out of range", value)))
+}
+
+/// Parse a decimal string into base units that must fit in a u64 (e.g. zatoshis)
+pub fn parse_units_u64(value: &str, decimals: u32) -> AppResult<u64> {
+    let units = parse_units(value, decimals)?;
+    if units > U256::from(u64::MAX) {
+        return Err(AppError::ValidationError(format!(
+            "Amount '{}' is out of range",
+            value.trim()
+        )));
+    }
+    Ok(units.as_u64())
+}
+
+/// Parse a decimal string into base units that must fit in a u128
+pub fn parse_units_u128(value: &str, decimals: u32) -> AppResult<u128> {
+    let units = parse_units(value, decimals)?;
+    if units > U256::from(u128::MAX) {
+        return Err(AppError::ValidationError(format!(
+            "Amount '{}' is out of range",
+            value.trim()
+        )));
+    }
+    Ok(units.as_u128())
+}
+
+/// Convert a `Decimal` amount into base units
+pub fn decimal_to_units(amount: Decimal, decimals: u32) -> AppResult<U256> {
+    parse_units(&amount.to_string(), decimals)
+}
+
+/// Format base units as a decimal string without trailing zeros ("1.5", "0.00000001", "2")
+pub fn format_units(value: impl Into<U256>, decimals: u32) -> String {
+    let digits = value.into().to_string();
+    let decimals = decimals as usize;
+    if decimals == 0 {
+        return digits;
+    }
+
+    let padded = format!("{:0>width$}", digits, width = decimals + 1);
+    let (integer, fraction) = padded.split_at(padded.len() - decimals);
+    let fraction = fraction.trim_end_matches('0');
+
+    if fraction.is_empty() {
+        integer.to_string()
+    } else {
+        format!("{}.{}", integer, fraction)
+    }
+}
+
+/// Format base units for display, rounded half-up to `places` decimal places
+///
+/// `None` keeps full precision (same as `format_units`). With `Some`, exactly
+/// `places` digits are shown ("1.2346", "2.0000"), capped at `decimals`.
+pub fn format_units_display(value: impl Into<U256>, decimals: u32, places: Option<u32>) -> String {
+    let value = value.into();
+    let places = match places {
+        Some(places) => places.min(decimals),
+        None => return format_units(value, decimals),
+    };
+
+    let divisor = U256::exp10((decimals - places) as usize);
+    let mut rounded = value / divisor;
+    if value % divisor >= (divisor + 1) / 2 {
+        rounded += U256::one();
+    }
+
+    let digits = rounded.to_string();
+    if places == 0 {
+        return digits;
+    }
+
+    let places = places as usize;
+    let padded = format!("{:0>width$}", digits, width = places + 1);
+    let (integer, fraction) = padded.split_at(padded.len() - places);
+    format!("{}.{}", integer, fraction)
+}
+
+/// Format a whole-unit `Decimal` amount for display, see `format_units_display`
+pub fn format_decimal_display(amount: Decimal, decimals: u32, places: Option<u32>) -> AppResult<String> {
+    Ok(format_units_display(decimal_to_units(amount, decimals)?, decimals, places))
+}
+
+/// Format base units as a `Decimal`
+///
+/// `Decimal` keeps 28 significant digits, so very large 18-decimal values lose
+/// their least significant fraction digits here; use `format_units` where that matters.
+pub fn units_to_decimal(value: impl Into<U256>, decimals: u32) -> AppResult<Decimal> {
+    let formatted = format_units(value, decimals);
+    Decimal::from_str(&formatted)
+        .map_err(|e| AppError::InternalError(format!("Amount {} does not fit a decimal: {}", formatted, e)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_zec() {
+        assert_eq!(parse_units_u64("1.5", ZEC_DECIMALS).unwrap(), 150_000_000);
+        assert_eq!(parse_units_u64("0.00000001", ZEC_DECIMALS).unwrap(), 1);
+        assert_eq!(parse_units_u64("21000000", ZEC_DECIMALS).unwrap(), 2_100_000_000_000_000);
+        assert_eq!(parse_units_u64(" 0.1 ", ZEC_DECIMALS).unwrap(), 10_000_000);
+        assert_eq!(parse_units_u64(".5", ZEC_DECIMALS).unwrap(), 50_000_000);
+        assert_eq!(parse_units_u64("3.", ZEC_DECIMALS).unwrap(), 300_000_000);
+        assert_eq!(parse_units_u64("0", ZEC_DECIMALS).unwrap(), 0);
+    }
+
+    #[test]
+    fn test_parse_eth() {
+        assert_eq!(
+            parse_units("1", ETH_DECIMALS).unwrap(),
+            U256::from(1_000_000_000_000_000_000u128)
+        );
+        assert_eq!(parse_units("0.000000000000000001", ETH_DECIMALS).unwrap(), U256::one());
+        assert_eq!(
+            parse_units_u128("123.456789012345678901", ETH_DECIMALS).unwrap(),
+            123_456_789_012_345_678_901
+        );
+    }
+
+    #[test]
+    fn test_values_that_break_f64_are_exact() {
+        // 0.1 + 0.2 style values and 17+ significant digits
+        assert_eq!(parse_units_u64("0.29", ZEC_DECIMALS).unwrap(), 29_000_000);
+        assert_eq!(parse_units_u64("20999999.99999999", ZEC_DECIMALS).unwrap(), 2_099_999_999_999_999);
+        assert_eq!(
+            parse_units_u128("9007199254740993.000000000000000001", ETH_DECIMALS).unwrap(),
+            9_007_199_254_740_993_000_000_000_000_000_001
+        );
+    }
+
+    #[test]
+    fn test_excess_precision_is_rejected_not_rounded() {
+        assert!(parse_units_u64("0.000000001", ZEC_DECIMALS).is_err());
+        assert!(parse_units_u64("1.123456789", ZEC_DECIMALS).is_err());
+        assert!(parse_units("0.0000000000000000001", ETH_DECIMALS).is_err());
+
+        // Trailing zeros beyond the precision carry no value
+        assert_eq!(parse_units_u64("1.5000000000", ZEC_DECIMALS).unwrap(), 150_000_000);
+    }
+
+    #[test]
+    fn test_invalid_input() {
+        for input in ["", ".", "-1", "+1", "1e8", "1.2.3", "abc", "1,5", "0x10"] {
+            assert!(parse_units_u64(input, ZEC_DECIMALS).is_err(), "accepted {:?}", input);
+        }
+        assert!(parse_units("1", 78).is_err());
+    }
+
+    #[test]
+    fn test_overflow() {
+        // u64::MAX zatoshis fits, one more does not
+        assert_eq!(parse_units_u64("184467440737.09551615", ZEC_DECIMALS).unwrap(), u64::MAX);
+        assert!(parse_units_u64("184467440737.09551616", ZEC_DECIMALS).is_err());
+
+        // u128 boundary with 18 decimals
+        assert_eq!(
+            parse_units_u128("340282366920938463463.374607431768211455", ETH_DECIMALS).unwrap(),
+            u128::MAX
+        );
+        assert!(parse_units_u128("340282366920938463463.374607431768211456", ETH_DECIMALS).is_err());
+
+        // Beyond U256
+        let too_big = format!("1{}", "0".repeat(60));
+        assert!(parse_units(&too_big, ETH_DECIMALS).is_err());
+    }
+
+    #[test]
+    fn test_format_units() {
+        assert_eq!(format_units(150_000_000u64, ZEC_DECIMALS), "1.5");
+        assert_eq!(format_units(1u64, ZEC_DECIMALS), "0.00000001");
+        assert_eq!(format_units(0u64, ZEC_DECIMALS), "0");
+        assert_eq!(format_units(200_000_000u64, ZEC_DECIMALS), "2");
+        assert_eq!(format_units(u64::MAX, ZEC_DECIMALS), "184467440737.09551615");
+
+        assert_eq!(format_units(1u64, ETH_DECIMALS), "0.000000000000000001");
+        assert_eq!(format_units(u128::MAX, ETH_DECIMALS), "340282366920938463463.374607431768211455");
+        assert_eq!(format_units(U256::MAX, 0), U256::MAX.to_string());
+    }
+
+    #[test]
+    fn test_format_units_display() {
+        let units = parse_units("1.23456789", ETH_DECIMALS).unwrap();
+        assert_eq!(format_units_display(units, ETH_DECIMALS, None), "1.23456789");
+        assert_eq!(format_units_display(units, ETH_DECIMALS, Some(4)), "1.2346");
+        assert_eq!(format_units_display(units, ETH_DECIMALS, Some(0)), "1");
+        assert_eq!(format_units_display(200_000_000u64, ZEC_DECIMALS, Some(4)), "2.0000");
+        assert_eq!(format_units_display(99_995_000u64, ZEC_DECIMALS, Some(4)), "1.0000");
+        assert_eq!(format_units_display(4_999u64, ZEC_DECIMALS, Some(4)), "0.0000");
+        assert_eq!(format_units_display(5_000u64, ZEC_DECIMALS, Some(4)), "0.0001");
+
+        // More places than the unit has are capped
+        assert_eq!(format_units_display(1u64, ZEC_DECIMALS, Some(12)), "0.00000001");
+        assert_eq!(format_units_display(U256::MAX, 0, Some(4)), U256::MAX.to_string());
+    }
+
+    #[test]
+    fn test_round_trip() {
+        for value in ["0.00000001", "1.5", "123456.789", "184467440737.09551615"] {
+            let units = parse_units_u64(value, ZEC_DECIMALS).unwrap();
+            assert_eq!(format_units(units, ZEC_DECIMALS), value);
+        }
+        for value in ["0.000000000000000001", "1", "1000000.123456789012345678"] {
+            let units = parse_units(value, ETH_DECIMALS).unwrap();
+            assert_eq!(format_units(units, ETH_DECIMALS), value);
+        }
+    }
+
+    #[test]
+    fn test_decimal_conversions() {
+        let amount = Decimal::from_str("0.12345678").unwrap();
+        assert_eq!(decimal_to_units(amount, ZEC_DECIMALS).unwrap(), U256::from(12_345_678u64));
+        assert!(decimal_to_units(Decimal::from_str("-1").unwrap(), ZEC_DECIMALS).is_err());
+
+        assert_eq!(
+            units_to_decimal(12_345_678u64, ZEC_DECIMALS).unwrap(),
+            Decimal::from_str("0.12345678").unwrap()
+        );
+        assert_eq!(
+            units_to_decimal(1_500_000_000_000_000_000u128, ETH_DECIMALS).unwrap(),
+            Decimal::from_str("1.5").unwrap()
+        );
+    }
+}
@@ -6,16 +6,16 @@ use secp256k1::{PublicKey, Secp256k1, SecretKey};
 use sha2::{Digest, Sha256};
 
 use crate::blockchain::zcash::orchard::{
-    address::OrchardAddressManager, keys::OrchardKeyManager, OrchardViewingKey, UnifiedAddressInfo,
+    address::OrchardAddressManager,
+    keys::OrchardKeyManager,
+    transfer::{transparent_prefixes, NetworkType},
+    OrchardViewingKey, SaplingViewingKey, UnifiedAddressInfo,
 };
 use crate::error::{AppError, AppResult};
 
-/// Zcash mainnet transparent address prefix (t1)
-const ZCASH_T_ADDR_PREFIX: [u8; 2] = [0x1C, 0xB8];
-
 /// Generate a new Zcash transparent address and private key
 /// Returns (address, private_key_hex)
-pub fn generate_zcash_wallet() -> AppResult<(String, String)> {
+pub fn generate_zcash_wallet(network: NetworkType) -> AppResult<(String, String)> {
     let secp = Secp256k1::new();
 
     // Generate random 32-byte private key
@@ -29,7 +29,7 @@ pub fn generate_zcash_wallet() -> AppResult<(String, String)> {
     let public_key = PublicKey::from_secret_key(&secp, &secret_key);
 
     // Generate address from public key
-    let address = public_key_to_t_address(&public_key)?;
+    let address = public_key_to_t_address(&public_key, network)?;
     let private_key_hex = hex::encode(key_bytes);
 
     Ok((address, private_key_hex))
@@ -38,7 +38,7 @@ pub fn generate_zcash_wallet() -> AppResult<(String, String)> {
 /// Import a Zcash wallet from private key
 /// Supports both WIF format (starts with 5, K, L) and raw hex format
 /// Returns the address derived from the private key
-pub fn import_zcash_wallet(private_key: &str) -> AppResult<String> {
+pub fn import_zcash_wallet(private_key: &str, network: NetworkType) -> AppResult<String> {
     tracing::debug!("Importing Zcash wallet, key length: {}, first char: {:?}",
         private_key.len(),
         private_key.chars().next()
@@ -81,7 +81,7 @@ pub fn import_zcash_wallet(private_key: &str) -> AppResult<String> {
     let public_key = PublicKey::from_secret_key(&secp, &secret_key);
 
     // Generate address from public key
-    let address = public_key_to_t_address(&public_key)?;
+    let address = public_key_to_t_address(&public_key, network)?;
     tracing::info!("Successfully derived Zcash address: {}", address);
     Ok(address)
 }
@@ -129,7 +129,7 @@ fn decode_wif_private_key(wif: &str) -> AppResult<Vec<u8>> {
 }
 
 /// Convert a secp256k1 public key to a Zcash transparent address (t-address)
-fn public_key_to_t_address(public_key: &PublicKey) -> AppResult<String> {
+fn public_key_to_t_address(public_key: &PublicKey, network: NetworkType) -> AppResult<String> {
     // Get compressed public key bytes
     let pubkey_bytes = public_key.serialize();
 
@@ -139,9 +139,10 @@ fn public_key_to_t_address(public_key: &PublicKey) -> AppResult<String> {
     // RIPEMD160 hash
     let ripemd_hash = Ripemd160::digest(&sha256_hash);
 
-    // Build payload: prefix + ripemd160 hash
+    // Build payload: P2PKH prefix (t1 or tm) + ripemd160 hash
+    let (p2pkh, _) = transparent_prefixes(network);
     let mut payload = Vec::with_capacity(22);
-    payload.extend_from_slice(&ZCASH_T_ADDR_PREFIX);
+    payload.extend_from_slice(&p2pkh);
     payload.extend_from_slice(&ripemd_hash);
 
     // Double SHA256 for checksum
@@ -209,20 +210,25 @@ pub(crate) fn validate_zcash_address(address: &str) -> bool {
 /// # Arguments
 /// * `private_key_hex` - The transparent wallet's private key in hex format
 /// * `birthday_height` - Block height when wallet was created (for scanning)
+/// * `network` - Network the unified address is encoded for
 ///
 /// # Returns
 /// * Tuple of (unified_address, viewing_key_encoded)
 pub fn enable_orchard_for_wallet(
     private_key_hex: &str,
     birthday_height: u64,
+    network: NetworkType,
 ) -> AppResult<(UnifiedAddressInfo, String)> {
     // Derive Orchard keys from the transparent private key
     let (spending_key, viewing_key) =
         OrchardKeyManager::derive_from_private_key(private_key_hex, 0, birthday_height)
             .map_err(|e| AppError::InternalError(format!("Failed to derive Orchard keys: {}", e)))?;
+    let sapling_key = derive_sapling_viewing_key(private_key_hex)?;
 
     // Create address manager and generate unified address
-    let mut address_manager = OrchardAddressManager::new(viewing_key.clone());
+    let mut address_manager = OrchardAddressManager::new(viewing_key.clone())
+        .with_network(network)
+        .with_sapling_key(sapling_key);
     let unified_address = address_manager
         .generate_unified_address()
         .map_err(|e| AppError::InternalError(format!("Failed to generate unified address: {}", e)))?;
@@ -241,12 +247,14 @@ pub fn enable_orchard_for_wallet(
 /// # Arguments
 /// * `seed` - 64-byte seed (e.g., from BIP39 mnemonic)
 /// * `birthday_height` - Current block height
+/// * `network` - Network the addresses are encoded for
 ///
 /// # Returns
 /// * Tuple of (unified_address, transparent_address, private_key_hex, viewing_key_encoded)
 pub fn generate_orchard_wallet(
     seed: &[u8],
     birthday_height: u64,
+    network: NetworkType,
 ) -> AppResult<(UnifiedAddressInfo, String, String, String)> {
     if seed.len() < 32 {
         return Err(AppError::ValidationError(
@@ -268,15 +276,19 @@ pub fn generate_orchard_wallet(
         .map_err(|e| AppError::InternalError(format!("Failed to generate secret key: {}", e)))?;
 
     let public_key = PublicKey::from_secret_key(&secp, &secret_key);
-    let transparent_address = public_key_to_t_address(&public_key)?;
+    let transparent_address = public_key_to_t_address(&public_key, network)?;
     let private_key_hex = hex::encode(key_bytes.as_bytes());
 
-    // Derive Orchard keys
+    // Derive Orchard and Sapling keys
     let (_, viewing_key) = OrchardKeyManager::derive_from_seed(seed, 0, birthday_height)
         .map_err(|e| AppError::InternalError(format!("Failed to derive Orchard keys: {}", e)))?;
+    let sapling_key = SaplingViewingKey::derive_from_seed(seed, 0)
+        .map_err(|e| AppError::InternalError(format!("Failed to derive Sapling key: {}", e)))?;
 
     // Generate unified address
-    let mut address_manager = OrchardAddressManager::new(viewing_key.clone());
+    let mut address_manager = OrchardAddressManager::new(viewing_key.clone())
+        .with_network(network)
+        .with_sapling_key(sapling_key);
     let unified_address = address_manager
         .generate_unified_address()
         .map_err(|e| AppError::InternalError(format!("Failed to generate unified address: {}", e)))?;
@@ -291,22 +303,37 @@ pub fn generate_orchard_wallet(
     ))
 }
 
+/// Derive the Sapling viewing key that shares its seed with the wallet's Orchard keys
+pub fn derive_sapling_viewing_key(private_key_hex: &str) -> AppResult<SaplingViewingKey> {
+    let seed = OrchardKeyManager::seed_from_private_key(private_key_hex)
+        .map_err(|e| AppError::InternalError(format!("Failed to derive Sapling key: {}", e)))?;
+    SaplingViewingKey::derive_from_seed(&seed, 0)
+        .map_err(|e| AppError::InternalError(format!("Failed to derive Sapling key: {}", e)))
+}
+
 /// Generate a new unified address for an existing Orchard account
 ///
 /// # Arguments
 /// * `viewing_key_encoded` - The encoded viewing key
+/// * `sapling_key` - The account's Sapling key; without it the address has no Sapling receiver
 /// * `address_index` - The address index to generate
+/// * `network` - Network the address is encoded for
 ///
 /// # Returns
 /// * UnifiedAddressInfo for the new address
 pub fn generate_unified_address(
     viewing_key_encoded: &str,
+    sapling_key: Option<SaplingViewingKey>,
     address_index: u32,
+    network: NetworkType,
 ) -> AppResult<UnifiedAddressInfo> {
     let viewing_key = OrchardViewingKey::decode(viewing_key_encoded)
         .map_err(|e| AppError::ValidationError(format!("Invalid viewing key: {}", e)))?;
 
-    let address_manager = OrchardAddressManager::new(viewing_key);
+    let mut address_manager = OrchardAddressManager::new(viewing_key).with_network(network);
+    if let Some(sapling_key) = sapling_key {
+        address_manager = address_manager.with_sapling_key(sapling_key);
+    }
     let address_info = address_manager
         .generate_address_at_index(address_index)
         .map_err(|e| AppError::InternalError(format!("Failed to generate address: {}", e)))?;
@@ -317,7 +344,7 @@ pub fn generate_unified_address(
 /// Parse and validate a unified address
 ///
 /// # Arguments
-/// * `address` - The unified address string (u1...)
+/// * `address` - The unified address string (u1... or utest1...)
 ///
 /// # Returns
 /// * UnifiedAddressInfo with parsed components
@@ -328,7 +355,7 @@ pub fn parse_unified_address(address: &str) -> AppResult<UnifiedAddressInfo> {
 
 /// Check if an address is a unified address
 pub fn is_unified_address(address: &str) -> bool {
-    address.starts_with("u1") && address.len() >= 100
+    (address.starts_with("u1") || address.starts_with("utest1")) && address.len() >= 100
 }
 
 #[cfg(test)]
@@ -337,7 +364,7 @@ mod tests {
 
     #[test]
     fn test_generate_zcash_wallet() {
-        let (address, private_key) = generate_zcash_wallet().unwrap();
+        let (address, private_key) = generate_zcash_wallet(NetworkType::Mainnet).unwrap();
 
         assert!(address.starts_with("t1"));
         assert_eq!(private_key.len(), 64); // 32 bytes = 64 hex chars
@@ -347,10 +374,10 @@ mod tests {
     #[test]
     fn test_import_zcash_wallet() {
         // Generate a wallet first
-        let (original_address, private_key) = generate_zcash_wallet().unwrap();
+        let (original_address, private_key) = generate_zcash_wallet(NetworkType::Mainnet).unwrap();
 
         // Import the same private key
-        let imported_address = import_zcash_wallet(&private_key).unwrap();
+        let imported_address = import_zcash_wallet(&private_key, NetworkType::Mainnet).unwrap();
 
         assert_eq!(original_address, imported_address);
     }
@@ -362,7 +389,31 @@ mod tests {
         assert!(!validate_zcash_address("invalid")); // Random string
 
         // Generate and validate
-        let (address, _) = generate_zcash_wallet().unwrap();
+        let (address, _) = generate_zcash_wallet(NetworkType::Mainnet).unwrap();
         assert!(validate_zcash_address(&address));
     }
+
+    #[test]
+    fn test_testnet_addresses() {
+        let (address, private_key) = generate_zcash_wallet(NetworkType::Testnet).unwrap();
+        assert!(address.starts_with("tm"));
+        assert_eq!(import_zcash_wallet(&private_key, NetworkType::Testnet).unwrap(), address);
+        assert!(import_zcash_wallet(&private_key, NetworkType::Mainnet).unwrap().starts_with("t1"));
+
+        let (unified, viewing_key) = enable_orchard_for_wallet(&private_key, 2_800_000, NetworkType::Testnet).unwrap();
+        assert!(unified.address.starts_with("utest1"));
+        assert!(is_unified_address(&unified.address));
+        assert!(parse_unified_address(&unified.address).unwrap().transparent_address.unwrap().starts_with("tm"));
+
+        let parsed = parse_unified_address(&unified.address).unwrap();
+        assert!(parsed.has_sapling);
+
+        let sapling_key = derive_sapling_viewing_key(&private_key).unwrap();
+        let testnet = generate_unified_address(&viewing_key, Some(sapling_key), 0, NetworkType::Testnet).unwrap();
+        assert_eq!(testnet.address, unified.address);
+
+        let mainnet = generate_unified_address(&viewing_key, None, 0, NetworkType::Mainnet).unwrap();
+        assert!(mainnet.address.starts_with("u1"));
+        assert!(!mainnet.has_sapling);
+    }
 }
@@ -3,6 +3,7 @@ pub mod repositories;
 
 use crate::config::DatabaseConfig;
 use crate::error::AppResult;
+use serde::Serialize;
 use sqlx::mysql::MySqlPoolOptions;
 use sqlx::MySqlPool;
 
@@ -26,11 +27,28 @@ pub async fn create_pool(config: &DatabaseConfig) -> AppResult<MySqlPool> {
     Ok(pool)
 }
 
+/// Connection counts of the database pool
+#[derive(Debug, Clone, Serialize)]
+pub struct PoolStats {
+    pub size: u32,
+    pub idle: u32,
+    pub active: u32,
+}
+
+/// Current database pool statistics
+pub fn pool_stats(pool: &MySqlPool) -> PoolStats {
+    let size = pool.size();
+    let idle = pool.num_idle() as u32;
+    PoolStats {
+        size,
+        idle,
+        active: size.saturating_sub(idle),
+    }
+}
+
 /// Log current database pool statistics
 pub fn log_pool_stats(pool: &MySqlPool) {
-    let size = pool.size();
-    let idle = pool.num_idle();
-    let active = size - idle as u32;
+    let PoolStats { size, idle, active } = pool_stats(pool);
 
     if active > 15 {
         tracing::warn!(
@@ -367,6 +385,622 @@ pub async fn run_migrations(pool: &MySqlPool) -> AppResult<()> {
         tracing::info!("Added witness_state column to orchard_notes table for incremental sync");
     }
 
+    // Add encrypted_mnemonic column to wallets table if not exists
+    // Wallets created from a BIP39 phrase keep the encrypted phrase for backup export
+    let mnemonic_column_exists: Option<(String,)> = sqlx::query_as(
+        r#"
+        SELECT COLUMN_NAME FROM INFORMATION_SCHEMA.COLUMNS
+        WHERE TABLE_SCHEMA = DATABASE()
+        AND TABLE_NAME = 'wallets'
+        AND COLUMN_NAME = 'encrypted_mnemonic'
+        "#,
+    )
+    .fetch_optional(pool)
+    .await?;
+
+    if mnemonic_column_exists.is_none() {
+        sqlx::query(
+            "ALTER TABLE wallets ADD COLUMN encrypted_mnemonic TEXT NULL"
+        )
+        .execute(pool)
+        .await?;
+        tracing::info!("Added encrypted_mnemonic column to wallets table");
+    }
+
+    // The account index a mnemonic wallet was derived at; the phrase alone does not
+    // lead back to a wallet imported at another index
+    let account_index_column_exists: Option<(String,)> = sqlx::query_as(
+        r#"
+        SELECT COLUMN_NAME FROM INFORMATION_SCHEMA.COLUMNS
+        WHERE TABLE_SCHEMA = DATABASE()
+        AND TABLE_NAME = 'wallets'
+        AND COLUMN_NAME = 'mnemonic_account_index'
+        "#,
+    )
+    .fetch_optional(pool)
+    .await?;
+
+    if account_index_column_exists.is_none() {
+        sqlx::query(
+            "ALTER TABLE wallets ADD COLUMN mnemonic_account_index INT UNSIGNED NULL"
+        )
+        .execute(pool)
+        .await?;
+        tracing::info!("Added mnemonic_account_index column to wallets table");
+    }
+
+    // Watch-only wallets are imported from an Orchard viewing key and hold no spending key
+    let watch_only_column_exists: Option<(String,)> = sqlx::query_as(
+        r#"
+        SELECT COLUMN_NAME FROM INFORMATION_SCHEMA.COLUMNS
+        WHERE TABLE_SCHEMA = DATABASE()
+        AND TABLE_NAME = 'wallets'
+        AND COLUMN_NAME = 'watch_only'
+        "#,
+    )
+    .fetch_optional(pool)
+    .await?;
+
+    if watch_only_column_exists.is_none() {
+        sqlx::query(
+            "ALTER TABLE wallets ADD COLUMN watch_only BOOLEAN NOT NULL DEFAULT FALSE"
+        )
+        .execute(pool)
+        .await?;
+        sqlx::query(
+            "ALTER TABLE wallets ADD COLUMN encrypted_viewing_key TEXT NULL"
+        )
+        .execute(pool)
+        .await?;
+        tracing::info!("Added watch_only and encrypted_viewing_key columns to wallets table");
+    }
+
+    // Watch-only wallets are stored under their unified address, which is far longer than 42 characters
+    let wallet_address_too_short: Option<(String,)> = sqlx::query_as(
+        r#"
+        SELECT DATA_TYPE FROM INFORMATION_SCHEMA.COLUMNS
+        WHERE TABLE_SCHEMA = DATABASE()
+        AND TABLE_NAME = 'wallets'
+        AND COLUMN_NAME = 'address'
+        AND CHARACTER_MAXIMUM_LENGTH < 512
+        "#,
+    )
+    .fetch_optional(pool)
+    .await?;
+
+    if wallet_address_too_short.is_some() {
+        sqlx::query(
+            "ALTER TABLE wallets MODIFY COLUMN address VARCHAR(512) NOT NULL"
+        )
+        .execute(pool)
+        .await?;
+        tracing::info!("Expanded wallets.address column to VARCHAR(512) for unified addresses");
+    }
+
+    // Recent block hashes seen by the Orchard scanner, used to detect chain reorgs
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS orchard_block_hashes (
+            height BIGINT UNSIGNED PRIMARY KEY,
+            hash VARCHAR(64) NOT NULL,
+            created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
+        )
+        "#,
+    )
+    .execute(pool)
+    .await?;
+
+    // Add spent_height column to orchard_notes table if not exists
+    // Lets a reorg rollback un-spend notes whose spending block was orphaned
+    let spent_height_exists: Option<(String,)> = sqlx::query_as(
+        r#"
+        SELECT COLUMN_NAME FROM INFORMATION_SCHEMA.COLUMNS
+        WHERE TABLE_SCHEMA = DATABASE()
+        AND TABLE_NAME = 'orchard_notes'
+        AND COLUMN_NAME = 'spent_height'
+        "#,
+    )
+    .fetch_optional(pool)
+    .await?;
+
+    if spent_height_exists.is_none() {
+        sqlx::query(
+            "ALTER TABLE orchard_notes ADD COLUMN spent_height BIGINT UNSIGNED NULL"
+        )
+        .execute(pool)
+        .await?;
+        tracing::info!("Added spent_height column to orchard_notes table");
+    }
+
+    // Spends by our own broadcast transactions stay pending until the scanner sees the
+    // nullifier on chain, and are reverted once the transaction's expiry height passes
+    let spent_pending_exists: Option<(String,)> = sqlx::query_as(
+        r#"
+        SELECT COLUMN_NAME FROM INFORMATION_SCHEMA.COLUMNS
+        WHERE TABLE_SCHEMA = DATABASE()
+        AND TABLE_NAME = 'orchard_notes'
+        AND COLUMN_NAME = 'spent_pending'
+        "#,
+    )
+    .fetch_optional(pool)
+    .await?;
+
+    if spent_pending_exists.is_none() {
+        sqlx::query(
+            r#"
+            ALTER TABLE orchard_notes
+            ADD COLUMN spent_pending BOOLEAN NOT NULL DEFAULT FALSE,
+            ADD COLUMN spent_expiry_height BIGINT UNSIGNED NULL
+            "#
+        )
+        .execute(pool)
+        .await?;
+        tracing::info!("Added spent_pending columns to orchard_notes table");
+    }
+
+    // Notes claimed by a transfer between building and broadcasting it
+    let reserved_until_exists: Option<(String,)> = sqlx::query_as(
+        r#"
+        SELECT COLUMN_NAME FROM INFORMATION_SCHEMA.COLUMNS
+        WHERE TABLE_SCHEMA = DATABASE()
+        AND TABLE_NAME = 'orchard_notes'
+        AND COLUMN_NAME = 'reserved_until'
+        "#,
+    )
+    .fetch_optional(pool)
+    .await?;
+
+    if reserved_until_exists.is_none() {
+        sqlx::query("ALTER TABLE orchard_notes ADD COLUMN reserved_until TIMESTAMP NULL")
+            .execute(pool)
+            .await?;
+        tracing::info!("Added reserved_until column to orchard_notes table");
+    }
+
+    // Revoked JWTs (logout). Rows are purged once the token would have expired anyway
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS revoked_tokens (
+            jti VARCHAR(64) PRIMARY KEY,
+            user_id INT NOT NULL,
+            expires_at TIMESTAMP NOT NULL,
+            created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+            INDEX idx_expires_at (expires_at)
+        )
+        "#,
+    )
+    .execute(pool)
+    .await?;
+
+    // Add token_epoch column to users table if not exists
+    // Bumping it invalidates every token issued to the user ("logout all sessions")
+    let token_epoch_exists: Option<(String,)> = sqlx::query_as(
+        r#"
+        SELECT COLUMN_NAME FROM INFORMATION_SCHEMA.COLUMNS
+        WHERE TABLE_SCHEMA = DATABASE()
+        AND TABLE_NAME = 'users'
+        AND COLUMN_NAME = 'token_epoch'
+        "#,
+    )
+    .fetch_optional(pool)
+    .await?;
+
+    if token_epoch_exists.is_none() {
+        sqlx::query(
+            "ALTER TABLE users ADD COLUMN token_epoch INT NOT NULL DEFAULT 0"
+        )
+        .execute(pool)
+        .await?;
+        tracing::info!("Added token_epoch column to users table");
+    }
+
+    // Add normalized fee columns to transfers table if not exists
+    // fee_native is in the chain's smallest unit (wei, zatoshis), fee_display in native coin
+    let fee_native_exists: Option<(String,)> = sqlx::query_as(
+        r#"
+        SELECT COLUMN_NAME FROM INFORMATION_SCHEMA.COLUMNS
+        WHERE TABLE_SCHEMA = DATABASE()
+        AND TABLE_NAME = 'transfers'
+        AND COLUMN_NAME = 'fee_native'
+        "#,
+    )
+    .fetch_optional(pool)
+    .await?;
+
+    if fee_native_exists.is_none() {
+        sqlx::query(
+            "ALTER TABLE transfers ADD COLUMN fee_native DECIMAL(38, 0) NULL, ADD COLUMN fee_display VARCHAR(64) NULL"
+        )
+        .execute(pool)
+        .await?;
+        tracing::info!("Added fee_native and fee_display columns to transfers table");
+    }
+
+    // Add approval columns to transfers table if not exists
+    // approval_status is NULL when no approval is needed, else required/approved/rejected
+    let approval_status_exists: Option<(String,)> = sqlx::query_as(
+        r#"
+        SELECT COLUMN_NAME FROM INFORMATION_SCHEMA.COLUMNS
+        WHERE TABLE_SCHEMA = DATABASE()
+        AND TABLE_NAME = 'transfers'
+        AND COLUMN_NAME = 'approval_status'
+        "#,
+    )
+    .fetch_optional(pool)
+    .await?;
+
+    if approval_status_exists.is_none() {
+        sqlx::query(
+            "ALTER TABLE transfers ADD COLUMN approval_status VARCHAR(20) NULL, ADD COLUMN approved_by INT NULL, ADD COLUMN approved_at TIMESTAMP NULL"
+        )
+        .execute(pool)
+        .await?;
+        tracing::info!("Added approval columns to transfers table");
+    }
+
+    // Add idempotency_key column to transfers table if not exists
+    // Unique per initiating user; cleared once the idempotency window has passed
+    let idempotency_key_exists: Option<(String,)> = sqlx::query_as(
+        r#"
+        SELECT COLUMN_NAME FROM INFORMATION_SCHEMA.COLUMNS
+        WHERE TABLE_SCHEMA = DATABASE()
+        AND TABLE_NAME = 'transfers'
+        AND COLUMN_NAME = 'idempotency_key'
+        "#,
+    )
+    .fetch_optional(pool)
+    .await?;
+
+    if idempotency_key_exists.is_none() {
+        sqlx::query(
+            "ALTER TABLE transfers ADD COLUMN idempotency_key VARCHAR(255) NULL, ADD UNIQUE KEY uq_transfers_idempotency (initiated_by, idempotency_key)"
+        )
+        .execute(pool)
+        .await?;
+        tracing::info!("Added idempotency_key column to transfers table");
+    }
+
+    // Sapling notes share orchard_notes; every Orchard query filters on pool
+    let pool_column_exists: Option<(String,)> = sqlx::query_as(
+        r#"
+        SELECT COLUMN_NAME FROM INFORMATION_SCHEMA.COLUMNS
+        WHERE TABLE_SCHEMA = DATABASE()
+        AND TABLE_NAME = 'orchard_notes'
+        AND COLUMN_NAME = 'pool'
+        "#,
+    )
+    .fetch_optional(pool)
+    .await?;
+
+    if pool_column_exists.is_none() {
+        sqlx::query(
+            "ALTER TABLE orchard_notes ADD COLUMN pool VARCHAR(16) NOT NULL DEFAULT 'orchard'"
+        )
+        .execute(pool)
+        .await?;
+        tracing::info!("Added pool column to orchard_notes table");
+    }
+
+    // Periodic snapshots of the Orchard tree and note witnesses, so rescans and
+    // restarts resume from a recent height instead of the earliest note
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS orchard_tree_checkpoints (
+            height BIGINT UNSIGNED PRIMARY KEY,
+            tree_data MEDIUMBLOB NOT NULL COMMENT 'Serialized CommitmentTree frontier',
+            tree_size BIGINT UNSIGNED NOT NULL COMMENT 'Number of commitments in tree',
+            witnesses LONGTEXT NOT NULL COMMENT 'JSON list of note witnesses at this height',
+            created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
+        )
+        "#,
+    )
+    .execute(pool)
+    .await?;
+
+    // Webhook deliveries that still failed after every retry, kept for inspection and replay
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS webhook_dead_letters (
+            id INT PRIMARY KEY AUTO_INCREMENT,
+            event VARCHAR(64) NOT NULL,
+            url VARCHAR(512) NOT NULL,
+            payload JSON NOT NULL,
+            attempts INT NOT NULL,
+            last_error TEXT,
+            created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
+        )
+        "#,
+    )
+    .execute(pool)
+    .await?;
+
+    // Refresh tokens, stored as SHA-256 hashes. A login starts a family; each refresh
+    // revokes the presented token and issues the next one in the same family.
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS refresh_tokens (
+            id INT PRIMARY KEY AUTO_INCREMENT,
+            token_hash CHAR(64) NOT NULL UNIQUE,
+            user_id INT NOT NULL,
+            family_id VARCHAR(32) NOT NULL,
+            token_epoch INT NOT NULL,
+            expires_at TIMESTAMP NOT NULL,
+            revoked_at TIMESTAMP NULL,
+            created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+            INDEX idx_family_id (family_id),
+            INDEX idx_expires_at (expires_at)
+        )
+        "#,
+    )
+    .execute(pool)
+    .await?;
+
+    // Capabilities per user role; admin implicitly holds all of them
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS role_permissions (
+            role VARCHAR(20) NOT NULL,
+            permission VARCHAR(64) NOT NULL,
+            PRIMARY KEY (role, permission)
+        )
+        "#,
+    )
+    .execute(pool)
+    .await?;
+
+    // Seed defaults only into an empty table so later edits are not undone on restart
+    let (role_permission_count,): (i64,) = sqlx::query_as("SELECT COUNT(*) FROM role_permissions")
+        .fetch_one(pool)
+        .await?;
+    if role_permission_count == 0 {
+        for (role, permission) in crate::services::permissions::DEFAULT_ROLE_PERMISSIONS {
+            sqlx::query("INSERT INTO role_permissions (role, permission) VALUES (?, ?)")
+                .bind(role)
+                .bind(permission.as_str())
+                .execute(pool)
+                .await?;
+        }
+        tracing::info!("Seeded default role permissions");
+    }
+
+    // Wallets skipped by the background Orchard sync until resumed
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS orchard_sync_paused (
+            wallet_id INT PRIMARY KEY,
+            paused_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+            FOREIGN KEY (wallet_id) REFERENCES wallets(id) ON DELETE CASCADE
+        )
+        "#,
+    )
+    .execute(pool)
+    .await?;
+
+    // Add nonce column to transfers table if not exists
+    // Lets the status checker tell a dropped/replaced EVM transaction from a slow one
+    let transfer_nonce_exists: Option<(String,)> = sqlx::query_as(
+        r#"
+        SELECT COLUMN_NAME FROM INFORMATION_SCHEMA.COLUMNS
+        WHERE TABLE_SCHEMA = DATABASE()
+        AND TABLE_NAME = 'transfers'
+        AND COLUMN_NAME = 'nonce'
+        "#,
+    )
+    .fetch_optional(pool)
+    .await?;
+
+    if transfer_nonce_exists.is_none() {
+        sqlx::query("ALTER TABLE transfers ADD COLUMN nonce BIGINT NULL")
+            .execute(pool)
+            .await?;
+        tracing::info!("Added nonce column to transfers table");
+    }
+
+    // Free-form labels for grouping wallets; indexed by tag for filtered listing
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS wallet_tags (
+            wallet_id INT NOT NULL,
+            tag VARCHAR(32) NOT NULL,
+            PRIMARY KEY (wallet_id, tag),
+            INDEX idx_wallet_tags_tag (tag),
+            FOREIGN KEY (wallet_id) REFERENCES wallets(id) ON DELETE CASCADE
+        )
+        "#,
+    )
+    .execute(pool)
+    .await?;
+
+    // Per-wallet caps on what a token may send per transfer and per rolling window
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS wallet_spending_limits (
+            wallet_id INT NOT NULL,
+            token VARCHAR(20) NOT NULL,
+            max_per_transfer DECIMAL(36, 18) NULL,
+            max_per_window DECIMAL(36, 18) NULL,
+            window_secs BIGINT NOT NULL,
+            updated_by INT NULL,
+            updated_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP ON UPDATE CURRENT_TIMESTAMP,
+            PRIMARY KEY (wallet_id, token),
+            FOREIGN KEY (wallet_id) REFERENCES wallets(id) ON DELETE CASCADE
+        )
+        "#,
+    )
+    .execute(pool)
+    .await?;
+
+    // Add limit_override_by column to transfers table if not exists
+    // Set when an admin lets a single transfer exceed its wallet's spending limit
+    let limit_override_exists: Option<(String,)> = sqlx::query_as(
+        r#"
+        SELECT COLUMN_NAME FROM INFORMATION_SCHEMA.COLUMNS
+        WHERE TABLE_SCHEMA = DATABASE()
+        AND TABLE_NAME = 'transfers'
+        AND COLUMN_NAME = 'limit_override_by'
+        "#,
+    )
+    .fetch_optional(pool)
+    .await?;
+
+    if limit_override_exists.is_none() {
+        sqlx::query("ALTER TABLE transfers ADD COLUMN limit_override_by INT NULL")
+            .execute(pool)
+            .await?;
+        tracing::info!("Added limit_override_by column to transfers table");
+    }
+
+    // Named recipients; in whitelist-only mode transfers may only go to these
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS address_book (
+            id INT AUTO_INCREMENT PRIMARY KEY,
+            label VARCHAR(64) NOT NULL,
+            chain VARCHAR(20) NOT NULL,
+            address VARCHAR(255) NOT NULL,
+            created_by INT NULL,
+            created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+            UNIQUE KEY uk_address_book_label (chain, label),
+            INDEX idx_address_book_address (chain, address)
+        )
+        "#,
+    )
+    .execute(pool)
+    .await?;
+
+    // Incoming transparent Zcash outputs found by the deposit watcher, one row per outpoint
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS transparent_deposits (
+            id INT AUTO_INCREMENT PRIMARY KEY,
+            wallet_id INT NOT NULL,
+            address VARCHAR(255) NOT NULL,
+            txid VARCHAR(64) NOT NULL,
+            output_index INT UNSIGNED NOT NULL,
+            value_zatoshis BIGINT UNSIGNED NOT NULL,
+            block_height BIGINT UNSIGNED NOT NULL,
+            detected_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+            UNIQUE KEY uk_deposit_outpoint (txid, output_index),
+            INDEX idx_deposit_wallet (wallet_id),
+            FOREIGN KEY (wallet_id) REFERENCES wallets(id) ON DELETE CASCADE
+        )
+        "#,
+    )
+    .execute(pool)
+    .await?;
+
+    // Block each wallet's t-address has been checked for deposits up to
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS deposit_scan_state (
+            wallet_id INT PRIMARY KEY,
+            scanned_height BIGINT UNSIGNED NOT NULL,
+            updated_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP ON UPDATE CURRENT_TIMESTAMP,
+            FOREIGN KEY (wallet_id) REFERENCES wallets(id) ON DELETE CASCADE
+        )
+        "#,
+    )
+    .execute(pool)
+    .await?;
+
+    // Receive addresses derived from a mnemonic wallet's seed, credited to that wallet
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS wallet_derived_addresses (
+            id INT AUTO_INCREMENT PRIMARY KEY,
+            wallet_id INT NOT NULL,
+            derivation_index INT UNSIGNED NOT NULL,
+            address VARCHAR(42) NOT NULL,
+            created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+            UNIQUE KEY uk_derived_index (wallet_id, derivation_index),
+            UNIQUE KEY uk_derived_address (address),
+            FOREIGN KEY (wallet_id) REFERENCES wallets(id) ON DELETE CASCADE
+        )
+        "#,
+    )
+    .execute(pool)
+    .await?;
+
+    // Add must_change_password column to users table if not exists
+    // Set for the seeded admin; such a user can only change their password until they do
+    let must_change_password_exists: Option<(String,)> = sqlx::query_as(
+        r#"
+        SELECT COLUMN_NAME FROM INFORMATION_SCHEMA.COLUMNS
+        WHERE TABLE_SCHEMA = DATABASE()
+        AND TABLE_NAME = 'users'
+        AND COLUMN_NAME = 'must_change_password'
+        "#,
+    )
+    .fetch_optional(pool)
+    .await?;
+
+    if must_change_password_exists.is_none() {
+        sqlx::query(
+            "ALTER TABLE users ADD COLUMN must_change_password BOOLEAN NOT NULL DEFAULT FALSE"
+        )
+        .execute(pool)
+        .await?;
+        tracing::info!("Added must_change_password column to users table");
+    }
+
+    // Add proposal_id column to transfers table if not exists
+    // Links a privacy transfer awaiting approval to the proposal that will send it
+    let proposal_id_exists: Option<(String,)> = sqlx::query_as(
+        r#"
+        SELECT COLUMN_NAME FROM INFORMATION_SCHEMA.COLUMNS
+        WHERE TABLE_SCHEMA = DATABASE()
+        AND TABLE_NAME = 'transfers'
+        AND COLUMN_NAME = 'proposal_id'
+        "#,
+    )
+    .fetch_optional(pool)
+    .await?;
+
+    if proposal_id_exists.is_none() {
+        sqlx::query("ALTER TABLE transfers ADD COLUMN proposal_id VARCHAR(64) NULL")
+            .execute(pool)
+            .await?;
+        tracing::info!("Added proposal_id column to transfers table");
+    }
+
+    // Add submitted_at column to transfers table if not exists
+    // When the transaction was broadcast; updated_at also moves on nonce and block updates
+    let submitted_at_exists: Option<(String,)> = sqlx::query_as(
+        r#"
+        SELECT COLUMN_NAME FROM INFORMATION_SCHEMA.COLUMNS
+        WHERE TABLE_SCHEMA = DATABASE()
+        AND TABLE_NAME = 'transfers'
+        AND COLUMN_NAME = 'submitted_at'
+        "#,
+    )
+    .fetch_optional(pool)
+    .await?;
+
+    if submitted_at_exists.is_none() {
+        sqlx::query("ALTER TABLE transfers ADD COLUMN submitted_at TIMESTAMP NULL")
+            .execute(pool)
+            .await?;
+        sqlx::query("UPDATE transfers SET submitted_at = updated_at WHERE tx_hash IS NOT NULL")
+            .execute(pool)
+            .await?;
+        tracing::info!("Added submitted_at column to transfers table");
+    }
+
+    // Transactions a transfer's fee bumps replaced; any of them may still be the one mined
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS transfer_replaced_txs (
+            id INT AUTO_INCREMENT PRIMARY KEY,
+            transfer_id INT NOT NULL,
+            tx_hash VARCHAR(128) NOT NULL,
+            replaced_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+            INDEX idx_transfer_replaced_txs_transfer (transfer_id)
+        )
+        "#,
+    )
+    .execute(pool)
+    .await?;
+
     tracing::info!("Database migrations completed successfully");
     Ok(())
 }
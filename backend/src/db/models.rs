@@ -3,6 +3,10 @@ use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
 use sqlx::FromRow;
 
+use crate::crypto::keystore::Keystore;
+use crate::crypto::units::format_units_display;
+use crate::error::{AppError, AppResult};
+
 #[derive(Debug, Clone, Serialize, Deserialize, sqlx::Type, PartialEq)]
 #[sqlx(type_name = "VARCHAR")]
 #[sqlx(rename_all = "lowercase")]
@@ -24,6 +28,10 @@ pub struct User {
     #[serde(skip_serializing)]
     pub password_hash: String,
     pub role: String,
+    #[serde(skip_serializing)]
+    pub token_epoch: i32,
+    /// Only the password may be changed until this is cleared
+    pub must_change_password: bool,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
@@ -33,6 +41,7 @@ pub struct UserResponse {
     pub id: i32,
     pub username: String,
     pub role: String,
+    pub must_change_password: bool,
 }
 
 impl From<User> for UserResponse {
@@ -41,6 +50,7 @@ impl From<User> for UserResponse {
             id: user.id,
             username: user.username,
             role: user.role,
+            must_change_password: user.must_change_password,
         }
     }
 }
@@ -57,6 +67,30 @@ pub struct Wallet {
     pub created_at: DateTime<Utc>,
     /// Block height when wallet was created (for Zcash Orchard scanning)
     pub orchard_birthday_height: Option<u64>,
+    /// Encrypted BIP39 phrase (only for wallets created/imported from a mnemonic)
+    #[serde(skip_serializing)]
+    pub encrypted_mnemonic: Option<String>,
+    /// BIP44 account index the key was derived at from the mnemonic (unknown for
+    /// mnemonic wallets stored before it was recorded)
+    pub mnemonic_account_index: Option<u32>,
+    /// Imported from a viewing key: can scan and show balances but never sign
+    pub watch_only: bool,
+    /// Encrypted Orchard viewing key (only for watch-only wallets)
+    #[serde(skip_serializing)]
+    pub encrypted_viewing_key: Option<String>,
+}
+
+impl Wallet {
+    /// Reject signing with a watch-only wallet, which has no spending key
+    pub fn ensure_can_sign(&self) -> AppResult<()> {
+        if self.watch_only {
+            return Err(AppError::ValidationError(format!(
+                "Wallet {} is watch-only and cannot send transactions",
+                self.id
+            )));
+        }
+        Ok(())
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -68,6 +102,10 @@ pub struct WalletResponse {
     pub is_active: bool,
     pub created_at: DateTime<Utc>,
     pub orchard_birthday_height: Option<u64>,
+    pub has_mnemonic: bool,
+    pub watch_only: bool,
+    #[serde(default)]
+    pub tags: Vec<String>,
 }
 
 impl From<Wallet> for WalletResponse {
@@ -80,10 +118,50 @@ impl From<Wallet> for WalletResponse {
             is_active: wallet.is_active,
             created_at: wallet.created_at,
             orchard_birthday_height: wallet.orchard_birthday_height,
+            has_mnemonic: wallet.encrypted_mnemonic.is_some(),
+            watch_only: wallet.watch_only,
+            tags: Vec::new(),
         }
     }
 }
 
+/// Most tags a single wallet can carry
+pub const MAX_WALLET_TAGS: usize = 16;
+
+/// Longest accepted tag, in characters
+pub const MAX_WALLET_TAG_LEN: usize = 32;
+
+/// Lowercase, trim and de-duplicate tags, rejecting anything outside `[a-z0-9_-]`
+pub fn normalize_wallet_tags(tags: &[String]) -> AppResult<Vec<String>> {
+    let mut normalized: Vec<String> = Vec::new();
+    for tag in tags {
+        let tag = tag.trim().to_lowercase();
+        if tag.is_empty() || tag.len() > MAX_WALLET_TAG_LEN {
+            return Err(AppError::ValidationError(format!(
+                "Tags must be 1 to {} characters",
+                MAX_WALLET_TAG_LEN
+            )));
+        }
+        if !tag.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-') {
+            return Err(AppError::ValidationError(format!(
+                "Tag '{}' may only contain letters, digits, '_' and '-'",
+                tag
+            )));
+        }
+        if !normalized.contains(&tag) {
+            normalized.push(tag);
+        }
+    }
+    if normalized.len() > MAX_WALLET_TAGS {
+        return Err(AppError::ValidationError(format!(
+            "A wallet can have at most {} tags",
+            MAX_WALLET_TAGS
+        )));
+    }
+    normalized.sort();
+    Ok(normalized)
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, sqlx::Type, PartialEq)]
 #[sqlx(type_name = "VARCHAR")]
 #[sqlx(rename_all = "lowercase")]
@@ -117,10 +195,214 @@ pub struct Transfer {
     pub block_number: Option<i64>,
     pub error_message: Option<String>,
     pub initiated_by: i32,
+    /// Account nonce of the broadcast transaction (EVM chains)
+    pub nonce: Option<i64>,
+    /// Fee paid in the chain's smallest unit (wei, zatoshis)
+    pub fee_native: Option<Decimal>,
+    /// Fee in native coin for display, e.g. "0.00042 ETH"
+    pub fee_display: Option<String>,
+    /// `None` when no approval is needed, else "required", "approved" or "rejected"
+    pub approval_status: Option<String>,
+    /// User who approved or rejected the transfer
+    pub approved_by: Option<i32>,
+    pub approved_at: Option<DateTime<Utc>>,
+    /// Admin who let this transfer exceed the wallet's spending limit
+    pub limit_override_by: Option<i32>,
+    /// Privacy transfer proposal that sends this transfer once approved
+    pub proposal_id: Option<String>,
+    /// When the transaction (or its latest replacement) was broadcast
+    pub submitted_at: Option<DateTime<Utc>>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
 
+impl Transfer {
+    /// Refuse to sign a transfer still waiting for (or denied) approval
+    pub fn ensure_approved(&self) -> AppResult<()> {
+        match self.approval_status.as_deref() {
+            None | Some("approved") => Ok(()),
+            Some(status) => Err(AppError::Forbidden(format!(
+                "Transfer {} cannot be executed: approval {}",
+                self.id, status
+            ))),
+        }
+    }
+
+    /// Refuse to send a privacy transfer's row directly; its proposal builds the transaction
+    pub fn ensure_not_proposal(&self) -> AppResult<()> {
+        match &self.proposal_id {
+            None => Ok(()),
+            Some(proposal_id) => Err(AppError::ValidationError(format!(
+                "Transfer {} is sent by executing privacy proposal {}",
+                self.id, proposal_id
+            ))),
+        }
+    }
+}
+
+/// Spending cap on one token of a wallet; either limit may be unset
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct SpendingLimit {
+    pub wallet_id: i32,
+    pub token: String,
+    /// Largest single transfer
+    pub max_per_transfer: Option<Decimal>,
+    /// Most that may be sent within any `window_secs` period
+    pub max_per_window: Option<Decimal>,
+    pub window_secs: i64,
+    pub updated_by: Option<i32>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl SpendingLimit {
+    /// Reject sending `amount` when `spent` has already gone out in the current window
+    pub fn check(&self, amount: Decimal, spent: Decimal) -> AppResult<()> {
+        if let Some(max) = self.max_per_transfer {
+            if amount > max {
+                return Err(AppError::SpendingLimitExceeded(format!(
+                    "{} {} exceeds wallet {}'s limit of {} per transfer",
+                    amount, self.token, self.wallet_id, max
+                )));
+            }
+        }
+        if let Some(max) = self.max_per_window {
+            if spent + amount > max {
+                return Err(AppError::SpendingLimitExceeded(format!(
+                    "{} {} would bring wallet {}'s spending to {} in the last {}s, above its limit of {}",
+                    amount,
+                    self.token,
+                    self.wallet_id,
+                    spent + amount,
+                    self.window_secs,
+                    max
+                )));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Token a spending limit is set on for transfers of `token`
+///
+/// Shielded Zcash transfers are recorded as `ZEC-shielded` but spend the same coin,
+/// so they fall under the wallet's `ZEC` limit.
+pub fn spending_limit_token(token: &str) -> String {
+    let token = token.to_uppercase();
+    match token.strip_suffix("-SHIELDED") {
+        Some(coin) => coin.to_string(),
+        None => token,
+    }
+}
+
+/// Default spending window: one day
+pub const DEFAULT_SPENDING_WINDOW_SECS: u64 = 24 * 3600;
+
+/// Create or replace the limit on one token of a wallet
+#[derive(Debug, Clone, Deserialize)]
+pub struct SetSpendingLimitRequest {
+    pub token: String,
+    pub max_per_transfer: Option<String>,
+    pub max_per_window: Option<String>,
+    /// Rolling window for `max_per_window`, one day when omitted
+    pub window_secs: Option<u64>,
+}
+
+/// Named recipient that transfers may address by label
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct AddressBookEntry {
+    pub id: i32,
+    pub label: String,
+    pub chain: String,
+    pub address: String,
+    pub created_by: Option<i32>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct CreateAddressBookEntryRequest {
+    pub label: String,
+    pub chain: String,
+    pub address: String,
+}
+
+/// Longest accepted address book label, in characters
+pub const MAX_ADDRESS_LABEL_LEN: usize = 64;
+
+/// Trim a label and reject empty, overlong or control-character labels
+pub fn normalize_address_label(label: &str) -> AppResult<String> {
+    let label = label.trim();
+    if label.is_empty() || label.chars().count() > MAX_ADDRESS_LABEL_LEN {
+        return Err(AppError::ValidationError(format!(
+            "Labels must be 1 to {} characters",
+            MAX_ADDRESS_LABEL_LEN
+        )));
+    }
+    if label.chars().any(char::is_control) {
+        return Err(AppError::ValidationError(
+            "Labels may not contain control characters".to_string(),
+        ));
+    }
+    Ok(label.to_string())
+}
+
+/// Transparent Zcash output received by a wallet, found by the deposit watcher
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct TransparentDeposit {
+    pub id: i32,
+    pub wallet_id: i32,
+    pub address: String,
+    pub txid: String,
+    pub output_index: u32,
+    pub value_zatoshis: u64,
+    pub block_height: u64,
+    pub detected_at: DateTime<Utc>,
+}
+
+/// Receive address derived from a mnemonic wallet's seed at a BIP44 index
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct DerivedAddress {
+    pub id: i32,
+    pub wallet_id: i32,
+    pub derivation_index: u32,
+    pub address: String,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Highest non-hardened BIP32 child index
+pub const MAX_DERIVATION_INDEX: u32 = (1 << 31) - 1;
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct DeriveAddressRequest {
+    pub index: u32,
+}
+
+/// Chain-independent representation of a transfer fee
+#[derive(Debug, Clone, PartialEq)]
+pub struct NativeFee {
+    /// Fee in the chain's smallest unit
+    pub units: Decimal,
+    pub display: String,
+}
+
+impl NativeFee {
+    /// Build from smallest units; `None` if the value does not fit a `Decimal`
+    pub fn new(units: u128, decimals: u32, symbol: &str) -> Option<Self> {
+        let units_decimal = i128::try_from(units)
+            .ok()
+            .and_then(|u| Decimal::try_from_i128_with_scale(u, 0).ok())?;
+
+        Some(Self {
+            units: units_decimal,
+            display: Self::display(units, decimals, symbol, None),
+        })
+    }
+
+    /// Display string of a fee, with `places` decimals or full precision
+    pub fn display(units: u128, decimals: u32, symbol: &str, places: Option<u32>) -> String {
+        format!("{} {}", format_units_display(units, decimals, places), symbol)
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
 pub struct AuditLog {
     pub id: i32,
@@ -142,14 +424,46 @@ pub struct LoginRequest {
 #[derive(Debug, Clone, Serialize)]
 pub struct LoginResponse {
     pub token: String,
+    /// Only issued when refresh tokens are enabled
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub refresh_token: Option<String>,
+    /// Access token lifetime in seconds
+    pub expires_in: i64,
     pub user: UserResponse,
 }
 
+#[derive(Debug, Clone, Deserialize)]
+pub struct RefreshTokenRequest {
+    pub refresh_token: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TokenResponse {
+    pub token: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub refresh_token: Option<String>,
+    pub expires_in: i64,
+}
+
 #[derive(Debug, Clone, Deserialize)]
 pub struct CreateWalletRequest {
     pub name: String,
     #[serde(default = "default_chain")]
     pub chain: String,
+    /// Generate the wallet from a new BIP39 mnemonic
+    #[serde(default)]
+    pub mnemonic: bool,
+    /// Mnemonic length (12 or 24 words, default 12)
+    pub word_count: Option<usize>,
+}
+
+/// Response for wallet creation; includes the phrase once when created from a mnemonic
+#[derive(Debug, Clone, Serialize)]
+pub struct CreateWalletResponse {
+    #[serde(flatten)]
+    pub wallet: WalletResponse,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mnemonic: Option<String>,
 }
 
 fn default_chain() -> String {
@@ -162,6 +476,44 @@ pub struct ImportWalletRequest {
     pub private_key: String,
     #[serde(default = "default_chain")]
     pub chain: String,
+    /// Zcash only: first block to scan for shielded funds (defaults to the chain tip)
+    #[serde(default)]
+    pub birthday_height: Option<u64>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ImportViewingKeyRequest {
+    pub name: String,
+    /// Encoded Orchard viewing key (`ufvk:...`)
+    pub viewing_key: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ImportMnemonicRequest {
+    pub name: String,
+    pub mnemonic: String,
+    #[serde(default = "default_chain")]
+    pub chain: String,
+    #[serde(default)]
+    pub account_index: u32,
+}
+
+/// Import an Ethereum key from a v3 keystore file (geth, MetaMask)
+#[derive(Debug, Clone, Deserialize)]
+pub struct ImportKeystoreRequest {
+    pub name: String,
+    pub keystore: Keystore,
+    pub passphrase: String,
+    #[serde(default = "default_chain")]
+    pub chain: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ExportKeystoreRequest {
+    /// The caller's account password
+    pub password: String,
+    /// Passphrase the exported keystore is encrypted under
+    pub passphrase: String,
 }
 
 #[derive(Debug, Clone, Deserialize)]
@@ -172,6 +524,11 @@ pub struct ExportPrivateKeyRequest {
 #[derive(Debug, Clone, Serialize)]
 pub struct ExportPrivateKeyResponse {
     pub private_key: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mnemonic: Option<String>,
+    /// Account index to import the mnemonic with to get this wallet back
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub account_index: Option<u32>,
     pub warning: String,
 }
 
@@ -183,6 +540,49 @@ pub struct TransferRequest {
     pub amount: String,
     pub gas_price_gwei: Option<String>,
     pub gas_limit: Option<i64>,
+    /// Explicit confirmation for amounts above the configured threshold
+    #[serde(default)]
+    pub confirm_large: bool,
+    /// Send even if an identical transfer was just submitted
+    #[serde(default)]
+    pub allow_duplicate: bool,
+}
+
+/// Most recipients accepted in one batch transfer
+pub const MAX_BATCH_TRANSFER_ITEMS: usize = 100;
+
+/// Several transfers from the chain's active wallet, sent in one request
+#[derive(Debug, Clone, Deserialize)]
+pub struct BatchTransferRequest {
+    pub chain: String,
+    pub items: Vec<BatchTransferItem>,
+    /// Explicit confirmation for amounts above the configured threshold
+    #[serde(default)]
+    pub confirm_large: bool,
+    /// Send even if an identical transfer was just submitted
+    #[serde(default)]
+    pub allow_duplicate: bool,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct BatchTransferItem {
+    pub to_address: String,
+    pub token: String,
+    pub amount: String,
+}
+
+/// Outcome of one batch item; a failed item does not undo the others
+#[derive(Debug, Clone, Serialize)]
+pub struct BatchTransferResult {
+    pub index: usize,
+    pub to_address: String,
+    pub token: String,
+    pub amount: String,
+    pub transfer_id: Option<i32>,
+    /// `submitted`, `failed`, or `pending` while awaiting approval
+    pub status: String,
+    pub tx_hash: Option<String>,
+    pub error: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize)]
@@ -190,13 +590,42 @@ pub struct BalanceResponse {
     pub address: String,
     pub chain: String,
     pub native_balance: String,
+    /// `native_balance` rounded to the requested display precision
+    pub native_balance_display: String,
+    pub native_symbol: String,
+    pub native_decimals: u8,
     pub tokens: Vec<TokenBalance>,
 }
 
+/// A transfer as returned by the API, with its fee also priced in fiat
+#[derive(Debug, Clone, Serialize)]
+pub struct TransferResponse {
+    #[serde(flatten)]
+    pub transfer: Transfer,
+    /// Fee in the configured fiat currency, e.g. "1.26 USD"; left out when the fee
+    /// or the price is unknown, or on a test network
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fee_fiat: Option<String>,
+}
+
+/// Balance of a wallet summed over its own and its derived addresses
+#[derive(Debug, Clone, Serialize)]
+pub struct WalletBalanceResponse {
+    pub wallet_id: i32,
+    /// Totals; `address` is the wallet's own address
+    #[serde(flatten)]
+    pub total: BalanceResponse,
+    /// One entry per address, the wallet's own first
+    pub addresses: Vec<BalanceResponse>,
+}
+
 #[derive(Debug, Clone, Serialize)]
 pub struct TokenBalance {
     pub symbol: String,
     pub balance: String,
+    /// `balance` rounded to the requested display precision
+    pub balance_display: String,
+    pub decimals: u8,
     pub contract_address: Option<String>,
 }
 
@@ -205,3 +634,215 @@ pub struct ChangePasswordRequest {
     pub old_password: String,
     pub new_password: String,
 }
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct SignMessageRequest {
+    /// UTF-8 text signed as-is with the EIP-191 `personal_sign` prefix
+    pub message: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SignedMessageResponse {
+    pub address: String,
+    /// 65-byte `r || s || v` signature as 0x hex
+    pub signature: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct SignTypedDataRequest {
+    /// EIP-712 payload as an object or as the JSON string used by `eth_signTypedData_v4`
+    pub typed_data: serde_json::Value,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SignedTypedDataResponse {
+    pub address: String,
+    pub primary_type: String,
+    /// The EIP-712 digest that was signed, as 0x hex
+    pub digest: String,
+    pub signature: String,
+}
+
+/// Replaces a wallet's tags; an empty list clears them
+#[derive(Debug, Clone, Deserialize)]
+pub struct SetWalletTagsRequest {
+    pub tags: Vec<String>,
+}
+
+#[derive(Deserialize)]
+pub struct RotateEncryptionKeyRequest {
+    pub old_key: String,
+    pub new_key: String,
+}
+
+/// Model rows for tests; tests override the fields they exercise
+#[cfg(test)]
+pub mod test_fixtures {
+    use super::*;
+
+    /// A Zcash wallet row without key material
+    pub fn wallet(id: i32) -> Wallet {
+        Wallet {
+            id,
+            name: format!("wallet-{}", id),
+            address: format!("t1address{}", id),
+            encrypted_private_key: String::new(),
+            chain: "zcash".to_string(),
+            is_active: false,
+            created_at: Utc::now(),
+            orchard_birthday_height: None,
+            encrypted_mnemonic: None,
+            mnemonic_account_index: None,
+            watch_only: false,
+            encrypted_viewing_key: None,
+        }
+    }
+
+    /// A pending ETH transfer from wallet 1, initiated by user 7
+    pub fn transfer(id: i32) -> Transfer {
+        Transfer {
+            id,
+            wallet_id: 1,
+            chain: "ethereum".to_string(),
+            from_address: "0xsender".to_string(),
+            to_address: "0xrecipient".to_string(),
+            token: "ETH".to_string(),
+            amount: Decimal::from(20),
+            gas_price: None,
+            gas_limit: None,
+            gas_used: None,
+            status: "pending".to_string(),
+            tx_hash: None,
+            block_number: None,
+            error_message: None,
+            initiated_by: 7,
+            nonce: None,
+            fee_native: None,
+            fee_display: None,
+            approval_status: None,
+            approved_by: None,
+            approved_at: None,
+            limit_override_by: None,
+            proposal_id: None,
+            submitted_at: None,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crypto::units::{ETH_DECIMALS, ZEC_DECIMALS};
+
+    #[test]
+    fn test_native_fee_ethereum() {
+        // 21000 gas at 20 gwei
+        let fee = NativeFee::new(21_000u128 * 20_000_000_000, ETH_DECIMALS, "ETH").unwrap();
+        assert_eq!(fee.units, Decimal::from(420_000_000_000_000u64));
+        assert_eq!(fee.display, "0.00042 ETH");
+    }
+
+    #[test]
+    fn test_native_fee_zcash() {
+        let fee = NativeFee::new(15_000, ZEC_DECIMALS, "ZEC").unwrap();
+        assert_eq!(fee.units, Decimal::from(15_000u64));
+        assert_eq!(fee.display, "0.00015 ZEC");
+    }
+
+    #[test]
+    fn test_native_fee_display_precision() {
+        let units = 21_000u128 * 23_456_789_012;
+        let fee = NativeFee::new(units, ETH_DECIMALS, "ETH").unwrap();
+        assert_eq!(fee.display, "0.000492592569252 ETH");
+
+        // Rounding only affects the display string, never the stored units
+        assert_eq!(NativeFee::display(units, ETH_DECIMALS, "ETH", Some(4)), "0.0005 ETH");
+        assert_eq!(fee.units, Decimal::from(492_592_569_252_000u64));
+    }
+
+    #[test]
+    fn test_native_fee_out_of_range() {
+        assert!(NativeFee::new(u128::MAX, ETH_DECIMALS, "ETH").is_none());
+    }
+
+    fn transfer(approval_status: Option<&str>, proposal_id: Option<&str>) -> Transfer {
+        Transfer {
+            chain: "zcash".to_string(),
+            from_address: "u1sender".to_string(),
+            to_address: "u1recipient".to_string(),
+            token: "ZEC-shielded".to_string(),
+            approval_status: approval_status.map(str::to_string),
+            proposal_id: proposal_id.map(str::to_string),
+            ..test_fixtures::transfer(5)
+        }
+    }
+
+    #[test]
+    fn test_privacy_transfer_row_is_sent_by_its_proposal() {
+        let awaiting = transfer(Some("required"), Some("abc"));
+        assert!(matches!(awaiting.ensure_approved(), Err(AppError::Forbidden(_))));
+        assert!(awaiting.ensure_not_proposal().is_err());
+
+        let approved = transfer(Some("approved"), Some("abc"));
+        assert!(approved.ensure_approved().is_ok());
+        // Approved, but only the proposal's own execute may build it
+        assert!(matches!(approved.ensure_not_proposal(), Err(AppError::ValidationError(_))));
+
+        assert!(transfer(None, None).ensure_not_proposal().is_ok());
+    }
+
+    #[test]
+    fn test_shielded_transfers_share_the_coin_limit() {
+        assert_eq!(spending_limit_token("ZEC-shielded"), "ZEC");
+        assert_eq!(spending_limit_token("zec"), "ZEC");
+        assert_eq!(spending_limit_token("usdc"), "USDC");
+    }
+
+    #[test]
+    fn test_spending_limit_check() {
+        let limit = SpendingLimit {
+            wallet_id: 1,
+            token: "ETH".to_string(),
+            max_per_transfer: Some(Decimal::from(2)),
+            max_per_window: Some(Decimal::from(1)),
+            window_secs: DEFAULT_SPENDING_WINDOW_SECS as i64,
+            updated_by: None,
+            updated_at: Utc::now(),
+        };
+
+        assert!(limit.check(Decimal::from(1), Decimal::ZERO).is_ok());
+        // A second transfer the same day is blocked once the daily cap is reached
+        assert!(matches!(
+            limit.check(Decimal::new(1, 1), Decimal::from(1)),
+            Err(AppError::SpendingLimitExceeded(_))
+        ));
+
+        let per_transfer_only = SpendingLimit { max_per_window: None, ..limit };
+        assert!(per_transfer_only.check(Decimal::from(2), Decimal::from(100)).is_ok());
+        assert!(per_transfer_only.check(Decimal::from(3), Decimal::ZERO).is_err());
+    }
+
+    #[test]
+    fn test_normalize_address_label() {
+        assert_eq!(normalize_address_label("  Cold storage ").unwrap(), "Cold storage");
+        assert!(normalize_address_label("   ").is_err());
+        assert!(normalize_address_label("tab\there").is_err());
+        assert!(normalize_address_label(&"x".repeat(MAX_ADDRESS_LABEL_LEN + 1)).is_err());
+    }
+
+    #[test]
+    fn test_normalize_wallet_tags() {
+        let tags = vec![" Treasury ".to_string(), "hot".to_string(), "treasury".to_string()];
+        assert_eq!(normalize_wallet_tags(&tags).unwrap(), vec!["hot", "treasury"]);
+        assert!(normalize_wallet_tags(&[]).unwrap().is_empty());
+
+        assert!(normalize_wallet_tags(&["".to_string()]).is_err());
+        assert!(normalize_wallet_tags(&["cold storage".to_string()]).is_err());
+        assert!(normalize_wallet_tags(&["x".repeat(MAX_WALLET_TAG_LEN + 1)]).is_err());
+
+        let many: Vec<String> = (0..=MAX_WALLET_TAGS).map(|i| format!("t{}", i)).collect();
+        assert!(normalize_wallet_tags(&many).is_err());
+    }
+}
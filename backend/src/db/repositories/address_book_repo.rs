@@ -0,0 +1,85 @@
+use sqlx::MySqlPool;
+
+use crate::db::models::AddressBookEntry;
+use crate::error::AppResult;
+
+pub struct AddressBookRepository {
+    pool: MySqlPool,
+}
+
+impl AddressBookRepository {
+    pub fn new(pool: MySqlPool) -> Self {
+        Self { pool }
+    }
+
+    /// All entries, or those of one chain, ordered by label
+    pub async fn list(&self, chain: Option<&str>) -> AppResult<Vec<AddressBookEntry>> {
+        let entries = sqlx::query_as::<_, AddressBookEntry>(
+            "SELECT * FROM address_book WHERE (? IS NULL OR chain = ?) ORDER BY chain, label"
+        )
+        .bind(chain)
+        .bind(chain)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(entries)
+    }
+
+    pub async fn find_by_id(&self, id: i32) -> AppResult<Option<AddressBookEntry>> {
+        let entry = sqlx::query_as::<_, AddressBookEntry>("SELECT * FROM address_book WHERE id = ?")
+            .bind(id)
+            .fetch_optional(&self.pool)
+            .await?;
+
+        Ok(entry)
+    }
+
+    pub async fn find_by_label(&self, chain: &str, label: &str) -> AppResult<Option<AddressBookEntry>> {
+        let entry = sqlx::query_as::<_, AddressBookEntry>(
+            "SELECT * FROM address_book WHERE chain = ? AND label = ?"
+        )
+        .bind(chain)
+        .bind(label)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(entry)
+    }
+
+    /// First entry holding `address` on the chain (one address may carry several labels)
+    pub async fn find_by_address(&self, chain: &str, address: &str) -> AppResult<Option<AddressBookEntry>> {
+        let entry = sqlx::query_as::<_, AddressBookEntry>(
+            "SELECT * FROM address_book WHERE chain = ? AND address = ? ORDER BY id LIMIT 1"
+        )
+        .bind(chain)
+        .bind(address)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(entry)
+    }
+
+    pub async fn create(&self, label: &str, chain: &str, address: &str, created_by: i32) -> AppResult<i32> {
+        let result = sqlx::query(
+            "INSERT INTO address_book (label, chain, address, created_by) VALUES (?, ?, ?, ?)"
+        )
+        .bind(label)
+        .bind(chain)
+        .bind(address)
+        .bind(created_by)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.last_insert_id() as i32)
+    }
+
+    /// Returns false if no entry had this id
+    pub async fn delete(&self, id: i32) -> AppResult<bool> {
+        let result = sqlx::query("DELETE FROM address_book WHERE id = ?")
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+}
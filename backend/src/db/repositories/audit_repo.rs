@@ -0,0 +1,98 @@
+use chrono::{DateTime, Utc};
+use sqlx::{MySql, MySqlPool, QueryBuilder};
+
+use crate::db::models::AuditLog;
+use crate::error::AppResult;
+
+/// Conditions for listing audit entries; unset fields match everything
+#[derive(Debug, Default, Clone)]
+pub struct AuditFilter {
+    pub user_id: Option<i32>,
+    pub action: Option<String>,
+    /// Inclusive lower bound on `created_at`
+    pub from: Option<DateTime<Utc>>,
+    /// Exclusive upper bound on `created_at`
+    pub to: Option<DateTime<Utc>>,
+}
+
+impl AuditFilter {
+    fn push_conditions(&self, query: &mut QueryBuilder<'_, MySql>) {
+        if let Some(user_id) = self.user_id {
+            query.push(" AND user_id = ").push_bind(user_id);
+        }
+        if let Some(action) = &self.action {
+            query.push(" AND action = ").push_bind(action.clone());
+        }
+        if let Some(from) = self.from {
+            query.push(" AND created_at >= ").push_bind(from);
+        }
+        if let Some(to) = self.to {
+            query.push(" AND created_at < ").push_bind(to);
+        }
+    }
+}
+
+pub struct AuditRepository {
+    pool: MySqlPool,
+}
+
+impl AuditRepository {
+    pub fn new(pool: MySqlPool) -> Self {
+        Self { pool }
+    }
+
+    pub async fn record(
+        &self,
+        user_id: Option<i32>,
+        action: &str,
+        resource: Option<&str>,
+        details: &serde_json::Value,
+        ip_address: Option<&str>,
+    ) -> AppResult<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO audit_logs (user_id, action, resource, details, ip_address)
+            VALUES (?, ?, ?, ?, ?)
+            "#
+        )
+        .bind(user_id)
+        .bind(action)
+        .bind(resource)
+        .bind(details)
+        .bind(ip_address)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// One page of audit entries matching the filter, newest first
+    pub async fn list(&self, filter: &AuditFilter, limit: i32, offset: i32) -> AppResult<Vec<AuditLog>> {
+        let mut query = QueryBuilder::<MySql>::new("SELECT * FROM audit_logs WHERE 1 = 1");
+        filter.push_conditions(&mut query);
+        query
+            .push(" ORDER BY created_at DESC, id DESC LIMIT ")
+            .push_bind(limit)
+            .push(" OFFSET ")
+            .push_bind(offset);
+
+        let entries = query
+            .build_query_as::<AuditLog>()
+            .fetch_all(&self.pool)
+            .await?;
+
+        Ok(entries)
+    }
+
+    pub async fn count(&self, filter: &AuditFilter) -> AppResult<i64> {
+        let mut query = QueryBuilder::<MySql>::new("SELECT COUNT(*) FROM audit_logs WHERE 1 = 1");
+        filter.push_conditions(&mut query);
+
+        let count: (i64,) = query
+            .build_query_as()
+            .fetch_one(&self.pool)
+            .await?;
+
+        Ok(count.0)
+    }
+}
@@ -0,0 +1,94 @@
+use sqlx::MySqlPool;
+
+use crate::db::models::TransparentDeposit;
+use crate::error::AppResult;
+
+pub struct DepositRepository {
+    pool: MySqlPool,
+}
+
+impl DepositRepository {
+    pub fn new(pool: MySqlPool) -> Self {
+        Self { pool }
+    }
+
+    /// Record a deposit; returns None if its outpoint was already recorded
+    pub async fn insert_if_new(
+        &self,
+        wallet_id: i32,
+        address: &str,
+        txid: &str,
+        output_index: u32,
+        value_zatoshis: u64,
+        block_height: u64,
+    ) -> AppResult<Option<TransparentDeposit>> {
+        let result = sqlx::query(
+            r#"
+            INSERT IGNORE INTO transparent_deposits
+            (wallet_id, address, txid, output_index, value_zatoshis, block_height)
+            VALUES (?, ?, ?, ?, ?, ?)
+            "#
+        )
+        .bind(wallet_id)
+        .bind(address)
+        .bind(txid)
+        .bind(output_index)
+        .bind(value_zatoshis)
+        .bind(block_height)
+        .execute(&self.pool)
+        .await?;
+
+        if result.rows_affected() == 0 {
+            return Ok(None);
+        }
+        let deposit = sqlx::query_as::<_, TransparentDeposit>(
+            "SELECT * FROM transparent_deposits WHERE id = ?"
+        )
+        .bind(result.last_insert_id() as i32)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(deposit)
+    }
+
+    /// Block the wallet's t-address was last checked for deposits up to
+    pub async fn scanned_height(&self, wallet_id: i32) -> AppResult<Option<u64>> {
+        let height: Option<(u64,)> = sqlx::query_as(
+            "SELECT scanned_height FROM deposit_scan_state WHERE wallet_id = ?"
+        )
+        .bind(wallet_id)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(height.map(|(h,)| h))
+    }
+
+    pub async fn set_scanned_height(&self, wallet_id: i32, height: u64) -> AppResult<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO deposit_scan_state (wallet_id, scanned_height) VALUES (?, ?)
+            ON DUPLICATE KEY UPDATE scanned_height = VALUES(scanned_height)
+            "#
+        )
+        .bind(wallet_id)
+        .bind(height)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// A wallet's deposits, newest first
+    pub async fn list_by_wallet(&self, wallet_id: i32, limit: i32, offset: i32) -> AppResult<Vec<TransparentDeposit>> {
+        let deposits = sqlx::query_as::<_, TransparentDeposit>(
+            "SELECT * FROM transparent_deposits WHERE wallet_id = ? ORDER BY block_height DESC, id DESC LIMIT ? OFFSET ?"
+        )
+        .bind(wallet_id)
+        .bind(limit)
+        .bind(offset)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(deposits)
+    }
+}
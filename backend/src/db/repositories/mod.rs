@@ -3,9 +3,23 @@ pub mod wallet_repo;
 pub mod transfer_repo;
 pub mod settings_repo;
 pub mod orchard_repo;
+pub mod revoked_token_repo;
+pub mod refresh_token_repo;
+pub mod role_permission_repo;
+pub mod audit_repo;
+pub mod webhook_repo;
+pub mod address_book_repo;
+pub mod deposit_repo;
 
 pub use user_repo::UserRepository;
 pub use wallet_repo::WalletRepository;
-pub use transfer_repo::TransferRepository;
+pub use transfer_repo::{NewTransfer, TransferFilter, TransferRepository};
 pub use settings_repo::SettingsRepository;
 pub use orchard_repo::OrchardRepository;
+pub use revoked_token_repo::RevokedTokenRepository;
+pub use refresh_token_repo::RefreshTokenRepository;
+pub use role_permission_repo::RolePermissionRepository;
+pub use audit_repo::{AuditFilter, AuditRepository};
+pub use webhook_repo::WebhookRepository;
+pub use address_book_repo::AddressBookRepository;
+pub use deposit_repo::DepositRepository;
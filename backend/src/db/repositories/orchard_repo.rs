@@ -1,12 +1,26 @@
 //! Orchard notes and sync state repository
 //!
 //! Provides persistence for Orchard scan state and discovered notes.
+//! Sapling notes live in the same table with `pool = 'sapling'`.
 //! Some methods are reserved for future use (e.g., mark_note_spent).
 
 #![allow(dead_code)]
 
 use crate::error::AppResult;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
 use sqlx::MySqlPool;
+use std::collections::BTreeMap;
+
+/// Insert of a discovered note that leaves an existing row alone
+///
+/// sqlx connects with CLIENT_FOUND_ROWS, so an `ON DUPLICATE KEY UPDATE` that matches an
+/// existing row reports 1 affected row just like an insert; an ignored duplicate reports 0.
+const INSERT_NOTE_SQL: &str = r#"
+    INSERT IGNORE INTO orchard_notes
+        (wallet_id, nullifier, value_zatoshis, block_height, tx_hash, position_in_block, memo, recipient, rho, rseed, witness_position)
+    VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+"#;
 
 /// Stored Orchard note from database
 #[derive(Debug, Clone, sqlx::FromRow)]
@@ -20,7 +34,7 @@ pub struct StoredOrchardNote {
     pub position_in_block: u32,
     pub is_spent: bool,
     pub spent_in_tx: Option<String>,
-    pub memo: Option<String>,
+    pub memo: Option<String>,  // Hex-encoded 512-byte memo (ZIP 302), absent when empty
     // Spending data (for shielded-to-shielded transfers)
     pub recipient: Option<String>,  // Hex-encoded 43 bytes
     pub rho: Option<String>,        // Hex-encoded 32 bytes
@@ -29,6 +43,54 @@ pub struct StoredOrchardNote {
     pub witness_position: Option<u64>,
     pub witness_auth_path: Option<String>, // JSON array of hex-encoded 32-byte hashes
     pub witness_root: Option<String>,      // Hex-encoded 32-byte root
+    /// The incremental witness the sync keeps up to date has been saved
+    pub has_witness_state: bool,
+    /// Held for a transfer being built until then
+    pub reserved_until: Option<DateTime<Utc>>,
+}
+
+impl StoredOrchardNote {
+    /// Blocks mined on top of the note's block at chain height `tip`
+    pub fn confirmations(&self, tip: u64) -> u64 {
+        tip.saturating_sub(self.block_height)
+    }
+
+    /// Position, auth path and root have been filled in by the witness sync, and
+    /// the incremental witness it keeps them current with has been saved
+    pub fn has_witness(&self) -> bool {
+        self.witness_position.is_some()
+            && self.witness_auth_path.is_some()
+            && self.witness_root.is_some()
+            && self.has_witness_state
+    }
+
+    /// Held by an unexpired reservation of a transfer being built
+    pub fn is_reserved(&self, now: DateTime<Utc>) -> bool {
+        self.reserved_until.is_some_and(|until| until >= now)
+    }
+
+    /// Recipient, rho and rseed, needed to rebuild the note when spending it
+    pub fn has_spend_data(&self) -> bool {
+        self.recipient.is_some() && self.rho.is_some() && self.rseed.is_some()
+    }
+
+    /// Why the note cannot be spent yet at `tip`; empty when it can
+    pub fn spend_blockers(&self, tip: u64, min_confirmations: u32, now: DateTime<Utc>) -> Vec<&'static str> {
+        let mut blockers = Vec::new();
+        if self.confirmations(tip) < min_confirmations as u64 {
+            blockers.push("confirmations");
+        }
+        if !self.has_witness() {
+            blockers.push("witness");
+        }
+        if !self.has_spend_data() {
+            blockers.push("spend_data");
+        }
+        if self.is_reserved(now) {
+            blockers.push("reserved");
+        }
+        blockers
+    }
 }
 
 /// Sync state for a wallet
@@ -42,6 +104,36 @@ pub struct OrchardSyncState {
     pub last_witness_height: u64,
 }
 
+/// A transaction's net effect on one shielded pool of a wallet, for transaction history
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct ShieldedActivityRow {
+    pub tx_hash: String,
+    pub pool: String,
+    /// Value received minus value spent; negative for a send
+    pub net_zatoshis: i64,
+    /// `None` while a spend is unconfirmed
+    pub block_height: Option<u64>,
+}
+
+/// Notes received and spent by a wallet, netted per transaction and pool
+///
+/// A spend that returns change to the wallet nets to the amount sent plus fee. Binds
+/// the wallet id twice, then an optional pool twice.
+const SHIELDED_ACTIVITY_SQL: &str = r#"
+    SELECT tx_hash, pool, CAST(SUM(amount) AS SIGNED) AS net_zatoshis,
+           CAST(MAX(height) AS UNSIGNED) AS block_height
+    FROM (
+        SELECT tx_hash, pool, CAST(value_zatoshis AS SIGNED) AS amount, block_height AS height
+        FROM orchard_notes WHERE wallet_id = ?
+        UNION ALL
+        SELECT spent_in_tx, pool, -CAST(value_zatoshis AS SIGNED), spent_height
+        FROM orchard_notes WHERE wallet_id = ? AND spent_in_tx IS NOT NULL
+    ) moves
+    WHERE (? IS NULL OR pool = ?)
+    GROUP BY tx_hash, pool
+    HAVING net_zatoshis <> 0
+"#;
+
 /// Global tree state for incremental witness sync
 #[derive(Debug, Clone)]
 pub struct OrchardTreeState {
@@ -50,6 +142,15 @@ pub struct OrchardTreeState {
     pub tree_size: u64,
 }
 
+/// Tree and witness snapshot at a block height
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct OrchardTreeCheckpoint {
+    pub height: u64,
+    pub tree_data: Vec<u8>,
+    pub tree_size: u64,
+    pub witnesses: String,
+}
+
 /// Note info with witness state for incremental sync
 #[derive(Debug, Clone)]
 pub struct NoteWitnessInfo {
@@ -59,6 +160,20 @@ pub struct NoteWitnessInfo {
     pub witness_state: Option<Vec<u8>>,
 }
 
+/// A Sapling note found by trial decryption, to insert
+#[derive(Debug, Clone, Copy)]
+pub struct NewSaplingNote<'a> {
+    pub wallet_id: i32,
+    pub nullifier: &'a str,
+    pub value_zatoshis: u64,
+    pub block_height: u64,
+    pub tx_hash: &'a str,
+    /// Index in the Sapling commitment tree, which the nullifier depends on
+    pub position: u64,
+    pub recipient: &'a str,
+    pub rseed: &'a str,
+}
+
 pub struct OrchardRepository {
     pool: MySqlPool,
 }
@@ -143,6 +258,55 @@ impl OrchardRepository {
         Ok(result.map(|(h,)| h).unwrap_or(0))
     }
 
+    /// Pause or resume the background sync for a wallet
+    pub async fn set_sync_paused(&self, wallet_id: i32, paused: bool) -> AppResult<()> {
+        let query = if paused {
+            "INSERT IGNORE INTO orchard_sync_paused (wallet_id) VALUES (?)"
+        } else {
+            "DELETE FROM orchard_sync_paused WHERE wallet_id = ?"
+        };
+        sqlx::query(query).bind(wallet_id).execute(&self.pool).await?;
+        Ok(())
+    }
+
+    /// Wallets the background sync skips
+    pub async fn list_paused_wallets(&self) -> AppResult<Vec<i32>> {
+        let rows: Vec<(i32,)> = sqlx::query_as("SELECT wallet_id FROM orchard_sync_paused")
+            .fetch_all(&self.pool)
+            .await?;
+        Ok(rows.into_iter().map(|(id,)| id).collect())
+    }
+
+    /// Delete a wallet's notes and rewind its scan height, so the next sync finds them again
+    /// Returns the number of notes removed.
+    pub async fn reset_wallet_scan(&self, wallet_id: i32, last_scanned_height: u64) -> AppResult<u64> {
+        let mut tx = self.pool.begin().await?;
+
+        let removed = sqlx::query("DELETE FROM orchard_notes WHERE wallet_id = ?")
+            .bind(wallet_id)
+            .execute(&mut *tx)
+            .await?
+            .rows_affected();
+
+        sqlx::query(
+            r#"
+            INSERT INTO orchard_sync_state (wallet_id, last_scanned_height, notes_found, last_witness_height)
+            VALUES (?, ?, 0, 0)
+            ON DUPLICATE KEY UPDATE
+                last_scanned_height = VALUES(last_scanned_height),
+                notes_found = 0,
+                last_witness_height = 0
+            "#
+        )
+        .bind(wallet_id)
+        .bind(last_scanned_height)
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        Ok(removed)
+    }
+
     /// Batch update sync state for multiple wallets
     pub async fn batch_update_sync_height(&self, wallet_ids: &[i32], height: u64) -> AppResult<()> {
         if wallet_ids.is_empty() {
@@ -203,6 +367,7 @@ impl OrchardRepository {
     ///
     /// The witness_position is the global position in the Orchard commitment tree,
     /// saved at discovery time to enable fast witness refresh without re-scanning.
+    /// Returns true when the nullifier was not stored before, false on a rescan.
     pub async fn save_note_full(
         &self,
         wallet_id: i32,
@@ -216,33 +381,88 @@ impl OrchardRepository {
         rho: &str,
         rseed: &str,
         witness_position: u64,  // Global tree position, saved at discovery
-    ) -> AppResult<i32> {
-        let result = sqlx::query(
+    ) -> AppResult<bool> {
+        let inserted = sqlx::query(INSERT_NOTE_SQL)
+            .bind(wallet_id)
+            .bind(nullifier)
+            .bind(value_zatoshis)
+            .bind(block_height)
+            .bind(tx_hash)
+            .bind(position_in_block)
+            .bind(memo)
+            .bind(recipient)
+            .bind(rho)
+            .bind(rseed)
+            .bind(witness_position)
+            .execute(&self.pool)
+            .await?
+            .rows_affected()
+            == 1;
+
+        if !inserted {
+            // Rescanned note: refresh the spending data, which older scans did not store
+            sqlx::query(
+                r#"
+                UPDATE orchard_notes
+                SET recipient = ?, rho = ?, rseed = ?, witness_position = ?
+                WHERE nullifier = ?
+                "#
+            )
+            .bind(recipient)
+            .bind(rho)
+            .bind(rseed)
+            .bind(witness_position)
+            .bind(nullifier)
+            .execute(&self.pool)
+            .await?;
+        }
+
+        Ok(inserted)
+    }
+
+    /// Save a Sapling note found by trial decryption
+    ///
+    /// Sapling notes are tracked for balance reporting only.
+    pub async fn save_sapling_note(&self, note: NewSaplingNote<'_>) -> AppResult<()> {
+        sqlx::query(
             r#"
             INSERT INTO orchard_notes
-                (wallet_id, nullifier, value_zatoshis, block_height, tx_hash, position_in_block, memo, recipient, rho, rseed, witness_position)
-            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
-            ON DUPLICATE KEY UPDATE
-                recipient = VALUES(recipient),
-                rho = VALUES(rho),
-                rseed = VALUES(rseed),
-                witness_position = VALUES(witness_position)
+                (wallet_id, nullifier, value_zatoshis, block_height, tx_hash, position_in_block,
+                 recipient, rseed, witness_position, pool)
+            VALUES (?, ?, ?, ?, ?, 0, ?, ?, ?, 'sapling')
+            ON DUPLICATE KEY UPDATE id = id
             "#
         )
-        .bind(wallet_id)
-        .bind(nullifier)
-        .bind(value_zatoshis)
-        .bind(block_height)
-        .bind(tx_hash)
-        .bind(position_in_block)
-        .bind(memo)
-        .bind(recipient)
-        .bind(rho)
-        .bind(rseed)
-        .bind(witness_position)
+        .bind(note.wallet_id)
+        .bind(note.nullifier)
+        .bind(note.value_zatoshis)
+        .bind(note.block_height)
+        .bind(note.tx_hash)
+        .bind(note.recipient)
+        .bind(note.rseed)
+        .bind(note.position)
         .execute(&self.pool)
         .await?;
-        Ok(result.last_insert_id() as i32)
+        Ok(())
+    }
+
+    /// Get all unspent Sapling notes for a wallet
+    pub async fn get_unspent_sapling_notes(&self, wallet_id: i32) -> AppResult<Vec<StoredOrchardNote>> {
+        let notes = sqlx::query_as::<_, StoredOrchardNote>(
+            r#"
+            SELECT id, wallet_id, nullifier, value_zatoshis, block_height, tx_hash,
+                   position_in_block, is_spent, spent_in_tx, memo, recipient, rho, rseed,
+                   witness_position, witness_auth_path, witness_root,
+                   witness_state IS NOT NULL AS has_witness_state, reserved_until
+            FROM orchard_notes
+            WHERE wallet_id = ? AND is_spent = FALSE AND pool = 'sapling'
+            ORDER BY block_height ASC
+            "#
+        )
+        .bind(wallet_id)
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(notes)
     }
 
     /// Batch save multiple notes
@@ -266,9 +486,10 @@ impl OrchardRepository {
             r#"
             SELECT id, wallet_id, nullifier, value_zatoshis, block_height, tx_hash,
                    position_in_block, is_spent, spent_in_tx, memo, recipient, rho, rseed,
-                   witness_position, witness_auth_path, witness_root
+                   witness_position, witness_auth_path, witness_root,
+                   witness_state IS NOT NULL AS has_witness_state, reserved_until
             FROM orchard_notes
-            WHERE wallet_id = ? AND is_spent = FALSE
+            WHERE wallet_id = ? AND is_spent = FALSE AND pool = 'orchard'
             ORDER BY block_height ASC
             "#
         )
@@ -278,16 +499,75 @@ impl OrchardRepository {
         Ok(notes)
     }
 
+    /// Get a single note of any pool by id
+    pub async fn get_note_by_id(&self, id: i32) -> AppResult<Option<StoredOrchardNote>> {
+        let note = sqlx::query_as::<_, StoredOrchardNote>(
+            r#"
+            SELECT id, wallet_id, nullifier, value_zatoshis, block_height, tx_hash,
+                   position_in_block, is_spent, spent_in_tx, memo, recipient, rho, rseed,
+                   witness_position, witness_auth_path, witness_root,
+                   witness_state IS NOT NULL AS has_witness_state, reserved_until
+            FROM orchard_notes
+            WHERE id = ?
+            "#
+        )
+        .bind(id)
+        .fetch_optional(&self.pool)
+        .await?;
+        Ok(note)
+    }
+
+    /// Get a page of a wallet's shielded activity, newest first, in one pool or both
+    ///
+    /// Unconfirmed spends come first; the hash keeps the order stable between pages.
+    pub async fn get_shielded_activity(
+        &self,
+        wallet_id: i32,
+        pool: Option<&str>,
+        limit: i32,
+        offset: i32,
+    ) -> AppResult<Vec<ShieldedActivityRow>> {
+        let query = format!(
+            "{} ORDER BY block_height IS NULL DESC, block_height DESC, tx_hash, pool LIMIT ? OFFSET ?",
+            SHIELDED_ACTIVITY_SQL
+        );
+        let rows = sqlx::query_as::<_, ShieldedActivityRow>(&query)
+            .bind(wallet_id)
+            .bind(wallet_id)
+            .bind(pool)
+            .bind(pool)
+            .bind(limit)
+            .bind(offset)
+            .fetch_all(&self.pool)
+            .await?;
+        Ok(rows)
+    }
+
+    /// Count the entries `get_shielded_activity` pages through
+    pub async fn count_shielded_activity(&self, wallet_id: i32, pool: Option<&str>) -> AppResult<i64> {
+        let query = format!("SELECT COUNT(*) FROM ({}) activity", SHIELDED_ACTIVITY_SQL);
+        let (count,): (i64,) = sqlx::query_as(&query)
+            .bind(wallet_id)
+            .bind(wallet_id)
+            .bind(pool)
+            .bind(pool)
+            .fetch_one(&self.pool)
+            .await?;
+        Ok(count)
+    }
+
     /// Get unspent notes with spending data (for shielded transfers)
     pub async fn get_spendable_notes(&self, wallet_id: i32) -> AppResult<Vec<StoredOrchardNote>> {
         let notes = sqlx::query_as::<_, StoredOrchardNote>(
             r#"
             SELECT id, wallet_id, nullifier, value_zatoshis, block_height, tx_hash,
                    position_in_block, is_spent, spent_in_tx, memo, recipient, rho, rseed,
-                   witness_position, witness_auth_path, witness_root
+                   witness_position, witness_auth_path, witness_root,
+                   witness_state IS NOT NULL AS has_witness_state, reserved_until
             FROM orchard_notes
-            WHERE wallet_id = ? AND is_spent = FALSE
+            WHERE wallet_id = ? AND is_spent = FALSE AND pool = 'orchard'
               AND recipient IS NOT NULL AND rho IS NOT NULL AND rseed IS NOT NULL
+              AND (reserved_until IS NULL OR reserved_until < NOW())
             ORDER BY value_zatoshis DESC
             "#
         )
@@ -297,15 +577,70 @@ impl OrchardRepository {
         Ok(notes)
     }
 
+    /// Reserve unspent notes for one transfer until `ttl_secs` from now
+    ///
+    /// All or nothing: returns false, reserving none, if any note is already spent or
+    /// held by an unexpired reservation of another transfer.
+    pub async fn reserve_notes(&self, nullifiers: &[String], ttl_secs: u64) -> AppResult<bool> {
+        if nullifiers.is_empty() {
+            return Ok(true);
+        }
+
+        let placeholders = vec!["?"; nullifiers.len()].join(",");
+        let query = format!(
+            r#"
+            UPDATE orchard_notes SET reserved_until = NOW() + INTERVAL ? SECOND
+            WHERE nullifier IN ({}) AND is_spent = FALSE
+              AND (reserved_until IS NULL OR reserved_until < NOW())
+            "#,
+            placeholders
+        );
+
+        let mut tx = self.pool.begin().await?;
+        let mut q = sqlx::query(&query).bind(ttl_secs);
+        for nullifier in nullifiers {
+            q = q.bind(nullifier);
+        }
+        let reserved = q.execute(&mut *tx).await?.rows_affected();
+
+        if reserved != nullifiers.len() as u64 {
+            tx.rollback().await?;
+            return Ok(false);
+        }
+        tx.commit().await?;
+        Ok(true)
+    }
+
+    /// Drop reservations, e.g. when the transfer holding them fails before broadcast
+    pub async fn release_note_reservations(&self, nullifiers: &[String]) -> AppResult<()> {
+        if nullifiers.is_empty() {
+            return Ok(());
+        }
+
+        let placeholders = vec!["?"; nullifiers.len()].join(",");
+        let query = format!(
+            "UPDATE orchard_notes SET reserved_until = NULL WHERE nullifier IN ({})",
+            placeholders
+        );
+
+        let mut q = sqlx::query(&query);
+        for nullifier in nullifiers {
+            q = q.bind(nullifier);
+        }
+        q.execute(&self.pool).await?;
+        Ok(())
+    }
+
     /// Get spendable notes with witness data
     pub async fn get_notes_with_witnesses(&self, wallet_id: i32) -> AppResult<Vec<StoredOrchardNote>> {
         let notes = sqlx::query_as::<_, StoredOrchardNote>(
             r#"
             SELECT id, wallet_id, nullifier, value_zatoshis, block_height, tx_hash,
                    position_in_block, is_spent, spent_in_tx, memo, recipient, rho, rseed,
-                   witness_position, witness_auth_path, witness_root
+                   witness_position, witness_auth_path, witness_root,
+                   witness_state IS NOT NULL AS has_witness_state, reserved_until
             FROM orchard_notes
-            WHERE wallet_id = ? AND is_spent = FALSE
+            WHERE wallet_id = ? AND is_spent = FALSE AND pool = 'orchard'
               AND recipient IS NOT NULL AND rho IS NOT NULL AND rseed IS NOT NULL
               AND witness_auth_path IS NOT NULL AND witness_root IS NOT NULL
             ORDER BY value_zatoshis DESC
@@ -369,7 +704,7 @@ impl OrchardRepository {
     pub async fn get_balance(&self, wallet_id: i32) -> AppResult<u64> {
         // CAST to UNSIGNED because SUM returns DECIMAL which isn't compatible with i64
         let result: Option<(u64,)> = sqlx::query_as(
-            "SELECT CAST(COALESCE(SUM(value_zatoshis), 0) AS UNSIGNED) FROM orchard_notes WHERE wallet_id = ? AND is_spent = FALSE"
+            "SELECT CAST(COALESCE(SUM(value_zatoshis), 0) AS UNSIGNED) FROM orchard_notes WHERE wallet_id = ? AND is_spent = FALSE AND pool = 'orchard'"
         )
         .bind(wallet_id)
         .fetch_optional(&self.pool)
@@ -377,18 +712,98 @@ impl OrchardRepository {
         Ok(result.map(|(b,)| b).unwrap_or(0))
     }
 
-    /// Mark a note as spent
-    pub async fn mark_note_spent(&self, nullifier: &str, spent_in_tx: &str) -> AppResult<bool> {
+    /// Mark a note as spent on chain at the height of the spending block, confirming a pending spend
+    pub async fn mark_note_spent_at(&self, nullifier: &str, spent_in_tx: &str, spent_height: u64) -> AppResult<bool> {
         let result = sqlx::query(
-            "UPDATE orchard_notes SET is_spent = TRUE, spent_in_tx = ? WHERE nullifier = ? AND is_spent = FALSE"
+            r#"
+            UPDATE orchard_notes
+            SET is_spent = TRUE, spent_in_tx = ?, spent_height = ?, spent_pending = FALSE, spent_expiry_height = NULL
+            WHERE nullifier = ? AND (is_spent = FALSE OR spent_height IS NULL)
+            "#
         )
         .bind(spent_in_tx)
+        .bind(spent_height)
         .bind(nullifier)
         .execute(&self.pool)
         .await?;
         Ok(result.rows_affected() > 0)
     }
 
+    /// Optimistically mark notes spent by one of our own broadcast transactions
+    ///
+    /// The spend stays pending until the scanner sees the nullifier on chain; it is
+    /// reverted if the transaction fails or `expiry_height` passes without it.
+    pub async fn mark_notes_spent_pending(
+        &self,
+        nullifiers: &[String],
+        spent_in_tx: &str,
+        expiry_height: u64,
+    ) -> AppResult<u64> {
+        let mut marked = 0;
+        for nullifier in nullifiers {
+            let result = sqlx::query(
+                r#"
+                UPDATE orchard_notes
+                SET is_spent = TRUE, spent_in_tx = ?, spent_pending = TRUE, spent_expiry_height = ?,
+                    reserved_until = NULL
+                WHERE nullifier = ? AND is_spent = FALSE
+                "#
+            )
+            .bind(spent_in_tx)
+            .bind(expiry_height)
+            .bind(nullifier)
+            .execute(&self.pool)
+            .await?;
+            marked += result.rows_affected();
+        }
+        Ok(marked)
+    }
+
+    /// Move optimistic spend marks from the locally computed txid to the node's txid
+    pub async fn reassign_spent_in_tx(&self, old_tx: &str, new_tx: &str) -> AppResult<u64> {
+        let result = sqlx::query(
+            "UPDATE orchard_notes SET spent_in_tx = ? WHERE spent_in_tx = ? AND is_spent = TRUE"
+        )
+        .bind(new_tx)
+        .bind(old_tx)
+        .execute(&self.pool)
+        .await?;
+        Ok(result.rows_affected())
+    }
+
+    /// Revert pending spends by a transaction that failed or never made it on chain
+    ///
+    /// Spends the scanner has seen on chain are left alone.
+    pub async fn unmark_notes_spent_in_tx(&self, spent_in_tx: &str) -> AppResult<u64> {
+        let result = sqlx::query(
+            r#"
+            UPDATE orchard_notes
+            SET is_spent = FALSE, spent_in_tx = NULL, spent_pending = FALSE, spent_expiry_height = NULL
+            WHERE spent_in_tx = ? AND spent_pending = TRUE
+            "#
+        )
+        .bind(spent_in_tx)
+        .execute(&self.pool)
+        .await?;
+        Ok(result.rows_affected())
+    }
+
+    /// Revert pending spends whose transaction expired before `scanned_height` without
+    /// being mined; the transaction can no longer confirm, so the notes are spendable again
+    pub async fn release_expired_pending_spends(&self, scanned_height: u64) -> AppResult<u64> {
+        let result = sqlx::query(
+            r#"
+            UPDATE orchard_notes
+            SET is_spent = FALSE, spent_in_tx = NULL, spent_pending = FALSE, spent_expiry_height = NULL
+            WHERE spent_pending = TRUE AND spent_expiry_height < ?
+            "#
+        )
+        .bind(scanned_height)
+        .execute(&self.pool)
+        .await?;
+        Ok(result.rows_affected())
+    }
+
     /// Check if a nullifier exists (note was spent)
     pub async fn nullifier_exists(&self, nullifier: &str) -> AppResult<bool> {
         let result: Option<(i32,)> = sqlx::query_as(
@@ -403,7 +818,7 @@ impl OrchardRepository {
     /// Get unspent notes count for a wallet
     pub async fn get_notes_count(&self, wallet_id: i32) -> AppResult<u32> {
         let result: Option<(i64,)> = sqlx::query_as(
-            "SELECT COUNT(*) FROM orchard_notes WHERE wallet_id = ? AND is_spent = FALSE"
+            "SELECT COUNT(*) FROM orchard_notes WHERE wallet_id = ? AND is_spent = FALSE AND pool = 'orchard'"
         )
         .bind(wallet_id)
         .fetch_optional(&self.pool)
@@ -419,6 +834,7 @@ impl OrchardRepository {
             SELECT COUNT(*) FROM orchard_notes
             WHERE wallet_id = ?
               AND is_spent = FALSE
+              AND pool = 'orchard'
               AND (witness_position IS NULL OR witness_auth_path IS NULL OR witness_root IS NULL)
             "#
         )
@@ -432,6 +848,63 @@ impl OrchardRepository {
     // Tree State Operations (for incremental witness sync)
     // =========================================================================
 
+    /// Save a tree checkpoint, replacing any earlier one at the same height
+    pub async fn save_tree_checkpoint(
+        &self,
+        height: u64,
+        tree_data: &[u8],
+        tree_size: u64,
+        witnesses: &str,
+    ) -> AppResult<()> {
+        sqlx::query(
+            "REPLACE INTO orchard_tree_checkpoints (height, tree_data, tree_size, witnesses) VALUES (?, ?, ?, ?)"
+        )
+        .bind(height)
+        .bind(tree_data)
+        .bind(tree_size)
+        .bind(witnesses)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Latest checkpoint at or below a height
+    pub async fn load_tree_checkpoint(&self, max_height: u64) -> AppResult<Option<OrchardTreeCheckpoint>> {
+        let checkpoint = sqlx::query_as::<_, OrchardTreeCheckpoint>(
+            r#"
+            SELECT height, tree_data, tree_size, witnesses
+            FROM orchard_tree_checkpoints
+            WHERE height <= ?
+            ORDER BY height DESC
+            LIMIT 1
+            "#
+        )
+        .bind(max_height)
+        .fetch_optional(&self.pool)
+        .await?;
+        Ok(checkpoint)
+    }
+
+    /// Keep only the `keep` most recent checkpoints
+    pub async fn prune_tree_checkpoints(&self, keep: u32) -> AppResult<u64> {
+        let result = sqlx::query(
+            r#"
+            DELETE FROM orchard_tree_checkpoints
+            WHERE height < (
+                SELECT min_height FROM (
+                    SELECT MIN(height) AS min_height FROM (
+                        SELECT height FROM orchard_tree_checkpoints ORDER BY height DESC LIMIT ?
+                    ) AS recent
+                ) AS bound
+            )
+            "#
+        )
+        .bind(keep)
+        .execute(&self.pool)
+        .await?;
+        Ok(result.rows_affected())
+    }
+
     /// Save global tree state
     /// Uses REPLACE INTO to ensure only one row exists
     pub async fn save_tree_state(&self, tree_data: &[u8], tree_height: u64, tree_size: u64) -> AppResult<()> {
@@ -503,7 +976,7 @@ impl OrchardRepository {
             r#"
             SELECT nullifier, block_height, witness_position, witness_state
             FROM orchard_notes
-            WHERE wallet_id IN ({}) AND is_spent = FALSE
+            WHERE wallet_id IN ({}) AND is_spent = FALSE AND pool = 'orchard'
             ORDER BY block_height ASC
             "#,
             placeholders.join(",")
@@ -534,7 +1007,7 @@ impl OrchardRepository {
 
         let placeholders: Vec<String> = wallet_ids.iter().map(|_| "?".to_string()).collect();
         let query = format!(
-            "SELECT MIN(block_height) FROM orchard_notes WHERE wallet_id IN ({}) AND is_spent = FALSE",
+            "SELECT MIN(block_height) FROM orchard_notes WHERE wallet_id IN ({}) AND is_spent = FALSE AND pool = 'orchard'",
             placeholders.join(",")
         );
 
@@ -559,7 +1032,7 @@ impl OrchardRepository {
             r#"
             SELECT nullifier, block_height, witness_position, witness_state
             FROM orchard_notes
-            WHERE wallet_id IN ({}) AND is_spent = FALSE
+            WHERE wallet_id IN ({}) AND is_spent = FALSE AND pool = 'orchard'
               AND witness_position IS NOT NULL
               AND witness_state IS NULL
             ORDER BY block_height ASC
@@ -594,7 +1067,7 @@ impl OrchardRepository {
         let query = format!(
             r#"
             SELECT MIN(block_height) FROM orchard_notes
-            WHERE wallet_id IN ({}) AND is_spent = FALSE
+            WHERE wallet_id IN ({}) AND is_spent = FALSE AND pool = 'orchard'
               AND witness_position IS NOT NULL
               AND witness_state IS NULL
             "#,
@@ -650,4 +1123,194 @@ impl OrchardRepository {
         .await?;
         Ok(result.rows_affected() > 0)
     }
+
+    // =========================================================================
+    // Reorg Handling
+    // =========================================================================
+
+    /// Record block hashes seen while scanning (for reorg detection)
+    pub async fn save_block_hashes(&self, hashes: &[(u64, String)]) -> AppResult<()> {
+        for (height, hash) in hashes {
+            sqlx::query(
+                r#"
+                INSERT INTO orchard_block_hashes (height, hash) VALUES (?, ?)
+                ON DUPLICATE KEY UPDATE hash = VALUES(hash)
+                "#
+            )
+            .bind(height)
+            .bind(hash)
+            .execute(&self.pool)
+            .await?;
+        }
+        Ok(())
+    }
+
+    /// Get the stored block hashes from `from_height` to `to_height`, inclusive
+    pub async fn get_block_hashes(&self, from_height: u64, to_height: u64) -> AppResult<BTreeMap<u64, String>> {
+        let rows: Vec<(u64, String)> = sqlx::query_as(
+            "SELECT height, hash FROM orchard_block_hashes WHERE height BETWEEN ? AND ?"
+        )
+        .bind(from_height)
+        .bind(to_height)
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(rows.into_iter().collect())
+    }
+
+    /// Drop block hashes below a height (too deep to reorg)
+    pub async fn prune_block_hashes(&self, below_height: u64) -> AppResult<u64> {
+        let result = sqlx::query("DELETE FROM orchard_block_hashes WHERE height < ?")
+            .bind(below_height)
+            .execute(&self.pool)
+            .await?;
+        Ok(result.rows_affected())
+    }
+
+    /// Roll notes, spends and scan state back to `height` after a chain reorg
+    ///
+    /// Notes from orphaned blocks are deleted, spends in orphaned blocks are reverted and
+    /// witness states are cleared since they include orphaned commitments.
+    /// Returns (notes_removed, notes_unspent).
+    pub async fn rollback_to_height(&self, height: u64) -> AppResult<(u64, u64)> {
+        let mut tx = self.pool.begin().await?;
+
+        let removed = sqlx::query("DELETE FROM orchard_notes WHERE block_height > ?")
+            .bind(height)
+            .execute(&mut *tx)
+            .await?
+            .rows_affected();
+
+        let unspent = sqlx::query(
+            r#"
+            UPDATE orchard_notes SET is_spent = FALSE, spent_in_tx = NULL, spent_height = NULL
+            WHERE spent_height > ?
+            "#
+        )
+        .bind(height)
+        .execute(&mut *tx)
+        .await?
+        .rows_affected();
+
+        sqlx::query("UPDATE orchard_notes SET witness_state = NULL WHERE is_spent = FALSE")
+            .execute(&mut *tx)
+            .await?;
+
+        sqlx::query("UPDATE orchard_sync_state SET last_scanned_height = ? WHERE last_scanned_height > ?")
+            .bind(height)
+            .bind(height)
+            .execute(&mut *tx)
+            .await?;
+
+        sqlx::query("DELETE FROM orchard_tree_state WHERE tree_height > ?")
+            .bind(height)
+            .execute(&mut *tx)
+            .await?;
+
+        sqlx::query("DELETE FROM orchard_block_hashes WHERE height > ?")
+            .bind(height)
+            .execute(&mut *tx)
+            .await?;
+
+        sqlx::query("DELETE FROM orchard_tree_checkpoints WHERE height > ?")
+            .bind(height)
+            .execute(&mut *tx)
+            .await?;
+
+        tx.commit().await?;
+        Ok((removed, unspent))
+    }
+}
+
+/// Optimistic spend marks on the notes of a transaction being broadcast
+#[async_trait]
+pub trait PendingSpends: Send + Sync {
+    async fn mark_notes_spent_pending(&self, nullifiers: &[String], spent_in_tx: &str, expiry_height: u64) -> AppResult<u64>;
+    async fn reassign_spent_in_tx(&self, old_tx: &str, new_tx: &str) -> AppResult<u64>;
+    async fn unmark_notes_spent_in_tx(&self, spent_in_tx: &str) -> AppResult<u64>;
+}
+
+#[async_trait]
+impl PendingSpends for OrchardRepository {
+    async fn mark_notes_spent_pending(&self, nullifiers: &[String], spent_in_tx: &str, expiry_height: u64) -> AppResult<u64> {
+        OrchardRepository::mark_notes_spent_pending(self, nullifiers, spent_in_tx, expiry_height).await
+    }
+
+    async fn reassign_spent_in_tx(&self, old_tx: &str, new_tx: &str) -> AppResult<u64> {
+        OrchardRepository::reassign_spent_in_tx(self, old_tx, new_tx).await
+    }
+
+    async fn unmark_notes_spent_in_tx(&self, spent_in_tx: &str) -> AppResult<u64> {
+        OrchardRepository::unmark_notes_spent_in_tx(self, spent_in_tx).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn note(block_height: u64) -> StoredOrchardNote {
+        StoredOrchardNote {
+            id: 1,
+            wallet_id: 1,
+            nullifier: "00".repeat(32),
+            value_zatoshis: 50_000,
+            block_height,
+            tx_hash: "ab".repeat(32),
+            position_in_block: 0,
+            is_spent: false,
+            spent_in_tx: None,
+            memo: None,
+            recipient: Some("11".repeat(43)),
+            rho: Some("22".repeat(32)),
+            rseed: Some("33".repeat(32)),
+            witness_position: Some(7),
+            witness_auth_path: Some("[]".to_string()),
+            witness_root: Some("44".repeat(32)),
+            has_witness_state: true,
+            reserved_until: None,
+        }
+    }
+
+    #[test]
+    fn test_note_insert_reports_only_new_rows() {
+        // An upsert's row count cannot tell a new note from a rescanned one
+        assert!(INSERT_NOTE_SQL.trim_start().starts_with("INSERT IGNORE"));
+        assert!(!INSERT_NOTE_SQL.contains("ON DUPLICATE KEY"));
+    }
+
+    #[test]
+    fn test_spend_blockers() {
+        let now = Utc::now();
+        let note = note(1_000);
+        assert_eq!(note.confirmations(1_010), 10);
+        assert!(note.spend_blockers(1_010, 10, now).is_empty());
+        assert_eq!(note.spend_blockers(1_005, 10, now), vec!["confirmations"]);
+        // A lagging node reports a tip below the note
+        assert_eq!(note.confirmations(990), 0);
+
+        let mut unwitnessed = note.clone();
+        unwitnessed.witness_root = None;
+        assert_eq!(unwitnessed.spend_blockers(1_010, 10, now), vec!["witness"]);
+    }
+
+    #[test]
+    fn test_note_without_witness_state_is_not_spendable() {
+        let mut note = note(1_000);
+        note.has_witness_state = false;
+        assert!(!note.has_witness());
+        assert_eq!(note.spend_blockers(1_010, 10, Utc::now()), vec!["witness"]);
+    }
+
+    #[test]
+    fn test_reserved_note_is_not_spendable() {
+        let now = Utc::now();
+        let mut note = note(1_000);
+        note.reserved_until = Some(now + chrono::Duration::seconds(60));
+        assert!(note.is_reserved(now));
+        assert_eq!(note.spend_blockers(1_010, 10, now), vec!["reserved"]);
+
+        // An expired reservation no longer holds the note
+        note.reserved_until = Some(now - chrono::Duration::seconds(1));
+        assert!(note.spend_blockers(1_010, 10, now).is_empty());
+    }
 }
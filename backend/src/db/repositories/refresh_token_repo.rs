@@ -0,0 +1,96 @@
+use chrono::{DateTime, Utc};
+use sqlx::{FromRow, MySqlPool};
+
+use crate::error::AppResult;
+
+#[derive(Debug, Clone, FromRow)]
+pub struct RefreshToken {
+    pub id: i32,
+    pub user_id: i32,
+    pub family_id: String,
+    pub token_epoch: i32,
+    pub expires_at: DateTime<Utc>,
+    pub revoked_at: Option<DateTime<Utc>>,
+}
+
+pub struct RefreshTokenRepository {
+    pool: MySqlPool,
+}
+
+impl RefreshTokenRepository {
+    pub fn new(pool: MySqlPool) -> Self {
+        Self { pool }
+    }
+
+    pub async fn create(
+        &self,
+        token_hash: &str,
+        user_id: i32,
+        family_id: &str,
+        token_epoch: i32,
+        expires_at: DateTime<Utc>,
+    ) -> AppResult<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO refresh_tokens (token_hash, user_id, family_id, token_epoch, expires_at)
+            VALUES (?, ?, ?, ?, ?)
+            "#
+        )
+        .bind(token_hash)
+        .bind(user_id)
+        .bind(family_id)
+        .bind(token_epoch)
+        .bind(expires_at)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn find_by_hash(&self, token_hash: &str) -> AppResult<Option<RefreshToken>> {
+        let token = sqlx::query_as::<_, RefreshToken>(
+            r#"
+            SELECT id, user_id, family_id, token_epoch, expires_at, revoked_at
+            FROM refresh_tokens WHERE token_hash = ?
+            "#
+        )
+        .bind(token_hash)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(token)
+    }
+
+    /// Revoke one token; false if it was already revoked (e.g. by a concurrent refresh)
+    pub async fn revoke(&self, id: i32) -> AppResult<bool> {
+        let result = sqlx::query(
+            "UPDATE refresh_tokens SET revoked_at = UTC_TIMESTAMP() WHERE id = ? AND revoked_at IS NULL"
+        )
+        .bind(id)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected() == 1)
+    }
+
+    /// Revoke every token descended from the same login
+    pub async fn revoke_family(&self, family_id: &str) -> AppResult<u64> {
+        let result = sqlx::query(
+            "UPDATE refresh_tokens SET revoked_at = UTC_TIMESTAMP() WHERE family_id = ? AND revoked_at IS NULL"
+        )
+        .bind(family_id)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected())
+    }
+
+    /// Delete tokens that have expired anyway
+    pub async fn purge_expired(&self) -> AppResult<u64> {
+        let result = sqlx::query("DELETE FROM refresh_tokens WHERE expires_at < UTC_TIMESTAMP()")
+            .execute(&self.pool)
+            .await?;
+
+        Ok(result.rows_affected())
+    }
+}
@@ -0,0 +1,50 @@
+
+use chrono::{DateTime, Utc};
+use sqlx::MySqlPool;
+
+use crate::error::AppResult;
+
+pub struct RevokedTokenRepository {
+    pool: MySqlPool,
+}
+
+impl RevokedTokenRepository {
+    pub fn new(pool: MySqlPool) -> Self {
+        Self { pool }
+    }
+
+    /// Record a token id as revoked until its natural expiry
+    pub async fn revoke(&self, jti: &str, user_id: i32, expires_at: DateTime<Utc>) -> AppResult<()> {
+        sqlx::query(
+            "INSERT IGNORE INTO revoked_tokens (jti, user_id, expires_at) VALUES (?, ?, ?)"
+        )
+        .bind(jti)
+        .bind(user_id)
+        .bind(expires_at)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Check whether a token id has been revoked
+    pub async fn is_revoked(&self, jti: &str) -> AppResult<bool> {
+        let result: Option<(i32,)> = sqlx::query_as(
+            "SELECT 1 FROM revoked_tokens WHERE jti = ? LIMIT 1"
+        )
+        .bind(jti)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(result.is_some())
+    }
+
+    /// Delete entries whose tokens have expired anyway
+    pub async fn purge_expired(&self) -> AppResult<u64> {
+        let result = sqlx::query("DELETE FROM revoked_tokens WHERE expires_at < UTC_TIMESTAMP()")
+            .execute(&self.pool)
+            .await?;
+
+        Ok(result.rows_affected())
+    }
+}
@@ -0,0 +1,25 @@
+use sqlx::MySqlPool;
+
+use crate::error::AppResult;
+
+pub struct RolePermissionRepository {
+    pool: MySqlPool,
+}
+
+impl RolePermissionRepository {
+    pub fn new(pool: MySqlPool) -> Self {
+        Self { pool }
+    }
+
+    /// Permission names granted to a role
+    pub async fn list_for_role(&self, role: &str) -> AppResult<Vec<String>> {
+        let rows: Vec<(String,)> = sqlx::query_as(
+            "SELECT permission FROM role_permissions WHERE role = ?"
+        )
+        .bind(role)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows.into_iter().map(|(permission,)| permission).collect())
+    }
+}
@@ -4,6 +4,14 @@ use sqlx::MySqlPool;
 
 use crate::error::AppResult;
 
+/// Zcash RPC password, stored encrypted with `security.encryption_key`
+pub const ZCASH_RPC_PASSWORD_KEY: &str = "zcash_rpc_password";
+
+const SET_SQL: &str = r#"
+    INSERT INTO settings (`key`, `value`) VALUES (?, ?)
+    ON DUPLICATE KEY UPDATE `value` = VALUES(`value`)
+"#;
+
 pub struct SettingsRepository {
     pool: MySqlPool,
 }
@@ -27,16 +35,27 @@ impl SettingsRepository {
 
     /// Set a setting value (insert or update)
     pub async fn set(&self, key: &str, value: &str) -> AppResult<()> {
-        sqlx::query(
-            r#"
-            INSERT INTO settings (`key`, `value`) VALUES (?, ?)
-            ON DUPLICATE KEY UPDATE `value` = VALUES(`value`)
-            "#
-        )
-        .bind(key)
-        .bind(value)
-        .execute(&self.pool)
-        .await?;
+        sqlx::query(SET_SQL)
+            .bind(key)
+            .bind(value)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Set a setting value in the caller's transaction
+    pub async fn set_in(
+        &self,
+        tx: &mut sqlx::Transaction<'_, sqlx::MySql>,
+        key: &str,
+        value: &str,
+    ) -> AppResult<()> {
+        sqlx::query(SET_SQL)
+            .bind(key)
+            .bind(value)
+            .execute(&mut **tx)
+            .await?;
 
         Ok(())
     }
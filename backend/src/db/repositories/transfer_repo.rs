@@ -1,7 +1,57 @@
-use crate::db::models::Transfer;
+use crate::db::models::{NativeFee, Transfer};
 use crate::error::AppResult;
 use rust_decimal::Decimal;
-use sqlx::MySqlPool;
+use chrono::{DateTime, Utc};
+use sqlx::{MySql, MySqlPool, QueryBuilder};
+
+/// Conditions for listing transfers; unset fields match everything
+#[derive(Debug, Default, Clone)]
+pub struct TransferFilter {
+    pub wallet_id: Option<i32>,
+    pub status: Option<String>,
+    pub chain: Option<String>,
+    /// Inclusive lower bound on `created_at`
+    pub from: Option<DateTime<Utc>>,
+    /// Exclusive upper bound on `created_at`
+    pub to: Option<DateTime<Utc>>,
+}
+
+impl TransferFilter {
+    fn push_conditions(&self, query: &mut QueryBuilder<'_, MySql>) {
+        if let Some(wallet_id) = self.wallet_id {
+            query.push(" AND wallet_id = ").push_bind(wallet_id);
+        }
+        if let Some(status) = &self.status {
+            query.push(" AND status = ").push_bind(status.clone());
+        }
+        if let Some(chain) = &self.chain {
+            query.push(" AND chain = ").push_bind(chain.clone());
+        }
+        if let Some(from) = self.from {
+            query.push(" AND created_at >= ").push_bind(from);
+        }
+        if let Some(to) = self.to {
+            query.push(" AND created_at < ").push_bind(to);
+        }
+    }
+}
+
+/// A transfer to insert as pending
+#[derive(Debug, Default, Clone, Copy)]
+pub struct NewTransfer<'a> {
+    pub wallet_id: i32,
+    pub chain: &'a str,
+    pub from_address: &'a str,
+    pub to_address: &'a str,
+    pub token: &'a str,
+    pub amount: Decimal,
+    pub gas_price: Option<Decimal>,
+    pub gas_limit: Option<i64>,
+    pub initiated_by: i32,
+    /// "required" when the transfer waits for a second user's approval
+    pub approval_status: Option<&'a str>,
+    pub idempotency_key: Option<&'a str>,
+}
 
 pub struct TransferRepository {
     pool: MySqlPool,
@@ -12,38 +62,152 @@ impl TransferRepository {
         Self { pool }
     }
 
-    pub async fn create(
-        &self,
-        wallet_id: i32,
-        chain: &str,
-        from_address: &str,
-        to_address: &str,
-        token: &str,
-        amount: Decimal,
-        gas_price: Option<Decimal>,
-        gas_limit: Option<i64>,
-        initiated_by: i32,
-    ) -> AppResult<i32> {
+    pub async fn create(&self, transfer: NewTransfer<'_>) -> AppResult<i32> {
         let result = sqlx::query(
             r#"INSERT INTO transfers
-            (wallet_id, chain, from_address, to_address, token, amount, gas_price, gas_limit, status, initiated_by)
-            VALUES (?, ?, ?, ?, ?, ?, ?, ?, 'pending', ?)"#
+            (wallet_id, chain, from_address, to_address, token, amount, gas_price, gas_limit, status, initiated_by, approval_status, idempotency_key)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, 'pending', ?, ?, ?)"#
         )
-        .bind(wallet_id)
-        .bind(chain)
-        .bind(from_address)
-        .bind(to_address)
-        .bind(token)
-        .bind(amount)
-        .bind(gas_price)
-        .bind(gas_limit)
-        .bind(initiated_by)
+        .bind(transfer.wallet_id)
+        .bind(transfer.chain)
+        .bind(transfer.from_address)
+        .bind(transfer.to_address)
+        .bind(transfer.token)
+        .bind(transfer.amount)
+        .bind(transfer.gas_price)
+        .bind(transfer.gas_limit)
+        .bind(transfer.initiated_by)
+        .bind(transfer.approval_status)
+        .bind(transfer.idempotency_key)
         .execute(&self.pool)
         .await?;
 
         Ok(result.last_insert_id() as i32)
     }
 
+    /// Transfer a user created with the given idempotency key
+    pub async fn find_by_idempotency_key(&self, initiated_by: i32, key: &str) -> AppResult<Option<Transfer>> {
+        let transfer = sqlx::query_as::<_, Transfer>(
+            "SELECT * FROM transfers WHERE initiated_by = ? AND idempotency_key = ?"
+        )
+        .bind(initiated_by)
+        .bind(key)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(transfer)
+    }
+
+    /// Release idempotency keys of transfers created before `before`
+    pub async fn expire_idempotency_keys(&self, before: DateTime<Utc>) -> AppResult<u64> {
+        let result = sqlx::query(
+            "UPDATE transfers SET idempotency_key = NULL WHERE idempotency_key IS NOT NULL AND created_at < ?"
+        )
+        .bind(before)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected())
+    }
+
+    /// Record an approval decision on a pending transfer awaiting one
+    ///
+    /// A rejection also moves the transfer to `rejected`. Returns false if the
+    /// transfer was no longer awaiting approval.
+    pub async fn decide_approval(
+        &self,
+        id: i32,
+        approved: bool,
+        decided_by: i32,
+        reason: Option<&str>,
+    ) -> AppResult<bool> {
+        let result = sqlx::query(
+            r#"UPDATE transfers
+            SET approval_status = ?, approved_by = ?, approved_at = UTC_TIMESTAMP(),
+                status = IF(?, status, 'rejected'), error_message = COALESCE(?, error_message)
+            WHERE id = ? AND status = 'pending' AND approval_status = 'required'"#
+        )
+        .bind(if approved { "approved" } else { "rejected" })
+        .bind(decided_by)
+        .bind(approved)
+        .bind(reason)
+        .bind(id)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected() == 1)
+    }
+
+    /// Let a pending transfer exceed its wallet's spending limit
+    ///
+    /// Returns false if the transfer is no longer pending.
+    pub async fn set_limit_override(&self, id: i32, admin_id: i32) -> AppResult<bool> {
+        let result = sqlx::query(
+            "UPDATE transfers SET limit_override_by = ? WHERE id = ? AND status = 'pending'"
+        )
+        .bind(admin_id)
+        .bind(id)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected() == 1)
+    }
+
+    /// Tie a transfer awaiting approval to the privacy proposal that will send it
+    pub async fn link_proposal(&self, id: i32, proposal_id: &str) -> AppResult<()> {
+        sqlx::query("UPDATE transfers SET proposal_id = ? WHERE id = ?")
+            .bind(proposal_id)
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Amount of a token a wallet has sent in transfers created since `since`
+    ///
+    /// Counts submitted and confirmed transfers; failed and rejected ones moved nothing.
+    /// Shielded sends of the token (`ZEC-shielded` for `ZEC`) count too; a wallet sending
+    /// to its own address, such as a note consolidation, does not.
+    pub async fn spent_since(&self, wallet_id: i32, token: &str, since: DateTime<Utc>) -> AppResult<Decimal> {
+        let (spent,): (Option<Decimal>,) = sqlx::query_as(
+            r#"SELECT SUM(amount) FROM transfers
+            WHERE wallet_id = ? AND token IN (?, ?) AND status IN ('submitted', 'confirmed') AND created_at >= ?
+              AND to_address <> from_address"#
+        )
+        .bind(wallet_id)
+        .bind(token)
+        .bind(format!("{}-shielded", token))
+        .bind(since)
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(spent.unwrap_or(Decimal::ZERO))
+    }
+
+    /// Whether one of the wallet's own transfers was broadcast as `tx_hash`
+    pub async fn exists_with_tx_hash(&self, wallet_id: i32, tx_hash: &str) -> AppResult<bool> {
+        let found: Option<(i32,)> = sqlx::query_as(
+            "SELECT id FROM transfers WHERE wallet_id = ? AND tx_hash = ? LIMIT 1"
+        )
+        .bind(wallet_id)
+        .bind(tx_hash)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(found.is_some())
+    }
+
+    /// Lowest id of the transfers broadcast as `tx_hash`; a combined batch shares one transaction
+    pub async fn first_id_with_tx_hash(&self, tx_hash: &str) -> AppResult<Option<i32>> {
+        let (id,): (Option<i32>,) = sqlx::query_as("SELECT MIN(id) FROM transfers WHERE tx_hash = ?")
+            .bind(tx_hash)
+            .fetch_one(&self.pool)
+            .await?;
+
+        Ok(id)
+    }
+
     pub async fn find_by_id(&self, id: i32) -> AppResult<Option<Transfer>> {
         let transfer = sqlx::query_as::<_, Transfer>(
             "SELECT * FROM transfers WHERE id = ?"
@@ -63,11 +227,14 @@ impl TransferRepository {
         error_message: Option<&str>,
     ) -> AppResult<()> {
         sqlx::query(
-            "UPDATE transfers SET status = ?, tx_hash = ?, error_message = ? WHERE id = ?"
+            r#"UPDATE transfers SET status = ?, tx_hash = ?, error_message = ?,
+            submitted_at = IF(? = 'submitted', CURRENT_TIMESTAMP, submitted_at)
+            WHERE id = ?"#
         )
         .bind(status)
         .bind(tx_hash)
         .bind(error_message)
+        .bind(status)
         .bind(id)
         .execute(&self.pool)
         .await?;
@@ -82,7 +249,7 @@ impl TransferRepository {
         gas_used: i64,
     ) -> AppResult<()> {
         sqlx::query(
-            "UPDATE transfers SET status = 'confirmed', block_number = ?, gas_used = ? WHERE id = ?"
+            "UPDATE transfers SET status = 'confirmed', block_number = ?, gas_used = ?, error_message = NULL WHERE id = ?"
         )
         .bind(block_number)
         .bind(gas_used)
@@ -93,28 +260,131 @@ impl TransferRepository {
         Ok(())
     }
 
-    pub async fn list_by_wallet(&self, wallet_id: i32, limit: i32, offset: i32) -> AppResult<Vec<Transfer>> {
-        let transfers = sqlx::query_as::<_, Transfer>(
-            "SELECT * FROM transfers WHERE wallet_id = ? ORDER BY created_at DESC LIMIT ? OFFSET ?"
+    pub async fn update_nonce(&self, id: i32, nonce: u64) -> AppResult<()> {
+        sqlx::query("UPDATE transfers SET nonce = ? WHERE id = ?")
+            .bind(nonce as i64)
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Record the block a submitted transfer was mined in (None after a reorg)
+    pub async fn update_block_number(&self, id: i32, block_number: Option<i64>) -> AppResult<()> {
+        sqlx::query("UPDATE transfers SET block_number = ? WHERE id = ? AND status = 'submitted'")
+            .bind(block_number)
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Note on a submitted transfer that it is stuck in the mempool; false if already noted
+    pub async fn flag_stuck(&self, id: i32, note: &str) -> AppResult<bool> {
+        let result = sqlx::query(
+            "UPDATE transfers SET error_message = ? WHERE id = ? AND status = 'submitted' AND error_message IS NULL"
         )
-        .bind(wallet_id)
-        .bind(limit)
-        .bind(offset)
-        .fetch_all(&self.pool)
+        .bind(note)
+        .bind(id)
+        .execute(&self.pool)
         .await?;
 
-        Ok(transfers)
+        Ok(result.rows_affected() == 1)
     }
 
-    pub async fn list_all(&self, limit: i32, offset: i32) -> AppResult<Vec<Transfer>> {
-        let transfers = sqlx::query_as::<_, Transfer>(
-            "SELECT * FROM transfers ORDER BY created_at DESC LIMIT ? OFFSET ?"
+    /// Point a submitted transfer at its fee-bumped replacement transaction
+    ///
+    /// The replaced hash is kept, since the original may still be the one that gets mined.
+    pub async fn replace_tx(&self, id: i32, old_tx_hash: &str, tx_hash: &str, gas_price: Decimal) -> AppResult<()> {
+        let mut tx = self.pool.begin().await?;
+        sqlx::query("INSERT INTO transfer_replaced_txs (transfer_id, tx_hash) VALUES (?, ?)")
+            .bind(id)
+            .bind(old_tx_hash)
+            .execute(&mut *tx)
+            .await?;
+        sqlx::query(
+            "UPDATE transfers SET tx_hash = ?, gas_price = ?, submitted_at = CURRENT_TIMESTAMP, error_message = NULL WHERE id = ?"
+        )
+        .bind(tx_hash)
+        .bind(gas_price)
+        .bind(id)
+        .execute(&mut *tx)
+        .await?;
+        tx.commit().await?;
+
+        Ok(())
+    }
+
+    /// Hashes of the transactions a transfer's fee bumps replaced, newest first
+    pub async fn replaced_tx_hashes(&self, id: i32) -> AppResult<Vec<String>> {
+        let hashes: Vec<(String,)> = sqlx::query_as(
+            "SELECT tx_hash FROM transfer_replaced_txs WHERE transfer_id = ? ORDER BY id DESC"
         )
-        .bind(limit)
-        .bind(offset)
+        .bind(id)
         .fetch_all(&self.pool)
         .await?;
 
+        Ok(hashes.into_iter().map(|(hash,)| hash).collect())
+    }
+
+    /// Point a transfer back at an earlier transaction that was mined instead of its replacement
+    pub async fn restore_tx(&self, id: i32, tx_hash: &str) -> AppResult<()> {
+        sqlx::query("UPDATE transfers SET tx_hash = ? WHERE id = ?")
+            .bind(tx_hash)
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+
+    pub async fn update_fee(&self, id: i32, fee: &NativeFee) -> AppResult<()> {
+        sqlx::query(
+            "UPDATE transfers SET fee_native = ?, fee_display = ? WHERE id = ?"
+        )
+        .bind(fee.units)
+        .bind(&fee.display)
+        .bind(id)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// One page of transfers matching the filter, newest first
+    pub async fn list(&self, filter: &TransferFilter, limit: i32, offset: i32) -> AppResult<Vec<Transfer>> {
+        let mut query = QueryBuilder::<MySql>::new("SELECT * FROM transfers WHERE 1 = 1");
+        filter.push_conditions(&mut query);
+        query
+            .push(" ORDER BY created_at DESC, id DESC LIMIT ")
+            .push_bind(limit)
+            .push(" OFFSET ")
+            .push_bind(offset);
+
+        let transfers = query
+            .build_query_as::<Transfer>()
+            .fetch_all(&self.pool)
+            .await?;
+
+        Ok(transfers)
+    }
+
+    /// Up to `limit` transfers matching the filter with an id above `after_id`, oldest first
+    ///
+    /// Pages by id so a long export neither skips nor repeats rows as new ones arrive.
+    pub async fn list_after(&self, filter: &TransferFilter, after_id: i32, limit: i32) -> AppResult<Vec<Transfer>> {
+        let mut query = QueryBuilder::<MySql>::new("SELECT * FROM transfers WHERE id > ");
+        query.push_bind(after_id);
+        filter.push_conditions(&mut query);
+        query.push(" ORDER BY id LIMIT ").push_bind(limit);
+
+        let transfers = query
+            .build_query_as::<Transfer>()
+            .fetch_all(&self.pool)
+            .await?;
+
         Ok(transfers)
     }
 
@@ -128,17 +398,12 @@ impl TransferRepository {
         Ok(transfers)
     }
 
-    pub async fn count_all(&self) -> AppResult<i64> {
-        let count: (i64,) = sqlx::query_as("SELECT COUNT(*) FROM transfers")
-            .fetch_one(&self.pool)
-            .await?;
-
-        Ok(count.0)
-    }
+    pub async fn count(&self, filter: &TransferFilter) -> AppResult<i64> {
+        let mut query = QueryBuilder::<MySql>::new("SELECT COUNT(*) FROM transfers WHERE 1 = 1");
+        filter.push_conditions(&mut query);
 
-    pub async fn count_by_wallet(&self, wallet_id: i32) -> AppResult<i64> {
-        let count: (i64,) = sqlx::query_as("SELECT COUNT(*) FROM transfers WHERE wallet_id = ?")
-            .bind(wallet_id)
+        let count: (i64,) = query
+            .build_query_as()
             .fetch_one(&self.pool)
             .await?;
 
@@ -15,7 +15,7 @@ impl UserRepository {
 
     pub async fn find_by_username(&self, username: &str) -> AppResult<Option<User>> {
         let user = sqlx::query_as::<_, User>(
-            "SELECT id, username, password_hash, role, created_at, updated_at FROM users WHERE username = ?"
+            "SELECT id, username, password_hash, role, token_epoch, must_change_password, created_at, updated_at FROM users WHERE username = ?"
         )
         .bind(username)
         .fetch_optional(&self.pool)
@@ -26,7 +26,7 @@ impl UserRepository {
 
     pub async fn find_by_id(&self, id: i32) -> AppResult<Option<User>> {
         let user = sqlx::query_as::<_, User>(
-            "SELECT id, username, password_hash, role, created_at, updated_at FROM users WHERE id = ?"
+            "SELECT id, username, password_hash, role, token_epoch, must_change_password, created_at, updated_at FROM users WHERE id = ?"
         )
         .bind(id)
         .fetch_optional(&self.pool)
@@ -48,8 +48,9 @@ impl UserRepository {
         Ok(result.last_insert_id() as i32)
     }
 
+    /// Set a new password, which also lifts a required password change
     pub async fn update_password(&self, user_id: i32, new_password_hash: &str) -> AppResult<()> {
-        sqlx::query("UPDATE users SET password_hash = ? WHERE id = ?")
+        sqlx::query("UPDATE users SET password_hash = ?, must_change_password = FALSE WHERE id = ?")
             .bind(new_password_hash)
             .bind(user_id)
             .execute(&self.pool)
@@ -58,9 +59,31 @@ impl UserRepository {
         Ok(())
     }
 
+    /// Current token epoch, and whether the user must change their password first;
+    /// tokens carrying an older epoch are rejected
+    pub async fn get_token_state(&self, user_id: i32) -> AppResult<Option<(i32, bool)>> {
+        let result: Option<(i32, bool)> =
+            sqlx::query_as("SELECT token_epoch, must_change_password FROM users WHERE id = ?")
+                .bind(user_id)
+                .fetch_optional(&self.pool)
+                .await?;
+
+        Ok(result)
+    }
+
+    /// Invalidate all tokens issued to the user so far
+    pub async fn bump_token_epoch(&self, user_id: i32) -> AppResult<()> {
+        sqlx::query("UPDATE users SET token_epoch = token_epoch + 1 WHERE id = ?")
+            .bind(user_id)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+
     pub async fn list_all(&self) -> AppResult<Vec<User>> {
         let users = sqlx::query_as::<_, User>(
-            "SELECT id, username, password_hash, role, created_at, updated_at FROM users ORDER BY id"
+            "SELECT id, username, password_hash, role, token_epoch, must_change_password, created_at, updated_at FROM users ORDER BY id"
         )
         .fetch_all(&self.pool)
         .await?;
@@ -75,10 +98,21 @@ impl UserRepository {
             .await?;
 
         if count.0 == 0 {
-            self.create("admin", password_hash, "admin").await?;
-            tracing::info!("Default admin user created");
+            let id = self.create("admin", password_hash, "admin").await?;
+            self.require_password_change(id).await?;
+            tracing::info!("Default admin user created; its password must be changed at first login");
         }
 
         Ok(())
     }
+
+    /// Allow the user nothing but a password change until they make one
+    pub async fn require_password_change(&self, user_id: i32) -> AppResult<()> {
+        sqlx::query("UPDATE users SET must_change_password = TRUE WHERE id = ?")
+            .bind(user_id)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
 }
@@ -1,6 +1,8 @@
-use crate::db::models::Wallet;
+use crate::db::models::{DerivedAddress, SpendingLimit, Wallet};
 use crate::error::AppResult;
+use rust_decimal::Decimal;
 use sqlx::MySqlPool;
+use std::collections::HashMap;
 
 pub struct WalletRepository {
     pool: MySqlPool,
@@ -11,6 +13,8 @@ impl WalletRepository {
         Self { pool }
     }
 
+    /// Store a wallet; a mnemonic wallet also records the account index its key was
+    /// derived at, so an exported phrase re-imports to the same address
     pub async fn create(
         &self,
         name: &str,
@@ -18,15 +22,41 @@ impl WalletRepository {
         encrypted_private_key: &str,
         chain: &str,
         orchard_birthday_height: Option<u64>,
+        encrypted_mnemonic: Option<(&str, u32)>,
     ) -> AppResult<i32> {
         let result = sqlx::query(
-            "INSERT INTO wallets (name, address, encrypted_private_key, chain, orchard_birthday_height) VALUES (?, ?, ?, ?, ?)"
+            "INSERT INTO wallets (name, address, encrypted_private_key, chain, orchard_birthday_height, encrypted_mnemonic, mnemonic_account_index) VALUES (?, ?, ?, ?, ?, ?, ?)"
         )
         .bind(name)
         .bind(address)
         .bind(encrypted_private_key)
         .bind(chain)
         .bind(orchard_birthday_height)
+        .bind(encrypted_mnemonic.map(|(mnemonic, _)| mnemonic))
+        .bind(encrypted_mnemonic.map(|(_, account_index)| account_index))
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.last_insert_id() as i32)
+    }
+
+    /// Store a watch-only wallet; it has no private key, only an encrypted viewing key
+    pub async fn create_watch_only(
+        &self,
+        name: &str,
+        address: &str,
+        encrypted_viewing_key: &str,
+        chain: &str,
+        orchard_birthday_height: u64,
+    ) -> AppResult<i32> {
+        let result = sqlx::query(
+            "INSERT INTO wallets (name, address, encrypted_private_key, chain, orchard_birthday_height, watch_only, encrypted_viewing_key) VALUES (?, ?, '', ?, ?, TRUE, ?)"
+        )
+        .bind(name)
+        .bind(address)
+        .bind(chain)
+        .bind(orchard_birthday_height)
+        .bind(encrypted_viewing_key)
         .execute(&self.pool)
         .await?;
 
@@ -45,7 +75,7 @@ impl WalletRepository {
 
     pub async fn find_by_id(&self, id: i32) -> AppResult<Option<Wallet>> {
         let wallet = sqlx::query_as::<_, Wallet>(
-            "SELECT id, name, address, encrypted_private_key, chain, is_active, created_at, orchard_birthday_height FROM wallets WHERE id = ?"
+            "SELECT id, name, address, encrypted_private_key, chain, is_active, created_at, orchard_birthday_height, encrypted_mnemonic, mnemonic_account_index, watch_only, encrypted_viewing_key FROM wallets WHERE id = ?"
         )
         .bind(id)
         .fetch_optional(&self.pool)
@@ -56,7 +86,7 @@ impl WalletRepository {
 
     pub async fn find_by_address(&self, address: &str, chain: &str) -> AppResult<Option<Wallet>> {
         let wallet = sqlx::query_as::<_, Wallet>(
-            "SELECT id, name, address, encrypted_private_key, chain, is_active, created_at, orchard_birthday_height FROM wallets WHERE address = ? AND chain = ?"
+            "SELECT id, name, address, encrypted_private_key, chain, is_active, created_at, orchard_birthday_height, encrypted_mnemonic, mnemonic_account_index, watch_only, encrypted_viewing_key FROM wallets WHERE address = ? AND chain = ?"
         )
         .bind(address)
         .bind(chain)
@@ -68,7 +98,7 @@ impl WalletRepository {
 
     pub async fn list_all(&self) -> AppResult<Vec<Wallet>> {
         let wallets = sqlx::query_as::<_, Wallet>(
-            "SELECT id, name, address, encrypted_private_key, chain, is_active, created_at, orchard_birthday_height FROM wallets ORDER BY id"
+            "SELECT id, name, address, encrypted_private_key, chain, is_active, created_at, orchard_birthday_height, encrypted_mnemonic, mnemonic_account_index, watch_only, encrypted_viewing_key FROM wallets ORDER BY id"
         )
         .fetch_all(&self.pool)
         .await?;
@@ -78,7 +108,7 @@ impl WalletRepository {
 
     pub async fn list_by_chain(&self, chain: &str) -> AppResult<Vec<Wallet>> {
         let wallets = sqlx::query_as::<_, Wallet>(
-            "SELECT id, name, address, encrypted_private_key, chain, is_active, created_at, orchard_birthday_height FROM wallets WHERE chain = ? ORDER BY id"
+            "SELECT id, name, address, encrypted_private_key, chain, is_active, created_at, orchard_birthday_height, encrypted_mnemonic, mnemonic_account_index, watch_only, encrypted_viewing_key FROM wallets WHERE chain = ? ORDER BY id"
         )
         .bind(chain)
         .fetch_all(&self.pool)
@@ -87,9 +117,178 @@ impl WalletRepository {
         Ok(wallets)
     }
 
+    pub async fn list_by_tag(&self, tag: &str) -> AppResult<Vec<Wallet>> {
+        let wallets = sqlx::query_as::<_, Wallet>(
+            "SELECT w.id, w.name, w.address, w.encrypted_private_key, w.chain, w.is_active, w.created_at, w.orchard_birthday_height, w.encrypted_mnemonic, w.mnemonic_account_index, w.watch_only, w.encrypted_viewing_key FROM wallets w JOIN wallet_tags t ON t.wallet_id = w.id WHERE t.tag = ? ORDER BY w.id"
+        )
+        .bind(tag)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(wallets)
+    }
+
+    /// Tags of every tagged wallet, sorted per wallet
+    pub async fn list_tags(&self) -> AppResult<HashMap<i32, Vec<String>>> {
+        let rows: Vec<(i32, String)> =
+            sqlx::query_as("SELECT wallet_id, tag FROM wallet_tags ORDER BY wallet_id, tag")
+                .fetch_all(&self.pool)
+                .await?;
+
+        let mut tags: HashMap<i32, Vec<String>> = HashMap::new();
+        for (wallet_id, tag) in rows {
+            tags.entry(wallet_id).or_default().push(tag);
+        }
+        Ok(tags)
+    }
+
+    pub async fn find_tags(&self, wallet_id: i32) -> AppResult<Vec<String>> {
+        let tags: Vec<(String,)> =
+            sqlx::query_as("SELECT tag FROM wallet_tags WHERE wallet_id = ? ORDER BY tag")
+                .bind(wallet_id)
+                .fetch_all(&self.pool)
+                .await?;
+
+        Ok(tags.into_iter().map(|(tag,)| tag).collect())
+    }
+
+    /// Replace a wallet's tags in one transaction
+    pub async fn set_tags(&self, wallet_id: i32, tags: &[String]) -> AppResult<()> {
+        let mut tx = self.pool.begin().await?;
+
+        sqlx::query("DELETE FROM wallet_tags WHERE wallet_id = ?")
+            .bind(wallet_id)
+            .execute(&mut *tx)
+            .await?;
+
+        for tag in tags {
+            sqlx::query("INSERT INTO wallet_tags (wallet_id, tag) VALUES (?, ?)")
+                .bind(wallet_id)
+                .bind(tag)
+                .execute(&mut *tx)
+                .await?;
+        }
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    pub async fn list_spending_limits(&self, wallet_id: i32) -> AppResult<Vec<SpendingLimit>> {
+        let limits = sqlx::query_as::<_, SpendingLimit>(
+            "SELECT * FROM wallet_spending_limits WHERE wallet_id = ? ORDER BY token"
+        )
+        .bind(wallet_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(limits)
+    }
+
+    pub async fn find_spending_limit(&self, wallet_id: i32, token: &str) -> AppResult<Option<SpendingLimit>> {
+        let limit = sqlx::query_as::<_, SpendingLimit>(
+            "SELECT * FROM wallet_spending_limits WHERE wallet_id = ? AND token = ?"
+        )
+        .bind(wallet_id)
+        .bind(token)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(limit)
+    }
+
+    /// Create or replace the limit on one token of a wallet
+    pub async fn set_spending_limit(
+        &self,
+        wallet_id: i32,
+        token: &str,
+        max_per_transfer: Option<Decimal>,
+        max_per_window: Option<Decimal>,
+        window_secs: i64,
+        updated_by: i32,
+    ) -> AppResult<()> {
+        sqlx::query(
+            r#"INSERT INTO wallet_spending_limits
+            (wallet_id, token, max_per_transfer, max_per_window, window_secs, updated_by)
+            VALUES (?, ?, ?, ?, ?, ?)
+            ON DUPLICATE KEY UPDATE max_per_transfer = VALUES(max_per_transfer),
+                max_per_window = VALUES(max_per_window), window_secs = VALUES(window_secs),
+                updated_by = VALUES(updated_by)"#
+        )
+        .bind(wallet_id)
+        .bind(token)
+        .bind(max_per_transfer)
+        .bind(max_per_window)
+        .bind(window_secs)
+        .bind(updated_by)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Returns false if the wallet had no limit on the token
+    pub async fn delete_spending_limit(&self, wallet_id: i32, token: &str) -> AppResult<bool> {
+        let result = sqlx::query("DELETE FROM wallet_spending_limits WHERE wallet_id = ? AND token = ?")
+            .bind(wallet_id)
+            .bind(token)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    pub async fn list_derived_addresses(&self, wallet_id: i32) -> AppResult<Vec<DerivedAddress>> {
+        let addresses = sqlx::query_as::<_, DerivedAddress>(
+            "SELECT * FROM wallet_derived_addresses WHERE wallet_id = ? ORDER BY derivation_index"
+        )
+        .bind(wallet_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(addresses)
+    }
+
+    pub async fn find_derived_address(&self, wallet_id: i32, index: u32) -> AppResult<Option<DerivedAddress>> {
+        let address = sqlx::query_as::<_, DerivedAddress>(
+            "SELECT * FROM wallet_derived_addresses WHERE wallet_id = ? AND derivation_index = ?"
+        )
+        .bind(wallet_id)
+        .bind(index)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(address)
+    }
+
+    /// Derived address entry for `address`, telling which wallet it belongs to
+    pub async fn find_derived_by_address(&self, address: &str) -> AppResult<Option<DerivedAddress>> {
+        let address = sqlx::query_as::<_, DerivedAddress>(
+            "SELECT * FROM wallet_derived_addresses WHERE address = ?"
+        )
+        .bind(address)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(address)
+    }
+
+    /// Record a derived address; a concurrent insert of the same index is not an error
+    pub async fn add_derived_address(&self, wallet_id: i32, index: u32, address: &str) -> AppResult<()> {
+        sqlx::query(
+            "INSERT IGNORE INTO wallet_derived_addresses (wallet_id, derivation_index, address) VALUES (?, ?, ?)"
+        )
+        .bind(wallet_id)
+        .bind(index)
+        .bind(address)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
     pub async fn get_active_wallet(&self, chain: &str) -> AppResult<Option<Wallet>> {
         let wallet = sqlx::query_as::<_, Wallet>(
-            "SELECT id, name, address, encrypted_private_key, chain, is_active, created_at, orchard_birthday_height FROM wallets WHERE chain = ? AND is_active = TRUE LIMIT 1"
+            "SELECT id, name, address, encrypted_private_key, chain, is_active, created_at, orchard_birthday_height, encrypted_mnemonic, mnemonic_account_index, watch_only, encrypted_viewing_key FROM wallets WHERE chain = ? AND is_active = TRUE LIMIT 1"
         )
         .bind(chain)
         .fetch_optional(&self.pool)
@@ -122,4 +321,26 @@ impl WalletRepository {
 
         Ok(())
     }
+
+    /// Store the encrypted secrets of many wallets in the caller's transaction, so they
+    /// commit together with whatever else it re-encrypts
+    pub async fn update_encrypted_secrets(
+        &self,
+        tx: &mut sqlx::Transaction<'_, sqlx::MySql>,
+        wallets: &[Wallet],
+    ) -> AppResult<()> {
+        for wallet in wallets {
+            sqlx::query(
+                "UPDATE wallets SET encrypted_private_key = ?, encrypted_mnemonic = ?, encrypted_viewing_key = ? WHERE id = ?"
+            )
+            .bind(&wallet.encrypted_private_key)
+            .bind(&wallet.encrypted_mnemonic)
+            .bind(&wallet.encrypted_viewing_key)
+            .bind(wallet.id)
+            .execute(&mut **tx)
+            .await?;
+        }
+
+        Ok(())
+    }
 }
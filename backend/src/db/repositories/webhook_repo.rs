@@ -0,0 +1,39 @@
+use sqlx::MySqlPool;
+
+use crate::error::AppResult;
+
+pub struct WebhookRepository {
+    pool: MySqlPool,
+}
+
+impl WebhookRepository {
+    pub fn new(pool: MySqlPool) -> Self {
+        Self { pool }
+    }
+
+    /// Keep a delivery that failed after every retry
+    pub async fn record_dead_letter(
+        &self,
+        event: &str,
+        url: &str,
+        payload: &serde_json::Value,
+        attempts: u32,
+        last_error: &str,
+    ) -> AppResult<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO webhook_dead_letters (event, url, payload, attempts, last_error)
+            VALUES (?, ?, ?, ?, ?)
+            "#
+        )
+        .bind(event)
+        .bind(url)
+        .bind(payload)
+        .bind(attempts)
+        .bind(last_error)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+}
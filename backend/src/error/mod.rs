@@ -10,6 +10,11 @@ pub enum AppError {
 
     // Authorization errors
     Forbidden(String),
+    SpendingLimitExceeded(String),
+    PasswordChangeRequired,
+
+    // Too many requests; seconds until the client may retry
+    RateLimited(u64),
 
     // Resource errors
     NotFound(String),
@@ -21,6 +26,7 @@ pub enum AppError {
     // Blockchain errors
     BlockchainError(String),
     InsufficientBalance(String),
+    ProposalExpired(String),
 
     // Encryption errors
     EncryptionError(String),
@@ -38,6 +44,32 @@ pub enum AppError {
     NotImplemented(String),
 }
 
+impl AppError {
+    /// Stable machine-readable code, sent as `code` in error responses
+    pub fn code(&self) -> &'static str {
+        match self {
+            AppError::Unauthorized(_) => "UNAUTHORIZED",
+            AppError::InvalidCredentials => "INVALID_CREDENTIALS",
+            AppError::TokenExpired => "TOKEN_EXPIRED",
+            AppError::Forbidden(_) => "FORBIDDEN",
+            AppError::SpendingLimitExceeded(_) => "SPENDING_LIMIT_EXCEEDED",
+            AppError::PasswordChangeRequired => "PASSWORD_CHANGE_REQUIRED",
+            AppError::RateLimited(_) => "RATE_LIMITED",
+            AppError::NotFound(_) => "NOT_FOUND",
+            AppError::AlreadyExists(_) => "ALREADY_EXISTS",
+            AppError::ValidationError(_) => "VALIDATION_ERROR",
+            AppError::BlockchainError(_) => "BLOCKCHAIN_ERROR",
+            AppError::InsufficientBalance(_) => "INSUFFICIENT_BALANCE",
+            AppError::ProposalExpired(_) => "PROPOSAL_EXPIRED",
+            AppError::EncryptionError(_) => "ENCRYPTION_ERROR",
+            AppError::DatabaseError(_) => "DATABASE_ERROR",
+            AppError::ConfigError(_) => "CONFIG_ERROR",
+            AppError::InternalError(_) => "INTERNAL_ERROR",
+            AppError::NotImplemented(_) => "NOT_IMPLEMENTED",
+        }
+    }
+}
+
 impl fmt::Display for AppError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
@@ -45,11 +77,15 @@ impl fmt::Display for AppError {
             AppError::InvalidCredentials => write!(f, "Invalid username or password"),
             AppError::TokenExpired => write!(f, "Token has expired"),
             AppError::Forbidden(msg) => write!(f, "Forbidden: {}", msg),
+            AppError::SpendingLimitExceeded(msg) => write!(f, "Spending limit exceeded: {}", msg),
+            AppError::PasswordChangeRequired => write!(f, "Password change required before continuing"),
+            AppError::RateLimited(secs) => write!(f, "Too many requests, retry after {} seconds", secs),
             AppError::NotFound(msg) => write!(f, "Not found: {}", msg),
             AppError::AlreadyExists(msg) => write!(f, "Already exists: {}", msg),
             AppError::ValidationError(msg) => write!(f, "Validation error: {}", msg),
             AppError::BlockchainError(msg) => write!(f, "Blockchain error: {}", msg),
             AppError::InsufficientBalance(msg) => write!(f, "Insufficient balance: {}", msg),
+            AppError::ProposalExpired(msg) => write!(f, "Proposal expired: {}", msg),
             AppError::EncryptionError(msg) => write!(f, "Encryption error: {}", msg),
             AppError::DatabaseError(msg) => write!(f, "Database error: {}", msg),
             AppError::ConfigError(msg) => write!(f, "Configuration error: {}", msg),
@@ -61,24 +97,34 @@ impl fmt::Display for AppError {
 
 impl ResponseError for AppError {
     fn error_response(&self) -> HttpResponse {
-        let error_message = serde_json::json!({
-            "error": self.to_string()
+        let mut error_message = serde_json::json!({
+            "error": self.to_string(),
+            "code": self.code()
         });
+        // Quoted to support so the failure can be found in the logs
+        if let Some(request_id) = crate::api::middleware::current_request_id() {
+            error_message["request_id"] = request_id.into();
+        }
 
         match self {
             AppError::Unauthorized(_) | AppError::InvalidCredentials | AppError::TokenExpired => {
                 HttpResponse::Unauthorized().json(error_message)
             }
-            AppError::Forbidden(_) => {
+            AppError::Forbidden(_) | AppError::SpendingLimitExceeded(_) | AppError::PasswordChangeRequired => {
                 HttpResponse::Forbidden().json(error_message)
             }
+            AppError::RateLimited(secs) => {
+                HttpResponse::TooManyRequests()
+                    .insert_header((actix_web::http::header::RETRY_AFTER, secs.to_string()))
+                    .json(error_message)
+            }
             AppError::NotFound(_) => {
                 HttpResponse::NotFound().json(error_message)
             }
             AppError::AlreadyExists(_) | AppError::ValidationError(_) => {
                 HttpResponse::BadRequest().json(error_message)
             }
-            AppError::InsufficientBalance(_) => {
+            AppError::InsufficientBalance(_) | AppError::ProposalExpired(_) => {
                 HttpResponse::BadRequest().json(error_message)
             }
             AppError::BlockchainError(_) | AppError::EncryptionError(_)
@@ -123,3 +169,20 @@ impl From<config::ConfigError> for AppError {
 }
 
 pub type AppResult<T> = Result<T, AppError>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::{body::to_bytes, http::StatusCode};
+
+    #[actix_web::test]
+    async fn test_error_response_includes_code() {
+        let response = AppError::InsufficientBalance("need 1 ETH".to_string()).error_response();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+        let body = to_bytes(response.into_body()).await.unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(body["code"], "INSUFFICIENT_BALANCE");
+        assert_eq!(body["error"], "Insufficient balance: need 1 ETH");
+    }
+}
@@ -0,0 +1,55 @@
+use tracing_appender::non_blocking::WorkerGuard;
+use tracing_subscriber::fmt::MakeWriter;
+use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt, Layer, Registry};
+
+use crate::config::{LogConfig, LogFormat};
+
+type BoxedLayer = Box<dyn Layer<Registry> + Send + Sync>;
+
+/// Initialize logging to the console and a size-rotated file under `LOG_DIR`
+///
+/// Keep the returned guard alive until shutdown; dropping it flushes buffered lines.
+pub fn init(config: &LogConfig) -> WorkerGuard {
+    let log_dir = std::env::var("LOG_DIR").unwrap_or_else(|_| "logs".into());
+    std::fs::create_dir_all(&log_dir).expect("Failed to create log directory");
+
+    // File appender - rotates when > 500MB, keeps 10 backup files
+    let log_path = std::path::Path::new(&log_dir).join("web3-wallet.log");
+    let file_appender = rolling_file::RollingFileAppender::new(
+        log_path,
+        rolling_file::RollingConditionBasic::new()
+            .max_size(500 * 1024 * 1024), // 500MB
+        10, // Keep 10 backup files
+    )
+    .expect("Failed to create log file appender");
+
+    let (non_blocking, guard) = tracing_appender::non_blocking(file_appender);
+
+    let env_filter = tracing_subscriber::EnvFilter::new(
+        std::env::var("RUST_LOG").unwrap_or_else(|_| "info,sqlx=warn".into()),
+    );
+
+    let layers = vec![
+        fmt_layer(config.console(), std::io::stdout, true),
+        fmt_layer(config.file(), non_blocking, false),
+    ];
+    tracing_subscriber::registry().with(layers).with(env_filter).init();
+
+    guard
+}
+
+fn fmt_layer<W>(format: LogFormat, writer: W, ansi: bool) -> BoxedLayer
+where
+    W: for<'w> MakeWriter<'w> + Send + Sync + 'static,
+{
+    let layer = tracing_subscriber::fmt::layer().with_target(true).with_writer(writer);
+    match format {
+        LogFormat::Text => layer.with_thread_ids(false).with_ansi(ansi).boxed(),
+        // Fields of the enclosing request span (method, path, user_id, ...) go on every line
+        LogFormat::Json => layer
+            .json()
+            .with_current_span(true)
+            .with_span_list(false)
+            .boxed(),
+    }
+}
@@ -4,67 +4,43 @@ mod config;
 mod crypto;
 mod db;
 mod error;
+mod logging;
+mod metrics;
 mod services;
 
-use actix_cors::Cors;
 use actix_web::{web, App, HttpServer};
 use std::sync::Arc;
-use tokio::time::{interval, Duration};
-use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
+use tokio::time::{interval, timeout, Duration};
 
-use api::handlers::load_rpc_config_from_db;
-use blockchain::{ethereum::EthereumClient, zcash::ZcashClient, ChainRegistry};
+use api::handlers::{load_rpc_config_from_db, load_token_allowlist_from_db, load_zcash_rpc_config_from_db};
+use blockchain::{ethereum::EthereumClient, traits::ChainClient, zcash::ZcashClient, ChainRegistry};
 use config::AppConfig;
-use db::repositories::{SettingsRepository, TransferRepository, UserRepository, WalletRepository};
-use services::{AuthService, TransferService, WalletService};
+use error::AppError;
+use db::repositories::{
+    AddressBookRepository, AuditRepository, DepositRepository, RefreshTokenRepository, RevokedTokenRepository, RolePermissionRepository,
+    SettingsRepository, TransferRepository, UserRepository, WalletRepository, WebhookRepository,
+};
+use services::{
+    AddressBook, AuthService, DepositWatcher, PriceFeed, Shutdown, TransferService, WalletService, WalletServiceConfig,
+    WebhookNotifier,
+};
+
+/// How long shutdown waits for each background task, e.g. an Orchard batch in flight
+const SHUTDOWN_TASK_TIMEOUT: Duration = Duration::from_secs(60);
 
 #[actix_web::main]
 async fn main() -> std::io::Result<()> {
-    // Initialize logging with console and file output
-    let log_dir = std::env::var("LOG_DIR").unwrap_or_else(|_| "logs".into());
-    std::fs::create_dir_all(&log_dir).expect("Failed to create log directory");
-
-    // File appender - rotates when > 500MB, keeps 10 backup files
-    let log_path = std::path::Path::new(&log_dir).join("web3-wallet.log");
-    let file_appender = rolling_file::RollingFileAppender::new(
-        log_path,
-        rolling_file::RollingConditionBasic::new()
-            .max_size(500 * 1024 * 1024), // 500MB
-        10, // Keep 10 backup files
-    )
-    .expect("Failed to create log file appender");
-
-    let (non_blocking, _guard) = tracing_appender::non_blocking(file_appender);
-
-    let env_filter = tracing_subscriber::EnvFilter::new(
-        std::env::var("RUST_LOG").unwrap_or_else(|_| "info,sqlx=warn".into()),
-    );
+    // Load configuration first so it can choose the log format
+    let config = AppConfig::load().expect("Failed to load configuration");
 
-    tracing_subscriber::registry()
-        .with(env_filter)
-        // Console output
-        .with(
-            tracing_subscriber::fmt::layer()
-                .with_target(true)
-                .with_thread_ids(false)
-        )
-        // File output
-        .with(
-            tracing_subscriber::fmt::layer()
-                .with_target(true)
-                .with_ansi(false)
-                .with_writer(non_blocking)
-        )
-        .init();
-
-    // Keep the guard alive for the duration of the program
-    let _log_guard = _guard;
+    // Initialize logging with console and file output; keep the guard alive until
+    // shutdown, dropping it flushes buffered log lines
+    let log_guard = logging::init(&config.log);
 
     tracing::info!("Starting Web3 Wallet Service");
-
-    // Load configuration
-    let config = AppConfig::load().expect("Failed to load configuration");
     tracing::info!("Configuration loaded successfully");
+    config.security.warn_unsafe_settings();
+    config.zcash.warn_unsafe_settings();
     tracing::info!("Database: {}:{}/{}", config.database.host, config.database.port, config.database.name);
 
     // Create database connection pool
@@ -82,6 +58,11 @@ async fn main() -> std::io::Result<()> {
     let wallet_repo = WalletRepository::new(pool.clone());
     let transfer_repo = TransferRepository::new(pool.clone());
     let settings_repo = Arc::new(SettingsRepository::new(pool.clone()));
+    let revoked_token_repo = RevokedTokenRepository::new(pool.clone());
+    let refresh_token_repo = RefreshTokenRepository::new(pool.clone());
+    let role_permission_repo = RolePermissionRepository::new(pool.clone());
+    let audit_repo = Arc::new(AuditRepository::new(pool.clone()));
+    let deposit_repo = Arc::new(DepositRepository::new(pool.clone()));
 
     // Load RPC configuration from database (or use defaults from .env)
     let rpc_config = load_rpc_config_from_db(
@@ -101,9 +82,10 @@ async fn main() -> std::io::Result<()> {
         EthereumClient::new(&eth_config).expect("Failed to create Ethereum client"),
     );
 
-    // Initialize Zcash client
+    // Initialize Zcash client with RPC settings saved through the settings API
+    let zcash_config = load_zcash_rpc_config_from_db(&settings_repo, &config.zcash, &config.security).await;
     let zcash_client = Arc::new(
-        ZcashClient::new(&config.zcash).expect("Failed to create Zcash client"),
+        ZcashClient::new(&zcash_config).expect("Failed to create Zcash client"),
     );
 
     // Initialize chain registry
@@ -111,39 +93,152 @@ async fn main() -> std::io::Result<()> {
     chain_registry.register(eth_client.clone());
     chain_registry.register(zcash_client.clone());
 
+    // Additional EVM chains (Polygon, BSC, ...) each get their own client
+    let mut evm_clients = vec![eth_client.clone()];
+    for (key, evm_config) in &config.evm_chains {
+        let client = Arc::new(
+            EthereumClient::for_evm_chain(key, evm_config)
+                .unwrap_or_else(|e| panic!("Failed to create {} client: {}", key, e)),
+        );
+        chain_registry.register(client.clone());
+        evm_clients.push(client);
+    }
+
+    // A wrong chain_id would sign transactions for another network; refuse to start.
+    // An unreachable RPC only warns, the fallbacks may still work later.
+    for client in &evm_clients {
+        match client.verify_chain_id().await {
+            Ok(()) => {}
+            Err(e @ AppError::ConfigError(_)) => panic!("{}", e),
+            Err(e) => tracing::warn!("Could not verify chain id of {}: {}", client.chain_id(), e),
+        }
+        load_token_allowlist_from_db(&settings_repo, client.as_ref()).await;
+    }
+
     let chain_registry = Arc::new(chain_registry);
 
     // Initialize services
-    let auth_service = Arc::new(AuthService::new(user_repo, config.jwt.clone()));
+    let auth_service = Arc::new(AuthService::new(
+        user_repo,
+        revoked_token_repo,
+        refresh_token_repo,
+        role_permission_repo,
+        config.jwt.clone(),
+    ));
+    let webhook_notifier = Arc::new(WebhookNotifier::new(
+        config.webhook.clone(),
+        WebhookRepository::new(pool.clone()),
+    ));
+    // Signalled once the HTTP server has stopped; background tasks finish their round and exit
+    let shutdown = Shutdown::new();
+    let mut background_tasks = Vec::new();
+
     let wallet_service = Arc::new(WalletService::new(
         wallet_repo,
         chain_registry.clone(),
-        config.security.clone(),
+        WalletServiceConfig {
+            security: config.security.clone(),
+            transfer_limits: config.transfer_limits.clone(),
+            zcash: config.zcash.clone(),
+            balance_cache_ttl: Duration::from_secs(config.balance_cache.ttl_secs),
+        },
         pool.clone(),
+        webhook_notifier.clone(),
+        shutdown.clone(),
     ));
+    let address_book = Arc::new(AddressBook::new(
+        AddressBookRepository::new(pool.clone()),
+        chain_registry.clone(),
+        config.transfer_limits.whitelist_only,
+    ));
+    if address_book.whitelist_only() {
+        tracing::info!("Whitelist-only mode: transfers may only go to address book entries");
+    }
     let transfer_service = Arc::new(TransferService::new(
         transfer_repo,
         wallet_service.clone(),
         chain_registry.clone(),
+        config.transfer_limits.clone(),
+        webhook_notifier.clone(),
+        address_book.clone(),
+        Arc::new(PriceFeed::new(config.price.clone())),
     ));
 
     // Create default admin user
     auth_service
-        .create_default_admin()
+        .create_default_admin(&config.security.admin_password)
         .await
         .expect("Failed to create default admin");
 
     // Start background task for checking pending transfers
     let transfer_service_bg = transfer_service.clone();
-    tokio::spawn(async move {
+    let shutdown_bg = shutdown.clone();
+    background_tasks.push(tokio::spawn(async move {
         let mut interval = interval(Duration::from_secs(30));
         loop {
-            interval.tick().await;
+            tokio::select! {
+                _ = interval.tick() => {}
+                _ = shutdown_bg.wait() => break,
+            }
             if let Err(e) = transfer_service_bg.check_pending_transfers().await {
                 tracing::error!("Error checking pending transfers: {}", e);
             }
         }
-    });
+    }));
+
+    // Release idempotency keys once their window has passed
+    let transfer_service_bg = transfer_service.clone();
+    let shutdown_bg = shutdown.clone();
+    background_tasks.push(tokio::spawn(async move {
+        let mut interval = interval(Duration::from_secs(3600));
+        loop {
+            tokio::select! {
+                _ = interval.tick() => {}
+                _ = shutdown_bg.wait() => break,
+            }
+            match transfer_service_bg.expire_idempotency_keys().await {
+                Ok(expired) if expired > 0 => {
+                    tracing::info!("Expired {} transfer idempotency keys", expired);
+                }
+                Ok(_) => {}
+                Err(e) => tracing::error!("Error expiring idempotency keys: {}", e),
+            }
+        }
+    }));
+
+    // Purge revoked tokens that have expired anyway
+    let auth_service_bg = auth_service.clone();
+    let shutdown_bg = shutdown.clone();
+    background_tasks.push(tokio::spawn(async move {
+        let mut interval = interval(Duration::from_secs(3600));
+        loop {
+            tokio::select! {
+                _ = interval.tick() => {}
+                _ = shutdown_bg.wait() => break,
+            }
+            match auth_service_bg.purge_revoked_tokens().await {
+                Ok(purged) if purged > 0 => {
+                    tracing::info!("Purged {} expired revoked and refresh tokens", purged);
+                }
+                Ok(_) => {}
+                Err(e) => tracing::error!("Error purging revoked tokens: {}", e),
+            }
+        }
+    }));
+
+    // Record transparent deposits to Zcash wallet t-addresses
+    if config.zcash.deposit_poll_secs > 0 {
+        let deposit_watcher = Arc::new(DepositWatcher::new(
+            chain_registry.clone(),
+            WalletRepository::new(pool.clone()),
+            TransferRepository::new(pool.clone()),
+            DepositRepository::new(pool.clone()),
+            webhook_notifier.clone(),
+            Duration::from_secs(config.zcash.deposit_poll_secs),
+            shutdown.clone(),
+        ));
+        background_tasks.push(deposit_watcher.start());
+    }
 
     // Pre-build Orchard proving key in background (expensive one-time operation)
     // This ensures the first privacy transfer doesn't have to wait
@@ -160,36 +255,59 @@ async fn main() -> std::io::Result<()> {
     });
 
     // Start Orchard background sync task (syncs all Zcash wallets every 5 minutes)
-    wallet_service.clone().start_background_sync();
+    background_tasks.push(wallet_service.clone().start_background_sync());
 
     let server_host = config.server.host.clone();
     let server_port = config.server.port;
+    let trusted_proxies = config.server.trusted_proxies;
+    let json_limit_bytes = config.server.json_limit_bytes;
+    let server_config = config.server.clone();
     let auth_service_for_routes = auth_service.clone();
+    let rate_limiter = Arc::new(api::middleware::RateLimiter::new(config.rate_limit.clone()));
 
     tracing::info!("Starting HTTP server at {}:{}", server_host, server_port);
 
     let settings_repo_for_app = settings_repo.clone();
     let eth_client_for_app = eth_client.clone();
+    let zcash_client_for_app = zcash_client.clone();
+    let security_config_for_app = config.security.clone();
 
-    HttpServer::new(move || {
-        let cors = Cors::default()
-            .allow_any_origin()
-            .allow_any_method()
-            .allow_any_header()
-            .max_age(3600);
-
+    // actix stops accepting connections on SIGINT/SIGTERM and drains in-flight requests
+    let server_result = HttpServer::new(move || {
         App::new()
-            .wrap(cors)
+            .wrap(api::cors(&server_config))
             .wrap(actix_web::middleware::from_fn(api::middleware::request_logger))
             .app_data(web::Data::new(auth_service.clone()))
             .app_data(web::Data::new(wallet_service.clone()))
             .app_data(web::Data::new(transfer_service.clone()))
             .app_data(web::Data::new(chain_registry.clone()))
+            .app_data(web::Data::new(address_book.clone()))
+            .app_data(web::Data::new(pool.clone()))
             .app_data(web::Data::new(settings_repo_for_app.clone()))
             .app_data(web::Data::new(eth_client_for_app.clone()))
-            .configure(|cfg| api::configure_routes(cfg, auth_service_for_routes.clone()))
+            .app_data(web::Data::new(zcash_client_for_app.clone()))
+            .app_data(web::Data::new(security_config_for_app.clone()))
+            .app_data(web::Data::new(audit_repo.clone()))
+            .app_data(web::Data::new(deposit_repo.clone()))
+            .app_data(web::Data::new(api::middleware::TrustedProxies(trusted_proxies)))
+            .app_data(api::json_config(json_limit_bytes))
+            .configure(|cfg| {
+                api::configure_routes(cfg, auth_service_for_routes.clone(), rate_limiter.clone())
+            })
     })
     .bind((server_host, server_port))?
     .run()
-    .await
+    .await;
+
+    tracing::info!("HTTP server stopped, waiting for background tasks");
+    shutdown.trigger();
+    for task in background_tasks {
+        if timeout(SHUTDOWN_TASK_TIMEOUT, task).await.is_err() {
+            tracing::warn!("Background task did not stop within {:?}", SHUTDOWN_TASK_TIMEOUT);
+        }
+    }
+    tracing::info!("Shutdown complete");
+    drop(log_guard);
+
+    server_result
 }
@@ -0,0 +1,189 @@
+//! Process-wide metrics exported in Prometheus text format on `/metrics`
+//!
+//! Labels are limited to a fixed set (transfer status, pool state) so the
+//! number of series never grows with traffic.
+
+use once_cell::sync::Lazy;
+use std::fmt::Write;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+
+use crate::blockchain::zcash::orchard::ScanProgress;
+use crate::db::PoolStats;
+
+pub static METRICS: Lazy<Metrics> = Lazy::new(Metrics::default);
+
+/// Transfer statuses counted by `web3_wallet_transfers_total`
+const TRANSFER_STATUSES: [&str; 4] = ["submitted", "confirmed", "failed", "rejected"];
+
+/// Upper bounds (seconds) of the proof generation histogram buckets
+const PROOF_BUCKETS: [f64; 8] = [0.5, 1.0, 2.0, 5.0, 10.0, 20.0, 30.0, 60.0];
+
+#[derive(Default)]
+struct Histogram {
+    /// Observations per bucket (not cumulative); the last slot is +Inf
+    buckets: [u64; PROOF_BUCKETS.len() + 1],
+    sum: f64,
+    count: u64,
+}
+
+#[derive(Default)]
+pub struct Metrics {
+    transfers: [AtomicU64; TRANSFER_STATUSES.len()],
+    orchard_sync_height: AtomicU64,
+    orchard_chain_tip: AtomicU64,
+    /// f64 stored as bits
+    orchard_blocks_per_second: AtomicU64,
+    proof_seconds: Mutex<Histogram>,
+}
+
+impl Metrics {
+    /// Count a transfer reaching `status`; statuses outside the fixed set are ignored
+    pub fn record_transfer_status(&self, status: &str) {
+        if let Some(i) = TRANSFER_STATUSES.iter().position(|s| *s == status) {
+            self.transfers[i].fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    /// Update Orchard sync gauges from a progress report
+    pub fn record_sync_progress(&self, progress: &ScanProgress) {
+        self.orchard_sync_height
+            .store(progress.last_scanned_height, Ordering::Relaxed);
+        self.orchard_chain_tip
+            .store(progress.chain_tip_height, Ordering::Relaxed);
+        if let Some(blocks_per_second) = progress.blocks_per_second {
+            self.orchard_blocks_per_second
+                .store(blocks_per_second.to_bits(), Ordering::Relaxed);
+        }
+    }
+
+    /// Record how long an Orchard `create_proof` took
+    pub fn observe_proof_seconds(&self, seconds: f64) {
+        let bucket = PROOF_BUCKETS
+            .iter()
+            .position(|le| seconds <= *le)
+            .unwrap_or(PROOF_BUCKETS.len());
+
+        let mut histogram = self.proof_seconds.lock().unwrap_or_else(|e| e.into_inner());
+        histogram.buckets[bucket] += 1;
+        histogram.sum += seconds;
+        histogram.count += 1;
+    }
+
+    /// Render all metrics, with the database pool sampled at scrape time
+    pub fn render(&self, pool: &PoolStats) -> String {
+        let mut out = String::new();
+
+        out.push_str("# HELP web3_wallet_transfers_total Transfers that reached each status\n");
+        out.push_str("# TYPE web3_wallet_transfers_total counter\n");
+        for (status, counter) in TRANSFER_STATUSES.iter().zip(&self.transfers) {
+            let _ = writeln!(
+                out,
+                "web3_wallet_transfers_total{{status=\"{}\"}} {}",
+                status,
+                counter.load(Ordering::Relaxed)
+            );
+        }
+
+        out.push_str("# HELP web3_wallet_db_pool_connections Database pool connections by state\n");
+        out.push_str("# TYPE web3_wallet_db_pool_connections gauge\n");
+        let _ = writeln!(out, "web3_wallet_db_pool_connections{{state=\"active\"}} {}", pool.active);
+        let _ = writeln!(out, "web3_wallet_db_pool_connections{{state=\"idle\"}} {}", pool.idle);
+
+        out.push_str("# HELP web3_wallet_orchard_sync_height Last block scanned by the Orchard sync\n");
+        out.push_str("# TYPE web3_wallet_orchard_sync_height gauge\n");
+        let _ = writeln!(
+            out,
+            "web3_wallet_orchard_sync_height {}",
+            self.orchard_sync_height.load(Ordering::Relaxed)
+        );
+
+        out.push_str("# HELP web3_wallet_orchard_chain_tip Chain tip seen by the Orchard sync\n");
+        out.push_str("# TYPE web3_wallet_orchard_chain_tip gauge\n");
+        let _ = writeln!(
+            out,
+            "web3_wallet_orchard_chain_tip {}",
+            self.orchard_chain_tip.load(Ordering::Relaxed)
+        );
+
+        out.push_str("# HELP web3_wallet_orchard_sync_blocks_per_second Orchard scan rate of the last sync\n");
+        out.push_str("# TYPE web3_wallet_orchard_sync_blocks_per_second gauge\n");
+        let _ = writeln!(
+            out,
+            "web3_wallet_orchard_sync_blocks_per_second {}",
+            f64::from_bits(self.orchard_blocks_per_second.load(Ordering::Relaxed))
+        );
+
+        let histogram = self.proof_seconds.lock().unwrap_or_else(|e| e.into_inner());
+        out.push_str("# HELP web3_wallet_orchard_proof_seconds Time spent creating Orchard proofs\n");
+        out.push_str("# TYPE web3_wallet_orchard_proof_seconds histogram\n");
+        let mut cumulative = 0;
+        for (le, count) in PROOF_BUCKETS.iter().zip(&histogram.buckets) {
+            cumulative += count;
+            let _ = writeln!(out, "web3_wallet_orchard_proof_seconds_bucket{{le=\"{}\"}} {}", le, cumulative);
+        }
+        let _ = writeln!(
+            out,
+            "web3_wallet_orchard_proof_seconds_bucket{{le=\"+Inf\"}} {}",
+            histogram.count
+        );
+        let _ = writeln!(out, "web3_wallet_orchard_proof_seconds_sum {}", histogram.sum);
+        let _ = writeln!(out, "web3_wallet_orchard_proof_seconds_count {}", histogram.count);
+
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pool() -> PoolStats {
+        PoolStats { size: 5, idle: 3, active: 2 }
+    }
+
+    #[test]
+    fn test_transfer_counters_ignore_unknown_status() {
+        let metrics = Metrics::default();
+        metrics.record_transfer_status("confirmed");
+        metrics.record_transfer_status("confirmed");
+        metrics.record_transfer_status("failed");
+        metrics.record_transfer_status("something-else");
+
+        let text = metrics.render(&pool());
+        assert!(text.contains("web3_wallet_transfers_total{status=\"confirmed\"} 2\n"));
+        assert!(text.contains("web3_wallet_transfers_total{status=\"failed\"} 1\n"));
+        assert!(text.contains("web3_wallet_transfers_total{status=\"submitted\"} 0\n"));
+        assert!(!text.contains("something-else"));
+        assert!(text.contains("web3_wallet_db_pool_connections{state=\"active\"} 2\n"));
+    }
+
+    #[test]
+    fn test_proof_histogram_is_cumulative() {
+        let metrics = Metrics::default();
+        metrics.observe_proof_seconds(0.3);
+        metrics.observe_proof_seconds(4.0);
+        metrics.observe_proof_seconds(90.0);
+
+        let text = metrics.render(&pool());
+        assert!(text.contains("web3_wallet_orchard_proof_seconds_bucket{le=\"0.5\"} 1\n"));
+        assert!(text.contains("web3_wallet_orchard_proof_seconds_bucket{le=\"5\"} 2\n"));
+        assert!(text.contains("web3_wallet_orchard_proof_seconds_bucket{le=\"60\"} 2\n"));
+        assert!(text.contains("web3_wallet_orchard_proof_seconds_bucket{le=\"+Inf\"} 3\n"));
+        assert!(text.contains("web3_wallet_orchard_proof_seconds_count 3\n"));
+    }
+
+    #[test]
+    fn test_sync_gauges() {
+        let metrics = Metrics::default();
+        let mut progress = ScanProgress::new("zcash", "orchard", 1_687_104, 2_800_000);
+        progress.last_scanned_height = 2_799_000;
+        progress.blocks_per_second = Some(125.5);
+        metrics.record_sync_progress(&progress);
+
+        let text = metrics.render(&pool());
+        assert!(text.contains("web3_wallet_orchard_sync_height 2799000\n"));
+        assert!(text.contains("web3_wallet_orchard_chain_tip 2800000\n"));
+        assert!(text.contains("web3_wallet_orchard_sync_blocks_per_second 125.5\n"));
+    }
+}
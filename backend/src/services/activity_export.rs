@@ -0,0 +1,172 @@
+use serde::Serialize;
+
+use crate::db::models::Transfer;
+
+/// Transfers fetched from the database per chunk of an export
+pub const EXPORT_PAGE_SIZE: i32 = 500;
+
+/// Output format of a wallet activity export
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    Csv,
+    Json,
+}
+
+impl ExportFormat {
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            ExportFormat::Csv => "text/csv; charset=utf-8",
+            ExportFormat::Json => "application/json",
+        }
+    }
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ExportFormat::Csv => "csv",
+            ExportFormat::Json => "json",
+        }
+    }
+
+    /// Text before the first row
+    pub fn header(&self) -> String {
+        match self {
+            ExportFormat::Csv => format!("{}\n", ActivityRow::COLUMNS.join(",")),
+            ExportFormat::Json => "[".to_string(),
+        }
+    }
+
+    /// Text after the last row
+    pub fn footer(&self) -> &'static str {
+        match self {
+            ExportFormat::Csv => "",
+            ExportFormat::Json => "]",
+        }
+    }
+
+    /// One row, `first` telling whether a JSON separator is needed before it
+    pub fn row(&self, row: &ActivityRow, first: bool) -> String {
+        match self {
+            ExportFormat::Csv => row.to_csv_line(),
+            ExportFormat::Json => {
+                let json = serde_json::to_string(row).unwrap_or_default();
+                if first {
+                    json
+                } else {
+                    format!(",{}", json)
+                }
+            }
+        }
+    }
+}
+
+/// One transfer as exported for bookkeeping
+#[derive(Debug, Clone, Serialize)]
+pub struct ActivityRow {
+    /// Creation time, RFC 3339 in UTC
+    pub date: String,
+    pub chain: String,
+    pub token: String,
+    pub amount: String,
+    /// Fee in the chain's native coin, empty until known
+    pub fee: String,
+    pub fee_token: String,
+    pub from: String,
+    pub to: String,
+    pub status: String,
+    pub tx_hash: String,
+}
+
+impl ActivityRow {
+    pub const COLUMNS: [&'static str; 10] = [
+        "date", "chain", "token", "amount", "fee", "fee_token", "from", "to", "status", "tx_hash",
+    ];
+
+    /// `fee` is the fee in native coin with its symbol, when the chain is known
+    pub fn new(transfer: &Transfer, fee: Option<(String, String)>) -> Self {
+        let (fee, fee_token) = fee.unwrap_or_default();
+        Self {
+            date: transfer.created_at.to_rfc3339(),
+            chain: transfer.chain.clone(),
+            token: transfer.token.clone(),
+            amount: transfer.amount.normalize().to_string(),
+            fee,
+            fee_token,
+            from: transfer.from_address.clone(),
+            to: transfer.to_address.clone(),
+            status: transfer.status.clone(),
+            tx_hash: transfer.tx_hash.clone().unwrap_or_default(),
+        }
+    }
+
+    fn to_csv_line(&self) -> String {
+        let fields = [
+            &self.date,
+            &self.chain,
+            &self.token,
+            &self.amount,
+            &self.fee,
+            &self.fee_token,
+            &self.from,
+            &self.to,
+            &self.status,
+            &self.tx_hash,
+        ];
+        let line: Vec<String> = fields.iter().map(|field| csv_field(field)).collect();
+        format!("{}\n", line.join(","))
+    }
+}
+
+/// Quote a CSV field when needed, and defuse text a spreadsheet would run as a formula
+fn csv_field(value: &str) -> String {
+    let value = if value.starts_with(['=', '+', '-', '@']) {
+        format!("'{}", value)
+    } else {
+        value.to_string()
+    };
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_csv_field_escaping() {
+        assert_eq!(csv_field("0xabc"), "0xabc");
+        assert_eq!(csv_field("a,b"), "\"a,b\"");
+        assert_eq!(csv_field("say \"hi\""), "\"say \"\"hi\"\"\"");
+        assert_eq!(csv_field("=HYPERLINK(1)"), "'=HYPERLINK(1)");
+    }
+
+    #[test]
+    fn test_json_rows_form_an_array() {
+        let row = ActivityRow {
+            date: "2024-01-02T03:04:05+00:00".to_string(),
+            chain: "ethereum".to_string(),
+            token: "ETH".to_string(),
+            amount: "1.5".to_string(),
+            fee: "0.00042".to_string(),
+            fee_token: "ETH".to_string(),
+            from: "0xfrom".to_string(),
+            to: "0xto".to_string(),
+            status: "confirmed".to_string(),
+            tx_hash: "0xhash".to_string(),
+        };
+
+        let format = ExportFormat::Json;
+        let body = format.header() + &format.row(&row, true) + &format.row(&row, false) + format.footer();
+        let parsed: Vec<serde_json::Value> = serde_json::from_str(&body).unwrap();
+        assert_eq!(parsed.len(), 2);
+        assert_eq!(parsed[0]["fee"], "0.00042");
+
+        let csv = ExportFormat::Csv;
+        assert_eq!(
+            csv.row(&row, true),
+            "2024-01-02T03:04:05+00:00,ethereum,ETH,1.5,0.00042,ETH,0xfrom,0xto,confirmed,0xhash\n"
+        );
+    }
+}
@@ -0,0 +1,206 @@
+use std::sync::Arc;
+
+use crate::blockchain::traits::ChainClient;
+use crate::blockchain::ChainRegistry;
+use crate::db::models::{normalize_address_label, AddressBookEntry, CreateAddressBookEntryRequest};
+use crate::db::repositories::AddressBookRepository;
+use crate::error::{AppError, AppResult};
+
+/// Deployment-wide book of named recipients
+///
+/// Transfers may name a recipient by its label instead of its address. In
+/// whitelist-only mode they may only go to addresses in the book.
+pub struct AddressBook {
+    repo: AddressBookRepository,
+    chain_registry: Arc<ChainRegistry>,
+    whitelist_only: bool,
+}
+
+impl AddressBook {
+    pub fn new(repo: AddressBookRepository, chain_registry: Arc<ChainRegistry>, whitelist_only: bool) -> Self {
+        Self {
+            repo,
+            chain_registry,
+            whitelist_only,
+        }
+    }
+
+    pub fn whitelist_only(&self) -> bool {
+        self.whitelist_only
+    }
+
+    pub async fn list(&self, chain: Option<&str>) -> AppResult<Vec<AddressBookEntry>> {
+        self.repo.list(chain).await
+    }
+
+    /// Add a recipient after checking its address is valid on the chain
+    pub async fn add(&self, request: &CreateAddressBookEntryRequest, user_id: i32) -> AppResult<AddressBookEntry> {
+        let client = self.chain_registry.get(&request.chain)?;
+        let label = normalize_address_label(&request.label)?;
+        let address = request.address.trim();
+
+        if !client.validate_address(address) {
+            return Err(AppError::ValidationError(format!(
+                "Invalid {} address",
+                client.chain_name()
+            )));
+        }
+        // Labels are looked up before addresses, so one must never shadow another address
+        if client.validate_address(&label) {
+            return Err(AppError::ValidationError("A label cannot itself be an address".to_string()));
+        }
+        if self.repo.find_by_label(client.chain_id(), &label).await?.is_some() {
+            return Err(AppError::AlreadyExists(format!(
+                "Address book label '{}' on {}",
+                label,
+                client.chain_id()
+            )));
+        }
+
+        let id = self.repo.create(&label, client.chain_id(), address, user_id).await?;
+        self.repo
+            .find_by_id(id)
+            .await?
+            .ok_or_else(|| AppError::InternalError("Failed to retrieve address book entry".to_string()))
+    }
+
+    /// Remove an entry, returning what was removed
+    pub async fn remove(&self, id: i32) -> AppResult<AddressBookEntry> {
+        let entry = self
+            .repo
+            .find_by_id(id)
+            .await?
+            .ok_or_else(|| AppError::NotFound(format!("Address book entry {} not found", id)))?;
+        self.repo.delete(id).await?;
+        Ok(entry)
+    }
+
+    /// Destination of a transfer addressed to `recipient`
+    ///
+    /// A label in the book wins; anything else goes through the chain's name
+    /// resolution (e.g. ENS), is validated, and in whitelist-only mode must be listed.
+    pub async fn resolve_recipient(&self, client: &dyn ChainClient, recipient: &str) -> AppResult<String> {
+        if let Some(address) = self.lookup_label(client.chain_id(), recipient).await? {
+            return Ok(address);
+        }
+
+        let address = client.resolve_address(recipient).await?;
+        if address != recipient {
+            tracing::info!("Recipient {} resolved to {}", recipient, address);
+        }
+        if !client.validate_address(&address) {
+            return Err(AppError::ValidationError("Invalid destination address".to_string()));
+        }
+        self.ensure_listed(client.chain_id(), &address).await?;
+        Ok(address)
+    }
+
+    /// Like `resolve_recipient`, for callers that validate the address themselves
+    /// (shielded Zcash transfers)
+    pub async fn resolve_label(&self, chain: &str, recipient: &str) -> AppResult<String> {
+        if let Some(address) = self.lookup_label(chain, recipient).await? {
+            return Ok(address);
+        }
+        self.ensure_listed(chain, recipient).await?;
+        Ok(recipient.to_string())
+    }
+
+    async fn lookup_label(&self, chain: &str, recipient: &str) -> AppResult<Option<String>> {
+        let Some(entry) = self.repo.find_by_label(chain, recipient.trim()).await? else {
+            return Ok(None);
+        };
+        tracing::info!("Recipient '{}' resolved from the address book to {}", entry.label, entry.address);
+        Ok(Some(entry.address))
+    }
+
+    /// In whitelist-only mode, reject addresses that are not in the book
+    ///
+    /// Checked again when a transfer is sent, as its entry may have been removed
+    /// since the transfer was created.
+    pub async fn ensure_listed(&self, chain: &str, address: &str) -> AppResult<()> {
+        if !self.whitelist_only {
+            return Ok(());
+        }
+        let entries = self.repo.list(Some(chain)).await?;
+        check_listed(&entries, chain, address)
+    }
+}
+
+/// Reject `address` unless an entry of the chain holds it
+///
+/// EVM addresses match in any letter case, like the column's collation.
+fn check_listed(entries: &[AddressBookEntry], chain: &str, address: &str) -> AppResult<()> {
+    let address = address.trim();
+    let listed = entries.iter().any(|entry| {
+        entry.chain == chain
+            && (entry.address == address
+                || (address.starts_with("0x") && entry.address.eq_ignore_ascii_case(address)))
+    });
+    if !listed {
+        return Err(AppError::Forbidden(format!(
+            "{} is not in the {} address book; whitelist-only mode only sends to listed recipients",
+            address, chain
+        )));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    const ETH_LISTED: &str = "0x52908400098527886E0F7030069857D2E4169EE7";
+    const ETH_UNLISTED: &str = "0x8617E340B3D01FA5F11F306F4090FD50E238070D";
+    const ZCASH_LISTED: &str = "u1listedrecipient";
+
+    fn entry(id: i32, chain: &str, address: &str) -> AddressBookEntry {
+        AddressBookEntry {
+            id,
+            label: format!("entry-{}", id),
+            chain: chain.to_string(),
+            address: address.to_string(),
+            created_by: Some(1),
+            created_at: Utc::now(),
+        }
+    }
+
+    fn book() -> Vec<AddressBookEntry> {
+        vec![entry(1, "ethereum", ETH_LISTED), entry(2, "zcash", ZCASH_LISTED)]
+    }
+
+    #[test]
+    fn test_unlisted_eth_recipient_is_rejected() {
+        assert!(check_listed(&book(), "ethereum", ETH_LISTED).is_ok());
+        assert!(check_listed(&book(), "ethereum", &ETH_LISTED.to_lowercase()).is_ok());
+        assert!(matches!(
+            check_listed(&book(), "ethereum", ETH_UNLISTED),
+            Err(AppError::Forbidden(_))
+        ));
+        // Listed on another chain only
+        assert!(check_listed(&book(), "polygon", ETH_LISTED).is_err());
+    }
+
+    #[test]
+    fn test_unlisted_batch_item_is_rejected() {
+        let items = [ETH_LISTED, ETH_UNLISTED, ETH_LISTED];
+        let rejected: Vec<usize> = items
+            .iter()
+            .enumerate()
+            .filter(|(_, address)| check_listed(&book(), "ethereum", address).is_err())
+            .map(|(index, _)| index)
+            .collect();
+        assert_eq!(rejected, vec![1]);
+    }
+
+    #[test]
+    fn test_unlisted_orchard_recipient_is_rejected() {
+        assert!(check_listed(&book(), "zcash", ZCASH_LISTED).is_ok());
+        // Shielded addresses are case-sensitive
+        assert!(check_listed(&book(), "zcash", &ZCASH_LISTED.to_uppercase()).is_err());
+        assert!(matches!(
+            check_listed(&book(), "zcash", "u1someoneelse"),
+            Err(AppError::Forbidden(_))
+        ));
+    }
+}
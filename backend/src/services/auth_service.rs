@@ -1,12 +1,17 @@
-use chrono::{Duration, Utc};
+use chrono::{DateTime, Duration, Utc};
 use jsonwebtoken::{decode, encode, DecodingKey, EncodingKey, Header, Validation};
 use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashSet;
 
-use crate::config::JwtConfig;
-use crate::crypto::password::{hash_password, verify_password};
-use crate::db::models::{LoginRequest, LoginResponse, User, UserResponse};
-use crate::db::repositories::UserRepository;
+use crate::config::{JwtConfig, INSECURE_ADMIN_PASSWORD};
+use crate::crypto::password::{hash_password, validate_password_strength, verify_password};
+use crate::db::models::{LoginRequest, LoginResponse, TokenResponse, User, UserResponse};
+use crate::db::repositories::{
+    RefreshTokenRepository, RevokedTokenRepository, RolePermissionRepository, UserRepository,
+};
 use crate::error::{AppError, AppResult};
+use crate::services::permissions::{resolve_permissions, Permission, ADMIN_ROLE};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Claims {
@@ -15,21 +20,84 @@ pub struct Claims {
     pub role: String,
     pub exp: i64,
     pub iat: i64,
+    /// Unique token id, used for revocation on logout
+    #[serde(default)]
+    pub jti: String,
+    /// User's token epoch at issue time; bumped by "logout all sessions"
+    #[serde(default)]
+    pub epoch: i32,
+    /// Read from the user record when the token is validated, not carried in the token
+    #[serde(skip)]
+    pub must_change_password: bool,
+}
+
+/// Refresh tokens are random, so an unsalted hash is enough to keep the stored value useless
+fn hash_refresh_token(token: &str) -> String {
+    hex::encode(Sha256::digest(token.as_bytes()))
 }
 
 pub struct AuthService {
     user_repo: UserRepository,
+    revoked_token_repo: RevokedTokenRepository,
+    refresh_token_repo: RefreshTokenRepository,
+    role_permission_repo: RolePermissionRepository,
     jwt_config: JwtConfig,
 }
 
 impl AuthService {
-    pub fn new(user_repo: UserRepository, jwt_config: JwtConfig) -> Self {
+    pub fn new(
+        user_repo: UserRepository,
+        revoked_token_repo: RevokedTokenRepository,
+        refresh_token_repo: RefreshTokenRepository,
+        role_permission_repo: RolePermissionRepository,
+        jwt_config: JwtConfig,
+    ) -> Self {
         Self {
             user_repo,
+            revoked_token_repo,
+            refresh_token_repo,
+            role_permission_repo,
             jwt_config,
         }
     }
 
+    /// Lifetime of an access token
+    fn access_token_ttl(&self) -> Duration {
+        if self.jwt_config.refresh_tokens {
+            Duration::minutes(self.jwt_config.access_token_minutes as i64)
+        } else {
+            Duration::hours(self.jwt_config.expire_hours as i64)
+        }
+    }
+
+    /// Access token, plus a refresh token when enabled
+    ///
+    /// `family_id` continues an existing refresh chain; `None` starts a new one.
+    async fn issue_tokens(&self, user: &User, family_id: Option<&str>) -> AppResult<TokenResponse> {
+        let token = self.generate_token(user)?;
+
+        let refresh_token = if self.jwt_config.refresh_tokens {
+            let refresh_token = hex::encode(rand::random::<[u8; 32]>());
+            let family_id = match family_id {
+                Some(id) => id.to_string(),
+                None => hex::encode(rand::random::<[u8; 16]>()),
+            };
+            let expires_at = Utc::now() + Duration::days(self.jwt_config.refresh_token_days as i64);
+            self.refresh_token_repo
+                .create(&hash_refresh_token(&refresh_token), user.id, &family_id, user.token_epoch, expires_at)
+                .await?;
+            Some(refresh_token)
+        } else {
+            None
+        };
+
+        Ok(TokenResponse {
+            token,
+            refresh_token,
+            expires_in: self.access_token_ttl().num_seconds(),
+        })
+    }
+
     pub async fn login(&self, request: LoginRequest) -> AppResult<LoginResponse> {
         let user = self
             .user_repo
@@ -41,20 +109,65 @@ impl AuthService {
             return Err(AppError::InvalidCredentials);
         }
 
-        let token = self.generate_token(&user)?;
+        let tokens = self.issue_tokens(&user, None).await?;
 
         Ok(LoginResponse {
-            token,
+            token: tokens.token,
+            refresh_token: tokens.refresh_token,
+            expires_in: tokens.expires_in,
             user: UserResponse::from(user),
         })
     }
 
+    /// Exchange a refresh token for a new access token and the next refresh token
+    ///
+    /// Each refresh token works once. Presenting one that was already used means it
+    /// was copied, so every token from the same login is revoked.
+    pub async fn refresh(&self, refresh_token: &str) -> AppResult<TokenResponse> {
+        if !self.jwt_config.refresh_tokens {
+            return Err(AppError::ValidationError("Refresh tokens are not enabled".to_string()));
+        }
+
+        let stored = self
+            .refresh_token_repo
+            .find_by_hash(&hash_refresh_token(refresh_token))
+            .await?
+            .ok_or_else(|| AppError::Unauthorized("Invalid refresh token".to_string()))?;
+
+        if stored.expires_at < Utc::now() {
+            return Err(AppError::Unauthorized("Refresh token has expired".to_string()));
+        }
+
+        if stored.revoked_at.is_some() || !self.refresh_token_repo.revoke(stored.id).await? {
+            self.refresh_token_repo.revoke_family(&stored.family_id).await?;
+            tracing::warn!(
+                "Refresh token reuse for user {}; revoked its session",
+                stored.user_id
+            );
+            return Err(AppError::Unauthorized("Refresh token has already been used".to_string()));
+        }
+
+        let user = self
+            .user_repo
+            .find_by_id(stored.user_id)
+            .await?
+            .ok_or_else(|| AppError::Unauthorized("User not found".to_string()))?;
+        if user.token_epoch != stored.token_epoch {
+            return Err(AppError::Unauthorized("Session has been logged out".to_string()));
+        }
+
+        self.issue_tokens(&user, Some(&stored.family_id)).await
+    }
+
+    /// Change the user's password and invalidate their other sessions
+    ///
+    /// Returns fresh tokens for the caller, since tokens issued before the change stop working.
     pub async fn change_password(
         &self,
         user_id: i32,
         old_password: &str,
         new_password: &str,
-    ) -> AppResult<()> {
+    ) -> AppResult<TokenResponse> {
         let user = self
             .user_repo
             .find_by_id(user_id)
@@ -65,10 +178,24 @@ impl AuthService {
             return Err(AppError::InvalidCredentials);
         }
 
+        validate_password_strength(new_password, &user.username)?;
+        if old_password == new_password {
+            return Err(AppError::ValidationError(
+                "New password must differ from the old password".to_string(),
+            ));
+        }
+
         let new_hash = hash_password(new_password)?;
         self.user_repo.update_password(user_id, &new_hash).await?;
+        self.user_repo.bump_token_epoch(user_id).await?;
 
-        Ok(())
+        let user = self
+            .user_repo
+            .find_by_id(user_id)
+            .await?
+            .ok_or_else(|| AppError::NotFound("User not found".to_string()))?;
+
+        self.issue_tokens(&user, None).await
     }
 
     pub async fn verify_user_password(&self, user_id: i32, password: &str) -> AppResult<bool> {
@@ -83,7 +210,7 @@ impl AuthService {
 
     pub fn generate_token(&self, user: &User) -> AppResult<String> {
         let now = Utc::now();
-        let expire = now + Duration::hours(self.jwt_config.expire_hours as i64);
+        let expire = now + self.access_token_ttl();
 
         let claims = Claims {
             sub: user.id,
@@ -91,6 +218,9 @@ impl AuthService {
             role: user.role.clone(),
             exp: expire.timestamp(),
             iat: now.timestamp(),
+            jti: hex::encode(rand::random::<[u8; 16]>()),
+            epoch: user.token_epoch,
+            must_change_password: false,
         };
 
         encode(
@@ -111,11 +241,86 @@ impl AuthService {
         Ok(token_data.claims)
     }
 
-    pub async fn create_default_admin(&self) -> AppResult<()> {
-        let default_password = hash_password("admin123")?;
+    /// Verify the token signature and expiry, then reject revoked or superseded tokens
+    pub async fn validate_token(&self, token: &str) -> AppResult<Claims> {
+        let mut claims = self.verify_token(token)?;
+
+        if !claims.jti.is_empty() && self.revoked_token_repo.is_revoked(&claims.jti).await? {
+            return Err(AppError::Unauthorized("Token has been revoked".to_string()));
+        }
+
+        match self.user_repo.get_token_state(claims.sub).await? {
+            Some((epoch, must_change_password)) if epoch == claims.epoch => {
+                claims.must_change_password = must_change_password;
+                Ok(claims)
+            }
+            _ => Err(AppError::Unauthorized("Session has been logged out".to_string())),
+        }
+    }
+
+    /// Permissions granted to a role
+    pub async fn permissions_for_role(&self, role: &str) -> AppResult<HashSet<Permission>> {
+        let stored = if role == ADMIN_ROLE {
+            Vec::new()
+        } else {
+            self.role_permission_repo.list_for_role(role).await?
+        };
+        Ok(resolve_permissions(role, &stored))
+    }
+
+    /// Revoke a single token until it would have expired, along with the refresh
+    /// token chain it came with
+    pub async fn logout(&self, claims: &Claims, refresh_token: Option<&str>) -> AppResult<()> {
+        if let Some(refresh_token) = refresh_token {
+            let stored = self
+                .refresh_token_repo
+                .find_by_hash(&hash_refresh_token(refresh_token))
+                .await?;
+            if let Some(stored) = stored.filter(|t| t.user_id == claims.sub) {
+                self.refresh_token_repo.revoke_family(&stored.family_id).await?;
+            }
+        }
+
+        if claims.jti.is_empty() {
+            // Tokens issued before revocation support have no id; only the epoch can kill them
+            return self.logout_all(claims.sub).await;
+        }
+
+        let expires_at = DateTime::<Utc>::from_timestamp(claims.exp, 0).unwrap_or_else(Utc::now);
+        self.revoked_token_repo
+            .revoke(&claims.jti, claims.sub, expires_at)
+            .await
+    }
+
+    /// Invalidate every token issued to the user; refresh tokens carry the old epoch too
+    pub async fn logout_all(&self, user_id: i32) -> AppResult<()> {
+        self.user_repo.bump_token_epoch(user_id).await
+    }
+
+    /// Drop revocation entries and refresh tokens that have expired anyway
+    pub async fn purge_revoked_tokens(&self) -> AppResult<u64> {
+        let revoked = self.revoked_token_repo.purge_expired().await?;
+        let refresh = self.refresh_token_repo.purge_expired().await?;
+        Ok(revoked + refresh)
+    }
+
+    /// Seed an `admin` account with `password` when there are no users yet
+    ///
+    /// An `admin` seeded before password changes were enforced may still use the
+    /// published default password; it is made to change it at its next request.
+    pub async fn create_default_admin(&self, password: &str) -> AppResult<()> {
+        let default_password = hash_password(password)?;
         self.user_repo
             .create_default_admin_if_not_exists(&default_password)
-            .await
+            .await?;
+
+        if let Some(admin) = self.user_repo.find_by_username("admin").await? {
+            if !admin.must_change_password && verify_password(INSECURE_ADMIN_PASSWORD, &admin.password_hash)? {
+                self.user_repo.require_password_change(admin.id).await?;
+                tracing::warn!("The admin user still has the default password; it must be changed before use");
+            }
+        }
+        Ok(())
     }
 
     pub async fn get_user(&self, user_id: i32) -> AppResult<Option<User>> {
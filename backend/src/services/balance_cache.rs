@@ -0,0 +1,81 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use rust_decimal::Decimal;
+
+use crate::blockchain::traits::TokenBalance;
+
+/// Native balance and token balances of one address
+pub type Balances = (Decimal, Vec<TokenBalance>);
+
+/// Balances recently fetched from the chain, keyed by chain and address
+///
+/// Dashboards poll balances far more often than they change. Entries older than
+/// the TTL are fetched again; a TTL of zero turns the cache off.
+pub struct BalanceCache {
+    ttl: Duration,
+    entries: Mutex<HashMap<(String, String), (Instant, Balances)>>,
+}
+
+impl BalanceCache {
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            ttl,
+            entries: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Cached balances of `address`, if fetched within the TTL
+    pub fn get(&self, chain: &str, address: &str) -> Option<Balances> {
+        let entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        entries
+            .get(&(chain.to_string(), address.to_string()))
+            .filter(|(fetched, _)| fetched.elapsed() < self.ttl)
+            .map(|(_, balances)| balances.clone())
+    }
+
+    pub fn insert(&self, chain: &str, address: &str, balances: Balances) {
+        if self.ttl.is_zero() {
+            return;
+        }
+        let mut entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        entries.retain(|_, (fetched, _)| fetched.elapsed() < self.ttl);
+        entries.insert((chain.to_string(), address.to_string()), (Instant::now(), balances));
+    }
+
+    /// Drop the entry of an address whose balance just changed
+    ///
+    /// EVM addresses may have been queried in another letter case, so the match ignores case.
+    pub fn invalidate(&self, chain: &str, address: &str) {
+        let mut entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        entries.retain(|(entry_chain, entry_address), _| {
+            entry_chain != chain || !entry_address.eq_ignore_ascii_case(address)
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cached_balance_until_invalidated() {
+        let cache = BalanceCache::new(Duration::from_secs(15));
+        assert!(cache.get("ethereum", "0xAbC").is_none());
+
+        cache.insert("ethereum", "0xAbC", (Decimal::ONE, Vec::new()));
+        assert_eq!(cache.get("ethereum", "0xAbC").unwrap().0, Decimal::ONE);
+        assert!(cache.get("polygon", "0xAbC").is_none());
+
+        cache.invalidate("ethereum", "0xabc");
+        assert!(cache.get("ethereum", "0xAbC").is_none());
+    }
+
+    #[test]
+    fn test_zero_ttl_disables_cache() {
+        let cache = BalanceCache::new(Duration::ZERO);
+        cache.insert("ethereum", "0xabc", (Decimal::ONE, Vec::new()));
+        assert!(cache.get("ethereum", "0xabc").is_none());
+    }
+}
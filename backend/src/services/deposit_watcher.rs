@@ -0,0 +1,186 @@
+use std::collections::HashSet;
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::blockchain::traits::{AddressOutput, ChainClient};
+use crate::blockchain::ChainRegistry;
+use crate::db::models::Wallet;
+use crate::db::repositories::{DepositRepository, TransferRepository, WalletRepository};
+use crate::error::AppResult;
+use crate::services::{Shutdown, WebhookNotifier};
+
+/// Blocks below the last checked height that are read again, in case they were reorganized
+const RESCAN_OVERLAP_BLOCKS: u64 = 10;
+
+/// Polls the Zcash node for outputs paid to wallet t-addresses and records them as deposits
+///
+/// The Orchard scanner only sees shielded notes. Each wallet's address history is read
+/// on from the block it was last checked up to, so a deposit spent before the next poll
+/// is still recorded. Outputs are deduplicated on their outpoint, and change from the
+/// wallet's own transfers is not counted as a deposit.
+pub struct DepositWatcher {
+    chain_registry: Arc<ChainRegistry>,
+    wallet_repo: WalletRepository,
+    transfer_repo: TransferRepository,
+    deposit_repo: DepositRepository,
+    webhooks: Arc<WebhookNotifier>,
+    poll_interval: Duration,
+    shutdown: Shutdown,
+}
+
+impl DepositWatcher {
+    pub fn new(
+        chain_registry: Arc<ChainRegistry>,
+        wallet_repo: WalletRepository,
+        transfer_repo: TransferRepository,
+        deposit_repo: DepositRepository,
+        webhooks: Arc<WebhookNotifier>,
+        poll_interval: Duration,
+        shutdown: Shutdown,
+    ) -> Self {
+        Self {
+            chain_registry,
+            wallet_repo,
+            transfer_repo,
+            deposit_repo,
+            webhooks,
+            poll_interval,
+            shutdown,
+        }
+    }
+
+    pub fn start(self: Arc<Self>) -> tokio::task::JoinHandle<()> {
+        tokio::spawn(async move {
+            tracing::info!("Transparent deposit watcher started (interval: {:?})", self.poll_interval);
+            let mut interval = tokio::time::interval(self.poll_interval);
+            loop {
+                tokio::select! {
+                    _ = interval.tick() => {}
+                    _ = self.shutdown.wait() => break,
+                }
+                match self.poll().await {
+                    Ok(found) if found > 0 => tracing::info!("Recorded {} new transparent deposits", found),
+                    Ok(_) => {}
+                    Err(e) => tracing::error!("Error polling transparent deposits: {}", e),
+                }
+            }
+            tracing::info!("Transparent deposit watcher stopped");
+        })
+    }
+
+    /// Check every Zcash wallet once; returns the number of new deposits
+    pub async fn poll(&self) -> AppResult<usize> {
+        let client = self.chain_registry.get("zcash")?;
+        let mut found = 0;
+        for wallet in self.wallet_repo.list_by_chain("zcash").await? {
+            if !is_transparent_address(&wallet.address) {
+                continue;
+            }
+            match self.poll_wallet(client.as_ref(), &wallet).await {
+                Ok(count) => found += count,
+                // One unreachable address should not hold up the others
+                Err(e) => tracing::warn!("Deposit check for wallet {} failed: {}", wallet.id, e),
+            }
+        }
+        Ok(found)
+    }
+
+    async fn poll_wallet(&self, client: &dyn ChainClient, wallet: &Wallet) -> AppResult<usize> {
+        let tip = client.get_block_height().await?;
+        let from_height = self
+            .deposit_repo
+            .scanned_height(wallet.id)
+            .await?
+            .map_or(0, |height| height.saturating_sub(RESCAN_OVERLAP_BLOCKS));
+        let outputs = client.get_address_outputs(&wallet.address, from_height, tip).await?;
+
+        let mut own_txids = HashSet::new();
+        let txids: HashSet<&str> = outputs.iter().map(|output| output.txid.as_str()).collect();
+        for txid in txids {
+            if self.transfer_repo.exists_with_tx_hash(wallet.id, txid).await? {
+                own_txids.insert(txid.to_string());
+            }
+        }
+
+        let mut found = 0;
+        for output in deposits_to_record(outputs, &own_txids) {
+            let Some(deposit) = self
+                .deposit_repo
+                .insert_if_new(wallet.id, &wallet.address, &output.txid, output.output_index, output.value, output.height)
+                .await?
+            else {
+                continue;
+            };
+
+            tracing::info!(
+                "Transparent deposit to wallet {}: {} zatoshis in {}:{} at height {}",
+                wallet.id,
+                deposit.value_zatoshis,
+                deposit.txid,
+                deposit.output_index,
+                deposit.block_height
+            );
+            self.webhooks.deposit_received(&deposit);
+            found += 1;
+        }
+
+        self.deposit_repo.set_scanned_height(wallet.id, tip).await?;
+        Ok(found)
+    }
+}
+
+/// Outputs that are deposits: one per outpoint, leaving out change of the wallet's own transfers
+fn deposits_to_record(outputs: Vec<AddressOutput>, own_txids: &HashSet<String>) -> Vec<AddressOutput> {
+    let mut seen = HashSet::new();
+    outputs
+        .into_iter()
+        .filter(|output| !own_txids.contains(&output.txid))
+        .filter(|output| seen.insert((output.txid.clone(), output.output_index)))
+        .collect()
+}
+
+/// Transparent (P2PKH/P2SH) Zcash address, mainnet or testnet
+fn is_transparent_address(address: &str) -> bool {
+    ["t1", "t3", "tm", "t2"].iter().any(|prefix| address.starts_with(prefix))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_transparent_address() {
+        assert!(is_transparent_address("t1Rv4exT7bqhZqi2j7xz8bUHDMxwosrjADU"));
+        assert!(is_transparent_address("tmBsTi2xWTjUdEXnuTceL7fecEQKeWaPDJd"));
+        assert!(!is_transparent_address("u1qqqq"));
+        assert!(!is_transparent_address("zs1qqqq"));
+    }
+
+    fn output(txid: &str, output_index: u32, value: u64) -> AddressOutput {
+        AddressOutput {
+            txid: txid.to_string(),
+            output_index,
+            value,
+            height: 2_500_000,
+        }
+    }
+
+    #[test]
+    fn test_deposits_skip_repeated_outpoints_and_own_change() {
+        let outputs = vec![
+            output("aa", 0, 50_000),
+            // Read again in the overlap with the previous poll
+            output("aa", 0, 50_000),
+            output("aa", 1, 20_000),
+            // Change of a transfer sent from the wallet
+            output("bb", 1, 990_000),
+            output("cc", 0, 70_000),
+        ];
+        let own_txids = HashSet::from(["bb".to_string()]);
+
+        assert_eq!(
+            deposits_to_record(outputs, &own_txids),
+            vec![output("aa", 0, 50_000), output("aa", 1, 20_000), output("cc", 0, 70_000)]
+        );
+    }
+}
@@ -0,0 +1,184 @@
+use rust_decimal::Decimal;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use crate::error::{AppError, AppResult};
+
+/// Rejects a transfer identical to one created moments ago
+///
+/// Catches accidental double-submits (double clicks, UI retries). A transfer is
+/// checked before its row is created and recorded once it is, so a failed insert
+/// does not block the retry. A window of 0 disables the guard.
+pub struct DuplicateTransferGuard {
+    window: Duration,
+    recent: Mutex<HashMap<String, Instant>>,
+}
+
+impl DuplicateTransferGuard {
+    pub fn new(window_secs: u64) -> Self {
+        Self {
+            window: Duration::from_secs(window_secs),
+            recent: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Fail if an identical transfer was recorded within the window
+    ///
+    /// `allow_duplicate` lets a deliberately repeated send through.
+    pub fn check(
+        &self,
+        wallet_id: i32,
+        to_address: &str,
+        amount: Decimal,
+        token: &str,
+        allow_duplicate: bool,
+    ) -> AppResult<()> {
+        if self.window.is_zero() {
+            return Ok(());
+        }
+
+        self.check_batch(wallet_id, &[(to_address, amount, token)], allow_duplicate)
+            .map_err(|(_, e)| e)
+    }
+
+    /// Fail with the index of the first `(to_address, amount, token)` item identical to
+    /// a transfer recorded within the window
+    ///
+    /// Items are only compared with earlier submissions, so a batch may pay the same
+    /// recipient the same amount more than once.
+    pub fn check_batch(
+        &self,
+        wallet_id: i32,
+        items: &[(&str, Decimal, &str)],
+        allow_duplicate: bool,
+    ) -> Result<(), (usize, AppError)> {
+        if self.window.is_zero() || allow_duplicate {
+            return Ok(());
+        }
+
+        let now = Instant::now();
+        let mut recent = self.recent.lock().unwrap_or_else(|e| e.into_inner());
+        recent.retain(|_, created| now.duration_since(*created) < self.window);
+
+        let duplicate = items
+            .iter()
+            .position(|(to_address, amount, token)| {
+                recent.contains_key(&Self::key(wallet_id, to_address, *amount, token))
+            });
+        match duplicate {
+            Some(index) => {
+                let (to_address, amount, token) = items[index];
+                Err((
+                    index,
+                    AppError::ValidationError(format!(
+                        "An identical transfer of {} {} to {} was submitted in the last {} seconds. \
+                         Resubmit with allow_duplicate=true if this is intended",
+                        amount,
+                        token,
+                        to_address,
+                        self.window.as_secs()
+                    )),
+                ))
+            }
+            None => Ok(()),
+        }
+    }
+
+    /// Remember a transfer whose row was created, so a repeat within the window is refused
+    pub fn record(&self, wallet_id: i32, to_address: &str, amount: Decimal, token: &str) {
+        if self.window.is_zero() {
+            return;
+        }
+
+        let mut recent = self.recent.lock().unwrap_or_else(|e| e.into_inner());
+        recent.insert(Self::key(wallet_id, to_address, amount, token), Instant::now());
+    }
+
+    fn key(wallet_id: i32, to_address: &str, amount: Decimal, token: &str) -> String {
+        format!(
+            "{}:{}:{}:{}",
+            wallet_id,
+            to_address.to_lowercase(),
+            token.to_uppercase(),
+            amount.normalize()
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    const TO: &str = "0x9858EfFD232B4033E47d90003D41EC34EcaEda94";
+
+    fn amount(value: &str) -> Decimal {
+        Decimal::from_str(value).unwrap()
+    }
+
+    #[test]
+    fn test_rapid_resubmit_rejected() {
+        let guard = DuplicateTransferGuard::new(30);
+        assert!(guard.check(1, TO, amount("1.5"), "ETH", false).is_ok());
+        guard.record(1, TO, amount("1.5"), "ETH");
+
+        // Same transfer, differently formatted
+        let err = guard
+            .check(1, &TO.to_lowercase(), amount("1.50"), "eth", false)
+            .unwrap_err();
+        assert!(matches!(err, AppError::ValidationError(_)));
+
+        // Any differing field is a different transfer
+        assert!(guard.check(2, TO, amount("1.5"), "ETH", false).is_ok());
+        assert!(guard.check(1, TO, amount("1.6"), "ETH", false).is_ok());
+        assert!(guard.check(1, TO, amount("1.5"), "USDT", false).is_ok());
+    }
+
+    #[test]
+    fn test_override_allows_repeat() {
+        let guard = DuplicateTransferGuard::new(30);
+        guard.record(1, TO, amount("1"), "ETH");
+        assert!(guard.check(1, TO, amount("1"), "ETH", true).is_ok());
+    }
+
+    #[test]
+    fn test_unrecorded_transfer_does_not_block_retry() {
+        let guard = DuplicateTransferGuard::new(30);
+        // Checked, but its row was never created
+        assert!(guard.check(1, TO, amount("1"), "ETH", false).is_ok());
+        assert!(guard.check(1, TO, amount("1"), "ETH", false).is_ok());
+    }
+
+    #[test]
+    fn test_batch_may_repeat_an_item() {
+        let guard = DuplicateTransferGuard::new(30);
+        let payout = (TO, amount("1"), "ETH");
+        assert!(guard.check_batch(1, &[payout, payout], false).is_ok());
+        guard.record(1, payout.0, payout.1, payout.2);
+
+        // The same batch submitted again is a double-submit
+        let other = ("0x0000000000000000000000000000000000000001", amount("2"), "ETH");
+        let (index, err) = guard.check_batch(1, &[other, payout], false).unwrap_err();
+        assert_eq!(index, 1);
+        assert!(matches!(err, AppError::ValidationError(_)));
+
+        // The rejected batch recorded nothing
+        assert!(guard.check(1, other.0, other.1, other.2, false).is_ok());
+    }
+
+    #[test]
+    fn test_window_expiry_and_disabled() {
+        let guard = DuplicateTransferGuard {
+            window: Duration::from_millis(20),
+            recent: Mutex::new(HashMap::new()),
+        };
+        guard.record(1, TO, amount("1"), "ETH");
+        std::thread::sleep(Duration::from_millis(40));
+        assert!(guard.check(1, TO, amount("1"), "ETH", false).is_ok());
+
+        let disabled = DuplicateTransferGuard::new(0);
+        disabled.record(1, TO, amount("1"), "ETH");
+        assert!(disabled.check(1, TO, amount("1"), "ETH", false).is_ok());
+    }
+}
@@ -0,0 +1,235 @@
+use serde::Serialize;
+
+use crate::blockchain::zcash::orchard::transfer::NetworkType;
+use crate::crypto::zcash::{
+    derive_sapling_viewing_key, enable_orchard_for_wallet, generate_unified_address, import_zcash_wallet,
+    parse_unified_address,
+};
+use crate::config::SecurityConfig;
+use crate::db::models::Wallet;
+
+/// Outcome of re-deriving one wallet's keys
+#[derive(Debug, Clone, Serialize)]
+pub struct WalletKeyCheck {
+    pub wallet_id: i32,
+    pub name: String,
+    pub address: String,
+    pub ok: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub unified_address: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Result of verifying every Zcash wallet's stored keys
+#[derive(Debug, Clone, Serialize)]
+pub struct WalletKeyReport {
+    pub checked: usize,
+    pub passed: usize,
+    pub failed: usize,
+    pub wallets: Vec<WalletKeyCheck>,
+}
+
+/// Decrypt each wallet's key, re-derive its transparent address, viewing key and
+/// unified address, and check them against the stored data
+///
+/// Meant for validating the database after migrations or an encryption key rotation;
+/// like every other read, a wallet still under the previous key passes.
+pub fn verify_zcash_wallet_keys(
+    wallets: &[Wallet],
+    keys: &SecurityConfig,
+    network: NetworkType,
+) -> WalletKeyReport {
+    let checks: Vec<WalletKeyCheck> = wallets
+        .iter()
+        .map(|wallet| {
+            let (unified_address, error) = match check_wallet(wallet, keys, network) {
+                Ok(unified_address) => (Some(unified_address), None),
+                Err(e) => (None, Some(e)),
+            };
+            WalletKeyCheck {
+                wallet_id: wallet.id,
+                name: wallet.name.clone(),
+                address: wallet.address.clone(),
+                ok: error.is_none(),
+                unified_address,
+                error,
+            }
+        })
+        .collect();
+
+    let passed = checks.iter().filter(|c| c.ok).count();
+    WalletKeyReport {
+        checked: checks.len(),
+        passed,
+        failed: checks.len() - passed,
+        wallets: checks,
+    }
+}
+
+/// Returns the re-derived unified address, or why the wallet is inconsistent
+fn check_wallet(wallet: &Wallet, keys: &SecurityConfig, network: NetworkType) -> Result<String, String> {
+    if wallet.watch_only {
+        return check_watch_only_wallet(wallet, keys, network);
+    }
+
+    let private_key = keys
+        .decrypt(&wallet.encrypted_private_key)
+        .map_err(|e| format!("Failed to decrypt private key: {}", e))?;
+
+    let derived_address = import_zcash_wallet(&private_key, network)
+        .map_err(|e| format!("Failed to derive address: {}", e))?;
+    if derived_address != wallet.address {
+        return Err(format!(
+            "Private key derives {} but the stored address is {}",
+            derived_address, wallet.address
+        ));
+    }
+
+    let birthday_height = wallet.orchard_birthday_height.unwrap_or(network.orchard_activation_height());
+    let (unified_address, viewing_key_encoded) = enable_orchard_for_wallet(&private_key, birthday_height, network)
+        .map_err(|e| format!("Failed to derive viewing key: {}", e))?;
+
+    // The encoded viewing key must decode back to the same address
+    let sapling_key = derive_sapling_viewing_key(&private_key).map_err(|e| e.to_string())?;
+    let regenerated = generate_unified_address(&viewing_key_encoded, Some(sapling_key), unified_address.address_index, network)
+        .map_err(|e| format!("Viewing key does not round-trip: {}", e))?;
+    if regenerated.address != unified_address.address {
+        return Err("Unified address from the encoded viewing key does not match".to_string());
+    }
+
+    let parsed = parse_unified_address(&unified_address.address)
+        .map_err(|e| format!("Derived unified address does not parse: {}", e))?;
+    if !parsed.has_orchard {
+        return Err("Derived unified address has no Orchard receiver".to_string());
+    }
+
+    Ok(unified_address.address)
+}
+
+/// Watch-only wallets have no private key; their viewing key must produce the stored address
+fn check_watch_only_wallet(wallet: &Wallet, keys: &SecurityConfig, network: NetworkType) -> Result<String, String> {
+    let encrypted = wallet
+        .encrypted_viewing_key
+        .as_deref()
+        .ok_or_else(|| "Watch-only wallet has no viewing key".to_string())?;
+    let viewing_key_encoded = keys
+        .decrypt(encrypted)
+        .map_err(|e| format!("Failed to decrypt viewing key: {}", e))?;
+
+    let unified_address = generate_unified_address(&viewing_key_encoded, None, 0, network)
+        .map_err(|e| format!("Failed to derive address from viewing key: {}", e))?;
+    if unified_address.address != wallet.address {
+        return Err(format!(
+            "Viewing key derives {} but the stored address is {}",
+            unified_address.address, wallet.address
+        ));
+    }
+
+    Ok(unified_address.address)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crypto::encrypt;
+    use crate::crypto::zcash::generate_zcash_wallet;
+    use crate::db::models::test_fixtures;
+
+    const KEY: &str = "0123456789abcdef0123456789abcdef";
+    const OTHER_KEY: &str = "fedcba9876543210fedcba9876543210";
+
+    fn keys(encryption_key: &str, previous_encryption_key: Option<&str>) -> SecurityConfig {
+        SecurityConfig {
+            encryption_key: encryption_key.to_string(),
+            previous_encryption_key: previous_encryption_key.map(str::to_string),
+            admin_password: "not-the-default".to_string(),
+            allow_insecure_defaults: false,
+        }
+    }
+
+    fn wallet(id: i32) -> Wallet {
+        let (address, private_key) = generate_zcash_wallet(NetworkType::Mainnet).unwrap();
+        Wallet {
+            address,
+            encrypted_private_key: encrypt(&private_key, KEY).unwrap(),
+            orchard_birthday_height: Some(2_800_000),
+            ..test_fixtures::wallet(id)
+        }
+    }
+
+    fn watch_only_wallet(id: i32) -> Wallet {
+        let (_, private_key) = generate_zcash_wallet(NetworkType::Mainnet).unwrap();
+        let (unified_address, viewing_key) = enable_orchard_for_wallet(&private_key, 2_800_000, NetworkType::Mainnet).unwrap();
+        Wallet {
+            address: unified_address.address,
+            encrypted_private_key: String::new(),
+            watch_only: true,
+            encrypted_viewing_key: Some(encrypt(&viewing_key, KEY).unwrap()),
+            ..wallet(id)
+        }
+    }
+
+    #[test]
+    fn test_consistent_wallets_pass() {
+        let wallets = vec![wallet(1), wallet(2)];
+        let report = verify_zcash_wallet_keys(&wallets, &keys(KEY, None), NetworkType::Mainnet);
+
+        assert_eq!(report.checked, 2);
+        assert_eq!(report.passed, 2);
+        assert!(report.wallets.iter().all(|c| c.ok && c.unified_address.is_some()));
+    }
+
+    #[test]
+    fn test_corrupted_wallets_are_flagged() {
+        let mut swapped = wallet(2);
+        swapped.address = wallet(99).address;
+
+        let mut garbled = wallet(3);
+        garbled.encrypted_private_key = encrypt("not a key", KEY).unwrap();
+
+        let wallets = vec![wallet(1), swapped, garbled, wallet(4)];
+        let report = verify_zcash_wallet_keys(&wallets, &keys(KEY, None), NetworkType::Mainnet);
+
+        assert_eq!(report.passed, 2);
+        assert_eq!(report.failed, 2);
+        let failed: Vec<i32> = report.wallets.iter().filter(|c| !c.ok).map(|c| c.wallet_id).collect();
+        assert_eq!(failed, vec![2, 3]);
+        assert!(report.wallets[1].error.as_ref().unwrap().contains("stored address"));
+    }
+
+    #[test]
+    fn test_watch_only_wallets_check_viewing_key() {
+        let mut mismatched = watch_only_wallet(2);
+        mismatched.address = watch_only_wallet(99).address;
+
+        let wallets = vec![watch_only_wallet(1), mismatched];
+        let report = verify_zcash_wallet_keys(&wallets, &keys(KEY, None), NetworkType::Mainnet);
+
+        assert!(report.wallets[0].ok);
+        assert_eq!(report.wallets[0].unified_address.as_deref(), Some(wallets[0].address.as_str()));
+        assert!(report.wallets[1].error.as_ref().unwrap().contains("Viewing key derives"));
+    }
+
+    #[test]
+    fn test_wrong_encryption_key_fails_every_wallet() {
+        let wallets = vec![wallet(1), wallet(2)];
+        let report = verify_zcash_wallet_keys(&wallets, &keys(OTHER_KEY, None), NetworkType::Mainnet);
+
+        assert_eq!(report.failed, 2);
+        assert!(report.wallets[0].error.as_ref().unwrap().starts_with("Failed to decrypt"));
+    }
+
+    #[test]
+    fn test_wallets_under_the_previous_key_pass_mid_rotation() {
+        let mut rotated = wallet(2);
+        let private_key = keys(KEY, None).decrypt(&rotated.encrypted_private_key).unwrap();
+        rotated.encrypted_private_key = encrypt(&private_key, OTHER_KEY).unwrap();
+
+        // Wallet 1 is not re-encrypted yet, wallet 2 already is
+        let wallets = vec![wallet(1), rotated, watch_only_wallet(3)];
+        let report = verify_zcash_wallet_keys(&wallets, &keys(OTHER_KEY, Some(KEY)), NetworkType::Mainnet);
+
+        assert_eq!(report.passed, 3);
+    }
+}
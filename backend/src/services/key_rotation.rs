@@ -0,0 +1,142 @@
+use serde::Serialize;
+
+use crate::crypto::{decrypt, encrypt};
+use crate::db::models::Wallet;
+use crate::error::{AppError, AppResult};
+
+/// Outcome of an encryption key rotation
+#[derive(Debug, Clone, Serialize)]
+pub struct KeyRotationReport {
+    pub wallets: usize,
+    pub rotated: usize,
+    /// Wallets already encrypted with the new key, e.g. created during the rotation window
+    pub already_rotated: usize,
+    /// Encrypted settings, such as the Zcash RPC password, re-encrypted
+    pub settings_rotated: usize,
+}
+
+/// Re-encrypt every wallet secret from `old_key` to `new_key`
+///
+/// Secrets that already decrypt with the new key are kept as they are, so a rotation
+/// can be re-run safely. Fails on the first secret neither key can decrypt.
+pub fn reencrypt_wallets(wallets: &[Wallet], old_key: &str, new_key: &str) -> AppResult<Vec<Wallet>> {
+    wallets
+        .iter()
+        .map(|wallet| {
+            let field = |value: &str| {
+                reencrypt(value, old_key, new_key).map_err(|_| {
+                    AppError::EncryptionError(format!(
+                        "Wallet {} cannot be decrypted with the old or the new key",
+                        wallet.id
+                    ))
+                })
+            };
+
+            Ok(Wallet {
+                // Watch-only wallets store no private key
+                encrypted_private_key: if wallet.encrypted_private_key.is_empty() {
+                    String::new()
+                } else {
+                    field(&wallet.encrypted_private_key)?
+                },
+                encrypted_mnemonic: wallet.encrypted_mnemonic.as_deref().map(field).transpose()?,
+                encrypted_viewing_key: wallet.encrypted_viewing_key.as_deref().map(field).transpose()?,
+                ..wallet.clone()
+            })
+        })
+        .collect()
+}
+
+/// Re-encrypt an encrypted setting from `old_key` to `new_key`, like a wallet secret
+pub fn reencrypt_setting(key: &str, value: &str, old_key: &str, new_key: &str) -> AppResult<String> {
+    reencrypt(value, old_key, new_key).map_err(|_| {
+        AppError::EncryptionError(format!(
+            "Setting {} cannot be decrypted with the old or the new key",
+            key
+        ))
+    })
+}
+
+/// Whether re-encryption changed any of the wallet's stored secrets
+pub fn is_changed(wallet: &Wallet, reencrypted: &Wallet) -> bool {
+    wallet.encrypted_private_key != reencrypted.encrypted_private_key
+        || wallet.encrypted_mnemonic != reencrypted.encrypted_mnemonic
+        || wallet.encrypted_viewing_key != reencrypted.encrypted_viewing_key
+}
+
+fn reencrypt(value: &str, old_key: &str, new_key: &str) -> AppResult<String> {
+    match decrypt(value, old_key) {
+        Ok(plaintext) => encrypt(&plaintext, new_key),
+        Err(_) => decrypt(value, new_key).map(|_| value.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::models::test_fixtures;
+
+    const OLD: &str = "0123456789abcdef0123456789abcdef";
+    const NEW: &str = "fedcba9876543210fedcba9876543210";
+
+    fn wallet(id: i32, key: &str) -> Wallet {
+        Wallet {
+            encrypted_private_key: encrypt("secret-key", key).unwrap(),
+            encrypted_mnemonic: Some(encrypt("abandon abandon about", key).unwrap()),
+            mnemonic_account_index: Some(0),
+            ..test_fixtures::wallet(id)
+        }
+    }
+
+    #[test]
+    fn test_reencrypts_and_skips_rotated_wallets() {
+        let watch_only = Wallet {
+            encrypted_private_key: String::new(),
+            encrypted_mnemonic: None,
+            watch_only: true,
+            encrypted_viewing_key: Some(encrypt("uview1", OLD).unwrap()),
+            ..wallet(3, OLD)
+        };
+        let wallets = vec![wallet(1, OLD), wallet(2, NEW), watch_only];
+
+        let rotated = reencrypt_wallets(&wallets, OLD, NEW).unwrap();
+
+        assert_eq!(decrypt(&rotated[0].encrypted_private_key, NEW).unwrap(), "secret-key");
+        assert_eq!(
+            decrypt(rotated[0].encrypted_mnemonic.as_ref().unwrap(), NEW).unwrap(),
+            "abandon abandon about"
+        );
+        assert!(!is_changed(&wallets[1], &rotated[1]));
+        assert_eq!(rotated[2].encrypted_private_key, "");
+        assert_eq!(decrypt(rotated[2].encrypted_viewing_key.as_ref().unwrap(), NEW).unwrap(), "uview1");
+        assert!(is_changed(&wallets[0], &rotated[0]) && is_changed(&wallets[2], &rotated[2]));
+    }
+
+    #[test]
+    fn test_reencrypts_settings() {
+        let stored = encrypt("rpc-secret", OLD).unwrap();
+        let rotated = reencrypt_setting("zcash_rpc_password", &stored, OLD, NEW).unwrap();
+        assert_eq!(decrypt(&rotated, NEW).unwrap(), "rpc-secret");
+
+        // Already on the new key
+        assert_eq!(reencrypt_setting("zcash_rpc_password", &rotated, OLD, NEW).unwrap(), rotated);
+
+        let unknown = encrypt("rpc-secret", "ffffffffffffffffffffffffffffffff").unwrap();
+        let AppError::EncryptionError(message) =
+            reencrypt_setting("zcash_rpc_password", &unknown, OLD, NEW).unwrap_err()
+        else {
+            panic!("expected an encryption error");
+        };
+        assert!(message.contains("zcash_rpc_password"));
+    }
+
+    #[test]
+    fn test_unknown_key_fails_whole_rotation() {
+        let wallets = vec![wallet(1, OLD), wallet(2, "ffffffffffffffffffffffffffffffff")];
+
+        let AppError::EncryptionError(message) = reencrypt_wallets(&wallets, OLD, NEW).unwrap_err() else {
+            panic!("expected an encryption error");
+        };
+        assert!(message.contains("Wallet 2"));
+    }
+}
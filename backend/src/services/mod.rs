@@ -1,7 +1,33 @@
+pub mod activity_export;
+pub mod address_book;
+pub mod balance_cache;
 pub mod auth_service;
+pub mod deposit_watcher;
+pub mod duplicate_guard;
+pub mod key_audit;
+pub mod key_rotation;
+pub mod permissions;
+pub mod price_feed;
+pub mod proposal_store;
+pub mod shutdown;
+pub mod transfer_gate;
 pub mod transfer_service;
+pub mod wallet_rescan;
 pub mod wallet_service;
+pub mod webhook;
+pub mod zcash_history;
 
+pub use address_book::AddressBook;
 pub use auth_service::AuthService;
+pub use deposit_watcher::DepositWatcher;
+pub use duplicate_guard::DuplicateTransferGuard;
+pub use key_audit::WalletKeyReport;
+pub use key_rotation::KeyRotationReport;
+pub use permissions::Permission;
+pub use price_feed::PriceFeed;
+pub use proposal_store::ProposalStore;
+pub use shutdown::Shutdown;
+pub use transfer_gate::TransferGate;
 pub use transfer_service::TransferService;
-pub use wallet_service::WalletService;
+pub use wallet_service::{WalletService, WalletServiceConfig};
+pub use webhook::WebhookNotifier;
@@ -0,0 +1,106 @@
+//! Capabilities granted to user roles
+//!
+//! Roles map to permissions through the `role_permissions` table. `admin` always
+//! holds every permission so it cannot be locked out by an edit to that table.
+
+use std::collections::HashSet;
+use std::fmt;
+
+pub const ADMIN_ROLE: &str = "admin";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Permission {
+    /// Create and import wallets
+    WalletCreate,
+    /// Activate, delete and sync wallets
+    WalletManage,
+    KeyExport,
+    /// Verify wallet keys and rotate the encryption key
+    KeyManage,
+    /// Create pending transfers and unsigned transfer packages
+    TransferCreate,
+    /// Sign and broadcast transfers, and sign messages with wallet keys
+    TransferExecute,
+    /// Approve or reject transfers above the approval threshold, and override spending limits
+    TransferApprove,
+    SettingsWrite,
+    AuditRead,
+}
+
+impl Permission {
+    pub const ALL: [Permission; 9] = [
+        Permission::WalletCreate,
+        Permission::WalletManage,
+        Permission::KeyExport,
+        Permission::KeyManage,
+        Permission::TransferCreate,
+        Permission::TransferExecute,
+        Permission::TransferApprove,
+        Permission::SettingsWrite,
+        Permission::AuditRead,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Permission::WalletCreate => "wallet:create",
+            Permission::WalletManage => "wallet:manage",
+            Permission::KeyExport => "key:export",
+            Permission::KeyManage => "key:manage",
+            Permission::TransferCreate => "transfer:create",
+            Permission::TransferExecute => "transfer:execute",
+            Permission::TransferApprove => "transfer:approve",
+            Permission::SettingsWrite => "settings:write",
+            Permission::AuditRead => "audit:read",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|p| p.as_str() == value)
+    }
+}
+
+impl fmt::Display for Permission {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Permissions seeded into an empty `role_permissions` table
+///
+/// `operator` stays read-only as before; `approver` may only sign off on transfers
+/// someone else prepared.
+pub const DEFAULT_ROLE_PERMISSIONS: &[(&str, Permission)] = &[
+    ("approver", Permission::TransferApprove),
+    ("approver", Permission::TransferExecute),
+];
+
+/// Resolve a role's stored permission names, ignoring names this build does not know
+pub fn resolve_permissions(role: &str, stored: &[String]) -> HashSet<Permission> {
+    if role == ADMIN_ROLE {
+        return Permission::ALL.into_iter().collect();
+    }
+    stored.iter().filter_map(|name| Permission::parse(name)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resolve_permissions() {
+        let stored = vec!["transfer:execute".to_string(), "wallet:fly".to_string()];
+
+        let approver = resolve_permissions("approver", &stored);
+        assert_eq!(approver, HashSet::from([Permission::TransferExecute]));
+
+        assert!(resolve_permissions("operator", &[]).is_empty());
+        assert_eq!(resolve_permissions(ADMIN_ROLE, &[]).len(), Permission::ALL.len());
+    }
+
+    #[test]
+    fn test_parse_round_trip() {
+        for permission in Permission::ALL {
+            assert_eq!(Permission::parse(permission.as_str()), Some(permission));
+        }
+    }
+}
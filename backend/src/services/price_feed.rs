@@ -0,0 +1,164 @@
+//! Fiat prices of native coins, used to show what a transfer fee cost
+//!
+//! Prices come from the HTTP endpoint in `price.url` and are cached for
+//! `price.cache_secs`. A failed lookup is logged and the fiat value left out;
+//! it never fails the request it was made for, and is not retried for
+//! `FAILED_LOOKUP_RETRY` so a price source that is down does not slow every request.
+
+use rust_decimal::Decimal;
+use std::collections::HashMap;
+use std::str::FromStr;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use crate::config::PriceConfig;
+use crate::error::{AppError, AppResult};
+
+/// How long a failed lookup is remembered before the price source is asked again
+const FAILED_LOOKUP_RETRY: Duration = Duration::from_secs(30);
+
+pub struct PriceFeed {
+    config: PriceConfig,
+    client: reqwest::Client,
+    /// Price per coin symbol (`None` for a failed lookup), with when it was fetched
+    cache: Mutex<HashMap<String, (Instant, Option<Decimal>)>>,
+}
+
+impl PriceFeed {
+    pub fn new(config: PriceConfig) -> Self {
+        let client = reqwest::Client::builder()
+            .timeout(Duration::from_secs(config.timeout_secs))
+            .build()
+            .unwrap_or_default();
+
+        Self {
+            config,
+            client,
+            cache: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Currency prices are quoted in, e.g. "USD"
+    pub fn currency(&self) -> &str {
+        &self.config.currency
+    }
+
+    /// Price of one `symbol` coin; `None` without a price source or when the lookup fails
+    pub async fn price(&self, symbol: &str) -> Option<Decimal> {
+        let url = self.config.url.as_deref()?;
+        if let Some(price) = self.cached(symbol) {
+            return price;
+        }
+
+        let price = match self.fetch(url, symbol).await {
+            Ok(price) => Some(price),
+            Err(e) => {
+                tracing::warn!("Price lookup for {} failed: {}", symbol, e);
+                None
+            }
+        };
+        let mut cache = self.cache.lock().unwrap_or_else(|e| e.into_inner());
+        cache.insert(symbol.to_string(), (Instant::now(), price));
+        price
+    }
+
+    /// Result of a recent lookup of `symbol`, including a recent failure
+    fn cached(&self, symbol: &str) -> Option<Option<Decimal>> {
+        let cache = self.cache.lock().unwrap_or_else(|e| e.into_inner());
+        let (fetched, price) = cache.get(symbol)?;
+        let ttl = match price {
+            Some(_) => Duration::from_secs(self.config.cache_secs),
+            None => FAILED_LOOKUP_RETRY,
+        };
+        (fetched.elapsed() < ttl).then_some(*price)
+    }
+
+    async fn fetch(&self, url: &str, symbol: &str) -> AppResult<Decimal> {
+        let url = url
+            .replace("{symbol}", symbol)
+            .replace("{currency}", &self.config.currency);
+        let body: serde_json::Value = self
+            .client
+            .get(&url)
+            .send()
+            .await
+            .and_then(|response| response.error_for_status())
+            .map_err(|e| AppError::InternalError(format!("Price request failed: {}", e)))?
+            .json()
+            .await
+            .map_err(|e| AppError::InternalError(format!("Price response is not JSON: {}", e)))?;
+
+        extract_price(&body, &self.config.price_pointer)
+    }
+}
+
+/// Price at a JSON pointer in a price response, given as a number or a numeric string
+fn extract_price(body: &serde_json::Value, pointer: &str) -> AppResult<Decimal> {
+    let text = match body.pointer(pointer) {
+        Some(serde_json::Value::String(text)) => text.clone(),
+        Some(serde_json::Value::Number(number)) => number.to_string(),
+        _ => {
+            return Err(AppError::InternalError(format!(
+                "Price response has no price at {}",
+                pointer
+            )))
+        }
+    };
+    let price = Decimal::from_str(&text)
+        .or_else(|_| Decimal::from_scientific(&text))
+        .map_err(|_| AppError::InternalError(format!("Invalid price '{}'", text)))?;
+    if price <= Decimal::ZERO {
+        return Err(AppError::InternalError(format!("Invalid price '{}'", text)));
+    }
+    Ok(price)
+}
+
+/// Fee in fiat for display, e.g. "1.26 USD"
+pub fn fiat_display(fee: Decimal, price: Decimal, currency: &str) -> String {
+    format!("{} {}", (fee * price).round_dp(2), currency)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_extract_price() {
+        let coinbase = json!({ "data": { "amount": "3012.45", "currency": "USD" } });
+        assert_eq!(
+            extract_price(&coinbase, "/data/amount").unwrap(),
+            Decimal::from_str("3012.45").unwrap()
+        );
+
+        let coingecko = json!({ "zcash": { "usd": 31.2 } });
+        assert_eq!(
+            extract_price(&coingecko, "/zcash/usd").unwrap(),
+            Decimal::from_str("31.2").unwrap()
+        );
+
+        assert!(extract_price(&coinbase, "/data/missing").is_err());
+        assert!(extract_price(&json!({ "price": "0" }), "/price").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_failed_lookup_is_cached() {
+        let feed = PriceFeed::new(PriceConfig {
+            url: Some("http://127.0.0.1:1/{symbol}".to_string()),
+            ..PriceConfig::default()
+        });
+        assert_eq!(feed.cached("ETH"), None);
+
+        assert_eq!(feed.price("ETH").await, None);
+        // The next request does not wait on the unreachable source again
+        assert_eq!(feed.cached("ETH"), Some(None));
+        assert_eq!(feed.cached("ZEC"), None);
+    }
+
+    #[test]
+    fn test_fiat_display_rounds_to_cents() {
+        let fee = Decimal::from_str("0.00042").unwrap();
+        let price = Decimal::from_str("3012.45").unwrap();
+        assert_eq!(fiat_display(fee, price, "USD"), "1.27 USD");
+    }
+}
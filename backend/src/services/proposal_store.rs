@@ -0,0 +1,250 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use crate::blockchain::zcash::orchard::transfer::TransferProposal;
+use crate::error::{AppError, AppResult};
+
+/// How long an unexecuted proposal is kept; it expires on chain after 40 blocks (~50 minutes)
+pub const PROPOSAL_TTL: Duration = Duration::from_secs(3600);
+
+struct StoredProposal {
+    wallet_id: i32,
+    user_id: i32,
+    proposal: TransferProposal,
+    /// Transaction signed from the proposal's offline package, the only one it may broadcast
+    signed_tx_id: Option<String>,
+    /// Transfer row awaiting approval before the proposal may be built
+    transfer_id: Option<i32>,
+    created: Instant,
+}
+
+/// Privacy transfer proposals handed out by initiate, keyed by `proposal_id`
+///
+/// Execute looks the proposal up here instead of trusting the copy the client
+/// sends back, so only proposals this instance created can be built.
+pub struct ProposalStore {
+    ttl: Duration,
+    proposals: Mutex<HashMap<String, StoredProposal>>,
+}
+
+impl ProposalStore {
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            ttl,
+            proposals: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Store a proposal; with a `transfer_id` it may only be built once that transfer is approved
+    pub fn insert(&self, wallet_id: i32, user_id: i32, proposal: TransferProposal, transfer_id: Option<i32>) {
+        let mut proposals = self.proposals.lock().unwrap_or_else(|e| e.into_inner());
+        proposals.retain(|_, stored| stored.created.elapsed() < self.ttl);
+        proposals.insert(
+            proposal.proposal_id.clone(),
+            StoredProposal {
+                wallet_id,
+                user_id,
+                proposal,
+                signed_tx_id: None,
+                transfer_id,
+                created: Instant::now(),
+            },
+        );
+    }
+
+    /// The stored proposal, if it exists, belongs to the wallet and is within the TTL
+    pub fn get(&self, wallet_id: i32, proposal_id: &str) -> AppResult<TransferProposal> {
+        let proposals = self.proposals.lock().unwrap_or_else(|e| e.into_inner());
+        proposals
+            .get(proposal_id)
+            .filter(|stored| stored.wallet_id == wallet_id && stored.created.elapsed() < self.ttl)
+            .map(|stored| stored.proposal.clone())
+            .ok_or_else(|| {
+                AppError::NotFound(format!(
+                    "Proposal {} not found or expired, create a new one",
+                    proposal_id
+                ))
+            })
+    }
+
+    /// Look a proposal up by id alone, returning the wallet it was created for
+    ///
+    /// Only the user who created the proposal may use it.
+    pub fn find(&self, proposal_id: &str, user_id: i32) -> AppResult<(i32, TransferProposal)> {
+        let proposals = self.proposals.lock().unwrap_or_else(|e| e.into_inner());
+        let stored = proposals
+            .get(proposal_id)
+            .filter(|stored| stored.created.elapsed() < self.ttl)
+            .ok_or_else(|| {
+                AppError::NotFound(format!(
+                    "Proposal {} not found or expired, create a new one",
+                    proposal_id
+                ))
+            })?;
+        if stored.user_id != user_id {
+            return Err(AppError::Forbidden(format!(
+                "Proposal {} belongs to another user",
+                proposal_id
+            )));
+        }
+        Ok((stored.wallet_id, stored.proposal.clone()))
+    }
+
+    /// Transfer row whose approval a proposal waits for, if it needs one
+    pub fn transfer_id(&self, proposal_id: &str) -> Option<i32> {
+        let proposals = self.proposals.lock().unwrap_or_else(|e| e.into_inner());
+        proposals.get(proposal_id).and_then(|stored| stored.transfer_id)
+    }
+
+    /// Remember the transaction signed from a proposal's offline package
+    pub fn mark_signed(&self, proposal_id: &str, tx_id: &str) {
+        let mut proposals = self.proposals.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(stored) = proposals.get_mut(proposal_id) {
+            stored.signed_tx_id = Some(tx_id.to_string());
+        }
+    }
+
+    /// Transaction signed offline for a proposal, if any
+    pub fn signed_tx_id(&self, proposal_id: &str) -> Option<String> {
+        let proposals = self.proposals.lock().unwrap_or_else(|e| e.into_inner());
+        proposals.get(proposal_id).and_then(|stored| stored.signed_tx_id.clone())
+    }
+
+    /// Drop a proposal once it has been broadcast so it cannot be executed twice
+    pub fn remove(&self, proposal_id: &str) {
+        let mut proposals = self.proposals.lock().unwrap_or_else(|e| e.into_inner());
+        proposals.remove(proposal_id);
+    }
+}
+
+/// Reject a proposal whose transaction could no longer be mined
+///
+/// A transaction is valid up to and including its expiry height, and the next
+/// block is `current_height + 1`, so the proposal is stale once the tip reaches it.
+pub fn check_not_expired(proposal: &TransferProposal, current_height: u64) -> AppResult<()> {
+    if current_height >= proposal.expiry_height {
+        return Err(AppError::ProposalExpired(format!(
+            "proposal {} expired at height {} (chain is at {}), create a new one",
+            proposal.proposal_id, proposal.expiry_height, current_height
+        )));
+    }
+    Ok(())
+}
+
+/// Reject a proposal received back from a client that differs from the stored one
+///
+/// Offline packages carry their proposal; only the checked, stored copy may be signed.
+pub fn check_same_proposal(stored: &TransferProposal, received: &TransferProposal) -> AppResult<()> {
+    let same = stored.proposal_id == received.proposal_id
+        && stored.to_address == received.to_address
+        && stored.amount_zatoshis == received.amount_zatoshis
+        && stored.fee_zatoshis == received.fee_zatoshis
+        && stored.fund_source == received.fund_source
+        && stored.is_shielding == received.is_shielding
+        && stored.is_deshielding == received.is_deshielding
+        && stored.memo == received.memo
+        && stored.expiry_height == received.expiry_height;
+    if !same {
+        return Err(AppError::ValidationError(format!(
+            "Package does not match proposal {}",
+            stored.proposal_id
+        )));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::blockchain::zcash::orchard::transfer::FundSource;
+
+    fn proposal(id: &str, expiry_height: u64) -> TransferProposal {
+        TransferProposal {
+            proposal_id: id.to_string(),
+            amount_zatoshis: 100_000,
+            fee_zatoshis: 10_000,
+            fund_source: FundSource::Shielded,
+            is_shielding: false,
+            is_deshielding: false,
+            to_address: "u1recipient".to_string(),
+            memo: None,
+            expiry_height,
+            coin_selection: Default::default(),
+        }
+    }
+
+    #[test]
+    fn test_only_the_owning_wallet_gets_a_live_proposal() {
+        let store = ProposalStore::new(Duration::from_millis(20));
+        store.insert(1, 7, proposal("abc", 100), None);
+
+        assert_eq!(store.get(1, "abc").unwrap().expiry_height, 100);
+        assert!(matches!(store.get(2, "abc"), Err(AppError::NotFound(_))));
+        assert!(matches!(store.get(1, "other"), Err(AppError::NotFound(_))));
+
+        std::thread::sleep(Duration::from_millis(40));
+        assert!(store.get(1, "abc").is_err());
+
+        store.insert(1, 7, proposal("def", 100), None);
+        store.remove("def");
+        assert!(store.get(1, "def").is_err());
+    }
+
+    #[test]
+    fn test_find_checks_the_owner() {
+        let store = ProposalStore::new(PROPOSAL_TTL);
+        store.insert(3, 7, proposal("abc", 100), None);
+
+        let (wallet_id, found) = store.find("abc", 7).unwrap();
+        assert_eq!(wallet_id, 3);
+        assert_eq!(found.proposal_id, "abc");
+        assert!(matches!(store.find("abc", 8), Err(AppError::Forbidden(_))));
+        assert!(matches!(store.find("missing", 7), Err(AppError::NotFound(_))));
+    }
+
+    #[test]
+    fn test_approval_transfer_is_remembered() {
+        let store = ProposalStore::new(PROPOSAL_TTL);
+        store.insert(3, 7, proposal("abc", 100), None);
+        assert!(store.transfer_id("abc").is_none());
+
+        store.insert(3, 7, proposal("def", 100), Some(12));
+        assert_eq!(store.transfer_id("def"), Some(12));
+    }
+
+    #[test]
+    fn test_signed_tx_is_remembered() {
+        let store = ProposalStore::new(PROPOSAL_TTL);
+        store.insert(3, 7, proposal("abc", 100), None);
+        assert!(store.signed_tx_id("abc").is_none());
+
+        store.mark_signed("abc", "txid");
+        assert_eq!(store.signed_tx_id("abc").as_deref(), Some("txid"));
+        store.mark_signed("missing", "txid");
+        assert!(store.signed_tx_id("missing").is_none());
+    }
+
+    #[test]
+    fn test_tampered_package_proposal_rejected() {
+        let stored = proposal("abc", 100);
+        assert!(check_same_proposal(&stored, &stored.clone()).is_ok());
+
+        let mut redirected = stored.clone();
+        redirected.to_address = "u1attacker".to_string();
+        assert!(matches!(check_same_proposal(&stored, &redirected), Err(AppError::ValidationError(_))));
+
+        let mut inflated = stored.clone();
+        inflated.amount_zatoshis += 1;
+        assert!(check_same_proposal(&stored, &inflated).is_err());
+    }
+
+    #[test]
+    fn test_expiry_height() {
+        let proposal = proposal("abc", 100);
+
+        assert!(check_not_expired(&proposal, 99).is_ok());
+        assert!(matches!(check_not_expired(&proposal, 100), Err(AppError::ProposalExpired(_))));
+        assert!(check_not_expired(&proposal, 140).is_err());
+    }
+}
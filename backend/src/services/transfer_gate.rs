@@ -0,0 +1,133 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use tokio::sync::{Mutex as AsyncMutex, OwnedMutexGuard, OwnedSemaphorePermit, Semaphore};
+
+use crate::error::{AppError, AppResult};
+
+/// Per-wallet limit on transfers in the build/broadcast phase
+///
+/// Concurrent sends from one wallet race for the same nonce (Ethereum) or the same
+/// notes/UTXOs (Zcash), so by default only one runs at a time.
+pub struct TransferGate {
+    max_concurrent: usize,
+    queue_when_busy: bool,
+    slots: Mutex<HashMap<i32, Arc<Semaphore>>>,
+    /// Held from a spending limit check until the send it allowed is recorded
+    spending: Mutex<HashMap<i32, Arc<AsyncMutex<()>>>>,
+}
+
+impl TransferGate {
+    pub fn new(max_concurrent: usize, queue_when_busy: bool) -> Self {
+        Self {
+            max_concurrent: max_concurrent.max(1),
+            queue_when_busy,
+            slots: Mutex::new(HashMap::new()),
+            spending: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Take a transfer slot for the wallet; it is released when the permit is dropped
+    ///
+    /// When all slots are taken the call either waits for one or fails with
+    /// "transfer in progress", depending on configuration.
+    pub async fn acquire(&self, wallet_id: i32) -> AppResult<OwnedSemaphorePermit> {
+        let semaphore = {
+            let mut slots = self.slots.lock().unwrap_or_else(|e| e.into_inner());
+            slots
+                .entry(wallet_id)
+                .or_insert_with(|| Arc::new(Semaphore::new(self.max_concurrent)))
+                .clone()
+        };
+
+        if self.queue_when_busy {
+            semaphore
+                .acquire_owned()
+                .await
+                .map_err(|e| AppError::InternalError(format!("Transfer gate closed: {}", e)))
+        } else {
+            semaphore.try_acquire_owned().map_err(|_| {
+                AppError::ValidationError(format!(
+                    "Transfer in progress for wallet {}, try again when it completes",
+                    wallet_id
+                ))
+            })
+        }
+    }
+
+    /// Serialize spending limit checks of a wallet with the sends they allow
+    ///
+    /// With more than one transfer slot, two sends could otherwise both pass the
+    /// check before either is recorded. Always waits, whatever `queue_when_busy` says.
+    pub async fn lock_spending(&self, wallet_id: i32) -> OwnedMutexGuard<()> {
+        let lock = {
+            let mut spending = self.spending.lock().unwrap_or_else(|e| e.into_inner());
+            spending.entry(wallet_id).or_default().clone()
+        };
+        lock.lock_owned().await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn test_second_transfer_rejected_when_not_queuing() {
+        let gate = TransferGate::new(1, false);
+
+        let first = gate.acquire(1).await.unwrap();
+        assert!(matches!(gate.acquire(1).await, Err(AppError::ValidationError(_))));
+
+        // Other wallets are not affected
+        assert!(gate.acquire(2).await.is_ok());
+
+        drop(first);
+        assert!(gate.acquire(1).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_second_transfer_waits_when_queuing() {
+        let gate = Arc::new(TransferGate::new(1, true));
+        let first = gate.acquire(1).await.unwrap();
+
+        let waiter = {
+            let gate = gate.clone();
+            tokio::spawn(async move { gate.acquire(1).await.map(|_| ()) })
+        };
+
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert!(!waiter.is_finished());
+
+        drop(first);
+        let result = tokio::time::timeout(Duration::from_secs(1), waiter).await;
+        assert!(result.unwrap().unwrap().is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_spending_checks_of_a_wallet_are_serialized() {
+        let gate = Arc::new(TransferGate::new(2, false));
+        let first = gate.lock_spending(1).await;
+
+        let waiter = {
+            let gate = gate.clone();
+            tokio::spawn(async move { drop(gate.lock_spending(1).await) })
+        };
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert!(!waiter.is_finished());
+        // Other wallets are not affected
+        drop(gate.lock_spending(2).await);
+
+        drop(first);
+        assert!(tokio::time::timeout(Duration::from_secs(1), waiter).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_configured_concurrency() {
+        let gate = TransferGate::new(2, false);
+        let _a = gate.acquire(1).await.unwrap();
+        let _b = gate.acquire(1).await.unwrap();
+        assert!(gate.acquire(1).await.is_err());
+    }
+}
@@ -1,17 +1,37 @@
+use chrono::{DateTime, Utc};
+use futures::Stream;
+use rust_decimal::prelude::ToPrimitive;
 use rust_decimal::Decimal;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
 use std::str::FromStr;
 use std::sync::Arc;
+use std::time::Duration;
 
+use crate::blockchain::traits::{ChainClient, SignedTransfer};
 use crate::blockchain::{ChainRegistry, TransferParams, TxStatus};
-use crate::db::models::{Transfer, TransferRequest};
-use crate::db::repositories::TransferRepository;
+use crate::config::TransferLimitsConfig;
+use crate::db::models::{
+    BatchTransferRequest, BatchTransferResult, NativeFee, Transfer, TransferRequest, TransferResponse,
+    Wallet, MAX_BATCH_TRANSFER_ITEMS,
+};
+use crate::db::repositories::{NewTransfer, TransferFilter, TransferRepository};
 use crate::error::{AppError, AppResult};
-use crate::services::WalletService;
+use crate::crypto::units::format_units;
+use crate::metrics::METRICS;
+use crate::services::activity_export::{ActivityRow, ExportFormat, EXPORT_PAGE_SIZE};
+use crate::services::price_feed::fiat_display;
+use crate::services::wallet_service::SpendingCheck;
+use crate::services::{AddressBook, DuplicateTransferGuard, PriceFeed, WalletService, WebhookNotifier};
 
 pub struct TransferService {
     transfer_repo: TransferRepository,
     wallet_service: Arc<WalletService>,
     chain_registry: Arc<ChainRegistry>,
+    transfer_limits: TransferLimitsConfig,
+    duplicate_guard: DuplicateTransferGuard,
+    webhooks: Arc<WebhookNotifier>,
+    address_book: Arc<AddressBook>,
+    prices: Arc<PriceFeed>,
 }
 
 impl TransferService {
@@ -19,30 +39,97 @@ impl TransferService {
         transfer_repo: TransferRepository,
         wallet_service: Arc<WalletService>,
         chain_registry: Arc<ChainRegistry>,
+        transfer_limits: TransferLimitsConfig,
+        webhooks: Arc<WebhookNotifier>,
+        address_book: Arc<AddressBook>,
+        prices: Arc<PriceFeed>,
     ) -> Self {
         Self {
             transfer_repo,
             wallet_service,
             chain_registry,
+            duplicate_guard: DuplicateTransferGuard::new(transfer_limits.duplicate_window_secs),
+            transfer_limits,
+            webhooks,
+            address_book,
+            prices,
         }
     }
 
+    /// Send the status-change webhook for a transfer using its stored state
+    async fn notify_status_change(&self, transfer_id: i32, previous_status: &str) {
+        match self.transfer_repo.find_by_id(transfer_id).await {
+            Ok(Some(transfer)) => self.webhooks.transfer_status_changed(&transfer, previous_status),
+            Ok(None) => {}
+            Err(e) => tracing::warn!("Failed to load transfer {} for webhook: {}", transfer_id, e),
+        }
+    }
+
+    /// Transfer previously created by `user_id` with this idempotency key, if still in its window
+    ///
+    /// `wallet_id` and `to_address` are the sending wallet and resolved recipient of
+    /// `request`. Fails with a conflict if the key was used for a different transfer.
+    async fn find_idempotent_transfer(
+        &self,
+        request: &TransferRequest,
+        wallet_id: i32,
+        to_address: &str,
+        user_id: i32,
+        key: &str,
+    ) -> AppResult<Option<Transfer>> {
+        let Some(existing) = self.transfer_repo.find_by_idempotency_key(user_id, key).await? else {
+            return Ok(None);
+        };
+
+        if !is_same_transfer(&existing, request, wallet_id, to_address) {
+            return Err(AppError::AlreadyExists(format!(
+                "Idempotency-Key was already used for a different transfer ({})",
+                existing.id
+            )));
+        }
+        Ok(Some(existing))
+    }
+
+    /// Release idempotency keys older than the configured window
+    pub async fn expire_idempotency_keys(&self) -> AppResult<u64> {
+        let window = chrono::Duration::seconds(self.transfer_limits.idempotency_window_secs as i64);
+        self.transfer_repo
+            .expire_idempotency_keys(chrono::Utc::now() - window)
+            .await
+    }
+
     /// Initiate a transfer (creates pending record)
+    ///
+    /// With an idempotency key, a retry returns the transfer created by the first
+    /// request. The flag is true when a new transfer was created.
     pub async fn initiate_transfer(
         &self,
         request: TransferRequest,
         user_id: i32,
-    ) -> AppResult<Transfer> {
+        idempotency_key: Option<&str>,
+    ) -> AppResult<(Transfer, bool)> {
         let chain_client = self.chain_registry.get(&request.chain)?;
 
-        // Validate address
-        if !chain_client.validate_address(&request.to_address) {
-            return Err(AppError::ValidationError("Invalid destination address".to_string()));
-        }
+        // Resolve address book labels and names such as ENS so the record holds the exact destination
+        let to_address = self
+            .address_book
+            .resolve_recipient(chain_client.as_ref(), &request.to_address)
+            .await?;
 
         // Get active wallet
         let wallet = self.wallet_service.get_active_wallet(&request.chain).await?;
 
+        if let Some(key) = idempotency_key {
+            if let Some(existing) = self
+                .find_idempotent_transfer(&request, wallet.id, &to_address, user_id, key)
+                .await?
+            {
+                tracing::info!("Transfer {} returned for repeated Idempotency-Key", existing.id);
+                return Ok((existing, false));
+            }
+        }
+        wallet.ensure_can_sign()?;
+
         // Parse amount
         let amount = Decimal::from_str(&request.amount)
             .map_err(|e| AppError::ValidationError(format!("Invalid amount: {}", e)))?;
@@ -51,6 +138,10 @@ impl TransferService {
             return Err(AppError::ValidationError("Amount must be positive".to_string()));
         }
 
+        // Guard against fat-finger amounts
+        self.transfer_limits
+            .check(&request.chain, amount, request.confirm_large)?;
+
         // Check balance
         let (native_balance, token_balances) = chain_client.get_all_balances(&wallet.address).await?;
 
@@ -89,26 +180,60 @@ impl TransferService {
             .transpose()
             .map_err(|e| AppError::ValidationError(format!("Invalid gas price: {}", e)))?;
 
+        // Catch accidental double-submits
+        self.duplicate_guard.check(
+            wallet.id,
+            &to_address,
+            amount,
+            &request.token,
+            request.allow_duplicate,
+        )?;
+
+        let approval_status = self
+            .transfer_limits
+            .requires_approval(&request.chain, amount)
+            .then_some("required");
+
         // Create transfer record
-        let transfer_id = self
+        let created = self
             .transfer_repo
-            .create(
-                wallet.id,
-                &request.chain,
-                &wallet.address,
-                &request.to_address,
-                &request.token,
+            .create(NewTransfer {
+                wallet_id: wallet.id,
+                chain: &request.chain,
+                from_address: &wallet.address,
+                to_address: &to_address,
+                token: &request.token,
                 amount,
                 gas_price,
-                request.gas_limit,
-                user_id,
-            )
-            .await?;
+                gas_limit: request.gas_limit,
+                initiated_by: user_id,
+                approval_status,
+                idempotency_key,
+            })
+            .await;
 
-        self.transfer_repo
+        let transfer_id = match (created, idempotency_key) {
+            (Ok(id), _) => {
+                self.duplicate_guard.record(wallet.id, &to_address, amount, &request.token);
+                id
+            }
+            // A concurrent request with the same key won the unique index
+            (Err(e), Some(key)) => match self
+                .find_idempotent_transfer(&request, wallet.id, &to_address, user_id, key)
+                .await?
+            {
+                Some(existing) => return Ok((existing, false)),
+                None => return Err(e),
+            },
+            (Err(e), None) => return Err(e),
+        };
+
+        let transfer = self
+            .transfer_repo
             .find_by_id(transfer_id)
             .await?
-            .ok_or_else(|| AppError::InternalError("Failed to retrieve transfer".to_string()))
+            .ok_or_else(|| AppError::InternalError("Failed to retrieve transfer".to_string()))?;
+        Ok((transfer, true))
     }
 
     /// Execute a pending transfer
@@ -119,27 +244,31 @@ impl TransferService {
             .await?
             .ok_or_else(|| AppError::NotFound("Transfer not found".to_string()))?;
 
+        // Only a limited number of transfers per wallet may build/broadcast at once
+        // (shared nonce). When queued, re-read the status after waiting.
+        let _slot = self.wallet_service.acquire_transfer_slot(transfer.wallet_id).await?;
+        let transfer = self
+            .transfer_repo
+            .find_by_id(transfer_id)
+            .await?
+            .ok_or_else(|| AppError::NotFound("Transfer not found".to_string()))?;
+
         if transfer.status != "pending" {
             return Err(AppError::ValidationError(format!(
                 "Transfer is not pending. Current status: {}",
                 transfer.status
             )));
         }
+        transfer.ensure_not_proposal()?;
+        transfer.ensure_approved()?;
+        self.address_book
+            .ensure_listed(&transfer.chain, &transfer.to_address)
+            .await?;
+        // Locked until the transfer is recorded, so concurrent sends see each other
+        let _spending = self.check_spending_limit(&transfer).await?;
 
         let chain_client = self.chain_registry.get(&transfer.chain)?;
-
-        // Get private key
-        let private_key = self.wallet_service.get_private_key(transfer.wallet_id).await?;
-
-        let params = TransferParams {
-            from_address: transfer.from_address.clone(),
-            to_address: transfer.to_address.clone(),
-            private_key,
-            token: transfer.token.clone(),
-            amount: transfer.amount,
-            gas_price_gwei: transfer.gas_price,
-            gas_limit: transfer.gas_limit.map(|g| g as u64),
-        };
+        let params = self.transfer_params(&transfer).await?;
 
         // Execute transfer
         let is_native = transfer.token.to_uppercase() == chain_client.native_token_symbol();
@@ -154,11 +283,17 @@ impl TransferService {
                 self.transfer_repo
                     .update_status(transfer_id, "submitted", Some(&tx_hash), None)
                     .await?;
+                self.record_nonce(chain_client.as_ref(), transfer_id, &tx_hash).await;
+                self.wallet_service.invalidate_balance(&transfer.chain, &transfer.from_address);
+                METRICS.record_transfer_status("submitted");
+                self.notify_status_change(transfer_id, &transfer.status).await;
             }
             Err(e) => {
                 self.transfer_repo
                     .update_status(transfer_id, "failed", None, Some(&e.to_string()))
                     .await?;
+                METRICS.record_transfer_status("failed");
+                self.notify_status_change(transfer_id, &transfer.status).await;
                 return Err(e);
             }
         }
@@ -169,32 +304,577 @@ impl TransferService {
             .ok_or_else(|| AppError::InternalError("Failed to retrieve transfer".to_string()))
     }
 
+    /// Create and send several transfers from the chain's active wallet
+    ///
+    /// Every item is validated, and the per-token totals checked against the balance and
+    /// the transfer limits, before any transfer is created. Items are then sent one after
+    /// another, or as one multi-output transaction on chains that support it. A failed item
+    /// is reported in its result and does not undo the others. Items of a token whose total
+    /// is above the approval threshold are created but left pending.
+    pub async fn batch_transfer(
+        &self,
+        request: BatchTransferRequest,
+        user_id: i32,
+    ) -> AppResult<Vec<BatchTransferResult>> {
+        if request.items.is_empty() {
+            return Err(AppError::ValidationError("A batch needs at least one item".to_string()));
+        }
+        if request.items.len() > MAX_BATCH_TRANSFER_ITEMS {
+            return Err(AppError::ValidationError(format!(
+                "A batch may contain at most {} items",
+                MAX_BATCH_TRANSFER_ITEMS
+            )));
+        }
+
+        let chain_client = self.chain_registry.get(&request.chain)?;
+        let wallet = self.wallet_service.get_active_wallet(&request.chain).await?;
+        wallet.ensure_can_sign()?;
+
+        // Resolved destination and parsed amount of each item
+        let mut outputs: Vec<(String, Decimal)> = Vec::with_capacity(request.items.len());
+        for (index, item) in request.items.iter().enumerate() {
+            let to_address = self
+                .address_book
+                .resolve_recipient(chain_client.as_ref(), &item.to_address)
+                .await
+                .map_err(|e| batch_item_error(index, e))?;
+
+            let amount = Decimal::from_str(&item.amount).map_err(|e| {
+                batch_item_error(index, AppError::ValidationError(format!("Invalid amount: {}", e)))
+            })?;
+            if amount <= Decimal::ZERO {
+                return Err(batch_item_error(
+                    index,
+                    AppError::ValidationError("Amount must be positive".to_string()),
+                ));
+            }
+            self.transfer_limits
+                .check(&request.chain, amount, request.confirm_large)
+                .map_err(|e| batch_item_error(index, e))?;
+
+            outputs.push((to_address, amount));
+        }
+
+        // The whole batch must be covered and within the limits, not just each item on its own
+        let totals = batch_totals(
+            request
+                .items
+                .iter()
+                .zip(&outputs)
+                .map(|(item, (_, amount))| (item.token.as_str(), *amount)),
+        )?;
+        let held_tokens =
+            check_batch_totals(&self.transfer_limits, &request.chain, &totals, request.confirm_large)?;
+        let (native_balance, token_balances) = chain_client.get_all_balances(&wallet.address).await?;
+        for (token, total) in &totals {
+            let available = if token == chain_client.native_token_symbol() {
+                native_balance
+            } else {
+                token_balances
+                    .iter()
+                    .find(|t| t.symbol.to_uppercase() == *token)
+                    .map(|t| t.balance)
+                    .unwrap_or(Decimal::ZERO)
+            };
+            if available < *total {
+                return Err(AppError::InsufficientBalance(format!(
+                    "Insufficient {} balance for the batch. Required: {}, Available: {}",
+                    token, total, available
+                )));
+            }
+        }
+
+        let guarded: Vec<(&str, Decimal, &str)> = request
+            .items
+            .iter()
+            .zip(&outputs)
+            .map(|(item, (to_address, amount))| (to_address.as_str(), *amount, item.token.as_str()))
+            .collect();
+        self.duplicate_guard
+            .check_batch(wallet.id, &guarded, request.allow_duplicate)
+            .map_err(|(index, e)| batch_item_error(index, e))?;
+
+        // Create every transfer; from here on failures are per item
+        let mut results = Vec::with_capacity(outputs.len());
+        let mut ready = Vec::new();
+        for (index, (item, (to_address, amount))) in request.items.iter().zip(&outputs).enumerate() {
+            let approval_status = (self.transfer_limits.requires_approval(&request.chain, *amount)
+                || held_tokens.contains(&item.token.to_uppercase()))
+            .then_some("required");
+            let created = self
+                .transfer_repo
+                .create(NewTransfer {
+                    wallet_id: wallet.id,
+                    chain: &request.chain,
+                    from_address: &wallet.address,
+                    to_address,
+                    token: &item.token,
+                    amount: *amount,
+                    initiated_by: user_id,
+                    approval_status,
+                    ..NewTransfer::default()
+                })
+                .await;
+
+            let mut result = BatchTransferResult {
+                index,
+                to_address: to_address.clone(),
+                token: item.token.clone(),
+                amount: amount.to_string(),
+                transfer_id: None,
+                status: "failed".to_string(),
+                tx_hash: None,
+                error: None,
+            };
+            match created {
+                Ok(transfer_id) => {
+                    self.duplicate_guard.record(wallet.id, to_address, *amount, &item.token);
+                    result.transfer_id = Some(transfer_id);
+                    result.status = "pending".to_string();
+                    if approval_status.is_none() {
+                        ready.push((index, transfer_id));
+                    }
+                }
+                Err(e) => result.error = Some(e.to_string()),
+            }
+            results.push(result);
+        }
+
+        let all_native = request
+            .items
+            .iter()
+            .all(|item| item.token.to_uppercase() == chain_client.native_token_symbol());
+        if chain_client.supports_batch_outputs() && all_native && ready.len() > 1 {
+            self.send_combined(chain_client.as_ref(), &wallet, &outputs, &ready, &mut results)
+                .await;
+        } else {
+            // One transaction per item, in order; each takes the wallet's next nonce
+            for (index, transfer_id) in ready {
+                let result = &mut results[index];
+                match self.execute_transfer(transfer_id).await {
+                    Ok(transfer) => {
+                        result.status = transfer.status;
+                        result.tx_hash = transfer.tx_hash;
+                    }
+                    Err(e) => {
+                        result.error = Some(e.to_string());
+                        if let Ok(Some(transfer)) = self.transfer_repo.find_by_id(transfer_id).await {
+                            result.status = transfer.status;
+                        }
+                    }
+                }
+            }
+        }
+
+        Ok(results)
+    }
+
+    /// Send the ready batch items as one multi-output transaction
+    async fn send_combined(
+        &self,
+        chain_client: &dyn ChainClient,
+        wallet: &Wallet,
+        outputs: &[(String, Decimal)],
+        ready: &[(usize, i32)],
+        results: &mut [BatchTransferResult],
+    ) {
+        let _slot = match self.wallet_service.acquire_transfer_slot(wallet.id).await {
+            Ok(slot) => slot,
+            Err(e) => {
+                for (index, _) in ready {
+                    results[*index].error = Some(e.to_string());
+                }
+                return;
+            }
+        };
+
+        // Locked until every item's status is recorded, like a single transfer
+        let spending = match self
+            .wallet_service
+            .begin_spending(wallet.id, chain_client.native_token_symbol())
+            .await
+        {
+            Ok(spending) => spending,
+            Err(e) => {
+                for (index, _) in ready {
+                    results[*index].error = Some(e.to_string());
+                }
+                return;
+            }
+        };
+
+        // A transfer executed separately since it was created must not be paid twice.
+        // Items sent together count toward the spending limit of those after them.
+        // Like a single transfer, an item whose recipient left the book is not sent.
+        let mut sending = Vec::with_capacity(ready.len());
+        let mut batch_total = Decimal::ZERO;
+        for (index, transfer_id) in ready {
+            match self.transfer_repo.find_by_id(*transfer_id).await {
+                Ok(Some(transfer)) if transfer.status == "pending" => {
+                    let listed = self
+                        .address_book
+                        .ensure_listed(&transfer.chain, &transfer.to_address)
+                        .await;
+                    let allowed = listed.and_then(|()| match &spending {
+                        Some(spending) if transfer.limit_override_by.is_none() => {
+                            spending.check(transfer.amount, batch_total)
+                        }
+                        _ => Ok(()),
+                    });
+                    match allowed {
+                        Ok(()) => {
+                            batch_total += transfer.amount;
+                            sending.push((*index, *transfer_id));
+                        }
+                        Err(e) => results[*index].error = Some(e.to_string()),
+                    }
+                }
+                Ok(Some(transfer)) => results[*index].status = transfer.status,
+                Ok(None) => results[*index].error = Some("Transfer not found".to_string()),
+                Err(e) => results[*index].error = Some(e.to_string()),
+            }
+        }
+        if sending.is_empty() {
+            return;
+        }
+
+        let batch_outputs: Vec<(String, Decimal)> =
+            sending.iter().map(|(index, _)| outputs[*index].clone()).collect();
+        let sent = match self.wallet_service.get_private_key(wallet.id).await {
+            Ok(private_key) => {
+                chain_client
+                    .transfer_native_batch(&wallet.address, &batch_outputs, &private_key)
+                    .await
+            }
+            Err(e) => Err(e),
+        };
+
+        if sent.is_ok() {
+            self.wallet_service.invalidate_balance(chain_client.chain_id(), &wallet.address);
+        }
+        let (status, tx_hash, error) = match &sent {
+            Ok(tx_hash) => ("submitted", Some(tx_hash.as_str()), None),
+            Err(e) => ("failed", None, Some(e.to_string())),
+        };
+        for (index, transfer_id) in sending {
+            let result = &mut results[index];
+            if let Err(e) = self
+                .transfer_repo
+                .update_status(transfer_id, status, tx_hash, error.as_deref())
+                .await
+            {
+                tracing::error!("Failed to record status of batch transfer {}: {}", transfer_id, e);
+            }
+            METRICS.record_transfer_status(status);
+            self.notify_status_change(transfer_id, "pending").await;
+
+            result.status = status.to_string();
+            result.tx_hash = tx_hash.map(str::to_string);
+            result.error = error.clone();
+        }
+    }
+
+    /// Fail if sending the transfer would break the wallet's spending limit on its token
+    ///
+    /// Transfers an admin overrode are let through. The returned check holds the
+    /// wallet's spending lock; keep it until the transfer is recorded as submitted.
+    async fn check_spending_limit(&self, transfer: &Transfer) -> AppResult<Option<SpendingCheck>> {
+        if let Some(admin_id) = transfer.limit_override_by {
+            tracing::info!(
+                "Transfer {} skips the spending limit, overridden by user {}",
+                transfer.id,
+                admin_id
+            );
+            return Ok(None);
+        }
+        let Some(spending) = self
+            .wallet_service
+            .begin_spending(transfer.wallet_id, &transfer.token)
+            .await?
+        else {
+            return Ok(None);
+        };
+        spending.check(transfer.amount, Decimal::ZERO)?;
+        Ok(Some(spending))
+    }
+
+    /// Let one pending transfer exceed its wallet's spending limit
+    ///
+    /// Only an admin other than the initiator may override.
+    pub async fn override_spending_limit(&self, transfer_id: i32, admin_id: i32) -> AppResult<Transfer> {
+        let transfer = self
+            .transfer_repo
+            .find_by_id(transfer_id)
+            .await?
+            .ok_or_else(|| AppError::NotFound("Transfer not found".to_string()))?;
+
+        if transfer.initiated_by == admin_id {
+            return Err(AppError::Forbidden(
+                "A spending limit override must come from someone other than the initiator".to_string(),
+            ));
+        }
+        if !self.transfer_repo.set_limit_override(transfer_id, admin_id).await? {
+            return Err(AppError::ValidationError(format!(
+                "Transfer is not pending. Current status: {}",
+                transfer.status
+            )));
+        }
+        tracing::info!("Spending limit overridden for transfer {} by user {}", transfer_id, admin_id);
+
+        self.transfer_repo
+            .find_by_id(transfer_id)
+            .await?
+            .ok_or_else(|| AppError::InternalError("Failed to retrieve transfer".to_string()))
+    }
+
+    /// Build and sign a pending transfer without broadcasting it
+    ///
+    /// The transfer row is left untouched, so it can still be executed normally.
+    pub async fn dry_run_transfer(&self, transfer_id: i32) -> AppResult<SignedTransfer> {
+        let transfer = self
+            .transfer_repo
+            .find_by_id(transfer_id)
+            .await?
+            .ok_or_else(|| AppError::NotFound("Transfer not found".to_string()))?;
+
+        check_dry_run(&transfer)?;
+        self.address_book
+            .ensure_listed(&transfer.chain, &transfer.to_address)
+            .await?;
+        self.check_spending_limit(&transfer).await?;
+
+        let chain_client = self.chain_registry.get(&transfer.chain)?;
+        let params = self.transfer_params(&transfer).await?;
+        chain_client.sign_transfer(&params).await
+    }
+
+    /// Approve or reject a transfer awaiting approval
+    ///
+    /// The decision must come from someone other than the initiator.
+    pub async fn decide_approval(
+        &self,
+        transfer_id: i32,
+        user_id: i32,
+        approved: bool,
+        reason: Option<&str>,
+    ) -> AppResult<Transfer> {
+        let transfer = self
+            .transfer_repo
+            .find_by_id(transfer_id)
+            .await?
+            .ok_or_else(|| AppError::NotFound("Transfer not found".to_string()))?;
+
+        if transfer.approval_status.as_deref() != Some("required") || transfer.status != "pending" {
+            return Err(AppError::ValidationError(format!(
+                "Transfer {} is not awaiting approval",
+                transfer_id
+            )));
+        }
+        if transfer.initiated_by == user_id {
+            return Err(AppError::Forbidden(
+                "A transfer must be approved by someone other than its initiator".to_string(),
+            ));
+        }
+
+        if !self
+            .transfer_repo
+            .decide_approval(transfer_id, approved, user_id, reason)
+            .await?
+        {
+            return Err(AppError::ValidationError(format!(
+                "Transfer {} is not awaiting approval",
+                transfer_id
+            )));
+        }
+
+        if approved {
+            tracing::info!("Transfer {} approved by user {}", transfer_id, user_id);
+        } else {
+            tracing::info!("Transfer {} rejected by user {}", transfer_id, user_id);
+            METRICS.record_transfer_status("rejected");
+            self.notify_status_change(transfer_id, &transfer.status).await;
+        }
+
+        self.transfer_repo
+            .find_by_id(transfer_id)
+            .await?
+            .ok_or_else(|| AppError::InternalError("Failed to retrieve transfer".to_string()))
+    }
+
+    /// Signing parameters for a stored transfer, with the wallet's decrypted key
+    async fn transfer_params(&self, transfer: &Transfer) -> AppResult<TransferParams> {
+        let private_key = self.wallet_service.get_private_key(transfer.wallet_id).await?;
+        Ok(signing_params(transfer, private_key))
+    }
+
+    /// Speed up a stuck EVM transfer by rebroadcasting it with the same nonce
+    /// and a higher fee. The transfer keeps its row; only tx_hash and gas_price change.
+    /// Returns the updated transfer and the hash of the transaction it replaced.
+    pub async fn bump_transfer_fee(
+        &self,
+        transfer_id: i32,
+        new_gas_price_gwei: Decimal,
+    ) -> AppResult<(Transfer, String)> {
+        let transfer = self
+            .transfer_repo
+            .find_by_id(transfer_id)
+            .await?
+            .ok_or_else(|| AppError::NotFound("Transfer not found".to_string()))?;
+
+        if transfer.status == "confirmed" {
+            return Err(AppError::ValidationError(
+                "Transfer is already confirmed".to_string(),
+            ));
+        }
+        let old_tx_hash = match (&transfer.tx_hash, transfer.status.as_str()) {
+            (Some(tx_hash), "submitted") => tx_hash.clone(),
+            _ => {
+                return Err(AppError::ValidationError(format!(
+                    "Only submitted transfers can be sped up. Current status: {}",
+                    transfer.status
+                )))
+            }
+        };
+
+        // Hold the wallet's slot so no other transfer reuses the nonce meanwhile
+        let _slot = self.wallet_service.acquire_transfer_slot(transfer.wallet_id).await?;
+
+        let chain_client = self.chain_registry.get(&transfer.chain)?;
+        let private_key = self.wallet_service.get_private_key(transfer.wallet_id).await?;
+
+        let new_tx_hash = chain_client
+            .replace_transaction(&old_tx_hash, &private_key, new_gas_price_gwei)
+            .await?;
+
+        self.transfer_repo
+            .replace_tx(transfer_id, &old_tx_hash, &new_tx_hash, new_gas_price_gwei)
+            .await?;
+        tracing::info!(
+            "Transfer {} fee bumped to {} Gwei: {} -> {}",
+            transfer_id,
+            new_gas_price_gwei,
+            old_tx_hash,
+            new_tx_hash
+        );
+
+        let transfer = self
+            .transfer_repo
+            .find_by_id(transfer_id)
+            .await?
+            .ok_or_else(|| AppError::InternalError("Failed to retrieve transfer".to_string()))?;
+        Ok((transfer, old_tx_hash))
+    }
+
     /// Check and update status of submitted transfers
     pub async fn check_pending_transfers(&self) -> AppResult<()> {
         let pending = self.transfer_repo.list_pending().await?;
 
         for transfer in pending {
-            if let Some(tx_hash) = &transfer.tx_hash {
+            if let Some(latest_tx_hash) = &transfer.tx_hash {
                 let chain_client = match self.chain_registry.get(&transfer.chain) {
                     Ok(c) => c,
                     Err(_) => continue,
                 };
 
-                match chain_client.get_tx_status(tx_hash).await {
-                    Ok(TxStatus::Confirmed { block_number, gas_used }) => {
+                let mut status = chain_client.get_tx_status(latest_tx_hash).await;
+                // A fee bump may have lost the race to the transaction it replaced
+                let mut earlier_tx_hash = None;
+                if matches!(status, Ok(TxStatus::NotFound)) {
+                    if let Some((hash, earlier_status)) = self.replaced_tx_status(chain_client.as_ref(), &transfer).await {
+                        if !matches!(earlier_status, TxStatus::Pending) {
+                            tracing::info!("Transfer {} was mined as its replaced transaction {}", transfer.id, hash);
+                            self.transfer_repo.restore_tx(transfer.id, &hash).await?;
+                        }
+                        status = Ok(earlier_status);
+                        earlier_tx_hash = Some(hash);
+                    }
+                }
+                let tx_hash = earlier_tx_hash.as_deref().unwrap_or(latest_tx_hash);
+
+                let status = status.map(|status| {
+                    poll_outcome(
+                        &transfer,
+                        status,
+                        chain_client.required_confirmations(),
+                        chain_client.pending_timeout(),
+                        Utc::now(),
+                    )
+                });
+                match status {
+                    Ok(PollOutcome::Mined { block_number, confirmations }) => {
+                        // Mined but not yet final: record the block and keep it submitted
+                        if transfer.block_number != Some(block_number as i64) {
+                            self.transfer_repo
+                                .update_block_number(transfer.id, Some(block_number as i64))
+                                .await?;
+                        }
+                        tracing::debug!(
+                            "Transfer {} at block {} has {}/{} confirmations",
+                            transfer.id,
+                            block_number,
+                            confirmations,
+                            chain_client.required_confirmations()
+                        );
+                    }
+                    Ok(PollOutcome::Confirmed { block_number, gas_used, fee }) => {
                         self.transfer_repo
                             .update_confirmed(transfer.id, block_number as i64, gas_used as i64)
                             .await?;
+                        METRICS.record_transfer_status("confirmed");
+
+                        // Backfill the normalized fee unless it was recorded at broadcast
+                        if transfer.fee_native.is_none() {
+                            let first_id = self.transfer_repo.first_id_with_tx_hash(tx_hash).await?;
+                            let fee = fee_share(fee, transfer.id, first_id).and_then(|units| {
+                                NativeFee::new(
+                                    units,
+                                    chain_client.native_decimals() as u32,
+                                    chain_client.native_token_symbol(),
+                                )
+                            });
+                            if let Some(fee) = fee {
+                                self.transfer_repo.update_fee(transfer.id, &fee).await?;
+                            }
+                        }
                         tracing::info!("Transfer {} confirmed at block {}", transfer.id, block_number);
+                        self.notify_status_change(transfer.id, &transfer.status).await;
                     }
-                    Ok(TxStatus::Failed { reason }) => {
+                    Ok(PollOutcome::Failed { reason }) => {
                         self.transfer_repo
                             .update_status(transfer.id, "failed", None, Some(&reason))
                             .await?;
+                        METRICS.record_transfer_status("failed");
                         tracing::warn!("Transfer {} failed: {}", transfer.id, reason);
+                        self.notify_status_change(transfer.id, &transfer.status).await;
+
+                        // Shielded notes were marked spent at broadcast time; give them back
+                        if transfer.token == "ZEC-shielded" {
+                            self.wallet_service.release_spent_notes(tx_hash).await?;
+                        }
+                    }
+                    Ok(PollOutcome::Pending { stuck }) => {
+                        self.clear_reorged_block(&transfer).await?;
+                        if transfer.nonce.is_none() {
+                            self.record_nonce(chain_client.as_ref(), transfer.id, tx_hash).await;
+                        }
+                        // Still spendable on chain, so it stays submitted and keeps being polled
+                        if let Some(note) = stuck {
+                            if self.transfer_repo.flag_stuck(transfer.id, &note).await? {
+                                tracing::warn!("Transfer {} is stuck in the mempool: {}", transfer.id, note);
+                            }
+                        }
                     }
-                    Ok(TxStatus::Pending) | Ok(TxStatus::NotFound) => {
-                        // Still pending, do nothing
+                    Ok(PollOutcome::NotFound) => {
+                        self.clear_reorged_block(&transfer).await?;
+                        if let Some(reason) = self.dropped_reason(chain_client.as_ref(), &transfer).await {
+                            self.transfer_repo
+                                .update_status(transfer.id, "failed", Some(tx_hash), Some(&reason))
+                                .await?;
+                            METRICS.record_transfer_status("failed");
+                            tracing::warn!("Transfer {} dropped: {}", transfer.id, reason);
+                            chain_client.reset_nonce(&transfer.from_address).await;
+                            self.notify_status_change(transfer.id, &transfer.status).await;
+                        }
                     }
                     Err(e) => {
                         tracing::warn!("Failed to check transfer {}: {}", transfer.id, e);
@@ -206,6 +886,78 @@ impl TransferService {
         Ok(())
     }
 
+    /// A transfer seen in a block earlier that is no longer there was reorged out
+    async fn clear_reorged_block(&self, transfer: &Transfer) -> AppResult<()> {
+        if let Some(block_number) = transfer.block_number {
+            tracing::warn!(
+                "Transfer {} is no longer in block {}, waiting for it to be mined again",
+                transfer.id,
+                block_number
+            );
+            self.transfer_repo.update_block_number(transfer.id, None).await?;
+        }
+        Ok(())
+    }
+
+    /// Status of the newest transaction a fee bump replaced that the node still knows about
+    async fn replaced_tx_status(&self, chain_client: &dyn ChainClient, transfer: &Transfer) -> Option<(String, TxStatus)> {
+        let hashes = match self.transfer_repo.replaced_tx_hashes(transfer.id).await {
+            Ok(hashes) => hashes,
+            Err(e) => {
+                tracing::warn!("Failed to load replaced transactions of transfer {}: {}", transfer.id, e);
+                return None;
+            }
+        };
+        for hash in hashes {
+            match chain_client.get_tx_status(&hash).await {
+                Ok(TxStatus::NotFound) => {}
+                Ok(status) => return Some((hash, status)),
+                Err(e) => tracing::warn!("Failed to check replaced transaction {}: {}", hash, e),
+            }
+        }
+        None
+    }
+
+    /// Store the nonce of a broadcast transaction so a replacement can be detected later
+    async fn record_nonce(&self, chain_client: &dyn ChainClient, transfer_id: i32, tx_hash: &str) {
+        match chain_client.tx_nonce(tx_hash).await {
+            Ok(Some(nonce)) => {
+                if let Err(e) = self.transfer_repo.update_nonce(transfer_id, nonce).await {
+                    tracing::warn!("Failed to store nonce of transfer {}: {}", transfer_id, e);
+                }
+            }
+            Ok(None) => {}
+            Err(e) => tracing::debug!("Nonce of transfer {} not available yet: {}", transfer_id, e),
+        }
+    }
+
+    /// Why a submitted transaction the node no longer knows about should be given up on
+    ///
+    /// Either its nonce was mined by another transaction (replaced outside this service),
+    /// or nothing has happened to it for longer than the chain's pending timeout.
+    async fn dropped_reason(&self, chain_client: &dyn ChainClient, transfer: &Transfer) -> Option<String> {
+        if let Some(nonce) = transfer.nonce {
+            match chain_client.mined_nonce(&transfer.from_address).await {
+                Ok(Some(mined)) if mined > nonce as u64 => {
+                    return Some(format!(
+                        "Nonce {} was used by a different transaction; this transfer was replaced or dropped",
+                        nonce
+                    ));
+                }
+                Ok(_) => {}
+                Err(e) => tracing::warn!("Failed to read nonce for transfer {}: {}", transfer.id, e),
+            }
+        }
+
+        let timeout = chain_client.pending_timeout()?;
+        submission_timed_out(transfer.submitted_at, transfer.created_at, timeout, Utc::now()).then(|| {
+            format!(
+                "Transaction was neither mined nor in the mempool {} minutes after submission; it was dropped",
+                timeout.as_secs() / 60
+            )
+        })
+    }
+
     /// Get transfer by ID
     pub async fn get_transfer(&self, id: i32) -> AppResult<Transfer> {
         self.transfer_repo
@@ -214,22 +966,458 @@ impl TransferService {
             .ok_or_else(|| AppError::NotFound("Transfer not found".to_string()))
     }
 
-    /// List transfers with pagination
-    pub async fn list_transfers(&self, limit: i32, offset: i32) -> AppResult<(Vec<Transfer>, i64)> {
-        let transfers = self.transfer_repo.list_all(limit, offset).await?;
-        let total = self.transfer_repo.count_all().await?;
-        Ok((transfers, total))
+    /// Re-render fee display strings with `places` decimals; `fee_native` stays exact
+    pub fn apply_display_decimals(&self, transfers: &mut [Transfer], places: u32) {
+        for transfer in transfers.iter_mut() {
+            let (Some(units), Ok(chain_client)) = (
+                transfer.fee_native.and_then(|fee| fee.to_u128()),
+                self.chain_registry.get(&transfer.chain),
+            ) else {
+                continue;
+            };
+            transfer.fee_display = Some(NativeFee::display(
+                units,
+                chain_client.native_decimals() as u32,
+                chain_client.native_token_symbol(),
+                Some(places),
+            ));
+        }
+    }
+
+    /// Transfers with their fees priced in fiat, when a price source is configured
+    ///
+    /// Each coin's price is looked up once per call. Test network coins are not priced.
+    pub async fn with_fiat_fees(&self, transfers: Vec<Transfer>) -> Vec<TransferResponse> {
+        let mut prices: HashMap<String, Option<Decimal>> = HashMap::new();
+        let mut responses = Vec::with_capacity(transfers.len());
+        for transfer in transfers {
+            let mut fee_fiat = None;
+            let priced = self
+                .chain_registry
+                .get(&transfer.chain)
+                .is_ok_and(|chain_client| !chain_client.is_testnet());
+            if let Some((fee, symbol)) = self.native_fee(&transfer).filter(|_| priced) {
+                if !prices.contains_key(&symbol) {
+                    let price = self.prices.price(&symbol).await;
+                    prices.insert(symbol.clone(), price);
+                }
+                if let (Ok(fee), Some(price)) = (Decimal::from_str(&fee), prices[&symbol]) {
+                    fee_fiat = Some(fiat_display(fee, price, self.prices.currency()));
+                }
+            }
+            responses.push(TransferResponse { transfer, fee_fiat });
+        }
+        responses
+    }
+
+    pub async fn with_fiat_fee(&self, transfer: Transfer) -> TransferResponse {
+        let mut responses = self.with_fiat_fees(vec![transfer]).await;
+        responses.remove(0)
+    }
+
+    /// Exact fee in the chain's native coin, with its symbol
+    fn native_fee(&self, transfer: &Transfer) -> Option<(String, String)> {
+        let units = transfer.fee_native.and_then(|fee| fee.to_u128())?;
+        let chain_client = self.chain_registry.get(&transfer.chain).ok()?;
+        Some((
+            format_units(units, chain_client.native_decimals() as u32),
+            chain_client.native_token_symbol().to_string(),
+        ))
+    }
+
+    /// Transfers matching the filter as export text, oldest first
+    ///
+    /// Rows are read and sent one page at a time, so an export of any size is
+    /// never held in memory as a whole.
+    pub fn export_activity(
+        self: Arc<Self>,
+        filter: TransferFilter,
+        format: ExportFormat,
+    ) -> impl Stream<Item = AppResult<String>> + 'static {
+        enum Stage {
+            Header,
+            Rows { after_id: i32, first: bool },
+            Done,
+        }
+
+        futures::stream::unfold(Stage::Header, move |stage| {
+            let service = self.clone();
+            let filter = filter.clone();
+            async move {
+                match stage {
+                    Stage::Header => Some((Ok(format.header()), Stage::Rows { after_id: 0, first: true })),
+                    Stage::Rows { after_id, first } => {
+                        match service.transfer_repo.list_after(&filter, after_id, EXPORT_PAGE_SIZE).await {
+                            Err(e) => Some((Err(e), Stage::Done)),
+                            Ok(page) if page.is_empty() => Some((Ok(format.footer().to_string()), Stage::Done)),
+                            Ok(page) => {
+                                let mut chunk = String::new();
+                                for (i, transfer) in page.iter().enumerate() {
+                                    let row = ActivityRow::new(transfer, service.native_fee(transfer));
+                                    chunk.push_str(&format.row(&row, first && i == 0));
+                                }
+                                let after_id = page.last().map_or(after_id, |t| t.id);
+                                Some((Ok(chunk), Stage::Rows { after_id, first: false }))
+                            }
+                        }
+                    }
+                    Stage::Done => None,
+                }
+            }
+        })
     }
 
-    /// List transfers for a specific wallet
-    pub async fn list_wallet_transfers(
+    /// List a page of transfers matching the filter, with the total number of matches
+    pub async fn list_transfers(
         &self,
-        wallet_id: i32,
+        filter: &TransferFilter,
         limit: i32,
         offset: i32,
     ) -> AppResult<(Vec<Transfer>, i64)> {
-        let transfers = self.transfer_repo.list_by_wallet(wallet_id, limit, offset).await?;
-        let total = self.transfer_repo.count_by_wallet(wallet_id).await?;
+        let transfers = self.transfer_repo.list(filter, limit, offset).await?;
+        let total = self.transfer_repo.count(filter).await?;
         Ok((transfers, total))
     }
 }
+
+/// Amount per token of a batch's `(token, amount)` items, keyed by upper-case symbol
+fn batch_totals<'a>(items: impl Iterator<Item = (&'a str, Decimal)>) -> AppResult<BTreeMap<String, Decimal>> {
+    let mut totals: BTreeMap<String, Decimal> = BTreeMap::new();
+    for (token, amount) in items {
+        let total = totals.entry(token.to_uppercase()).or_default();
+        *total = total
+            .checked_add(amount)
+            .ok_or_else(|| AppError::ValidationError("Batch total overflows".to_string()))?;
+    }
+    Ok(totals)
+}
+
+/// Apply the transfer limits to each token's batch total, as if it were one transfer
+///
+/// Returns the tokens whose total needs approval; all their items are then held, so
+/// splitting a payout into small items does not get around the threshold.
+fn check_batch_totals(
+    limits: &TransferLimitsConfig,
+    chain: &str,
+    totals: &BTreeMap<String, Decimal>,
+    confirm_large: bool,
+) -> AppResult<BTreeSet<String>> {
+    let mut held = BTreeSet::new();
+    for (token, total) in totals {
+        limits.check(chain, *total, confirm_large).map_err(|e| match e {
+            AppError::ValidationError(message) => {
+                AppError::ValidationError(format!("Batch total of {}: {}", token, message))
+            }
+            other => other,
+        })?;
+        if limits.requires_approval(chain, *total) {
+            held.insert(token.clone());
+        }
+    }
+    Ok(held)
+}
+
+/// Part of a transaction's fee recorded on one of the transfers it paid
+///
+/// The items of a combined batch share a transaction; its fee goes on the first
+/// of them and the others record none, so summing fees does not count it repeatedly.
+fn fee_share(fee: Option<u128>, transfer_id: i32, first_id: Option<i32>) -> Option<u128> {
+    match first_id {
+        Some(first_id) if first_id != transfer_id => fee.map(|_| 0),
+        _ => fee,
+    }
+}
+
+/// Prefix a validation error with the batch item it belongs to
+fn batch_item_error(index: usize, e: AppError) -> AppError {
+    match e {
+        AppError::ValidationError(message) => AppError::ValidationError(format!("Item {}: {}", index, message)),
+        AppError::Forbidden(message) => AppError::Forbidden(format!("Item {}: {}", index, message)),
+        other => other,
+    }
+}
+
+/// What one poll of a submitted transfer's transaction means for the transfer
+#[derive(Debug, PartialEq)]
+enum PollOutcome {
+    /// In a block, but not yet under the required confirmations
+    Mined { block_number: u64, confirmations: u64 },
+    Confirmed { block_number: u64, gas_used: u64, fee: Option<u128> },
+    Failed { reason: String },
+    /// Still in the mempool; `stuck` is set once it has waited past the pending timeout
+    Pending { stuck: Option<String> },
+    NotFound,
+}
+
+fn poll_outcome(
+    transfer: &Transfer,
+    status: TxStatus,
+    required_confirmations: u64,
+    pending_timeout: Option<Duration>,
+    now: DateTime<Utc>,
+) -> PollOutcome {
+    match status {
+        TxStatus::Confirmed { block_number, confirmations, .. } if confirmations < required_confirmations => {
+            PollOutcome::Mined { block_number, confirmations }
+        }
+        TxStatus::Confirmed { block_number, gas_used, fee, .. } => PollOutcome::Confirmed { block_number, gas_used, fee },
+        TxStatus::Failed { reason } => PollOutcome::Failed { reason },
+        TxStatus::Pending => PollOutcome::Pending {
+            stuck: stuck_note(transfer, pending_timeout, now),
+        },
+        TxStatus::NotFound => PollOutcome::NotFound,
+    }
+}
+
+/// Note for a transaction still waiting in the mempool past the pending timeout
+///
+/// It may yet be mined, so the transfer is only flagged, never failed; it fails once
+/// its nonce is taken by another transaction (see `dropped_reason`).
+fn stuck_note(transfer: &Transfer, timeout: Option<Duration>, now: DateTime<Utc>) -> Option<String> {
+    let timeout = timeout?;
+    submission_timed_out(transfer.submitted_at, transfer.created_at, timeout, now).then(|| {
+        format!(
+            "Transaction still unmined in the mempool {} minutes after submission; \
+             replace it with a higher fee or keep waiting",
+            timeout.as_secs() / 60
+        )
+    })
+}
+
+/// Whether a transfer has waited longer than `timeout` since its transaction was broadcast
+///
+/// `updated_at` is no measure: recording the nonce or the block moves it. Rows without
+/// a submission time fall back to when they were created.
+fn submission_timed_out(
+    submitted_at: Option<DateTime<Utc>>,
+    created_at: DateTime<Utc>,
+    timeout: Duration,
+    now: DateTime<Utc>,
+) -> bool {
+    now.signed_duration_since(submitted_at.unwrap_or(created_at))
+        .to_std()
+        .is_ok_and(|waited| waited > timeout)
+}
+
+/// Refuse to dry-run a transfer that could not be executed now
+///
+/// A signed transaction can be broadcast elsewhere, so a dry run needs the transfer
+/// approved like a real execution (the spending limit is checked by the caller).
+fn check_dry_run(transfer: &Transfer) -> AppResult<()> {
+    if transfer.status != "pending" {
+        return Err(AppError::ValidationError(format!(
+            "Transfer is not pending. Current status: {}",
+            transfer.status
+        )));
+    }
+    transfer.ensure_not_proposal()?;
+    transfer.ensure_approved()
+}
+
+/// Signing parameters for a stored transfer
+fn signing_params(transfer: &Transfer, private_key: String) -> TransferParams {
+    TransferParams {
+        from_address: transfer.from_address.clone(),
+        to_address: transfer.to_address.clone(),
+        private_key,
+        token: transfer.token.clone(),
+        amount: transfer.amount,
+        gas_price_gwei: transfer.gas_price,
+        gas_limit: transfer.gas_limit.map(|g| g as u64),
+    }
+}
+
+/// Whether `existing` was created from the same request: same wallet, recipient,
+/// token, amount and gas settings
+fn is_same_transfer(existing: &Transfer, request: &TransferRequest, wallet_id: i32, to_address: &str) -> bool {
+    let same_amount = Decimal::from_str(&request.amount).is_ok_and(|a| a == existing.amount);
+    let gas_price = request
+        .gas_price_gwei
+        .as_deref()
+        .map(Decimal::from_str)
+        .transpose();
+
+    existing.chain == request.chain
+        && existing.wallet_id == wallet_id
+        && existing.to_address == to_address
+        && existing.token.eq_ignore_ascii_case(&request.token)
+        && same_amount
+        && gas_price.is_ok_and(|price| price == existing.gas_price)
+        && existing.gas_limit == request.gas_limit
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::blockchain::mock::MockChainClient;
+    use crate::db::models::test_fixtures;
+
+    fn amount(value: &str) -> Decimal {
+        Decimal::from_str(value).unwrap()
+    }
+
+    fn transfer_request(to_address: &str, amount: &str) -> TransferRequest {
+        TransferRequest {
+            chain: "ethereum".to_string(),
+            to_address: to_address.to_string(),
+            token: "usdt".to_string(),
+            amount: amount.to_string(),
+            gas_price_gwei: None,
+            gas_limit: None,
+            confirm_large: false,
+            allow_duplicate: false,
+        }
+    }
+
+    fn stored_transfer() -> Transfer {
+        Transfer {
+            token: "USDT".to_string(),
+            amount: amount("25"),
+            ..test_fixtures::transfer(9)
+        }
+    }
+
+    #[test]
+    fn test_idempotency_key_matches_only_the_same_request() {
+        let existing = stored_transfer();
+        let request = transfer_request("vendor", "25.0");
+        assert!(is_same_transfer(&existing, &request, 1, "0xrecipient"));
+
+        // Another recipient, or the key reused after the active wallet changed
+        assert!(!is_same_transfer(&existing, &request, 1, "0xsomeone-else"));
+        assert!(!is_same_transfer(&existing, &request, 2, "0xrecipient"));
+        assert!(!is_same_transfer(&existing, &transfer_request("vendor", "26"), 1, "0xrecipient"));
+
+        let repriced = TransferRequest {
+            gas_price_gwei: Some("30".to_string()),
+            ..request
+        };
+        assert!(!is_same_transfer(&existing, &repriced, 1, "0xrecipient"));
+    }
+
+    #[tokio::test]
+    async fn test_dry_run_signs_without_broadcasting() {
+        let transfer = Transfer {
+            gas_limit: Some(65_000),
+            ..stored_transfer()
+        };
+        check_dry_run(&transfer).unwrap();
+
+        let client = MockChainClient::new("ethereum").accepting_broadcasts("0xfeed");
+        let params = signing_params(&transfer, "key".to_string());
+        assert_eq!(params.gas_limit, Some(65_000));
+
+        let signed = client.sign_transfer(&params).await.unwrap();
+        assert_eq!(signed.tx_hash, "0xfeed");
+        assert_eq!(client.signed_count(), 1);
+        assert_eq!(client.broadcast_count(), 0);
+    }
+
+    #[test]
+    fn test_dry_run_refuses_transfers_that_cannot_execute() {
+        let submitted = Transfer {
+            status: "submitted".to_string(),
+            ..stored_transfer()
+        };
+        assert!(matches!(check_dry_run(&submitted), Err(AppError::ValidationError(_))));
+
+        let awaiting_approval = Transfer {
+            approval_status: Some("required".to_string()),
+            ..stored_transfer()
+        };
+        assert!(matches!(check_dry_run(&awaiting_approval), Err(AppError::Forbidden(_))));
+
+        let proposal = Transfer {
+            proposal_id: Some("p-1".to_string()),
+            ..stored_transfer()
+        };
+        assert!(check_dry_run(&proposal).is_err());
+    }
+
+    #[test]
+    fn test_batch_totals_per_token() {
+        let items = [("eth", amount("1")), ("USDT", amount("5")), ("ETH", amount("2.5"))];
+        let totals = batch_totals(items.into_iter()).unwrap();
+        assert_eq!(totals["ETH"], amount("3.5"));
+        assert_eq!(totals["USDT"], amount("5"));
+
+        let overflow = [("ETH", Decimal::MAX), ("ETH", Decimal::MAX)];
+        assert!(batch_totals(overflow.into_iter()).is_err());
+    }
+
+    #[test]
+    fn test_batch_totals_are_limited_like_one_transfer() {
+        let mut limits = TransferLimitsConfig::default();
+        limits.approval_threshold.insert("ethereum".to_string(), amount("10"));
+        limits.max_amount.insert("ethereum".to_string(), amount("100"));
+
+        // Each item is below the approval threshold but the ETH total is not
+        let items = [("ETH", amount("6")), ("ETH", amount("6")), ("USDT", amount("6"))];
+        let totals = batch_totals(items.into_iter()).unwrap();
+        let held = check_batch_totals(&limits, "ethereum", &totals, false).unwrap();
+        assert_eq!(held.into_iter().collect::<Vec<_>>(), vec!["ETH".to_string()]);
+
+        let totals = batch_totals([("ETH", amount("60")), ("ETH", amount("60"))].into_iter()).unwrap();
+        let err = check_batch_totals(&limits, "ethereum", &totals, true).unwrap_err();
+        assert!(matches!(err, AppError::ValidationError(message) if message.starts_with("Batch total of ETH")));
+    }
+
+    #[test]
+    fn test_combined_batch_fee_recorded_once() {
+        assert_eq!(fee_share(Some(1_000), 7, Some(7)), Some(1_000));
+        assert_eq!(fee_share(Some(1_000), 8, Some(7)), Some(0));
+        assert_eq!(fee_share(None, 8, Some(7)), None);
+        assert_eq!(fee_share(Some(1_000), 7, None), Some(1_000));
+    }
+
+    #[test]
+    fn test_pending_timeout_counts_from_submission() {
+        let now = Utc::now();
+        let timeout = Duration::from_secs(30 * 60);
+        let created = now - chrono::Duration::hours(2);
+
+        // Broadcast 40 minutes ago; a nonce recorded since does not restart the clock
+        let submitted = Some(now - chrono::Duration::minutes(40));
+        assert!(submission_timed_out(submitted, created, timeout, now));
+
+        // Created long ago but only broadcast (or replaced) 10 minutes ago
+        let submitted = Some(now - chrono::Duration::minutes(10));
+        assert!(!submission_timed_out(submitted, created, timeout, now));
+
+        assert!(submission_timed_out(None, created, timeout, now));
+        assert!(!submission_timed_out(None, now, timeout, now));
+    }
+
+    #[test]
+    fn test_transfer_stuck_in_mempool_stays_submitted_until_mined() {
+        let now = Utc::now();
+        let timeout = Some(Duration::from_secs(30 * 60));
+        let stuck = Transfer {
+            status: "submitted".to_string(),
+            tx_hash: Some("0xstuck".to_string()),
+            submitted_at: Some(now - chrono::Duration::minutes(40)),
+            ..stored_transfer()
+        };
+
+        // Past the timeout the transfer is only flagged, not failed
+        let PollOutcome::Pending { stuck: Some(note) } = poll_outcome(&stuck, TxStatus::Pending, 1, timeout, now) else {
+            panic!("a transaction still in the mempool should stay pending");
+        };
+        assert!(note.contains("still unmined in the mempool 30 minutes"));
+
+        // Mined later, it is confirmed like any other transfer
+        let mined = TxStatus::Confirmed { block_number: 100, gas_used: 21_000, fee: Some(42), confirmations: 3 };
+        assert_eq!(
+            poll_outcome(&stuck, mined, 1, timeout, now + chrono::Duration::hours(1)),
+            PollOutcome::Confirmed { block_number: 100, gas_used: 21_000, fee: Some(42) }
+        );
+
+        let recent = Transfer {
+            submitted_at: Some(now - chrono::Duration::minutes(10)),
+            ..stuck.clone()
+        };
+        assert_eq!(poll_outcome(&recent, TxStatus::Pending, 1, timeout, now), PollOutcome::Pending { stuck: None });
+        // Chains without a pending timeout wait forever
+        assert_eq!(stuck_note(&stuck, None, now), None);
+    }
+}
@@ -0,0 +1,164 @@
+//! Background rescans of single Zcash wallets
+//!
+//! A rescan has to wait for the sync cycle in progress, so it runs off the request
+//! and its status is kept here until the wallet is rescanned again.
+
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use crate::error::{AppError, AppResult};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RescanStatus {
+    /// Waiting for the sync cycle in progress to finish
+    Queued,
+    /// Notes cleared; the background sync is scanning the wallet again
+    Scanning,
+    /// Scanned back up to where the wallet was before the rescan
+    Completed,
+    Failed,
+}
+
+/// One wallet's rescan
+#[derive(Debug, Clone, Serialize)]
+pub struct WalletRescan {
+    pub wallet_id: i32,
+    /// First block the next sync scans for this wallet
+    pub from_height: u64,
+    /// Height the wallet was scanned to before the rescan; notes up to it are not reported again
+    pub previous_height: u64,
+    /// Height the wallet has been scanned to since
+    pub scanned_height: Option<u64>,
+    pub status: RescanStatus,
+    pub notes_removed: Option<u64>,
+    pub error: Option<String>,
+}
+
+/// Latest rescan of each wallet since the server started
+#[derive(Default)]
+pub struct RescanJobs {
+    jobs: Mutex<HashMap<i32, WalletRescan>>,
+}
+
+impl RescanJobs {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Queue a rescan, or fail while one of the same wallet is still queued
+    pub fn queue(&self, wallet_id: i32, from_height: u64, previous_height: u64) -> AppResult<WalletRescan> {
+        let mut jobs = self.jobs.lock().unwrap_or_else(|e| e.into_inner());
+        if jobs.get(&wallet_id).is_some_and(|job| job.status == RescanStatus::Queued) {
+            return Err(AppError::ValidationError(format!(
+                "A rescan of wallet {} is already queued",
+                wallet_id
+            )));
+        }
+
+        // Restarting an unfinished rescan must not report the notes it has found again
+        let previous_height = match jobs.get(&wallet_id) {
+            Some(job) if job.status == RescanStatus::Scanning => job.previous_height.max(previous_height),
+            _ => previous_height,
+        };
+
+        let rescan = WalletRescan {
+            wallet_id,
+            from_height,
+            previous_height,
+            scanned_height: None,
+            status: RescanStatus::Queued,
+            notes_removed: None,
+            error: None,
+        };
+        jobs.insert(wallet_id, rescan.clone());
+        Ok(rescan)
+    }
+
+    /// The wallet's notes were cleared and its scan height reset
+    pub fn started(&self, wallet_id: i32, notes_removed: u64) {
+        self.update(wallet_id, |job| {
+            job.status = RescanStatus::Scanning;
+            job.notes_removed = Some(notes_removed);
+        });
+    }
+
+    pub fn failed(&self, wallet_id: i32, error: String) {
+        self.update(wallet_id, |job| {
+            job.status = RescanStatus::Failed;
+            job.error = Some(error);
+        });
+    }
+
+    /// Latest rescan of a wallet, given the height the wallet has been scanned to
+    pub fn status(&self, wallet_id: i32, scanned_height: Option<u64>) -> Option<WalletRescan> {
+        let mut jobs = self.jobs.lock().unwrap_or_else(|e| e.into_inner());
+        let job = jobs.get_mut(&wallet_id)?;
+        if job.status == RescanStatus::Scanning {
+            job.scanned_height = scanned_height;
+            if scanned_height.is_some_and(|height| height >= job.previous_height) {
+                job.status = RescanStatus::Completed;
+            }
+        }
+        Some(job.clone())
+    }
+
+    /// Whether a note found at `block_height` had been reported before its wallet was rescanned
+    pub fn is_rediscovered(&self, wallet_id: i32, block_height: u64) -> bool {
+        let jobs = self.jobs.lock().unwrap_or_else(|e| e.into_inner());
+        jobs.get(&wallet_id)
+            .is_some_and(|job| job.status != RescanStatus::Failed && block_height <= job.previous_height)
+    }
+
+    fn update(&self, wallet_id: i32, apply: impl FnOnce(&mut WalletRescan)) {
+        let mut jobs = self.jobs.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(job) = jobs.get_mut(&wallet_id) {
+            apply(job);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rescan_completes_once_scanned_back_to_previous_height() {
+        let jobs = RescanJobs::new();
+        assert!(jobs.status(1, None).is_none());
+
+        jobs.queue(1, 2_000_000, 2_100_000).unwrap();
+        // A second request while the first still waits for the sync lock
+        assert!(matches!(jobs.queue(1, 2_000_000, 2_100_000), Err(AppError::ValidationError(_))));
+        assert!(jobs.queue(2, 2_000_000, 2_100_000).is_ok());
+
+        jobs.started(1, 12);
+        let scanning = jobs.status(1, Some(2_050_000)).unwrap();
+        assert_eq!(scanning.status, RescanStatus::Scanning);
+        assert_eq!(scanning.notes_removed, Some(12));
+
+        // Restarted halfway; the wallet's sync state is below where it used to be
+        let restarted = jobs.queue(1, 2_000_000, 2_050_000).unwrap();
+        assert_eq!(restarted.previous_height, 2_100_000);
+        jobs.started(1, 5);
+
+        assert_eq!(jobs.status(1, Some(2_100_000)).unwrap().status, RescanStatus::Completed);
+        // Finished jobs can be replaced by a new rescan
+        assert!(jobs.queue(1, 2_000_000, 2_100_000).is_ok());
+    }
+
+    #[test]
+    fn test_only_the_rescanned_wallets_old_notes_are_rediscovered() {
+        let jobs = RescanJobs::new();
+        jobs.queue(1, 2_000_000, 2_100_000).unwrap();
+        jobs.started(1, 3);
+        assert!(jobs.is_rediscovered(1, 2_050_000));
+        assert!(jobs.is_rediscovered(1, 2_100_000));
+        assert!(!jobs.is_rediscovered(1, 2_100_001));
+        assert!(!jobs.is_rediscovered(2, 2_050_000));
+
+        jobs.failed(1, "database unavailable".to_string());
+        assert!(!jobs.is_rediscovered(1, 2_050_000));
+    }
+}
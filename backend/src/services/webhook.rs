@@ -0,0 +1,297 @@
+//! Signed HTTP callbacks on transfer status changes, incoming shielded notes and
+//! transparent deposits
+//!
+//! Each event is POSTed as JSON with an `X-Webhook-Signature: sha256=<hex>` header,
+//! the HMAC-SHA256 of the raw body under the configured secret. Deliveries run in
+//! the background and are retried with exponential backoff on errors and non-2xx
+//! responses; events that still fail are stored in `webhook_dead_letters`.
+
+use chrono::Utc;
+use hmac::{Hmac, Mac};
+use rust_decimal::Decimal;
+use serde::Serialize;
+use sha2::Sha256;
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::blockchain::zcash::orchard::memo::{decode_memo, Memo};
+use crate::blockchain::zcash::orchard::scanner::OrchardNote;
+use crate::config::WebhookConfig;
+use crate::db::models::{Transfer, TransparentDeposit};
+use crate::db::repositories::WebhookRepository;
+
+pub const TRANSFER_STATUS_EVENT: &str = "transfer.status_changed";
+pub const NOTE_RECEIVED_EVENT: &str = "note.received";
+pub const DEPOSIT_RECEIVED_EVENT: &str = "deposit.received";
+
+/// Delay before the second attempt; doubled after each failure
+const RETRY_BASE_DELAY: Duration = Duration::from_secs(2);
+const RETRY_MAX_DELAY: Duration = Duration::from_secs(300);
+
+/// Payload sent when a transfer moves between statuses
+#[derive(Debug, Clone, Serialize)]
+pub struct TransferStatusEvent {
+    pub event: &'static str,
+    pub transfer_id: i32,
+    pub wallet_id: i32,
+    pub chain: String,
+    pub token: String,
+    pub amount: Decimal,
+    pub previous_status: String,
+    pub status: String,
+    pub tx_hash: Option<String>,
+    pub block_number: Option<i64>,
+    pub error: Option<String>,
+    /// Unix seconds when the event was emitted, so receivers can reject replays
+    pub timestamp: i64,
+}
+
+impl TransferStatusEvent {
+    pub fn new(transfer: &Transfer, previous_status: &str) -> Self {
+        Self {
+            event: TRANSFER_STATUS_EVENT,
+            transfer_id: transfer.id,
+            wallet_id: transfer.wallet_id,
+            chain: transfer.chain.clone(),
+            token: transfer.token.clone(),
+            amount: transfer.amount,
+            previous_status: previous_status.to_string(),
+            status: transfer.status.clone(),
+            tx_hash: transfer.tx_hash.clone(),
+            block_number: transfer.block_number,
+            error: transfer.error_message.clone(),
+            timestamp: Utc::now().timestamp(),
+        }
+    }
+}
+
+/// Payload sent when the scanner stores a note it has not seen before
+#[derive(Debug, Clone, Serialize)]
+pub struct NoteReceivedEvent {
+    pub event: &'static str,
+    pub wallet_id: i32,
+    pub value_zatoshis: u64,
+    pub tx_hash: String,
+    pub block_height: u64,
+    /// Text memo; `None` for empty or binary memos
+    pub memo: Option<String>,
+    pub timestamp: i64,
+}
+
+impl NoteReceivedEvent {
+    pub fn new(wallet_id: i32, note: &OrchardNote) -> Self {
+        Self {
+            event: NOTE_RECEIVED_EVENT,
+            wallet_id,
+            value_zatoshis: note.value_zatoshis,
+            tx_hash: note.tx_hash.clone(),
+            block_height: note.block_height,
+            memo: memo_text(note.memo.as_deref()),
+            timestamp: Utc::now().timestamp(),
+        }
+    }
+}
+
+/// Payload sent when the deposit watcher finds a new transparent output
+#[derive(Debug, Clone, Serialize)]
+pub struct DepositReceivedEvent {
+    pub event: &'static str,
+    pub wallet_id: i32,
+    pub address: String,
+    pub tx_hash: String,
+    pub output_index: u32,
+    pub value_zatoshis: u64,
+    pub block_height: u64,
+    pub timestamp: i64,
+}
+
+impl DepositReceivedEvent {
+    pub fn new(deposit: &TransparentDeposit) -> Self {
+        Self {
+            event: DEPOSIT_RECEIVED_EVENT,
+            wallet_id: deposit.wallet_id,
+            address: deposit.address.clone(),
+            tx_hash: deposit.txid.clone(),
+            output_index: deposit.output_index,
+            value_zatoshis: deposit.value_zatoshis,
+            block_height: deposit.block_height,
+            timestamp: Utc::now().timestamp(),
+        }
+    }
+}
+
+/// Text of a hex-encoded memo, if it holds text
+fn memo_text(memo_hex: Option<&str>) -> Option<String> {
+    let bytes = hex::decode(memo_hex?).ok()?;
+    match decode_memo(&bytes).ok()? {
+        Memo::Text(text) => Some(text),
+        Memo::Empty | Memo::Binary(_) => None,
+    }
+}
+
+/// Hex HMAC-SHA256 of `body` under `secret`
+pub fn sign_payload(secret: &str, body: &[u8]) -> String {
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes())
+        .expect("HMAC accepts keys of any length");
+    mac.update(body);
+    hex::encode(mac.finalize().into_bytes())
+}
+
+/// Wait before retry number `attempt` (1 = first retry)
+fn retry_delay(attempt: u32) -> Duration {
+    RETRY_BASE_DELAY
+        .saturating_mul(1u32 << (attempt - 1).min(16))
+        .min(RETRY_MAX_DELAY)
+}
+
+pub struct WebhookNotifier {
+    config: WebhookConfig,
+    client: reqwest::Client,
+    dead_letters: WebhookRepository,
+}
+
+impl WebhookNotifier {
+    pub fn new(config: WebhookConfig, dead_letters: WebhookRepository) -> Self {
+        let client = reqwest::Client::builder()
+            .timeout(Duration::from_secs(config.timeout_secs))
+            .build()
+            .unwrap_or_default();
+
+        Self {
+            config,
+            client,
+            dead_letters,
+        }
+    }
+
+    /// Queue a callback for a transfer that changed status; no-op without a URL
+    pub fn transfer_status_changed(self: &Arc<Self>, transfer: &Transfer, previous_status: &str) {
+        if self.config.url.is_none() || transfer.status == previous_status {
+            return;
+        }
+
+        let event = TransferStatusEvent::new(transfer, previous_status);
+        let subject = format!("transfer {} ({})", event.transfer_id, event.status);
+        self.spawn_delivery(event.event, subject, &event);
+    }
+
+    /// Queue a callback for a newly discovered incoming note; no-op without a URL
+    pub fn note_received(self: &Arc<Self>, wallet_id: i32, note: &OrchardNote) {
+        if self.config.url.is_none() {
+            return;
+        }
+
+        let event = NoteReceivedEvent::new(wallet_id, note);
+        let subject = format!("note in tx {} for wallet {}", event.tx_hash, wallet_id);
+        self.spawn_delivery(event.event, subject, &event);
+    }
+
+    /// Queue a callback for a newly found transparent deposit; no-op without a URL
+    pub fn deposit_received(self: &Arc<Self>, deposit: &TransparentDeposit) {
+        if self.config.url.is_none() {
+            return;
+        }
+
+        let event = DepositReceivedEvent::new(deposit);
+        let subject = format!("deposit {}:{} for wallet {}", event.tx_hash, event.output_index, deposit.wallet_id);
+        self.spawn_delivery(event.event, subject, &event);
+    }
+
+    fn spawn_delivery(self: &Arc<Self>, event: &'static str, subject: String, payload: &impl Serialize) {
+        let payload = match serde_json::to_value(payload) {
+            Ok(payload) => payload,
+            Err(e) => {
+                tracing::error!("Failed to serialize webhook event: {}", e);
+                return;
+            }
+        };
+        let notifier = Arc::clone(self);
+        tokio::spawn(async move {
+            notifier.deliver(event, &subject, payload).await;
+        });
+    }
+
+    async fn deliver(&self, event: &str, subject: &str, payload: serde_json::Value) {
+        let Some(url) = self.config.url.as_deref() else {
+            return;
+        };
+        let body = payload.to_string().into_bytes();
+        let signature = format!("sha256={}", sign_payload(&self.config.secret, &body));
+
+        let mut last_error = String::new();
+        for attempt in 1..=self.config.max_attempts {
+            if attempt > 1 {
+                tokio::time::sleep(retry_delay(attempt - 1)).await;
+            }
+
+            let result = self
+                .client
+                .post(url)
+                .header("Content-Type", "application/json")
+                .header("X-Webhook-Event", event)
+                .header("X-Webhook-Signature", &signature)
+                .body(body.clone())
+                .send()
+                .await;
+
+            match result {
+                Ok(response) if response.status().is_success() => {
+                    tracing::debug!("Webhook delivered for {} on attempt {}", subject, attempt);
+                    return;
+                }
+                Ok(response) => last_error = format!("HTTP {}", response.status()),
+                Err(e) => last_error = e.to_string(),
+            }
+            tracing::warn!(
+                "Webhook attempt {}/{} for {} failed: {}",
+                attempt,
+                self.config.max_attempts,
+                subject,
+                last_error
+            );
+        }
+
+        tracing::error!(
+            "Webhook for {} dead-lettered after {} attempts: {}",
+            subject,
+            self.config.max_attempts,
+            last_error
+        );
+        if let Err(e) = self
+            .dead_letters
+            .record_dead_letter(event, url, &payload, self.config.max_attempts, &last_error)
+            .await
+        {
+            tracing::error!("Failed to store dead-lettered webhook: {}", e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sign_payload() {
+        // RFC 4231 test case 2
+        assert_eq!(
+            sign_payload("Jefe", b"what do ya want for nothing?"),
+            "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+        );
+    }
+
+    #[test]
+    fn test_memo_text() {
+        assert_eq!(memo_text(Some(&hex::encode("invoice 42"))).as_deref(), Some("invoice 42"));
+        assert_eq!(memo_text(Some("f6")), None);
+        assert_eq!(memo_text(Some("ff01")), None);
+        assert_eq!(memo_text(None), None);
+    }
+
+    #[test]
+    fn test_retry_delay_backs_off() {
+        assert_eq!(retry_delay(1), Duration::from_secs(2));
+        assert_eq!(retry_delay(3), Duration::from_secs(8));
+        assert_eq!(retry_delay(30), RETRY_MAX_DELAY);
+    }
+}
@@ -0,0 +1,115 @@
+use serde::Serialize;
+
+use crate::blockchain::traits::AddressDelta;
+use crate::db::repositories::orchard_repo::ShieldedActivityRow;
+
+/// Pools a Zcash history entry can be filtered by
+pub const ZCASH_POOLS: [&str; 3] = ["transparent", "orchard", "sapling"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Direction {
+    In,
+    Out,
+}
+
+/// One transaction's net effect on one pool of a wallet
+#[derive(Debug, Clone, Serialize)]
+pub struct ZcashActivity {
+    pub tx_hash: String,
+    pub pool: String,
+    pub direction: Direction,
+    pub amount_zatoshis: u64,
+    /// `None` while a shielded spend is unconfirmed
+    pub block_height: Option<u64>,
+}
+
+/// Merge transparent deltas and shielded activity into one list, newest first
+///
+/// Shielded activity comes netted per transaction like the transparent side (see
+/// `OrchardRepository::get_shielded_activity`), and is sorted the same way.
+pub fn merge_activity(transparent: &[AddressDelta], shielded: &[ShieldedActivityRow]) -> Vec<ZcashActivity> {
+    let mut activity: Vec<ZcashActivity> = transparent
+        .iter()
+        .filter_map(|delta| entry(&delta.txid, "transparent", delta.amount, Some(delta.height)))
+        .chain(
+            shielded
+                .iter()
+                .filter_map(|row| entry(&row.tx_hash, &row.pool, row.net_zatoshis, row.block_height)),
+        )
+        .collect();
+
+    // Unconfirmed first, then by height; the hash keeps the order stable between pages
+    activity.sort_by(|a, b| {
+        let height = |h: Option<u64>| h.unwrap_or(u64::MAX);
+        height(b.block_height)
+            .cmp(&height(a.block_height))
+            .then_with(|| a.tx_hash.cmp(&b.tx_hash))
+            .then_with(|| a.pool.cmp(&b.pool))
+    });
+    activity
+}
+
+fn entry(tx_hash: &str, pool: &str, net: i64, block_height: Option<u64>) -> Option<ZcashActivity> {
+    if net == 0 {
+        return None;
+    }
+    Some(ZcashActivity {
+        tx_hash: tx_hash.to_string(),
+        pool: pool.to_string(),
+        direction: if net > 0 { Direction::In } else { Direction::Out },
+        amount_zatoshis: net.unsigned_abs(),
+        block_height,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn activity_row(tx: &str, pool: &str, net_zatoshis: i64, block_height: Option<u64>) -> ShieldedActivityRow {
+        ShieldedActivityRow {
+            tx_hash: tx.to_string(),
+            pool: pool.to_string(),
+            net_zatoshis,
+            block_height,
+        }
+    }
+
+    #[test]
+    fn test_merges_pools_newest_first() {
+        let transparent = vec![
+            AddressDelta { txid: "t1".to_string(), height: 100, amount: 500_000 },
+            // Shielding: the transparent side is spent...
+            AddressDelta { txid: "shield".to_string(), height: 110, amount: -500_000 },
+        ];
+        let shielded = vec![
+            // ...and an Orchard note received in the same transaction
+            activity_row("shield", "orchard", 490_000, Some(110)),
+            // Sent 200_000 of that note, with 290_000 change coming back
+            activity_row("send", "orchard", -200_000, Some(120)),
+            // The change spent, not yet mined
+            activity_row("pending", "orchard", -290_000, None),
+            activity_row("old", "sapling", 70_000, Some(90)),
+        ];
+
+        let activity = merge_activity(&transparent, &shielded);
+        let summary: Vec<(&str, &str, Direction, u64)> = activity
+            .iter()
+            .map(|a| (a.tx_hash.as_str(), a.pool.as_str(), a.direction, a.amount_zatoshis))
+            .collect();
+
+        assert_eq!(
+            summary,
+            vec![
+                ("pending", "orchard", Direction::Out, 290_000),
+                ("send", "orchard", Direction::Out, 200_000),
+                ("shield", "orchard", Direction::In, 490_000),
+                ("shield", "transparent", Direction::Out, 500_000),
+                ("t1", "transparent", Direction::In, 500_000),
+                ("old", "sapling", Direction::In, 70_000),
+            ]
+        );
+        assert_eq!(activity[0].block_height, None);
+    }
+}
@@ -2,7 +2,7 @@ import React from 'react';
 import { useTranslation } from 'react-i18next';
 
 interface StatusBadgeProps {
-  status: 'pending' | 'submitted' | 'confirmed' | 'failed';
+  status: 'pending' | 'submitted' | 'confirmed' | 'failed' | 'rejected';
 }
 
 export function StatusBadge({ status }: StatusBadgeProps) {
@@ -25,6 +25,10 @@ export function StatusBadge({ status }: StatusBadgeProps) {
       bg: 'bg-red-100',
       text: 'text-red-800',
     },
+    rejected: {
+      bg: 'bg-gray-100',
+      text: 'text-gray-800',
+    },
   };
 
   const config = statusConfig[status] || statusConfig.pending;
@@ -1,5 +1,5 @@
 import React from 'react';
-import { Navigate } from 'react-router-dom';
+import { Navigate, useLocation } from 'react-router-dom';
 import { useAuth } from '../../hooks/useAuth';
 import { LoadingSpinner } from '../Common';
 import { Layout } from './Layout';
@@ -11,6 +11,7 @@ interface ProtectedRouteProps {
 
 export function ProtectedRoute({ children, requiredRole }: ProtectedRouteProps) {
   const { user, isLoading } = useAuth();
+  const location = useLocation();
 
   if (isLoading) {
     return (
@@ -24,6 +25,11 @@ export function ProtectedRoute({ children, requiredRole }: ProtectedRouteProps)
     return <Navigate to="/login" replace />;
   }
 
+  // Everything but the settings page is locked until the default password is changed
+  if (user.must_change_password && location.pathname !== '/settings') {
+    return <Navigate to="/settings" replace />;
+  }
+
   if (requiredRole && user.role !== requiredRole && user.role !== 'admin') {
     return <Navigate to="/" replace />;
   }
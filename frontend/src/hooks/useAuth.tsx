@@ -9,6 +9,7 @@ interface AuthContextType {
   error: string | null;
   login: (username: string, password: string) => Promise<void>;
   logout: () => Promise<void>;
+  passwordChanged: () => void;
   clearError: () => void;
 }
 
@@ -67,11 +68,20 @@ export function AuthProvider({ children }: { children: ReactNode }) {
     }
   };
 
+  // The server lifts a required password change once the password is rotated
+  const passwordChanged = () => {
+    if (user?.must_change_password) {
+      const updated = { ...user, must_change_password: false };
+      setUser(updated);
+      localStorage.setItem('user', JSON.stringify(updated));
+    }
+  };
+
   const clearError = () => setError(null);
 
   return (
     <AuthContext.Provider
-      value={{ user, token, isLoading, error, login, logout, clearError }}
+      value={{ user, token, isLoading, error, login, logout, passwordChanged, clearError }}
     >
       {children}
     </AuthContext.Provider>
@@ -12,7 +12,7 @@ const languages = [
 
 export function Settings() {
   const { t, i18n } = useTranslation();
-  const { user } = useAuth();
+  const { user, passwordChanged } = useAuth();
   const [oldPassword, setOldPassword] = useState('');
   const [newPassword, setNewPassword] = useState('');
   const [confirmPassword, setConfirmPassword] = useState('');
@@ -30,14 +30,17 @@ export function Settings() {
       return;
     }
 
-    if (newPassword.length < 6) {
+    if (newPassword.length < 8) {
       setError(t('settings.passwordTooShort'));
       return;
     }
 
     setIsLoading(true);
     try {
-      await authService.changePassword(oldPassword, newPassword);
+      // Older sessions are invalidated server-side; keep this one alive with the new token
+      const { token } = await authService.changePassword(oldPassword, newPassword);
+      localStorage.setItem('token', token);
+      passwordChanged();
       setSuccess(t('settings.passwordChanged'));
       setOldPassword('');
       setNewPassword('');
@@ -99,6 +102,12 @@ export function Settings() {
 
         {/* Change Password */}
         <Card title={t('settings.changePassword')}>
+          {user?.must_change_password && (
+            <div className="mb-4 p-3 bg-yellow-50 border border-yellow-200 rounded-lg text-yellow-800 flex items-center">
+              <AlertCircle className="w-5 h-5 mr-2" />
+              {t('settings.passwordChangeRequired')}
+            </div>
+          )}
           {error && (
             <div className="mb-4 p-3 bg-red-50 border border-red-200 rounded-lg text-red-700 flex items-center">
               <AlertCircle className="w-5 h-5 mr-2" />
@@ -744,7 +744,7 @@ export function ChainWallets({ chainId }: ChainWalletsProps) {
                   >
                     <div className="flex items-center justify-between mb-2">
                       <span className="font-semibold text-green-700">
-                        {note.value_zec.toFixed(8)} ZEC
+                        {note.value_zec} ZEC
                       </span>
                       <span className="text-xs text-gray-500">
                         {t('zcash.blockHeight', 'Block')}: {note.block_height.toLocaleString()}
@@ -781,7 +781,7 @@ export function ChainWallets({ chainId }: ChainWalletsProps) {
                 <div className="flex items-center justify-between text-sm font-semibold">
                   <span>{t('zcash.totalShielded', 'Total Shielded')}:</span>
                   <span className="text-green-700">
-                    {selectedNotes.reduce((sum, n) => sum + n.value_zec, 0).toFixed(8)} ZEC
+                    {(selectedNotes.reduce((sum, n) => sum + n.value_zatoshis, 0) / 1e8).toFixed(8)} ZEC
                   </span>
                 </div>
               </div>
@@ -95,7 +95,7 @@ export function PrivacyTransfer() {
               address: wallet.address,
               transparent_balance: balanceResponse.native_balance,
               shielded_balance: null,
-              total_zec: parseFloat(balanceResponse.native_balance) || 0,
+              total_zec: balanceResponse.native_balance,
             });
           }
         }
@@ -258,21 +258,10 @@ export function PrivacyTransfer() {
     setError(null);
 
     try {
-      // Execute transfer with full proposal data
+      // Execute the stored proposal by id
       const executeResponse = await orchardApi.executeOrchardTransfer(
         pendingProposal.proposal_id,
-        {
-          wallet_id: selectedWalletId,
-          proposal_id: pendingProposal.proposal_id,
-          amount_zatoshis: pendingProposal.amount_zatoshis,
-          fee_zatoshis: pendingProposal.fee_zatoshis,
-          to_address: pendingProposal.to_address,
-          memo: pendingProposal.memo,
-          fund_source: pendingProposal.fund_source,
-          is_shielding: pendingProposal.is_shielding,
-          is_deshielding: pendingProposal.is_deshielding,
-          expiry_height: pendingProposal.expiry_height,
-        }
+        { wallet_id: selectedWalletId }
       );
 
       setTxHash(executeResponse.tx_id);
@@ -65,7 +65,7 @@ export function BalanceBreakdown({
               {t('zcash.orchard.totalBalance', 'Total Balance')}
             </p>
             <p className="text-white text-3xl font-bold">
-              {balance.total_zec.toFixed(8)} ZEC
+              {balance.total_zec} ZEC
             </p>
           </div>
           <TrendingUp className="w-8 h-8 text-yellow-200" />
@@ -10,7 +10,7 @@ export const authService = {
     return api.post('/auth/logout');
   },
 
-  async changePassword(oldPassword: string, newPassword: string): Promise<void> {
+  async changePassword(oldPassword: string, newPassword: string): Promise<{ token: string }> {
     return api.put('/auth/password', {
       old_password: oldPassword,
       new_password: newPassword,
@@ -96,11 +96,11 @@ export async function initiateOrchardTransfer(
 
 /**
  * Execute a pending Orchard transfer
- * Requires the full proposal data from initiateOrchardTransfer
+ * The proposal is looked up server-side by the id from initiateOrchardTransfer
  */
 export async function executeOrchardTransfer(
   proposalId: string,
-  request: ExecuteTransferRequest
+  request: ExecuteTransferRequest = {}
 ): Promise<OrchardTransferResponse> {
   return axios.post(`/transfers/orchard/${proposalId}/execute`, request);
 }
@@ -8,9 +8,20 @@ export interface RpcPreset {
   website: string;
 }
 
+export interface RpcEndpointStatus {
+  url: string;
+  active: boolean;
+  demoted: boolean;
+  requests: number;
+  error_rate: number;
+  latency_ms?: number;
+}
+
 export interface RpcConfig {
   primary_rpc: string;
   fallback_rpcs: string[];
+  active_rpc?: string | null;
+  endpoints?: RpcEndpointStatus[];
 }
 
 export interface TestRpcResponse {
@@ -2,6 +2,7 @@ export interface User {
   id: number;
   username: string;
   role: 'admin' | 'operator';
+  must_change_password?: boolean;
 }
 
 export interface LoginResponse {
@@ -16,11 +17,14 @@ export interface Wallet {
   chain: string;
   is_active: boolean;
   created_at: string;
+  watch_only?: boolean;
 }
 
 export interface TokenBalance {
   symbol: string;
   balance: string;
+  balance_display: string;
+  decimals: number;
   contract_address: string | null;
 }
 
@@ -28,6 +32,9 @@ export interface BalanceResponse {
   address: string;
   chain: string;
   native_balance: string;
+  native_balance_display: string;
+  native_symbol: string;
+  native_decimals: number;
   tokens: TokenBalance[];
 }
 
@@ -42,7 +49,10 @@ export interface Transfer {
   gas_price: string | null;
   gas_limit: number | null;
   gas_used: number | null;
-  status: 'pending' | 'submitted' | 'confirmed' | 'failed';
+  fee_native: string | null;
+  fee_display: string | null;
+  fee_fiat?: string | null;
+  status: 'pending' | 'submitted' | 'confirmed' | 'failed' | 'rejected';
   tx_hash: string | null;
   block_number: number | null;
   error_message: string | null;
@@ -73,6 +83,8 @@ export interface ImportWalletRequest {
   name: string;
   private_key: string;
   chain?: string;
+  /** Zcash only: first block to scan for shielded funds */
+  birthday_height?: number;
 }
 
 export interface TransferRequest {
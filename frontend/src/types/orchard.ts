@@ -47,8 +47,10 @@ export interface CombinedZcashBalance {
   transparent_balance: string;
   /** Shielded balance (null if Orchard not enabled) */
   shielded_balance: ShieldedBalance | null;
-  /** Total balance in ZEC */
-  total_zec: number;
+  /** Sapling funds found by the scan; read-only */
+  sapling_balance?: ShieldedBalance;
+  /** Total balance in ZEC (exact decimal string) */
+  total_zec: string;
 }
 
 /** Scan progress information */
@@ -65,6 +67,8 @@ export interface ScanProgress {
   progress_percent: number;
   /** Estimated time remaining in seconds */
   estimated_seconds_remaining: number | null;
+  /** Scan speed of the current run */
+  blocks_per_second: number | null;
   /** Whether scanning is currently active */
   is_scanning: boolean;
   /** Number of notes found */
@@ -90,7 +94,7 @@ export interface StoredOrchardNote {
   id: number;
   nullifier: string;
   value_zatoshis: number;
-  value_zec: number;
+  value_zec: string;
   block_height: number;
   tx_hash: string;
   is_spent: boolean;
@@ -143,29 +147,23 @@ export interface OrchardTransferRequest {
 export interface OrchardTransferProposal {
   proposal_id: string;
   amount_zatoshis: number;
-  amount_zec: number;
+  amount_zec: string;
   fee_zatoshis: number;
-  fee_zec: number;
+  fee_zec: string;
   fund_source: string;
   is_shielding: boolean;
   is_deshielding: boolean;
   to_address: string;
   memo?: string;
   expiry_height: number;
+  /** Transfer a second user must approve before this proposal can be executed */
+  approval_transfer_id?: number;
 }
 
-/** Request to execute a transfer */
+/** Request to execute a transfer; the proposal itself is stored server-side */
 export interface ExecuteTransferRequest {
-  wallet_id: number;
-  proposal_id: string;
-  amount_zatoshis: number;
-  fee_zatoshis: number;
-  to_address: string;
-  memo?: string;
-  fund_source: string;
-  is_shielding: boolean;
-  is_deshielding: boolean;
-  expiry_height: number;
+  wallet_id?: number;
+  dry_run?: boolean;
 }
 
 /** Response from executing Orchard transfer */